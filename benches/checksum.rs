@@ -0,0 +1,64 @@
+#[macro_use]
+extern crate bencher;
+extern crate etherparse;
+
+use bencher::{Bencher, black_box};
+use etherparse::checksum::Sum16BitWords;
+
+///Previous implementation summing the data two bytes at a time (used as reference).
+fn reference_checksum(data: &[u8]) -> u16 {
+    let mut sum: u64 = 0;
+    for i in 0..(data.len()/2) {
+        sum += u64::from( u16::from_be_bytes([data[i*2], data[i*2 + 1]]) );
+    }
+    //pad the last byte with 0
+    if data.len() % 2 == 1 {
+        sum += u64::from( u16::from_be_bytes([*data.last().unwrap(), 0]) );
+    }
+    let carry_add = (sum & 0xffff) +
+                    ((sum >> 16) & 0xffff) +
+                    ((sum >> 32) & 0xffff) +
+                    ((sum >> 48) & 0xffff);
+    let result = ((carry_add & 0xffff) + (carry_add >> 16)) as u16;
+    !result
+}
+
+fn data(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i*7 + 3) as u8).collect()
+}
+
+fn bench_reference(bench: &mut Bencher, len: usize) {
+    let data = data(len);
+    bench.bytes = len as u64;
+    bench.iter(|| reference_checksum(black_box(&data)));
+}
+
+fn bench_sum16bitwords(bench: &mut Bencher, len: usize) {
+    let data = data(len);
+    assert_eq!(reference_checksum(&data), Sum16BitWords::new().add_slice(&data).ones_complement());
+    bench.bytes = len as u64;
+    bench.iter(|| Sum16BitWords::new().add_slice(black_box(&data)).ones_complement());
+}
+
+fn reference_64(bench: &mut Bencher) { bench_reference(bench, 64) }
+fn reference_512(bench: &mut Bencher) { bench_reference(bench, 512) }
+fn reference_1500(bench: &mut Bencher) { bench_reference(bench, 1500) }
+fn reference_9000(bench: &mut Bencher) { bench_reference(bench, 9000) }
+
+fn sum16bitwords_64(bench: &mut Bencher) { bench_sum16bitwords(bench, 64) }
+fn sum16bitwords_512(bench: &mut Bencher) { bench_sum16bitwords(bench, 512) }
+fn sum16bitwords_1500(bench: &mut Bencher) { bench_sum16bitwords(bench, 1500) }
+fn sum16bitwords_9000(bench: &mut Bencher) { bench_sum16bitwords(bench, 9000) }
+
+benchmark_group!(
+    benches,
+    reference_64,
+    reference_512,
+    reference_1500,
+    reference_9000,
+    sum16bitwords_64,
+    sum16bitwords_512,
+    sum16bitwords_1500,
+    sum16bitwords_9000
+);
+benchmark_main!(benches);
@@ -35,6 +35,8 @@ fn main() {
 
             match value.link {
                 Some(Ethernet2(value)) => println!("  Ethernet2 {:?} => {:?}", value.source(), value.destination()),
+                Some(Ppp(value)) => println!("  Ppp {:?}", value.protocol()),
+                Some(value) => println!("  Link {:?}", value.slice()),
                 None => {}
             }
 
@@ -0,0 +1,753 @@
+//! Compilation of packet filters to classic BPF programs & an interpreter for classic BPF programs.
+//!
+//! [`compile`](fn.compile.html) translates a [`packet_filter::Filter`](../packet_filter/struct.Filter.html) into a
+//! classic BPF (cBPF) program for ethernet II frames (with zero, one or two VLAN tags and IPv4 or IPv6 packets). The
+//! [`BpfInstruction`](struct.BpfInstruction.html) struct has the same memory layout as `struct sock_filter`, which
+//! allows attaching the program to a socket via `SO_ATTACH_FILTER` (pass a pointer to the first instruction & the
+//! number of instructions as `struct sock_fprog`).
+//!
+//! [`run`](fn.run.html) executes a program the same way the kernel does (loads outside of the packet & divisions by
+//! zero reject the packet), which allows testing programs without a socket.
+//!
+//! # Example
+//!
+//! ```
+//! use etherparse::*;
+//! use etherparse::bpf;
+//! use etherparse::packet_filter::*;
+//!
+//! let filter = Filter {
+//!     vlan: ElementFilter::Some(VlanFilter::Single(Some(100))),
+//!     transport: ElementFilter::Some(TransportFilter::Tcp{ source_port: None, destination_port: Some(443) }),
+//!     ..Default::default()
+//! };
+//! let program = bpf::compile(&filter);
+//!
+//! let mut packet = Vec::new();
+//! PacketBuilder::ethernet2([1;6], [2;6])
+//!     .single_vlan(100)
+//!     .ipv4([10,1,2,3], [192,168,1,1], 20)
+//!     .tcp(50000, 443, 0, 1024)
+//!     .write(&mut packet, &[])
+//!     .unwrap();
+//!
+//! //a return value other then 0 means the packet is accepted
+//! assert_ne!(0, bpf::run(&program, &packet).unwrap());
+//! assert!(filter.applies_to_slice(&SlicedPacket::from_ethernet(&packet).unwrap()));
+//! ```
+//!
+//! # Limitations
+//!
+//! Compiled programs assume well formed packets (e.g. the ip version field is not checked against the ether type).
+//! The transport header is only evaluated if it directly follows the IPv4 or IPv6 header. As the transport header can
+//! not be located otherwise, packets with IPv6 extension headers (except the ESP header) or an IPv4 authentication
+//! header are rejected by programs with a transport filter (even though `SlicedPacket` skips these headers).
+use super::*;
+
+use crate::packet_filter::*;
+
+const ETH_IPV4: u16 = EtherType::Ipv4 as u16;
+const ETH_IPV6: u16 = EtherType::Ipv6 as u16;
+const ETH_VLAN: [u16;3] = [
+    EtherType::VlanTaggedFrame as u16,
+    EtherType::ProviderBridging as u16,
+    EtherType::VlanDoubleTaggedFrame as u16
+];
+
+const IP_UDP: u8 = IpTrafficClass::Udp as u8;
+const IP_TCP: u8 = IpTrafficClass::Tcp as u8;
+const IP_AUTH: u8 = IpTrafficClass::IPv6AuthenticationHeader as u8;
+
+///IPv6 extension headers that are skipped by `SlicedPacket` (the esp header ends the extension headers).
+const IPV6_EXT_HEADERS: [u8;10] = [
+    IpTrafficClass::IPv6HeaderHopByHop as u8,
+    IpTrafficClass::IPv6RouteHeader as u8,
+    IpTrafficClass::IPv6FragmentationHeader as u8,
+    IpTrafficClass::IPv6AuthenticationHeader as u8,
+    IpTrafficClass::IPv6DestinationOptions as u8,
+    IpTrafficClass::MobilityHeader as u8,
+    IpTrafficClass::Hip as u8,
+    IpTrafficClass::Shim6 as u8,
+    IpTrafficClass::ExperimentalAndTesting0 as u8,
+    IpTrafficClass::ExperimentalAndTesting1 as u8
+];
+
+///Scratch memory words used by compiled programs.
+const MEM_ETHER_TYPE: u32 = 0;
+const MEM_IP_OFFSET: u32 = 1;
+
+///Offsets of the ip header depending on the number of vlan tags.
+const IP_OFFSET_NO_VLAN: u32 = 14;
+const IP_OFFSET_SINGLE_VLAN: u32 = 18;
+const IP_OFFSET_DOUBLE_VLAN: u32 = 22;
+
+//instruction classes
+pub const BPF_LD: u16 = 0x00;
+pub const BPF_LDX: u16 = 0x01;
+pub const BPF_ST: u16 = 0x02;
+pub const BPF_STX: u16 = 0x03;
+pub const BPF_ALU: u16 = 0x04;
+pub const BPF_JMP: u16 = 0x05;
+pub const BPF_RET: u16 = 0x06;
+pub const BPF_MISC: u16 = 0x07;
+
+//load sizes
+pub const BPF_W: u16 = 0x00;
+pub const BPF_H: u16 = 0x08;
+pub const BPF_B: u16 = 0x10;
+
+//load modes
+pub const BPF_IMM: u16 = 0x00;
+pub const BPF_ABS: u16 = 0x20;
+pub const BPF_IND: u16 = 0x40;
+pub const BPF_MEM: u16 = 0x60;
+pub const BPF_LEN: u16 = 0x80;
+pub const BPF_MSH: u16 = 0xa0;
+
+//alu operations
+pub const BPF_ADD: u16 = 0x00;
+pub const BPF_SUB: u16 = 0x10;
+pub const BPF_MUL: u16 = 0x20;
+pub const BPF_DIV: u16 = 0x30;
+pub const BPF_OR: u16 = 0x40;
+pub const BPF_AND: u16 = 0x50;
+pub const BPF_LSH: u16 = 0x60;
+pub const BPF_RSH: u16 = 0x70;
+pub const BPF_NEG: u16 = 0x80;
+pub const BPF_MOD: u16 = 0x90;
+pub const BPF_XOR: u16 = 0xa0;
+
+//jump operations
+pub const BPF_JA: u16 = 0x00;
+pub const BPF_JEQ: u16 = 0x10;
+pub const BPF_JGT: u16 = 0x20;
+pub const BPF_JGE: u16 = 0x30;
+pub const BPF_JSET: u16 = 0x40;
+
+//operand sources (alu, jump & return instructions)
+pub const BPF_K: u16 = 0x00;
+pub const BPF_X: u16 = 0x08;
+pub const BPF_A: u16 = 0x10;
+
+//misc operations
+pub const BPF_TAX: u16 = 0x00;
+pub const BPF_TXA: u16 = 0x80;
+
+///Number of 32 bit words in the scratch memory.
+pub const BPF_MEMWORDS: usize = 16;
+
+///Maximum number of instructions in a program.
+pub const BPF_MAXINSNS: usize = 4096;
+
+///Value returned by compiled programs for accepted packets (keeps the whole packet).
+pub const BPF_ACCEPT: u32 = u32::MAX;
+
+///Classic BPF instruction (same memory layout as `struct sock_filter`).
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[repr(C)]
+pub struct BpfInstruction {
+    pub code: u16,
+    ///Number of instructions skipped if the jump condition is true.
+    pub jt: u8,
+    ///Number of instructions skipped if the jump condition is false.
+    pub jf: u8,
+    pub k: u32,
+}
+
+impl BpfInstruction {
+    ///Creates a non jump instruction (`BPF_STMT`).
+    pub fn statement(code: u16, k: u32) -> BpfInstruction {
+        BpfInstruction{ code, jt: 0, jf: 0, k }
+    }
+
+    ///Creates a jump instruction (`BPF_JUMP`).
+    pub fn jump(code: u16, k: u32, jt: u8, jf: u8) -> BpfInstruction {
+        BpfInstruction{ code, jt, jf, k }
+    }
+}
+
+///Errors of invalid programs (detected before a program is executed).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BpfError {
+    ///The program contains no instructions.
+    EmptyProgram,
+    ///The program contains more then `BPF_MAXINSNS` instructions (number of instructions as argument).
+    TooManyInstructions(usize),
+    ///The instruction at the index has an unknown opcode.
+    UnknownOpcode { index: usize, code: u16 },
+    ///A jump of the instruction at the index targets an instruction after the end of the program.
+    JumpOutOfRange { index: usize },
+    ///The instruction at the index accesses a scratch memory word bigger or equal to `BPF_MEMWORDS`.
+    InvalidMemoryIndex { index: usize },
+    ///The instruction at the index divides by the constant 0 or shifts by a constant bigger then 31.
+    InvalidConstant { index: usize },
+    ///The last instruction of the program is not a return instruction.
+    MissingReturn,
+}
+
+impl fmt::Display for BpfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use BpfError::*;
+        match self {
+            EmptyProgram => {
+                write!(f, "BpfError: The program contains no instructions.")
+            },
+            TooManyInstructions(count) => {
+                write!(f, "BpfError: The program contains {} instructions (at most {} instructions are allowed).", count, BPF_MAXINSNS)
+            },
+            UnknownOpcode{ index, code } => {
+                write!(f, "BpfError: The instruction {} has the unknown opcode 0x{:04x}.", index, code)
+            },
+            JumpOutOfRange{ index } => {
+                write!(f, "BpfError: A jump of the instruction {} targets an instruction after the end of the program.", index)
+            },
+            InvalidMemoryIndex{ index } => {
+                write!(f, "BpfError: The instruction {} accesses a scratch memory word outside of the {} available words.", index, BPF_MEMWORDS)
+            },
+            InvalidConstant{ index } => {
+                write!(f, "BpfError: The instruction {} divides by 0 or shifts by more then 31 bits.", index)
+            },
+            MissingReturn => {
+                write!(f, "BpfError: The last instruction of the program is not a return instruction.")
+            },
+        }
+    }
+}
+
+impl Error for BpfError {
+
+}
+
+///Decoded instruction (result of the validation).
+#[derive(Clone, Copy, Debug)]
+enum Operation {
+    ///Load of 1, 2 or 4 bytes from the packet to A (offset relative to X if indirect is set).
+    Load { size: u32, offset: u32, indirect: bool },
+    LoadLength,
+    LoadImmediate(u32),
+    LoadMemory(usize),
+    LoadXImmediate(u32),
+    LoadXLength,
+    LoadXMemory(usize),
+    ///X = 4 * (packet[offset] & 0xf) (length of an ipv4 header).
+    LoadXHeaderLength(u32),
+    Store(usize),
+    StoreX(usize),
+    Alu { operation: u16, source_x: bool, k: u32 },
+    Negate,
+    JumpAlways(u32),
+    Jump { operation: u16, source_x: bool, k: u32, jt: u8, jf: u8 },
+    ReturnConstant(u32),
+    ReturnA,
+    Tax,
+    Txa,
+}
+
+///Checks that the program is valid (same checks as done by the kernel when attaching a filter).
+pub fn validate(program: &[BpfInstruction]) -> Result<(), BpfError> {
+    decode(program).map(|_| ())
+}
+
+///Executes the program on the packet and returns the result of the program (0 if the packet is rejected).
+///
+///Loads outside of the packet & divisions by zero reject the packet. Errors are only returned for invalid programs
+///(see [`validate`](fn.validate.html)).
+pub fn run(program: &[BpfInstruction], packet: &[u8]) -> Result<u32, BpfError> {
+    use self::Operation::*;
+
+    let operations = decode(program)?;
+
+    let mut a: u32 = 0;
+    let mut x: u32 = 0;
+    let mut memory = [0u32; BPF_MEMWORDS];
+    let mut pc = 0;
+
+    //the validation ensures all jumps stay in the program & the last instruction is a return
+    loop {
+        let operation = operations[pc];
+        pc += 1;
+        match operation {
+            Load{ size, offset, indirect } => {
+                let offset = if indirect { x.checked_add(offset) } else { Some(offset) };
+                match offset.and_then(|offset| load(packet, offset, size)) {
+                    Some(value) => a = value,
+                    None => return Ok(0)
+                }
+            },
+            LoadLength => a = packet.len() as u32,
+            LoadImmediate(value) => a = value,
+            LoadMemory(index) => a = memory[index],
+            LoadXImmediate(value) => x = value,
+            LoadXLength => x = packet.len() as u32,
+            LoadXMemory(index) => x = memory[index],
+            LoadXHeaderLength(offset) => match load(packet, offset, 1) {
+                Some(value) => x = 4*(value & 0xf),
+                None => return Ok(0)
+            },
+            Store(index) => memory[index] = a,
+            StoreX(index) => memory[index] = x,
+            Alu{ operation, source_x, k } => {
+                let value = if source_x { x } else { k };
+                a = match operation {
+                    BPF_ADD => a.wrapping_add(value),
+                    BPF_SUB => a.wrapping_sub(value),
+                    BPF_MUL => a.wrapping_mul(value),
+                    BPF_DIV => match a.checked_div(value) {
+                        Some(result) => result,
+                        None => return Ok(0)
+                    },
+                    BPF_MOD => match a.checked_rem(value) {
+                        Some(result) => result,
+                        None => return Ok(0)
+                    },
+                    BPF_OR => a | value,
+                    BPF_AND => a & value,
+                    BPF_XOR => a ^ value,
+                    BPF_LSH => a.checked_shl(value).unwrap_or(0),
+                    _ => a.checked_shr(value).unwrap_or(0), //BPF_RSH
+                };
+            },
+            Negate => a = a.wrapping_neg(),
+            JumpAlways(offset) => pc += offset as usize,
+            Jump{ operation, source_x, k, jt, jf } => {
+                let value = if source_x { x } else { k };
+                let condition = match operation {
+                    BPF_JEQ => a == value,
+                    BPF_JGT => a > value,
+                    BPF_JGE => a >= value,
+                    _ => 0 != a & value, //BPF_JSET
+                };
+                pc += usize::from(if condition { jt } else { jf });
+            },
+            ReturnConstant(value) => return Ok(value),
+            ReturnA => return Ok(a),
+            Tax => x = a,
+            Txa => a = x,
+        }
+    }
+}
+
+///Loads a big endian value with the given size (1, 2 or 4 bytes) from the packet.
+fn load(packet: &[u8], offset: u32, size: u32) -> Option<u32> {
+    let start = offset as usize;
+    let bytes = packet.get(start..start.checked_add(size as usize)?)?;
+    Some(bytes.iter().fold(0, |result, value| (result << 8) | u32::from(*value)))
+}
+
+///Validates & decodes all instructions of the program.
+fn decode(program: &[BpfInstruction]) -> Result<Vec<Operation>, BpfError> {
+    use self::Operation::*;
+
+    if program.is_empty() {
+        return Err(BpfError::EmptyProgram);
+    }
+    if program.len() > BPF_MAXINSNS {
+        return Err(BpfError::TooManyInstructions(program.len()));
+    }
+
+    let mut result = Vec::with_capacity(program.len());
+    for (index, instruction) in program.iter().enumerate() {
+        let code = instruction.code;
+        let k = instruction.k;
+        let unknown = BpfError::UnknownOpcode{ index, code };
+        let memory_index = || -> Result<usize, BpfError> {
+            if (k as usize) < BPF_MEMWORDS {
+                Ok(k as usize)
+            } else {
+                Err(BpfError::InvalidMemoryIndex{ index })
+            }
+        };
+        //number of instructions following the current one
+        let remaining = program.len() - index - 1;
+
+        let operation = match code & 0x07 {
+            BPF_LD => {
+                let size = match code & 0x18 {
+                    BPF_W => 4,
+                    BPF_H => 2,
+                    BPF_B => 1,
+                    _ => return Err(unknown)
+                };
+                match (code & 0xe0, size) {
+                    (BPF_ABS, _) => Load{ size, offset: k, indirect: false },
+                    (BPF_IND, _) => Load{ size, offset: k, indirect: true },
+                    (BPF_IMM, 4) => LoadImmediate(k),
+                    (BPF_MEM, 4) => LoadMemory(memory_index()?),
+                    (BPF_LEN, 4) => LoadLength,
+                    _ => return Err(unknown)
+                }
+            },
+            BPF_LDX => match (code & 0x18, code & 0xe0) {
+                (BPF_W, BPF_IMM) => LoadXImmediate(k),
+                (BPF_W, BPF_MEM) => LoadXMemory(memory_index()?),
+                (BPF_W, BPF_LEN) => LoadXLength,
+                (BPF_B, BPF_MSH) => LoadXHeaderLength(k),
+                _ => return Err(unknown)
+            },
+            BPF_ST if BPF_ST == code => Store(memory_index()?),
+            BPF_STX if BPF_STX == code => StoreX(memory_index()?),
+            BPF_ALU => {
+                let operation = code & 0xf0;
+                let source_x = BPF_X == code & 0x08;
+                if BPF_NEG == operation {
+                    if source_x {
+                        return Err(unknown);
+                    }
+                    Negate
+                } else if operation > BPF_XOR {
+                    return Err(unknown);
+                } else {
+                    let invalid_constant = !source_x && (
+                        ((BPF_DIV == operation || BPF_MOD == operation) && 0 == k) ||
+                        ((BPF_LSH == operation || BPF_RSH == operation) && k > 31)
+                    );
+                    if invalid_constant {
+                        return Err(BpfError::InvalidConstant{ index });
+                    }
+                    Alu{ operation, source_x, k }
+                }
+            },
+            BPF_JMP => {
+                let operation = code & 0xf0;
+                let source_x = BPF_X == code & 0x08;
+                if BPF_JA == operation {
+                    if source_x {
+                        return Err(unknown);
+                    }
+                    if k as usize >= remaining {
+                        return Err(BpfError::JumpOutOfRange{ index });
+                    }
+                    JumpAlways(k)
+                } else if operation > BPF_JSET {
+                    return Err(unknown);
+                } else {
+                    if usize::from(instruction.jt) >= remaining || usize::from(instruction.jf) >= remaining {
+                        return Err(BpfError::JumpOutOfRange{ index });
+                    }
+                    Jump{ operation, source_x, k, jt: instruction.jt, jf: instruction.jf }
+                }
+            },
+            BPF_RET => match code & 0x18 {
+                BPF_K => ReturnConstant(k),
+                BPF_A => ReturnA,
+                _ => return Err(unknown)
+            },
+            BPF_MISC => match code & 0xf8 {
+                BPF_TAX => Tax,
+                BPF_TXA => Txa,
+                _ => return Err(unknown)
+            },
+            _ => return Err(unknown)
+        };
+        result.push(operation);
+    }
+
+    match result.last() {
+        Some(ReturnConstant(_)) | Some(ReturnA) => Ok(result),
+        _ => Err(BpfError::MissingReturn)
+    }
+}
+
+///Compiles the filter to a program for ethernet II frames.
+///
+///The program returns `BPF_ACCEPT` for packets matching the filter & 0 for all other packets (see the
+///[module documentation](index.html) for the limitations).
+pub fn compile(filter: &Filter) -> Vec<BpfInstruction> {
+    let mut compiler = Compiler::new();
+
+    //determine the ether type & the ip header offset
+    if ElementFilter::Any != filter.vlan || ElementFilter::Any != filter.ip || ElementFilter::Any != filter.transport {
+        compiler.layout();
+    }
+
+    match &filter.link {
+        ElementFilter::Any => {},
+        //all frames contain an ethernet II header
+        ElementFilter::No => compiler.jump_always(Target::Reject),
+        ElementFilter::Some(LinkFilter::Ethernet2{ source, destination }) => {
+            if let Some(destination) = destination {
+                compiler.expect_mac_address(0, destination);
+            }
+            if let Some(source) = source {
+                compiler.expect_mac_address(6, source);
+            }
+        },
+    }
+
+    match &filter.vlan {
+        ElementFilter::Any => {},
+        ElementFilter::No => {
+            compiler.statement(BPF_LD | BPF_MEM, MEM_IP_OFFSET);
+            compiler.expect(IP_OFFSET_NO_VLAN);
+        },
+        ElementFilter::Some(VlanFilter::Single(identifier)) => {
+            compiler.statement(BPF_LD | BPF_MEM, MEM_IP_OFFSET);
+            compiler.expect(IP_OFFSET_SINGLE_VLAN);
+            compiler.expect_vlan_identifier(14, identifier);
+        },
+        ElementFilter::Some(VlanFilter::Double{ outer_identifier, inner_identifier }) => {
+            compiler.statement(BPF_LD | BPF_MEM, MEM_IP_OFFSET);
+            compiler.expect(IP_OFFSET_DOUBLE_VLAN);
+            compiler.expect_vlan_identifier(14, outer_identifier);
+            compiler.expect_vlan_identifier(18, inner_identifier);
+        },
+    }
+
+    match &filter.ip {
+        ElementFilter::Any => {},
+        ElementFilter::No => {
+            compiler.statement(BPF_LD | BPF_MEM, MEM_ETHER_TYPE);
+            compiler.expect_not(u32::from(ETH_IPV4));
+            compiler.expect_not(u32::from(ETH_IPV6));
+        },
+        ElementFilter::Some(IpFilter::Ipv4{ source, destination }) => {
+            compiler.statement(BPF_LD | BPF_MEM, MEM_ETHER_TYPE);
+            compiler.expect(u32::from(ETH_IPV4));
+            compiler.statement(BPF_LDX | BPF_MEM, MEM_IP_OFFSET);
+            if let Some(source) = source {
+                compiler.expect_bytes(12, source);
+            }
+            if let Some(destination) = destination {
+                compiler.expect_bytes(16, destination);
+            }
+        },
+        ElementFilter::Some(IpFilter::Ipv6{ source, destination }) => {
+            compiler.statement(BPF_LD | BPF_MEM, MEM_ETHER_TYPE);
+            compiler.expect(u32::from(ETH_IPV6));
+            compiler.statement(BPF_LDX | BPF_MEM, MEM_IP_OFFSET);
+            if let Some(source) = source {
+                compiler.expect_bytes(8, source);
+            }
+            if let Some(destination) = destination {
+                compiler.expect_bytes(24, destination);
+            }
+        },
+    }
+
+    match &filter.transport {
+        ElementFilter::Any => {},
+        ElementFilter::No => {
+            let ipv6 = compiler.label();
+            let done = compiler.label();
+            compiler.statement(BPF_LDX | BPF_MEM, MEM_IP_OFFSET);
+            compiler.statement(BPF_LD | BPF_MEM, MEM_ETHER_TYPE);
+            compiler.jump(BPF_JMP | BPF_JEQ | BPF_K, u32::from(ETH_IPV4), Target::Next, Target::Label(ipv6));
+            compiler.statement(BPF_LD | BPF_B | BPF_IND, 9);
+            compiler.expect_not(u32::from(IP_TCP));
+            compiler.expect_not(u32::from(IP_UDP));
+            //the authentication header can be followed by a transport header
+            compiler.expect_not(u32::from(IP_AUTH));
+            compiler.jump_always(Target::Label(done));
+            compiler.place(ipv6);
+            compiler.jump(BPF_JMP | BPF_JEQ | BPF_K, u32::from(ETH_IPV6), Target::Next, Target::Label(done));
+            compiler.statement(BPF_LD | BPF_B | BPF_IND, 6);
+            compiler.expect_not(u32::from(IP_TCP));
+            compiler.expect_not(u32::from(IP_UDP));
+            //extension headers can be followed by a transport header
+            for ext in IPV6_EXT_HEADERS.iter() {
+                compiler.expect_not(u32::from(*ext));
+            }
+            compiler.place(done);
+        },
+        ElementFilter::Some(TransportFilter::Udp{ source_port, destination_port }) => {
+            compiler.transport_header(IP_UDP);
+            compiler.expect_ports(source_port, destination_port);
+        },
+        ElementFilter::Some(TransportFilter::Tcp{ source_port, destination_port }) => {
+            compiler.transport_header(IP_TCP);
+            compiler.expect_ports(source_port, destination_port);
+        },
+    }
+
+    compiler.finish()
+}
+
+///Target of a jump during the compilation.
+#[derive(Clone, Copy, Debug)]
+enum Target {
+    ///The following instruction.
+    Next,
+    ///Return instruction rejecting the packet.
+    Reject,
+    ///Label created via `Compiler::label`.
+    Label(usize),
+}
+
+///Builder of a program with symbolic jump targets.
+struct Compiler {
+    ///Instructions and the targets of the jumps (jt & jf or k for BPF_JA).
+    instructions: Vec<(BpfInstruction, Target, Target)>,
+    ///Instruction index of each label.
+    labels: Vec<Option<usize>>,
+}
+
+impl Compiler {
+    fn new() -> Compiler {
+        Compiler {
+            instructions: Vec::new(),
+            labels: Vec::new(),
+        }
+    }
+
+    fn statement(&mut self, code: u16, k: u32) {
+        self.instructions.push((BpfInstruction::statement(code, k), Target::Next, Target::Next));
+    }
+
+    fn jump(&mut self, code: u16, k: u32, jt: Target, jf: Target) {
+        self.instructions.push((BpfInstruction::jump(code, k, 0, 0), jt, jf));
+    }
+
+    fn jump_always(&mut self, target: Target) {
+        self.instructions.push((BpfInstruction::statement(BPF_JMP | BPF_JA, 0), target, Target::Next));
+    }
+
+    ///Rejects the packet if A is not equal to the value.
+    fn expect(&mut self, value: u32) {
+        self.jump(BPF_JMP | BPF_JEQ | BPF_K, value, Target::Next, Target::Reject);
+    }
+
+    ///Rejects the packet if A is equal to the value.
+    fn expect_not(&mut self, value: u32) {
+        self.jump(BPF_JMP | BPF_JEQ | BPF_K, value, Target::Reject, Target::Next);
+    }
+
+    fn label(&mut self) -> usize {
+        self.labels.push(None);
+        self.labels.len() - 1
+    }
+
+    ///Sets the label to the next instruction.
+    fn place(&mut self, label: usize) {
+        self.labels[label] = Some(self.instructions.len());
+    }
+
+    ///Stores the ether type following the vlan headers in `MEM_ETHER_TYPE` & the offset of the data following the
+    ///vlan headers in `MEM_IP_OFFSET` (same vlan detection as `SlicedPacket::from_ethernet`).
+    fn layout(&mut self) {
+        let single_vlan = self.label();
+        let double_vlan = self.label();
+        let store = self.label();
+
+        self.statement(BPF_LD | BPF_H | BPF_ABS, 12);
+        self.jump_vlan(single_vlan);
+        self.statement(BPF_ST, MEM_ETHER_TYPE);
+        self.statement(BPF_LDX | BPF_IMM, IP_OFFSET_NO_VLAN);
+        self.jump_always(Target::Label(store));
+
+        self.place(single_vlan);
+        self.statement(BPF_LD | BPF_H | BPF_ABS, 16);
+        self.jump_vlan(double_vlan);
+        self.statement(BPF_ST, MEM_ETHER_TYPE);
+        self.statement(BPF_LDX | BPF_IMM, IP_OFFSET_SINGLE_VLAN);
+        self.jump_always(Target::Label(store));
+
+        self.place(double_vlan);
+        self.statement(BPF_LD | BPF_H | BPF_ABS, 20);
+        self.statement(BPF_ST, MEM_ETHER_TYPE);
+        self.statement(BPF_LDX | BPF_IMM, IP_OFFSET_DOUBLE_VLAN);
+
+        self.place(store);
+        self.statement(BPF_STX, MEM_IP_OFFSET);
+    }
+
+    ///Jumps to the label if A contains a vlan ether type.
+    fn jump_vlan(&mut self, label: usize) {
+        for ether_type in ETH_VLAN.iter() {
+            self.jump(BPF_JMP | BPF_JEQ | BPF_K, u32::from(*ether_type), Target::Label(label), Target::Next);
+        }
+    }
+
+    fn expect_mac_address(&mut self, offset: u32, address: &[u8;6]) {
+        self.statement(BPF_LD | BPF_W | BPF_ABS, offset);
+        self.expect(u32::from_be_bytes([address[0], address[1], address[2], address[3]]));
+        self.statement(BPF_LD | BPF_H | BPF_ABS, offset + 4);
+        self.expect(u32::from(u16::from_be_bytes([address[4], address[5]])));
+    }
+
+    fn expect_vlan_identifier(&mut self, offset: u32, identifier: &Option<u16>) {
+        if let Some(identifier) = identifier {
+            self.statement(BPF_LD | BPF_H | BPF_ABS, offset);
+            self.statement(BPF_ALU | BPF_AND | BPF_K, 0xfff);
+            self.expect(u32::from(*identifier));
+        }
+    }
+
+    ///Compares the bytes (multiple of 4) starting at the offset relative to X.
+    fn expect_bytes(&mut self, offset: u32, bytes: &[u8]) {
+        for (index, word) in bytes.chunks(4).enumerate() {
+            self.statement(BPF_LD | BPF_W | BPF_IND, offset + 4*(index as u32));
+            self.expect(u32::from_be_bytes([word[0], word[1], word[2], word[3]]));
+        }
+    }
+
+    ///Rejects packets without a transport header with the protocol & sets X to the offset of the transport header.
+    fn transport_header(&mut self, protocol: u8) {
+        let ipv6 = self.label();
+        let done = self.label();
+        self.statement(BPF_LDX | BPF_MEM, MEM_IP_OFFSET);
+        self.statement(BPF_LD | BPF_MEM, MEM_ETHER_TYPE);
+        self.jump(BPF_JMP | BPF_JEQ | BPF_K, u32::from(ETH_IPV4), Target::Next, Target::Label(ipv6));
+
+        //ipv4 (header length in the lower 4 bits of the first byte)
+        self.statement(BPF_LD | BPF_B | BPF_IND, 9);
+        self.expect(u32::from(protocol));
+        self.statement(BPF_LD | BPF_B | BPF_IND, 0);
+        self.statement(BPF_ALU | BPF_AND | BPF_K, 0xf);
+        self.statement(BPF_ALU | BPF_LSH | BPF_K, 2);
+        self.statement(BPF_ALU | BPF_ADD | BPF_X, 0);
+        self.statement(BPF_MISC | BPF_TAX, 0);
+        self.jump_always(Target::Label(done));
+
+        //ipv6 (fixed header length)
+        self.place(ipv6);
+        self.expect(u32::from(ETH_IPV6));
+        self.statement(BPF_LD | BPF_B | BPF_IND, 6);
+        self.expect(u32::from(protocol));
+        self.statement(BPF_MISC | BPF_TXA, 0);
+        self.statement(BPF_ALU | BPF_ADD | BPF_K, 40);
+        self.statement(BPF_MISC | BPF_TAX, 0);
+
+        self.place(done);
+    }
+
+    ///Compares the ports of the tcp or udp header at the offset X.
+    fn expect_ports(&mut self, source_port: &Option<u16>, destination_port: &Option<u16>) {
+        if let Some(port) = source_port {
+            self.statement(BPF_LD | BPF_H | BPF_IND, 0);
+            self.expect(u32::from(*port));
+        }
+        if let Some(port) = destination_port {
+            self.statement(BPF_LD | BPF_H | BPF_IND, 2);
+            self.expect(u32::from(*port));
+        }
+    }
+
+    ///Appends the return instructions & resolves the jump targets.
+    fn finish(mut self) -> Vec<BpfInstruction> {
+        self.statement(BPF_RET | BPF_K, BPF_ACCEPT);
+        let reject = self.instructions.len();
+        self.statement(BPF_RET | BPF_K, 0);
+
+        let labels = self.labels;
+        let offset = |index: usize, target: Target| -> usize {
+            let destination = match target {
+                Target::Next => index + 1,
+                Target::Reject => reject,
+                Target::Label(label) => labels[label].expect("label was not placed"),
+            };
+            destination - index - 1
+        };
+
+        self.instructions.iter().enumerate().map(|(index, (instruction, jt, jf))| {
+            let mut result = *instruction;
+            if BPF_JMP | BPF_JA == result.code {
+                result.k = offset(index, *jt) as u32;
+            } else if BPF_JMP == result.code & 0x07 {
+                //programs compiled from filters are short enough for all jumps to fit in 8 bits
+                result.jt = offset(index, *jt) as u8;
+                result.jf = offset(index, *jf) as u8;
+            }
+            result
+        }).collect()
+    }
+}
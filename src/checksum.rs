@@ -0,0 +1,212 @@
+//! Helpers for calculating & updating the 16 bit ones complement checksum used in the
+//! ipv4, udp & tcp headers (rfc1071) including incremental updates (rfc1624).
+//!
+//! # Example
+//!
+//! Calculating the checksum of an udp packet & updating it after the source port was changed:
+//!
+//! ```
+//! use etherparse::checksum::*;
+//!
+//! let source = [192,168,1,1];
+//! let destination = [192,168,1,2];
+//! let udp = [
+//!     0x04, 0xd2, //source port
+//!     0x16, 0x2e, //destination port
+//!     0x00, 0x0c, //length
+//!     0x00, 0x00, //checksum (zero during the calculation)
+//!     1, 2, 3, 4 //payload
+//! ];
+//!
+//! let checksum = ipv4_pseudo_header_sum(source, destination, 17, udp.len() as u16)
+//!     .add_slice(&udp)
+//!     .to_ones_complement_with_no_zero();
+//!
+//! //change the source port from 1234 to 4321
+//! let updated = update_u16(checksum, 1234, 4321);
+//! # let mut changed = udp;
+//! # changed[0..2].copy_from_slice(&4321u16.to_be_bytes());
+//! # assert_eq!(
+//! #     ipv4_pseudo_header_sum(source, destination, 17, udp.len() as u16)
+//! #         .add_slice(&changed)
+//! #         .to_ones_complement_with_no_zero(),
+//! #     updated
+//! # );
+//! ```
+
+extern crate byteorder;
+use self::byteorder::{ByteOrder, BigEndian};
+
+///Accumulator for the ones complement sum of 16 bit words.
+///
+///The sum is kept in a 64 bit value & only folded to 16 bits when the result is requested,
+///so an arbitrary amount of data (up to 2^48 words) can be added without overflow.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Sum16BitWords {
+    sum: u64
+}
+
+impl Sum16BitWords {
+    ///Creates an empty sum.
+    pub fn new() -> Sum16BitWords {
+        Sum16BitWords { sum: 0 }
+    }
+
+    ///Adds a 16 bit word given as two big endian bytes.
+    #[inline]
+    pub fn add_2bytes(self, value: [u8;2]) -> Sum16BitWords {
+        self.add_u16(u16::from_be_bytes(value))
+    }
+
+    ///Adds two 16 bit words given as four big endian bytes.
+    #[inline]
+    pub fn add_4bytes(self, value: [u8;4]) -> Sum16BitWords {
+        self.add_u32(u32::from_be_bytes(value))
+    }
+
+    ///Adds eight 16 bit words given as sixteen big endian bytes (e.g. an ipv6 address).
+    #[inline]
+    pub fn add_16bytes(self, value: [u8;16]) -> Sum16BitWords {
+        self.add_slice(&value)
+    }
+
+    ///Adds a 16 bit word.
+    #[inline]
+    pub fn add_u16(self, value: u16) -> Sum16BitWords {
+        Sum16BitWords {
+            sum: self.sum + u64::from(value)
+        }
+    }
+
+    ///Adds the upper & lower 16 bits of a 32 bit value as two words.
+    #[inline]
+    pub fn add_u32(self, value: u32) -> Sum16BitWords {
+        Sum16BitWords {
+            sum: self.sum + u64::from(value >> 16) + u64::from(value & 0xffff)
+        }
+    }
+
+    ///Adds the given slice as big endian 16 bit words. If the slice has an odd length
+    ///the last byte is padded with a zero.
+    ///
+    ///Note that because of the padding all slices except the last one added to a sum
+    ///must have an even length.
+    pub fn add_slice(self, slice: &[u8]) -> Sum16BitWords {
+        //sum 8 bytes at a time in native byte order into a 128 bit accumulator (the carries
+        //are added back when folding). As the ones complement sum is independent of the byte
+        //order (rfc1071 2.B), only the folded result has to be swapped to big endian.
+        let mut chunks = slice.chunks_exact(8);
+        let mut wide_sum: u128 = 0;
+        for chunk in &mut chunks {
+            let mut value: [u8;8] = [0;8];
+            value.copy_from_slice(chunk);
+            wide_sum += u128::from(u64::from_ne_bytes(value));
+        }
+        let wide_sum = {
+            let (result, carry) = (wide_sum as u64).overflowing_add((wide_sum >> 64) as u64);
+            result + u64::from(carry)
+        };
+        let mut sum = self.sum + u64::from(u16::from_be(fold_u64(wide_sum)));
+
+        //sum the rest 2 bytes at a time
+        let rest = chunks.remainder();
+        let even_len = rest.len() / 2 * 2;
+        for chunk in rest[..even_len].chunks(2) {
+            sum += u64::from( BigEndian::read_u16(chunk) );
+        }
+        //pad the last byte with 0
+        if even_len != rest.len() {
+            sum += u64::from( BigEndian::read_u16(&[rest[even_len], 0]) );
+        }
+        Sum16BitWords { sum }
+    }
+
+    ///Returns the sum folded to 16 bits (without the ones complement applied).
+    pub fn fold(self) -> u16 {
+        fold_u64(self.sum)
+    }
+
+    ///Returns the ones complement of the folded sum (the checksum as used in the ipv4 & tcp headers).
+    pub fn ones_complement(self) -> u16 {
+        !self.fold()
+    }
+
+    ///Returns the ones complement of the folded sum but returns 0xffff instead of 0 (as used in udp,
+    ///where a checksum of 0 means that no checksum was calculated).
+    pub fn to_ones_complement_with_no_zero(self) -> u16 {
+        let folded = self.fold();
+        if 0xffff == folded {
+            folded
+        } else {
+            !folded
+        }
+    }
+}
+
+///Folds a 64 bit ones complement sum to 16 bits by adding the carries back.
+#[inline]
+fn fold_u64(value: u64) -> u16 {
+    let mut sum = value;
+    while 0 != sum >> 16 {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    sum as u16
+}
+
+///Returns the sum of an ipv4 pseudo header (source & destination address, protocol & the
+///length of the transport header plus payload) as used in the tcp & udp checksum calculation.
+pub fn ipv4_pseudo_header_sum(source: [u8;4], destination: [u8;4], protocol: u8, length: u16) -> Sum16BitWords {
+    Sum16BitWords::new()
+        .add_4bytes(source)
+        .add_4bytes(destination)
+        .add_u16(u16::from(protocol))
+        .add_u16(length)
+}
+
+///Returns the sum of an ipv6 pseudo header (source & destination address, next header & the
+///length of the transport header plus payload) as used in the tcp & udp checksum calculation.
+pub fn ipv6_pseudo_header_sum(source: &[u8;16], destination: &[u8;16], next_header: u8, length: u32) -> Sum16BitWords {
+    Sum16BitWords::new()
+        .add_16bytes(*source)
+        .add_16bytes(*destination)
+        .add_u16(u16::from(next_header))
+        .add_u32(length)
+}
+
+///Updates a checksum after a 16 bit word covered by the checksum was changed from `old` to `new` (rfc1624 eqn. 3).
+///
+///Note that an udp checksum of 0 (no checksum) must not be updated & an updated udp checksum
+///of 0 has to be transmitted as 0xffff.
+pub fn update_u16(checksum: u16, old: u16, new: u16) -> u16 {
+    // HC' = ~(~HC + ~m + m')
+    Sum16BitWords::new()
+        .add_u16(!checksum)
+        .add_u16(!old)
+        .add_u16(new)
+        .ones_complement()
+}
+
+///Updates a checksum after a 32 bit value covered by the checksum was changed from `old` to `new`
+///(e.g. a tcp sequence number).
+pub fn update_u32(checksum: u16, old: u32, new: u32) -> u16 {
+    let result = update_u16(checksum, (old >> 16) as u16, (new >> 16) as u16);
+    update_u16(result, (old & 0xffff) as u16, (new & 0xffff) as u16)
+}
+
+///Updates a checksum after an ipv4 address covered by the checksum (directly or via the pseudo header)
+///was changed from `old` to `new`.
+pub fn update_ipv4_address(checksum: u16, old: [u8;4], new: [u8;4]) -> u16 {
+    update_u32(checksum, u32::from_be_bytes(old), u32::from_be_bytes(new))
+}
+
+///Updates a checksum after an ipv6 address covered by the checksum (via the pseudo header) was
+///changed from `old` to `new`.
+pub fn update_ipv6_address(checksum: u16, old: &[u8;16], new: &[u8;16]) -> u16 {
+    // HC' = ~(~HC + ~m + m') applied to all words at once
+    let mut sum = Sum16BitWords::new().add_u16(!checksum);
+    for (old, new) in old.chunks(2).zip(new.chunks(2)) {
+        sum = sum.add_u16(!BigEndian::read_u16(old))
+                 .add_u16(BigEndian::read_u16(new));
+    }
+    sum.ones_complement()
+}
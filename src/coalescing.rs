@@ -0,0 +1,407 @@
+use super::*;
+
+///Errors that can occur when passing a packet to a [`TcpCoalescer`](struct.TcpCoalescer.html).
+///
+///With the exception of `Write` the errors signal that the packet was not taken by the coalescer & has to be
+///processed by the caller directly.
+#[derive(Debug)]
+pub enum CoalescingError {
+    ///The packet contains no ip header or no tcp header.
+    MissingTcpHeader,
+    ///The packet is an ip fragment.
+    IpFragment,
+    ///The packet contains an IPsec authentication header (the integrity check value can not be recalculated).
+    AuthenticationHeader,
+    ///Error while encoding the merged packet.
+    Write(WriteError),
+}
+
+impl fmt::Display for CoalescingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use CoalescingError::*;
+        match self {
+            MissingTcpHeader => {
+                write!(f, "CoalescingError: The packet contains no ip header or no tcp header.")
+            },
+            IpFragment => {
+                write!(f, "CoalescingError: The packet is an ip fragment.")
+            },
+            AuthenticationHeader => {
+                write!(f, "CoalescingError: The packet contains an IPsec authentication header (the integrity check value can not be recalculated).")
+            },
+            Write(err) => {
+                write!(f, "CoalescingError: Error while encoding the merged packet: {}", err)
+            },
+        }
+    }
+}
+
+impl Error for CoalescingError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use CoalescingError::*;
+        match self {
+            Write(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl From<WriteError> for CoalescingError {
+    fn from(err: WriteError) -> CoalescingError {
+        CoalescingError::Write(err)
+    }
+}
+
+impl From<ValueError> for CoalescingError {
+    fn from(err: ValueError) -> CoalescingError {
+        CoalescingError::Write(WriteError::ValueError(err))
+    }
+}
+
+///Merges consecutive in-order tcp segments of the same flow into larger packets (software generic receive
+///offload, the inverse of [`SlicedPacket::segment`](struct.SlicedPacket.html#method.segment)).
+///
+///A segment is appended to the packet held back for its flow (same ip addresses & ports) if
+///
+///* the link, vlan, ip (except the identification & length) & ipv6 extension headers are identical,
+///* the ipv4 identification is incremented by one (or unchanged if the don't fragment flag is set),
+///* the sequence number directly follows the held back data & the acknowledgment number is identical,
+///* the tcp options are identical (except the values of the timestamp option),
+///* no SYN, RST, URG or CWR flag is set & the checksums are valid,
+///* the payload is not larger then the first segment & the merged payload does not exceed the maximum size.
+///
+///Segments with a smaller payload then the first segment or with the PSH or FIN flag set end the merged packet.
+///The merged packet uses the window size & options (timestamps) of the last merged segment, its ip length &
+///checksums are recalculated. Packets that could not be merged with any other segment are returned unchanged.
+///
+///Held back packets are only returned once a segment that can not be merged arrives for the same flow or when
+///[`flush`](struct.TcpCoalescer.html#method.flush) is called.
+///
+/// # Example
+///
+/// ```
+/// # use etherparse::*;
+/// let mut coalescer = TcpCoalescer::new(0xffff);
+/// let mut merged = Vec::new();
+/// for index in 0..3 {
+///     let mut packet = Vec::new();
+///     let builder = PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+///         .tcp(1234, 80, 1000 + index*100, 1024)
+///         .ack(1);
+///     //the last segment has the psh flag set & ends the merged packet
+///     let builder = if 2 == index { builder.psh() } else { builder };
+///     builder.write(&mut packet, &[index as u8;100]).unwrap();
+///
+///     merged.extend(coalescer.push(&SlicedPacket::from_ip(&packet).unwrap()).unwrap());
+/// }
+/// assert_eq!(1, merged.len());
+///
+/// let packet = SlicedPacket::from_ip(&merged[0]).unwrap();
+/// assert_eq!(300, packet.payload.len());
+/// assert!(!packet.verify_checksums().contains_invalid());
+/// ```
+#[derive(Clone, Debug)]
+pub struct TcpCoalescer {
+    max_payload_len: usize,
+    pending: Vec<PendingTcpPacket>,
+}
+
+impl TcpCoalescer {
+    ///Creates a coalescer that merges segments into packets with a tcp payload of at most `max_payload_len` bytes
+    ///(additionally limited by the ip length fields).
+    pub fn new(max_payload_len: usize) -> TcpCoalescer {
+        TcpCoalescer {
+            max_payload_len,
+            pending: Vec::new(),
+        }
+    }
+
+    ///Maximum tcp payload length of the merged packets.
+    pub fn max_payload_len(&self) -> usize {
+        self.max_payload_len
+    }
+
+    ///Number of flows that currently have a packet held back.
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    ///Passes a packet to the coalescer & returns the packets that are complete afterwards (serialized starting
+    ///with the same layer as the given packet).
+    ///
+    ///Returns an error if the packet is not a tcp packet that can be processed (e.g. udp packets or ip fragments).
+    ///In this case the packet is not taken & the held back packets are not changed.
+    pub fn push(&mut self, packet: &SlicedPacket) -> Result<Vec<Vec<u8>>, CoalescingError> {
+        let segment = TcpSegment::from_sliced(packet)?;
+        let mut result = Vec::new();
+
+        let flow = self.pending.iter().position(|pending| pending.is_same_flow(&segment));
+        if let Some(index) = flow {
+            if segment.mergeable && self.pending[index].can_merge(&segment, self.max_payload_len) {
+                let ends = segment.ends_merge(self.pending[index].segment_size);
+                self.pending[index].merge(segment);
+                if ends {
+                    result.push(self.pending.remove(index).to_packet()?);
+                }
+                return Ok(result);
+            }
+            result.push(self.pending.remove(index).to_packet()?);
+        }
+
+        if segment.mergeable && !segment.ends_merge(segment.payload.len()) {
+            self.pending.push(PendingTcpPacket::new(segment));
+        } else {
+            result.push(PendingTcpPacket::new(segment).to_packet()?);
+        }
+        Ok(result)
+    }
+
+    ///Returns all held back packets & resets the coalescer.
+    pub fn flush(&mut self) -> Result<Vec<Vec<u8>>, CoalescingError> {
+        let mut result = Vec::with_capacity(self.pending.len());
+        for pending in self.pending.drain(..) {
+            result.push(pending.to_packet()?);
+        }
+        Ok(result)
+    }
+}
+
+///Decoded tcp segment passed to the coalescer.
+#[derive(Clone, Debug)]
+struct TcpSegment {
+    ///Serialized link & vlan headers.
+    link: Vec<u8>,
+    ip: IpHeader,
+    ///Serialized ipv6 extension headers.
+    extensions: Vec<u8>,
+    tcp: TcpHeader,
+    payload: Vec<u8>,
+    ///True if the segment can be merged with other segments.
+    mergeable: bool,
+}
+
+impl TcpSegment {
+    fn from_sliced(packet: &SlicedPacket) -> Result<TcpSegment, CoalescingError> {
+        use crate::CoalescingError::*;
+        const IP_FRAG: u8 = IpTrafficClass::IPv6FragmentationHeader as u8;
+        const IP_AUTH: u8 = IpTrafficClass::IPv6AuthenticationHeader as u8;
+
+        if packet.authentication_header.is_some() {
+            return Err(AuthenticationHeader);
+        }
+        let tcp = match &packet.transport {
+            Some(TransportSlice::Tcp(header)) => header.to_header(),
+            _ => return Err(MissingTcpHeader)
+        };
+
+        let mut link = Vec::new();
+        match &packet.link {
+            Some(LinkSlice::Ethernet2(value)) => link.extend_from_slice(value.slice()),
+            Some(LinkSlice::Ppp(value)) => link.extend_from_slice(value.slice()),
+            None => {}
+        }
+        match &packet.vlan {
+            Some(VlanSlice::SingleVlan(value)) => link.extend_from_slice(value.slice()),
+            Some(VlanSlice::DoubleVlan(value)) => link.extend_from_slice(value.slice()),
+            None => {}
+        }
+
+        let mut extensions = Vec::new();
+        let (ip, ip_payload_len) = match &packet.ip {
+            Some(InternetSlice::Ipv4(header)) => {
+                if header.more_fragments() || 0 != header.fragments_offset() {
+                    return Err(IpFragment);
+                }
+                (IpHeader::Version4(header.to_header()), usize::from(header.payload_len()))
+            },
+            Some(InternetSlice::Ipv6(header, extension_headers)) => {
+                for (id, extension) in extension_headers.iter().flatten() {
+                    match *id {
+                        IP_FRAG => return Err(IpFragment),
+                        IP_AUTH => return Err(AuthenticationHeader),
+                        _ => extensions.extend_from_slice(extension.slice())
+                    }
+                }
+                (IpHeader::Version6(header.to_header()), usize::from(header.payload_length()))
+            },
+            None => return Err(MissingTcpHeader)
+        };
+
+        //ignore data past the end of the ip packet (e.g. ethernet padding)
+        let payload_len = std::cmp::min(
+            packet.payload.len(),
+            ip_payload_len.saturating_sub(extensions.len() + usize::from(tcp.header_len()))
+        );
+        let payload = packet.payload[..payload_len].to_vec();
+
+        let special_flags = tcp.syn || tcp.rst || tcp.urg || tcp.cwr;
+        let mergeable = !payload.is_empty() &&
+                        !special_flags &&
+                        !packet.verify_checksums().contains_invalid();
+
+        Ok(TcpSegment {
+            link,
+            ip,
+            extensions,
+            tcp,
+            payload,
+            mergeable,
+        })
+    }
+
+    ///Returns true if no further segments can be appended after this segment.
+    fn ends_merge(&self, segment_size: usize) -> bool {
+        self.tcp.psh || self.tcp.fin || self.payload.len() < segment_size
+    }
+}
+
+///Packet held back by the coalescer (the first segment with the payload of all merged segments appended).
+#[derive(Clone, Debug)]
+struct PendingTcpPacket {
+    first: TcpSegment,
+    ///Payload size of the first segment.
+    segment_size: usize,
+    ///Number of merged segments.
+    segment_count: usize,
+    ///Ipv4 identification of the last merged segment.
+    last_identification: u16,
+}
+
+impl PendingTcpPacket {
+    fn new(segment: TcpSegment) -> PendingTcpPacket {
+        PendingTcpPacket {
+            segment_size: segment.payload.len(),
+            segment_count: 1,
+            last_identification: match &segment.ip {
+                IpHeader::Version4(header) => header.identification,
+                IpHeader::Version6(_) => 0
+            },
+            first: segment,
+        }
+    }
+
+    fn is_same_flow(&self, segment: &TcpSegment) -> bool {
+        let addresses_match = match (&self.first.ip, &segment.ip) {
+            (IpHeader::Version4(a), IpHeader::Version4(b)) => a.source == b.source && a.destination == b.destination,
+            (IpHeader::Version6(a), IpHeader::Version6(b)) => a.source == b.source && a.destination == b.destination,
+            _ => false
+        };
+        addresses_match &&
+        self.first.tcp.source_port == segment.tcp.source_port &&
+        self.first.tcp.destination_port == segment.tcp.destination_port
+    }
+
+    fn can_merge(&self, segment: &TcpSegment, max_payload_len: usize) -> bool {
+        let first = &self.first;
+        let payload_len = first.payload.len() + segment.payload.len();
+
+        //ip headers
+        let ip_matches = match (&first.ip, &segment.ip) {
+            (IpHeader::Version4(a), IpHeader::Version4(b)) => {
+                let identification_matches = b.identification == self.last_identification.wrapping_add(1) ||
+                                             (b.dont_fragment && b.identification == self.last_identification);
+                let mut b = b.clone();
+                b.identification = a.identification;
+                b.payload_len = a.payload_len;
+                b.header_checksum = a.header_checksum;
+                identification_matches &&
+                *a == b &&
+                a.header_len() + first.extensions.len() + usize::from(first.tcp.header_len()) + payload_len <= usize::from(u16::MAX)
+            },
+            (IpHeader::Version6(a), IpHeader::Version6(b)) => {
+                a.traffic_class == b.traffic_class &&
+                a.flow_label == b.flow_label &&
+                a.next_header == b.next_header &&
+                a.hop_limit == b.hop_limit &&
+                first.extensions.len() + usize::from(first.tcp.header_len()) + payload_len <= usize::from(u16::MAX)
+            },
+            _ => false
+        };
+
+        //tcp headers (the flags of the first segment are checked when it is pushed)
+        let a = &first.tcp;
+        let b = &segment.tcp;
+        let tcp_matches = b.sequence_number == a.sequence_number.wrapping_add(first.payload.len() as u32) &&
+                          a.acknowledgment_number == b.acknowledgment_number &&
+                          a.ns == b.ns &&
+                          a.ack == b.ack &&
+                          a.ece == b.ece &&
+                          options_match(a, b);
+
+        ip_matches &&
+        tcp_matches &&
+        first.link == segment.link &&
+        first.extensions == segment.extensions &&
+        segment.payload.len() <= self.segment_size &&
+        payload_len <= max_payload_len
+    }
+
+    fn merge(&mut self, segment: TcpSegment) {
+        if let IpHeader::Version4(header) = &segment.ip {
+            self.last_identification = header.identification;
+        }
+        //take over the tcp header of the segment (psh & fin flags, window size & the latest timestamps),
+        //except the fields describing the start of the merged payload (the other fields are equal, see `can_merge`)
+        let mut tcp = segment.tcp;
+        tcp.sequence_number = self.first.tcp.sequence_number;
+        tcp.urgent_pointer = self.first.tcp.urgent_pointer;
+        tcp.checksum = self.first.tcp.checksum;
+        self.first.tcp = tcp;
+        self.first.payload.extend_from_slice(&segment.payload);
+        self.segment_count += 1;
+    }
+
+    ///Serializes the packet (the checksums are only recalculated if multiple segments were merged).
+    fn to_packet(&self) -> Result<Vec<u8>, CoalescingError> {
+        let segment = &self.first;
+        let mut tcp = segment.tcp.clone();
+        let ip_payload_len = segment.extensions.len() + usize::from(tcp.header_len()) + segment.payload.len();
+
+        let mut result = Vec::with_capacity(segment.link.len() + 60 + ip_payload_len);
+        result.extend_from_slice(&segment.link);
+        match &segment.ip {
+            IpHeader::Version4(header) => {
+                let mut header = header.clone();
+                if self.segment_count > 1 {
+                    header.set_payload_len(ip_payload_len)?;
+                    tcp.checksum = tcp.calc_checksum_ipv4(&header, &segment.payload)?;
+                    header.write(&mut result)?;
+                } else {
+                    header.write_raw(&mut result)?;
+                }
+            },
+            IpHeader::Version6(header) => {
+                let mut header = header.clone();
+                if self.segment_count > 1 {
+                    header.set_payload_length(ip_payload_len)?;
+                    tcp.checksum = tcp.calc_checksum_ipv6(&header, &segment.payload)?;
+                }
+                header.write(&mut result)?;
+            }
+        }
+        result.extend_from_slice(&segment.extensions);
+        tcp.write(&mut result).map_err(WriteError::from)?;
+        result.extend_from_slice(&segment.payload);
+        Ok(result)
+    }
+}
+
+///Returns true if the options of both headers are identical (ignoring the values of timestamp options).
+fn options_match(a: &TcpHeader, b: &TcpHeader) -> bool {
+    use crate::TcpOptionElement::Timestamp;
+
+    if a.options().len() != b.options().len() {
+        return false;
+    }
+    let mut a_iter = a.options_iterator();
+    let mut b_iter = b.options_iterator();
+    loop {
+        match (a_iter.next(), b_iter.next()) {
+            (None, None) => return a_iter.rest() == b_iter.rest(),
+            (Some(Ok(Timestamp(_, _))), Some(Ok(Timestamp(_, _)))) => {},
+            (Some(Ok(a)), Some(Ok(b))) if a == b => {},
+            //unknown options have to be identical
+            _ => return a.options() == b.options()
+        }
+    }
+}
@@ -0,0 +1,511 @@
+//! Aggregation of packets into flows.
+//!
+//! A [`FlowKey`](struct.FlowKey.html) identifies the flow a packet belongs to (addresses, protocol, ports & optionally
+//! the link layer addresses & vlan ids). The [`FlowTable`](struct.FlowTable.html) counts the packets, bytes & tcp flags
+//! of every flow and emits a [`FlowRecord`](struct.FlowRecord.html) when a flow ends (timeouts, TCP FIN/RST or if the
+//! table is full), similar to the flow cache of a NetFlow exporter.
+//!
+//! # Example
+//!
+//! ```
+//! use etherparse::*;
+//! use etherparse::flow::*;
+//! use std::time::Duration;
+//!
+//! let mut packet = Vec::new();
+//! PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+//!     .udp(1234, 53)
+//!     .write(&mut packet, &[0;10])
+//!     .unwrap();
+//!
+//! let mut table = FlowTable::new(FlowTableConfig{
+//!     idle_timeout: Duration::from_secs(15),
+//!     ..FlowTableConfig::default()
+//! });
+//! for second in 0..3 {
+//!     let ended = table.push(&SlicedPacket::from_ip(&packet).unwrap(), Duration::from_secs(second)).unwrap();
+//!     assert!(ended.is_empty());
+//! }
+//!
+//! //the flow ends after 15 seconds without packets
+//! let records = table.expire(Duration::from_secs(17));
+//! assert_eq!(1, records.len());
+//! assert_eq!(53, records[0].key.destination_port);
+//! assert_eq!(3, records[0].forward.packets);
+//! assert_eq!(3*38, records[0].forward.bytes);
+//! assert_eq!(Some(FlowEndReason::IdleTimeout), records[0].end_reason);
+//! ```
+use super::*;
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::Duration;
+
+///Errors that can occur when passing a packet to a [`FlowTable`](struct.FlowTable.html).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FlowError {
+    ///The packet contains no ip header.
+    MissingIpHeader,
+}
+
+impl fmt::Display for FlowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use FlowError::*;
+        match self {
+            MissingIpHeader => {
+                write!(f, "FlowError: The packet contains no ip header.")
+            },
+        }
+    }
+}
+
+impl Error for FlowError {
+
+}
+
+///Fields identifying a flow.
+///
+///The ports are set to 0 for protocols without ports & for ip fragments that don't contain the transport header.
+///For ICMP & ICMPv6 packets the destination port contains the type & code (`type << 8 | code`) like in NetFlow.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct FlowKey {
+    pub source_mac: Option<[u8;6]>,
+    pub destination_mac: Option<[u8;6]>,
+    ///Vlan identifier of the single or of the outer vlan header.
+    pub outer_vlan: Option<u16>,
+    ///Vlan identifier of the inner vlan header (only set for double vlan tagged packets).
+    pub inner_vlan: Option<u16>,
+    pub source: IpAddr,
+    pub destination: IpAddr,
+    ///Ip protocol number of the transport layer (see IpTrafficClass for a definition of the ids).
+    pub protocol: u8,
+    pub source_port: u16,
+    pub destination_port: u16,
+}
+
+impl FlowKey {
+    ///Extracts the key of a sliced packet (oriented from the sender to the receiver).
+    ///
+    ///None is returned if the packet contains no ip header.
+    pub fn from_sliced(packet: &SlicedPacket) -> Option<FlowKey> {
+        use crate::InternetSlice::*;
+
+        let (source, destination) = match &packet.ip {
+            Some(Ipv4(header)) => (IpAddr::from(header.source_addr()), IpAddr::from(header.destination_addr())),
+            Some(Ipv6(header, _)) => (IpAddr::from(header.source_addr()), IpAddr::from(header.destination_addr())),
+            None => return None
+        };
+        let protocol = packet.transport_ip_protocol()?;
+        let (source_port, destination_port) = match &packet.transport {
+            Some(TransportSlice::Udp(udp)) => (udp.source_port(), udp.destination_port()),
+            Some(TransportSlice::Tcp(tcp)) => (tcp.source_port(), tcp.destination_port()),
+            None => match packet.payload_ip_protocol() {
+                Some(value) => (0, icmp_port(value, packet.payload)),
+                None => (0, 0)
+            }
+        };
+        let (source_mac, destination_mac) = match &packet.link {
+            Some(LinkSlice::Ethernet2(ethernet)) => (Some(mac(ethernet.source())), Some(mac(ethernet.destination()))),
+            _ => (None, None)
+        };
+        let (outer_vlan, inner_vlan) = match &packet.vlan {
+            Some(VlanSlice::SingleVlan(vlan)) => (Some(vlan.vlan_identifier()), None),
+            Some(VlanSlice::DoubleVlan(vlan)) => (Some(vlan.outer().vlan_identifier()), Some(vlan.inner().vlan_identifier())),
+            None => (None, None)
+        };
+        Some(FlowKey {
+            source_mac,
+            destination_mac,
+            outer_vlan,
+            inner_vlan,
+            source,
+            destination,
+            protocol,
+            source_port,
+            destination_port,
+        })
+    }
+
+    ///Extracts the key of decoded packet headers (oriented from the sender to the receiver).
+    ///
+    ///None is returned if the packet contains no ip header. Note that the ipv6 extension headers are not contained
+    ///in `PacketHeaders`. If no transport header was decoded, the protocol of an ipv6 packet is the "next header"
+    ///field of the ipv6 header.
+    pub fn from_headers(headers: &PacketHeaders) -> Option<FlowKey> {
+        use crate::IpTrafficClass::*;
+
+        let (source, destination, ip_protocol, is_fragment) = match &headers.ip {
+            Some(IpHeader::Version4(header)) => (
+                IpAddr::from(header.source),
+                IpAddr::from(header.destination),
+                header.protocol,
+                header.more_fragments || 0 != header.fragments_offset
+            ),
+            Some(IpHeader::Version6(header)) => (
+                IpAddr::from(header.source),
+                IpAddr::from(header.destination),
+                header.next_header,
+                false
+            ),
+            None => return None
+        };
+        let (protocol, source_port, destination_port) = match &headers.transport {
+            Some(TransportHeader::Udp(udp)) => (Udp as u8, udp.source_port, udp.destination_port),
+            Some(TransportHeader::Tcp(tcp)) => (Tcp as u8, tcp.source_port, tcp.destination_port),
+            None => {
+                let destination_port = if is_fragment {
+                    0
+                } else {
+                    icmp_port(ip_protocol, headers.payload)
+                };
+                (ip_protocol, 0, destination_port)
+            }
+        };
+        let (outer_vlan, inner_vlan) = match &headers.vlan {
+            Some(VlanHeader::Single(vlan)) => (Some(vlan.vlan_identifier), None),
+            Some(VlanHeader::Double(vlan)) => (Some(vlan.outer.vlan_identifier), Some(vlan.inner.vlan_identifier)),
+            None => (None, None)
+        };
+        Some(FlowKey {
+            source_mac: headers.link.as_ref().map(|value| value.source),
+            destination_mac: headers.link.as_ref().map(|value| value.destination),
+            outer_vlan,
+            inner_vlan,
+            source,
+            destination,
+            protocol,
+            source_port,
+            destination_port,
+        })
+    }
+
+    ///Returns the key of the opposite direction (source & destination swapped).
+    ///
+    ///Note that the ICMP type & code stored in the destination port is moved to the source port.
+    pub fn reversed(&self) -> FlowKey {
+        FlowKey {
+            source_mac: self.destination_mac,
+            destination_mac: self.source_mac,
+            outer_vlan: self.outer_vlan,
+            inner_vlan: self.inner_vlan,
+            source: self.destination,
+            destination: self.source,
+            protocol: self.protocol,
+            source_port: self.destination_port,
+            destination_port: self.source_port,
+        }
+    }
+
+    ///Returns the same key for both directions of a flow (the smaller one of the key & the reversed key).
+    pub fn canonical(&self) -> FlowKey {
+        std::cmp::min(*self, self.reversed())
+    }
+
+    ///Returns the key without the mac addresses.
+    pub fn without_link(&self) -> FlowKey {
+        FlowKey {
+            source_mac: None,
+            destination_mac: None,
+            ..*self
+        }
+    }
+
+    ///Returns the key without the vlan identifiers.
+    pub fn without_vlan(&self) -> FlowKey {
+        FlowKey {
+            outer_vlan: None,
+            inner_vlan: None,
+            ..*self
+        }
+    }
+}
+
+///Converts a 6 byte slice into a mac address.
+fn mac(slice: &[u8]) -> [u8;6] {
+    let mut result = [0;6];
+    result.copy_from_slice(slice);
+    result
+}
+
+///Returns the ICMP type & code as port (`type << 8 | code`) or 0 if the payload is no ICMP message.
+fn icmp_port(protocol: u8, payload: &[u8]) -> u16 {
+    use crate::IpTrafficClass::*;
+    let is_icmp = [Icmp as u8, IPv6Icmp as u8].contains(&protocol);
+    if is_icmp && payload.len() >= 2 {
+        u16::from_be_bytes([payload[0], payload[1]])
+    } else {
+        0
+    }
+}
+
+///Reason why a flow record was emitted (the values are the IPFIX "flowEndReason" codes).
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum FlowEndReason {
+    ///No packet was received for the idle timeout.
+    IdleTimeout = 1,
+    ///The flow was active for longer then the active timeout.
+    ActiveTimeout = 2,
+    ///The end of the flow was detected (TCP FIN or RST).
+    EndOfFlow = 3,
+    ///The flow was ended via [`FlowTable::flush`](struct.FlowTable.html#method.flush).
+    ForcedEnd = 4,
+    ///The flow was evicted as the maximum number of flows was reached.
+    LackOfResources = 5,
+}
+
+///Counters of one direction of a flow.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FlowCounters {
+    pub packets: u64,
+    ///Number of bytes on the ip layer (ip headers & payload).
+    pub bytes: u64,
+    ///Bitwise or of the tcp flags of all packets (same bit layout as the byte 13 of the tcp header).
+    pub tcp_flags: u8,
+}
+
+///Statistics of a flow.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FlowRecord {
+    ///Key oriented in the direction of the first packet of the flow.
+    pub key: FlowKey,
+    ///Timestamp of the first packet.
+    pub first_seen: Duration,
+    ///Timestamp of the last packet.
+    pub last_seen: Duration,
+    ///Counters of the packets sent in the direction of the key.
+    pub forward: FlowCounters,
+    ///Counters of the packets sent in the opposite direction (only used by bidirectional flow tables).
+    pub reverse: FlowCounters,
+    ///Reason why the record was emitted (None while the flow is still active).
+    pub end_reason: Option<FlowEndReason>,
+}
+
+///TCP flag bits in the flow counters.
+const TCP_FIN: u8 = 0x01;
+const TCP_RST: u8 = 0x04;
+
+///Configuration of a [`FlowTable`](struct.FlowTable.html).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FlowTableConfig {
+    ///Duration without packets after which a flow ends.
+    pub idle_timeout: Duration,
+    ///Duration after which a long lived flow is ended (following packets start a new flow).
+    pub active_timeout: Duration,
+    ///If true both directions are counted in the same record (in `forward` & `reverse`).
+    pub bidirectional: bool,
+    ///If true the mac addresses are part of the flow keys.
+    pub include_link: bool,
+    ///If true the vlan identifiers are part of the flow keys.
+    pub include_vlan: bool,
+    ///Maximum number of flows (if reached the least recently active flow is ended).
+    pub max_flows: usize,
+}
+
+impl Default for FlowTableConfig {
+    fn default() -> FlowTableConfig {
+        FlowTableConfig {
+            idle_timeout: Duration::from_secs(15),
+            active_timeout: Duration::from_secs(30*60),
+            bidirectional: false,
+            include_link: false,
+            include_vlan: false,
+            max_flows: 64*1024,
+        }
+    }
+}
+
+///Table aggregating packets into flows.
+///
+///The timestamps passed to the table can have any origin (e.g. capture timestamps) but have to be from the same clock.
+#[derive(Clone, Debug)]
+pub struct FlowTable {
+    config: FlowTableConfig,
+    ///Flows by their lookup key (canonical for bidirectional tables).
+    flows: HashMap<FlowKey, FlowRecord>,
+}
+
+impl FlowTable {
+    pub fn new(config: FlowTableConfig) -> FlowTable {
+        FlowTable {
+            config,
+            flows: HashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &FlowTableConfig {
+        &self.config
+    }
+
+    ///Number of currently active flows.
+    pub fn flow_count(&self) -> usize {
+        self.flows.len()
+    }
+
+    ///Iterates over the records of the currently active flows.
+    pub fn flows(&self) -> impl Iterator<Item = &FlowRecord> {
+        self.flows.values()
+    }
+
+    ///Returns the record of the active flow containing the given key (both directions are matched in bidirectional tables).
+    pub fn get(&self, key: &FlowKey) -> Option<&FlowRecord> {
+        self.flows.get(&self.lookup_key(&self.table_key(key)))
+    }
+
+    ///Accounts a sliced packet & returns the records of the flows that ended because of it.
+    pub fn push(&mut self, packet: &SlicedPacket, timestamp: Duration) -> Result<Vec<FlowRecord>, FlowError> {
+        use crate::InternetSlice::*;
+
+        let key = FlowKey::from_sliced(packet).ok_or(FlowError::MissingIpHeader)?;
+        let bytes = match &packet.ip {
+            Some(Ipv4(header)) => u64::from(header.total_len()),
+            Some(Ipv6(header, _)) => Ipv6Header::SERIALIZED_SIZE as u64 + u64::from(header.payload_length()),
+            None => 0
+        };
+        let tcp_flags = match &packet.transport {
+            Some(TransportSlice::Tcp(tcp)) => tcp.slice()[13],
+            _ => 0
+        };
+        Ok(self.account(key, bytes, tcp_flags, timestamp))
+    }
+
+    ///Accounts decoded packet headers & returns the records of the flows that ended because of it.
+    pub fn push_headers(&mut self, headers: &PacketHeaders, timestamp: Duration) -> Result<Vec<FlowRecord>, FlowError> {
+        let key = FlowKey::from_headers(headers).ok_or(FlowError::MissingIpHeader)?;
+        let bytes = match &headers.ip {
+            Some(IpHeader::Version4(header)) => header.header_len() as u64 + u64::from(header.payload_len),
+            Some(IpHeader::Version6(header)) => Ipv6Header::SERIALIZED_SIZE as u64 + u64::from(header.payload_length),
+            None => 0
+        };
+        let tcp_flags = match &headers.transport {
+            Some(TransportHeader::Tcp(tcp)) => {
+                [tcp.fin, tcp.syn, tcp.rst, tcp.psh, tcp.ack, tcp.urg, tcp.ece, tcp.cwr]
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, value)| **value)
+                    .fold(0, |flags, (bit, _)| flags | (1 << bit))
+            },
+            _ => 0
+        };
+        Ok(self.account(key, bytes, tcp_flags, timestamp))
+    }
+
+    ///Ends all flows that reached the idle or active timeout at the given time & returns their records.
+    pub fn expire(&mut self, now: Duration) -> Vec<FlowRecord> {
+        let expired: Vec<(FlowKey, FlowEndReason)> = self.flows
+            .iter()
+            .filter_map(|(key, record)| self.timeout(record, now).map(|reason| (*key, reason)))
+            .collect();
+        let mut result: Vec<FlowRecord> = expired
+            .into_iter()
+            .filter_map(|(key, reason)| self.end(&key, reason))
+            .collect();
+        sort_records(&mut result);
+        result
+    }
+
+    ///Ends all flows & returns their records.
+    pub fn flush(&mut self) -> Vec<FlowRecord> {
+        let mut result: Vec<FlowRecord> = self.flows
+            .drain()
+            .map(|(_, mut record)| {
+                record.end_reason = Some(FlowEndReason::ForcedEnd);
+                record
+            })
+            .collect();
+        sort_records(&mut result);
+        result
+    }
+
+    ///Removes the fields not configured to be part of the keys.
+    fn table_key(&self, key: &FlowKey) -> FlowKey {
+        let key = if self.config.include_link { *key } else { key.without_link() };
+        if self.config.include_vlan { key } else { key.without_vlan() }
+    }
+
+    ///Returns the key under which the flow of a table key is stored.
+    fn lookup_key(&self, key: &FlowKey) -> FlowKey {
+        if self.config.bidirectional {
+            key.canonical()
+        } else {
+            *key
+        }
+    }
+
+    ///Returns the reason why the given flow has to be ended at the given time (if any).
+    fn timeout(&self, record: &FlowRecord, now: Duration) -> Option<FlowEndReason> {
+        if now.saturating_sub(record.last_seen) >= self.config.idle_timeout {
+            Some(FlowEndReason::IdleTimeout)
+        } else if now.saturating_sub(record.first_seen) >= self.config.active_timeout {
+            Some(FlowEndReason::ActiveTimeout)
+        } else {
+            None
+        }
+    }
+
+    ///Removes a flow & returns its record with the given end reason.
+    fn end(&mut self, lookup_key: &FlowKey, reason: FlowEndReason) -> Option<FlowRecord> {
+        self.flows.remove(lookup_key).map(|mut record| {
+            record.end_reason = Some(reason);
+            record
+        })
+    }
+
+    ///Adds a packet to its flow & returns the records of the flows that ended.
+    fn account(&mut self, key: FlowKey, bytes: u64, tcp_flags: u8, timestamp: Duration) -> Vec<FlowRecord> {
+        let mut result = Vec::new();
+        let key = self.table_key(&key);
+        let lookup_key = self.lookup_key(&key);
+
+        //end the previous flow if it timed out
+        let timeout = self.flows.get(&lookup_key).and_then(|record| self.timeout(record, timestamp));
+        if let Some(reason) = timeout {
+            result.extend(self.end(&lookup_key, reason));
+        }
+
+        //make room for a new flow
+        let is_new = !self.flows.contains_key(&lookup_key);
+        if is_new && self.flows.len() >= self.config.max_flows {
+            let oldest = self.flows
+                .iter()
+                .min_by_key(|(_, record)| record.last_seen)
+                .map(|(key, _)| *key);
+            if let Some(oldest) = oldest {
+                result.extend(self.end(&oldest, FlowEndReason::LackOfResources));
+            }
+        }
+
+        let bidirectional = self.config.bidirectional;
+        let record = self.flows.entry(lookup_key).or_insert_with(|| FlowRecord {
+            key,
+            first_seen: timestamp,
+            last_seen: timestamp,
+            forward: FlowCounters::default(),
+            reverse: FlowCounters::default(),
+            end_reason: None,
+        });
+        record.last_seen = std::cmp::max(record.last_seen, timestamp);
+        let counters = if record.key == key {
+            &mut record.forward
+        } else {
+            &mut record.reverse
+        };
+        counters.packets += 1;
+        counters.bytes += bytes;
+        counters.tcp_flags |= tcp_flags;
+
+        //end tcp flows after a reset or after all directions sent a FIN
+        let reset = 0 != (record.forward.tcp_flags | record.reverse.tcp_flags) & TCP_RST;
+        let forward_fin = 0 != record.forward.tcp_flags & TCP_FIN;
+        let reverse_fin = 0 != record.reverse.tcp_flags & TCP_FIN;
+        let finished = forward_fin && (reverse_fin || !bidirectional);
+        if reset || finished {
+            result.extend(self.end(&lookup_key, FlowEndReason::EndOfFlow));
+        }
+        result
+    }
+}
+
+///Sorts records by their first timestamp (& key) so the output does not depend on the hash map order.
+fn sort_records(records: &mut [FlowRecord]) {
+    records.sort_by_key(|record| (record.first_seen, record.key));
+}
@@ -0,0 +1,194 @@
+//! Hashes of packets to distribute them over worker threads (e.g. consistently with NIC receive side scaling).
+//!
+//! The hashes are calculated with [`SlicedPacket::rss_hash`](../struct.SlicedPacket.html#method.rss_hash) (Toeplitz
+//! hash with the keys & fields defined in this module) & [`SlicedPacket::five_tuple_hash`](../struct.SlicedPacket.html#method.five_tuple_hash).
+
+use super::*;
+
+///Key used by Microsoft to verify RSS implementations (also the default key of many NICs).
+pub const RSS_DEFAULT_KEY: [u8;40] = [
+    0x6d, 0x5a, 0x56, 0xda, 0x25, 0x5b, 0x0e, 0xc2,
+    0x41, 0x67, 0x25, 0x3d, 0x43, 0xa3, 0x8f, 0xb0,
+    0xd0, 0xca, 0x2b, 0xcb, 0xae, 0x7b, 0x30, 0xb4,
+    0x77, 0xcb, 0x2d, 0xa3, 0x80, 0x30, 0xf2, 0x0c,
+    0x6a, 0x42, 0xb7, 0x3b, 0xbe, 0xac, 0x01, 0xfa,
+];
+
+///Key resulting in the same Toeplitz hash for both directions of a flow (repeated 16 bit pattern 0x6d5a).
+///
+///As every 16 bit window of the key is identical, swapping the source & destination addresses and ports does not
+///change the hash value (see "Scalable TCP Session Monitoring with Symmetric Receive-side Scaling" by Woo & Park).
+pub const RSS_SYMMETRIC_KEY: [u8;40] = [
+    0x6d, 0x5a, 0x6d, 0x5a, 0x6d, 0x5a, 0x6d, 0x5a,
+    0x6d, 0x5a, 0x6d, 0x5a, 0x6d, 0x5a, 0x6d, 0x5a,
+    0x6d, 0x5a, 0x6d, 0x5a, 0x6d, 0x5a, 0x6d, 0x5a,
+    0x6d, 0x5a, 0x6d, 0x5a, 0x6d, 0x5a, 0x6d, 0x5a,
+    0x6d, 0x5a, 0x6d, 0x5a, 0x6d, 0x5a, 0x6d, 0x5a,
+];
+
+///Fields of a packet included in the RSS hash.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RssFields {
+    ///Source & destination ip address.
+    Ip,
+    ///Source & destination ip address and (if present) the TCP or UDP source & destination port.
+    ///
+    ///Packets without TCP or UDP header & ip fragments are hashed using only the addresses (like NICs do).
+    IpPorts,
+}
+
+///Calculates the Toeplitz hash of the input with the given key.
+///
+///The key has to be at least 4 bytes longer then the input (missing key bits are treated as zeros).
+pub fn toeplitz_hash(key: &[u8], input: &[u8]) -> u32 {
+    let mut hasher = Toeplitz::new(key);
+    hasher.update(input);
+    hasher.result
+}
+
+///Incremental Toeplitz hash calculation (allows hashing multiple slices without copying them).
+struct Toeplitz<'a> {
+    key: &'a [u8],
+    ///32 bit window of the key starting at the current input bit.
+    window: u32,
+    ///Index of the key bit following the window.
+    next_bit: usize,
+    result: u32,
+}
+
+impl<'a> Toeplitz<'a> {
+    fn new(key: &'a [u8]) -> Toeplitz<'a> {
+        let mut result = Toeplitz {
+            key,
+            window: 0,
+            next_bit: 0,
+            result: 0,
+        };
+        for _ in 0..32 {
+            result.window = (result.window << 1) | result.next_key_bit();
+        }
+        result
+    }
+
+    fn next_key_bit(&mut self) -> u32 {
+        let index = self.next_bit;
+        self.next_bit += 1;
+        match self.key.get(index / 8) {
+            Some(byte) => u32::from((byte >> (7 - index % 8)) & 1),
+            None => 0
+        }
+    }
+
+    fn update(&mut self, input: &[u8]) {
+        for byte in input {
+            for bit in 0..8 {
+                if 0 != byte & (0x80 >> bit) {
+                    self.result ^= self.window;
+                }
+                self.window = (self.window << 1) | self.next_key_bit();
+            }
+        }
+    }
+}
+
+///FNV-1a 64 bit offset basis & prime.
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+fn fnv1a(state: u64, input: &[u8]) -> u64 {
+    input.iter().fold(state, |state, byte| (state ^ u64::from(*byte)).wrapping_mul(FNV_PRIME))
+}
+
+impl<'a> SlicedPacket<'a> {
+    ///Calculates the RSS (receive side scaling) Toeplitz hash of the packet with the given key.
+    ///
+    ///The input of the hash is the source address, destination address, source port & destination port (in network
+    ///byte order) as defined in the Microsoft RSS specification. Use [`RSS_SYMMETRIC_KEY`](flow_hash/constant.RSS_SYMMETRIC_KEY.html)
+    ///to get the same hash value for both directions of a flow. None is returned if the packet contains no ip header.
+    ///
+    ///```
+    ///# use etherparse::*;
+    ///use etherparse::flow_hash::*;
+    ///
+    ///let mut packet = Vec::new();
+    ///PacketBuilder::ipv4([66,9,149,187], [161,142,100,80], 20)
+    ///    .tcp(2794, 1766, 0, 1024)
+    ///    .write(&mut packet, &[])
+    ///    .unwrap();
+    ///
+    ///let sliced = SlicedPacket::from_ip(&packet).unwrap();
+    ///assert_eq!(Some(0x51cc_c178), sliced.rss_hash(&RSS_DEFAULT_KEY, RssFields::IpPorts));
+    ///assert_eq!(Some(0x323e_8fc2), sliced.rss_hash(&RSS_DEFAULT_KEY, RssFields::Ip));
+    ///```
+    pub fn rss_hash(&self, key: &[u8], fields: RssFields) -> Option<u32> {
+        let (source, destination) = self.ip_addresses()?;
+        let mut hasher = Toeplitz::new(key);
+        hasher.update(source);
+        hasher.update(destination);
+        if RssFields::IpPorts == fields {
+            if let Some(ports) = self.port_bytes() {
+                hasher.update(ports);
+            }
+        }
+        Some(hasher.result)
+    }
+
+    ///Calculates a fast non-cryptographic hash (FNV-1a) of the 5-tuple (addresses, ip protocol & ports) of the packet.
+    ///
+    ///The seed allows different hash values on different devices (e.g. to avoid polarization in ECMP setups). Ports
+    ///are only included for TCP & UDP packets that are not ip fragments. None is returned if the packet contains no ip
+    ///header.
+    pub fn five_tuple_hash(&self, seed: u64) -> Option<u64> {
+        let (source, destination) = self.ip_addresses()?;
+        let ports = self.port_bytes().unwrap_or(&[0;4]);
+        Some(self.hash_tuple(seed, (source, &ports[..2]), (destination, &ports[2..])))
+    }
+
+    ///Calculates the same hash as [`five_tuple_hash`](#method.five_tuple_hash) for both directions of a flow.
+    ///
+    ///The (address, port) pairs of the source & destination are ordered before they are hashed, so the result is
+    ///different from the result of `five_tuple_hash`.
+    pub fn symmetric_five_tuple_hash(&self, seed: u64) -> Option<u64> {
+        let (source, destination) = self.ip_addresses()?;
+        let ports = self.port_bytes().unwrap_or(&[0;4]);
+        let source = (source, &ports[..2]);
+        let destination = (destination, &ports[2..]);
+        Some(if source <= destination {
+            self.hash_tuple(seed, source, destination)
+        } else {
+            self.hash_tuple(seed, destination, source)
+        })
+    }
+
+    ///Hashes the given endpoints (address & port bytes) together with the ip protocol.
+    fn hash_tuple(&self, seed: u64, first: (&[u8], &[u8]), second: (&[u8], &[u8])) -> u64 {
+        let protocol = self.transport_ip_protocol().unwrap_or(0);
+        let mut state = FNV_OFFSET_BASIS ^ seed;
+        for value in &[first.0, second.0, &[protocol][..], first.1, second.1] {
+            state = fnv1a(state, value);
+        }
+        state
+    }
+
+    ///Returns the source & destination address of the ip header.
+    fn ip_addresses(&self) -> Option<(&'a [u8], &'a [u8])> {
+        use crate::InternetSlice::*;
+        match &self.ip {
+            Some(Ipv4(header)) => Some((header.source(), header.destination())),
+            Some(Ipv6(header, _)) => Some((header.source(), header.destination())),
+            None => None
+        }
+    }
+
+    ///Returns the source & destination port bytes of a TCP or UDP header (None for fragments).
+    fn port_bytes(&self) -> Option<&'a [u8]> {
+        if self.is_ip_fragment() {
+            return None;
+        }
+        match &self.transport {
+            Some(TransportSlice::Udp(udp)) => Some(&udp.slice()[..4]),
+            Some(TransportSlice::Tcp(tcp)) => Some(&tcp.slice()[..4]),
+            None => None
+        }
+    }
+}
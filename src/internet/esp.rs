@@ -0,0 +1,105 @@
+use super::super::*;
+
+extern crate byteorder;
+use self::byteorder::{ByteOrder, BigEndian, ReadBytesExt, WriteBytesExt};
+
+///IP Encapsulating Security Payload (ESP) header according to rfc4303.
+///
+///Only the unencrypted start of the ESP packet (security parameters index & sequence number) is represented.
+///The rest of the packet (payload, padding, next header & integrity check value) is encrypted and
+///not decoded by etherparse.
+#[derive(Clone, Debug, Eq, PartialEq, Default)]
+pub struct EspHeader {
+    ///Security parameters index (identifies the security association).
+    pub spi: u32,
+    ///Monotonically increasing sequence number used for anti-replay protection.
+    pub sequence_number: u32
+}
+
+impl SerializedSize for EspHeader {
+    ///Size of the header in bytes.
+    const SERIALIZED_SIZE: usize = 8;
+}
+
+impl EspHeader {
+
+    ///Creates an ESP header with the given security parameters index & sequence number.
+    pub fn new(spi: u32, sequence_number: u32) -> EspHeader {
+        EspHeader {
+            spi,
+            sequence_number
+        }
+    }
+
+    ///Reads an ESP header from a slice and returns a tuple containing the resulting header & unused part of the slice.
+    pub fn read_from_slice(slice: &[u8]) -> Result<(EspHeader, &[u8]), ReadError> {
+        Ok((
+            EspHeaderSlice::from_slice(slice)?.to_header(),
+            &slice[EspHeader::SERIALIZED_SIZE..]
+        ))
+    }
+
+    ///Reads an ESP header from the current position.
+    pub fn read<T: io::Read + io::Seek + Sized>(reader: &mut T) -> Result<EspHeader, io::Error> {
+        let spi = reader.read_u32::<BigEndian>()?;
+        let sequence_number = reader.read_u32::<BigEndian>()?;
+        Ok(EspHeader{
+            spi,
+            sequence_number
+        })
+    }
+
+    ///Writes the ESP header to the current position.
+    pub fn write<T: io::Write + Sized>(&self, writer: &mut T) -> Result<(), WriteError> {
+        writer.write_u32::<BigEndian>(self.spi)?;
+        writer.write_u32::<BigEndian>(self.sequence_number)?;
+        Ok(())
+    }
+}
+
+///A slice containing an ESP header of a network package.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EspHeaderSlice<'a> {
+    slice: &'a [u8]
+}
+
+impl<'a> EspHeaderSlice<'a> {
+
+    ///Creates a slice containing an ESP header.
+    pub fn from_slice(slice: &'a[u8]) -> Result<EspHeaderSlice<'a>, ReadError> {
+        //check length
+        use crate::ReadError::*;
+        if slice.len() < EspHeader::SERIALIZED_SIZE {
+            return Err(UnexpectedEndOfSlice(EspHeader::SERIALIZED_SIZE));
+        }
+
+        //done
+        Ok(EspHeaderSlice{
+            slice: &slice[..EspHeader::SERIALIZED_SIZE]
+        })
+    }
+
+    ///Returns the slice containing the ESP header
+    #[inline]
+    pub fn slice(&self) -> &'a [u8] {
+        self.slice
+    }
+
+    ///Read the "security parameters index" from the slice.
+    pub fn spi(&self) -> u32 {
+        BigEndian::read_u32(&self.slice[..4])
+    }
+
+    ///Read the "sequence number" from the slice.
+    pub fn sequence_number(&self) -> u32 {
+        BigEndian::read_u32(&self.slice[4..8])
+    }
+
+    ///Decode all the fields and copy the results to an EspHeader struct
+    pub fn to_header(&self) -> EspHeader {
+        EspHeader {
+            spi: self.spi(),
+            sequence_number: self.sequence_number()
+        }
+    }
+}
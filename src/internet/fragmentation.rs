@@ -0,0 +1,289 @@
+use super::super::*;
+
+extern crate byteorder;
+use self::byteorder::{ByteOrder, BigEndian};
+
+///Errors that can occur when splitting an ip packet into fragments.
+#[derive(Debug)]
+pub enum FragmentationError {
+    ///Error while decoding the packet that should be fragmented.
+    Read(ReadError),
+    ///Error while encoding the headers of the fragments.
+    Write(WriteError),
+    ///Error while decoding the ipv4 options (required to determine the options copied into all fragments).
+    Ipv4Option(Ipv4OptionReadError),
+    ///The ipv4 packet is larger then the mtu, but the don't fragment flag is set.
+    DontFragment,
+    ///The mtu is too small to contain the headers & at least 8 bytes of data per fragment.
+    MtuTooSmall(usize),
+    ///The ipv6 packet already contains a fragmentation header.
+    AlreadyFragmented,
+    ///The ip version of the identification passed to `write_fragmented` does not match the version of the
+    ///outermost ip header.
+    IdentificationMismatch(FragmentIdentification),
+}
+
+impl fmt::Display for FragmentationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use FragmentationError::*;
+        match self {
+            Read(err) => {
+                write!(f, "FragmentationError: Error while decoding the packet to fragment: {}", err)
+            },
+            Write(err) => {
+                write!(f, "FragmentationError: Error while encoding the fragments: {}", err)
+            },
+            Ipv4Option(err) => {
+                write!(f, "FragmentationError: Error while decoding the ipv4 options of the packet to fragment: {:?}", err)
+            },
+            DontFragment => {
+                write!(f, "FragmentationError: The ipv4 packet is larger then the mtu, but the don't fragment flag is set.")
+            },
+            MtuTooSmall(mtu) => {
+                write!(f, "FragmentationError: The mtu of {} bytes is too small to contain the headers & at least 8 bytes of data per fragment.", mtu)
+            },
+            AlreadyFragmented => {
+                write!(f, "FragmentationError: The ipv6 packet already contains a fragmentation header.")
+            },
+            IdentificationMismatch(identification) => {
+                write!(f, "FragmentationError: The identification {:?} does not match the ip version of the outermost ip header.", identification)
+            },
+        }
+    }
+}
+
+impl Error for FragmentationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use FragmentationError::*;
+        match self {
+            Read(ref err) => Some(err),
+            Write(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl From<ReadError> for FragmentationError {
+    fn from(err: ReadError) -> FragmentationError {
+        FragmentationError::Read(err)
+    }
+}
+
+impl From<WriteError> for FragmentationError {
+    fn from(err: WriteError) -> FragmentationError {
+        FragmentationError::Write(err)
+    }
+}
+
+///Identification set in the fragments written by `write_fragmented`.
+///
+///The version has to match the outermost ip header of the packet.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FragmentIdentification {
+    ///Identification field of the ipv4 header.
+    Ipv4(u16),
+    ///Identification field of the inserted ipv6 fragmentation headers.
+    Ipv6(u32),
+}
+
+///Size of the ipv6 fragmentation header.
+const IPV6_FRAG_HEADER_LEN: usize = 8;
+
+///Splits an ipv4 packet (starting with the ipv4 header) into fragments with a size of at most `mtu` bytes (rfc791).
+///
+///The first fragment contains all options of the original header, all following fragments only the options
+///with the copied flag set (e.g. the security & source route options). The fragment offsets are relative to the
+///offset of the given packet & the last fragment keeps its more fragments flag, so already fragmented packets
+///can be fragmented further.
+///
+///If the packet is not larger then the mtu it is returned unchanged. Data past the total length of the ipv4 header is
+///ignored.
+///
+/// # Example
+///
+/// ```
+/// # use etherparse::*;
+/// let mut header = Ipv4Header::new(0, 20, IpTrafficClass::Udp, [192,168,1,1], [192,168,1,2]);
+/// header.dont_fragment = false;
+/// header.identification = 1234;
+/// let mut packet = Vec::new();
+/// PacketBuilder::ip(IpHeader::Version4(header))
+///     .udp(1234, 5678)
+///     .write(&mut packet, &[0;2000])
+///     .unwrap();
+///
+/// let fragments = fragment_ipv4_packet(&packet, 1500).unwrap();
+/// assert_eq!(2, fragments.len());
+/// assert_eq!(1500, fragments[0].len());
+///
+/// let first = Ipv4HeaderSlice::from_slice(&fragments[0]).unwrap();
+/// assert!(first.more_fragments());
+/// let second = Ipv4HeaderSlice::from_slice(&fragments[1]).unwrap();
+/// assert_eq!(1480/8, second.fragments_offset());
+/// assert!(!second.more_fragments());
+/// ```
+pub fn fragment_ipv4_packet(packet: &[u8], mtu: usize) -> Result<Vec<Vec<u8>>, FragmentationError> {
+    use crate::FragmentationError::*;
+
+    let (header, rest) = Ipv4Header::read_from_slice(packet)?;
+    let payload_len = usize::from(header.payload_len);
+    if rest.len() < payload_len {
+        return Err(ReadError::UnexpectedEndOfSlice(header.header_len() + payload_len).into());
+    }
+    let payload = &rest[..payload_len];
+
+    if header.header_len() + payload.len() <= mtu {
+        return Ok(vec![packet[..header.header_len() + payload.len()].to_vec()]);
+    }
+    if header.dont_fragment {
+        return Err(DontFragment);
+    }
+
+    //header of the following fragments (only containing the copied options)
+    let following_header = {
+        let mut options = Vec::new();
+        for option in header.options_iterator() {
+            let option = option.map_err(Ipv4Option)?;
+            match option {
+                Ipv4OptionElement::EndOfOptionsList => break,
+                //copied flag
+                _ if 0 != option.option_type() & 0x80 => options.push(option),
+                _ => {}
+            }
+        }
+        let mut result = header.clone();
+        result.set_option_elements(&options).map_err(WriteError::from)?;
+        result
+    };
+
+    let mut result = Vec::new();
+    let mut offset = 0;
+    while offset < payload.len() {
+        let mut fragment_header = if 0 == offset {
+            header.clone()
+        } else {
+            following_header.clone()
+        };
+        if mtu < fragment_header.header_len() + 8 {
+            return Err(MtuTooSmall(mtu));
+        }
+        //the data length has to be a multiple of 8 (except for the last fragment)
+        let data_len = std::cmp::min((mtu - fragment_header.header_len()) / 8 * 8, payload.len() - offset);
+        let last = offset + data_len == payload.len();
+
+        fragment_header.set_payload_len(data_len).map_err(WriteError::from)?;
+        fragment_header.more_fragments = !last || header.more_fragments;
+        fragment_header.fragments_offset = header.fragments_offset + (offset / 8) as u16;
+
+        let mut fragment = Vec::with_capacity(fragment_header.header_len() + data_len);
+        fragment_header.write(&mut fragment)?;
+        fragment.extend_from_slice(&payload[offset..offset + data_len]);
+        result.push(fragment);
+
+        offset += data_len;
+    }
+    Ok(result)
+}
+
+///Splits an ipv6 packet (starting with the ipv6 header) into fragments with a size of at most `mtu` bytes by
+///inserting fragmentation headers with the given identification (rfc8200 section 4.5).
+///
+///The ipv6 header & the extension headers that have to be processed by the routers on the way (hop by hop, routing
+///& the destination options preceding a routing header) are repeated in every fragment, all following headers &
+///the payload are split.
+///
+///If the packet is not larger then the mtu it is returned unchanged. Data past the payload length of the ipv6 header is
+///ignored.
+///
+/// # Example
+///
+/// ```
+/// # use etherparse::*;
+/// let mut packet = Vec::new();
+/// PacketBuilder::ipv6([1;16], [2;16], 20)
+///     .udp(1234, 5678)
+///     .write(&mut packet, &[0;2000])
+///     .unwrap();
+///
+/// let fragments = fragment_ipv6_packet(&packet, 1280, 0x1234_5678).unwrap();
+/// assert_eq!(2, fragments.len());
+/// assert!(fragments.iter().all(|fragment| fragment.len() <= 1280));
+///
+/// //the next header of the ipv6 header points to the fragmentation header
+/// let ip = Ipv6HeaderSlice::from_slice(&fragments[0]).unwrap();
+/// assert_eq!(IpTrafficClass::IPv6FragmentationHeader as u8, ip.next_header());
+/// ```
+pub fn fragment_ipv6_packet(packet: &[u8], mtu: usize, identification: u32) -> Result<Vec<Vec<u8>>, FragmentationError> {
+    use crate::FragmentationError::*;
+    const HOP_BY_HOP: u8 = IpTrafficClass::IPv6HeaderHopByHop as u8;
+    const ROUTE: u8 = IpTrafficClass::IPv6RouteHeader as u8;
+    const DEST_OPTIONS: u8 = IpTrafficClass::IPv6DestinationOptions as u8;
+    const FRAG: u8 = IpTrafficClass::IPv6FragmentationHeader as u8;
+
+    let (header, rest) = Ipv6Header::read_from_slice(packet)?;
+    let packet_len = Ipv6Header::SERIALIZED_SIZE + usize::from(header.payload_length);
+    if rest.len() + Ipv6Header::SERIALIZED_SIZE < packet_len {
+        return Err(ReadError::UnexpectedEndOfSlice(packet_len).into());
+    }
+    let packet = &packet[..packet_len];
+    if packet.len() <= mtu {
+        return Ok(vec![packet.to_vec()]);
+    }
+
+    //determine the end of the unfragmentable part & the position of the next header field pointing to the
+    //first fragmentable header (every header is at least 8 bytes long, so the loop terminates)
+    let mut unfragmentable_len = Ipv6Header::SERIALIZED_SIZE;
+    let mut next_header_position = 6;
+    let mut next_header = header.next_header;
+    let mut position = Ipv6Header::SERIALIZED_SIZE;
+    loop {
+        match next_header {
+            HOP_BY_HOP | ROUTE | DEST_OPTIONS => {
+                let (next, rest) = Ipv6Header::skip_header_extension_in_slice(&packet[position..], next_header)?;
+                let header_position = position;
+                position = packet.len() - rest.len();
+                if DEST_OPTIONS != next_header {
+                    unfragmentable_len = position;
+                    next_header_position = header_position;
+                }
+                next_header = next;
+            },
+            FRAG => return Err(AlreadyFragmented),
+            _ => break
+        }
+    }
+    let first_fragmentable_header = packet[next_header_position];
+    let fragmentable = &packet[unfragmentable_len..];
+
+    if mtu < unfragmentable_len + IPV6_FRAG_HEADER_LEN + 8 {
+        return Err(MtuTooSmall(mtu));
+    }
+    //the data length has to be a multiple of 8 (except for the last fragment)
+    let max_data_len = (mtu - unfragmentable_len - IPV6_FRAG_HEADER_LEN) / 8 * 8;
+
+    let mut result = Vec::new();
+    let mut offset = 0;
+    while offset < fragmentable.len() {
+        let data_len = std::cmp::min(max_data_len, fragmentable.len() - offset);
+        let last = offset + data_len == fragmentable.len();
+
+        let mut fragment = Vec::with_capacity(unfragmentable_len + IPV6_FRAG_HEADER_LEN + data_len);
+        fragment.extend_from_slice(&packet[..unfragmentable_len]);
+        fragment[next_header_position] = FRAG;
+        BigEndian::write_u16(
+            &mut fragment[4..6],
+            (unfragmentable_len - Ipv6Header::SERIALIZED_SIZE + IPV6_FRAG_HEADER_LEN + data_len) as u16
+        );
+
+        let mut fragment_header = [0;IPV6_FRAG_HEADER_LEN];
+        fragment_header[0] = first_fragmentable_header;
+        BigEndian::write_u16(&mut fragment_header[2..4], ((offset / 8) as u16) << 3 | if last { 0 } else { 1 });
+        BigEndian::write_u32(&mut fragment_header[4..8], identification);
+        fragment.extend_from_slice(&fragment_header);
+        fragment.extend_from_slice(&fragmentable[offset..offset + data_len]);
+        result.push(fragment);
+
+        offset += data_len;
+    }
+    Ok(result)
+}
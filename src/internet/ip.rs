@@ -40,6 +40,15 @@ impl IpHeader {
             version => Err(ReadError::IpUnsupportedVersion(version))
         }
     }
+    ///Returns the size of the ip header in bytes (including ipv4 options).
+    pub fn header_len(&self) -> usize {
+        use crate::IpHeader::*;
+        match *self {
+            Version4(ref value) => value.header_len(),
+            Version6(_) => Ipv6Header::SERIALIZED_SIZE
+        }
+    }
+
     ///Writes an IP (v4 or v6) header to the current position
     pub fn write<T: io::Write + Sized>(&self, writer: &mut T) -> Result<(), WriteError> {
         use crate::IpHeader::*;
@@ -0,0 +1,249 @@
+use super::super::*;
+
+use std::fmt::{Debug, Formatter};
+
+extern crate byteorder;
+use self::byteorder::{ByteOrder, BigEndian, ReadBytesExt, WriteBytesExt};
+
+///Maximum length of the integrity check value of an authentication header in bytes
+///(8 bit payload length field in multiples of 4 bytes minus the fixed part of the header).
+pub const IP_AUTHENTICATION_HEADER_MAX_ICV_LEN: usize = 0xfe*4;
+
+///IP Authentication Header (AH) according to rfc4302.
+#[derive(Clone)]
+pub struct AuthenticationHeader {
+    ///Type of the next header (see IpTrafficClass for a definition of all ids).
+    pub next_header: u8,
+    ///Security parameters index (identifies the security association).
+    pub spi: u32,
+    ///Monotonically increasing sequence number used for anti-replay protection.
+    pub sequence_number: u32,
+    ///Length of the integrity check value in the raw_icv_buffer in bytes.
+    raw_icv_len: u16,
+    raw_icv_buffer: [u8;IP_AUTHENTICATION_HEADER_MAX_ICV_LEN]
+}
+
+impl SerializedSize for AuthenticationHeader {
+    ///Size of the header itself (without the integrity check value) in bytes.
+    const SERIALIZED_SIZE: usize = 12;
+}
+
+impl AuthenticationHeader {
+
+    ///Creates an authentication header with the given field values.
+    ///
+    ///The length of the integrity check value must be a multiple of 4 bytes and not bigger then
+    ///`IP_AUTHENTICATION_HEADER_MAX_ICV_LEN`, otherwise an error is returned.
+    pub fn new(next_header: u8, spi: u32, sequence_number: u32, raw_icv: &[u8]) -> Result<AuthenticationHeader, ValueError> {
+        let mut result = AuthenticationHeader {
+            next_header,
+            spi,
+            sequence_number,
+            raw_icv_len: 0,
+            raw_icv_buffer: [0;IP_AUTHENTICATION_HEADER_MAX_ICV_LEN]
+        };
+        result.set_raw_icv(raw_icv)?;
+        Ok(result)
+    }
+
+    ///Returns a slice containing the integrity check value.
+    pub fn raw_icv(&self) -> &[u8] {
+        &self.raw_icv_buffer[..usize::from(self.raw_icv_len)]
+    }
+
+    ///Sets the integrity check value (already encoded as bytes).
+    ///
+    ///The length of the given slice must be a multiple of 4 and maximum `IP_AUTHENTICATION_HEADER_MAX_ICV_LEN` bytes.
+    ///If the length is not fullfilling these constraints, no data is set and an error is returned.
+    ///
+    ///Note that for IPv6 the total header length has to be a multiple of 8 bytes, which requires
+    ///the integrity check value length to be 4 bytes bigger then a multiple of 8.
+    pub fn set_raw_icv(&mut self, raw_icv: &[u8]) -> Result<(), ValueError> {
+        //check that the icv length is within bounds
+        if (IP_AUTHENTICATION_HEADER_MAX_ICV_LEN < raw_icv.len()) ||
+           (0 != raw_icv.len() & 0b11)
+        {
+            Err(ValueError::IpAuthenticationHeaderBadIcvLength(raw_icv.len()))
+        } else {
+            self.raw_icv_buffer[..raw_icv.len()].copy_from_slice(raw_icv);
+            self.raw_icv_len = raw_icv.len() as u16;
+            Ok(())
+        }
+    }
+
+    ///Length of the header (includes the integrity check value) in bytes.
+    pub fn header_len(&self) -> usize {
+        AuthenticationHeader::SERIALIZED_SIZE + usize::from(self.raw_icv_len)
+    }
+
+    ///Reads an authentication header from a slice and returns a tuple containing the resulting header & unused part of the slice.
+    pub fn read_from_slice(slice: &[u8]) -> Result<(AuthenticationHeader, &[u8]), ReadError> {
+        let header = AuthenticationHeaderSlice::from_slice(slice)?.to_header();
+        let rest = &slice[header.header_len()..];
+        Ok((
+            header,
+            rest
+        ))
+    }
+
+    ///Reads an authentication header from the current position.
+    pub fn read<T: io::Read + io::Seek + Sized>(reader: &mut T) -> Result<AuthenticationHeader, ReadError> {
+        let next_header = reader.read_u8()?;
+        let payload_len = reader.read_u8()?;
+
+        //payload len must be at least 1 (fixed part of the header)
+        if payload_len < 1 {
+            return Err(ReadError::IpAuthenticationHeaderTooSmall(payload_len));
+        }
+
+        //skip the reserved bytes
+        let mut reserved: [u8;2] = [0;2];
+        reader.read_exact(&mut reserved)?;
+
+        let spi = reader.read_u32::<BigEndian>()?;
+        let sequence_number = reader.read_u32::<BigEndian>()?;
+
+        let raw_icv_len = (usize::from(payload_len) - 1)*4;
+        Ok(AuthenticationHeader {
+            next_header,
+            spi,
+            sequence_number,
+            raw_icv_len: raw_icv_len as u16,
+            raw_icv_buffer: {
+                let mut values: [u8;IP_AUTHENTICATION_HEADER_MAX_ICV_LEN] = [0;IP_AUTHENTICATION_HEADER_MAX_ICV_LEN];
+                reader.read_exact(&mut values[..raw_icv_len])?;
+                values
+            }
+        })
+    }
+
+    ///Writes the authentication header (including the integrity check value) to the current position (reserved fields are set to zero).
+    pub fn write<T: io::Write + Sized>(&self, writer: &mut T) -> Result<(), WriteError> {
+        writer.write_u8(self.next_header)?;
+        //payload length in 4 byte units minus 2
+        writer.write_u8((self.header_len()/4 - 2) as u8)?;
+        writer.write_all(&[0;2])?;
+        writer.write_u32::<BigEndian>(self.spi)?;
+        writer.write_u32::<BigEndian>(self.sequence_number)?;
+        writer.write_all(self.raw_icv())?;
+        Ok(())
+    }
+}
+
+//NOTE: Same as for the Ipv4Header, Default, Debug & PartialEq have to be implemented by hand
+//      as the traits are not availible for the icv buffer array.
+
+impl Default for AuthenticationHeader {
+    fn default() -> AuthenticationHeader {
+        AuthenticationHeader {
+            next_header: 0,
+            spi: 0,
+            sequence_number: 0,
+            raw_icv_len: 0,
+            raw_icv_buffer: [0;IP_AUTHENTICATION_HEADER_MAX_ICV_LEN]
+        }
+    }
+}
+
+impl Debug for AuthenticationHeader {
+    fn fmt(&self, fotmatter: &mut Formatter) -> Result<(), std::fmt::Error> {
+        write!(fotmatter, "AuthenticationHeader {{ next_header: {}, spi: {}, sequence_number: {}, raw_icv: {:?} }}",
+            self.next_header,
+            self.spi,
+            self.sequence_number,
+            self.raw_icv())
+    }
+}
+
+impl std::cmp::PartialEq for AuthenticationHeader {
+    fn eq(&self, other: &AuthenticationHeader) -> bool {
+        self.next_header == other.next_header &&
+        self.spi == other.spi &&
+        self.sequence_number == other.sequence_number &&
+        self.raw_icv() == other.raw_icv()
+    }
+}
+
+impl std::cmp::Eq for AuthenticationHeader {}
+
+///A slice containing an authentication header (including the integrity check value) of a network package.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AuthenticationHeaderSlice<'a> {
+    slice: &'a [u8]
+}
+
+impl<'a> AuthenticationHeaderSlice<'a> {
+
+    ///Creates a slice containing an authentication header (including the integrity check value).
+    pub fn from_slice(slice: &'a[u8]) -> Result<AuthenticationHeaderSlice<'a>, ReadError> {
+        //check length
+        use crate::ReadError::*;
+        if slice.len() < AuthenticationHeader::SERIALIZED_SIZE {
+            return Err(UnexpectedEndOfSlice(AuthenticationHeader::SERIALIZED_SIZE));
+        }
+
+        //payload len must be at least 1 (fixed part of the header)
+        let payload_len = slice[1];
+        if payload_len < 1 {
+            return Err(IpAuthenticationHeaderTooSmall(payload_len));
+        }
+
+        //check that the icv is also contained in the slice
+        let header_len = (usize::from(payload_len) + 2)*4;
+        if slice.len() < header_len {
+            return Err(UnexpectedEndOfSlice(header_len));
+        }
+
+        //done
+        Ok(AuthenticationHeaderSlice{
+            slice: &slice[..header_len]
+        })
+    }
+
+    ///Returns the slice containing the authentication header
+    #[inline]
+    pub fn slice(&self) -> &'a [u8] {
+        self.slice
+    }
+
+    ///Returns the id of the next header (see IpTrafficClass for a definition of all ids).
+    pub fn next_header(&self) -> u8 {
+        self.slice[0]
+    }
+
+    ///Read the "payload length" field from the slice (length of the header in 4 byte units minus 2).
+    pub fn payload_len(&self) -> u8 {
+        self.slice[1]
+    }
+
+    ///Read the "security parameters index" from the slice.
+    pub fn spi(&self) -> u32 {
+        BigEndian::read_u32(&self.slice[4..8])
+    }
+
+    ///Read the "sequence number" from the slice.
+    pub fn sequence_number(&self) -> u32 {
+        BigEndian::read_u32(&self.slice[8..12])
+    }
+
+    ///Returns a slice containing the integrity check value.
+    pub fn raw_icv(&self) -> &'a [u8] {
+        &self.slice[AuthenticationHeader::SERIALIZED_SIZE..]
+    }
+
+    ///Decode all the fields and copy the results to an AuthenticationHeader struct
+    pub fn to_header(&self) -> AuthenticationHeader {
+        let raw_icv = self.raw_icv();
+        AuthenticationHeader {
+            next_header: self.next_header(),
+            spi: self.spi(),
+            sequence_number: self.sequence_number(),
+            raw_icv_len: raw_icv.len() as u16,
+            raw_icv_buffer: {
+                let mut result: [u8;IP_AUTHENTICATION_HEADER_MAX_ICV_LEN] = [0;IP_AUTHENTICATION_HEADER_MAX_ICV_LEN];
+                result[..raw_icv.len()].copy_from_slice(raw_icv);
+                result
+            }
+        }
+    }
+}
@@ -119,6 +119,44 @@ impl Ipv4Header {
         }
     }
 
+    ///Sets the options to the given option elements & pads the options with zeros
+    ///(end of options list) to a multiple of 4 bytes.
+    ///
+    ///If the options do not fit into the maximum of 40 bytes or an option contains
+    ///invalid data, no data is set and an error is returned.
+    pub fn set_option_elements(&mut self, options: &[Ipv4OptionElement]) -> Result<(), ValueError> {
+        use crate::ValueError::*;
+
+        //check the elements & the resulting length
+        for option in options {
+            option.check_ranges()?;
+        }
+        let required_length = options.iter().fold(0, |acc, x| acc + x.serialized_len());
+        if IPV4_MAX_OPTIONS_LENGTH < required_length {
+            return Err(Ipv4OptionsLengthBad(required_length));
+        }
+
+        //reset the options to zero to ensure padding
+        self.options_buffer = [0;40];
+
+        //write the options
+        let mut offset = 0;
+        for option in options {
+            option.write_to_slice(&mut self.options_buffer[offset..]);
+            offset += option.serialized_len();
+        }
+
+        //set the header length (padded to multiple of 4)
+        let padding = (4 - offset % 4) % 4;
+        self.options_len = (offset + padding) as u8;
+        Ok(())
+    }
+
+    ///Returns an iterator that allows to iterate through all options of the header.
+    pub fn options_iterator(&self) -> Ipv4OptionsIterator<'_> {
+        Ipv4OptionsIterator::from_slice(self.options())
+    }
+
     ///Read an Ipv4Header from a slice and return the header & unused parts of the slice.
     pub fn read_from_slice(slice: &[u8]) -> Result<(Ipv4Header, &[u8]), ReadError> {
         let header = Ipv4HeaderSlice::from_slice(slice)?.to_header();
@@ -300,14 +338,14 @@ impl Ipv4Header {
 
     ///Calculate the header checksum under the assumtion that all value ranges in the header are correct
     fn calc_header_checksum_unchecked(&self) -> u16 {
-        //version & header_length
-        let mut sum: u32 = [
-            BigEndian::read_u16(&[ (4 << 4) | self.ihl(),
-                                (self.differentiated_services_code_point << 2) | self.explicit_congestion_notification ]),
-            self.total_len(),
-            self.identification,
+        checksum::Sum16BitWords::new()
+            //version & header_length
+            .add_2bytes([ (4 << 4) | self.ihl(),
+                          (self.differentiated_services_code_point << 2) | self.explicit_congestion_notification ])
+            .add_u16(self.total_len())
+            .add_u16(self.identification)
             //flags & fragmentation offset
-            {
+            .add_2bytes({
                 let mut buf: [u8;2] = [0;2];
                 BigEndian::write_u16(&mut buf, self.fragments_offset);
                 let flags = {
@@ -320,22 +358,14 @@ impl Ipv4Header {
                     }
                     result
                 };
-                BigEndian::read_u16(&[flags | (buf[0] & 0x1f), buf[1]])
-            },
-            BigEndian::read_u16(&[self.time_to_live, self.protocol]),
+                [flags | (buf[0] & 0x1f), buf[1]]
+            })
+            .add_2bytes([self.time_to_live, self.protocol])
             //skip checksum (for obvious reasons)
-            BigEndian::read_u16(&self.source[0..2]),
-            BigEndian::read_u16(&self.source[2..4]),
-            BigEndian::read_u16(&self.destination[0..2]),
-            BigEndian::read_u16(&self.destination[2..4])
-        ].iter().map(|x| u32::from(*x)).sum();
-        let options = self.options();
-        for i in 0..(options.len()/2) {
-            sum += u32::from( BigEndian::read_u16(&options[i*2..i*2 + 2]) );
-        }
-
-        let carry_add = (sum & 0xffff) + (sum >> 16);
-        !( ((carry_add & 0xffff) + (carry_add >> 16)) as u16 )
+            .add_4bytes(self.source)
+            .add_4bytes(self.destination)
+            .add_slice(self.options())
+            .ones_complement()
     }
 }
 
@@ -561,6 +591,11 @@ impl<'a> Ipv4HeaderSlice<'a> {
         &self.slice[20..]
     }
 
+    ///Returns an iterator that allows to iterate through all options of the header.
+    pub fn options_iterator(&self) -> Ipv4OptionsIterator<'a> {
+        Ipv4OptionsIterator::from_slice(self.options())
+    }
+
     ///Decode all the fields and copy the results to a Ipv4Header struct
     pub fn to_header(&self) -> Ipv4Header {
         let options = self.options();
@@ -594,3 +629,400 @@ impl<'a> Ipv4HeaderSlice<'a> {
         }
     }
 }
+
+///A mutable slice containing an ipv4 header of a network package. Allows the in place modification of the header fields.
+///
+///All setters (except `set_header_checksum`) incrementally update the header checksum (rfc1624), so a valid checksum
+///stays valid after a field was modified.
+#[derive(Debug, Eq, PartialEq)]
+pub struct Ipv4HeaderSliceMut<'a> {
+    slice: &'a mut [u8]
+}
+
+impl<'a> Ipv4HeaderSliceMut<'a> {
+
+    ///Creates a mutable slice containing an ipv4 header (including header options).
+    pub fn from_slice(slice: &'a mut [u8]) -> Result<Ipv4HeaderSliceMut<'a>, ReadError> {
+        //check the header
+        let len = Ipv4HeaderSlice::from_slice(slice)?.slice().len();
+
+        //all good
+        Ok(Ipv4HeaderSliceMut {
+            slice: &mut slice[..len]
+        })
+    }
+
+    ///Returns the slice containing the ipv4 header
+    #[inline]
+    pub fn slice(&self) -> &[u8] {
+        self.slice
+    }
+
+    ///Returns a read only slice of the header (allows the reading of the header fields).
+    pub fn as_slice(&self) -> Ipv4HeaderSlice<'_> {
+        Ipv4HeaderSlice {
+            slice: self.slice
+        }
+    }
+
+    ///Sets the "differentiated_services_code_point" field (6 bits).
+    pub fn set_dcp(&mut self, value: u8) -> Result<(), ValueError> {
+        max_check_u8(value, 0x3f, ErrorField::Ipv4Dscp)?;
+        self.set_word(0, [self.slice[0], (value << 2) | (self.slice[1] & 0x3)]);
+        Ok(())
+    }
+
+    ///Sets the "explicit_congestion_notification" field (2 bits).
+    pub fn set_ecn(&mut self, value: u8) -> Result<(), ValueError> {
+        max_check_u8(value, 0x3, ErrorField::Ipv4Ecn)?;
+        self.set_word(0, [self.slice[0], (self.slice[1] & 0xfc) | value]);
+        Ok(())
+    }
+
+    ///Sets the "identification" field.
+    pub fn set_identification(&mut self, value: u16) {
+        self.set_word(4, value.to_be_bytes());
+    }
+
+    ///Sets the "dont fragment" flag.
+    pub fn set_dont_fragment(&mut self, value: bool) {
+        let flags = if value {
+            self.slice[6] | 0x40
+        } else {
+            self.slice[6] & !0x40
+        };
+        self.set_word(6, [flags, self.slice[7]]);
+    }
+
+    ///Sets the "more fragments" flag.
+    pub fn set_more_fragments(&mut self, value: bool) {
+        let flags = if value {
+            self.slice[6] | 0x20
+        } else {
+            self.slice[6] & !0x20
+        };
+        self.set_word(6, [flags, self.slice[7]]);
+    }
+
+    ///Sets the "fragment_offset" field (13 bits, in multiples of 8 bytes).
+    pub fn set_fragments_offset(&mut self, value: u16) -> Result<(), ValueError> {
+        max_check_u16(value, 0x1fff, ErrorField::Ipv4FragmentsOffset)?;
+        let bytes = value.to_be_bytes();
+        self.set_word(6, [(self.slice[6] & 0xe0) | bytes[0], bytes[1]]);
+        Ok(())
+    }
+
+    ///Sets the "time_to_live" field.
+    pub fn set_ttl(&mut self, value: u8) {
+        self.set_word(8, [value, self.slice[9]]);
+    }
+
+    ///Sets the "protocol" field.
+    pub fn set_protocol(&mut self, value: u8) {
+        self.set_word(8, [self.slice[8], value]);
+    }
+
+    ///Sets the "header checksum" field (the checksum is NOT recalculated).
+    pub fn set_header_checksum(&mut self, value: u16) {
+        BigEndian::write_u16(&mut self.slice[10..12], value);
+    }
+
+    ///Sets the ipv4 source address.
+    ///
+    ///Note that the address is also part of the tcp & udp checksum (pseudo header). Use
+    ///`TcpHeaderSliceMut::update_checksum_ipv4_address` or `UdpHeaderSliceMut::update_checksum_ipv4_address`
+    ///to keep the transport checksum consistent.
+    pub fn set_source(&mut self, value: [u8;4]) {
+        self.set_address(12, value);
+    }
+
+    ///Sets the ipv4 destination address.
+    ///
+    ///Note that the address is also part of the tcp & udp checksum (pseudo header). Use
+    ///`TcpHeaderSliceMut::update_checksum_ipv4_address` or `UdpHeaderSliceMut::update_checksum_ipv4_address`
+    ///to keep the transport checksum consistent.
+    pub fn set_destination(&mut self, value: [u8;4]) {
+        self.set_address(16, value);
+    }
+
+    ///Returns the header checksum.
+    fn header_checksum(&self) -> u16 {
+        BigEndian::read_u16(&self.slice[10..12])
+    }
+
+    ///Writes the 16 bit word at the given offset & updates the header checksum.
+    fn set_word(&mut self, offset: usize, value: [u8;2]) {
+        let old = BigEndian::read_u16(&self.slice[offset..offset + 2]);
+        let new = u16::from_be_bytes(value);
+        let checksum = checksum::update_u16(self.header_checksum(), old, new);
+        self.slice[offset..offset + 2].copy_from_slice(&value);
+        self.set_header_checksum(checksum);
+    }
+
+    ///Writes the address at the given offset & updates the header checksum.
+    fn set_address(&mut self, offset: usize, value: [u8;4]) {
+        let mut old: [u8;4] = [0;4];
+        old.copy_from_slice(&self.slice[offset..offset + 4]);
+        let checksum = checksum::update_ipv4_address(self.header_checksum(), old, value);
+        self.slice[offset..offset + 4].copy_from_slice(&value);
+        self.set_header_checksum(checksum);
+    }
+}
+
+///Different kinds of options that can be present in the options part of an ipv4 header.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Ipv4OptionElement<'a> {
+    ///End of the options list (the rest of the options is padding).
+    EndOfOptionsList,
+    Nop,
+    ///Record route option (the route data contains ipv4 addresses of 4 bytes each).
+    RecordRoute{ pointer: u8, route: &'a [u8] },
+    ///Internet timestamp option. Depending on the flags the data contains timestamps
+    ///or ipv4 address & timestamp pairs (4 bytes per address & timestamp).
+    Timestamp{ pointer: u8, overflow: u8, flags: u8, data: &'a [u8] },
+    ///Loose source and record route option (the route data contains ipv4 addresses of 4 bytes each).
+    LooseSourceRoute{ pointer: u8, route: &'a [u8] },
+    ///Strict source and record route option (the route data contains ipv4 addresses of 4 bytes each).
+    StrictSourceRoute{ pointer: u8, route: &'a [u8] },
+    ///Router alert option (rfc2113) containing the 16 bit value (0 means "router shall examine packet").
+    RouterAlert(u16),
+    ///Basic security option (rfc1108).
+    Security{ classification_level: u8, protection_authority: &'a [u8] },
+    ///Option with an unknown type (the data contains the option content after the length field).
+    Unknown{ option_type: u8, data: &'a [u8] },
+}
+
+pub const IPV4_OPTION_ID_END: u8 = 0;
+pub const IPV4_OPTION_ID_NOP: u8 = 1;
+pub const IPV4_OPTION_ID_RECORD_ROUTE: u8 = 7;
+pub const IPV4_OPTION_ID_TIMESTAMP: u8 = 68;
+pub const IPV4_OPTION_ID_SECURITY: u8 = 130;
+pub const IPV4_OPTION_ID_LOOSE_SOURCE_ROUTE: u8 = 131;
+pub const IPV4_OPTION_ID_STRICT_SOURCE_ROUTE: u8 = 137;
+pub const IPV4_OPTION_ID_ROUTER_ALERT: u8 = 148;
+
+impl<'a> Ipv4OptionElement<'a> {
+    ///Returns the option type identifying the option.
+    pub fn option_type(&self) -> u8 {
+        use crate::Ipv4OptionElement::*;
+        match self {
+            EndOfOptionsList => IPV4_OPTION_ID_END,
+            Nop => IPV4_OPTION_ID_NOP,
+            RecordRoute{..} => IPV4_OPTION_ID_RECORD_ROUTE,
+            Timestamp{..} => IPV4_OPTION_ID_TIMESTAMP,
+            LooseSourceRoute{..} => IPV4_OPTION_ID_LOOSE_SOURCE_ROUTE,
+            StrictSourceRoute{..} => IPV4_OPTION_ID_STRICT_SOURCE_ROUTE,
+            RouterAlert(_) => IPV4_OPTION_ID_ROUTER_ALERT,
+            Security{..} => IPV4_OPTION_ID_SECURITY,
+            Unknown{ option_type, .. } => *option_type,
+        }
+    }
+
+    ///Returns the serialized length of the option in bytes.
+    pub fn serialized_len(&self) -> usize {
+        use crate::Ipv4OptionElement::*;
+        match self {
+            EndOfOptionsList | Nop => 1,
+            RecordRoute{ route, .. } |
+            LooseSourceRoute{ route, .. } |
+            StrictSourceRoute{ route, .. } => 3 + route.len(),
+            Timestamp{ data, .. } => 4 + data.len(),
+            RouterAlert(_) => 4,
+            Security{ protection_authority, .. } => 3 + protection_authority.len(),
+            Unknown{ data, .. } => 2 + data.len(),
+        }
+    }
+
+    ///Checks that the option can be serialized (route & timestamp data must be a multiple of 4 bytes
+    ///& the timestamp overflow & flags must fit into 4 bits).
+    fn check_ranges(&self) -> Result<(), ValueError> {
+        use crate::Ipv4OptionElement::*;
+        use crate::ErrorField::*;
+        match self {
+            RecordRoute{ route, .. } |
+            LooseSourceRoute{ route, .. } |
+            StrictSourceRoute{ route, .. } if 0 != route.len() % 4 => {
+                Err(ValueError::Ipv4OptionDataLengthBad{
+                    option_id: self.option_type(),
+                    data_len: route.len()
+                })
+            },
+            Timestamp{ overflow, flags, data, .. } => {
+                max_check_u8(*overflow, 0xf, Ipv4TimestampOverflow)?;
+                max_check_u8(*flags, 0xf, Ipv4TimestampFlags)?;
+                if 0 != data.len() % 4 {
+                    Err(ValueError::Ipv4OptionDataLengthBad{
+                        option_id: IPV4_OPTION_ID_TIMESTAMP,
+                        data_len: data.len()
+                    })
+                } else {
+                    Ok(())
+                }
+            },
+            _ => Ok(())
+        }
+    }
+
+    ///Writes the option to the given slice (the slice must have at least the length returned by serialized_len()).
+    fn write_to_slice(&self, target: &mut [u8]) {
+        use crate::Ipv4OptionElement::*;
+        target[0] = self.option_type();
+        if 1 == self.serialized_len() {
+            return;
+        }
+        target[1] = self.serialized_len() as u8;
+        match self {
+            EndOfOptionsList | Nop => {},
+            RecordRoute{ pointer, route } |
+            LooseSourceRoute{ pointer, route } |
+            StrictSourceRoute{ pointer, route } => {
+                target[2] = *pointer;
+                target[3..3 + route.len()].copy_from_slice(route);
+            },
+            Timestamp{ pointer, overflow, flags, data } => {
+                target[2] = *pointer;
+                target[3] = (overflow << 4) | flags;
+                target[4..4 + data.len()].copy_from_slice(data);
+            },
+            RouterAlert(value) => {
+                BigEndian::write_u16(&mut target[2..4], *value);
+            },
+            Security{ classification_level, protection_authority } => {
+                target[2] = *classification_level;
+                target[3..3 + protection_authority.len()].copy_from_slice(protection_authority);
+            },
+            Unknown{ data, .. } => {
+                target[2..2 + data.len()].copy_from_slice(data);
+            },
+        }
+    }
+}
+
+///Errors that can occour while reading the options of an ipv4 header.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Ipv4OptionReadError {
+    ///Returned if an option id was read, but there was not enough memory in the options left to completely read it.
+    UnexpectedEndOfSlice(u8),
+
+    ///Returned if the option has an unexpected size argument (e.g. != 4 for the router alert option).
+    UnexpectedSize{option_id: u8, size: u8 },
+}
+
+///Allows iterating over the options of an ipv4 header.
+pub struct Ipv4OptionsIterator<'a> {
+    options: &'a [u8]
+}
+
+impl<'a> Ipv4OptionsIterator<'a> {
+    ///Creates an options iterator from a slice containing encoded ipv4 options.
+    pub fn from_slice(options: &'a [u8]) -> Ipv4OptionsIterator<'a> {
+        Ipv4OptionsIterator{ options }
+    }
+
+    ///Returns the non processed part of the options slice.
+    pub fn rest(&self) -> &'a [u8] {
+        self.options
+    }
+}
+
+impl<'a> Iterator for Ipv4OptionsIterator<'a> {
+    type Item = Result<Ipv4OptionElement<'a>, Ipv4OptionReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+
+        use crate::Ipv4OptionReadError::*;
+        use crate::Ipv4OptionElement::*;
+
+        if self.options.is_empty() {
+            return None;
+        }
+
+        let options = self.options;
+        let id = options[0];
+
+        //first determine the result
+        let result = match id {
+            IPV4_OPTION_ID_END => Ok(EndOfOptionsList),
+            IPV4_OPTION_ID_NOP => Ok(Nop),
+            _ => {
+                //all other options contain a length field
+                if options.len() < 2 {
+                    Err(UnexpectedEndOfSlice(id))
+                } else {
+                    let len = usize::from(options[1]);
+                    let unexpected_size = Err(UnexpectedSize{
+                        option_id: id,
+                        size: options[1]
+                    });
+                    let route_len_ok = len >= 3 && 0 == (len - 3) % 4;
+                    match id {
+                        _ if len < 2 => unexpected_size,
+                        _ if options.len() < len => Err(UnexpectedEndOfSlice(id)),
+                        IPV4_OPTION_ID_RECORD_ROUTE if route_len_ok => Ok(RecordRoute{
+                            pointer: options[2],
+                            route: &options[3..len]
+                        }),
+                        IPV4_OPTION_ID_LOOSE_SOURCE_ROUTE if route_len_ok => Ok(LooseSourceRoute{
+                            pointer: options[2],
+                            route: &options[3..len]
+                        }),
+                        IPV4_OPTION_ID_STRICT_SOURCE_ROUTE if route_len_ok => Ok(StrictSourceRoute{
+                            pointer: options[2],
+                            route: &options[3..len]
+                        }),
+                        IPV4_OPTION_ID_RECORD_ROUTE |
+                        IPV4_OPTION_ID_LOOSE_SOURCE_ROUTE |
+                        IPV4_OPTION_ID_STRICT_SOURCE_ROUTE => unexpected_size,
+                        IPV4_OPTION_ID_TIMESTAMP => {
+                            if len >= 4 && 0 == len % 4 {
+                                Ok(Timestamp{
+                                    pointer: options[2],
+                                    overflow: options[3] >> 4,
+                                    flags: options[3] & 0xf,
+                                    data: &options[4..len]
+                                })
+                            } else {
+                                unexpected_size
+                            }
+                        },
+                        IPV4_OPTION_ID_ROUTER_ALERT => {
+                            if 4 == len {
+                                Ok(RouterAlert(BigEndian::read_u16(&options[2..4])))
+                            } else {
+                                unexpected_size
+                            }
+                        },
+                        IPV4_OPTION_ID_SECURITY => {
+                            if len >= 3 {
+                                Ok(Security{
+                                    classification_level: options[2],
+                                    protection_authority: &options[3..len]
+                                })
+                            } else {
+                                unexpected_size
+                            }
+                        },
+                        _ => Ok(Unknown{
+                            option_type: id,
+                            data: &options[2..len]
+                        })
+                    }
+                }
+            }
+        };
+
+        //in case the result was an error or the end move the slice to an end position
+        match result {
+            Ok(EndOfOptionsList) | Err(_) => {
+                let len = options.len();
+                self.options = &options[len..len];
+            },
+            Ok(ref element) => {
+                self.options = &options[element.serialized_len()..];
+            }
+        }
+
+        //finally return the result
+        Some(result)
+    }
+}
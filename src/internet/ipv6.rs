@@ -93,11 +93,13 @@ impl Ipv6Header {
         } else {
             let next_header = slice[0];
             const FRAG: u8 = IpTrafficClass::IPv6FragmentationHeader as u8;
-            //determine the length (fragmentation header has a fixed length & the rest a length field)
-            let len = if traffic_class == FRAG {
-                8
-            } else {
-                (usize::from(slice[1]) + 1)*8
+            const AUTH: u8 = IpTrafficClass::IPv6AuthenticationHeader as u8;
+            //determine the length (fragmentation header has a fixed length, the authentication header
+            //a length field in 4 octet units & the rest a length field in 8 octet units)
+            let len = match traffic_class {
+                FRAG => 8,
+                AUTH => (usize::from(slice[1]) + 2)*4,
+                _ => (usize::from(slice[1]) + 1)*8
             };
             if slice.len() < len {
                 Err(ReadError::UnexpectedEndOfSlice(len))
@@ -108,6 +110,9 @@ impl Ipv6Header {
     }
 
     ///Takes a slice & traffic class (identifying the first header type) and returns next_header id & the slice past after all ipv6 header extensions.
+    ///
+    ///The encapsulating security payload header ends the header extensions (its content is encrypted), in this case
+    ///its traffic class & the slice starting with the esp header is returned.
     pub fn skip_all_header_extensions_in_slice(slice: &[u8], traffic_class: u8) -> Result<(u8, &[u8]), ReadError> {
         
         let mut next_traffic_class = traffic_class;
@@ -115,7 +120,7 @@ impl Ipv6Header {
         
         for _i in 0..IPV6_MAX_NUM_HEADER_EXTENSIONS {
 
-            if IpTrafficClass::is_ipv6_ext_header_value(next_traffic_class)
+            if is_skippable_header_extension(next_traffic_class)
             {
                 let (n_id, n_rest) = Ipv6Header::skip_header_extension_in_slice(rest, next_traffic_class)?;
                 next_traffic_class = n_id;
@@ -126,7 +131,7 @@ impl Ipv6Header {
         }
 
         //final check
-        if IpTrafficClass::is_ipv6_ext_header_value(next_traffic_class) {
+        if is_skippable_header_extension(next_traffic_class) {
             Err(ReadError::Ipv6TooManyHeaderExtensions)
         } else {
             Ok((next_traffic_class, rest))
@@ -138,12 +143,14 @@ impl Ipv6Header {
         let next_header = reader.read_u8()?;
         //determine the length (fragmentation header has a fixed length & the rest a length field)
         const FRAG: u8 = IpTrafficClass::IPv6FragmentationHeader as u8;
-        let rest_length = if traffic_class == FRAG {
+        const AUTH: u8 = IpTrafficClass::IPv6AuthenticationHeader as u8;
+        let rest_length = match traffic_class {
             //fragmentation header has the fixed length of 64bits (one already read)
-            7
-        } else {
+            FRAG => 7,
+            //Length of the authentication header in 4-octet units, minus 2.
+            AUTH => ((i64::from(reader.read_u8()?) + 2)*4) - 2,
             //Length of the Hop-by-Hop Options header in 8-octet units, not including the first 8 octets.
-            ((i64::from(reader.read_u8()?) + 1)*8) - 2
+            _ => ((i64::from(reader.read_u8()?) + 1)*8) - 2
         };
         //Sadly seek does not return an error if the seek could not be fullfilled.
         //Some implementations do not even truncate the returned position to the
@@ -158,13 +165,14 @@ impl Ipv6Header {
         Ok(next_header)
     }
 
-    ///Skips all ipv6 header extensions and returns the last traffic_class
+    ///Skips all ipv6 header extensions and returns the last traffic_class (the encapsulating security payload
+    ///header ends the header extensions & is not skipped).
     pub fn skip_all_header_extensions<T: io::Read + io::Seek + Sized>(reader: &mut T, traffic_class: u8) -> Result<u8, ReadError> {
 
         let mut next_traffic_class = traffic_class;
 
         for _i in 0..IPV6_MAX_NUM_HEADER_EXTENSIONS {
-            if IpTrafficClass::is_ipv6_ext_header_value(next_traffic_class)
+            if is_skippable_header_extension(next_traffic_class)
             {
                 next_traffic_class = Ipv6Header::skip_header_extension(reader, next_traffic_class)?;
             } else {
@@ -173,7 +181,7 @@ impl Ipv6Header {
         }
 
         //final check
-        if IpTrafficClass::is_ipv6_ext_header_value(next_traffic_class) {
+        if is_skippable_header_extension(next_traffic_class) {
             Err(ReadError::Ipv6TooManyHeaderExtensions)
         } else {
             Ok(next_traffic_class)
@@ -347,9 +355,88 @@ impl<'a> Ipv6HeaderSlice<'a, > {
     }
 }
 
+///A mutable slice containing an ipv6 header of a network package. Allows the in place modification of the header fields.
+#[derive(Debug, Eq, PartialEq)]
+pub struct Ipv6HeaderSliceMut<'a> {
+    slice: &'a mut [u8]
+}
+
+impl<'a> Ipv6HeaderSliceMut<'a> {
+
+    ///Creates a mutable slice containing an ipv6 header (without header extensions).
+    pub fn from_slice(slice: &'a mut [u8]) -> Result<Ipv6HeaderSliceMut<'a>, ReadError> {
+        //check the header
+        let len = Ipv6HeaderSlice::from_slice(slice)?.slice().len();
+
+        //all good
+        Ok(Ipv6HeaderSliceMut {
+            slice: &mut slice[..len]
+        })
+    }
+
+    ///Returns the slice containing the ipv6 header
+    #[inline]
+    pub fn slice(&self) -> &[u8] {
+        self.slice
+    }
+
+    ///Returns a read only slice of the header (allows the reading of the header fields).
+    pub fn as_slice(&self) -> Ipv6HeaderSlice<'_> {
+        Ipv6HeaderSlice {
+            slice: self.slice
+        }
+    }
+
+    ///Sets the "traffic class" field.
+    pub fn set_traffic_class(&mut self, value: u8) {
+        self.slice[0] = (self.slice[0] & 0xf0) | (value >> 4);
+        self.slice[1] = (self.slice[1] & 0x0f) | (value << 4);
+    }
+
+    ///Sets the 20 bit "flow label" field.
+    pub fn set_flow_label(&mut self, value: u32) -> Result<(), ValueError> {
+        max_check_u32(value, 0xfffff, ErrorField::Ipv6FlowLabel)?;
+        let bytes = value.to_be_bytes();
+        self.slice[1] = (self.slice[1] & 0xf0) | bytes[1];
+        self.slice[2] = bytes[2];
+        self.slice[3] = bytes[3];
+        Ok(())
+    }
+
+    ///Sets the "hop limit" field.
+    pub fn set_hop_limit(&mut self, value: u8) {
+        self.slice[7] = value;
+    }
+
+    ///Sets the ipv6 source address.
+    ///
+    ///Note that the address is part of the tcp & udp checksum (pseudo header). Use
+    ///`TcpHeaderSliceMut::update_checksum_ipv6_address` or `UdpHeaderSliceMut::update_checksum_ipv6_address`
+    ///to keep the transport checksum consistent.
+    pub fn set_source(&mut self, value: [u8;16]) {
+        self.slice[8..24].copy_from_slice(&value);
+    }
+
+    ///Sets the ipv6 destination address.
+    ///
+    ///Note that the address is part of the tcp & udp checksum (pseudo header). Use
+    ///`TcpHeaderSliceMut::update_checksum_ipv6_address` or `UdpHeaderSliceMut::update_checksum_ipv6_address`
+    ///to keep the transport checksum consistent.
+    pub fn set_destination(&mut self, value: [u8;16]) {
+        self.slice[24..40].copy_from_slice(&value);
+    }
+}
+
 ///Maximum number of header extensions allowed (according to the ipv6 rfc8200, & iana protocol numbers).
 pub const IPV6_MAX_NUM_HEADER_EXTENSIONS: usize = 12;
 
+///Returns true if the given traffic class identifies an ipv6 header extension that can be skipped
+///(the encapsulating security payload header can not be skipped as its content is encrypted).
+fn is_skippable_header_extension(traffic_class: u8) -> bool {
+    traffic_class != IpTrafficClass::IPv6EncapSecurityPayload as u8 &&
+    IpTrafficClass::is_ipv6_ext_header_value(traffic_class)
+}
+
 ///Dummy struct for ipv6 header extensions.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Ipv6ExtensionHeader {
@@ -375,10 +462,12 @@ impl<'a> Ipv6ExtensionHeaderSlice<'a> {
 
         //check length
         const FRAG: u8 = IpTrafficClass::IPv6FragmentationHeader as u8;
-        let len = if FRAG == header_type {
-            8
-        } else {
-            ((slice[1] as usize) + 1)*8
+        const AUTH: u8 = IpTrafficClass::IPv6AuthenticationHeader as u8;
+        let len = match header_type {
+            FRAG => 8,
+            //the authentication header length is given in 4 octet units (rfc4302)
+            AUTH => ((slice[1] as usize) + 2)*4,
+            _ => ((slice[1] as usize) + 1)*8
         };
 
         //check the length again now that the expected length is known
@@ -5,8 +5,14 @@
 //! * IEEE 802.1Q VLAN Tagging Header
 //! * IPv4
 //! * IPv6 (missing extension headers, but supporting skipping them)
+//! * IPsec Authentication Header (AH) & Encapsulating Security Payload (ESP) header
 //! * UDP
 //! * TCP
+//! * VXLAN
+//! * Geneve
+//! * GTP-U (GTPv1 user plane)
+//! * L2TPv2 & L2TPv3 (decoding only)
+//! * PPP (decoding of the header in L2TPv2 data messages & L2TPv3 PPP pseudowires)
 //! 
 //! # Usage
 //! 
@@ -117,8 +123,17 @@
 //! * [`Ipv4HeaderSlice.from_slice`](struct.Ipv4HeaderSlice.html#method.from_slice)
 //! * [`Ipv6HeaderSlice.from_slice`](struct.Ipv6HeaderSlice.html#method.from_slice)
 //! * [`Ipv6ExtensionHeaderSlice.from_slice`](struct.Ipv6ExtensionHeaderSlice.html)
+//! * [`AuthenticationHeaderSlice.from_slice`](struct.AuthenticationHeaderSlice.html#method.from_slice)
+//! * [`EspHeaderSlice.from_slice`](struct.EspHeaderSlice.html#method.from_slice)
 //! * [`UdpHeaderSlice.from_slice`](struct.UdpHeaderSlice.html#method.from_slice)
 //! * [`TcpHeaderSlice.from_slice`](struct.TcpHeaderSlice.html#method.from_slice)
+//! * [`VxlanHeaderSlice.from_slice`](struct.VxlanHeaderSlice.html#method.from_slice)
+//! * [`GeneveHeaderSlice.from_slice`](struct.GeneveHeaderSlice.html#method.from_slice)
+//! * [`GtpuHeaderSlice.from_slice`](struct.GtpuHeaderSlice.html#method.from_slice)
+//! * [`L2tpv2HeaderSlice.from_slice`](struct.L2tpv2HeaderSlice.html#method.from_slice)
+//! * [`L2tpv3ControlHeaderSlice.from_slice`](struct.L2tpv3ControlHeaderSlice.html#method.from_slice)
+//! * [`L2tpv3SessionHeaderSlice.from_slice`](struct.L2tpv3SessionHeaderSlice.html#method.from_slice)
+//! * [`PppHeaderSlice.from_slice`](struct.PppHeaderSlice.html#method.from_slice)
 //! 
 //! And for deserialization into the corresponding header structs have a look at:
 //! 
@@ -128,8 +143,35 @@
 //! * [`IpHeader.read`](enum.IpHeader.html#method.read) & [`IpHeader.read_from_slice`](enum.IpHeader.html#method.read_from_slice)
 //! * [`Ipv4Header.read`](struct.Ipv4Header.html#method.read) & [`Ipv4Header.read_from_slice`](struct.Ipv4Header.html#method.read_from_slice)
 //! * [`Ipv6Header.read`](struct.Ipv6Header.html#method.read) & [`Ipv6Header.read_from_slice`](struct.Ipv6Header.html#method.read_from_slice)
+//! * [`AuthenticationHeader.read`](struct.AuthenticationHeader.html#method.read) & [`AuthenticationHeader.read_from_slice`](struct.AuthenticationHeader.html#method.read_from_slice)
+//! * [`EspHeader.read`](struct.EspHeader.html#method.read) & [`EspHeader.read_from_slice`](struct.EspHeader.html#method.read_from_slice)
 //! * [`UdpHeader.read`](struct.UdpHeader.html#method.read) & [`UdpHeader.read_from_slice`](struct.UdpHeader.html#method.read_from_slice)
 //! * [`TcpHeader.read`](struct.TcpHeader.html#method.read) & [`TcpHeader.read_from_slice`](struct.TcpHeader.html#method.read_from_slice)
+//! * [`VxlanHeader.read`](struct.VxlanHeader.html#method.read) & [`VxlanHeader.read_from_slice`](struct.VxlanHeader.html#method.read_from_slice)
+//! * [`GeneveHeader.read`](struct.GeneveHeader.html#method.read) & [`GeneveHeader.read_from_slice`](struct.GeneveHeader.html#method.read_from_slice)
+//! * [`GtpuHeader.read`](struct.GtpuHeader.html#method.read) & [`GtpuHeader.read_from_slice`](struct.GtpuHeader.html#method.read_from_slice)
+//! 
+//! ## Verifying checksums
+//! The ipv4 header checksum & the tcp or udp checksum of a received packet can be verified via
+//! [`SlicedPacket.verify_checksums`](struct.SlicedPacket.html#method.verify_checksums) & [`PacketHeaders.verify_checksums`](struct.PacketHeaders.html#method.verify_checksums).
+//! 
+//! The [`checksum`](checksum/index.html) module contains the underlying ones complement sum calculation, pseudo header helpers
+//! & functions to incrementally update a checksum after a field was modified (rfc1624).
+//! 
+//! ## Modifying packets in place
+//! [`SlicedPacketMut`](struct.SlicedPacketMut.html) slices a mutable packet buffer into mutable header slices
+//! (e.g. [`Ipv4HeaderSliceMut`](struct.Ipv4HeaderSliceMut.html) & [`TcpHeaderSliceMut`](struct.TcpHeaderSliceMut.html)).
+//! Their setters write directly into the buffer and incrementally update the ipv4 header checksum and the tcp & udp
+//! checksum, so fields like the ttl or the ports can be changed without re-serializing the packet.
+//! 
+//! [`SlicedPacketMut::apply_nat`](struct.SlicedPacketMut.html#method.apply_nat) rewrites the ip addresses & ports described
+//! by a [`NatRewrite`](struct.NatRewrite.html) and fixes up all affected checksums, including the packet quoted in ICMP &
+//! ICMPv6 error messages.
+//! 
+//! ## Translating between IPv4 & IPv6
+//! [`SiitTranslator`](struct.SiitTranslator.html) translates ipv4 packets to ipv6 packets & back (SIIT/NAT64, rfc7915).
+//! The ipv4 addresses are embedded in ipv6 addresses using a [`Nat64Prefix`](struct.Nat64Prefix.html) (rfc6052), the tcp & udp
+//! checksums are updated and ICMP messages are translated to ICMPv6 messages & back (including the packets quoted in error messages).
 //! 
 //! # How to generate fake packet data?
 //! ## Packet Builder
@@ -164,6 +206,45 @@
 //! 
 //! Check out the [PacketBuilder documentation](struct.PacketBuilder.html) for more informations.
 //! 
+//! Packets larger then the MTU of a link can be split into IP fragments with `write_fragmented` (IPv4 options are copied
+//! according to their copied flag & IPv6 packets get a fragmentation header). Already serialized packets can be fragmented
+//! with [`fragment_ipv4_packet`](fn.fragment_ipv4_packet.html) & [`fragment_ipv6_packet`](fn.fragment_ipv6_packet.html).
+//! 
+//! TCP & UDP packets with a payload larger then the maximum segment size can be split into multiple complete packets
+//! (software TSO/USO) with [`SlicedPacket::segment`](struct.SlicedPacket.html#method.segment) &
+//! [`PacketHeaders::segment`](struct.PacketHeaders.html#method.segment). The sequence numbers, lengths & checksums of the
+//! segments are adjusted automatically.
+//! 
+//! The inverse, merging consecutive in-order TCP segments of a flow into larger packets (like Linux GRO), is available
+//! via [`TcpCoalescer`](struct.TcpCoalescer.html).
+//! 
+//! The byte streams of TCP connections can be reassembled with the [`tcp_reassembly`](tcp_reassembly/index.html) module,
+//! which reorders segments, resolves overlaps & delivers the data of both directions of every connection to a callback.
+//! 
+//! The [`tcp_tracking`](tcp_tracking/index.html) module tracks the RFC 793 states & sequence windows of TCP connections
+//! (similar to the connection tracking of stateful firewalls) & classifies packets as new, established, related or invalid.
+//! 
+//! Packets can be aggregated into flows with the [`flow`](flow/index.html) module. The flow table counts the packets,
+//! bytes & TCP flags per flow key & emits flow records on idle/active timeouts (similar to a NetFlow exporter).
+//! 
+//! To distribute packets over worker threads consistently with NIC receive side scaling, the Toeplitz hash of a packet
+//! can be calculated with [`SlicedPacket::rss_hash`](struct.SlicedPacket.html#method.rss_hash) (with a configurable or
+//! symmetric key from the [`flow_hash`](flow_hash/index.html) module). A faster non-cryptographic 5-tuple hash is available via
+//! [`SlicedPacket::five_tuple_hash`](struct.SlicedPacket.html#method.five_tuple_hash).
+//! 
+//! NetFlow v5, NetFlow v9 & IPFIX export packets can be decoded & IPFIX messages (e.g. containing the records of a flow
+//! table) can be encoded with the [`netflow`](netflow/index.html) module. sFlow v5 datagrams can be decoded with the
+//! [`sflow`](sflow/index.html) module, which hands the sampled packet headers directly to `SlicedPacket`.
+//! 
+//! Sliced packets can be matched against tcpdump style filter expressions (e.g. `vlan 100 and ip src 10.0.0.0/8 and tcp
+//! dst port 443`) parsed via [`packet_filter::FilterExpression::parse`](packet_filter/enum.FilterExpression.html#method.parse).
+//! Expressions also support port & VLAN ranges, TCP flag masks & payload length bounds and can be combined with the
+//! field based `packet_filter::Filter`.
+//! Both filter types can also be evaluated on `PacketHeaders` via `applies_to_headers` or directly on raw data via
+//! `applies_to_ethernet` & `applies_to_ip`, which only decode the layers the filter references.
+//! Field based filters can be compiled to classic BPF programs (e.g. for `SO_ATTACH_FILTER`) via the [`bpf`](bpf/index.html)
+//! module, which also contains an interpreter to test the programs without a socket.
+//! 
 //! ## Manually serialising each header
 //! Alternativly it is possible to manually build a packet ([example](https://github.com/JulianSchmid/etherparse/blob/0.8.0/examples/write_ipv4_udp.rs)). Generally each struct representing a header has a "write" method that allows it to be serialized. These write methods sometimes automatically calculate checksums and fill them in. In case this is unwanted behavior (e.g. if you want to generate a packet with an invalid checksum), it is also possible to call a "write_raw" method that will simply serialize the data without doing checksum calculations.
 //! 
@@ -175,8 +256,13 @@
 //! * [`Ipv4Header.write`](struct.Ipv4Header.html#method.write)
 //! * [`Ipv4Header.write_raw`](struct.Ipv4Header.html#method.write_raw)
 //! * [`Ipv6Header.write`](struct.Ipv6Header.html#method.write)
+//! * [`AuthenticationHeader.write`](struct.AuthenticationHeader.html#method.write)
+//! * [`EspHeader.write`](struct.EspHeader.html#method.write)
 //! * [`UdpHeader.write`](struct.UdpHeader.html#method.write)
 //! * [`TcpHeader.write`](struct.TcpHeader.html#method.write)
+//! * [`VxlanHeader.write`](struct.VxlanHeader.html#method.write)
+//! * [`GeneveHeader.write`](struct.GeneveHeader.html#method.write)
+//! * [`GtpuHeader.write`](struct.GtpuHeader.html#method.write)
 //!
 //! # Roadmap
 //! * Documentation
@@ -192,11 +278,21 @@
 //! * [IANA Protocol Numbers](https://www.iana.org/assignments/protocol-numbers/protocol-numbers.xhtml)
 //! * [Internet Protocol Version 6 (IPv6) Parameters](https://www.iana.org/assignments/ipv6-parameters/ipv6-parameters.xhtml)
 //! * [Wikipedia IEEE_802.1Q](https://en.wikipedia.org/w/index.php?title=IEEE_802.1Q&oldid=820983900)
+//! * IP Authentication Header [RFC 4302](https://tools.ietf.org/html/rfc4302)
+//! * IP Encapsulating Security Payload (ESP) [RFC 4303](https://tools.ietf.org/html/rfc4303)
 //! * User Datagram Protocol (UDP) [RFC 768](https://tools.ietf.org/html/rfc768)
 //! * Transmission Control Protocol [RFC 793](https://tools.ietf.org/html/rfc793)
 //! * TCP Extensions for High Performance [RFC 7323](https://tools.ietf.org/html/rfc7323)
 //! * The Addition of Explicit Congestion Notification (ECN) to IP [RFC 3168](https://tools.ietf.org/html/rfc3168)
 //! * Robust Explicit Congestion Notification (ECN) Signaling with Nonces [RFC 3540](https://tools.ietf.org/html/rfc3540)
+//! * Virtual eXtensible Local Area Network (VXLAN) [RFC 7348](https://tools.ietf.org/html/rfc7348)
+//! * Geneve: Generic Network Virtualization Encapsulation [RFC 8926](https://tools.ietf.org/html/rfc8926)
+//! * GPRS Tunnelling Protocol User Plane (GTPv1-U) [3GPP TS 29.281](https://www.3gpp.org/DynaReport/29281.htm)
+//! * Layer Two Tunneling Protocol "L2TP" [RFC 2661](https://tools.ietf.org/html/rfc2661)
+//! * Layer Two Tunneling Protocol - Version 3 (L2TPv3) [RFC 3931](https://tools.ietf.org/html/rfc3931)
+//! * The Point-to-Point Protocol (PPP) [RFC 1661](https://tools.ietf.org/html/rfc1661)
+//! * Computing the Internet Checksum [RFC 1071](https://tools.ietf.org/html/rfc1071)
+//! * Computation of the Internet Checksum via Incremental Update [RFC 1624](https://tools.ietf.org/html/rfc1624)
 
 use std::io;
 use std::fmt;
@@ -205,17 +301,28 @@ use std::error::Error;
 mod link;
 pub use crate::link::ethernet::*;
 pub use crate::link::vlan_tagging::*;
+pub use crate::link::ppp::*;
 
 mod internet;
 pub use crate::internet::ip::*;
 pub use crate::internet::ipv4::*;
 pub use crate::internet::ipv6::*;
+pub use crate::internet::ip_authentication_header::*;
+pub use crate::internet::esp::*;
+pub use crate::internet::fragmentation::*;
 
 mod transport;
 pub use crate::transport::tcp::*;
 pub use crate::transport::udp::*;
 pub use crate::transport::TransportHeader;
 
+mod tunnel;
+pub use crate::tunnel::vxlan::*;
+pub use crate::tunnel::geneve::*;
+pub use crate::tunnel::gtpu::*;
+pub use crate::tunnel::l2tp::*;
+pub use crate::tunnel::TunnelHeader;
+
 mod packet_builder;
 pub use crate::packet_builder::*;
 
@@ -225,8 +332,39 @@ pub use crate::packet_decoder::*;
 mod packet_slicing;
 pub use crate::packet_slicing::*;
 
+mod packet_slicing_mut;
+pub use crate::packet_slicing_mut::*;
+
+mod nat;
+pub use crate::nat::*;
+mod siit;
+pub use crate::siit::*;
+mod segmentation;
+pub use crate::segmentation::*;
+mod coalescing;
+pub use crate::coalescing::*;
+
+mod packet_checksums;
+pub use crate::packet_checksums::*;
+
+pub mod checksum;
+
 pub mod packet_filter;
 
+pub mod tcp_reassembly;
+
+pub mod tcp_tracking;
+
+pub mod flow;
+
+pub mod flow_hash;
+
+pub mod netflow;
+
+pub mod sflow;
+
+pub mod bpf;
+
 ///Contains the size when serialized.
 pub trait SerializedSize {
     const SERIALIZED_SIZE: usize;
@@ -254,6 +392,20 @@ pub enum ReadError {
     Ipv6TooManyHeaderExtensions,
     ///Error given if the data_offset field in a TCP header is smaller then the minimum size of the tcp header itself.
     TcpDataOffsetTooSmall(u8),
+    ///Error when the payload length field of an ip authentication header is smaller then the fixed part of the header (payload length must be at least 1).
+    IpAuthenticationHeaderTooSmall(u8),
+    ///Error when the gtpu header version field is not equal 1. The value is the version that was received.
+    GtpuUnexpectedVersion(u8),
+    ///Error when the length field of a gtpu extension header is zero.
+    GtpuExtensionHeaderLengthZero,
+    ///Error when the gtpu extension headers are bigger then the supported maximum of 256 bytes (length of the extension headers as argument).
+    GtpuExtensionHeadersTooLarge(usize),
+    ///Error when the l2tp header version field does not contain the expected version (2 for L2TPv2 & 3 for L2TPv3). The value is the version that was received.
+    L2tpUnexpectedVersion(u8),
+    ///Error when the length field of an l2tp control message is smaller then the l2tp header itself.
+    L2tpLengthTooSmall(u16),
+    ///Error when the length field of an l2tp attribute value pair is smaller then the attribute value pair header (6 bytes).
+    L2tpAvpLengthTooSmall(u16),
 }
 
 impl ReadError {
@@ -301,6 +453,27 @@ impl fmt::Display for ReadError {
             TcpDataOffsetTooSmall(data_offset) => { //u8
                 write!(f, "ReadError: TCP data offset too small. The data offset value {} in the tcp header is smaller then the tcp header itself.", data_offset)
             },
+            IpAuthenticationHeaderTooSmall(payload_length) => { //u8
+                write!(f, "ReadError: IP authentication header too small. The payload length value {} in the ip authentication header is smaller then the minimum of 1 (fixed part of the header).", payload_length)
+            },
+            GtpuUnexpectedVersion(version_number) => { //u8
+                write!(f, "ReadError: Unexpected GTP version number. Expected a GTPv1-U Header but the header contained the version number {}.", version_number)
+            },
+            GtpuExtensionHeaderLengthZero => {
+                write!(f, "ReadError: Bad GTP-U extension header length. The length field of a GTP-U extension header is zero.")
+            },
+            GtpuExtensionHeadersTooLarge(length) => { //usize
+                write!(f, "ReadError: GTP-U extension headers too large. The GTP-U extension headers ({} bytes) are bigger then the supported maximum of 256 bytes.", length)
+            },
+            L2tpUnexpectedVersion(version_number) => { //u8
+                write!(f, "ReadError: Unexpected L2TP version number. The L2TP header contained the unexpected version number {}.", version_number)
+            },
+            L2tpLengthTooSmall(length) => { //u16
+                write!(f, "ReadError: Bad L2TP length. The length value {} in the L2TP header is smaller then the L2TP header itself.", length)
+            },
+            L2tpAvpLengthTooSmall(length) => { //u16
+                write!(f, "ReadError: Bad L2TP attribute value pair length. The length value {} of the L2TP attribute value pair is smaller then the minimum of 6 bytes.", length)
+            },
         }
     }
 }
@@ -383,6 +556,10 @@ impl Error for WriteError {
 pub enum ValueError {
     ///Error when the ipv4 options length is too big or not aligned (cannot be bigger then 40 bytes and must be a multiple of 4 bytes).
     Ipv4OptionsLengthBad(usize),
+    ///Error when the data length of an ipv4 option is not aligned (route & timestamp data must be a multiple of 4 bytes).
+    Ipv4OptionDataLengthBad{option_id: u8, data_len: usize},
+    ///Error when ipv4 options are set on a packet builder whose last added ip header is not an ipv4 header.
+    Ipv4OptionsWithoutIpv4Header,
     ///Error when a given payload & ipv4 header is bigger then what fits inside an ipv4 total_length field.
     Ipv4PayloadLengthTooLarge(usize),
     ///Error when a given payload & ipv6 header block is bigger then what fits inside an ipv6 payload_length field.
@@ -393,6 +570,14 @@ pub enum ValueError {
     ///Error when a given payload + tcp header options is bigger then what fits inside an tcp packet
     ///Note that a the maximum size, as far as tcp is conceirned, is max_value(u16) - tcp_header.data_offset()*4. The data_offset is for the size of the udp header itself.
     TcpLengthTooLarge(usize),
+    ///Error when the integrity check value length of an ip authentication header is not a multiple of 4 bytes or bigger then the maximum of 1016 bytes.
+    IpAuthenticationHeaderBadIcvLength(usize),
+    ///Error when the geneve options length is too big or not aligned (cannot be bigger then 252 bytes and must be a multiple of 4 bytes).
+    GeneveOptionsLengthBad(usize),
+    ///Error when the content length of a gtpu extension header is not aligned (content length + 2 must be a multiple of 4 bytes) or the extension headers would get bigger then 256 bytes.
+    GtpuExtensionHeaderLengthBad(usize),
+    ///Error when a given payload & gtpu header is bigger then what fits inside a gtpu length field.
+    GtpuPayloadLengthTooLarge(usize),
     ///Error when a u8 field in a header has a larger value then supported.
     U8TooLarge{value: u8, max: u8, field: ErrorField},
     ///Error when a u16 field in a header has a larger value then supported.
@@ -412,6 +597,12 @@ impl fmt::Display for ValueError {
             Ipv4OptionsLengthBad(options_len) => { //usize
                 write!(f, "Bad IPv4 'options_len'. The IPv4 options length ({} bytes) is either not a multiple of 4 bytes or bigger then the maximum of 40 bytes.", options_len)
             },
+            Ipv4OptionDataLengthBad{option_id, data_len} => {
+                write!(f, "Bad IPv4 option data length. The data length ({} bytes) of the IPv4 option with the type {} is not a multiple of 4 bytes.", data_len, option_id)
+            },
+            Ipv4OptionsWithoutIpv4Header => {
+                write!(f, "IPv4 options can not be set as the last added IP header is not an IPv4 header.")
+            },
             Ipv4PayloadLengthTooLarge(total_length) => { //usize
                 write!(f, "IPv4 'total_legnth' too large. The IPv4 header and payload have a larger size ({} bytes) than can be be represented by the 'total_legnth' field in the IPv4 header.", total_length)
             },
@@ -424,6 +615,18 @@ impl fmt::Display for ValueError {
             TcpLengthTooLarge(length) => {  //usize
                 write!(f, "TCP length too large. The TCP packet length ({} bytes) is larger then what is supported.", length)
             },
+            IpAuthenticationHeaderBadIcvLength(icv_len) => { //usize
+                write!(f, "Bad IP authentication header integrity check value length. The integrity check value length ({} bytes) is either not a multiple of 4 bytes or bigger then the maximum of 1016 bytes.", icv_len)
+            },
+            GeneveOptionsLengthBad(options_len) => { //usize
+                write!(f, "Bad Geneve options length. The Geneve options length ({} bytes) is either not a multiple of 4 bytes or bigger then the maximum of 252 bytes.", options_len)
+            },
+            GtpuExtensionHeaderLengthBad(content_len) => { //usize
+                write!(f, "Bad GTP-U extension header length. The GTP-U extension header content length ({} bytes) plus 2 is either not a multiple of 4 bytes or the extension headers would get bigger then the maximum of 256 bytes.", content_len)
+            },
+            GtpuPayloadLengthTooLarge(size) => { //usize
+                write!(f, "GTP-U 'length' too large. The GTP-U payload size ({} bytes) is larger then what can be be represented by the 'length' field in the GTP-U header.", size)
+            },
             U8TooLarge{value, max, field} => {
                 write!(f, "The value {} of the field '{}' is larger then the allowed maximum of {}.", value, field, max)
             },
@@ -444,11 +647,27 @@ pub enum ErrorField {
     Ipv4Dscp,
     Ipv4Ecn,
     Ipv4FragmentsOffset,
+    ///Ipv4OptionElement::Timestamp.overflow
+    Ipv4TimestampOverflow,
+    ///Ipv4OptionElement::Timestamp.flags
+    Ipv4TimestampFlags,
     Ipv6FlowLabel,
     ///VlanTaggingHeader.priority_code_point
     VlanTagPriorityCodePoint,
     ///VlanTaggingHeader.vlan_identifier
     VlanTagVlanId,
+    ///VxlanHeader.vni
+    VxlanVni,
+    ///GeneveHeader.version
+    GeneveVersion,
+    ///GeneveHeader.vni
+    GeneveVni,
+    ///GtpuHeader.version
+    GtpuVersion,
+    ///GtpuPduSessionContainer.pdu_type
+    GtpuPduType,
+    ///GtpuPduSessionContainer.qos_flow_identifier
+    GtpuQosFlowIdentifier,
 }
 
 impl fmt::Display for ErrorField {
@@ -459,9 +678,17 @@ impl fmt::Display for ErrorField {
             Ipv4Dscp => write!(f, "Ipv4Header.differentiated_services_code_point"),
             Ipv4Ecn => write!(f, "Ipv4Header.explicit_congestion_notification"),
             Ipv4FragmentsOffset => write!(f, "Ipv4Header.fragments_offset"),
+            Ipv4TimestampOverflow => write!(f, "Ipv4OptionElement::Timestamp.overflow"),
+            Ipv4TimestampFlags => write!(f, "Ipv4OptionElement::Timestamp.flags"),
             Ipv6FlowLabel => write!(f, "Ipv6Header.flow_label"),
             VlanTagPriorityCodePoint => write!(f, "SingleVlanHeader.priority_code_point"),
-            VlanTagVlanId => write!(f, "SingleVlanHeader.vlan_identifier")
+            VlanTagVlanId => write!(f, "SingleVlanHeader.vlan_identifier"),
+            VxlanVni => write!(f, "VxlanHeader.vni"),
+            GeneveVersion => write!(f, "GeneveHeader.version"),
+            GeneveVni => write!(f, "GeneveHeader.vni"),
+            GtpuVersion => write!(f, "GtpuHeader.version"),
+            GtpuPduType => write!(f, "GtpuPduSessionContainer.pdu_type"),
+            GtpuQosFlowIdentifier => write!(f, "GtpuPduSessionContainer.qos_flow_identifier")
         }
     }
 }
@@ -492,34 +719,16 @@ fn max_check_u16(value: u16, max: u16, field: ErrorField) -> Result<(), ValueErr
     }
 }
 
-//NOTE: Replace this with std::Iterator::step_by as soon as it is in stable (see https://github.com/rust-lang/rust/issues/27741)
-struct RangeStep {
-    start: usize,
-    end: usize,
-    step: usize 
-}
 
-impl RangeStep {
-    fn new(start: usize, end: usize, step: usize) -> RangeStep {
-        RangeStep {
-            start,
-            end,
-            step 
-        }
-    }
-}
-
-impl Iterator for RangeStep {
-    type Item = usize;
-
-    #[inline]
-    fn next(&mut self) -> Option<usize> {
-        if self.start < self.end {
-            let result = self.start;
-            self.start = result + self.step;
-            Some(result)
-        } else {
-            None
-        }
+fn max_check_u32(value: u32, max: u32, field: ErrorField) -> Result<(), ValueError> {
+    use crate::ValueError::U32TooLarge;
+    if value <= max {
+        Ok(())
+    } else {
+        Err(U32TooLarge{ 
+            value, 
+            max, 
+            field
+        })
     }
 }
\ No newline at end of file
@@ -12,6 +12,8 @@ pub enum EtherType {
     Ipv6 = 0x86dd,
     Arp = 0x0806,
     WakeOnLan = 0x0842,
+    ///Ethernet frame encapsulated in a tunnel (e.g. Geneve or GRE)
+    TransparentEthernetBridging = 0x6558,
     VlanTaggedFrame = 0x8100,
     ProviderBridging = 0x88A8,
     VlanDoubleTaggedFrame = 0x9100
@@ -26,6 +28,7 @@ impl EtherType {
             0x86dd => Some(Ipv6),
             0x0806 => Some(Arp),
             0x0842 => Some(WakeOnLan),
+            0x6558 => Some(TransparentEthernetBridging),
             0x88A8 => Some(ProviderBridging),
             0x8100 => Some(VlanTaggedFrame),
             0x9100 => Some(VlanDoubleTaggedFrame),
@@ -157,4 +160,51 @@ impl<'a> Ethernet2HeaderSlice<'a> {
             ether_type: self.ether_type()
         }
     }
-}
\ No newline at end of file
+}
+
+///A mutable slice containing an ethernet 2 header of a network package. Allows the in place modification of the header fields.
+#[derive(Debug, Eq, PartialEq)]
+pub struct Ethernet2HeaderSliceMut<'a> {
+    slice: &'a mut [u8]
+}
+
+impl<'a> Ethernet2HeaderSliceMut<'a> {
+    ///Creates a mutable ethernet slice from an other slice.
+    pub fn from_slice(slice: &'a mut [u8]) -> Result<Ethernet2HeaderSliceMut<'a>, ReadError>{
+        //check length
+        let len = Ethernet2HeaderSlice::from_slice(slice)?.slice().len();
+
+        //all done
+        Ok(Ethernet2HeaderSliceMut {
+            slice: &mut slice[..len]
+        })
+    }
+
+    ///Returns the slice containing the ethernet 2 header
+    #[inline]
+    pub fn slice(&self) -> &[u8] {
+        self.slice
+    }
+
+    ///Returns a read only slice of the header (allows the reading of the header fields).
+    pub fn as_slice(&self) -> Ethernet2HeaderSlice<'_> {
+        Ethernet2HeaderSlice {
+            slice: self.slice
+        }
+    }
+
+    ///Sets the destination mac address.
+    pub fn set_destination(&mut self, value: [u8;6]) {
+        self.slice[..6].copy_from_slice(&value);
+    }
+
+    ///Sets the source mac address.
+    pub fn set_source(&mut self, value: [u8;6]) {
+        self.slice[6..12].copy_from_slice(&value);
+    }
+
+    ///Sets the ether_type field of the header (in system native byte order).
+    pub fn set_ether_type(&mut self, value: u16) {
+        BigEndian::write_u16(&mut self.slice[12..14], value);
+    }
+}
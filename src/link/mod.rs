@@ -1,2 +1,3 @@
 pub mod ethernet;
-pub mod vlan_tagging;
\ No newline at end of file
+pub mod vlan_tagging;
+pub mod ppp;
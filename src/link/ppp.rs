@@ -0,0 +1,83 @@
+use super::super::*;
+
+extern crate byteorder;
+use self::byteorder::{ByteOrder, BigEndian};
+
+///PPP protocol number of an encapsulated IPv4 packet.
+pub const PPP_PROTOCOL_IPV4: u16 = 0x0021;
+///PPP protocol number of an encapsulated IPv6 packet.
+pub const PPP_PROTOCOL_IPV6: u16 = 0x0057;
+
+///Value of the address field of a ppp header (all stations).
+const PPP_ADDRESS: u8 = 0xff;
+///Value of the control field of a ppp header (unnumbered information).
+const PPP_CONTROL: u8 = 0x03;
+
+///A slice containing the header of a PPP frame (rfc1661) as used in L2TP & PPPoE.
+///
+///The address & control fields (0xff 0x03) are optional (address and control field compression)
+///and the protocol field can be compressed to one byte (protocol field compression).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PppHeaderSlice<'a> {
+    slice: &'a [u8]
+}
+
+impl<'a> PppHeaderSlice<'a> {
+
+    ///Creates a slice containing a ppp header.
+    pub fn from_slice(slice: &'a [u8]) -> Result<PppHeaderSlice<'a>, ReadError> {
+        use crate::ReadError::*;
+
+        //skip the address & control fields if present
+        let protocol_start = if slice.len() >= 2 && PPP_ADDRESS == slice[0] && PPP_CONTROL == slice[1] {
+            2
+        } else {
+            0
+        };
+
+        //protocol numbers are always odd in the last byte, a compressed protocol field is detected
+        //by checking the lowest bit of the first byte
+        if slice.len() < protocol_start + 1 {
+            return Err(UnexpectedEndOfSlice(protocol_start + 1));
+        }
+        let len = if 0 != slice[protocol_start] & 1 {
+            protocol_start + 1
+        } else {
+            protocol_start + 2
+        };
+        if slice.len() < len {
+            return Err(UnexpectedEndOfSlice(len));
+        }
+
+        //done
+        Ok(PppHeaderSlice{
+            slice: &slice[..len]
+        })
+    }
+
+    ///Returns the slice containing the ppp header
+    #[inline]
+    pub fn slice(&self) -> &'a [u8] {
+        self.slice
+    }
+
+    ///Returns true if the address & control fields are present.
+    pub fn has_address_and_control(&self) -> bool {
+        //the protocol field has a maximum length of 2 so a longer header always contains address & control
+        self.slice.len() > 2
+    }
+
+    ///Returns the protocol number identifying the content of the ppp frame.
+    pub fn protocol(&self) -> u16 {
+        let protocol = if self.has_address_and_control() {
+            &self.slice[2..]
+        } else {
+            self.slice
+        };
+        if 1 == protocol.len() {
+            u16::from(protocol[0])
+        } else {
+            BigEndian::read_u16(protocol)
+        }
+    }
+}
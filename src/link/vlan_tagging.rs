@@ -260,4 +260,112 @@ impl<'a> DoubleVlanHeaderSlice<'a> {
             inner: self.inner().to_header()
         }
     }
-}
\ No newline at end of file
+}
+
+///A mutable slice containing a single vlan header of a network package. Allows the in place modification of the header fields.
+#[derive(Debug, Eq, PartialEq)]
+pub struct SingleVlanHeaderSliceMut<'a> {
+    slice: &'a mut [u8]
+}
+
+impl<'a> SingleVlanHeaderSliceMut<'a> {
+    ///Creates a mutable vlan header slice from a slice.
+    pub fn from_slice(slice: &'a mut [u8]) -> Result<SingleVlanHeaderSliceMut<'a>, ReadError>{
+        //check length
+        let len = SingleVlanHeaderSlice::from_slice(slice)?.slice().len();
+
+        //all done
+        Ok(SingleVlanHeaderSliceMut {
+            slice: &mut slice[..len]
+        })
+    }
+
+    ///Returns the slice containing the single vlan header
+    #[inline]
+    pub fn slice(&self) -> &[u8] {
+        self.slice
+    }
+
+    ///Returns a read only slice of the header (allows the reading of the header fields).
+    pub fn as_slice(&self) -> SingleVlanHeaderSlice<'_> {
+        SingleVlanHeaderSlice {
+            slice: self.slice
+        }
+    }
+
+    ///Sets the 3 bit "priority_code_point" field.
+    pub fn set_priority_code_point(&mut self, value: u8) -> Result<(), ValueError> {
+        max_check_u8(value, 0x7, ErrorField::VlanTagPriorityCodePoint)?;
+        self.slice[0] = (self.slice[0] & 0x1f) | (value << 5);
+        Ok(())
+    }
+
+    ///Sets the "drop_eligible_indicator" flag.
+    pub fn set_drop_eligible_indicator(&mut self, value: bool) {
+        if value {
+            self.slice[0] |= 0x10;
+        } else {
+            self.slice[0] &= !0x10;
+        }
+    }
+
+    ///Sets the 12 bits "vlan identifier" field.
+    pub fn set_vlan_identifier(&mut self, value: u16) -> Result<(), ValueError> {
+        max_check_u16(value, 0xfff, ErrorField::VlanTagVlanId)?;
+        let bytes = value.to_be_bytes();
+        self.slice[0] = (self.slice[0] & 0xf0) | bytes[0];
+        self.slice[1] = bytes[1];
+        Ok(())
+    }
+
+    ///Sets the "Tag protocol identifier" field (ether type of the following header).
+    pub fn set_ether_type(&mut self, value: u16) {
+        BigEndian::write_u16(&mut self.slice[2..4], value);
+    }
+}
+
+///A mutable slice containing a double vlan header of a network package. Allows the in place modification of the header fields.
+#[derive(Debug, Eq, PartialEq)]
+pub struct DoubleVlanHeaderSliceMut<'a> {
+    slice: &'a mut [u8]
+}
+
+impl<'a> DoubleVlanHeaderSliceMut<'a> {
+    ///Creates a mutable double vlan header slice from a slice.
+    pub fn from_slice(slice: &'a mut [u8]) -> Result<DoubleVlanHeaderSliceMut<'a>, ReadError>{
+        //check length & outer ether type
+        let len = DoubleVlanHeaderSlice::from_slice(slice)?.slice().len();
+
+        //all done
+        Ok(DoubleVlanHeaderSliceMut {
+            slice: &mut slice[..len]
+        })
+    }
+
+    ///Returns the slice containing the double vlan header
+    #[inline]
+    pub fn slice(&self) -> &[u8] {
+        self.slice
+    }
+
+    ///Returns a read only slice of the header (allows the reading of the header fields).
+    pub fn as_slice(&self) -> DoubleVlanHeaderSlice<'_> {
+        DoubleVlanHeaderSlice {
+            slice: self.slice
+        }
+    }
+
+    ///Returns a mutable slice with the outer vlan header.
+    pub fn outer_mut(&mut self) -> SingleVlanHeaderSliceMut<'_> {
+        SingleVlanHeaderSliceMut {
+            slice: &mut self.slice[..SingleVlanHeader::SERIALIZED_SIZE]
+        }
+    }
+
+    ///Returns a mutable slice with the inner vlan header.
+    pub fn inner_mut(&mut self) -> SingleVlanHeaderSliceMut<'_> {
+        SingleVlanHeaderSliceMut {
+            slice: &mut self.slice[SingleVlanHeader::SERIALIZED_SIZE..SingleVlanHeader::SERIALIZED_SIZE*2]
+        }
+    }
+}
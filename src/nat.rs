@@ -0,0 +1,388 @@
+use super::*;
+
+extern crate byteorder;
+use self::byteorder::{ByteOrder, BigEndian};
+
+use std::net::IpAddr;
+
+///Addresses & ports that are replaced by [`SlicedPacketMut::apply_nat`](struct.SlicedPacketMut.html#method.apply_nat)
+///(fields that are `None` are left unchanged).
+///
+///The rewrite is described in the direction of the packet it is applied to. For ICMP error messages the
+///quoted packet (that was sent in the opposite direction) is rewritten with the source & destination swapped.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct NatRewrite {
+    ///New source address (must have the same version as the ip header of the packet).
+    pub source: Option<IpAddr>,
+    ///New destination address (must have the same version as the ip header of the packet).
+    pub destination: Option<IpAddr>,
+    ///New tcp or udp source port.
+    pub source_port: Option<u16>,
+    ///New tcp or udp destination port.
+    pub destination_port: Option<u16>,
+}
+
+impl NatRewrite {
+    fn contains_ports(&self) -> bool {
+        self.source_port.is_some() || self.destination_port.is_some()
+    }
+
+    ///Returns the rewrite for a packet sent in the opposite direction.
+    fn reversed(&self) -> NatRewrite {
+        NatRewrite {
+            source: self.destination,
+            destination: self.source,
+            source_port: self.destination_port,
+            destination_port: self.source_port,
+        }
+    }
+}
+
+///Errors that can occur when applying a NAT rewrite to a packet (the packet is not modified if an error is returned).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NatError {
+    ///The packet contains no ip header.
+    NoIpHeader,
+    ///The version of a given address does not match the version of the ip header.
+    IpVersionMismatch,
+    ///Ports should be rewritten but the packet contains no tcp or udp header (e.g. non first ip fragments
+    ///or ICMP errors that do not quote a tcp or udp header).
+    PortsNotPresent,
+}
+
+impl fmt::Display for NatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use NatError::*;
+        match self {
+            NoIpHeader => {
+                write!(f, "NatError: The packet contains no ip header.")
+            },
+            IpVersionMismatch => {
+                write!(f, "NatError: The version of a given address does not match the version of the ip header.")
+            },
+            PortsNotPresent => {
+                write!(f, "NatError: Ports should be rewritten, but the packet contains no tcp or udp header.")
+            },
+        }
+    }
+}
+
+impl Error for NatError {
+
+}
+
+const IP_ICMP: u8 = IpTrafficClass::Icmp as u8;
+const IP_ICMPV6: u8 = IpTrafficClass::IPv6Icmp as u8;
+const IP_UDP: u8 = IpTrafficClass::Udp as u8;
+const IP_TCP: u8 = IpTrafficClass::Tcp as u8;
+const IP_AUTH: u8 = IpTrafficClass::IPv6AuthenticationHeader as u8;
+const IP_ESP: u8 = IpTrafficClass::IPv6EncapSecurityPayload as u8;
+const IP_FRAG: u8 = IpTrafficClass::IPv6FragmentationHeader as u8;
+
+impl<'a> SlicedPacketMut<'a> {
+    ///Replaces the ip addresses & tcp/udp ports of the packet in place and updates the ipv4 header
+    ///checksum & the transport checksum accordingly.
+    ///
+    ///The following cases are handled:
+    ///
+    ///* Udp checksums of 0 (no checksum) are left unchanged & an updated checksum of 0 is written as 0xffff.
+    ///* The ICMPv6 checksum is updated if an address of the ipv6 pseudo header is changed.
+    ///* The ip & transport header quoted in ICMP & ICMPv6 error messages is rewritten with the reversed
+    ///  rewrite (source & destination swapped) and the ICMP checksum is updated. Ports in ICMP query messages
+    ///  (e.g. echo identifiers) are not rewritten.
+    ///* Transport headers of non first fragments are not modified (they only contain data).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use etherparse::*;
+    /// # use std::net::Ipv4Addr;
+    /// # let mut packet = Vec::new();
+    /// # PacketBuilder::ipv4([10,0,0,1], [192,168,1,2], 20)
+    /// #    .tcp(1234, 80, 1, 1024)
+    /// #    .write(&mut packet, &[1,2,3,4]).unwrap();
+    /// //source nat
+    /// SlicedPacketMut::from_ip(&mut packet).unwrap()
+    ///     .apply_nat(&NatRewrite {
+    ///         source: Some(Ipv4Addr::new(1,2,3,4).into()),
+    ///         source_port: Some(40000),
+    ///         ..Default::default()
+    ///     })
+    ///     .unwrap();
+    ///
+    /// let sliced = SlicedPacket::from_ip(&packet).unwrap();
+    /// assert!(!sliced.verify_checksums().contains_invalid());
+    /// ```
+    pub fn apply_nat(&mut self, rewrite: &NatRewrite) -> Result<(), NatError> {
+        use crate::NatError::*;
+
+        let ip = match self.ip.as_mut() {
+            Some(ip) => ip,
+            None => return if NatRewrite::default() == *rewrite { Ok(()) } else { Err(NoIpHeader) }
+        };
+
+        //check the address versions
+        let version4 = match ip {
+            InternetSliceMut::Ipv4(_) => true,
+            InternetSliceMut::Ipv6(_) => false
+        };
+        if [rewrite.source, rewrite.destination].iter().flatten().any(|addr| addr.is_ipv4() != version4) {
+            return Err(IpVersionMismatch);
+        }
+
+        //determine the protocol of the ip payload
+        let (protocol, fragmented) = match ip {
+            InternetSliceMut::Ipv4(header) => {
+                let header = header.as_slice();
+                let protocol = match (header.protocol(), self.ip_extensions.first()) {
+                    //next header field of the authentication header
+                    (IP_AUTH, Some(next_header)) => *next_header,
+                    (protocol, _) => protocol
+                };
+                (protocol, 0 != header.fragments_offset())
+            },
+            InternetSliceMut::Ipv6(header) => {
+                match ipv6_payload(header.as_slice().next_header(), self.ip_extensions) {
+                    Some((protocol, _, fragmented)) => (protocol, fragmented),
+                    None => (IP_ESP, false)
+                }
+            }
+        };
+
+        //transport headers of non first fragments only contain data
+        let transport = if fragmented { None } else { self.transport.as_mut() };
+
+        //icmp (only if the icmp header is contained)
+        let icmp_protocol = if version4 { IP_ICMP } else { IP_ICMPV6 };
+        let icmp = if transport.is_none() && !fragmented && icmp_protocol == protocol && self.payload.len() >= 8 {
+            Some(&mut self.payload[..])
+        } else {
+            None
+        };
+        let icmp_error = match icmp.as_ref() {
+            Some(icmp) => if version4 {
+                //destination unreachable, source quench, redirect, time exceeded & parameter problem
+                [3, 4, 5, 11, 12].contains(&icmp[0])
+            } else {
+                //destination unreachable, packet too big, time exceeded & parameter problem
+                [1, 2, 3, 4].contains(&icmp[0])
+            },
+            None => false
+        };
+
+        //check that the ports can be rewritten
+        if rewrite.contains_ports() {
+            let ports_present = match (&transport, &icmp) {
+                (Some(_), _) => true,
+                (None, Some(icmp)) if icmp_error => {
+                    match quoted_packet(&icmp[8..], version4) {
+                        Some((_, Some((IP_UDP, offset)))) |
+                        Some((_, Some((IP_TCP, offset)))) => icmp.len() >= 8 + offset + 4,
+                        _ => false
+                    }
+                },
+                _ => false
+            };
+            if !ports_present {
+                return Err(PortsNotPresent);
+            }
+        }
+
+        //ip header
+        let (old_source, old_destination) = match ip {
+            InternetSliceMut::Ipv4(header) => {
+                let old = (IpAddr::from(header.as_slice().source_addr()), IpAddr::from(header.as_slice().destination_addr()));
+                if let Some(IpAddr::V4(addr)) = rewrite.source {
+                    header.set_source(addr.octets());
+                }
+                if let Some(IpAddr::V4(addr)) = rewrite.destination {
+                    header.set_destination(addr.octets());
+                }
+                old
+            },
+            InternetSliceMut::Ipv6(header) => {
+                let old = (IpAddr::from(header.as_slice().source_addr()), IpAddr::from(header.as_slice().destination_addr()));
+                if let Some(IpAddr::V6(addr)) = rewrite.source {
+                    header.set_source(addr.octets());
+                }
+                if let Some(IpAddr::V6(addr)) = rewrite.destination {
+                    header.set_destination(addr.octets());
+                }
+                old
+            }
+        };
+        let address_changes = [(old_source, rewrite.source), (old_destination, rewrite.destination)];
+
+        //transport header
+        match transport {
+            Some(TransportSliceMut::Udp(header)) => {
+                for (old, new) in address_changes.iter() {
+                    match (old, new) {
+                        (IpAddr::V4(old), Some(IpAddr::V4(new))) => header.update_checksum_ipv4_address(old.octets(), new.octets()),
+                        (IpAddr::V6(old), Some(IpAddr::V6(new))) => header.update_checksum_ipv6_address(&old.octets(), &new.octets()),
+                        _ => {}
+                    }
+                }
+                if let Some(port) = rewrite.source_port {
+                    header.set_source_port(port);
+                }
+                if let Some(port) = rewrite.destination_port {
+                    header.set_destination_port(port);
+                }
+            },
+            Some(TransportSliceMut::Tcp(header)) => {
+                for (old, new) in address_changes.iter() {
+                    match (old, new) {
+                        (IpAddr::V4(old), Some(IpAddr::V4(new))) => header.update_checksum_ipv4_address(old.octets(), new.octets()),
+                        (IpAddr::V6(old), Some(IpAddr::V6(new))) => header.update_checksum_ipv6_address(&old.octets(), &new.octets()),
+                        _ => {}
+                    }
+                }
+                if let Some(port) = rewrite.source_port {
+                    header.set_source_port(port);
+                }
+                if let Some(port) = rewrite.destination_port {
+                    header.set_destination_port(port);
+                }
+            },
+            None => {}
+        }
+
+        //icmp
+        if let Some(icmp) = icmp {
+            let mut icmp_checksum = BigEndian::read_u16(&icmp[2..4]);
+
+            //the icmpv6 checksum contains a pseudo header
+            if !version4 {
+                for (old, new) in address_changes.iter() {
+                    if let (IpAddr::V6(old), Some(IpAddr::V6(new))) = (old, new) {
+                        icmp_checksum = checksum::update_ipv6_address(icmp_checksum, &old.octets(), &new.octets());
+                    }
+                }
+            }
+
+            //rewrite the quoted packet (sent in the opposite direction)
+            if icmp_error {
+                let quoted = &mut icmp[8..];
+                let old_sum = checksum::Sum16BitWords::new().add_slice(quoted).fold();
+                rewrite_quoted_packet(quoted, version4, &rewrite.reversed());
+                let new_sum = checksum::Sum16BitWords::new().add_slice(quoted).fold();
+                icmp_checksum = checksum::update_u16(icmp_checksum, old_sum, new_sum);
+            }
+
+            BigEndian::write_u16(&mut icmp[2..4], icmp_checksum);
+        }
+
+        Ok(())
+    }
+}
+
+///Walks through the ipv6 extension headers & returns the protocol of the payload, the length of the
+///extension headers & if the packet is a non first fragment (None if the extension headers are incomplete).
+fn ipv6_payload(next_header: u8, extensions: &[u8]) -> Option<(u8, usize, bool)> {
+    let mut next_header = next_header;
+    let mut rest = extensions;
+    let mut fragmented = false;
+    for _ in 0..IPV6_MAX_NUM_HEADER_EXTENSIONS {
+        //the esp header is followed by encrypted data
+        if IP_ESP == next_header || !IpTrafficClass::is_ipv6_ext_header_value(next_header) {
+            return Some((next_header, extensions.len() - rest.len(), fragmented));
+        }
+        if IP_FRAG == next_header && rest.len() >= 4 {
+            fragmented = fragmented || 0 != rest[2] || 0 != rest[3] & 0xf8;
+        }
+        let (next, next_rest) = Ipv6Header::skip_header_extension_in_slice(rest, next_header).ok()?;
+        next_header = next;
+        rest = next_rest;
+    }
+    None
+}
+
+///Determines the ip header length & the protocol & offset of the transport header of the packet quoted in an ICMP
+///error message (None if the quoted packet does not start with a complete ip header of the expected version).
+fn quoted_packet(quoted: &[u8], version4: bool) -> Option<(usize, Option<(u8, usize)>)> {
+    if version4 {
+        let header = Ipv4HeaderSlice::from_slice(quoted).ok()?;
+        let len = header.slice().len();
+        if 0 == header.fragments_offset() {
+            Some((len, Some((header.protocol(), len))))
+        } else {
+            Some((len, None))
+        }
+    } else {
+        let header = Ipv6HeaderSlice::from_slice(quoted).ok()?;
+        let len = header.slice().len();
+        match ipv6_payload(header.next_header(), &quoted[len..]) {
+            Some((protocol, extensions_len, false)) => Some((len, Some((protocol, len + extensions_len)))),
+            _ => Some((len, None))
+        }
+    }
+}
+
+///Rewrites the addresses & ports of a (possibly truncated) packet quoted in an ICMP error message.
+fn rewrite_quoted_packet(quoted: &mut [u8], version4: bool, rewrite: &NatRewrite) {
+    let (ip_len, transport) = match quoted_packet(quoted, version4) {
+        Some(value) => value,
+        None => return
+    };
+
+    //sum of the addresses before & after the rewrite (for the transport checksum update)
+    let addresses_sum = |quoted: &[u8]| {
+        let range = if version4 { 12..20 } else { 8..40 };
+        checksum::Sum16BitWords::new().add_slice(&quoted[range])
+    };
+    let old_addresses_sum = addresses_sum(quoted);
+
+    //ip header
+    if version4 {
+        //unwrap is safe as the header was already checked
+        let mut header = Ipv4HeaderSliceMut::from_slice(&mut quoted[..ip_len]).unwrap();
+        if let Some(IpAddr::V4(addr)) = rewrite.source {
+            header.set_source(addr.octets());
+        }
+        if let Some(IpAddr::V4(addr)) = rewrite.destination {
+            header.set_destination(addr.octets());
+        }
+    } else {
+        let mut header = Ipv6HeaderSliceMut::from_slice(&mut quoted[..ip_len]).unwrap();
+        if let Some(IpAddr::V6(addr)) = rewrite.source {
+            header.set_source(addr.octets());
+        }
+        if let Some(IpAddr::V6(addr)) = rewrite.destination {
+            header.set_destination(addr.octets());
+        }
+    }
+    let new_addresses_sum = addresses_sum(quoted);
+
+    //transport header (only the parts contained in the quoted data)
+    let (protocol, offset) = match transport {
+        Some(value) => value,
+        None => return
+    };
+    let checksum_offset = match protocol {
+        IP_UDP => 6,
+        IP_TCP => 16,
+        _ => return
+    };
+    let header = &mut quoted[offset..];
+    let old_sum = old_addresses_sum.add_slice(&header[..std::cmp::min(4, header.len())]).fold();
+    if header.len() >= 4 {
+        if let Some(port) = rewrite.source_port {
+            BigEndian::write_u16(&mut header[0..2], port);
+        }
+        if let Some(port) = rewrite.destination_port {
+            BigEndian::write_u16(&mut header[2..4], port);
+        }
+    }
+    let new_sum = new_addresses_sum.add_slice(&header[..std::cmp::min(4, header.len())]).fold();
+
+    if header.len() >= checksum_offset + 2 {
+        let old_checksum = BigEndian::read_u16(&header[checksum_offset..checksum_offset + 2]);
+        //udp checksums of 0 are not calculated
+        if IP_TCP == protocol || 0 != old_checksum {
+            let new_checksum = checksum::update_u16(old_checksum, old_sum, new_sum);
+            let new_checksum = if IP_UDP == protocol && 0 == new_checksum { 0xffff } else { new_checksum };
+            BigEndian::write_u16(&mut header[checksum_offset..checksum_offset + 2], new_checksum);
+        }
+    }
+}
@@ -0,0 +1,1101 @@
+//! Decoding of NetFlow v5, NetFlow v9 & IPFIX export packets and encoding of IPFIX messages.
+//!
+//! The [`ExportDecoder`](struct.ExportDecoder.html) decodes the UDP payloads received by a collector. The templates
+//! of NetFlow v9 & IPFIX are stored per exporter (socket address) & observation domain, so data sets can be decoded
+//! in later packets. Values of well known [`InformationElement`](enum.InformationElement.html)s are decoded as
+//! unsigned numbers, ip or mac addresses.
+//!
+//! The [`IpfixWriter`](struct.IpfixWriter.html) encodes data records into IPFIX messages that can be used as the
+//! payload of a UDP packet (e.g. written with a `PacketBuilder`). Records of the [`flow`](../flow/index.html) module
+//! can be converted with [`DataRecord::from_flow_record`](struct.DataRecord.html#method.from_flow_record).
+//!
+//! # Example
+//!
+//! ```
+//! use etherparse::*;
+//! use etherparse::flow::*;
+//! use etherparse::netflow::*;
+//! use std::time::Duration;
+//!
+//! //aggregate a packet into a flow
+//! let mut packet = Vec::new();
+//! PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+//!     .udp(1234, 53)
+//!     .write(&mut packet, &[0;10])
+//!     .unwrap();
+//! let mut table = FlowTable::new(FlowTableConfig::default());
+//! table.push(&SlicedPacket::from_ip(&packet).unwrap(), Duration::from_secs(1_600_000_000)).unwrap();
+//! let flows = table.flush();
+//!
+//! //encode the flow as ipfix message in an udp packet
+//! let template = Template::flow_records(256, false, false);
+//! let mut writer = IpfixWriter::new(1);
+//! writer.add_template(template.clone()).unwrap();
+//! let records: Vec<DataRecord> = flows.iter().map(|flow| DataRecord::from_flow_record(&template, flow)).collect();
+//! let message = writer.write_message(1_600_000_010, true, &records).unwrap();
+//! let mut export = Vec::new();
+//! PacketBuilder::ipv4([10,0,0,1], [10,0,0,2], 64)
+//!     .udp(50000, IPFIX_PORT)
+//!     .write(&mut export, &message)
+//!     .unwrap();
+//!
+//! //decode the message on the collector side
+//! let sliced = SlicedPacket::from_ip(&export).unwrap();
+//! let mut decoder = ExportDecoder::new();
+//! let exporter = "10.0.0.1:50000".parse().unwrap();
+//! match decoder.decode(exporter, sliced.payload).unwrap() {
+//!     ExportPacket::Ipfix(message) => {
+//!         assert_eq!(1, message.records.len());
+//!         let record = &message.records[0];
+//!         assert_eq!(Some(&FieldValue::Ipv4Address([192,168,1,1])), record.get(InformationElement::SourceIpv4Address));
+//!         assert_eq!(Some(&FieldValue::Unsigned(53)), record.get(InformationElement::DestinationTransportPort));
+//!         assert_eq!(Some(&FieldValue::Unsigned(1)), record.get(InformationElement::PacketDeltaCount));
+//!     },
+//!     _ => panic!("expected an ipfix message")
+//! }
+//! ```
+use super::*;
+
+extern crate byteorder;
+use self::byteorder::{ByteOrder, BigEndian};
+
+use crate::flow::FlowRecord;
+use std::collections::{BTreeMap, HashMap};
+use std::net::{IpAddr, SocketAddr};
+
+///Default UDP port of NetFlow collectors.
+pub const NETFLOW_PORT: u16 = 2055;
+
+///UDP port of IPFIX collectors (RFC 7011).
+pub const IPFIX_PORT: u16 = 4739;
+
+///Field length indicating a variable length field (IPFIX only).
+pub const VARIABLE_LENGTH: u16 = 0xffff;
+
+///Private enterprise number of the reverse information elements of bidirectional flows (RFC 5103).
+pub const REVERSE_ENTERPRISE_NUMBER: u32 = 29305;
+
+///Version numbers in the export packet headers.
+const NETFLOW_V5_VERSION: u16 = 5;
+const NETFLOW_V9_VERSION: u16 = 9;
+const IPFIX_VERSION: u16 = 10;
+
+///Size of the NetFlow v9 & IPFIX message headers.
+const NETFLOW_V9_HEADER_LEN: usize = 20;
+const IPFIX_HEADER_LEN: usize = 16;
+
+///Set ids of template & options template sets.
+const NETFLOW_V9_TEMPLATE_SET: u16 = 0;
+const NETFLOW_V9_OPTIONS_TEMPLATE_SET: u16 = 1;
+const IPFIX_TEMPLATE_SET: u16 = 2;
+const IPFIX_OPTIONS_TEMPLATE_SET: u16 = 3;
+
+///Smallest id of templates & data sets.
+const MIN_DATA_SET_ID: u16 = 256;
+
+///Errors that can occur when decoding or encoding export packets.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NetflowError {
+    ///The slice ended before the end of the packet (expected minimum size as argument).
+    UnexpectedEndOfSlice(usize),
+    ///The version in the header is not 5, 9 or 10 (IPFIX).
+    UnsupportedVersion(u16),
+    ///The length field of an IPFIX message header is smaller then the header itself.
+    InvalidMessageLength(u16),
+    ///The length field of a set is smaller then the set header or exceeds the message.
+    InvalidSetLength { set_id: u16, length: u16 },
+    ///A template record is malformed (template id below 256, missing scope fields, fields exceeding the set or
+    ///only fields with a length of 0).
+    InvalidTemplate(u16),
+    ///A data record references a template that was not added to the writer.
+    UnknownTemplate(u16),
+    ///A field of a data record does not match the field specifier of the template.
+    InvalidFieldValue { template_id: u16, element_id: u16 },
+    ///The encoded message is larger then the maximum IPFIX message length of 65535 bytes (length as argument).
+    MessageTooLarge(usize),
+}
+
+impl fmt::Display for NetflowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use NetflowError::*;
+        match self {
+            UnexpectedEndOfSlice(expected_minimum_size) => {
+                write!(f, "NetflowError: Unexpected end of slice. The given slice contained less then minimum required {} bytes.", expected_minimum_size)
+            },
+            UnsupportedVersion(version) => {
+                write!(f, "NetflowError: Unsupported export packet version {} (only 5, 9 & 10 are supported).", version)
+            },
+            InvalidMessageLength(length) => {
+                write!(f, "NetflowError: The IPFIX message length {} is smaller then the message header.", length)
+            },
+            InvalidSetLength{ set_id, length } => {
+                write!(f, "NetflowError: The length {} of the set {} is smaller then the set header or exceeds the message.", length, set_id)
+            },
+            InvalidTemplate(template_id) => {
+                write!(f, "NetflowError: The template record {} is malformed.", template_id)
+            },
+            UnknownTemplate(template_id) => {
+                write!(f, "NetflowError: The data record references the unknown template {}.", template_id)
+            },
+            InvalidFieldValue{ template_id, element_id } => {
+                write!(f, "NetflowError: A field value for the information element {} does not match the field specifier of the template {}.", element_id, template_id)
+            },
+            MessageTooLarge(length) => {
+                write!(f, "NetflowError: The encoded message length of {} bytes exceeds the maximum IPFIX message length of 65535 bytes.", length)
+            },
+        }
+    }
+}
+
+impl Error for NetflowError {
+
+}
+
+///Common information elements (ids shared by NetFlow v9 & IPFIX).
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum InformationElement {
+    OctetDeltaCount = 1,
+    PacketDeltaCount = 2,
+    ProtocolIdentifier = 4,
+    IpClassOfService = 5,
+    TcpControlBits = 6,
+    SourceTransportPort = 7,
+    SourceIpv4Address = 8,
+    SourceIpv4PrefixLength = 9,
+    IngressInterface = 10,
+    DestinationTransportPort = 11,
+    DestinationIpv4Address = 12,
+    DestinationIpv4PrefixLength = 13,
+    EgressInterface = 14,
+    IpNextHopIpv4Address = 15,
+    BgpSourceAsNumber = 16,
+    BgpDestinationAsNumber = 17,
+    FlowEndSysUpTime = 21,
+    FlowStartSysUpTime = 22,
+    SourceIpv6Address = 27,
+    DestinationIpv6Address = 28,
+    SourceIpv6PrefixLength = 29,
+    DestinationIpv6PrefixLength = 30,
+    FlowLabelIpv6 = 31,
+    IcmpTypeCodeIpv4 = 32,
+    SamplingInterval = 34,
+    SamplingAlgorithm = 35,
+    SourceMacAddress = 56,
+    VlanId = 58,
+    IpVersion = 60,
+    FlowDirection = 61,
+    IpNextHopIpv6Address = 62,
+    DestinationMacAddress = 80,
+    FlowEndReason = 136,
+    IcmpTypeCodeIpv6 = 139,
+    ObservationDomainId = 149,
+    FlowStartSeconds = 150,
+    FlowEndSeconds = 151,
+    FlowStartMilliseconds = 152,
+    FlowEndMilliseconds = 153,
+    Dot1qVlanId = 243,
+    Dot1qCustomerVlanId = 245,
+}
+
+///Data types of the information elements.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum DataType {
+    Unsigned,
+    Ipv4Address,
+    Ipv6Address,
+    MacAddress,
+}
+
+impl InformationElement {
+    ///Returns the information element with the given id (None if the id is unknown).
+    pub fn from_id(id: u16) -> Option<InformationElement> {
+        use self::InformationElement::*;
+        const ALL: [InformationElement;41] = [
+            OctetDeltaCount, PacketDeltaCount, ProtocolIdentifier, IpClassOfService, TcpControlBits,
+            SourceTransportPort, SourceIpv4Address, SourceIpv4PrefixLength, IngressInterface,
+            DestinationTransportPort, DestinationIpv4Address, DestinationIpv4PrefixLength, EgressInterface,
+            IpNextHopIpv4Address, BgpSourceAsNumber, BgpDestinationAsNumber, FlowEndSysUpTime, FlowStartSysUpTime,
+            SourceIpv6Address, DestinationIpv6Address, SourceIpv6PrefixLength, DestinationIpv6PrefixLength,
+            FlowLabelIpv6, IcmpTypeCodeIpv4, SamplingInterval, SamplingAlgorithm, SourceMacAddress, VlanId,
+            IpVersion, FlowDirection, IpNextHopIpv6Address, DestinationMacAddress, FlowEndReason, IcmpTypeCodeIpv6,
+            ObservationDomainId, FlowStartSeconds, FlowEndSeconds, FlowStartMilliseconds, FlowEndMilliseconds, Dot1qVlanId,
+            Dot1qCustomerVlanId,
+        ];
+        ALL.iter().find(|value| value.id() == id).cloned()
+    }
+
+    ///Returns the id of the information element.
+    pub fn id(self) -> u16 {
+        self as u16
+    }
+
+    fn data_type(self) -> DataType {
+        use self::InformationElement::*;
+        match self {
+            SourceIpv4Address | DestinationIpv4Address | IpNextHopIpv4Address => DataType::Ipv4Address,
+            SourceIpv6Address | DestinationIpv6Address | IpNextHopIpv6Address => DataType::Ipv6Address,
+            SourceMacAddress | DestinationMacAddress => DataType::MacAddress,
+            _ => DataType::Unsigned
+        }
+    }
+}
+
+///Id, length & enterprise number of a field in a template.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct FieldSpecifier {
+    ///Information element id (without the enterprise bit).
+    pub element_id: u16,
+    ///Length of the field in bytes (`VARIABLE_LENGTH` for variable length fields).
+    pub length: u16,
+    ///Private enterprise number of enterprise specific information elements (IPFIX only).
+    pub enterprise_number: Option<u32>,
+}
+
+impl FieldSpecifier {
+    ///Field specifier of a common information element.
+    pub fn new(element: InformationElement, length: u16) -> FieldSpecifier {
+        FieldSpecifier {
+            element_id: element.id(),
+            length,
+            enterprise_number: None,
+        }
+    }
+
+    ///Field specifier of the reverse direction of a common information element (RFC 5103).
+    pub fn reverse(element: InformationElement, length: u16) -> FieldSpecifier {
+        FieldSpecifier {
+            element_id: element.id(),
+            length,
+            enterprise_number: Some(REVERSE_ENTERPRISE_NUMBER),
+        }
+    }
+
+    ///Returns the common information element of the field (None for unknown & enterprise specific elements).
+    pub fn information_element(&self) -> Option<InformationElement> {
+        match self.enterprise_number {
+            None => InformationElement::from_id(self.element_id),
+            Some(_) => None
+        }
+    }
+
+    ///Returns true if the field is a reverse information element (RFC 5103).
+    pub fn is_reverse(&self) -> bool {
+        Some(REVERSE_ENTERPRISE_NUMBER) == self.enterprise_number
+    }
+
+    ///Returns the data type of the field (reverse information elements have the type of the forward element).
+    fn data_type(&self) -> Option<DataType> {
+        if self.enterprise_number.is_none() || self.is_reverse() {
+            InformationElement::from_id(self.element_id).map(|value| value.data_type())
+        } else {
+            None
+        }
+    }
+}
+
+///Template or options template describing the fields of data records.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Template {
+    pub id: u16,
+    ///Number of scope fields at the start of the fields (only non zero for options templates).
+    pub scope_field_count: u16,
+    pub fields: Vec<FieldSpecifier>,
+}
+
+impl Template {
+    ///Template for exporting flow records of the [`flow`](../flow/index.html) module.
+    ///
+    ///The template contains the addresses (ipv4 or ipv6), ports, protocol, byte & packet counters, tcp flags, start & end
+    ///time in milliseconds and the end reason of a flow. For bidirectional flows the reverse byte & packet counters and
+    ///tcp flags are added (RFC 5103).
+    pub fn flow_records(id: u16, ipv6: bool, bidirectional: bool) -> Template {
+        use self::InformationElement::*;
+        let mut fields = if ipv6 {
+            vec![FieldSpecifier::new(SourceIpv6Address, 16), FieldSpecifier::new(DestinationIpv6Address, 16)]
+        } else {
+            vec![FieldSpecifier::new(SourceIpv4Address, 4), FieldSpecifier::new(DestinationIpv4Address, 4)]
+        };
+        fields.extend_from_slice(&[
+            FieldSpecifier::new(SourceTransportPort, 2),
+            FieldSpecifier::new(DestinationTransportPort, 2),
+            FieldSpecifier::new(ProtocolIdentifier, 1),
+            FieldSpecifier::new(TcpControlBits, 2),
+            FieldSpecifier::new(OctetDeltaCount, 8),
+            FieldSpecifier::new(PacketDeltaCount, 8),
+            FieldSpecifier::new(FlowStartMilliseconds, 8),
+            FieldSpecifier::new(FlowEndMilliseconds, 8),
+            FieldSpecifier::new(FlowEndReason, 1),
+        ]);
+        if bidirectional {
+            fields.extend_from_slice(&[
+                FieldSpecifier::reverse(TcpControlBits, 2),
+                FieldSpecifier::reverse(OctetDeltaCount, 8),
+                FieldSpecifier::reverse(PacketDeltaCount, 8),
+            ]);
+        }
+        Template {
+            id,
+            scope_field_count: 0,
+            fields,
+        }
+    }
+
+    ///Returns true if the template is an options template.
+    pub fn is_options_template(&self) -> bool {
+        0 != self.scope_field_count
+    }
+}
+
+///Decoded value of a field.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FieldValue {
+    ///Value of an unsigned information element (encoded with 1 to 8 bytes).
+    Unsigned(u64),
+    Ipv4Address([u8;4]),
+    Ipv6Address([u8;16]),
+    MacAddress([u8;6]),
+    ///Value of an unknown or enterprise specific information element (or a value with an unexpected length).
+    Bytes(Vec<u8>),
+}
+
+impl FieldValue {
+    ///Decodes the value of a field based on the data type of its information element.
+    fn decode(specifier: &FieldSpecifier, bytes: &[u8]) -> FieldValue {
+        match (specifier.data_type(), bytes.len()) {
+            (Some(DataType::Ipv4Address), 4) => FieldValue::Ipv4Address([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            (Some(DataType::Ipv6Address), 16) => {
+                let mut address = [0;16];
+                address.copy_from_slice(bytes);
+                FieldValue::Ipv6Address(address)
+            },
+            (Some(DataType::MacAddress), 6) => {
+                let mut mac = [0;6];
+                mac.copy_from_slice(bytes);
+                FieldValue::MacAddress(mac)
+            },
+            (Some(DataType::Unsigned), 1..=8) => {
+                FieldValue::Unsigned(bytes.iter().fold(0, |value, byte| (value << 8) | u64::from(*byte)))
+            },
+            _ => FieldValue::Bytes(bytes.to_vec())
+        }
+    }
+
+    ///Value with all bytes set to zero for the given field.
+    fn zero(specifier: &FieldSpecifier) -> FieldValue {
+        if VARIABLE_LENGTH == specifier.length {
+            FieldValue::Bytes(Vec::new())
+        } else {
+            FieldValue::decode(specifier, &vec![0; usize::from(specifier.length)])
+        }
+    }
+
+    ///Encodes the value with the length of the field specifier (None if the value does not match the field).
+    fn encode(&self, specifier: &FieldSpecifier, target: &mut Vec<u8>) -> Option<()> {
+        let length = usize::from(specifier.length);
+        let expected_type = specifier.data_type();
+        match self {
+            FieldValue::Unsigned(value) => {
+                let is_unsigned = expected_type.is_none() || Some(DataType::Unsigned) == expected_type;
+                let fits = length >= 8 || *value < (1u64 << (8*length));
+                if !is_unsigned || 0 == length || length > 8 || !fits {
+                    return None;
+                }
+                target.extend_from_slice(&value.to_be_bytes()[8 - length..]);
+            },
+            FieldValue::Ipv4Address(value) => encode_fixed(&value[..], length, target)?,
+            FieldValue::Ipv6Address(value) => encode_fixed(&value[..], length, target)?,
+            FieldValue::MacAddress(value) => encode_fixed(&value[..], length, target)?,
+            FieldValue::Bytes(value) => {
+                if VARIABLE_LENGTH == specifier.length {
+                    if value.len() < 255 {
+                        target.push(value.len() as u8);
+                    } else if value.len() <= usize::from(u16::MAX) {
+                        target.push(255);
+                        target.extend_from_slice(&(value.len() as u16).to_be_bytes());
+                    } else {
+                        return None;
+                    }
+                    target.extend_from_slice(value);
+                } else {
+                    encode_fixed(value, length, target)?;
+                }
+            }
+        }
+        Some(())
+    }
+}
+
+///Appends bytes of a fixed length field (None if the length does not match).
+fn encode_fixed(value: &[u8], length: usize, target: &mut Vec<u8>) -> Option<()> {
+    if value.len() == length {
+        target.extend_from_slice(value);
+        Some(())
+    } else {
+        None
+    }
+}
+
+///Values of a data record in the order of the fields of its template.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DataRecord {
+    pub template_id: u16,
+    pub fields: Vec<(FieldSpecifier, FieldValue)>,
+}
+
+impl DataRecord {
+    ///Returns the value of the first field containing the given (forward) information element.
+    pub fn get(&self, element: InformationElement) -> Option<&FieldValue> {
+        self.fields
+            .iter()
+            .find(|(specifier, _)| Some(element) == specifier.information_element())
+            .map(|(_, value)| value)
+    }
+
+    ///Returns the value of the first field containing the reverse direction of the given information element (RFC 5103).
+    pub fn get_reverse(&self, element: InformationElement) -> Option<&FieldValue> {
+        self.fields
+            .iter()
+            .find(|(specifier, _)| specifier.is_reverse() && element.id() == specifier.element_id)
+            .map(|(_, value)| value)
+    }
+
+    ///Fills the fields of the template with the values of a flow record.
+    ///
+    ///The timestamps of the flow record are interpreted as durations since the unix epoch. Fields without a
+    ///corresponding value in the flow record (or addresses of the other ip version) are set to zero. Values that don't
+    ///fit into the length of a field are saturated.
+    pub fn from_flow_record(template: &Template, record: &FlowRecord) -> DataRecord {
+        let fields = template.fields
+            .iter()
+            .map(|specifier| {
+                let value = flow_record_value(specifier, record).unwrap_or_else(|| FieldValue::zero(specifier));
+                (*specifier, value)
+            })
+            .collect();
+        DataRecord {
+            template_id: template.id,
+            fields,
+        }
+    }
+}
+
+///Returns the value of a flow record for the given field (None if the flow record contains no matching value).
+fn flow_record_value(specifier: &FieldSpecifier, record: &FlowRecord) -> Option<FieldValue> {
+    use self::InformationElement::*;
+    use crate::IpTrafficClass::{Icmp, IPv6Icmp};
+
+    let element = if specifier.is_reverse() {
+        InformationElement::from_id(specifier.element_id)?
+    } else {
+        specifier.information_element()?
+    };
+    let counters = if specifier.is_reverse() {
+        &record.reverse
+    } else {
+        &record.forward
+    };
+    let key = &record.key;
+    let unsigned = |value: u64| {
+        let length = usize::from(specifier.length);
+        let max = if length >= 8 { u64::MAX } else { (1u64 << (8*length)) - 1 };
+        Some(FieldValue::Unsigned(std::cmp::min(value, max)))
+    };
+    let icmp_type_code = |protocol: u8| if protocol == key.protocol {
+        unsigned(u64::from(key.destination_port))
+    } else {
+        None
+    };
+    let value = match (element, key.source, key.destination) {
+        (SourceIpv4Address, IpAddr::V4(value), _) => Some(FieldValue::Ipv4Address(value.octets())),
+        (DestinationIpv4Address, _, IpAddr::V4(value)) => Some(FieldValue::Ipv4Address(value.octets())),
+        (SourceIpv6Address, IpAddr::V6(value), _) => Some(FieldValue::Ipv6Address(value.octets())),
+        (DestinationIpv6Address, _, IpAddr::V6(value)) => Some(FieldValue::Ipv6Address(value.octets())),
+        (SourceMacAddress, _, _) => key.source_mac.map(FieldValue::MacAddress),
+        (DestinationMacAddress, _, _) => key.destination_mac.map(FieldValue::MacAddress),
+        (IpVersion, IpAddr::V4(_), _) => unsigned(4),
+        (IpVersion, IpAddr::V6(_), _) => unsigned(6),
+        (SourceTransportPort, _, _) => unsigned(u64::from(key.source_port)),
+        (DestinationTransportPort, _, _) => unsigned(u64::from(key.destination_port)),
+        (ProtocolIdentifier, _, _) => unsigned(u64::from(key.protocol)),
+        (IcmpTypeCodeIpv4, _, _) => icmp_type_code(Icmp as u8),
+        (IcmpTypeCodeIpv6, _, _) => icmp_type_code(IPv6Icmp as u8),
+        (VlanId, _, _) | (Dot1qVlanId, _, _) => key.outer_vlan.and_then(|value| unsigned(u64::from(value))),
+        (Dot1qCustomerVlanId, _, _) => key.inner_vlan.and_then(|value| unsigned(u64::from(value))),
+        (OctetDeltaCount, _, _) => unsigned(counters.bytes),
+        (PacketDeltaCount, _, _) => unsigned(counters.packets),
+        (TcpControlBits, _, _) => unsigned(u64::from(counters.tcp_flags)),
+        (FlowStartSeconds, _, _) => unsigned(record.first_seen.as_secs()),
+        (FlowEndSeconds, _, _) => unsigned(record.last_seen.as_secs()),
+        (FlowStartMilliseconds, _, _) => unsigned(record.first_seen.as_millis() as u64),
+        (FlowEndMilliseconds, _, _) => unsigned(record.last_seen.as_millis() as u64),
+        (FlowEndReason, _, _) => record.end_reason.and_then(|value| unsigned(value as u64)),
+        _ => None
+    }?;
+    //only use the value if it can be encoded with the field length
+    let mut buffer = Vec::new();
+    value.encode(specifier, &mut buffer).map(|_| value)
+}
+
+///Header of a NetFlow v5 packet.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NetflowV5Header {
+    ///Number of flow records in the packet.
+    pub count: u16,
+    ///Milliseconds since the exporter booted.
+    pub sys_uptime: u32,
+    pub unix_secs: u32,
+    pub unix_nsecs: u32,
+    ///Sequence number of the first flow record in the packet.
+    pub flow_sequence: u32,
+    pub engine_type: u8,
+    pub engine_id: u8,
+    ///Sampling mode (first two bits) & sampling interval (remaining 14 bits).
+    pub sampling_interval: u16,
+}
+
+impl SerializedSize for NetflowV5Header {
+    ///Size of the header including the version field.
+    const SERIALIZED_SIZE: usize = 24;
+}
+
+///Flow record of a NetFlow v5 packet.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NetflowV5Record {
+    pub source: [u8;4],
+    pub destination: [u8;4],
+    pub next_hop: [u8;4],
+    ///SNMP index of the input interface.
+    pub input_interface: u16,
+    ///SNMP index of the output interface.
+    pub output_interface: u16,
+    pub packets: u32,
+    ///Number of bytes on the ip layer.
+    pub bytes: u32,
+    ///System uptime in milliseconds at the start of the flow.
+    pub first: u32,
+    ///System uptime in milliseconds at the last packet of the flow.
+    pub last: u32,
+    pub source_port: u16,
+    pub destination_port: u16,
+    ///Bitwise or of the tcp flags.
+    pub tcp_flags: u8,
+    pub protocol: u8,
+    pub tos: u8,
+    pub source_as: u16,
+    pub destination_as: u16,
+    pub source_mask: u8,
+    pub destination_mask: u8,
+}
+
+impl SerializedSize for NetflowV5Record {
+    const SERIALIZED_SIZE: usize = 48;
+}
+
+impl NetflowV5Record {
+    ///Decodes a record from a slice that is at least 48 bytes long.
+    fn from_slice(slice: &[u8]) -> NetflowV5Record {
+        let address = |offset: usize| [slice[offset], slice[offset + 1], slice[offset + 2], slice[offset + 3]];
+        NetflowV5Record {
+            source: address(0),
+            destination: address(4),
+            next_hop: address(8),
+            input_interface: BigEndian::read_u16(&slice[12..14]),
+            output_interface: BigEndian::read_u16(&slice[14..16]),
+            packets: BigEndian::read_u32(&slice[16..20]),
+            bytes: BigEndian::read_u32(&slice[20..24]),
+            first: BigEndian::read_u32(&slice[24..28]),
+            last: BigEndian::read_u32(&slice[28..32]),
+            source_port: BigEndian::read_u16(&slice[32..34]),
+            destination_port: BigEndian::read_u16(&slice[34..36]),
+            tcp_flags: slice[37],
+            protocol: slice[38],
+            tos: slice[39],
+            source_as: BigEndian::read_u16(&slice[40..42]),
+            destination_as: BigEndian::read_u16(&slice[42..44]),
+            source_mask: slice[44],
+            destination_mask: slice[45],
+        }
+    }
+}
+
+///NetFlow v5 packet (header & flow records).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NetflowV5Packet {
+    pub header: NetflowV5Header,
+    pub records: Vec<NetflowV5Record>,
+}
+
+impl NetflowV5Packet {
+    ///Decodes a NetFlow v5 packet (the UDP payload sent by the exporter).
+    pub fn from_slice(slice: &[u8]) -> Result<NetflowV5Packet, NetflowError> {
+        use self::NetflowError::*;
+
+        if slice.len() < NetflowV5Header::SERIALIZED_SIZE {
+            return Err(UnexpectedEndOfSlice(NetflowV5Header::SERIALIZED_SIZE));
+        }
+        let version = BigEndian::read_u16(&slice[..2]);
+        if NETFLOW_V5_VERSION != version {
+            return Err(UnsupportedVersion(version));
+        }
+        let header = NetflowV5Header {
+            count: BigEndian::read_u16(&slice[2..4]),
+            sys_uptime: BigEndian::read_u32(&slice[4..8]),
+            unix_secs: BigEndian::read_u32(&slice[8..12]),
+            unix_nsecs: BigEndian::read_u32(&slice[12..16]),
+            flow_sequence: BigEndian::read_u32(&slice[16..20]),
+            engine_type: slice[20],
+            engine_id: slice[21],
+            sampling_interval: BigEndian::read_u16(&slice[22..24]),
+        };
+        let expected_len = NetflowV5Header::SERIALIZED_SIZE + usize::from(header.count)*NetflowV5Record::SERIALIZED_SIZE;
+        if slice.len() < expected_len {
+            return Err(UnexpectedEndOfSlice(expected_len));
+        }
+        let records = slice[NetflowV5Header::SERIALIZED_SIZE..expected_len]
+            .chunks(NetflowV5Record::SERIALIZED_SIZE)
+            .map(NetflowV5Record::from_slice)
+            .collect();
+        Ok(NetflowV5Packet {
+            header,
+            records,
+        })
+    }
+}
+
+///Decoded NetFlow v9 or IPFIX message.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExportMessage {
+    ///Export time in seconds since the unix epoch.
+    pub export_time: u32,
+    ///Sequence number of the message (NetFlow v9) or of the first data record (IPFIX).
+    pub sequence_number: u32,
+    ///Observation domain id (IPFIX) or source id (NetFlow v9).
+    pub observation_domain_id: u32,
+    ///Milliseconds since the exporter booted (only present in NetFlow v9).
+    pub sys_uptime: Option<u32>,
+    ///Templates & options templates defined in the message.
+    pub templates: Vec<Template>,
+    ///Data records of all data sets with known templates.
+    pub records: Vec<DataRecord>,
+    ///Ids of the data sets that were skipped as their templates are unknown.
+    pub missing_templates: Vec<u16>,
+}
+
+///Decoded export packet.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ExportPacket {
+    NetflowV5(NetflowV5Packet),
+    NetflowV9(ExportMessage),
+    Ipfix(ExportMessage),
+}
+
+///Identifies the templates of an observation domain of an exporter.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+struct TemplateScope {
+    exporter: SocketAddr,
+    version: u16,
+    observation_domain_id: u32,
+}
+
+///Decoder of export packets keeping the NetFlow v9 & IPFIX templates of all exporters.
+#[derive(Clone, Debug, Default)]
+pub struct ExportDecoder {
+    templates: HashMap<TemplateScope, BTreeMap<u16, Template>>,
+}
+
+impl ExportDecoder {
+    pub fn new() -> ExportDecoder {
+        ExportDecoder::default()
+    }
+
+    ///Number of stored templates (of all exporters).
+    pub fn template_count(&self) -> usize {
+        self.templates.values().map(|value| value.len()).sum()
+    }
+
+    ///Returns a stored template.
+    pub fn template(&self, exporter: SocketAddr, version: u16, observation_domain_id: u32, template_id: u16) -> Option<&Template> {
+        let scope = TemplateScope {
+            exporter,
+            version,
+            observation_domain_id,
+        };
+        self.templates.get(&scope).and_then(|value| value.get(&template_id))
+    }
+
+    ///Removes all templates of an exporter (e.g. after it was restarted).
+    pub fn remove_exporter(&mut self, exporter: SocketAddr) {
+        self.templates.retain(|scope, _| scope.exporter != exporter);
+    }
+
+    ///Decodes an export packet (UDP payload) sent by the given exporter.
+    ///
+    ///The templates contained in NetFlow v9 & IPFIX packets are stored and used to decode the data sets of the same
+    ///and of later packets of the exporter & observation domain.
+    pub fn decode(&mut self, exporter: SocketAddr, slice: &[u8]) -> Result<ExportPacket, NetflowError> {
+        use self::NetflowError::*;
+
+        if slice.len() < 2 {
+            return Err(UnexpectedEndOfSlice(2));
+        }
+        match BigEndian::read_u16(&slice[..2]) {
+            NETFLOW_V5_VERSION => Ok(ExportPacket::NetflowV5(NetflowV5Packet::from_slice(slice)?)),
+            NETFLOW_V9_VERSION => {
+                if slice.len() < NETFLOW_V9_HEADER_LEN {
+                    return Err(UnexpectedEndOfSlice(NETFLOW_V9_HEADER_LEN));
+                }
+                let mut message = ExportMessage {
+                    export_time: BigEndian::read_u32(&slice[8..12]),
+                    sequence_number: BigEndian::read_u32(&slice[12..16]),
+                    observation_domain_id: BigEndian::read_u32(&slice[16..20]),
+                    sys_uptime: Some(BigEndian::read_u32(&slice[4..8])),
+                    templates: Vec::new(),
+                    records: Vec::new(),
+                    missing_templates: Vec::new(),
+                };
+                let scope = TemplateScope {
+                    exporter,
+                    version: NETFLOW_V9_VERSION,
+                    observation_domain_id: message.observation_domain_id,
+                };
+                self.decode_sets(scope, &slice[NETFLOW_V9_HEADER_LEN..], &mut message)?;
+                Ok(ExportPacket::NetflowV9(message))
+            },
+            IPFIX_VERSION => {
+                if slice.len() < IPFIX_HEADER_LEN {
+                    return Err(UnexpectedEndOfSlice(IPFIX_HEADER_LEN));
+                }
+                let length = BigEndian::read_u16(&slice[2..4]);
+                if usize::from(length) < IPFIX_HEADER_LEN {
+                    return Err(InvalidMessageLength(length));
+                }
+                if slice.len() < usize::from(length) {
+                    return Err(UnexpectedEndOfSlice(usize::from(length)));
+                }
+                let mut message = ExportMessage {
+                    export_time: BigEndian::read_u32(&slice[4..8]),
+                    sequence_number: BigEndian::read_u32(&slice[8..12]),
+                    observation_domain_id: BigEndian::read_u32(&slice[12..16]),
+                    sys_uptime: None,
+                    templates: Vec::new(),
+                    records: Vec::new(),
+                    missing_templates: Vec::new(),
+                };
+                let scope = TemplateScope {
+                    exporter,
+                    version: IPFIX_VERSION,
+                    observation_domain_id: message.observation_domain_id,
+                };
+                self.decode_sets(scope, &slice[IPFIX_HEADER_LEN..usize::from(length)], &mut message)?;
+                Ok(ExportPacket::Ipfix(message))
+            },
+            version => Err(UnsupportedVersion(version))
+        }
+    }
+
+    ///Decodes the sets (NetFlow v9 flow sets) following the message header.
+    fn decode_sets(&mut self, scope: TemplateScope, mut rest: &[u8], message: &mut ExportMessage) -> Result<(), NetflowError> {
+        let (template_set, options_template_set) = if NETFLOW_V9_VERSION == scope.version {
+            (NETFLOW_V9_TEMPLATE_SET, NETFLOW_V9_OPTIONS_TEMPLATE_SET)
+        } else {
+            (IPFIX_TEMPLATE_SET, IPFIX_OPTIONS_TEMPLATE_SET)
+        };
+        //trailing bytes shorter then a set header are treated as padding
+        while rest.len() >= 4 {
+            let set_id = BigEndian::read_u16(&rest[..2]);
+            let length = BigEndian::read_u16(&rest[2..4]);
+            if usize::from(length) < 4 || usize::from(length) > rest.len() {
+                return Err(NetflowError::InvalidSetLength{ set_id, length });
+            }
+            let content = &rest[4..usize::from(length)];
+            rest = &rest[usize::from(length)..];
+
+            if set_id == template_set || set_id == options_template_set {
+                let templates = decode_templates(scope.version, set_id == options_template_set, content)?;
+                let stored = self.templates.entry(scope).or_default();
+                for (template, withdrawn) in templates {
+                    if !withdrawn {
+                        stored.insert(template.id, template.clone());
+                        message.templates.push(template);
+                    } else if template.id == set_id {
+                        //withdrawal of all templates of the set type
+                        stored.retain(|_, value| value.is_options_template() != (set_id == options_template_set));
+                    } else {
+                        stored.remove(&template.id);
+                    }
+                }
+            } else if set_id >= MIN_DATA_SET_ID {
+                let template = self.templates.get(&scope).and_then(|value| value.get(&set_id));
+                match template {
+                    Some(template) => decode_data_records(template, content, &mut message.records),
+                    None => if !message.missing_templates.contains(&set_id) {
+                        message.missing_templates.push(set_id);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+///Decodes the templates of a template or options template set (the flag is true for template withdrawals).
+fn decode_templates(version: u16, options: bool, mut content: &[u8]) -> Result<Vec<(Template, bool)>, NetflowError> {
+    use self::NetflowError::*;
+
+    let ipfix = IPFIX_VERSION == version;
+    let mut result = Vec::new();
+    //remaining bytes shorter then a template header are padding
+    let min_header_len = if options && !ipfix { 6 } else { 4 };
+    while content.len() >= min_header_len {
+        let id = BigEndian::read_u16(&content[..2]);
+        let (field_count, scope_field_count, header_len) = if options && !ipfix {
+            //netflow v9 options templates contain the lengths of the scope & option fields in bytes
+            let scope_len = BigEndian::read_u16(&content[2..4]) / 4;
+            let option_len = BigEndian::read_u16(&content[4..6]) / 4;
+            (scope_len + option_len, scope_len, 6)
+        } else {
+            let field_count = BigEndian::read_u16(&content[2..4]);
+            if ipfix && 0 == field_count {
+                result.push((Template{ id, scope_field_count: 0, fields: Vec::new() }, true));
+                content = &content[4..];
+                continue;
+            }
+            if options {
+                if content.len() < 6 {
+                    return Err(InvalidTemplate(id));
+                }
+                (field_count, BigEndian::read_u16(&content[4..6]), 6)
+            } else {
+                (field_count, 0, 4)
+            }
+        };
+        let missing_scope = options && (0 == scope_field_count || scope_field_count > field_count);
+        if id < MIN_DATA_SET_ID || missing_scope {
+            return Err(InvalidTemplate(id));
+        }
+        content = &content[header_len..];
+
+        let mut fields = Vec::with_capacity(usize::from(field_count));
+        for _ in 0..field_count {
+            if content.len() < 4 {
+                return Err(InvalidTemplate(id));
+            }
+            let element_id = BigEndian::read_u16(&content[..2]);
+            let length = BigEndian::read_u16(&content[2..4]);
+            content = &content[4..];
+            let enterprise_number = if ipfix && 0 != element_id & 0x8000 {
+                if content.len() < 4 {
+                    return Err(InvalidTemplate(id));
+                }
+                let value = BigEndian::read_u32(&content[..4]);
+                content = &content[4..];
+                Some(value)
+            } else {
+                None
+            };
+            fields.push(FieldSpecifier {
+                element_id: if ipfix { element_id & 0x7fff } else { element_id },
+                length,
+                enterprise_number,
+            });
+        }
+        //records of templates without data would never advance the decoding of a data set
+        if fields.iter().all(|field| 0 == field.length) {
+            return Err(InvalidTemplate(id));
+        }
+        result.push((Template{ id, scope_field_count, fields }, false));
+
+        //netflow v9 options template sets contain only one template followed by padding
+        if options && !ipfix {
+            break;
+        }
+    }
+    Ok(result)
+}
+
+///Decodes the records of a data set (trailing bytes that don't contain a complete record are treated as padding).
+fn decode_data_records(template: &Template, mut content: &[u8], records: &mut Vec<DataRecord>) {
+    if template.fields.is_empty() {
+        return;
+    }
+    while let Some((record, len)) = decode_data_record(template, content) {
+        records.push(record);
+        content = &content[len..];
+    }
+}
+
+///Decodes a data record & returns it together with its length (None if the slice is too short).
+fn decode_data_record(template: &Template, slice: &[u8]) -> Option<(DataRecord, usize)> {
+    let mut offset = 0;
+    let mut fields = Vec::with_capacity(template.fields.len());
+    for specifier in &template.fields {
+        let length = if VARIABLE_LENGTH == specifier.length {
+            let first = *slice.get(offset)?;
+            if 255 == first {
+                let value = slice.get(offset + 1..offset + 3)?;
+                offset += 3;
+                usize::from(BigEndian::read_u16(value))
+            } else {
+                offset += 1;
+                usize::from(first)
+            }
+        } else {
+            usize::from(specifier.length)
+        };
+        let bytes = slice.get(offset..offset + length)?;
+        offset += length;
+        fields.push((*specifier, FieldValue::decode(specifier, bytes)));
+    }
+    Some((DataRecord{ template_id: template.id, fields }, offset))
+}
+
+///Encoder of IPFIX messages.
+#[derive(Clone, Debug)]
+pub struct IpfixWriter {
+    observation_domain_id: u32,
+    sequence_number: u32,
+    templates: BTreeMap<u16, Template>,
+}
+
+impl IpfixWriter {
+    pub fn new(observation_domain_id: u32) -> IpfixWriter {
+        IpfixWriter {
+            observation_domain_id,
+            sequence_number: 0,
+            templates: BTreeMap::new(),
+        }
+    }
+
+    pub fn observation_domain_id(&self) -> u32 {
+        self.observation_domain_id
+    }
+
+    ///Sequence number of the next message (number of data records written so far modulo 2^32).
+    pub fn sequence_number(&self) -> u32 {
+        self.sequence_number
+    }
+
+    ///Adds or replaces a template that can be used by data records.
+    pub fn add_template(&mut self, template: Template) -> Result<(), NetflowError> {
+        let is_valid = template.id >= MIN_DATA_SET_ID &&
+            template.fields.iter().any(|field| 0 != field.length) &&
+            usize::from(template.scope_field_count) <= template.fields.len();
+        if !is_valid {
+            return Err(NetflowError::InvalidTemplate(template.id));
+        }
+        self.templates.insert(template.id, template);
+        Ok(())
+    }
+
+    ///Iterates over the added templates (ordered by their id).
+    pub fn templates(&self) -> impl Iterator<Item = &Template> {
+        self.templates.values()
+    }
+
+    ///Encodes an IPFIX message containing the data records (optionally preceded by all templates).
+    ///
+    ///Consecutive records with the same template are written into one data set. The fields of the records have to
+    ///match the fields of their templates. Templates should be included in the first message & periodically
+    ///afterwards (as required for UDP transport).
+    pub fn write_message(&mut self, export_time: u32, include_templates: bool, records: &[DataRecord]) -> Result<Vec<u8>, NetflowError> {
+        let mut result = Vec::with_capacity(512);
+        result.extend_from_slice(&IPFIX_VERSION.to_be_bytes());
+        //length is set after all sets were written
+        result.extend_from_slice(&[0, 0]);
+        result.extend_from_slice(&export_time.to_be_bytes());
+        result.extend_from_slice(&self.sequence_number.to_be_bytes());
+        result.extend_from_slice(&self.observation_domain_id.to_be_bytes());
+
+        if include_templates {
+            for (set_id, options) in &[(IPFIX_TEMPLATE_SET, false), (IPFIX_OPTIONS_TEMPLATE_SET, true)] {
+                let mut templates = self.templates.values().filter(|value| value.is_options_template() == *options).peekable();
+                if templates.peek().is_none() {
+                    continue;
+                }
+                let set_start = start_set(&mut result, *set_id);
+                for template in templates {
+                    write_template(template, &mut result);
+                }
+                end_set(&mut result, set_start)?;
+            }
+        }
+
+        let mut set_start = None;
+        for (index, record) in records.iter().enumerate() {
+            let template = self.templates.get(&record.template_id).ok_or(NetflowError::UnknownTemplate(record.template_id))?;
+            let same_set = index > 0 && records[index - 1].template_id == record.template_id;
+            if !same_set {
+                if let Some(start) = set_start {
+                    end_set(&mut result, start)?;
+                }
+                set_start = Some(start_set(&mut result, record.template_id));
+            }
+            write_data_record(template, record, &mut result)?;
+        }
+        if let Some(start) = set_start {
+            end_set(&mut result, start)?;
+        }
+
+        if result.len() > usize::from(u16::MAX) {
+            return Err(NetflowError::MessageTooLarge(result.len()));
+        }
+        let length = result.len() as u16;
+        BigEndian::write_u16(&mut result[2..4], length);
+        self.sequence_number = self.sequence_number.wrapping_add(records.len() as u32);
+        Ok(result)
+    }
+}
+
+///Writes a set header with a placeholder length & returns the start of the set.
+fn start_set(target: &mut Vec<u8>, set_id: u16) -> usize {
+    let start = target.len();
+    target.extend_from_slice(&set_id.to_be_bytes());
+    target.extend_from_slice(&[0, 0]);
+    start
+}
+
+///Sets the length of the set starting at the given offset.
+fn end_set(target: &mut [u8], start: usize) -> Result<(), NetflowError> {
+    let length = target.len() - start;
+    if length > usize::from(u16::MAX) {
+        return Err(NetflowError::MessageTooLarge(target.len()));
+    }
+    BigEndian::write_u16(&mut target[start + 2..start + 4], length as u16);
+    Ok(())
+}
+
+fn write_template(template: &Template, target: &mut Vec<u8>) {
+    target.extend_from_slice(&template.id.to_be_bytes());
+    target.extend_from_slice(&(template.fields.len() as u16).to_be_bytes());
+    if template.is_options_template() {
+        target.extend_from_slice(&template.scope_field_count.to_be_bytes());
+    }
+    for field in &template.fields {
+        match field.enterprise_number {
+            Some(enterprise_number) => {
+                target.extend_from_slice(&(field.element_id | 0x8000).to_be_bytes());
+                target.extend_from_slice(&field.length.to_be_bytes());
+                target.extend_from_slice(&enterprise_number.to_be_bytes());
+            },
+            None => {
+                target.extend_from_slice(&field.element_id.to_be_bytes());
+                target.extend_from_slice(&field.length.to_be_bytes());
+            }
+        }
+    }
+}
+
+fn write_data_record(template: &Template, record: &DataRecord, target: &mut Vec<u8>) -> Result<(), NetflowError> {
+    let invalid = |element_id| NetflowError::InvalidFieldValue{ template_id: template.id, element_id };
+    if record.fields.len() != template.fields.len() {
+        let element_id = template.fields.get(record.fields.len()).map(|value| value.element_id).unwrap_or(0);
+        return Err(invalid(element_id));
+    }
+    for (specifier, (record_specifier, value)) in template.fields.iter().zip(&record.fields) {
+        if specifier != record_specifier {
+            return Err(invalid(record_specifier.element_id));
+        }
+        value.encode(specifier, target).ok_or_else(|| invalid(specifier.element_id))?;
+    }
+    Ok(())
+}
@@ -79,7 +79,9 @@ impl PacketBuilder {
                 }),
                 vlan_header: None,
                 ip_header: None,
-                transport_header: None
+                transport_header: None,
+                tunnel_header: None,
+                outer_ip_headers: Vec::new()
             },
             _marker: marker::PhantomData::<Ethernet2Header>{}
         }
@@ -117,7 +119,9 @@ impl PacketBuilder {
                 ethernet2_header: None,
                 vlan_header: None,
                 ip_header: None,
-                transport_header: None
+                transport_header: None,
+                tunnel_header: None,
+                outer_ip_headers: Vec::new()
             },
             _marker: marker::PhantomData::<Ethernet2Header>{}
         }.ipv4(source, destination, time_to_live)
@@ -159,7 +163,9 @@ impl PacketBuilder {
                 ethernet2_header: None,
                 vlan_header: None,
                 ip_header: None,
-                transport_header: None
+                transport_header: None,
+                tunnel_header: None,
+                outer_ip_headers: Vec::new()
             },
             _marker: marker::PhantomData::<Ethernet2Header>{}
         }.ipv6(source, destination, hop_limit)
@@ -231,7 +237,9 @@ impl PacketBuilder {
                 ethernet2_header: None,
                 vlan_header: None,
                 ip_header: None,
-                transport_header: None
+                transport_header: None,
+                tunnel_header: None,
+                outer_ip_headers: Vec::new()
             },
             _marker: marker::PhantomData::<Ethernet2Header>{}
         }.ip(ip_header)
@@ -242,7 +250,10 @@ struct PacketImpl {
     ethernet2_header: Option<Ethernet2Header>,
     ip_header: Option<IpHeader>,
     vlan_header: Option<VlanHeader>,
-    transport_header: Option<TransportHeader>
+    transport_header: Option<TransportHeader>,
+    tunnel_header: Option<TunnelHeader>,
+    ///Ip headers encapsulating the packet (outermost first, the innermost ip header is stored in ip_header).
+    outer_ip_headers: Vec<IpHeader>
 }
 
 ///An unfinished packet that is build with the packet builder
@@ -453,6 +464,91 @@ impl PacketBuilderStep<VlanHeader> {
 }
 
 impl PacketBuilderStep<IpHeader> {
+    ///Adds an encapsulated ip header (length, protocol/next_header & checksum fields will be overwritten based on the rest of the packet).
+    ///
+    ///The previously added ip header becomes the outer ip header with the protocol set to IPv4 (4) or IPv6 (41)
+    ///depending on the version of the encapsulated ip header (IP-in-IP, 6in4, 4in6 & 6in6).
+    pub fn ip(mut self, ip_header: IpHeader) -> PacketBuilderStep<IpHeader> {
+        //the current ip header becomes an outer header
+        if let Some(outer) = self.state.ip_header.take() {
+            self.state.outer_ip_headers.push(outer);
+        }
+        //use the method from the Ethernet2Header implementation
+        PacketBuilderStep {
+            state: self.state,
+            _marker: marker::PhantomData::<Ethernet2Header>{}
+        }.ip(ip_header)
+    }
+
+    ///Adds an encapsulated ip v6 header (see [`PacketBuilderStep<IpHeader>::ip`](#method.ip) for details).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use etherparse::*;
+    /// #
+    /// //ipv6 packet encapsulated in an ipv4 packet (6in4)
+    /// let builder = PacketBuilder::
+    ///     ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+    ///    .ipv4([192,168,1,1], [192,168,1,2], 20)
+    ///    .ipv6([1;16], [2;16], 20)
+    ///    .udp(1234, 5678);
+    ///
+    /// let payload = [1,2,3,4];
+    /// let mut result = Vec::<u8>::with_capacity(builder.size(payload.len()));
+    /// builder.write(&mut result, &payload).unwrap();
+    /// ```
+    pub fn ipv6(mut self, source: [u8;16], destination: [u8;16], hop_limit: u8) -> PacketBuilderStep<IpHeader> {
+        //the current ip header becomes an outer header
+        if let Some(outer) = self.state.ip_header.take() {
+            self.state.outer_ip_headers.push(outer);
+        }
+        //use the method from the Ethernet2Header implementation
+        PacketBuilderStep {
+            state: self.state,
+            _marker: marker::PhantomData::<Ethernet2Header>{}
+        }.ipv6(source, destination, hop_limit)
+    }
+
+    ///Adds an encapsulated ip v4 header (see [`PacketBuilderStep<IpHeader>::ip`](#method.ip) for details).
+    pub fn ipv4(mut self, source: [u8;4], destination: [u8;4], time_to_live: u8) -> PacketBuilderStep<IpHeader> {
+        //the current ip header becomes an outer header
+        if let Some(outer) = self.state.ip_header.take() {
+            self.state.outer_ip_headers.push(outer);
+        }
+        //use the method from the Ethernet2Header implementation
+        PacketBuilderStep {
+            state: self.state,
+            _marker: marker::PhantomData::<Ethernet2Header>{}
+        }.ipv4(source, destination, time_to_live)
+    }
+
+    ///Set the options of the last added ipv4 header (padded with zeros to a multiple of 4 bytes).
+    ///
+    ///Returns `ValueError::Ipv4OptionsWithoutIpv4Header` if the last added ip header is an ipv6 header.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use etherparse::*;
+    /// #
+    /// let builder = PacketBuilder::
+    ///     ipv4([192,168,1,1], [224,0,0,22], 1)
+    ///    .ipv4_options(&[Ipv4OptionElement::RouterAlert(0)]).unwrap()
+    ///    .udp(1234, 5678);
+    ///
+    /// let payload = [1,2,3,4];
+    /// let mut result = Vec::<u8>::with_capacity(builder.size(payload.len()));
+    /// builder.write(&mut result, &payload).unwrap();
+    /// ```
+    pub fn ipv4_options(mut self, options: &[Ipv4OptionElement]) -> Result<PacketBuilderStep<IpHeader>, ValueError> {
+        match self.state.ip_header.as_mut() {
+            Some(IpHeader::Version4(header)) => header.set_option_elements(options)?,
+            _ => return Err(ValueError::Ipv4OptionsWithoutIpv4Header)
+        }
+        Ok(self)
+    }
+
     pub fn udp(mut self, source_port: u16, destination_port: u16) -> PacketBuilderStep<UdpHeader> {
         self.state.transport_header = Some(TransportHeader::Udp(UdpHeader{
             source_port,
@@ -480,6 +576,100 @@ impl PacketBuilderStep<IpHeader> {
 }
 
 impl PacketBuilderStep<UdpHeader> {
+    ///Adds a VXLAN header. The payload given to write is the encapsulated ethernet II frame.
+    ///
+    ///Note that the udp ports are not modified (the udp destination port of VXLAN is `VXLAN_PORT`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use etherparse::*;
+    /// #
+    /// //inner ethernet II frame
+    /// let inner_builder = PacketBuilder::
+    ///     ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+    ///    .ipv4([10,0,0,1], [10,0,0,2], 20)
+    ///    .udp(1234, 5678);
+    /// let mut inner = Vec::<u8>::with_capacity(inner_builder.size(4));
+    /// inner_builder.write(&mut inner, &[1,2,3,4]).unwrap();
+    ///
+    /// //outer packet encapsulating the inner frame
+    /// let builder = PacketBuilder::
+    ///     ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+    ///    .ipv4([192,168,1,1], [192,168,1,2], 20)
+    ///    .udp(49152, VXLAN_PORT)
+    ///    .vxlan(100); //vni
+    ///
+    /// let mut result = Vec::<u8>::with_capacity(builder.size(inner.len()));
+    /// builder.write(&mut result, &inner).unwrap();
+    /// ```
+    pub fn vxlan(mut self, vni: u32) -> PacketBuilderStep<VxlanHeader> {
+        self.state.tunnel_header = Some(TunnelHeader::Vxlan(VxlanHeader::new(vni)));
+        //return for next step
+        PacketBuilderStep {
+            state: self.state,
+            _marker: marker::PhantomData::<VxlanHeader>{}
+        }
+    }
+
+    ///Adds a Geneve header with the protocol type set to ethernet (`EtherType::TransparentEthernetBridging`).
+    ///The payload given to write is the encapsulated ethernet II frame.
+    ///
+    ///Note that the udp ports are not modified (the udp destination port of Geneve is `GENEVE_PORT`).
+    pub fn geneve(mut self, vni: u32) -> PacketBuilderStep<GeneveHeader> {
+        self.state.tunnel_header = Some(TunnelHeader::Geneve(
+            GeneveHeader::new(EtherType::TransparentEthernetBridging as u16, vni)
+        ));
+        //return for next step
+        PacketBuilderStep {
+            state: self.state,
+            _marker: marker::PhantomData::<GeneveHeader>{}
+        }
+    }
+
+    ///Adds a GTP-U header for a G-PDU message with the given tunnel endpoint identifier.
+    ///The payload given to write is the encapsulated ip packet.
+    ///
+    ///Note that the udp ports are not modified (the udp port of GTP-U is `GTPU_PORT`).
+    ///The length field of the GTP-U header is automatically set when the packet is written.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use etherparse::*;
+    /// #
+    /// //inner ip packet
+    /// let inner_builder = PacketBuilder::
+    ///     ipv4([10,0,0,1], [10,0,0,2], 20)
+    ///    .udp(1234, 5678);
+    /// let mut inner = Vec::<u8>::with_capacity(inner_builder.size(4));
+    /// inner_builder.write(&mut inner, &[1,2,3,4]).unwrap();
+    ///
+    /// //outer packet encapsulating the inner packet
+    /// let builder = PacketBuilder::
+    ///     ipv4([192,168,1,1], [192,168,1,2], 20)
+    ///    .udp(GTPU_PORT, GTPU_PORT)
+    ///    .gtpu(0x1234_5678) //tunnel endpoint identifier
+    ///    .sequence_number(1)
+    ///    .pdu_session_container(&GtpuPduSessionContainer {
+    ///         pdu_type: GtpuPduSessionContainer::UL_PDU_SESSION_INFORMATION,
+    ///         qos_flow_identifier: 9,
+    ///         paging_policy_presence: false,
+    ///         reflective_qos_indicator: false
+    ///    }).unwrap();
+    ///
+    /// let mut result = Vec::<u8>::with_capacity(builder.size(inner.len()));
+    /// builder.write(&mut result, &inner).unwrap();
+    /// ```
+    pub fn gtpu(mut self, teid: u32) -> PacketBuilderStep<GtpuHeader> {
+        self.state.tunnel_header = Some(TunnelHeader::Gtpu(GtpuHeader::new(teid)));
+        //return for next step
+        PacketBuilderStep {
+            state: self.state,
+            _marker: marker::PhantomData::<GtpuHeader>{}
+        }
+    }
+
     ///Write all the headers and the payload.
     pub fn write<T: io::Write + Sized>(self, writer: &mut T, payload: &[u8]) -> Result<(),WriteError> {
         final_write(self, writer, payload)
@@ -487,7 +677,84 @@ impl PacketBuilderStep<UdpHeader> {
 
     ///Returns the size of the packet when it is serialized
     pub fn size(&self, payload_size: usize) -> usize {
-        final_size(&self, payload_size)
+        final_size(self, payload_size)
+    }
+}
+
+impl PacketBuilderStep<VxlanHeader> {
+    ///Write all the headers and the encapsulated ethernet II frame.
+    pub fn write<T: io::Write + Sized>(self, writer: &mut T, payload: &[u8]) -> Result<(),WriteError> {
+        final_write(self, writer, payload)
+    }
+
+    ///Returns the size of the packet when it is serialized
+    pub fn size(&self, payload_size: usize) -> usize {
+        final_size(self, payload_size)
+    }
+}
+
+impl PacketBuilderStep<GeneveHeader> {
+    ///Set the geneve options of the header (setting the bytes directly).
+    pub fn options_raw(mut self, options: &[u8]) -> Result<PacketBuilderStep<GeneveHeader>, ValueError> {
+        match self.state.tunnel_header.as_mut() {
+            Some(TunnelHeader::Geneve(header)) => header.set_options_raw(options)?,
+            _ => unreachable!()
+        }
+        Ok(self)
+    }
+
+    ///Write all the headers and the encapsulated ethernet II frame.
+    pub fn write<T: io::Write + Sized>(self, writer: &mut T, payload: &[u8]) -> Result<(),WriteError> {
+        final_write(self, writer, payload)
+    }
+
+    ///Returns the size of the packet when it is serialized
+    pub fn size(&self, payload_size: usize) -> usize {
+        final_size(self, payload_size)
+    }
+}
+
+impl PacketBuilderStep<GtpuHeader> {
+    ///Returns a mutable reference to the gtpu header of the builder.
+    fn gtpu_header(&mut self) -> &mut GtpuHeader {
+        match self.state.tunnel_header.as_mut() {
+            Some(TunnelHeader::Gtpu(header)) => header,
+            _ => unreachable!()
+        }
+    }
+
+    ///Set the sequence number of the gtpu header (also sets the "S" flag).
+    pub fn sequence_number(mut self, sequence_number: u16) -> PacketBuilderStep<GtpuHeader> {
+        self.gtpu_header().sequence_number = Some(sequence_number);
+        self
+    }
+
+    ///Set the N-PDU number of the gtpu header (also sets the "PN" flag).
+    pub fn n_pdu_number(mut self, n_pdu_number: u8) -> PacketBuilderStep<GtpuHeader> {
+        self.gtpu_header().n_pdu_number = Some(n_pdu_number);
+        self
+    }
+
+    ///Appends an extension header with the given type & content to the gtpu header (see `GtpuHeader::add_extension_header`).
+    pub fn extension_header(mut self, extension_type: u8, content: &[u8]) -> Result<PacketBuilderStep<GtpuHeader>, ValueError> {
+        self.gtpu_header().add_extension_header(extension_type, content)?;
+        Ok(self)
+    }
+
+    ///Appends a "PDU Session Container" extension header to the gtpu header.
+    pub fn pdu_session_container(mut self, container: &GtpuPduSessionContainer) -> Result<PacketBuilderStep<GtpuHeader>, ValueError> {
+        self.gtpu_header().add_pdu_session_container(container)?;
+        Ok(self)
+    }
+
+    ///Write all the headers and the encapsulated ip packet.
+    pub fn write<T: io::Write + Sized>(self, writer: &mut T, payload: &[u8]) -> Result<(),WriteError> {
+        final_write(self, writer, payload)
+    }
+
+    ///Returns the size of the packet when it is serialized
+    pub fn size(&self, payload_size: usize) -> usize {
+        final_size(self, payload_size)
     }
 }
 
@@ -576,12 +843,88 @@ impl PacketBuilderStep<TcpHeader> {
     }
 }
 
+mod sealed {
+    pub trait Sealed {}
+}
+
+///Marker for the packet builder steps after which the packet is complete & can be written (transport
+///& tunnel headers). This trait is sealed and can not be implemented outside of etherparse.
+pub trait PacketBuilderFinalStep: sealed::Sealed {}
+
+impl sealed::Sealed for UdpHeader {}
+impl sealed::Sealed for TcpHeader {}
+impl sealed::Sealed for VxlanHeader {}
+impl sealed::Sealed for GeneveHeader {}
+impl sealed::Sealed for GtpuHeader {}
+
+impl PacketBuilderFinalStep for UdpHeader {}
+impl PacketBuilderFinalStep for TcpHeader {}
+impl PacketBuilderFinalStep for VxlanHeader {}
+impl PacketBuilderFinalStep for GeneveHeader {}
+impl PacketBuilderFinalStep for GtpuHeader {}
+
+impl<B: PacketBuilderFinalStep> PacketBuilderStep<B> {
+    ///Write all the headers and the payload split into ip fragments with a size of at most `mtu` bytes
+    ///(not counting the ethernet II & vlan headers, which are repeated in every fragment).
+    ///
+    ///The identification is set in the outermost ip header (ipv4) or in the inserted ipv6 fragmentation
+    ///headers (ipv6) and its version has to match the version of the outermost ip header. See
+    ///[`fragment_ipv4_packet`](fn.fragment_ipv4_packet.html) & [`fragment_ipv6_packet`](fn.fragment_ipv6_packet.html)
+    ///for details.
+    ///
+    ///The don't fragment flag of the outermost ipv4 header is not modified. As the ipv4 headers added via
+    ///`ipv4` have the don't fragment flag set, `FragmentationError::DontFragment` is returned for them if the
+    ///packet is larger then the mtu. Use `ip` with a header that has the flag cleared to allow fragmentation.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use etherparse::*;
+    /// let mut header = Ipv4Header::new(0, 20, IpTrafficClass::Udp, [192,168,1,1], [192,168,1,2]);
+    /// header.dont_fragment = false;
+    /// let fragments = PacketBuilder::ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+    ///     .ip(IpHeader::Version4(header))
+    ///     .udp(1234, 5678)
+    ///     .write_fragmented(1500, FragmentIdentification::Ipv4(42), &[0;4000])
+    ///     .unwrap();
+    ///
+    /// assert_eq!(3, fragments.len());
+    /// assert!(fragments.iter().all(|fragment| fragment.len() <= Ethernet2Header::SERIALIZED_SIZE + 1500));
+    /// ```
+    pub fn write_fragmented(self, mtu: usize, identification: FragmentIdentification, payload: &[u8]) -> Result<Vec<Vec<u8>>, FragmentationError> {
+        final_write_fragmented(self, mtu, identification, payload)
+    }
+}
+
 ///Write all the headers and the payload.
 fn final_write<T: io::Write + Sized, B>(builder: PacketBuilderStep<B>, writer: &mut T, payload: &[u8]) -> Result<(),WriteError> {
-    
+
+    //the tunnel header is part of the transport payload (serialize it
+    //together with the payload so the transport checksum can be calculated)
+    let tunneled_payload = match builder.state.tunnel_header {
+        Some(ref tunnel) => {
+            //set the length field of gtpu
+            let mut tunnel = tunnel.clone();
+            if let TunnelHeader::Gtpu(ref mut gtpu) = tunnel {
+                gtpu.set_payload_length(payload.len())?;
+            }
+
+            let mut buffer = Vec::with_capacity(tunnel.header_len() + payload.len());
+            tunnel.write(&mut buffer)?;
+            buffer.extend_from_slice(payload);
+            Some(buffer)
+        },
+        None => None
+    };
+    let payload = match tunneled_payload {
+        Some(ref value) => &value[..],
+        None => payload
+    };
+
     let ip_ether_type = {
         use crate::IpHeader::*;
-        match builder.state.ip_header {
+        //the outermost ip header defines the ether type
+        match builder.state.outer_ip_headers.first().or(builder.state.ip_header.as_ref()) {
             Some(Version4(_)) => EtherType::Ipv4 as u16,
             Some(Version6(_)) => EtherType::Ipv6 as u16,
             None => panic!("Missing ip header")
@@ -624,11 +967,97 @@ fn final_write<T: io::Write + Sized, B>(builder: PacketBuilderStep<B>, writer: &
     }
 
     //unpack the transport header
-    let mut transport = builder.state.transport_header.unwrap();
+    let transport = builder.state.transport_header.unwrap();
+    let ip_header = builder.state.ip_header.unwrap();
+
+    if builder.state.outer_ip_headers.is_empty() {
+        write_ip_and_transport(ip_header, transport, writer, payload)
+    } else {
+        //serialize the innermost packet and encapsulate it in the outer
+        //ip headers (starting with the innermost outer header)
+        let mut protocol = encapsulated_ip_protocol(&ip_header);
+        let mut packet = Vec::with_capacity(ip_header.header_len() + transport.header_len() + payload.len());
+        write_ip_and_transport(ip_header, transport, &mut packet, payload)?;
+
+        for outer in builder.state.outer_ip_headers.into_iter().rev() {
+            let next_protocol = encapsulated_ip_protocol(&outer);
+            let mut buffer = Vec::with_capacity(outer.header_len() + packet.len());
+            write_encapsulating_ip(outer, protocol, &mut buffer, &packet)?;
+            packet = buffer;
+            protocol = next_protocol;
+        }
+        writer.write_all(&packet)?;
+        Ok(())
+    }
+}
 
+///Write all headers & the payload and split the outermost ip packet into fragments.
+fn final_write_fragmented<B>(mut builder: PacketBuilderStep<B>, mtu: usize, identification: FragmentIdentification, payload: &[u8]) -> Result<Vec<Vec<u8>>, FragmentationError> {
+    //the link headers are repeated in every fragment
+    let link_len = builder.state.ethernet2_header.as_ref().map_or(0, |_| Ethernet2Header::SERIALIZED_SIZE) +
+        match builder.state.vlan_header {
+            Some(VlanHeader::Single(_)) => SingleVlanHeader::SERIALIZED_SIZE,
+            Some(VlanHeader::Double(_)) => DoubleVlanHeader::SERIALIZED_SIZE,
+            None => 0
+        };
+
+    //set the identification of the outermost ip header
+    let outermost = builder.state.outer_ip_headers.first_mut().or(builder.state.ip_header.as_mut());
+    match (outermost, identification) {
+        (Some(IpHeader::Version4(ip)), FragmentIdentification::Ipv4(value)) => {
+            ip.identification = value;
+        },
+        (Some(IpHeader::Version6(_)), FragmentIdentification::Ipv6(_)) => {},
+        _ => return Err(FragmentationError::IdentificationMismatch(identification))
+    }
+
+    let mut packet = Vec::with_capacity(final_size(&builder, payload.len()));
+    final_write(builder, &mut packet, payload)?;
+    let (link, ip) = packet.split_at(link_len);
+    let fragments = match identification {
+        FragmentIdentification::Ipv4(_) => fragment_ipv4_packet(ip, mtu)?,
+        FragmentIdentification::Ipv6(value) => fragment_ipv6_packet(ip, mtu, value)?
+    };
+    Ok(fragments.into_iter().map(|fragment| {
+        let mut result = Vec::with_capacity(link.len() + fragment.len());
+        result.extend_from_slice(link);
+        result.extend_from_slice(&fragment);
+        result
+    }).collect())
+}
+
+///Returns the protocol number used to signal that the given ip header is encapsulated in another ip header.
+fn encapsulated_ip_protocol(ip_header: &IpHeader) -> u8 {
+    match ip_header {
+        IpHeader::Version4(_) => IpTrafficClass::IPv4 as u8,
+        IpHeader::Version6(_) => IpTrafficClass::Ipv6 as u8
+    }
+}
+
+///Write an ip header that encapsulates the given ip packet.
+fn write_encapsulating_ip<T: io::Write + Sized>(ip_header: IpHeader, protocol: u8, writer: &mut T, packet: &[u8]) -> Result<(),WriteError> {
+    use crate::IpHeader::*;
+    match ip_header {
+        Version4(mut ip) => {
+            ip.set_payload_len(packet.len())?;
+            ip.protocol = protocol;
+            //write (will automatically calculate the checksum)
+            ip.write(writer)?
+        },
+        Version6(mut ip) => {
+            ip.set_payload_length(packet.len())?;
+            ip.next_header = protocol;
+            ip.write(writer)?
+        }
+    }
+    writer.write_all(packet)?;
+    Ok(())
+}
+
+///Write the ip header, transport header & payload.
+fn write_ip_and_transport<T: io::Write + Sized>(ip_header: IpHeader, mut transport: TransportHeader, writer: &mut T, payload: &[u8]) -> Result<(),WriteError> {
     //ip header
     use crate::IpHeader::*;
-    let ip_header = builder.state.ip_header.unwrap();
     match ip_header {
         Version4(mut ip) => {
             //set total length & udp payload length (ip checks that the payload length is ok)
@@ -703,7 +1132,11 @@ fn final_size<B>(builder: &PacketBuilderStep<B>, payload_size: usize) -> usize {
         Some(Udp(_)) => UdpHeader::SERIALIZED_SIZE,
         Some(Tcp(ref value)) => value.header_len() as usize,
         None => 0
-    } + payload_size
+    } + match builder.state.tunnel_header {
+        Some(ref value) => value.header_len(),
+        None => 0
+    } + builder.state.outer_ip_headers.iter().map(|value| value.header_len()).sum::<usize>()
+      + payload_size
 }
 
 #[cfg(test)]
@@ -719,7 +1152,9 @@ mod whitebox_tests {
                 ethernet2_header: None,
                 ip_header: None,
                 vlan_header: None,
-                transport_header: None
+                transport_header: None,
+                tunnel_header: None,
+                outer_ip_headers: Vec::new()
             },
             _marker: marker::PhantomData::<UdpHeader>{}
         }.size(0));
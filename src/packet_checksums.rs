@@ -0,0 +1,188 @@
+use super::*;
+
+///Result of the verification of a single checksum.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ChecksumStatus {
+    ///The checksum matches the checksummed data.
+    Valid,
+    ///The checksum does not match the checksummed data.
+    Invalid,
+    ///The layer is not present or has no checksum (ipv6 headers & udp over ipv4 with a checksum of 0).
+    NotPresent,
+    ///The checksum can not be verified as parts of the checksummed data are missing
+    ///(the packet was truncated or is an ip fragment).
+    Truncated,
+}
+
+///Result of the checksum verification of a packet (see [`SlicedPacket::verify_checksums`](struct.SlicedPacket.html#method.verify_checksums)
+///& [`PacketHeaders::verify_checksums`](struct.PacketHeaders.html#method.verify_checksums)).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PacketChecksums {
+    ///Status of the ipv4 header checksum (`NotPresent` for ipv6 headers & packets without an ip header).
+    pub ip: ChecksumStatus,
+    ///Status of the tcp or udp checksum (`NotPresent` if no transport header is present).
+    pub transport: ChecksumStatus,
+}
+
+impl PacketChecksums {
+    ///Returns true if at least one of the checksums is invalid.
+    pub fn contains_invalid(&self) -> bool {
+        ChecksumStatus::Invalid == self.ip ||
+        ChecksumStatus::Invalid == self.transport
+    }
+}
+
+impl<'a> SlicedPacket<'a> {
+    ///Verifies the ipv4 header checksum & the tcp or udp checksum of the packet.
+    ///
+    ///The length of the checksummed transport data is determined based on the ip headers
+    ///(udp length field for udp), data after the ip payload (e.g. ethernet padding) is ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use etherparse::*;
+    /// # let mut packet = Vec::new();
+    /// # PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+    /// #    .udp(1234, 5678)
+    /// #    .write(&mut packet, &[1,2,3,4]).unwrap();
+    /// let checksums = SlicedPacket::from_ip(&packet).unwrap().verify_checksums();
+    /// assert_eq!(ChecksumStatus::Valid, checksums.ip);
+    /// assert_eq!(ChecksumStatus::Valid, checksums.transport);
+    /// ```
+    pub fn verify_checksums(&self) -> PacketChecksums {
+        use crate::InternetSlice::*;
+
+        let ip = self.ip.as_ref().map(|ip| match ip {
+            Ipv4(header) => IpHeader::Version4(header.to_header()),
+            Ipv6(header, _) => IpHeader::Version6(header.to_header()),
+        });
+
+        //length of the transport header & payload & fragmentation state based on the ip header
+        let (segment_len, fragmented) = match &self.ip {
+            Some(Ipv4(header)) => (
+                //the ipv4 authentication header is not part of the transport data
+                usize::from(header.payload_len()).checked_sub(
+                    self.authentication_header.as_ref().map_or(0, |auth| auth.slice().len())
+                ),
+                header.more_fragments() || 0 != header.fragments_offset()
+            ),
+            Some(Ipv6(header, extensions)) => (
+                //the authentication header is part of the extension headers
+                usize::from(header.payload_length()).checked_sub(
+                    extensions.iter().flatten().map(|(_, ext)| ext.slice().len()).sum()
+                ),
+                extensions.iter().flatten().any(|(id, ext)| {
+                    //fragment offset & more fragments flag
+                    IpTrafficClass::IPv6FragmentationHeader as u8 == *id &&
+                    (0 != ext.slice()[2] || 0 != ext.slice()[3] & 0xf9)
+                })
+            ),
+            None => (None, false)
+        };
+
+        let transport = self.transport.as_ref().map(|transport| match transport {
+            TransportSlice::Udp(header) => TransportHeader::Udp(header.to_header()),
+            TransportSlice::Tcp(header) => TransportHeader::Tcp(header.to_header()),
+        });
+
+        verify_checksums(ip.as_ref(), segment_len, fragmented, transport.as_ref(), self.payload)
+    }
+}
+
+impl<'a> PacketHeaders<'a> {
+    ///Verifies the ipv4 header checksum & the tcp or udp checksum of the packet.
+    ///
+    ///The length of the checksummed transport data is determined based on the ip header
+    ///(udp length field for udp), data after the ip payload (e.g. ethernet padding) is ignored.
+    ///As the ipv6 extension headers are skipped during decoding, the transport data of
+    ///ipv6 packets containing extension headers is assumed to end at the end of the payload
+    ///and fragmentation is only detected based on the ipv4 header.
+    pub fn verify_checksums(&self) -> PacketChecksums {
+
+        let segment_len = match (&self.ip, &self.transport) {
+            (Some(IpHeader::Version4(header)), _) => Some(usize::from(header.payload_len)),
+            (Some(IpHeader::Version6(header)), Some(transport)) => {
+                use crate::TransportHeader::*;
+                let protocol = match transport {
+                    Udp(_) => IpTrafficClass::Udp as u8,
+                    Tcp(_) => IpTrafficClass::Tcp as u8,
+                };
+                if protocol == header.next_header {
+                    Some(usize::from(header.payload_length))
+                } else {
+                    Some(transport.header_len() + self.payload.len())
+                }
+            },
+            _ => None
+        };
+
+        let fragmented = match &self.ip {
+            Some(IpHeader::Version4(header)) => header.more_fragments || 0 != header.fragments_offset,
+            _ => false
+        };
+
+        verify_checksums(self.ip.as_ref(), segment_len, fragmented, self.transport.as_ref(), self.payload)
+    }
+}
+
+///Verifies the checksums based on the decoded headers.
+///
+///`segment_len` is the length of the transport header & payload as indicated by the ip header.
+fn verify_checksums(ip: Option<&IpHeader>, segment_len: Option<usize>, fragmented: bool, transport: Option<&TransportHeader>, payload: &[u8]) -> PacketChecksums {
+    use crate::ChecksumStatus::*;
+
+    let compare = |calculated: Result<u16, ValueError>, checksum: u16| -> ChecksumStatus {
+        match calculated {
+            Ok(value) if value == checksum => Valid,
+            _ => Invalid
+        }
+    };
+
+    let ip_status = match ip {
+        Some(IpHeader::Version4(header)) => compare(header.calc_header_checksum(), header.header_checksum),
+        _ => NotPresent
+    };
+
+    let transport_status = match (ip, transport) {
+        (Some(ip), Some(transport)) => {
+            use crate::TransportHeader::*;
+
+            //length of the checksummed payload
+            let payload_len = match transport {
+                Udp(header) => usize::from(header.length).checked_sub(UdpHeader::SERIALIZED_SIZE),
+                Tcp(header) => segment_len.and_then(|len| len.checked_sub(usize::from(header.header_len()))),
+            };
+
+            match (ip, transport, payload_len) {
+                //udp over ipv4 without checksum
+                (IpHeader::Version4(_), Udp(header), _) if 0 == header.checksum => NotPresent,
+                //lengths in the headers are inconsistent
+                (_, _, None) => Invalid,
+                (_, _, Some(len)) if fragmented || payload.len() < len => Truncated,
+                (IpHeader::Version4(ip), Udp(header), Some(len)) => compare(
+                    header.calc_checksum_ipv4_raw(ip.source, ip.destination, IpTrafficClass::Udp as u8, &payload[..len]),
+                    header.checksum
+                ),
+                (IpHeader::Version6(ip), Udp(header), Some(len)) => compare(
+                    header.calc_checksum_ipv6(ip, &payload[..len]),
+                    header.checksum
+                ),
+                (IpHeader::Version4(ip), Tcp(header), Some(len)) => compare(
+                    header.calc_checksum_ipv4(ip, &payload[..len]),
+                    header.checksum
+                ),
+                (IpHeader::Version6(ip), Tcp(header), Some(len)) => compare(
+                    header.calc_checksum_ipv6(ip, &payload[..len]),
+                    header.checksum
+                ),
+            }
+        },
+        _ => NotPresent
+    };
+
+    PacketChecksums {
+        ip: ip_status,
+        transport: transport_status
+    }
+}
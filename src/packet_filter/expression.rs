@@ -0,0 +1,432 @@
+use super::*;
+
+use std::net::IpAddr;
+use std::ops::RangeInclusive;
+
+///Addresses or ports of a packet a filter primitive is applied to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Direction {
+    Source,
+    Destination,
+    ///Matches if the source or the destination matches.
+    SourceOrDestination,
+    ///Matches if the source and the destination match.
+    SourceAndDestination,
+}
+
+impl Direction {
+    ///Applies the check to the source and/or destination value depending on the direction.
+    fn matches<T, F: Fn(T) -> bool>(self, source: T, destination: T, check: F) -> bool {
+        use self::Direction::*;
+        match self {
+            Source => check(source),
+            Destination => check(destination),
+            SourceOrDestination => check(source) || check(destination),
+            SourceAndDestination => check(source) && check(destination),
+        }
+    }
+}
+
+///IPv4 or IPv6 address prefix (CIDR notation, e.g. 10.0.0.0/8).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IpPrefix {
+    pub address: IpAddr,
+    ///Number of leading bits of the address that have to match.
+    pub prefix_len: u8,
+}
+
+impl IpPrefix {
+    ///Creates a prefix (returns None if the prefix length is bigger then the address length).
+    pub fn new(address: IpAddr, prefix_len: u8) -> Option<IpPrefix> {
+        let max_len = match address {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        if prefix_len > max_len {
+            None
+        } else {
+            Some(IpPrefix{ address, prefix_len })
+        }
+    }
+
+    ///Creates a prefix matching only the given address.
+    pub fn host(address: IpAddr) -> IpPrefix {
+        IpPrefix {
+            address,
+            prefix_len: match address {
+                IpAddr::V4(_) => 32,
+                IpAddr::V6(_) => 128,
+            }
+        }
+    }
+
+    ///Returns true if the given address (4 or 16 bytes) is part of the prefix.
+    ///
+    ///Addresses of the other ip version never match.
+    pub fn contains(&self, address: &[u8]) -> bool {
+        let octets = self.octets();
+        if octets.len() != address.len() {
+            return false;
+        }
+        let full_bytes = usize::from(self.prefix_len / 8);
+        let remaining_bits = self.prefix_len % 8;
+        if octets[..full_bytes] != address[..full_bytes] {
+            return false;
+        }
+        if 0 == remaining_bits {
+            true
+        } else {
+            let mask = 0xffu8 << (8 - remaining_bits);
+            (octets[full_bytes] & mask) == (address[full_bytes] & mask)
+        }
+    }
+
+    ///Returns true if address bits after the prefix length are set.
+    pub fn has_host_bits(&self) -> bool {
+        let octets = self.octets();
+        let full_bytes = usize::from(self.prefix_len / 8);
+        let remaining_bits = self.prefix_len % 8;
+        octets.iter().enumerate().skip(full_bytes).any(|(index, value)| {
+            if index == full_bytes {
+                0 != value & (0xffu8 >> remaining_bits)
+            } else {
+                0 != *value
+            }
+        })
+    }
+
+    ///Address bytes in network byte order.
+    fn octets(&self) -> Vec<u8> {
+        match self.address {
+            IpAddr::V4(value) => value.octets().to_vec(),
+            IpAddr::V6(value) => value.octets().to_vec(),
+        }
+    }
+}
+
+///Tree of filter primitives combined with boolean operators.
+///
+///Expression trees can be build directly or parsed from a tcpdump style expression via
+///[`FilterExpression::parse`](#method.parse) (see the [module documentation](index.html) for the supported syntax).
+///
+///Expressions are evaluated recursively. Trees that are build directly should stay below the nesting depth allowed for
+///parsed expressions ([`FILTER_MAX_NESTING_DEPTH`](constant.FILTER_MAX_NESTING_DEPTH.html)).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FilterExpression {
+    ///Matches all packets (result of an empty expression).
+    Any,
+    And(Box<FilterExpression>, Box<FilterExpression>),
+    Or(Box<FilterExpression>, Box<FilterExpression>),
+    Not(Box<FilterExpression>),
+    ///Ether type following the ethernet & vlan headers.
+    ///
+    ///For packets without an ethernet header the ether type is derived from the ip header.
+    EtherType(u16),
+    EthernetAddress {
+        direction: Direction,
+        address: [u8;6],
+    },
+    ///Packets with a vlan tag.
+    ///
+    ///If identifiers and/or priorities are given, the outer or inner tag has to have an identifier & priority code
+    ///point in the ranges.
+    Vlan {
+        identifiers: Option<RangeInclusive<u16>>,
+        priorities: Option<RangeInclusive<u8>>,
+    },
+    ///Packets with an ip address in the prefix (packets of the other ip version don't match).
+    IpAddress {
+        direction: Direction,
+        prefix: IpPrefix,
+    },
+    ///Ip protocol number of the transport layer (following the ipv6 extension headers & authentication header).
+    IpProtocol(u8),
+    ///TCP or UDP port in the range (protocol None matches both).
+    Port {
+        protocol: Option<u8>,
+        direction: Direction,
+        ports: RangeInclusive<u16>,
+    },
+    ///TCP packets with the flags (byte 13 of the tcp header) equal to the value after applying the mask.
+    TcpFlags {
+        mask: u8,
+        value: u8,
+    },
+    ///Length of the payload following the last decoded header (see `SlicedPacket::payload`).
+    PayloadLength(RangeInclusive<usize>),
+    ///Packets matching a field based filter.
+    Filter(Filter),
+}
+
+///Flag bits of the tcp header byte containing the flags (for `FilterExpression::TcpFlags`).
+pub const TCP_FLAG_FIN: u8 = 0x01;
+pub const TCP_FLAG_SYN: u8 = 0x02;
+pub const TCP_FLAG_RST: u8 = 0x04;
+pub const TCP_FLAG_PSH: u8 = 0x08;
+pub const TCP_FLAG_ACK: u8 = 0x10;
+pub const TCP_FLAG_URG: u8 = 0x20;
+pub const TCP_FLAG_ECE: u8 = 0x40;
+pub const TCP_FLAG_CWR: u8 = 0x80;
+
+impl From<Filter> for FilterExpression {
+    fn from(value: Filter) -> FilterExpression {
+        FilterExpression::Filter(value)
+    }
+}
+
+impl FilterExpression {
+    ///Returns an expression that matches if both expressions match.
+    pub fn and(self, other: FilterExpression) -> FilterExpression {
+        FilterExpression::And(Box::new(self), Box::new(other))
+    }
+
+    ///Returns an expression that matches if one of the expressions matches.
+    pub fn or(self, other: FilterExpression) -> FilterExpression {
+        FilterExpression::Or(Box::new(self), Box::new(other))
+    }
+
+    ///Returns an expression that matches if this expression does not match.
+    pub fn negate(self) -> FilterExpression {
+        FilterExpression::Not(Box::new(self))
+    }
+
+    ///Returns true if the given sliced packet matches the expression.
+    pub fn applies_to_slice(&self, slice: &SlicedPacket) -> bool {
+        use self::FilterExpression::*;
+        match self {
+            Any => true,
+            And(left, right) => left.applies_to_slice(slice) && right.applies_to_slice(slice),
+            Or(left, right) => left.applies_to_slice(slice) || right.applies_to_slice(slice),
+            Not(value) => !value.applies_to_slice(slice),
+            EtherType(ether_type) => Some(*ether_type) == slice_ether_type(slice),
+            EthernetAddress{ direction, address } => match &slice.link {
+                Some(LinkSlice::Ethernet2(header)) => direction.matches(
+                    header.source(),
+                    header.destination(),
+                    |value| value == &address[..]
+                ),
+                _ => false
+            },
+            Vlan{ identifiers, priorities } => {
+                let tag_matches = |header: SingleVlanHeaderSlice| {
+                    let identifier_matches = match identifiers {
+                        Some(range) => range.contains(&header.vlan_identifier()),
+                        None => true
+                    };
+                    let priority_matches = match priorities {
+                        Some(range) => range.contains(&header.priority_code_point()),
+                        None => true
+                    };
+                    identifier_matches && priority_matches
+                };
+                match &slice.vlan {
+                    Some(VlanSlice::SingleVlan(header)) => tag_matches(header.clone()),
+                    Some(VlanSlice::DoubleVlan(header)) => tag_matches(header.outer()) || tag_matches(header.inner()),
+                    None => false
+                }
+            },
+            IpAddress{ direction, prefix } => match &slice.ip {
+                Some(InternetSlice::Ipv4(header)) => direction.matches(
+                    header.source(),
+                    header.destination(),
+                    |value| prefix.contains(value)
+                ),
+                Some(InternetSlice::Ipv6(header, _)) => direction.matches(
+                    header.source(),
+                    header.destination(),
+                    |value| prefix.contains(value)
+                ),
+                None => false
+            },
+            IpProtocol(protocol) => Some(*protocol) == slice.transport_ip_protocol(),
+            Port{ protocol, direction, ports } => {
+                let transport_ports = match &slice.transport {
+                    Some(TransportSlice::Tcp(header)) => Some((IpTrafficClass::Tcp as u8, header.source_port(), header.destination_port())),
+                    Some(TransportSlice::Udp(header)) => Some((IpTrafficClass::Udp as u8, header.source_port(), header.destination_port())),
+                    None => None
+                };
+                match transport_ports {
+                    Some((transport_protocol, source, destination)) => {
+                        let protocol_matches = match protocol {
+                            Some(value) => *value == transport_protocol,
+                            None => true
+                        };
+                        protocol_matches && direction.matches(source, destination, |value| ports.contains(&value))
+                    },
+                    None => false
+                }
+            },
+            TcpFlags{ mask, value } => match &slice.transport {
+                Some(TransportSlice::Tcp(header)) => header.slice()[13] & mask == *value,
+                _ => false
+            },
+            PayloadLength(range) => range.contains(&slice.payload.len()),
+            Filter(filter) => filter.applies_to_slice(slice),
+        }
+    }
+
+    ///Returns true if the given decoded headers match the expression.
+    ///
+    ///`PacketHeaders` does not contain the ipv6 extension headers. For ipv6 packets with extension headers and
+    ///without a TCP or UDP header, `IpProtocol` is compared with the "next header" field of the ipv6 header.
+    pub fn applies_to_headers(&self, headers: &PacketHeaders) -> bool {
+        use self::FilterExpression::*;
+        match self {
+            Any => true,
+            And(left, right) => left.applies_to_headers(headers) && right.applies_to_headers(headers),
+            Or(left, right) => left.applies_to_headers(headers) || right.applies_to_headers(headers),
+            Not(value) => !value.applies_to_headers(headers),
+            EtherType(ether_type) => Some(*ether_type) == headers_ether_type(headers),
+            EthernetAddress{ direction, address } => match &headers.link {
+                Some(header) => direction.matches(header.source, header.destination, |value| value == *address),
+                None => false
+            },
+            Vlan{ identifiers, priorities } => {
+                let tag_matches = |header: &SingleVlanHeader| {
+                    let identifier_matches = match identifiers {
+                        Some(range) => range.contains(&header.vlan_identifier),
+                        None => true
+                    };
+                    let priority_matches = match priorities {
+                        Some(range) => range.contains(&header.priority_code_point),
+                        None => true
+                    };
+                    identifier_matches && priority_matches
+                };
+                match &headers.vlan {
+                    Some(VlanHeader::Single(header)) => tag_matches(header),
+                    Some(VlanHeader::Double(header)) => tag_matches(&header.outer) || tag_matches(&header.inner),
+                    None => false
+                }
+            },
+            IpAddress{ direction, prefix } => match &headers.ip {
+                Some(IpHeader::Version4(header)) => direction.matches(
+                    &header.source[..],
+                    &header.destination[..],
+                    |value| prefix.contains(value)
+                ),
+                Some(IpHeader::Version6(header)) => direction.matches(
+                    &header.source[..],
+                    &header.destination[..],
+                    |value| prefix.contains(value)
+                ),
+                None => false
+            },
+            IpProtocol(protocol) => {
+                let actual = match (&headers.transport, &headers.ip) {
+                    (Some(TransportHeader::Udp(_)), _) => Some(IpTrafficClass::Udp as u8),
+                    (Some(TransportHeader::Tcp(_)), _) => Some(IpTrafficClass::Tcp as u8),
+                    (None, Some(IpHeader::Version4(header))) => Some(header.protocol),
+                    (None, Some(IpHeader::Version6(header))) => Some(header.next_header),
+                    (None, None) => None
+                };
+                Some(*protocol) == actual
+            },
+            Port{ protocol, direction, ports } => {
+                let transport_ports = match &headers.transport {
+                    Some(TransportHeader::Tcp(header)) => Some((IpTrafficClass::Tcp as u8, header.source_port, header.destination_port)),
+                    Some(TransportHeader::Udp(header)) => Some((IpTrafficClass::Udp as u8, header.source_port, header.destination_port)),
+                    None => None
+                };
+                match transport_ports {
+                    Some((transport_protocol, source, destination)) => {
+                        let protocol_matches = match protocol {
+                            Some(value) => *value == transport_protocol,
+                            None => true
+                        };
+                        protocol_matches && direction.matches(source, destination, |value| ports.contains(&value))
+                    },
+                    None => false
+                }
+            },
+            TcpFlags{ mask, value } => match &headers.transport {
+                Some(TransportHeader::Tcp(header)) => tcp_header_flags(header) & mask == *value,
+                _ => false
+            },
+            PayloadLength(range) => range.contains(&headers.payload.len()),
+            Filter(filter) => filter.applies_to_headers(headers),
+        }
+    }
+
+    ///Returns true if the given ethernet II frame matches the expression.
+    ///
+    ///Only the layers referenced by the expression are decoded (e.g. an expression only containing vlan & ether type
+    ///primitives does not decode the ip & transport headers). Errors are only returned if one of these layers can not
+    ///be decoded.
+    pub fn applies_to_ethernet(&self, data: &[u8]) -> Result<bool, ReadError> {
+        SlicedPacket::from_ethernet_up_to(data, self.last_layer())
+            .map(|slice| self.applies_to_slice(&slice))
+    }
+
+    ///Returns true if the given ip packet matches the expression.
+    ///
+    ///Only the layers referenced by the expression are decoded (see [`applies_to_ethernet`](#method.applies_to_ethernet)).
+    pub fn applies_to_ip(&self, data: &[u8]) -> Result<bool, ReadError> {
+        SlicedPacket::from_ip_up_to(data, self.last_layer())
+            .map(|slice| self.applies_to_slice(&slice))
+    }
+
+    ///Returns the last layer that has to be decoded to evaluate the expression.
+    pub(crate) fn last_layer(&self) -> SliceLayer {
+        use self::FilterExpression::*;
+        match self {
+            Any | EthernetAddress{ .. } => SliceLayer::Link,
+            And(left, right) | Or(left, right) => std::cmp::max(left.last_layer(), right.last_layer()),
+            Not(value) => value.last_layer(),
+            EtherType(_) | Vlan{ .. } => SliceLayer::Vlan,
+            IpAddress{ .. } | IpProtocol(_) => SliceLayer::Ip,
+            //the payload length depends on all decoded headers
+            Port{ .. } | TcpFlags{ .. } | PayloadLength(_) => SliceLayer::Transport,
+            Filter(filter) => filter.last_layer(),
+        }
+    }
+}
+
+///Returns the ether type following the ethernet & vlan headers (derived from the ip header if no ethernet header is present).
+fn slice_ether_type(slice: &SlicedPacket) -> Option<u16> {
+    match &slice.vlan {
+        Some(VlanSlice::SingleVlan(header)) => return Some(header.ether_type()),
+        Some(VlanSlice::DoubleVlan(header)) => return Some(header.inner().ether_type()),
+        None => {}
+    }
+    if let Some(LinkSlice::Ethernet2(header)) = &slice.link {
+        return Some(header.ether_type());
+    }
+    match &slice.ip {
+        Some(InternetSlice::Ipv4(_)) => Some(EtherType::Ipv4 as u16),
+        Some(InternetSlice::Ipv6(_, _)) => Some(EtherType::Ipv6 as u16),
+        None => None
+    }
+}
+
+///Returns the ether type following the ethernet & vlan headers (derived from the ip header if no ethernet header is present).
+fn headers_ether_type(headers: &PacketHeaders) -> Option<u16> {
+    match &headers.vlan {
+        Some(VlanHeader::Single(header)) => return Some(header.ether_type),
+        Some(VlanHeader::Double(header)) => return Some(header.inner.ether_type),
+        None => {}
+    }
+    if let Some(header) = &headers.link {
+        return Some(header.ether_type);
+    }
+    match &headers.ip {
+        Some(IpHeader::Version4(_)) => Some(EtherType::Ipv4 as u16),
+        Some(IpHeader::Version6(_)) => Some(EtherType::Ipv6 as u16),
+        None => None
+    }
+}
+
+///Returns the byte of the tcp header containing the flags.
+fn tcp_header_flags(header: &TcpHeader) -> u8 {
+    let flags = [
+        (header.fin, TCP_FLAG_FIN),
+        (header.syn, TCP_FLAG_SYN),
+        (header.rst, TCP_FLAG_RST),
+        (header.psh, TCP_FLAG_PSH),
+        (header.ack, TCP_FLAG_ACK),
+        (header.urg, TCP_FLAG_URG),
+        (header.ece, TCP_FLAG_ECE),
+        (header.cwr, TCP_FLAG_CWR),
+    ];
+    flags.iter().filter(|(set, _)| *set).fold(0, |result, (_, flag)| result | flag)
+}
@@ -0,0 +1,421 @@
+//! Filters deciding if a packet should be accepted.
+//!
+//! [`Filter`](struct.Filter.html) matches packets by comparing a fixed set of header fields. More complex conditions
+//! can be expressed with a [`FilterExpression`](enum.FilterExpression.html) tree, which can also be parsed from a
+//! tcpdump style expression:
+//!
+//! ```
+//! use etherparse::*;
+//! use etherparse::packet_filter::*;
+//!
+//! let filter = FilterExpression::parse("vlan 100 and ip src 10.0.0.0/8 and tcp dst port 443").unwrap();
+//!
+//! let mut packet = Vec::new();
+//! PacketBuilder::ethernet2([1;6], [2;6])
+//!     .single_vlan(100)
+//!     .ipv4([10,1,2,3], [192,168,1,1], 20)
+//!     .tcp(50000, 443, 0, 1024)
+//!     .write(&mut packet, &[])
+//!     .unwrap();
+//! assert!(filter.applies_to_slice(&SlicedPacket::from_ethernet(&packet).unwrap()));
+//!
+//! //errors contain the position in the expression
+//! let error = FilterExpression::parse("tcp dst port 70000").unwrap_err();
+//! assert_eq!(13, error.position);
+//! ```
+//!
+//! # Expression syntax
+//!
+//! A subset of the pcap-filter syntax is supported:
+//!
+//! * `ether src|dst|host MAC` (MAC written as `aa:bb:cc:dd:ee:ff`) & `ether proto NUMBER|ip|ip6|arp`
+//! * `vlan [ID|FIRST-LAST] [pcp PCP|FIRST-LAST]` matching packets with a VLAN tag (and an outer or inner tag with an
+//!   identifier & priority code point in the given ranges)
+//! * `ip`, `ip6` & `arp` matching the ether type following the ethernet & VLAN headers
+//! * `[ip|ip6] [DIR] host ADDRESS`, `[ip|ip6] [DIR] net ADDRESS/LENGTH` & `[ip|ip6] proto NUMBER|tcp|udp|icmp|icmp6`
+//! * `tcp`, `udp`, `icmp` & `icmp6` matching the ip protocol of the transport layer
+//! * `[tcp|udp] [DIR] port NUMBER` & `[tcp|udp] [DIR] portrange FIRST-LAST`
+//! * `tcp flags FLAGS[/MASK]` matching TCP packets whose flags are equal to `FLAGS` after applying `MASK` (defaults to
+//!   `FLAGS`). Flags are given as number or as names separated by `,` (`fin`, `syn`, `rst`, `psh`, `ack`, `urg`, `ece`
+//!   & `cwr`), e.g. `tcp flags syn/syn,ack` matches connection requests.
+//! * `payloadlen LENGTH|MIN-MAX` matching the length of the payload following the last decoded header
+//! * `not`/`!`, `and`/`&&`, `or`/`||` & parentheses
+//!
+//! `DIR` is one of `src`, `dst`, `src or dst` (default) & `src and dst`. An address (or `ADDRESS/LENGTH` network)
+//! without the `host` or `net` keyword is treated as `host` (or `net`). Numbers can be written in decimal or hexadecimal (`0x` prefix). Like in pcap-filter, `and` &
+//! `or` have the same precedence and are evaluated from left to right (`not` binds stronger). Host names, implicit
+//! qualifiers (`host a or b`), byte offset expressions (`tcp[13]`) & network masks (`net A mask M`) are not supported.
+//! The `vlan` ranges, `pcp`, `tcp flags` & `payloadlen` primitives are extensions not present in pcap-filter.
+//! Parsed expressions can contain at most [`FILTER_MAX_NESTING_DEPTH`](constant.FILTER_MAX_NESTING_DEPTH.html) nested
+//! operators & parentheses.
+//!
+//! Field based filters can be combined with expressions via `FilterExpression::from(filter)`.
+use super::*;
+
+mod expression;
+pub use self::expression::*;
+
+mod parser;
+pub use self::parser::*;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ElementFilter<T> {
+    Any,
+    No,
+    Some(T)
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum LinkFilter {
+    Ethernet2 {
+        source: Option<[u8;6]>,
+        destination: Option<[u8;6]>
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum VlanFilter {
+    Single(Option<u16>),
+    Double {
+        outer_identifier: Option<u16>,
+        inner_identifier: Option<u16>
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum IpFilter {
+    Ipv4 {
+        source: Option<[u8;4]>,
+        destination: Option<[u8;4]>
+    },
+    Ipv6 {
+        source: Option<[u8;16]>,
+        destination: Option<[u8;16]>
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum TransportFilter {
+    Udp {
+        source_port: Option<u16>,
+        destination_port: Option<u16>
+    },
+    Tcp {
+        source_port: Option<u16>,
+        destination_port: Option<u16>
+    }
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Filter {
+    pub link: ElementFilter<LinkFilter>,
+    pub vlan: ElementFilter<VlanFilter>,
+    pub ip: ElementFilter<IpFilter>,
+    pub transport: ElementFilter<TransportFilter>,
+}
+
+impl<T> Default for ElementFilter<T> {
+    fn default() -> ElementFilter<T> {
+        ElementFilter::Any
+    }
+}
+
+impl LinkFilter {
+    pub fn applies_to_slice(&self, slice: &LinkSlice) -> bool {
+        use crate::LinkSlice::*;
+        match self {
+            LinkFilter::Ethernet2{
+                source: expected_source,
+                destination: expected_destination
+            } =>  match slice {
+                Ethernet2(header) => (match expected_source {
+                    Some(e) => header.source() == *e,
+                    None => true
+                }) && (match expected_destination {
+                    Some(e) => header.destination() == *e,
+                    None => true
+                }),
+                _ => false //not an ethernet II header
+            }
+        }
+    }
+}
+
+impl VlanFilter {
+    pub fn applies_to_slice(&self, slice: &VlanSlice) -> bool {
+        use crate::VlanSlice::*;
+        match self {
+            VlanFilter::Single(expected_id) => {
+                match slice {
+                    SingleVlan(header) => {
+                        match expected_id {
+                            Some(e) => header.vlan_identifier() == *e,
+                            None => true
+                        }
+                    },
+                    _ => false //not a single vlan header
+                }
+            },
+            VlanFilter::Double { inner_identifier: expected_inner_id, outer_identifier: expecetd_outer_id } => {
+                match slice {
+                    DoubleVlan(header) => {
+                        (match expecetd_outer_id {
+                            Some(e) => header.outer().vlan_identifier() == *e,
+                            None => true
+                        }) && (match expected_inner_id {
+                            Some(e) => header.inner().vlan_identifier() == *e,
+                            None => true
+                        })
+                    },
+                    _ => false
+                }
+            }
+        }
+    }
+}
+
+impl IpFilter {
+    pub fn applies_to_slice(&self, slice: &InternetSlice) -> bool {
+        use crate::InternetSlice::*;
+        match self {
+            IpFilter::Ipv4 { source: expected_source, destination: expected_destination } => {
+                match slice {
+                    Ipv4(header) => {
+                        (match expected_source {
+                            Some(e) => header.source() == &e[..],
+                            None => true
+                        }) && (match expected_destination {
+                            Some(e) => header.destination() == &e[..],
+                            None => true
+                        })
+                    },
+                    _ => false
+                }
+            },
+            IpFilter::Ipv6 { source: expected_source, destination: expected_destination } => {
+                match slice {
+                    Ipv6(header, _) => {
+                        (match expected_source {
+                            Some(e) => header.source() == &e[..],
+                            None => true
+                        }) && (match expected_destination {
+                            Some(e) => header.destination() == &e[..],
+                            None => true
+                        })
+                    },
+                    _ => false
+                }
+            }
+        }
+    }
+}
+
+impl TransportFilter {
+    pub fn applies_to_slice(&self, slice: &TransportSlice) -> bool {
+        use crate::TransportSlice::*;
+        match self {
+            TransportFilter::Udp { 
+                source_port: expected_source_port, 
+                destination_port: expected_destination_port
+            } => {
+                match slice {
+                    Udp(header) => {
+                        (match expected_source_port {
+                            Some(e) => header.source_port() == *e,
+                            None => true
+                        }) && (match expected_destination_port {
+                            Some(e) => header.destination_port() == *e,
+                            None => true
+                        })
+                    }
+                    _ => false
+                }
+            },
+            TransportFilter::Tcp { 
+                source_port: expected_source_port, 
+                destination_port: expected_destination_port
+            } => {
+                match slice {
+                    Tcp(header) => {
+                        (match expected_source_port {
+                            Some(e) => header.source_port() == *e,
+                            None => true
+                        }) && (match expected_destination_port {
+                            Some(e) => header.destination_port() == *e,
+                            None => true
+                        })
+                    }
+                    _ => false
+                }
+            }
+        }
+    }
+}
+
+impl Filter {
+    ///Returns true if a given sliced network package fullfills all conditions of this filter.
+    pub fn applies_to_slice(&self, slice: &SlicedPacket) -> bool {
+        //TODO link
+         (match &self.link {
+            ElementFilter::Any => true,
+            ElementFilter::No => slice.link.is_none(),
+            ElementFilter::Some(filter) => {
+                match &slice.link {
+                    Some(value) => filter.applies_to_slice(value),
+                    None => false
+                }
+            }
+         }) && (match &self.vlan {
+            ElementFilter::Any => true,
+            ElementFilter::No => slice.vlan.is_none(),
+            ElementFilter::Some(filter) => {
+                match &slice.vlan {
+                    Some(value) => filter.applies_to_slice(value),
+                    None => false
+                }
+            }
+        }) && (match &self.ip {
+            ElementFilter::Any => true,
+            ElementFilter::No => slice.ip.is_none(),
+            ElementFilter::Some(filter) => {
+                match &slice.ip {
+                    Some(value) => filter.applies_to_slice(value),
+                    None => false
+                }
+            }
+        }) && (match &self.transport {
+            ElementFilter::Any => true,
+            ElementFilter::No => slice.transport.is_none(),
+            ElementFilter::Some(filter) => {
+                match &slice.transport {
+                    Some(value) => filter.applies_to_slice(value),
+                    None => false
+                }
+            }
+        })
+    }
+}
+
+impl<T> ElementFilter<T> {
+    ///Applies the filter to an optional header with the given check.
+    fn applies_to<V, F: Fn(&T, &V) -> bool>(&self, value: Option<&V>, check: F) -> bool {
+        match self {
+            ElementFilter::Any => true,
+            ElementFilter::No => value.is_none(),
+            ElementFilter::Some(filter) => match value {
+                Some(value) => check(filter, value),
+                None => false
+            }
+        }
+    }
+}
+
+///Returns true if the expected value is None or equal to the actual value.
+fn optional_matches<T: PartialEq>(expected: &Option<T>, actual: &T) -> bool {
+    match expected {
+        Some(expected) => expected == actual,
+        None => true
+    }
+}
+
+impl LinkFilter {
+    ///Returns true if the ethernet header fullfills the conditions of this filter.
+    pub fn applies_to_header(&self, header: &Ethernet2Header) -> bool {
+        match self {
+            LinkFilter::Ethernet2{ source, destination } => {
+                optional_matches(source, &header.source) &&
+                optional_matches(destination, &header.destination)
+            }
+        }
+    }
+}
+
+impl VlanFilter {
+    ///Returns true if the vlan header fullfills the conditions of this filter.
+    pub fn applies_to_header(&self, header: &VlanHeader) -> bool {
+        match (self, header) {
+            (VlanFilter::Single(identifier), VlanHeader::Single(header)) => {
+                optional_matches(identifier, &header.vlan_identifier)
+            },
+            (VlanFilter::Double{ outer_identifier, inner_identifier }, VlanHeader::Double(header)) => {
+                optional_matches(outer_identifier, &header.outer.vlan_identifier) &&
+                optional_matches(inner_identifier, &header.inner.vlan_identifier)
+            },
+            _ => false
+        }
+    }
+}
+
+impl IpFilter {
+    ///Returns true if the ip header fullfills the conditions of this filter.
+    pub fn applies_to_header(&self, header: &IpHeader) -> bool {
+        match (self, header) {
+            (IpFilter::Ipv4{ source, destination }, IpHeader::Version4(header)) => {
+                optional_matches(source, &header.source) &&
+                optional_matches(destination, &header.destination)
+            },
+            (IpFilter::Ipv6{ source, destination }, IpHeader::Version6(header)) => {
+                optional_matches(source, &header.source) &&
+                optional_matches(destination, &header.destination)
+            },
+            _ => false
+        }
+    }
+}
+
+impl TransportFilter {
+    ///Returns true if the transport header fullfills the conditions of this filter.
+    pub fn applies_to_header(&self, header: &TransportHeader) -> bool {
+        match (self, header) {
+            (TransportFilter::Udp{ source_port, destination_port }, TransportHeader::Udp(header)) => {
+                optional_matches(source_port, &header.source_port) &&
+                optional_matches(destination_port, &header.destination_port)
+            },
+            (TransportFilter::Tcp{ source_port, destination_port }, TransportHeader::Tcp(header)) => {
+                optional_matches(source_port, &header.source_port) &&
+                optional_matches(destination_port, &header.destination_port)
+            },
+            _ => false
+        }
+    }
+}
+
+impl Filter {
+    ///Returns true if the given decoded headers fullfill all conditions of this filter.
+    pub fn applies_to_headers(&self, headers: &PacketHeaders) -> bool {
+        self.link.applies_to(headers.link.as_ref(), |filter, header| filter.applies_to_header(header)) &&
+        self.vlan.applies_to(headers.vlan.as_ref(), |filter, header| filter.applies_to_header(header)) &&
+        self.ip.applies_to(headers.ip.as_ref(), |filter, header| filter.applies_to_header(header)) &&
+        self.transport.applies_to(headers.transport.as_ref(), |filter, header| filter.applies_to_header(header))
+    }
+
+    ///Returns true if the given ethernet II frame fullfills all conditions of this filter.
+    ///
+    ///Only the layers referenced by the filter are decoded (e.g. a filter only containing a vlan condition does not
+    ///decode the ip & transport headers). Errors are only returned if one of these layers can not be decoded.
+    pub fn applies_to_ethernet(&self, data: &[u8]) -> Result<bool, ReadError> {
+        SlicedPacket::from_ethernet_up_to(data, self.last_layer())
+            .map(|slice| self.applies_to_slice(&slice))
+    }
+
+    ///Returns true if the given ip packet fullfills all conditions of this filter.
+    ///
+    ///Only the layers referenced by the filter are decoded (see [`applies_to_ethernet`](#method.applies_to_ethernet)).
+    pub fn applies_to_ip(&self, data: &[u8]) -> Result<bool, ReadError> {
+        SlicedPacket::from_ip_up_to(data, self.last_layer())
+            .map(|slice| self.applies_to_slice(&slice))
+    }
+
+    ///Returns the last layer that has to be decoded to evaluate the filter.
+    pub(crate) fn last_layer(&self) -> SliceLayer {
+        if ElementFilter::Any != self.transport {
+            SliceLayer::Transport
+        } else if ElementFilter::Any != self.ip {
+            SliceLayer::Ip
+        } else if ElementFilter::Any != self.vlan {
+            SliceLayer::Vlan
+        } else {
+            SliceLayer::Link
+        }
+    }
+}
@@ -0,0 +1,627 @@
+use super::*;
+
+use std::error::Error;
+use std::fmt;
+use std::net::IpAddr;
+use std::ops::RangeInclusive;
+
+///Maximum nesting depth of the operators & parentheses in a parsed filter expression.
+///
+///Expressions are parsed & evaluated recursively, the limit prevents user supplied expressions from overflowing the
+///stack.
+pub const FILTER_MAX_NESTING_DEPTH: usize = 256;
+
+///Error when parsing a filter expression.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FilterParseError {
+    ///Byte offset in the expression at which the error was detected.
+    pub position: usize,
+    pub kind: FilterParseErrorKind,
+}
+
+///Cause of a filter expression parse error.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FilterParseErrorKind {
+    ///The expression ended while the given element was still expected.
+    UnexpectedEnd { expected: &'static str },
+    ///A different token was found then expected.
+    UnexpectedToken { expected: &'static str, found: String },
+    ///Number could not be parsed or is bigger then the allowed maximum.
+    InvalidNumber { value: String, max: u32 },
+    ///Ip or MAC address could not be parsed.
+    InvalidAddress(String),
+    ///Network prefix length is too large or the address has bits set after the prefix length.
+    InvalidPrefix(String),
+    ///Address does not match the ip version given as qualifier (e.g. `ip6 host 10.0.0.1`).
+    AddressFamilyMismatch(String),
+    ///First value of a range is bigger then the last value.
+    InvalidRange(String),
+    ///Unknown tcp flag name (or value bigger then 0xff).
+    InvalidTcpFlags(String),
+    ///The operators & parentheses are nested deeper then `FILTER_MAX_NESTING_DEPTH`.
+    NestingTooDeep,
+}
+
+impl Error for FilterParseError {
+
+}
+
+impl fmt::Display for FilterParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use self::FilterParseErrorKind::*;
+        match &self.kind {
+            UnexpectedEnd{ expected } => {
+                write!(f, "FilterParseError: Unexpected end of filter expression at position {}, expected {}.", self.position, expected)
+            },
+            UnexpectedToken{ expected, found } => {
+                write!(f, "FilterParseError: Unexpected '{}' at position {} of filter expression, expected {}.", found, self.position, expected)
+            },
+            InvalidNumber{ value, max } => {
+                write!(f, "FilterParseError: Invalid number '{}' at position {} of filter expression (numbers have to be between 0 and {}).", value, self.position, max)
+            },
+            InvalidAddress(value) => {
+                write!(f, "FilterParseError: Invalid address '{}' at position {} of filter expression.", value, self.position)
+            },
+            InvalidPrefix(value) => {
+                write!(f, "FilterParseError: Invalid network '{}' at position {} of filter expression (prefix length too large or bits set after the prefix length).", value, self.position)
+            },
+            AddressFamilyMismatch(value) => {
+                write!(f, "FilterParseError: Address '{}' at position {} of filter expression does not match the ip version qualifier.", value, self.position)
+            },
+            InvalidRange(value) => {
+                write!(f, "FilterParseError: Invalid range '{}' at position {} of filter expression (the first value is bigger then the last value).", value, self.position)
+            },
+            InvalidTcpFlags(value) => {
+                write!(f, "FilterParseError: Invalid tcp flags '{}' at position {} of filter expression (expected a number or flag names separated by ',').", value, self.position)
+            },
+            NestingTooDeep => {
+                write!(f, "FilterParseError: Filter expression is nested too deeply at position {} (at most {} nested operators & parentheses are supported).", self.position, FILTER_MAX_NESTING_DEPTH)
+            },
+        }
+    }
+}
+
+impl FilterExpression {
+    ///Parses a tcpdump style filter expression (see the [module documentation](index.html) for the supported syntax).
+    ///
+    ///An empty expression matches all packets.
+    pub fn parse(expression: &str) -> Result<FilterExpression, FilterParseError> {
+        let mut parser = Parser {
+            tokens: tokenize(expression)?,
+            index: 0,
+            end: expression.len(),
+            nesting: 0,
+        };
+        if parser.tokens.is_empty() {
+            return Ok(FilterExpression::Any);
+        }
+        let (result, _) = parser.expression()?;
+        match parser.peek() {
+            None => Ok(result),
+            Some(_) => Err(parser.unexpected("'and', 'or' or end of expression"))
+        }
+    }
+}
+
+impl std::str::FromStr for FilterExpression {
+    type Err = FilterParseError;
+
+    fn from_str(value: &str) -> Result<FilterExpression, FilterParseError> {
+        FilterExpression::parse(value)
+    }
+}
+
+///Token of an expression (position & text).
+type Token<'a> = (usize, &'a str);
+
+///Splits the expression into words, parentheses & operators.
+fn tokenize(expression: &str) -> Result<Vec<Token<'_>>, FilterParseError> {
+    let mut result = Vec::new();
+    let mut word_start = None;
+    let mut chars = expression.char_indices().peekable();
+    while let Some((position, c)) = chars.next() {
+        let is_word_char = !(c.is_whitespace() || "()!&|".contains(c));
+        if is_word_char {
+            if word_start.is_none() {
+                word_start = Some(position);
+            }
+            continue;
+        }
+        if let Some(start) = word_start.take() {
+            result.push((start, &expression[start..position]));
+        }
+        match c {
+            '(' | ')' | '!' => result.push((position, &expression[position..position + 1])),
+            '&' | '|' => {
+                //only the double character versions are operators
+                match chars.peek() {
+                    Some((_, next)) if *next == c => {
+                        chars.next();
+                        result.push((position, &expression[position..position + 2]));
+                    },
+                    _ => return Err(FilterParseError {
+                        position,
+                        kind: FilterParseErrorKind::UnexpectedToken {
+                            expected: if '&' == c { "'&&'" } else { "'||'" },
+                            found: c.to_string(),
+                        }
+                    })
+                }
+            },
+            _ => {} //whitespace
+        }
+    }
+    if let Some(start) = word_start {
+        result.push((start, &expression[start..]));
+    }
+    Ok(result)
+}
+
+///Qualifiers preceding a "host", "net", "port" or "proto" primitive.
+#[derive(Clone, Copy)]
+struct Qualifiers {
+    ///Ip version (4 or 6) given via "ip" or "ip6".
+    ip_version: Option<u8>,
+    ///Ip protocol given via "tcp" or "udp".
+    protocol: Option<u8>,
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    index: usize,
+    ///Length of the expression (position of "unexpected end" errors).
+    end: usize,
+    ///Number of currently parsed "not" operators & parentheses containing the current token.
+    nesting: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a str> {
+        self.peek_at(0)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&'a str> {
+        self.tokens.get(self.index + offset).map(|token| token.1)
+    }
+
+    ///Returns the next token or an error if the expression ended.
+    fn next(&mut self, expected: &'static str) -> Result<Token<'a>, FilterParseError> {
+        match self.tokens.get(self.index) {
+            Some(token) => {
+                self.index += 1;
+                Ok(*token)
+            },
+            None => Err(FilterParseError {
+                position: self.end,
+                kind: FilterParseErrorKind::UnexpectedEnd{ expected }
+            })
+        }
+    }
+
+    ///Error for the current token (or the end of the expression).
+    fn unexpected(&self, expected: &'static str) -> FilterParseError {
+        match self.tokens.get(self.index) {
+            Some((position, text)) => FilterParseError {
+                position: *position,
+                kind: FilterParseErrorKind::UnexpectedToken{ expected, found: text.to_string() }
+            },
+            None => FilterParseError {
+                position: self.end,
+                kind: FilterParseErrorKind::UnexpectedEnd{ expected }
+            }
+        }
+    }
+
+    ///Error if the depth exceeds `FILTER_MAX_NESTING_DEPTH` (otherwise the depth is returned).
+    fn check_depth(&self, position: usize, depth: usize) -> Result<usize, FilterParseError> {
+        if depth > FILTER_MAX_NESTING_DEPTH {
+            Err(FilterParseError {
+                position,
+                kind: FilterParseErrorKind::NestingTooDeep
+            })
+        } else {
+            Ok(depth)
+        }
+    }
+
+    ///Parses a sequence of "and" & "or" combined expressions (both operators have the same precedence).
+    ///
+    ///Returns the expression & its depth (number of nested operators).
+    fn expression(&mut self) -> Result<(FilterExpression, usize), FilterParseError> {
+        let (mut result, mut depth) = self.unary()?;
+        loop {
+            let (position, is_and) = match self.tokens.get(self.index) {
+                Some((position, "and")) | Some((position, "&&")) => (*position, true),
+                Some((position, "or")) | Some((position, "||")) => (*position, false),
+                _ => return Ok((result, depth))
+            };
+            self.index += 1;
+            let (right, right_depth) = self.unary()?;
+            depth = self.check_depth(position, std::cmp::max(depth, right_depth) + 1)?;
+            result = if is_and {
+                result.and(right)
+            } else {
+                result.or(right)
+            };
+        }
+    }
+
+    fn unary(&mut self) -> Result<(FilterExpression, usize), FilterParseError> {
+        let position = match self.tokens.get(self.index) {
+            Some((position, "not")) | Some((position, "!")) | Some((position, "(")) => *position,
+            _ => return Ok((self.primitive()?, 0))
+        };
+        //limit the recursion before parsing the nested expression
+        self.nesting = self.check_depth(position, self.nesting + 1)?;
+        let is_not = Some("(") != self.peek();
+        self.index += 1;
+        let result = if is_not {
+            let (value, depth) = self.unary()?;
+            (value.negate(), self.check_depth(position, depth + 1)?)
+        } else {
+            let result = self.expression()?;
+            match self.peek() {
+                Some(")") => self.index += 1,
+                _ => return Err(self.unexpected("')'"))
+            }
+            result
+        };
+        self.nesting -= 1;
+        Ok(result)
+    }
+
+    fn primitive(&mut self) -> Result<FilterExpression, FilterParseError> {
+        use self::FilterExpression::*;
+        const EXPECTED: &str = "filter primitive";
+        let (position, word) = match self.peek() {
+            Some(")") | Some("and") | Some("&&") | Some("or") | Some("||") | None => return Err(self.unexpected(EXPECTED)),
+            Some(_) => self.next(EXPECTED)?
+        };
+        match word {
+            "vlan" => {
+                let identifiers = if self.peek_is_number() {
+                    let range = self.range(0xfff)?;
+                    Some(*range.start() as u16..=*range.end() as u16)
+                } else {
+                    None
+                };
+                let priorities = if Some("pcp") == self.peek() {
+                    self.index += 1;
+                    let range = self.range(7)?;
+                    Some(*range.start() as u8..=*range.end() as u8)
+                } else {
+                    None
+                };
+                Ok(Vlan{ identifiers, priorities })
+            },
+            "payloadlen" => {
+                let range = self.range(u32::MAX)?;
+                Ok(PayloadLength(*range.start() as usize..=*range.end() as usize))
+            },
+            "ether" => self.ether(),
+            "arp" => Ok(EtherType(crate::EtherType::Arp as u16)),
+            "ip" | "ip6" => {
+                let ip_version = if "ip" == word { 4 } else { 6 };
+                match self.peek() {
+                    Some("src") | Some("dst") | Some("host") | Some("net") | Some("port") | Some("portrange") | Some("proto") => {
+                        self.qualified(Qualifiers{ ip_version: Some(ip_version), protocol: None })
+                    },
+                    _ => Ok(ip_version_expression(ip_version))
+                }
+            },
+            "tcp" | "udp" => {
+                let protocol = if "tcp" == word { IpTrafficClass::Tcp } else { IpTrafficClass::Udp } as u8;
+                match self.peek() {
+                    Some("src") | Some("dst") | Some("host") | Some("net") | Some("port") | Some("portrange") | Some("proto") => {
+                        self.qualified(Qualifiers{ ip_version: None, protocol: Some(protocol) })
+                    },
+                    Some("flags") if "tcp" == word => {
+                        self.index += 1;
+                        self.tcp_flags()
+                    },
+                    _ => Ok(IpProtocol(protocol))
+                }
+            },
+            "icmp" => Ok(IpProtocol(IpTrafficClass::Icmp as u8)),
+            "icmp6" => Ok(IpProtocol(IpTrafficClass::IPv6Icmp as u8)),
+            "src" | "dst" | "host" | "net" | "port" | "portrange" | "proto" => {
+                //qualified primitive without protocol qualifier
+                self.index -= 1;
+                self.qualified(Qualifiers{ ip_version: None, protocol: None })
+            },
+            _ if self.previous_is_address() => {
+                //an address without qualifiers is a host or net primitive
+                self.index -= 1;
+                self.address(Qualifiers{ ip_version: None, protocol: None }, Direction::SourceOrDestination, word.contains('/'))
+            },
+            _ => Err(FilterParseError {
+                position,
+                kind: FilterParseErrorKind::UnexpectedToken{ expected: EXPECTED, found: word.to_string() }
+            })
+        }
+    }
+
+    ///Parses the "src|dst|host" or "proto" part following "ether".
+    fn ether(&mut self) -> Result<FilterExpression, FilterParseError> {
+        const EXPECTED: &str = "'src', 'dst', 'host' or 'proto'";
+        let direction = match self.peek() {
+            Some("proto") => {
+                self.index += 1;
+                return match self.peek() {
+                    Some("ip") => {
+                        self.index += 1;
+                        Ok(FilterExpression::EtherType(EtherType::Ipv4 as u16))
+                    },
+                    Some("ip6") => {
+                        self.index += 1;
+                        Ok(FilterExpression::EtherType(EtherType::Ipv6 as u16))
+                    },
+                    Some("arp") => {
+                        self.index += 1;
+                        Ok(FilterExpression::EtherType(EtherType::Arp as u16))
+                    },
+                    _ => Ok(FilterExpression::EtherType(self.number(0xffff)? as u16))
+                };
+            },
+            Some("host") => {
+                self.index += 1;
+                Direction::SourceOrDestination
+            },
+            Some("src") | Some("dst") => {
+                let direction = self.direction();
+                //optional "host" keyword
+                if Some("host") == self.peek() {
+                    self.index += 1;
+                }
+                direction
+            },
+            _ => return Err(self.unexpected(EXPECTED))
+        };
+        let (position, text) = self.next("MAC address")?;
+        let mut address = [0u8;6];
+        let mut parts = text.split(':');
+        for value in address.iter_mut() {
+            *value = parts.next()
+                .filter(|part| !part.is_empty() && part.len() <= 2)
+                .and_then(|part| u8::from_str_radix(part, 16).ok())
+                .ok_or_else(|| invalid_address(position, text))?;
+        }
+        if parts.next().is_some() {
+            return Err(invalid_address(position, text));
+        }
+        Ok(FilterExpression::EthernetAddress{ direction, address })
+    }
+
+    ///Parses an optional direction qualifier ("src", "dst", "src or dst", "src and dst").
+    fn direction(&mut self) -> Direction {
+        let first = match self.peek() {
+            Some("src") => Direction::Source,
+            Some("dst") => Direction::Destination,
+            _ => return Direction::SourceOrDestination
+        };
+        self.index += 1;
+        let combined = match (self.peek(), self.peek_at(1)) {
+            (Some("or"), Some("src")) | (Some("or"), Some("dst")) => Some(Direction::SourceOrDestination),
+            (Some("and"), Some("src")) | (Some("and"), Some("dst")) => Some(Direction::SourceAndDestination),
+            _ => None
+        };
+        match combined {
+            Some(value) => {
+                self.index += 2;
+                value
+            },
+            None => first
+        }
+    }
+
+    ///Parses "[DIR] host|net|port VALUE" & "proto VALUE" primitives.
+    fn qualified(&mut self, qualifiers: Qualifiers) -> Result<FilterExpression, FilterParseError> {
+        let has_direction = [Some("src"), Some("dst")].contains(&self.peek());
+        let direction = self.direction();
+        let keyword = self.peek();
+        match keyword {
+            Some("port") | Some("portrange") if qualifiers.ip_version.is_none() => {
+                self.index += 1;
+                let ports = if Some("port") == keyword {
+                    let port = self.number(0xffff)? as u16;
+                    port..=port
+                } else {
+                    let range = self.range(0xffff)?;
+                    *range.start() as u16..=*range.end() as u16
+                };
+                Ok(FilterExpression::Port{ protocol: qualifiers.protocol, direction, ports })
+            },
+            Some("host") | Some("net") if qualifiers.protocol.is_none() => {
+                self.index += 1;
+                let is_net = Some("net") == keyword;
+                self.address(qualifiers, direction, is_net)
+            },
+            Some("proto") if !has_direction && qualifiers.protocol.is_none() => {
+                self.index += 1;
+                let protocol = self.ip_protocol()?;
+                let result = FilterExpression::IpProtocol(protocol);
+                Ok(match qualifiers.ip_version {
+                    Some(version) => ip_version_expression(version).and(result),
+                    None => result
+                })
+            },
+            Some(text) if has_direction && qualifiers.protocol.is_none() && self.peek_is_address() => {
+                //a direction followed by an address is a host or net primitive
+                self.address(qualifiers, direction, text.contains('/'))
+            },
+            _ => Err(self.unexpected(
+                if qualifiers.protocol.is_some() {
+                    "'port' or 'portrange'"
+                } else if qualifiers.ip_version.is_some() {
+                    "'host', 'net' or 'proto'"
+                } else {
+                    "'host', 'net', 'port', 'portrange' or 'proto'"
+                }
+            ))
+        }
+    }
+
+    fn peek_is_address(&self) -> bool {
+        self.peek().map(is_address).unwrap_or(false)
+    }
+
+    fn previous_is_address(&self) -> bool {
+        self.index.checked_sub(1)
+            .and_then(|index| self.tokens.get(index))
+            .map(|token| is_address(token.1))
+            .unwrap_or(false)
+    }
+
+    ///Parses a host address or network prefix.
+    fn address(&mut self, qualifiers: Qualifiers, direction: Direction, is_net: bool) -> Result<FilterExpression, FilterParseError> {
+        let (position, text) = self.next(if is_net { "network" } else { "ip address" })?;
+        let (address_text, prefix_len_text) = match text.find('/') {
+            Some(index) if is_net => (&text[..index], Some(&text[index + 1..])),
+            _ => (text, None)
+        };
+        let address: IpAddr = address_text.parse().map_err(|_| invalid_address(position, text))?;
+        let prefix = match prefix_len_text {
+            Some(value) => value.parse::<u8>().ok()
+                .and_then(|prefix_len| IpPrefix::new(address, prefix_len))
+                .filter(|prefix| !prefix.has_host_bits())
+                .ok_or_else(|| FilterParseError {
+                    position,
+                    kind: FilterParseErrorKind::InvalidPrefix(text.to_string())
+                })?,
+            None => IpPrefix::host(address)
+        };
+        let version = match address {
+            IpAddr::V4(_) => 4,
+            IpAddr::V6(_) => 6,
+        };
+        if qualifiers.ip_version.map(|value| value != version).unwrap_or(false) {
+            return Err(FilterParseError {
+                position,
+                kind: FilterParseErrorKind::AddressFamilyMismatch(text.to_string())
+            });
+        }
+        Ok(FilterExpression::IpAddress{ direction, prefix })
+    }
+
+    ///Parses an ip protocol number or name.
+    fn ip_protocol(&mut self) -> Result<u8, FilterParseError> {
+        let protocol = match self.peek() {
+            Some("tcp") => IpTrafficClass::Tcp,
+            Some("udp") => IpTrafficClass::Udp,
+            Some("icmp") => IpTrafficClass::Icmp,
+            Some("icmp6") => IpTrafficClass::IPv6Icmp,
+            _ => return Ok(self.number(0xff)? as u8)
+        };
+        self.index += 1;
+        Ok(protocol as u8)
+    }
+
+    fn peek_is_number(&self) -> bool {
+        self.peek().map(|value| value.starts_with(|c: char| c.is_ascii_digit())).unwrap_or(false)
+    }
+
+    ///Parses a decimal or hexadecimal (0x prefix) number.
+    fn number(&mut self, max: u32) -> Result<u32, FilterParseError> {
+        let (position, text) = self.next("number")?;
+        parse_number(position, text, max)
+    }
+
+    ///Parses a single number or a range of numbers ("FIRST-LAST").
+    fn range(&mut self, max: u32) -> Result<RangeInclusive<u32>, FilterParseError> {
+        let (position, text) = self.next("number or range")?;
+        match text.find('-') {
+            Some(index) => {
+                let first = parse_number(position, &text[..index], max)?;
+                let last = parse_number(position + index + 1, &text[index + 1..], max)?;
+                if first > last {
+                    Err(FilterParseError {
+                        position,
+                        kind: FilterParseErrorKind::InvalidRange(text.to_string())
+                    })
+                } else {
+                    Ok(first..=last)
+                }
+            },
+            None => {
+                let value = parse_number(position, text, max)?;
+                Ok(value..=value)
+            }
+        }
+    }
+
+    ///Parses the "FLAGS[/MASK]" part of a "tcp flags" primitive.
+    fn tcp_flags(&mut self) -> Result<FilterExpression, FilterParseError> {
+        let (position, text) = self.next("tcp flags")?;
+        let (value_text, mask_text) = match text.find('/') {
+            Some(index) => (&text[..index], Some(&text[index + 1..])),
+            None => (text, None)
+        };
+        let value = parse_tcp_flags(position, value_text)?;
+        let mask = match mask_text {
+            Some(mask_text) => parse_tcp_flags(position, mask_text)?,
+            None => value
+        };
+        Ok(FilterExpression::TcpFlags{ mask, value: value & mask })
+    }
+}
+
+///Parses a decimal or hexadecimal (0x prefix) number.
+fn parse_number(position: usize, text: &str, max: u32) -> Result<u32, FilterParseError> {
+    let value = if text.starts_with("0x") || text.starts_with("0X") {
+        u32::from_str_radix(&text[2..], 16).ok()
+    } else {
+        text.parse::<u32>().ok()
+    };
+    match value {
+        Some(value) if value <= max => Ok(value),
+        _ => Err(FilterParseError {
+            position,
+            kind: FilterParseErrorKind::InvalidNumber{ value: text.to_string(), max }
+        })
+    }
+}
+
+///Parses tcp flags given as number or as flag names separated by ',' (e.g. "syn,ack").
+fn parse_tcp_flags(position: usize, text: &str) -> Result<u8, FilterParseError> {
+    let error = || FilterParseError {
+        position,
+        kind: FilterParseErrorKind::InvalidTcpFlags(text.to_string())
+    };
+    if text.starts_with(|c: char| c.is_ascii_digit()) {
+        return parse_number(position, text, 0xff).map(|value| value as u8).map_err(|_| error());
+    }
+    let mut result = 0;
+    for name in text.split(',') {
+        result |= match name {
+            "fin" => TCP_FLAG_FIN,
+            "syn" => TCP_FLAG_SYN,
+            "rst" => TCP_FLAG_RST,
+            "psh" => TCP_FLAG_PSH,
+            "ack" => TCP_FLAG_ACK,
+            "urg" => TCP_FLAG_URG,
+            "ece" => TCP_FLAG_ECE,
+            "cwr" => TCP_FLAG_CWR,
+            _ => return Err(error())
+        };
+    }
+    Ok(result)
+}
+
+///Expression matching the ether type of the given ip version.
+fn ip_version_expression(ip_version: u8) -> FilterExpression {
+    FilterExpression::EtherType(
+        if 4 == ip_version { EtherType::Ipv4 } else { EtherType::Ipv6 } as u16
+    )
+}
+
+///Returns true if the text is an ip address or network (address followed by a prefix length).
+fn is_address(text: &str) -> bool {
+    text.split('/').next().map(|value| value.parse::<IpAddr>().is_ok()).unwrap_or(false)
+}
+
+fn invalid_address(position: usize, text: &str) -> FilterParseError {
+    FilterParseError {
+        position,
+        kind: FilterParseErrorKind::InvalidAddress(text.to_string())
+    }
+}
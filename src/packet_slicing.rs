@@ -1,10 +1,26 @@
 use super::*;
 
-///A slice containing the link layer header (currently only Ethernet II is supported).
+///A slice containing the link layer header (Ethernet II or the PPP header of a frame tunneled via L2TP).
+///
+///Further link layer protocols may be added in the future, so matches have to contain a wildcard arm.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
 pub enum LinkSlice<'a> {
     ///A slice containing an Ethernet II header.
-    Ethernet2(Ethernet2HeaderSlice<'a>)
+    Ethernet2(Ethernet2HeaderSlice<'a>),
+    ///A slice containing a PPP header (only used for frames tunneled via L2TPv2 or an L2TPv3 PPP pseudowire).
+    Ppp(PppHeaderSlice<'a>)
+}
+
+impl<'a> LinkSlice<'a> {
+    ///Returns the slice containing the link layer header.
+    pub fn slice(&self) -> &'a [u8] {
+        use crate::LinkSlice::*;
+        match self {
+            Ethernet2(value) => value.slice(),
+            Ppp(value) => value.slice()
+        }
+    }
 }
 
 ///A slice containing a single or double vlan header.
@@ -41,12 +57,48 @@ pub enum TransportSlice<'a> {
     Tcp(TcpHeaderSlice<'a>)
 }
 
+///A slice containing a tunnel header that encapsulates a further packet.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TunnelSlice<'a> {
+    ///A slice containing a VXLAN header.
+    Vxlan(VxlanHeaderSlice<'a>),
+    ///A slice containing a Geneve header (including the options).
+    Geneve(GeneveHeaderSlice<'a>),
+    ///A slice containing a GTP-U header (including the optional fields & extension headers).
+    Gtpu(GtpuHeaderSlice<'a>),
+    ///A slice containing an L2TPv2 header (including the attribute value pairs in case of a control message).
+    L2tpv2(L2tpv2HeaderSlice<'a>),
+    ///A slice containing an L2TPv3 control message (including the attribute value pairs).
+    L2tpv3Control(L2tpv3ControlHeaderSlice<'a>),
+    ///A slice containing an L2TPv3 data message session header.
+    L2tpv3Session(L2tpv3SessionHeaderSlice<'a>)
+}
+
+impl<'a> TunnelSlice<'a> {
+    ///Returns the slice containing the tunnel header.
+    pub fn slice(&self) -> &'a [u8] {
+        use crate::TunnelSlice::*;
+        match self {
+            Vxlan(value) => value.slice(),
+            Geneve(value) => value.slice(),
+            Gtpu(value) => value.slice(),
+            L2tpv2(value) => value.slice(),
+            L2tpv3Control(value) => value.slice(),
+            L2tpv3Session(value) => value.slice()
+        }
+    }
+}
+
 ///A sliced into its component headers. Everything that could not be parsed is stored in a slice in the field "payload".
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct SlicedPacket<'a> {
     pub link: Option<LinkSlice<'a>>,
     pub vlan: Option<VlanSlice<'a>>,
     pub ip: Option<InternetSlice<'a>>,
+    ///IPsec authentication header (AH). For IPv6 the authentication header is also contained in the extension headers of the ip field.
+    pub authentication_header: Option<AuthenticationHeaderSlice<'a>>,
+    ///IPsec encapsulating security payload header (ESP). The encrypted data following the ESP header is contained in the payload field.
+    pub esp: Option<EspHeaderSlice<'a>>,
     pub transport: Option<TransportSlice<'a>>,
     /// The payload field points to the rest of the packet that could not be parsed by etherparse.
     ///
@@ -55,18 +107,62 @@ pub struct SlicedPacket<'a> {
     ///
     /// For example if transport field contains Some(Udp(_)) then the payload field points to the udp payload.
     /// On the other hand if the transport field contains None then the payload contains the payload of
-    /// next field containing a Some value (in order of transport, ip, vlan, link).
+    /// next field containing a Some value (in order of transport, esp, authentication_header, ip, vlan, link).
     pub payload: &'a [u8]
 }
 
+///A packet sliced with tunnel detection enabled, containing the headers of the outer packet, the tunnel header and the encapsulated inner packet.
+///
+///Tunnels are detected based on the udp destination port (`VXLAN_PORT`, `GENEVE_PORT` & `GTPU_PORT`), the udp
+///source or destination port `L2TP_PORT` or the ip protocol number 115 (L2TPv3 over ip).
+#[derive(Debug)]
+pub struct TunneledSlicedPacket<'a> {
+    ///Headers of the outer packet. The payload of the outer packet contains the tunnel header and the encapsulated packet.
+    pub outer: SlicedPacket<'a>,
+    ///The tunnel header (None if no tunnel was detected or the tunnel header is malformed).
+    pub tunnel: Option<TunnelSlice<'a>>,
+    ///Headers of the encapsulated packet (None if no tunnel was detected, the encapsulated protocol is not supported
+    ///or the encapsulated packet is malformed).
+    pub inner: Option<SlicedPacket<'a>>,
+    ///Error that occurred while slicing the tunnel header or the encapsulated packet (with the offset relative to the
+    ///start of the given data). None if no error occurred.
+    pub error: Option<ReadError>
+}
+
+///Maximum number of encapsulated ip packets (IP-in-IP, 6in4, 4in6 & 6in6) that are decapsulated by
+///[`SlicedPacket::from_ethernet_decapsulated`](struct.SlicedPacket.html#method.from_ethernet_decapsulated) &
+///[`SlicedPacket::from_ip_decapsulated`](struct.SlicedPacket.html#method.from_ip_decapsulated).
+pub const IP_MAX_DECAPSULATION_DEPTH: usize = 8;
+
+///A packet sliced with ip decapsulation enabled, containing the headers of the outer packet and the encapsulated ip packets (IP-in-IP, 6in4, 4in6 & 6in6).
+#[derive(Debug)]
+pub struct DecapsulatedSlicedPacket<'a> {
+    ///Headers of the outer packet. If an encapsulated packet was decoded the transport field is None and the payload contains the encapsulated packet.
+    pub outer: SlicedPacket<'a>,
+    ///Encapsulated packets filled in order from the outermost to the innermost starting at index 0 (the link & vlan fields are always None).
+    ///
+    ///Only the innermost packet can contain a transport header, all other packets contain the next encapsulated packet as payload.
+    pub encapsulated: [Option<SlicedPacket<'a>>; IP_MAX_DECAPSULATION_DEPTH],
+    ///Error that occurred while slicing an encapsulated packet (with the offset relative to the start of the given
+    ///data). None if no error occurred.
+    pub error: Option<ReadError>
+}
+
 const ETH_IPV4: u16 = EtherType::Ipv4 as u16;
 const ETH_IPV6: u16 = EtherType::Ipv6 as u16;
 const ETH_VLAN: u16 = EtherType::VlanTaggedFrame as u16;
 const ETH_BRIDGE: u16 = EtherType::ProviderBridging as u16;
 const ETH_VLAN_DOUBLE: u16 = EtherType::VlanDoubleTaggedFrame as u16;
+const ETH_TEB: u16 = EtherType::TransparentEthernetBridging as u16;
 
 const IP_UDP: u8 = IpTrafficClass::Udp as u8;
 const IP_TCP: u8 = IpTrafficClass::Tcp as u8;
+const IP_IPV4: u8 = IpTrafficClass::IPv4 as u8;
+const IP_IPV6: u8 = IpTrafficClass::Ipv6 as u8;
+const IP_IPV6_FRAG: u8 = IpTrafficClass::IPv6FragmentationHeader as u8;
+const IP_AUTH: u8 = IpTrafficClass::IPv6AuthenticationHeader as u8;
+const IP_ESP: u8 = IpTrafficClass::IPv6EncapSecurityPayload as u8;
+const IP_L2TP: u8 = IpTrafficClass::Layer2TunnelingProtocol as u8;
 
 impl<'a> SlicedPacket<'a> {
     /// Seperates a network packet slice into different slices containing the headers from the ethernet header downwards. 
@@ -147,13 +243,462 @@ impl<'a> SlicedPacket<'a> {
     pub fn from_ip(data: &'a [u8]) -> Result<SlicedPacket, ReadError> {
         CursorSlice::new(data).slice_ip()
     }
+
+    ///Same as `from_ethernet` but stops slicing after the given layer (the rest is returned as payload).
+    pub(crate) fn from_ethernet_up_to(data: &'a [u8], last_layer: SliceLayer) -> Result<SlicedPacket<'a>, ReadError> {
+        let mut cursor = CursorSlice::new(data);
+        cursor.last_layer = last_layer;
+        cursor.slice_ethernet2()
+    }
+
+    ///Same as `from_ip` but stops slicing after the given layer (the ip header is always sliced).
+    pub(crate) fn from_ip_up_to(data: &'a [u8], last_layer: SliceLayer) -> Result<SlicedPacket<'a>, ReadError> {
+        let mut cursor = CursorSlice::new(data);
+        cursor.last_layer = std::cmp::max(SliceLayer::Ip, last_layer);
+        cursor.slice_ip()
+    }
+
+    /// Seperates a network packet slice into different slices containing the headers from the ethernet header downwards 
+    /// and additionally decodes VXLAN, Geneve, GTP-U & L2TP tunnels and the encapsulated packet.
+    ///
+    /// Tunnels are detected based on the udp destination port (4789 for VXLAN, 6081 for Geneve & 2152 for GTP-U). L2TP is
+    /// detected based on the udp source or destination port 1701 (L2TPv2 & L2TPv3) and the ip protocol number 115 (L2TPv3).
+    /// If a tunnel is detected, the headers of the encapsulated packet are sliced as well (for GTP-U only the ip packets of
+    /// G-PDU messages and for L2TP only the PPP frames of L2TPv2 data messages, L2TPv3 sessions can be sliced with
+    /// [`SlicedPacket::from_ethernet_tunneled_l2tpv3`](#method.from_ethernet_tunneled_l2tpv3)). As tunnels are
+    /// only detected based on port & protocol numbers, errors in the tunnel header or the encapsulated packet don't
+    /// fail the slicing. Instead the outer packet (& the tunnel header) are still returned and the error is set in the
+    /// `error` field (with the offset relative to the start of the given data).
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    ///```
+    /// # use etherparse::{SlicedPacket, PacketBuilder, VXLAN_PORT};
+    /// # let inner_builder = PacketBuilder::
+    /// #    ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+    /// #    .ipv4([10,0,0,1], [10,0,0,2], 20)
+    /// #    .udp(1234, 5678);
+    /// # let mut inner = Vec::<u8>::with_capacity(inner_builder.size(4));
+    /// # inner_builder.write(&mut inner, &[1,2,3,4]).unwrap();
+    /// # let builder = PacketBuilder::
+    /// #    ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+    /// #    .ipv4([192,168,1,1], [192,168,1,2], 20)
+    /// #    .udp(49152, VXLAN_PORT)
+    /// #    .vxlan(100);
+    /// # let mut packet = Vec::<u8>::with_capacity(builder.size(inner.len()));
+    /// # builder.write(&mut packet, &inner).unwrap();
+    /// match SlicedPacket::from_ethernet_tunneled(&packet) {
+    ///     Err(value) => println!("Err {:?}", value),
+    ///     Ok(value) => {
+    ///         println!("outer ip: {:?}", value.outer.ip);
+    ///         println!("tunnel: {:?}", value.tunnel);
+    ///         if let Some(inner) = value.inner {
+    ///             println!("inner ip: {:?}", inner.ip);
+    ///             println!("inner transport: {:?}", inner.transport);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn from_ethernet_tunneled(data: &'a [u8]) -> Result<TunneledSlicedPacket<'a>, ReadError> {
+        let outer = CursorSlice::new(data).slice_ethernet2()?;
+        Ok(TunneledSlicedPacket::from_outer(data, outer, None))
+    }
+
+    /// Seperates a network packet slice into different slices containing the headers from the ip header downwards 
+    /// and additionally decodes VXLAN, Geneve, GTP-U & L2TP tunnels and the encapsulated packet.
+    ///
+    /// See [`SlicedPacket::from_ethernet_tunneled`](#method.from_ethernet_tunneled) for details on the tunnel detection.
+    pub fn from_ip_tunneled(data: &'a [u8]) -> Result<TunneledSlicedPacket<'a>, ReadError> {
+        let outer = CursorSlice::new(data).slice_ip()?;
+        Ok(TunneledSlicedPacket::from_outer(data, outer, None))
+    }
+
+    /// Seperates a network packet slice into different slices containing the headers from the ethernet header downwards
+    /// and additionally decodes tunnels like [`SlicedPacket::from_ethernet_tunneled`](#method.from_ethernet_tunneled).
+    ///
+    /// Additionally the frames tunneled in L2TPv3 data messages are sliced based on the given session format
+    /// (cookie length, L2-specific sublayer & pseudowire type), which is only known via the control connection.
+    ///
+    /// # Example
+    ///
+    ///```
+    /// # use etherparse::*;
+    /// # let inner_builder = PacketBuilder::
+    /// #    ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+    /// #    .ipv4([10,0,0,1], [10,0,0,2], 20)
+    /// #    .udp(1234, 5678);
+    /// # let mut payload = vec![0, 3, 0, 0, 0, 0, 0, 1, 0xc0, 0xff, 0xee, 0x00, 0, 0, 0, 0];
+    /// # inner_builder.write(&mut payload, &[1,2,3,4]).unwrap();
+    /// # let builder = PacketBuilder::
+    /// #    ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+    /// #    .ipv4([192,168,1,1], [192,168,1,2], 20)
+    /// #    .udp(L2TP_PORT, L2TP_PORT);
+    /// # let mut packet = Vec::<u8>::with_capacity(builder.size(payload.len()));
+    /// # builder.write(&mut packet, &payload).unwrap();
+    /// let format = L2tpv3SessionFormat{
+    ///     cookie_len: 4,
+    ///     sublayer_len: 4,
+    ///     pseudowire_type: L2tpv3PseudowireType::Ethernet,
+    /// };
+    /// let value = SlicedPacket::from_ethernet_tunneled_l2tpv3(&packet, &format).unwrap();
+    /// assert!(value.inner.unwrap().ip.is_some());
+    /// ```
+    pub fn from_ethernet_tunneled_l2tpv3(data: &'a [u8], format: &L2tpv3SessionFormat) -> Result<TunneledSlicedPacket<'a>, ReadError> {
+        let outer = CursorSlice::new(data).slice_ethernet2()?;
+        Ok(TunneledSlicedPacket::from_outer(data, outer, Some(format)))
+    }
+
+    /// Seperates a network packet slice into different slices containing the headers from the ip header downwards
+    /// and additionally decodes tunnels including the frames tunneled via L2TPv3 with the given session format.
+    ///
+    /// See [`SlicedPacket::from_ethernet_tunneled_l2tpv3`](#method.from_ethernet_tunneled_l2tpv3) for details.
+    pub fn from_ip_tunneled_l2tpv3(data: &'a [u8], format: &L2tpv3SessionFormat) -> Result<TunneledSlicedPacket<'a>, ReadError> {
+        let outer = CursorSlice::new(data).slice_ip()?;
+        Ok(TunneledSlicedPacket::from_outer(data, outer, Some(format)))
+    }
+
+    /// Seperates a network packet slice into different slices containing the headers from the ethernet header downwards
+    /// and additionally decodes ip packets encapsulated in ip packets (IP-in-IP, 6in4, 4in6 & 6in6).
+    ///
+    /// Encapsulated packets are detected based on the ip protocol number (4 for IPv4 & 41 for IPv6). Decapsulation
+    /// is repeated until no further encapsulated packet is present or `max_depth` encapsulated packets have been
+    /// decoded (`max_depth` is limited to `IP_MAX_DECAPSULATION_DEPTH`). Packets beyond the depth limit are kept as
+    /// payload of the last decoded packet. Fragmented ip packets are not decapsulated, as they only contain a part
+    /// of the encapsulated packet.
+    ///
+    /// Errors that occur while slicing the encapsulated packets don't fail the slicing. Instead the outer packet & the
+    /// encapsulated packets decoded before the error are returned and the error is set in the `error` field (with the
+    /// offset relative to the start of the given data).
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    ///```
+    /// # use etherparse::{SlicedPacket, PacketBuilder};
+    /// # let builder = PacketBuilder::
+    /// #    ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+    /// #    .ipv4([192,168,1,1], [192,168,1,2], 20)
+    /// #    .ipv6([1;16], [2;16], 20)
+    /// #    .udp(1234, 5678);
+    /// # let mut packet = Vec::<u8>::with_capacity(builder.size(4));
+    /// # builder.write(&mut packet, &[1,2,3,4]).unwrap();
+    /// match SlicedPacket::from_ethernet_decapsulated(&packet, 4) {
+    ///     Err(value) => println!("Err {:?}", value),
+    ///     Ok(value) => {
+    ///         println!("outer ip: {:?}", value.outer.ip);
+    ///         let innermost = value.innermost();
+    ///         println!("inner ip: {:?}", innermost.ip);
+    ///         println!("inner transport: {:?}", innermost.transport);
+    ///     }
+    /// }
+    /// ```
+    pub fn from_ethernet_decapsulated(data: &'a [u8], max_depth: usize) -> Result<DecapsulatedSlicedPacket<'a>, ReadError> {
+        let outer = CursorSlice::new(data).slice_ethernet2()?;
+        Ok(DecapsulatedSlicedPacket::from_outer(data, outer, max_depth))
+    }
+
+    /// Seperates a network packet slice into different slices containing the headers from the ip header downwards
+    /// and additionally decodes ip packets encapsulated in ip packets (IP-in-IP, 6in4, 4in6 & 6in6).
+    ///
+    /// See [`SlicedPacket::from_ethernet_decapsulated`](#method.from_ethernet_decapsulated) for details on the decapsulation.
+    pub fn from_ip_decapsulated(data: &'a [u8], max_depth: usize) -> Result<DecapsulatedSlicedPacket<'a>, ReadError> {
+        let outer = CursorSlice::new(data).slice_ip()?;
+        Ok(DecapsulatedSlicedPacket::from_outer(data, outer, max_depth))
+    }
+}
+
+impl<'a> SlicedPacket<'a> {
+
+    ///Returns the ip protocol number of the payload if the payload directly follows the ip header (& authentication header).
+    ///
+    ///None is returned if no ip header is present, a transport or esp header was decoded or the packet is fragmented
+    ///(fragmented packets only contain a part of the payload).
+    pub(crate) fn payload_ip_protocol(&self) -> Option<u8> {
+        use crate::InternetSlice::*;
+        if self.transport.is_some() || self.esp.is_some() {
+            return None;
+        }
+        match &self.ip {
+            Some(Ipv4(header)) => {
+                if header.more_fragments() || 0 != header.fragments_offset() {
+                    return None;
+                }
+                //the authentication header protects the payload
+                match &self.authentication_header {
+                    Some(auth) => Some(auth.next_header()),
+                    None => Some(header.protocol())
+                }
+            },
+            Some(Ipv6(header, extensions)) => {
+                let mut next_header = header.next_header();
+                for (ext_type, ext) in extensions.iter().flatten() {
+                    if IP_IPV6_FRAG == *ext_type {
+                        return None;
+                    }
+                    next_header = ext.next_header();
+                }
+                Some(next_header)
+            },
+            None => None
+        }
+    }
+
+    ///Returns the ip protocol number of the transport layer (None if no ip header is present).
+    ///
+    ///Other then `payload_ip_protocol` a value is also returned for fragments & packets with a decoded transport
+    ///header (for ipv6 fragments the "next header" of the fragmentation header is returned).
+    pub(crate) fn transport_ip_protocol(&self) -> Option<u8> {
+        use crate::InternetSlice::*;
+        match &self.transport {
+            Some(TransportSlice::Udp(_)) => return Some(IpTrafficClass::Udp as u8),
+            Some(TransportSlice::Tcp(_)) => return Some(IpTrafficClass::Tcp as u8),
+            None => {}
+        }
+        match &self.ip {
+            Some(Ipv4(header)) => match &self.authentication_header {
+                Some(auth) => Some(auth.next_header()),
+                None => Some(header.protocol())
+            },
+            Some(Ipv6(header, extensions)) => {
+                let mut next_header = header.next_header();
+                for (ext_type, ext) in extensions.iter().flatten() {
+                    next_header = ext.next_header();
+                    //headers after the fragmentation header are not decoded
+                    if IP_IPV6_FRAG == *ext_type {
+                        break;
+                    }
+                }
+                Some(next_header)
+            },
+            None => None
+        }
+    }
+
+    ///Returns true if the packet contains an ipv4 header or ipv6 fragmentation header indicating a fragment.
+    pub(crate) fn is_ip_fragment(&self) -> bool {
+        use crate::InternetSlice::*;
+        match &self.ip {
+            Some(Ipv4(header)) => header.more_fragments() || 0 != header.fragments_offset(),
+            Some(Ipv6(_, extensions)) => extensions.iter().flatten().any(|(ext_type, _)| IP_IPV6_FRAG == *ext_type),
+            None => false
+        }
+    }
+}
+
+impl<'a> TunneledSlicedPacket<'a> {
+
+    ///Detects & slices tunnel headers in the payload of an already sliced packet.
+    ///
+    ///The frames tunneled via L2TPv3 sessions are only sliced if the session format is known.
+    fn from_outer(data: &'a [u8], outer: SlicedPacket<'a>, l2tpv3_format: Option<&L2tpv3SessionFormat>) -> TunneledSlicedPacket<'a> {
+        use crate::TunnelSlice::*;
+
+        //offset of the outer payload in the original data (used for the errors)
+        let offset = data.len() - outer.payload.len();
+
+        //detect the tunnel based on the udp ports or the ip protocol
+        let tunnel = match &outer.transport {
+            Some(TransportSlice::Udp(udp)) => match udp.destination_port() {
+                VXLAN_PORT => VxlanHeaderSlice::from_slice(outer.payload).map(Vxlan),
+                GENEVE_PORT => GeneveHeaderSlice::from_slice(outer.payload).map(Geneve),
+                GTPU_PORT => GtpuHeaderSlice::from_slice(outer.payload).map(Gtpu),
+                //l2tp replies are send from the l2tp port to the port choosen by the peer
+                destination_port if L2TP_PORT == destination_port || L2TP_PORT == udp.source_port() => 
+                    TunneledSlicedPacket::slice_l2tp_udp(outer.payload),
+                _ => return TunneledSlicedPacket{ outer, tunnel: None, inner: None, error: None }
+            },
+            None => match outer.payload_ip_protocol() {
+                Some(IP_L2TP) => TunneledSlicedPacket::slice_l2tp_ip(outer.payload),
+                _ => return TunneledSlicedPacket{ outer, tunnel: None, inner: None, error: None }
+            },
+            _ => return TunneledSlicedPacket{ outer, tunnel: None, inner: None, error: None }
+        };
+
+        //a malformed tunnel header is reported together with the outer packet (the tunnel was only detected
+        //based on the port or protocol number & the payload might be something else)
+        let tunnel = match tunnel {
+            Ok(value) => value,
+            Err(err) => return TunneledSlicedPacket{
+                outer,
+                tunnel: None,
+                inner: None,
+                error: Some(err.add_slice_offset(offset))
+            }
+        };
+
+        //slice the encapsulated packet (if it is malformed the outer packet & tunnel header are still returned)
+        let tunnel_len = tunnel.slice().len();
+        let mut cursor = CursorSlice::new(&outer.payload[tunnel_len..]);
+        cursor.offset = offset + tunnel_len;
+        let (inner, error) = match TunneledSlicedPacket::slice_inner(&tunnel, cursor, l2tpv3_format) {
+            Ok(value) => (value, None),
+            Err(err) => (None, Some(err))
+        };
+
+        TunneledSlicedPacket{
+            outer,
+            tunnel: Some(tunnel),
+            inner,
+            error
+        }
+    }
+
+    ///Slices the packet encapsulated in the given tunnel (None if the encapsulated protocol is not supported).
+    fn slice_inner(tunnel: &TunnelSlice<'a>, mut cursor: CursorSlice<'a>, l2tpv3_format: Option<&L2tpv3SessionFormat>) -> Result<Option<SlicedPacket<'a>>, ReadError> {
+        use crate::TunnelSlice::*;
+        Ok(match tunnel {
+            Vxlan(_) => Some(cursor.slice_ethernet2()?),
+            Geneve(header) => match header.protocol_type() {
+                ETH_TEB => Some(cursor.slice_ethernet2()?),
+                ETH_IPV4 => Some(cursor.slice_ipv4()?),
+                ETH_IPV6 => Some(cursor.slice_ipv6()?),
+                _ => None
+            },
+            Gtpu(header) => match header.message_type() {
+                GTPU_MESSAGE_TYPE_G_PDU => Some(cursor.slice_ip()?),
+                _ => None
+            },
+            L2tpv2(header) => if header.is_control() {
+                None
+            } else {
+                Some(cursor.slice_ppp()?)
+            },
+            L2tpv3Control(_) => None,
+            //the pseudowire type & cookie length of l2tpv3 sessions are only known via the control connection
+            L2tpv3Session(_) => match l2tpv3_format {
+                Some(format) => {
+                    //skip the cookie & the l2-specific sublayer
+                    let skip_len = format.cookie_len + format.sublayer_len;
+                    if cursor.slice.len() < skip_len {
+                        return Err(ReadError::UnexpectedEndOfSlice(cursor.offset + skip_len));
+                    }
+                    let skipped = &cursor.slice[..skip_len];
+                    cursor.move_by_slice(skipped);
+                    match format.pseudowire_type {
+                        L2tpv3PseudowireType::Ethernet => Some(cursor.slice_ethernet2()?),
+                        L2tpv3PseudowireType::Ppp => Some(cursor.slice_ppp()?)
+                    }
+                },
+                None => None
+            }
+        })
+    }
+
+    ///Slices an L2TPv2 or L2TPv3 message transported via udp (based on the version field).
+    fn slice_l2tp_udp(payload: &'a [u8]) -> Result<TunnelSlice<'a>, ReadError> {
+        use crate::TunnelSlice::*;
+        if payload.len() < 2 {
+            return Err(ReadError::UnexpectedEndOfSlice(2));
+        }
+        match payload[1] & 0xf {
+            2 => Ok(L2tpv2(L2tpv2HeaderSlice::from_slice(payload)?)),
+            //control messages are identified by the "type" flag
+            3 => if 0 != payload[0] & 0x80 {
+                Ok(L2tpv3Control(L2tpv3ControlHeaderSlice::from_slice(payload)?))
+            } else {
+                Ok(L2tpv3Session(L2tpv3SessionHeaderSlice::from_slice(payload)?))
+            },
+            version => Err(ReadError::L2tpUnexpectedVersion(version))
+        }
+    }
+
+    ///Slices an L2TPv3 message transported directly over ip.
+    fn slice_l2tp_ip(payload: &'a [u8]) -> Result<TunnelSlice<'a>, ReadError> {
+        use crate::TunnelSlice::*;
+        if payload.len() < 4 {
+            return Err(ReadError::UnexpectedEndOfSlice(4));
+        }
+        //control messages are identified by the reserved session id 0
+        if [0;4] == payload[..4] {
+            Ok(L2tpv3Control(L2tpv3ControlHeaderSlice::from_ip_slice(payload)?))
+        } else {
+            Ok(L2tpv3Session(L2tpv3SessionHeaderSlice::from_ip_slice(payload)?))
+        }
+    }
+}
+
+impl<'a> DecapsulatedSlicedPacket<'a> {
+
+    ///Returns the number of decoded encapsulated packets.
+    pub fn depth(&self) -> usize {
+        self.encapsulated.iter().take_while(|value| value.is_some()).count()
+    }
+
+    ///Returns the innermost packet (the outer packet if no encapsulated packet was decoded).
+    pub fn innermost(&self) -> &SlicedPacket<'a> {
+        match self.encapsulated.iter().take_while(|value| value.is_some()).last() {
+            Some(Some(value)) => value,
+            _ => &self.outer
+        }
+    }
+
+    ///Decodes the encapsulated ip packets in the payload of an already sliced packet.
+    fn from_outer(data: &'a [u8], outer: SlicedPacket<'a>, max_depth: usize) -> DecapsulatedSlicedPacket<'a> {
+        let mut result = DecapsulatedSlicedPacket {
+            outer,
+            encapsulated: [None, None, None, None,
+                           None, None, None, None],
+            error: None
+        };
+
+        let mut next = DecapsulatedSlicedPacket::encapsulated_protocol(&result.outer);
+        for i in 0..std::cmp::min(max_depth, IP_MAX_DECAPSULATION_DEPTH) {
+            let (protocol, payload) = match next {
+                Some(value) => value,
+                None => break
+            };
+
+            //slice the encapsulated packet (if it is malformed the already decoded packets are still returned)
+            let mut cursor = CursorSlice::new(payload);
+            cursor.offset = data.len() - payload.len();
+            let packet = match protocol {
+                IP_IPV4 => cursor.slice_ipv4(),
+                _ => cursor.slice_ipv6()
+            };
+            match packet {
+                Ok(packet) => {
+                    next = DecapsulatedSlicedPacket::encapsulated_protocol(&packet);
+                    result.encapsulated[i] = Some(packet);
+                },
+                Err(err) => {
+                    result.error = Some(err);
+                    break;
+                }
+            }
+        }
+        result
+    }
+
+    ///Returns the protocol & payload if the given packet contains an encapsulated ip packet.
+    fn encapsulated_protocol(packet: &SlicedPacket<'a>) -> Option<(u8, &'a [u8])> {
+        match packet.payload_ip_protocol() {
+            Some(protocol) if IP_IPV4 == protocol || IP_IPV6 == protocol => Some((protocol, packet.payload)),
+            _ => None
+        }
+    }
+}
+
+///Layers of a packet in the order they are sliced.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub(crate) enum SliceLayer {
+    Link,
+    Vlan,
+    ///Ip header including the ip extension & authentication headers.
+    Ip,
+    ///Transport & esp header.
+    Transport,
 }
 
 ///Helper class for slicing packets
 struct CursorSlice<'a> {
     pub slice: &'a [u8],
     pub offset: usize,
-    pub result: SlicedPacket<'a>
+    pub result: SlicedPacket<'a>,
+    ///Layers after this layer are not sliced and left in the payload.
+    pub last_layer: SliceLayer
 }
 
 impl<'a> CursorSlice<'a> {
@@ -166,9 +711,12 @@ impl<'a> CursorSlice<'a> {
                 link: None,
                 vlan: None,
                 ip: None,
+                authentication_header: None,
+                esp: None,
                 transport: None,
                 payload: slice
-            }
+            },
+            last_layer: SliceLayer::Transport
         }
     }
 
@@ -204,6 +752,10 @@ impl<'a> CursorSlice<'a> {
     pub fn slice_vlan(mut self) -> Result<SlicedPacket<'a>, ReadError> {
         use crate::VlanSlice::*;
 
+        if self.last_layer < SliceLayer::Vlan {
+            return self.slice_payload();
+        }
+
         let single = SingleVlanHeaderSlice::from_slice(self.slice)
                      .map_err(|err| 
                         err.add_slice_offset(self.offset)
@@ -267,6 +819,10 @@ impl<'a> CursorSlice<'a> {
     pub fn slice_ipv4(mut self) -> Result<SlicedPacket<'a>, ReadError> {
         use crate::InternetSlice::*;
 
+        if self.last_layer < SliceLayer::Ip {
+            return self.slice_payload();
+        }
+
         let result = Ipv4HeaderSlice::from_slice(self.slice)
                      .map_err(|err| 
                         err.add_slice_offset(self.offset)
@@ -282,6 +838,8 @@ impl<'a> CursorSlice<'a> {
         match protocol {
             IP_UDP => self.slice_udp(),
             IP_TCP => self.slice_tcp(),
+            IP_AUTH => self.slice_ip_authentication_header(),
+            IP_ESP => self.slice_esp(),
             _ => self.slice_payload()
         }
     }
@@ -289,6 +847,10 @@ impl<'a> CursorSlice<'a> {
     pub fn slice_ipv6(mut self) -> Result<SlicedPacket<'a>, ReadError> {
         use crate::InternetSlice::*;
 
+        if self.last_layer < SliceLayer::Ip {
+            return self.slice_payload();
+        }
+
         let ip = Ipv6HeaderSlice::from_slice(self.slice)
                  .map_err(|err| 
                     err.add_slice_offset(self.offset)
@@ -304,9 +866,20 @@ impl<'a> CursorSlice<'a> {
 
         let mut next_header = ip.next_header();
         for extension_header in ip_extensions.iter_mut() {
-            if !IpTrafficClass::is_ipv6_ext_header_value(next_header) {
+            //the esp header is followed by encrypted data and ends the extension headers
+            if IP_ESP == next_header || !IpTrafficClass::is_ipv6_ext_header_value(next_header) {
                 break;
             } else {
+                //additionally decode the authentication header
+                if IP_AUTH == next_header {
+                    self.result.authentication_header = Some(
+                        AuthenticationHeaderSlice::from_slice(self.slice)
+                        .map_err(|err| 
+                            err.add_slice_offset(self.offset)
+                        )?
+                    );
+                }
+
                 let ext = Ipv6ExtensionHeaderSlice::from_slice(next_header, self.slice)
                           .map_err(|err| 
                             err.add_slice_offset(self.offset)
@@ -323,7 +896,7 @@ impl<'a> CursorSlice<'a> {
         }
 
         //parse the underlying protocol (or error in case of too many extension headers)
-        if IpTrafficClass::is_ipv6_ext_header_value(next_header)
+        if IP_ESP != next_header && IpTrafficClass::is_ipv6_ext_header_value(next_header)
         {
             Err(ReadError::Ipv6TooManyHeaderExtensions)
         } else {
@@ -334,14 +907,82 @@ impl<'a> CursorSlice<'a> {
             match next_header {
                 IP_UDP => self.slice_udp(),
                 IP_TCP => self.slice_tcp(),
+                IP_ESP => self.slice_esp(),
                 _ => self.slice_payload()
             }
         }
     }
 
+    pub fn slice_ip_authentication_header(mut self) -> Result<SlicedPacket<'a>, ReadError> {
+        let result = AuthenticationHeaderSlice::from_slice(self.slice)
+                     .map_err(|err| 
+                        err.add_slice_offset(self.offset)
+                     )?;
+
+        //cache the next header for later
+        let next_header = result.next_header();
+
+        //set the new data
+        self.move_by_slice(result.slice());
+        self.result.authentication_header = Some(result);
+
+        //continue with the protected data
+        match next_header {
+            IP_UDP => self.slice_udp(),
+            IP_TCP => self.slice_tcp(),
+            IP_ESP => self.slice_esp(),
+            _ => self.slice_payload()
+        }
+    }
+
+    pub fn slice_esp(mut self) -> Result<SlicedPacket<'a>, ReadError> {
+        if self.last_layer < SliceLayer::Transport {
+            return self.slice_payload();
+        }
+
+        let result = EspHeaderSlice::from_slice(self.slice)
+                     .map_err(|err| 
+                        err.add_slice_offset(self.offset)
+                     )?;
+
+        //set the new data
+        self.move_by_slice(result.slice());
+        self.result.esp = Some(result);
+
+        //the rest is encrypted
+        self.slice_payload()
+    }
+
+    pub fn slice_ppp(mut self) -> Result<SlicedPacket<'a>, ReadError> {
+        use crate::LinkSlice::*;
+
+        let result = PppHeaderSlice::from_slice(self.slice)
+                     .map_err(|err| 
+                        err.add_slice_offset(self.offset)
+                     )?;
+
+        //cache the protocol for later
+        let protocol = result.protocol();
+
+        //set the new data
+        self.move_by_slice(result.slice());
+        self.result.link = Some(Ppp(result));
+
+        //continue parsing (if required)
+        match protocol {
+            PPP_PROTOCOL_IPV4 => self.slice_ipv4(),
+            PPP_PROTOCOL_IPV6 => self.slice_ipv6(),
+            _ => self.slice_payload()
+        }
+    }
+
     pub fn slice_udp(mut self) -> Result<SlicedPacket<'a>, ReadError> {
         use crate::TransportSlice::*;
 
+        if self.last_layer < SliceLayer::Transport {
+            return self.slice_payload();
+        }
+
         let result = UdpHeaderSlice::from_slice(self.slice)
                      .map_err(|err| 
                         err.add_slice_offset(self.offset)
@@ -358,6 +999,10 @@ impl<'a> CursorSlice<'a> {
     pub fn slice_tcp(mut self) -> Result<SlicedPacket<'a>, ReadError> {
         use crate::TransportSlice::*;
 
+        if self.last_layer < SliceLayer::Transport {
+            return self.slice_payload();
+        }
+
         let result = TcpHeaderSlice::from_slice(self.slice)
                      .map_err(|err| 
                         err.add_slice_offset(self.offset)
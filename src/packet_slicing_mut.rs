@@ -0,0 +1,204 @@
+use super::*;
+
+///A mutable slice containing a single or double vlan header.
+#[derive(Debug, Eq, PartialEq)]
+pub enum VlanSliceMut<'a> {
+    SingleVlan(SingleVlanHeaderSliceMut<'a>),
+    DoubleVlan(DoubleVlanHeaderSliceMut<'a>),
+}
+
+///A mutable slice containing an ipv4 or ipv6 header.
+#[derive(Debug, Eq, PartialEq)]
+pub enum InternetSliceMut<'a> {
+    Ipv4(Ipv4HeaderSliceMut<'a>),
+    Ipv6(Ipv6HeaderSliceMut<'a>),
+}
+
+///A mutable slice containing a transport header.
+#[derive(Debug, Eq, PartialEq)]
+pub enum TransportSliceMut<'a> {
+    ///A mutable slice containing an UDP header.
+    Udp(UdpHeaderSliceMut<'a>),
+    ///A mutable slice containing a TCP header.
+    Tcp(TcpHeaderSliceMut<'a>)
+}
+
+///A packet sliced into mutable slices of its component headers, allowing the in place modification of the header fields.
+///
+///The packet is sliced the same way as by [`SlicedPacket`](struct.SlicedPacket.html).
+#[derive(Debug, Eq, PartialEq)]
+pub struct SlicedPacketMut<'a> {
+    pub link: Option<Ethernet2HeaderSliceMut<'a>>,
+    pub vlan: Option<VlanSliceMut<'a>>,
+    pub ip: Option<InternetSliceMut<'a>>,
+    ///Headers located between the ip header and the transport header or payload (ipv6 extension headers,
+    ///IPsec authentication header & encapsulating security payload header). Empty if no such headers are present.
+    pub ip_extensions: &'a mut [u8],
+    pub transport: Option<TransportSliceMut<'a>>,
+    ///The rest of the packet that could not be parsed by etherparse (see the payload field of [`SlicedPacket`](struct.SlicedPacket.html)).
+    pub payload: &'a mut [u8]
+}
+
+impl<'a> SlicedPacketMut<'a> {
+    ///Seperates a network packet slice into mutable slices containing the headers from the ethernet header downwards.
+    ///
+    ///This function assumes the given data starts with an ethernet II header.
+    ///
+    /// # Example
+    ///
+    /// Decrementing the ttl & changing the udp source port (the checksums are updated automatically):
+    ///
+    ///```
+    /// # use etherparse::*;
+    /// # let mut packet = Vec::new();
+    /// # PacketBuilder::ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+    /// #    .ipv4([192,168,1,1], [192,168,1,2], 20)
+    /// #    .udp(21, 1234)
+    /// #    .write(&mut packet, &[1,2,3,4]).unwrap();
+    /// let mut sliced = SlicedPacketMut::from_ethernet(&mut packet).unwrap();
+    /// if let Some(InternetSliceMut::Ipv4(ip)) = &mut sliced.ip {
+    ///     let ttl = ip.as_slice().ttl();
+    ///     ip.set_ttl(ttl - 1);
+    /// }
+    /// if let Some(TransportSliceMut::Udp(udp)) = &mut sliced.transport {
+    ///     udp.set_source_port(4321);
+    /// }
+    ///
+    /// let checksums = SlicedPacket::from_ethernet(&packet).unwrap().verify_checksums();
+    /// assert!(!checksums.contains_invalid());
+    /// ```
+    pub fn from_ethernet(data: &'a mut [u8]) -> Result<SlicedPacketMut<'a>, ReadError> {
+        let layout = Layout::new(data, &SlicedPacket::from_ethernet(data)?);
+        layout.split(data)
+    }
+
+    ///Seperates a network packet slice into mutable slices containing the headers from the ip header downwards.
+    ///
+    ///This function assumes the given data starts with an IPv4 or IPv6 header.
+    pub fn from_ip(data: &'a mut [u8]) -> Result<SlicedPacketMut<'a>, ReadError> {
+        let layout = Layout::new(data, &SlicedPacket::from_ip(data)?);
+        layout.split(data)
+    }
+}
+
+///Positions of the headers in a sliced packet.
+struct Layout {
+    ///Start & length.
+    link: Option<(usize, usize)>,
+    ///Double tagged, start & length.
+    vlan: Option<(bool, usize, usize)>,
+    ///Version 4, start & length.
+    ip: Option<(bool, usize, usize)>,
+    ///Start & length of the ip extensions.
+    ip_extensions: (usize, usize),
+    ///Udp, start & length.
+    transport: Option<(bool, usize, usize)>,
+    ///Start & length of the payload.
+    payload: (usize, usize)
+}
+
+impl Layout {
+    ///Determines the positions of the headers of a packet sliced from the given data.
+    fn new(data: &[u8], sliced: &SlicedPacket) -> Layout {
+        let offset = |slice: &[u8]| slice.as_ptr() as usize - data.as_ptr() as usize;
+
+        let link = sliced.link.as_ref().and_then(|link| match link {
+            LinkSlice::Ethernet2(header) => Some((offset(header.slice()), header.slice().len())),
+            //only present in tunneled frames
+            LinkSlice::Ppp(_) => None
+        });
+
+        let vlan = sliced.vlan.as_ref().map(|vlan| match vlan {
+            VlanSlice::SingleVlan(header) => (false, offset(header.slice()), header.slice().len()),
+            VlanSlice::DoubleVlan(header) => (true, offset(header.slice()), header.slice().len())
+        });
+
+        let ip = sliced.ip.as_ref().map(|ip| match ip {
+            InternetSlice::Ipv4(header) => (true, offset(header.slice()), header.slice().len()),
+            InternetSlice::Ipv6(header, _) => (false, offset(header.slice()), header.slice().len())
+        });
+
+        let transport = sliced.transport.as_ref().map(|transport| match transport {
+            TransportSlice::Udp(header) => (true, offset(header.slice()), header.slice().len()),
+            TransportSlice::Tcp(header) => (false, offset(header.slice()), header.slice().len())
+        });
+
+        let payload = (offset(sliced.payload), sliced.payload.len());
+
+        //everything between the ip header & the transport header or payload
+        let ip_extensions = match ip {
+            Some((_, start, len)) => (
+                start + len,
+                transport.map_or(payload.0, |(_, start, _)| start) - (start + len)
+            ),
+            None => (payload.0, 0)
+        };
+
+        Layout {
+            link,
+            vlan,
+            ip,
+            ip_extensions,
+            transport,
+            payload
+        }
+    }
+
+    ///Splits the data into the mutable header slices.
+    fn split(self, data: &mut [u8]) -> Result<SlicedPacketMut<'_>, ReadError> {
+        let mut rest = data;
+        let mut position = 0;
+
+        let link = match self.link {
+            Some((start, len)) => Some(Ethernet2HeaderSliceMut::from_slice(take(&mut rest, &mut position, start, len))?),
+            None => None
+        };
+        let vlan = match self.vlan {
+            Some((false, start, len)) => Some(VlanSliceMut::SingleVlan(
+                SingleVlanHeaderSliceMut::from_slice(take(&mut rest, &mut position, start, len))?
+            )),
+            Some((true, start, len)) => Some(VlanSliceMut::DoubleVlan(
+                DoubleVlanHeaderSliceMut::from_slice(take(&mut rest, &mut position, start, len))?
+            )),
+            None => None
+        };
+        let ip = match self.ip {
+            Some((true, start, len)) => Some(InternetSliceMut::Ipv4(
+                Ipv4HeaderSliceMut::from_slice(take(&mut rest, &mut position, start, len))?
+            )),
+            Some((false, start, len)) => Some(InternetSliceMut::Ipv6(
+                Ipv6HeaderSliceMut::from_slice(take(&mut rest, &mut position, start, len))?
+            )),
+            None => None
+        };
+        let ip_extensions = take(&mut rest, &mut position, self.ip_extensions.0, self.ip_extensions.1);
+        let transport = match self.transport {
+            Some((true, start, len)) => Some(TransportSliceMut::Udp(
+                UdpHeaderSliceMut::from_slice(take(&mut rest, &mut position, start, len))?
+            )),
+            Some((false, start, len)) => Some(TransportSliceMut::Tcp(
+                TcpHeaderSliceMut::from_slice(take(&mut rest, &mut position, start, len))?
+            )),
+            None => None
+        };
+        let payload = take(&mut rest, &mut position, self.payload.0, self.payload.1);
+
+        Ok(SlicedPacketMut {
+            link,
+            vlan,
+            ip,
+            ip_extensions,
+            transport,
+            payload
+        })
+    }
+}
+
+///Takes `len` bytes starting at `start` from the rest of the data (`position` is the start of the rest).
+fn take<'a>(rest: &mut &'a mut [u8], position: &mut usize, start: usize, len: usize) -> &'a mut [u8] {
+    let data = std::mem::take(rest);
+    let (result, remaining) = data[start - *position..].split_at_mut(len);
+    *rest = remaining;
+    *position = start + len;
+    result
+}
@@ -0,0 +1,275 @@
+use super::*;
+
+///Errors that can occur when splitting a packet into segments.
+#[derive(Debug)]
+pub enum SegmentationError {
+    ///The packet contains no ip header or no tcp or udp header.
+    MissingTransportHeader,
+    ///The packet is an ip fragment (only complete packets can be segmented).
+    IpFragment,
+    ///The packet contains an IPsec authentication header (the integrity check value can not be recalculated).
+    AuthenticationHeader,
+    ///The maximum segment size is zero.
+    ZeroSegmentSize,
+    ///Error while encoding the headers of the segments (e.g. a segment that is too large for the length fields).
+    Write(WriteError),
+}
+
+impl fmt::Display for SegmentationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use SegmentationError::*;
+        match self {
+            MissingTransportHeader => {
+                write!(f, "SegmentationError: The packet contains no ip header or no tcp or udp header.")
+            },
+            IpFragment => {
+                write!(f, "SegmentationError: The packet is an ip fragment (only complete packets can be segmented).")
+            },
+            AuthenticationHeader => {
+                write!(f, "SegmentationError: The packet contains an IPsec authentication header (the integrity check value can not be recalculated).")
+            },
+            ZeroSegmentSize => {
+                write!(f, "SegmentationError: The maximum segment size is zero.")
+            },
+            Write(err) => {
+                write!(f, "SegmentationError: Error while encoding the segments: {}", err)
+            },
+        }
+    }
+}
+
+impl Error for SegmentationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use SegmentationError::*;
+        match self {
+            Write(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl From<WriteError> for SegmentationError {
+    fn from(err: WriteError) -> SegmentationError {
+        SegmentationError::Write(err)
+    }
+}
+
+impl From<ValueError> for SegmentationError {
+    fn from(err: ValueError) -> SegmentationError {
+        SegmentationError::Write(WriteError::ValueError(err))
+    }
+}
+
+impl<'a> SlicedPacket<'a> {
+    ///Splits a tcp or udp packet with a payload larger then `max_segment_size` bytes into multiple packets
+    ///with at most `max_segment_size` payload bytes each (software TCP segmentation & UDP segmentation offload).
+    ///
+    ///The link, vlan, ip & transport headers are repeated in every segment with the following changes:
+    ///
+    ///* The lengths & checksums of the ip & transport headers are recalculated (ipv4 udp packets without
+    ///  checksum stay without checksum).
+    ///* The identification of the ipv4 header is incremented for every segment.
+    ///* The tcp sequence number is advanced by the payload size of the previous segments, the PSH & FIN flags
+    ///  are only kept in the last & the CWR flag only in the first segment.
+    ///* Every udp segment is a complete udp datagram (for UDP fragmentation offload the udp packet can instead be
+    ///  split into ip fragments using [`fragment_ipv4_packet`](fn.fragment_ipv4_packet.html) & [`fragment_ipv6_packet`](fn.fragment_ipv6_packet.html)).
+    ///
+    ///Data past the length given in the ip header (e.g. ethernet padding) is ignored. A packet that is not larger then
+    ///the segment size is returned as a single segment.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use etherparse::*;
+    /// let mut packet = Vec::new();
+    /// PacketBuilder::ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+    ///     .ipv4([192,168,1,1], [192,168,1,2], 20)
+    ///     .tcp(1234, 80, 1000, 1024)
+    ///     .psh()
+    ///     .write(&mut packet, &[0;3000])
+    ///     .unwrap();
+    ///
+    /// let segments = SlicedPacket::from_ethernet(&packet).unwrap().segment(1460).unwrap();
+    /// assert_eq!(3, segments.len());
+    ///
+    /// let last = SlicedPacket::from_ethernet(&segments[2]).unwrap();
+    /// match &last.transport {
+    ///     Some(TransportSlice::Tcp(tcp)) => {
+    ///         assert_eq!(1000 + 2*1460, tcp.sequence_number());
+    ///         assert!(tcp.psh());
+    ///     },
+    ///     _ => panic!("expected a tcp header")
+    /// }
+    /// assert_eq!(80, last.payload.len());
+    /// assert!(!last.verify_checksums().contains_invalid());
+    /// ```
+    pub fn segment(&self, max_segment_size: usize) -> Result<Vec<Vec<u8>>, SegmentationError> {
+        use crate::SegmentationError::*;
+        const IP_FRAG: u8 = IpTrafficClass::IPv6FragmentationHeader as u8;
+        const IP_AUTH: u8 = IpTrafficClass::IPv6AuthenticationHeader as u8;
+
+        if self.authentication_header.is_some() {
+            return Err(AuthenticationHeader);
+        }
+
+        let mut link = Vec::new();
+        match &self.link {
+            Some(LinkSlice::Ethernet2(value)) => link.extend_from_slice(value.slice()),
+            Some(LinkSlice::Ppp(value)) => link.extend_from_slice(value.slice()),
+            None => {}
+        }
+        match &self.vlan {
+            Some(VlanSlice::SingleVlan(value)) => link.extend_from_slice(value.slice()),
+            Some(VlanSlice::DoubleVlan(value)) => link.extend_from_slice(value.slice()),
+            None => {}
+        }
+
+        let mut extensions = Vec::new();
+        let ip = match &self.ip {
+            Some(InternetSlice::Ipv4(header)) => IpHeader::Version4(header.to_header()),
+            Some(InternetSlice::Ipv6(header, extension_headers)) => {
+                for (id, extension) in extension_headers.iter().flatten() {
+                    match *id {
+                        IP_FRAG => return Err(IpFragment),
+                        IP_AUTH => return Err(AuthenticationHeader),
+                        _ => extensions.extend_from_slice(extension.slice())
+                    }
+                }
+                IpHeader::Version6(header.to_header())
+            },
+            None => return Err(MissingTransportHeader)
+        };
+
+        let transport = match &self.transport {
+            Some(TransportSlice::Udp(header)) => TransportHeader::Udp(header.to_header()),
+            Some(TransportSlice::Tcp(header)) => TransportHeader::Tcp(header.to_header()),
+            None => return Err(MissingTransportHeader)
+        };
+
+        segment(&link, ip, &extensions, transport, self.payload, max_segment_size)
+    }
+}
+
+impl<'a> PacketHeaders<'a> {
+    ///Splits a tcp or udp packet with a payload larger then `max_segment_size` bytes into multiple packets
+    ///with at most `max_segment_size` payload bytes each.
+    ///
+    ///See [`SlicedPacket::segment`](struct.SlicedPacket.html#method.segment) for details. As `PacketHeaders`
+    ///does not contain the ipv6 extension headers, the segments are written without them.
+    pub fn segment(&self, max_segment_size: usize) -> Result<Vec<Vec<u8>>, SegmentationError> {
+        use crate::SegmentationError::*;
+
+        let mut link = Vec::new();
+        if let Some(value) = &self.link {
+            value.write(&mut link).map_err(WriteError::from)?;
+        }
+        match &self.vlan {
+            Some(VlanHeader::Single(value)) => value.write(&mut link)?,
+            Some(VlanHeader::Double(value)) => value.write(&mut link)?,
+            None => {}
+        }
+
+        let transport = match &self.transport {
+            Some(value) => value.clone(),
+            None => return Err(MissingTransportHeader)
+        };
+        let ip = match &self.ip {
+            Some(IpHeader::Version6(value)) => {
+                //the extension headers are not part of the segments
+                let mut value = value.clone();
+                value.next_header = transport_protocol(&transport);
+                IpHeader::Version6(value)
+            },
+            Some(value) => value.clone(),
+            None => return Err(MissingTransportHeader)
+        };
+
+        segment(&link, ip, &[], transport, self.payload, max_segment_size)
+    }
+}
+
+fn transport_protocol(transport: &TransportHeader) -> u8 {
+    match transport {
+        TransportHeader::Udp(_) => IpTrafficClass::Udp as u8,
+        TransportHeader::Tcp(_) => IpTrafficClass::Tcp as u8
+    }
+}
+
+///Writes the segments of a packet (`extensions` contains the raw ipv6 extension headers).
+fn segment(link: &[u8], ip: IpHeader, extensions: &[u8], transport: TransportHeader, payload: &[u8], max_segment_size: usize) -> Result<Vec<Vec<u8>>, SegmentationError> {
+    use crate::SegmentationError::*;
+
+    if 0 == max_segment_size {
+        return Err(ZeroSegmentSize);
+    }
+
+    //determine the payload length based on the ip header (ignoring padding past the packet end)
+    let ip_payload_len = match &ip {
+        IpHeader::Version4(header) => {
+            if header.more_fragments || 0 != header.fragments_offset {
+                return Err(IpFragment);
+            }
+            usize::from(header.payload_len)
+        },
+        IpHeader::Version6(header) => usize::from(header.payload_length)
+    };
+    let payload_len = std::cmp::min(
+        payload.len(),
+        ip_payload_len.saturating_sub(extensions.len() + transport.header_len())
+    );
+    let payload = &payload[..payload_len];
+
+    //packets without payload result in one segment
+    let segments: Vec<&[u8]> = if payload.is_empty() {
+        vec![payload]
+    } else {
+        payload.chunks(max_segment_size).collect()
+    };
+    let mut result = Vec::with_capacity(segments.len());
+    for (index, data) in segments.iter().enumerate() {
+        let start = index * max_segment_size;
+        let first = 0 == index;
+        let last = index + 1 == segments.len();
+
+        let mut transport = transport.clone();
+        match &mut transport {
+            TransportHeader::Tcp(tcp) => {
+                tcp.sequence_number = tcp.sequence_number.wrapping_add(start as u32);
+                tcp.cwr = tcp.cwr && first;
+                tcp.psh = tcp.psh && last;
+                tcp.fin = tcp.fin && last;
+            },
+            TransportHeader::Udp(udp) => {
+                udp.length = (UdpHeader::SERIALIZED_SIZE + data.len()) as u16;
+            }
+        }
+        let ip_payload_len = extensions.len() + transport.header_len() + data.len();
+
+        let mut segment = Vec::with_capacity(link.len() + 40 + ip_payload_len);
+        segment.extend_from_slice(link);
+        match &ip {
+            IpHeader::Version4(header) => {
+                let mut header = header.clone();
+                header.identification = header.identification.wrapping_add(index as u16);
+                header.set_payload_len(ip_payload_len)?;
+                match &mut transport {
+                    //keep udp packets without checksum without a checksum
+                    TransportHeader::Udp(udp) if 0 == udp.checksum => {},
+                    _ => transport.update_checksum_ipv4(&header, data)?
+                }
+                header.write(&mut segment)?;
+            },
+            IpHeader::Version6(header) => {
+                let mut header = header.clone();
+                header.set_payload_length(ip_payload_len)?;
+                transport.update_checksum_ipv6(&header, data)?;
+                header.write(&mut segment)?;
+            }
+        }
+        segment.extend_from_slice(extensions);
+        transport.write(&mut segment)?;
+        segment.extend_from_slice(data);
+        result.push(segment);
+    }
+    Ok(result)
+}
@@ -0,0 +1,557 @@
+//! Decoding of sFlow version 5 datagrams.
+//!
+//! [`SflowDatagram::from_slice`](struct.SflowDatagram.html#method.from_slice) decodes the UDP payload sent by an sFlow
+//! agent into flow samples & counter samples (compact & expanded formats) and their records. The sampled packet
+//! headers are not copied and can be sliced directly via [`RawPacketHeader::sliced`](struct.RawPacketHeader.html#method.sliced).
+//!
+//! # Example
+//!
+//! ```
+//! use etherparse::*;
+//! use etherparse::sflow::*;
+//!
+//! # let mut frame = Vec::new();
+//! # PacketBuilder::ethernet2([1;6], [2;6])
+//! #     .ipv4([192,168,1,1], [192,168,1,2], 20)
+//! #     .udp(1234, 53)
+//! #     .write(&mut frame, &[0;4])
+//! #     .unwrap();
+//! # let words = |values: &[u32]| values.iter().flat_map(|value| value.to_be_bytes().to_vec()).collect::<Vec<u8>>();
+//! # let mut record = words(&[1, frame.len() as u32 + 4, 4, frame.len() as u32]);
+//! # record.extend_from_slice(&frame);
+//! # while record.len() % 4 != 0 { record.push(0); }
+//! # let mut sample = words(&[1, 3, 512, 1024, 0, 3, 4, 1, 1, record.len() as u32]);
+//! # sample.extend_from_slice(&record);
+//! # let mut datagram = words(&[5, 1, 0x0a000001, 0, 1, 1000, 1, 1, sample.len() as u32]);
+//! # datagram.extend_from_slice(&sample);
+//! //datagram contains the UDP payload sent by the agent
+//! let datagram = SflowDatagram::from_slice(&datagram).unwrap();
+//! for sample in &datagram.samples {
+//!     if let SflowSample::Flow(flow) = sample {
+//!         for record in &flow.records {
+//!             if let FlowSampleRecord::RawPacketHeader(header) = record {
+//!                 let sliced = header.sliced().unwrap().unwrap();
+//!                 assert_matches_udp(&sliced);
+//!             }
+//!         }
+//!     }
+//! }
+//! # fn assert_matches_udp(sliced: &SlicedPacket) {
+//! #     match &sliced.transport {
+//! #         Some(TransportSlice::Udp(udp)) => assert_eq!(53, udp.destination_port()),
+//! #         value => panic!("unexpected transport {:?}", value)
+//! #     }
+//! # }
+//! ```
+use super::*;
+
+extern crate byteorder;
+use self::byteorder::{ByteOrder, BigEndian};
+
+use std::net::IpAddr;
+
+///UDP port of sFlow collectors.
+pub const SFLOW_PORT: u16 = 6343;
+
+///Header protocol values of sampled packet headers that can be sliced.
+const HEADER_PROTOCOL_ETHERNET: u32 = 1;
+const HEADER_PROTOCOL_IPV4: u32 = 11;
+const HEADER_PROTOCOL_IPV6: u32 = 12;
+
+///Errors that can occur when decoding an sFlow datagram.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SflowError {
+    ///The slice ended before the end of the datagram (expected minimum size as argument).
+    UnexpectedEndOfSlice(usize),
+    ///The datagram version is not 5.
+    UnsupportedVersion(u32),
+    ///The agent address type is neither 1 (IPv4) nor 2 (IPv6).
+    UnsupportedAddressType(u32),
+}
+
+impl fmt::Display for SflowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use SflowError::*;
+        match self {
+            UnexpectedEndOfSlice(expected_minimum_size) => {
+                write!(f, "SflowError: Unexpected end of slice. The given slice contained less then minimum required {} bytes.", expected_minimum_size)
+            },
+            UnsupportedVersion(version) => {
+                write!(f, "SflowError: Unsupported datagram version {} (only version 5 is supported).", version)
+            },
+            UnsupportedAddressType(address_type) => {
+                write!(f, "SflowError: Unsupported agent address type {} (only 1 for IPv4 & 2 for IPv6 are supported).", address_type)
+            },
+        }
+    }
+}
+
+impl Error for SflowError {
+
+}
+
+///Decoded sFlow version 5 datagram.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SflowDatagram<'a> {
+    pub agent_address: IpAddr,
+    pub sub_agent_id: u32,
+    pub sequence_number: u32,
+    ///Milliseconds since the agent booted.
+    pub uptime: u32,
+    pub samples: Vec<SflowSample<'a>>,
+}
+
+///Sample contained in an sFlow datagram.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SflowSample<'a> {
+    ///Flow sample (compact or expanded format).
+    Flow(FlowSample<'a>),
+    ///Counter sample (compact or expanded format).
+    Counter(CounterSample<'a>),
+    ///Sample with an unknown enterprise or format.
+    Unknown { enterprise: u32, format: u32, data: &'a [u8] },
+}
+
+///Interface of a flow sample (format & value as defined in the sFlow specification).
+///
+///For format 0 the value is the ifIndex of the interface (0x3fffffff if the interface is unknown).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SflowInterface {
+    pub format: u32,
+    pub value: u32,
+}
+
+///Sampled packet & the records describing it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FlowSample<'a> {
+    ///True if the sample was encoded in the expanded format.
+    pub expanded: bool,
+    pub sequence_number: u32,
+    pub source_id_type: u32,
+    pub source_id_index: u32,
+    pub sampling_rate: u32,
+    ///Total number of packets that could have been sampled.
+    pub sample_pool: u32,
+    ///Number of packets dropped due to a lack of resources.
+    pub drops: u32,
+    pub input: SflowInterface,
+    pub output: SflowInterface,
+    pub records: Vec<FlowSampleRecord<'a>>,
+}
+
+///Record of a flow sample.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FlowSampleRecord<'a> {
+    RawPacketHeader(RawPacketHeader<'a>),
+    EthernetFrame {
+        ///Length of the frame (including the FCS).
+        length: u32,
+        source: [u8;6],
+        destination: [u8;6],
+        ether_type: u32,
+    },
+    Ipv4 {
+        ///Length of the ip packet (excluding lower layer encapsulations).
+        length: u32,
+        protocol: u32,
+        source: [u8;4],
+        destination: [u8;4],
+        source_port: u32,
+        destination_port: u32,
+        tcp_flags: u32,
+        tos: u32,
+    },
+    Ipv6 {
+        ///Length of the ip packet (excluding lower layer encapsulations).
+        length: u32,
+        protocol: u32,
+        source: [u8;16],
+        destination: [u8;16],
+        source_port: u32,
+        destination_port: u32,
+        tcp_flags: u32,
+        priority: u32,
+    },
+    ExtendedSwitch {
+        source_vlan: u32,
+        source_priority: u32,
+        destination_vlan: u32,
+        destination_priority: u32,
+    },
+    ///Record with an unknown enterprise or format.
+    Unknown { enterprise: u32, format: u32, data: &'a [u8] },
+}
+
+///Sampled header of a packet (usually truncated to the first bytes of the packet).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RawPacketHeader<'a> {
+    ///Protocol of the header (1 = Ethernet, 11 = IPv4, 12 = IPv6, see the sFlow specification for other values).
+    pub protocol: u32,
+    ///Original length of the packet before sampling.
+    pub frame_length: u32,
+    ///Number of bytes removed from the packet before the header was sampled (e.g. the FCS).
+    pub stripped: u32,
+    pub header: &'a [u8],
+}
+
+impl<'a> RawPacketHeader<'a> {
+    ///Slices the sampled header with `SlicedPacket::from_ethernet` or `SlicedPacket::from_ip` depending on the protocol.
+    ///
+    ///None is returned if the protocol is neither Ethernet, IPv4 nor IPv6. Note that a sampled header usually only
+    ///contains the start of a packet. Payloads shorter then indicated by the ip headers are accepted, but an error is
+    ///returned if the sampled header ends within one of the decoded headers.
+    pub fn sliced(&self) -> Option<Result<SlicedPacket<'a>, ReadError>> {
+        match self.protocol {
+            HEADER_PROTOCOL_ETHERNET => Some(SlicedPacket::from_ethernet(self.header)),
+            HEADER_PROTOCOL_IPV4 | HEADER_PROTOCOL_IPV6 => Some(SlicedPacket::from_ip(self.header)),
+            _ => None
+        }
+    }
+}
+
+///Counters of a data source.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CounterSample<'a> {
+    ///True if the sample was encoded in the expanded format.
+    pub expanded: bool,
+    pub sequence_number: u32,
+    pub source_id_type: u32,
+    pub source_id_index: u32,
+    pub records: Vec<CounterRecord<'a>>,
+}
+
+///Record of a counter sample.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CounterRecord<'a> {
+    GenericInterface(GenericInterfaceCounters),
+    EthernetInterface(EthernetInterfaceCounters),
+    Processor(ProcessorCounters),
+    ///Record with an unknown enterprise or format.
+    Unknown { enterprise: u32, format: u32, data: &'a [u8] },
+}
+
+///Generic interface counters (RFC 2233).
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct GenericInterfaceCounters {
+    pub index: u32,
+    pub if_type: u32,
+    pub speed: u64,
+    ///Direction (0 = unknown, 1 = full duplex, 2 = half duplex, 3 = in, 4 = out).
+    pub direction: u32,
+    ///Admin status (bit 0) & operational status (bit 1).
+    pub status: u32,
+    pub in_octets: u64,
+    pub in_unicast_packets: u32,
+    pub in_multicast_packets: u32,
+    pub in_broadcast_packets: u32,
+    pub in_discards: u32,
+    pub in_errors: u32,
+    pub in_unknown_protocols: u32,
+    pub out_octets: u64,
+    pub out_unicast_packets: u32,
+    pub out_multicast_packets: u32,
+    pub out_broadcast_packets: u32,
+    pub out_discards: u32,
+    pub out_errors: u32,
+    pub promiscuous_mode: u32,
+}
+
+///Ethernet interface counters (RFC 2358).
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EthernetInterfaceCounters {
+    pub alignment_errors: u32,
+    pub fcs_errors: u32,
+    pub single_collision_frames: u32,
+    pub multiple_collision_frames: u32,
+    pub sqe_test_errors: u32,
+    pub deferred_transmissions: u32,
+    pub late_collisions: u32,
+    pub excessive_collisions: u32,
+    pub internal_mac_transmit_errors: u32,
+    pub carrier_sense_errors: u32,
+    pub frame_too_longs: u32,
+    pub internal_mac_receive_errors: u32,
+    pub symbol_errors: u32,
+}
+
+///Processor & memory counters.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ProcessorCounters {
+    ///Cpu utilization over the last 5 seconds, 1 minute & 5 minutes (in 1/100 of a percent).
+    pub cpu_5s: u32,
+    pub cpu_1m: u32,
+    pub cpu_5m: u32,
+    pub total_memory: u64,
+    pub free_memory: u64,
+}
+
+///Reads the XDR encoded values of a datagram.
+///
+///Keeps track of the offset in the complete datagram so the errors contain the minimum datagram size.
+struct Reader<'a> {
+    slice: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], SflowError> {
+        if self.slice.len() < len {
+            return Err(SflowError::UnexpectedEndOfSlice(self.offset + len));
+        }
+        let (result, rest) = self.slice.split_at(len);
+        self.slice = rest;
+        self.offset += len;
+        Ok(result)
+    }
+
+    ///Reads an opaque value padded to a multiple of 4 bytes.
+    fn opaque(&mut self, len: usize) -> Result<&'a [u8], SflowError> {
+        let result = self.bytes(len)?;
+        let padding = std::cmp::min((4 - len % 4) % 4, self.slice.len());
+        self.bytes(padding)?;
+        Ok(result)
+    }
+
+    fn u32(&mut self) -> Result<u32, SflowError> {
+        self.bytes(4).map(BigEndian::read_u32)
+    }
+
+    fn u64(&mut self) -> Result<u64, SflowError> {
+        self.bytes(8).map(BigEndian::read_u64)
+    }
+
+    fn array<T: AsMut<[u8]> + Default>(&mut self) -> Result<T, SflowError> {
+        let mut result = T::default();
+        let len = result.as_mut().len();
+        result.as_mut().copy_from_slice(self.bytes(len)?);
+        Ok(result)
+    }
+
+    ///Reads a (data format, length) pair & returns the enterprise, format & a reader for the data.
+    fn structure(&mut self) -> Result<(u32, u32, Reader<'a>), SflowError> {
+        let data_format = self.u32()?;
+        let len = self.u32()? as usize;
+        let offset = self.offset;
+        let slice = self.bytes(len)?;
+        Ok((data_format >> 12, data_format & 0xfff, Reader{ slice, offset }))
+    }
+}
+
+impl<'a> SflowDatagram<'a> {
+    ///Decodes an sFlow version 5 datagram (the UDP payload sent by the agent).
+    pub fn from_slice(slice: &'a [u8]) -> Result<SflowDatagram<'a>, SflowError> {
+        use self::SflowError::*;
+
+        let mut reader = Reader{ slice, offset: 0 };
+        let version = reader.u32()?;
+        if 5 != version {
+            return Err(UnsupportedVersion(version));
+        }
+        let agent_address = match reader.u32()? {
+            1 => IpAddr::from(reader.array::<[u8;4]>()?),
+            2 => IpAddr::from(reader.array::<[u8;16]>()?),
+            value => return Err(UnsupportedAddressType(value))
+        };
+        let sub_agent_id = reader.u32()?;
+        let sequence_number = reader.u32()?;
+        let uptime = reader.u32()?;
+        let sample_count = reader.u32()?;
+        let mut samples = Vec::new();
+        for _ in 0..sample_count {
+            let (enterprise, format, mut data) = reader.structure()?;
+            samples.push(match (enterprise, format) {
+                (0, 1) => SflowSample::Flow(decode_flow_sample(&mut data, false)?),
+                (0, 2) => SflowSample::Counter(decode_counter_sample(&mut data, false)?),
+                (0, 3) => SflowSample::Flow(decode_flow_sample(&mut data, true)?),
+                (0, 4) => SflowSample::Counter(decode_counter_sample(&mut data, true)?),
+                _ => SflowSample::Unknown{ enterprise, format, data: data.slice }
+            });
+        }
+        Ok(SflowDatagram {
+            agent_address,
+            sub_agent_id,
+            sequence_number,
+            uptime,
+            samples,
+        })
+    }
+}
+
+///Reads the source id of a sample (type & index).
+fn decode_source_id(reader: &mut Reader, expanded: bool) -> Result<(u32, u32), SflowError> {
+    if expanded {
+        Ok((reader.u32()?, reader.u32()?))
+    } else {
+        let value = reader.u32()?;
+        Ok((value >> 24, value & 0x00ff_ffff))
+    }
+}
+
+fn decode_interface(reader: &mut Reader, expanded: bool) -> Result<SflowInterface, SflowError> {
+    if expanded {
+        Ok(SflowInterface {
+            format: reader.u32()?,
+            value: reader.u32()?,
+        })
+    } else {
+        let value = reader.u32()?;
+        Ok(SflowInterface {
+            format: value >> 30,
+            value: value & 0x3fff_ffff,
+        })
+    }
+}
+
+fn decode_flow_sample<'a>(reader: &mut Reader<'a>, expanded: bool) -> Result<FlowSample<'a>, SflowError> {
+    let sequence_number = reader.u32()?;
+    let (source_id_type, source_id_index) = decode_source_id(reader, expanded)?;
+    let sampling_rate = reader.u32()?;
+    let sample_pool = reader.u32()?;
+    let drops = reader.u32()?;
+    let input = decode_interface(reader, expanded)?;
+    let output = decode_interface(reader, expanded)?;
+    let record_count = reader.u32()?;
+    let mut records = Vec::new();
+    for _ in 0..record_count {
+        let (enterprise, format, mut data) = reader.structure()?;
+        records.push(decode_flow_record(enterprise, format, &mut data)?);
+    }
+    Ok(FlowSample {
+        expanded,
+        sequence_number,
+        source_id_type,
+        source_id_index,
+        sampling_rate,
+        sample_pool,
+        drops,
+        input,
+        output,
+        records,
+    })
+}
+
+fn decode_flow_record<'a>(enterprise: u32, format: u32, reader: &mut Reader<'a>) -> Result<FlowSampleRecord<'a>, SflowError> {
+    use self::FlowSampleRecord::*;
+    Ok(match (enterprise, format) {
+        (0, 1) => {
+            let protocol = reader.u32()?;
+            let frame_length = reader.u32()?;
+            let stripped = reader.u32()?;
+            let header_len = reader.u32()? as usize;
+            RawPacketHeader(self::RawPacketHeader {
+                protocol,
+                frame_length,
+                stripped,
+                header: reader.opaque(header_len)?,
+            })
+        },
+        (0, 2) => {
+            let length = reader.u32()?;
+            //mac addresses are padded to 8 bytes
+            let source = reader.array::<[u8;6]>()?;
+            reader.bytes(2)?;
+            let destination = reader.array::<[u8;6]>()?;
+            reader.bytes(2)?;
+            EthernetFrame {
+                length,
+                source,
+                destination,
+                ether_type: reader.u32()?,
+            }
+        },
+        (0, 3) => Ipv4 {
+            length: reader.u32()?,
+            protocol: reader.u32()?,
+            source: reader.array()?,
+            destination: reader.array()?,
+            source_port: reader.u32()?,
+            destination_port: reader.u32()?,
+            tcp_flags: reader.u32()?,
+            tos: reader.u32()?,
+        },
+        (0, 4) => Ipv6 {
+            length: reader.u32()?,
+            protocol: reader.u32()?,
+            source: reader.array()?,
+            destination: reader.array()?,
+            source_port: reader.u32()?,
+            destination_port: reader.u32()?,
+            tcp_flags: reader.u32()?,
+            priority: reader.u32()?,
+        },
+        (0, 1001) => ExtendedSwitch {
+            source_vlan: reader.u32()?,
+            source_priority: reader.u32()?,
+            destination_vlan: reader.u32()?,
+            destination_priority: reader.u32()?,
+        },
+        _ => Unknown{ enterprise, format, data: reader.slice }
+    })
+}
+
+fn decode_counter_sample<'a>(reader: &mut Reader<'a>, expanded: bool) -> Result<CounterSample<'a>, SflowError> {
+    let sequence_number = reader.u32()?;
+    let (source_id_type, source_id_index) = decode_source_id(reader, expanded)?;
+    let record_count = reader.u32()?;
+    let mut records = Vec::new();
+    for _ in 0..record_count {
+        let (enterprise, format, mut data) = reader.structure()?;
+        records.push(decode_counter_record(enterprise, format, &mut data)?);
+    }
+    Ok(CounterSample {
+        expanded,
+        sequence_number,
+        source_id_type,
+        source_id_index,
+        records,
+    })
+}
+
+fn decode_counter_record<'a>(enterprise: u32, format: u32, reader: &mut Reader<'a>) -> Result<CounterRecord<'a>, SflowError> {
+    Ok(match (enterprise, format) {
+        (0, 1) => CounterRecord::GenericInterface(GenericInterfaceCounters {
+            index: reader.u32()?,
+            if_type: reader.u32()?,
+            speed: reader.u64()?,
+            direction: reader.u32()?,
+            status: reader.u32()?,
+            in_octets: reader.u64()?,
+            in_unicast_packets: reader.u32()?,
+            in_multicast_packets: reader.u32()?,
+            in_broadcast_packets: reader.u32()?,
+            in_discards: reader.u32()?,
+            in_errors: reader.u32()?,
+            in_unknown_protocols: reader.u32()?,
+            out_octets: reader.u64()?,
+            out_unicast_packets: reader.u32()?,
+            out_multicast_packets: reader.u32()?,
+            out_broadcast_packets: reader.u32()?,
+            out_discards: reader.u32()?,
+            out_errors: reader.u32()?,
+            promiscuous_mode: reader.u32()?,
+        }),
+        (0, 2) => CounterRecord::EthernetInterface(EthernetInterfaceCounters {
+            alignment_errors: reader.u32()?,
+            fcs_errors: reader.u32()?,
+            single_collision_frames: reader.u32()?,
+            multiple_collision_frames: reader.u32()?,
+            sqe_test_errors: reader.u32()?,
+            deferred_transmissions: reader.u32()?,
+            late_collisions: reader.u32()?,
+            excessive_collisions: reader.u32()?,
+            internal_mac_transmit_errors: reader.u32()?,
+            carrier_sense_errors: reader.u32()?,
+            frame_too_longs: reader.u32()?,
+            internal_mac_receive_errors: reader.u32()?,
+            symbol_errors: reader.u32()?,
+        }),
+        (0, 1001) => CounterRecord::Processor(ProcessorCounters {
+            cpu_5s: reader.u32()?,
+            cpu_1m: reader.u32()?,
+            cpu_5m: reader.u32()?,
+            total_memory: reader.u64()?,
+            free_memory: reader.u64()?,
+        }),
+        _ => CounterRecord::Unknown{ enterprise, format, data: reader.slice }
+    })
+}
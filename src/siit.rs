@@ -0,0 +1,628 @@
+use super::*;
+
+extern crate byteorder;
+use self::byteorder::{ByteOrder, BigEndian};
+
+use crate::checksum::Sum16BitWords;
+
+///Ipv6 prefix used to embed ipv4 addresses in ipv6 addresses (rfc6052).
+///
+///The ipv4 address is placed directly after the prefix, skipping the bits 64 to 71 of the ipv6 address
+///("u" octet), which are always zero. The suffix following the ipv4 address is zero.
+///
+/// # Example
+///
+/// ```
+/// # use etherparse::*;
+/// let prefix = Nat64Prefix::WELL_KNOWN;
+/// let address = prefix.embed([192,0,2,33]);
+/// assert_eq!(
+///     [0,0x64,0xff,0x9b, 0,0,0,0, 0,0,0,0, 192,0,2,33],
+///     address
+/// );
+/// assert_eq!(Some([192,0,2,33]), prefix.extract(&address));
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Nat64Prefix {
+    prefix: [u8;16],
+    prefix_len: u8
+}
+
+impl Nat64Prefix {
+    ///Well-known prefix 64:ff9b::/96 (rfc6052 section 2.1).
+    pub const WELL_KNOWN: Nat64Prefix = Nat64Prefix {
+        prefix: [0,0x64,0xff,0x9b, 0,0,0,0, 0,0,0,0, 0,0,0,0],
+        prefix_len: 96
+    };
+
+    ///Creates a prefix with the given length in bits (allowed are 32, 40, 48, 56, 64 & 96).
+    ///
+    ///Bits past the prefix length are ignored. For a prefix length of 96 the "u" octet (bits 64 to 71)
+    ///has to be zero.
+    pub fn new(prefix: [u8;16], prefix_len: u8) -> Result<Nat64Prefix, SiitError> {
+        match prefix_len {
+            32 | 40 | 48 | 56 | 64 => {},
+            96 if 0 == prefix[8] => {},
+            _ => return Err(SiitError::InvalidPrefix(prefix, prefix_len))
+        }
+        let mut masked = [0;16];
+        let len = usize::from(prefix_len / 8);
+        masked[..len].copy_from_slice(&prefix[..len]);
+        Ok(Nat64Prefix {
+            prefix: masked,
+            prefix_len
+        })
+    }
+
+    ///Returns the prefix (bits past the prefix length are zero).
+    pub fn prefix(&self) -> [u8;16] {
+        self.prefix
+    }
+
+    ///Returns the length of the prefix in bits.
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    ///Embeds an ipv4 address in an ipv6 address.
+    pub fn embed(&self, address: [u8;4]) -> [u8;16] {
+        let mut result = self.prefix;
+        for (position, value) in self.positions().iter().zip(address.iter()) {
+            result[*position] = *value;
+        }
+        result
+    }
+
+    ///Extracts the ipv4 address embedded in an ipv6 address. Returns `None` if the address does not
+    ///start with the prefix or the "u" octet is not zero.
+    pub fn extract(&self, address: &[u8;16]) -> Option<[u8;4]> {
+        let len = usize::from(self.prefix_len / 8);
+        if address[..len] != self.prefix[..len] || 0 != address[8] && len <= 8 {
+            None
+        } else {
+            let mut result = [0;4];
+            for (value, position) in result.iter_mut().zip(self.positions().iter()) {
+                *value = address[*position];
+            }
+            Some(result)
+        }
+    }
+
+    ///Positions of the ipv4 address octets in the ipv6 address.
+    fn positions(&self) -> [usize;4] {
+        let mut result = [0;4];
+        let mut position = usize::from(self.prefix_len / 8);
+        for value in result.iter_mut() {
+            //skip the "u" octet
+            if 8 == position {
+                position += 1;
+            }
+            *value = position;
+            position += 1;
+        }
+        result
+    }
+}
+
+///Errors that can occur when translating a packet (according to rfc7915 the packet should be dropped).
+#[derive(Debug)]
+pub enum SiitError {
+    ///Error while decoding the packet (or the packet quoted in an ICMP error message).
+    Read(ReadError),
+    ///Error while encoding the translated headers (e.g. a payload that is too large for an ipv4 packet).
+    Write(WriteError),
+    ///Prefix with an unsupported length or a non zero "u" octet.
+    InvalidPrefix([u8;16], u8),
+    ///Ipv6 address that does not contain an ipv4 address embedded with the prefix of the translator.
+    AddressNotTranslatable([u8;16]),
+    ///ICMP or ICMPv6 message without a translation (e.g. ICMP timestamps or ICMPv6 neighbor discovery).
+    IcmpNotTranslatable{ icmp_type: u8, code: u8 },
+    ///ICMP message that is split into multiple fragments (the checksum can not be recalculated).
+    FragmentedIcmp,
+    ///Fragmented ipv4 udp packet without a checksum (ipv6 requires an udp checksum, which can not be
+    ///calculated for a fragment).
+    FragmentedUdpWithoutChecksum,
+}
+
+impl fmt::Display for SiitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use SiitError::*;
+        match self {
+            Read(err) => {
+                write!(f, "SiitError: Error while decoding the packet: {}", err)
+            },
+            Write(err) => {
+                write!(f, "SiitError: Error while encoding the translated packet: {}", err)
+            },
+            InvalidPrefix(prefix, prefix_len) => {
+                write!(f, "SiitError: The prefix {}/{} has an unsupported length or a non zero \"u\" octet.", std::net::Ipv6Addr::from(*prefix), prefix_len)
+            },
+            AddressNotTranslatable(address) => {
+                write!(f, "SiitError: The ipv6 address {} does not contain an ipv4 address embedded with the prefix of the translator.", std::net::Ipv6Addr::from(*address))
+            },
+            IcmpNotTranslatable{ icmp_type, code } => {
+                write!(f, "SiitError: The ICMP message with type {} & code {} can not be translated.", icmp_type, code)
+            },
+            FragmentedIcmp => {
+                write!(f, "SiitError: Fragmented ICMP messages can not be translated.")
+            },
+            FragmentedUdpWithoutChecksum => {
+                write!(f, "SiitError: Fragmented ipv4 udp packets without a checksum can not be translated.")
+            },
+        }
+    }
+}
+
+impl Error for SiitError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use SiitError::*;
+        match self {
+            Read(ref err) => Some(err),
+            Write(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl From<ReadError> for SiitError {
+    fn from(err: ReadError) -> SiitError {
+        SiitError::Read(err)
+    }
+}
+
+impl From<WriteError> for SiitError {
+    fn from(err: WriteError) -> SiitError {
+        SiitError::Write(err)
+    }
+}
+
+const IP_ICMP: u8 = IpTrafficClass::Icmp as u8;
+const IP_ICMPV6: u8 = IpTrafficClass::IPv6Icmp as u8;
+const IP_UDP: u8 = IpTrafficClass::Udp as u8;
+const IP_TCP: u8 = IpTrafficClass::Tcp as u8;
+const IP_HOP_BY_HOP: u8 = IpTrafficClass::IPv6HeaderHopByHop as u8;
+const IP_ROUTE: u8 = IpTrafficClass::IPv6RouteHeader as u8;
+const IP_DEST_OPTIONS: u8 = IpTrafficClass::IPv6DestinationOptions as u8;
+const IP_FRAG: u8 = IpTrafficClass::IPv6FragmentationHeader as u8;
+
+///Size of the ipv6 fragmentation header.
+const IPV6_FRAG_HEADER_LEN: usize = 8;
+
+///Ipv6 packets that are translated from ipv4 packets without the don't fragment flag & are larger
+///then this size get a fragmentation header (rfc7915 section 4.1).
+const IPV6_MIN_MTU: usize = 1280;
+
+///Ipv4 packets translated from ipv6 packets without a fragmentation header & larger then this size
+///get the don't fragment flag set (rfc7915 section 5.1).
+const IPV4_DF_THRESHOLD: usize = 1260;
+
+///Maximum size of an ipv4 packet containing an ICMP error (rfc1812 section 4.3.2.3).
+const IPV4_MAX_ICMP_ERROR_LEN: usize = 576;
+
+///Stateless ip/icmp translator between ipv4 & ipv6 packets (SIIT, rfc7915).
+///
+///The ipv4 addresses are mapped to ipv6 addresses by embedding them in an [`Nat64Prefix`](struct.Nat64Prefix.html)
+///(rfc6052) & ipv6 addresses are mapped back by extracting them. The headers are translated as follows:
+///
+///* The type of service is copied to the traffic class (& back), the flow label is set to zero.
+///* The time to live is copied to the hop limit (& back). It is not decremented.
+///* Ipv4 fragments & ipv4 packets without the don't fragment flag that are larger then 1280 bytes after
+///  the translation get an ipv6 fragmentation header with the identification, offset & more fragments flag
+///  of the ipv4 header. Ipv6 packets with a fragmentation header are translated to ipv4 packets with these
+///  values & without the don't fragment flag, otherwise the don't fragment flag is set if the translated
+///  packet is larger then 1260 bytes.
+///* Ipv4 options & ipv6 extension headers (hop by hop, routing & destination options) are removed.
+///* The tcp & udp checksums are updated for the changed pseudo header (udp packets without checksum get
+///  one when translated to ipv6).
+///* ICMP messages are translated to ICMPv6 messages & back (including the packets quoted in error messages).
+///  Messages without a translation are rejected with [`SiitError::IcmpNotTranslatable`](enum.SiitError.html#variant.IcmpNotTranslatable).
+///
+///Packets with other protocols (including non first fragments) are copied unchanged after the ip header.
+///
+/// # Example
+///
+/// ```
+/// # use etherparse::*;
+/// let translator = SiitTranslator::new(Nat64Prefix::WELL_KNOWN);
+///
+/// let mut packet = Vec::new();
+/// PacketBuilder::ipv4([192,0,2,1], [198,51,100,2], 20)
+///     .udp(1234, 53)
+///     .write(&mut packet, &[1,2,3,4])
+///     .unwrap();
+///
+/// let translated = translator.ipv4_to_ipv6(&packet).unwrap();
+/// let headers = PacketHeaders::from_ip_slice(&translated).unwrap();
+/// match &headers.ip {
+///     Some(IpHeader::Version6(ip)) => {
+///         assert_eq!(Nat64Prefix::WELL_KNOWN.embed([192,0,2,1]), ip.source);
+///         assert_eq!(20, ip.hop_limit);
+///     },
+///     _ => panic!("expected an ipv6 header")
+/// }
+/// assert!(!headers.verify_checksums().contains_invalid());
+///
+/// //and back
+/// let back = translator.ipv6_to_ipv4(&translated).unwrap();
+/// let headers = PacketHeaders::from_ip_slice(&back).unwrap();
+/// match headers.ip {
+///     Some(IpHeader::Version4(ip)) => assert_eq!([192,0,2,1], ip.source),
+///     _ => panic!("expected an ipv4 header")
+/// }
+/// assert_eq!(&[1,2,3,4], headers.payload);
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SiitTranslator {
+    ///Prefix used to map the ipv4 addresses to ipv6 addresses & back.
+    pub prefix: Nat64Prefix
+}
+
+impl SiitTranslator {
+    ///Creates a translator that maps the addresses using the given prefix.
+    pub fn new(prefix: Nat64Prefix) -> SiitTranslator {
+        SiitTranslator {
+            prefix
+        }
+    }
+
+    ///Translates an ipv4 or ipv6 packet (starting with the ip header) to the other ip version.
+    pub fn translate(&self, packet: &[u8]) -> Result<Vec<u8>, SiitError> {
+        match packet.first().map(|value| value >> 4) {
+            Some(6) => self.ipv6_to_ipv4(packet),
+            _ => self.ipv4_to_ipv6(packet)
+        }
+    }
+
+    ///Translates an ipv4 packet (starting with the ipv4 header) to an ipv6 packet.
+    ///
+    ///Data past the total length of the ipv4 header (e.g. ethernet padding) is ignored.
+    pub fn ipv4_to_ipv6(&self, packet: &[u8]) -> Result<Vec<u8>, SiitError> {
+        let (ip, rest) = Ipv4Header::read_from_slice(packet)?;
+        let payload_len = usize::from(ip.payload_len);
+        if rest.len() < payload_len {
+            return Err(ReadError::UnexpectedEndOfSlice(ip.header_len() + payload_len).into());
+        }
+        let mut result = Vec::with_capacity(Ipv6Header::SERIALIZED_SIZE + IPV6_FRAG_HEADER_LEN + payload_len + 20);
+        self.write_ipv4_as_ipv6(&ip, &rest[..payload_len], false, &mut result)?;
+        Ok(result)
+    }
+
+    ///Translates an ipv6 packet (starting with the ipv6 header) to an ipv4 packet.
+    ///
+    ///Data past the payload length of the ipv6 header is ignored.
+    pub fn ipv6_to_ipv4(&self, packet: &[u8]) -> Result<Vec<u8>, SiitError> {
+        let (ip, rest) = Ipv6Header::read_from_slice(packet)?;
+        let payload_len = usize::from(ip.payload_length);
+        if rest.len() < payload_len {
+            return Err(ReadError::UnexpectedEndOfSlice(Ipv6Header::SERIALIZED_SIZE + payload_len).into());
+        }
+        let mut result = Vec::with_capacity(Ipv4Header::SERIALIZED_SIZE + payload_len);
+        self.write_ipv6_as_ipv4(&ip, &rest[..payload_len], false, &mut result)?;
+        Ok(result)
+    }
+
+    ///Writes the ipv6 translation of an ipv4 packet. Quoted packets (contained in ICMP errors) can be
+    ///truncated and keep the lengths of the original header.
+    fn write_ipv4_as_ipv6(&self, ip: &Ipv4Header, payload: &[u8], quoted: bool, result: &mut Vec<u8>) -> Result<(), SiitError> {
+        let source = self.prefix.embed(ip.source);
+        let destination = self.prefix.embed(ip.destination);
+        let is_fragment = ip.more_fragments || 0 != ip.fragments_offset;
+        let next_header = if IP_ICMP == ip.protocol { IP_ICMPV6 } else { ip.protocol };
+
+        //translate the transport layer
+        let mut translated = payload.to_vec();
+        if 0 == ip.fragments_offset {
+            match ip.protocol {
+                IP_TCP | IP_UDP => {
+                    let old = Sum16BitWords::new().add_4bytes(ip.source).add_4bytes(ip.destination);
+                    let new = Sum16BitWords::new().add_16bytes(source).add_16bytes(destination);
+                    if IP_UDP == ip.protocol && translated.len() >= 8 && 0 == BigEndian::read_u16(&translated[6..8]) && !quoted {
+                        //ipv6 requires an udp checksum
+                        if is_fragment {
+                            return Err(SiitError::FragmentedUdpWithoutChecksum);
+                        }
+                        let checksum = checksum::ipv6_pseudo_header_sum(&source, &destination, IP_UDP, translated.len() as u32)
+                            .add_slice(&translated)
+                            .to_ones_complement_with_no_zero();
+                        BigEndian::write_u16(&mut translated[6..8], checksum);
+                    } else {
+                        update_transport_checksum(ip.protocol, &mut translated, old, new);
+                    }
+                },
+                IP_ICMP if quoted => translate_quoted_icmp_type(&mut translated, icmpv4_to_icmpv6_query_type),
+                IP_ICMP => {
+                    if is_fragment {
+                        return Err(SiitError::FragmentedIcmp);
+                    }
+                    translated = self.icmpv4_to_icmpv6(payload, &source, &destination)?;
+                },
+                _ => {}
+            }
+        }
+
+        let fragment = is_fragment || (!ip.dont_fragment && !quoted &&
+            Ipv6Header::SERIALIZED_SIZE + translated.len() > IPV6_MIN_MTU);
+        let payload_len = if quoted {
+            usize::from(ip.payload_len)
+        } else {
+            translated.len()
+        } + if fragment { IPV6_FRAG_HEADER_LEN } else { 0 };
+        if payload_len > usize::from(u16::MAX) {
+            return Err(SiitError::Write(WriteError::ValueError(ValueError::Ipv6PayloadLengthTooLarge(payload_len))));
+        }
+
+        Ipv6Header {
+            traffic_class: (ip.differentiated_services_code_point << 2) | ip.explicit_congestion_notification,
+            flow_label: 0,
+            payload_length: payload_len as u16,
+            next_header: if fragment { IP_FRAG } else { next_header },
+            hop_limit: ip.time_to_live,
+            source,
+            destination
+        }.write(result)?;
+
+        if fragment {
+            let mut header = [0;IPV6_FRAG_HEADER_LEN];
+            header[0] = next_header;
+            BigEndian::write_u16(&mut header[2..4], (ip.fragments_offset << 3) | if ip.more_fragments { 1 } else { 0 });
+            BigEndian::write_u32(&mut header[4..8], u32::from(ip.identification));
+            result.extend_from_slice(&header);
+        }
+        result.extend_from_slice(&translated);
+        Ok(())
+    }
+
+    ///Writes the ipv4 translation of an ipv6 packet. Quoted packets (contained in ICMPv6 errors) can be
+    ///truncated and keep the lengths of the original header.
+    fn write_ipv6_as_ipv4(&self, ip: &Ipv6Header, payload: &[u8], quoted: bool, result: &mut Vec<u8>) -> Result<(), SiitError> {
+        use crate::SiitError::*;
+        let source = self.prefix.extract(&ip.source).ok_or(AddressNotTranslatable(ip.source))?;
+        let destination = self.prefix.extract(&ip.destination).ok_or(AddressNotTranslatable(ip.destination))?;
+
+        //skip the extension headers (every header is at least 8 bytes long, so the loop terminates)
+        let mut next_header = ip.next_header;
+        let mut rest = payload;
+        //offset, more fragments & identification
+        let mut fragment: Option<(u16, bool, u32)> = None;
+        loop {
+            match next_header {
+                IP_HOP_BY_HOP | IP_ROUTE | IP_DEST_OPTIONS => {
+                    let (next, next_rest) = Ipv6Header::skip_header_extension_in_slice(rest, next_header)?;
+                    next_header = next;
+                    rest = next_rest;
+                },
+                IP_FRAG => {
+                    if rest.len() < IPV6_FRAG_HEADER_LEN {
+                        return Err(ReadError::UnexpectedEndOfSlice(IPV6_FRAG_HEADER_LEN).into());
+                    }
+                    fragment = Some((
+                        BigEndian::read_u16(&rest[2..4]) >> 3,
+                        0 != rest[3] & 1,
+                        BigEndian::read_u32(&rest[4..8])
+                    ));
+                    next_header = rest[0];
+                    rest = &rest[IPV6_FRAG_HEADER_LEN..];
+                },
+                _ => break
+            }
+        }
+        let extensions_len = payload.len() - rest.len();
+        let (fragments_offset, more_fragments, identification) = fragment.unwrap_or((0, false, 0));
+        let is_fragment = more_fragments || 0 != fragments_offset;
+        let protocol = if IP_ICMPV6 == next_header { IP_ICMP } else { next_header };
+
+        //translate the transport layer
+        let mut translated = rest.to_vec();
+        if 0 == fragments_offset {
+            match next_header {
+                IP_TCP | IP_UDP => {
+                    let old = Sum16BitWords::new().add_16bytes(ip.source).add_16bytes(ip.destination);
+                    let new = Sum16BitWords::new().add_4bytes(source).add_4bytes(destination);
+                    update_transport_checksum(next_header, &mut translated, old, new);
+                },
+                IP_ICMPV6 if quoted => translate_quoted_icmp_type(&mut translated, icmpv6_to_icmpv4_query_type),
+                IP_ICMPV6 => {
+                    if is_fragment {
+                        return Err(FragmentedIcmp);
+                    }
+                    translated = self.icmpv6_to_icmpv4(rest, &ip.source, &ip.destination)?;
+                },
+                _ => {}
+            }
+        }
+
+        let mut header = Ipv4Header::new(0, ip.hop_limit, IpTrafficClass::Icmp, source, destination);
+        header.protocol = protocol;
+        header.differentiated_services_code_point = ip.traffic_class >> 2;
+        header.explicit_congestion_notification = ip.traffic_class & 0x3;
+        header.identification = identification as u16;
+        header.more_fragments = more_fragments;
+        header.fragments_offset = fragments_offset;
+        if quoted {
+            header.payload_len = ip.payload_length.saturating_sub(extensions_len as u16);
+            header.dont_fragment = fragment.is_none();
+        } else {
+            header.set_payload_len(translated.len()).map_err(WriteError::from)?;
+            header.dont_fragment = fragment.is_none() &&
+                Ipv4Header::SERIALIZED_SIZE + translated.len() > IPV4_DF_THRESHOLD;
+        }
+        header.write(result)?;
+        result.extend_from_slice(&translated);
+        Ok(())
+    }
+
+    ///Translates an ICMP message to an ICMPv6 message (rfc7915 section 4.2).
+    fn icmpv4_to_icmpv6(&self, icmp: &[u8], source: &[u8;16], destination: &[u8;16]) -> Result<Vec<u8>, SiitError> {
+        use crate::SiitError::*;
+        if icmp.len() < 8 {
+            return Err(ReadError::UnexpectedEndOfSlice(8).into());
+        }
+        let (icmp_type, code) = (icmp[0], icmp[1]);
+        let not_translatable = IcmpNotTranslatable{ icmp_type, code };
+
+        //query messages (only the type changes)
+        if let Some(new_type) = icmpv4_to_icmpv6_query_type(icmp_type) {
+            let mut result = icmp.to_vec();
+            result[0] = new_type;
+            let pseudo_header = checksum::ipv6_pseudo_header_sum(source, destination, IP_ICMPV6, icmp.len() as u32);
+            let checksum = Sum16BitWords::new()
+                .add_u16(!BigEndian::read_u16(&icmp[2..4]))
+                .add_u16(!BigEndian::read_u16(&icmp[0..2]))
+                .add_2bytes([new_type, code])
+                .add_u16(pseudo_header.fold())
+                .ones_complement();
+            BigEndian::write_u16(&mut result[2..4], checksum);
+            return Ok(result);
+        }
+
+        //error messages
+        let (new_type, new_code, rest_of_header) = match (icmp_type, code) {
+            //destination unreachable
+            (3, 0) | (3, 1) | (3, 5) | (3, 6) | (3, 7) | (3, 8) | (3, 11) | (3, 12) => (1, 0, 0),
+            (3, 2) => (4, 1, 6), //protocol unreachable -> parameter problem pointing to the next header
+            (3, 3) => (1, 4, 0),
+            (3, 4) => (2, 0, u32::from(BigEndian::read_u16(&icmp[6..8])) + 20), //mtu
+            (3, 9) | (3, 10) | (3, 13) | (3, 15) => (1, 1, 0),
+            //time exceeded
+            (11, 0) | (11, 1) => (3, code, 0),
+            //parameter problem
+            (12, 0) | (12, 2) => (4, 0, match icmp[4] {
+                0 | 1 => u32::from(icmp[4]),
+                2 | 3 => 4,
+                8 => 7,
+                9 => 6,
+                12..=15 => 8,
+                16..=19 => 24,
+                _ => return Err(not_translatable)
+            }),
+            _ => return Err(not_translatable)
+        };
+
+        let (quoted_ip, quoted_rest) = Ipv4Header::read_from_slice(&icmp[8..])?;
+        let quoted_payload = &quoted_rest[..std::cmp::min(quoted_rest.len(), usize::from(quoted_ip.payload_len))];
+
+        let mut result = vec![new_type, new_code, 0, 0, 0, 0, 0, 0];
+        BigEndian::write_u32(&mut result[4..8], rest_of_header);
+        self.write_ipv4_as_ipv6(&quoted_ip, quoted_payload, true, &mut result)?;
+        result.truncate(IPV6_MIN_MTU - Ipv6Header::SERIALIZED_SIZE);
+
+        let checksum = checksum::ipv6_pseudo_header_sum(source, destination, IP_ICMPV6, result.len() as u32)
+            .add_slice(&result)
+            .ones_complement();
+        BigEndian::write_u16(&mut result[2..4], checksum);
+        Ok(result)
+    }
+
+    ///Translates an ICMPv6 message to an ICMP message (rfc7915 section 5.2).
+    fn icmpv6_to_icmpv4(&self, icmp: &[u8], source: &[u8;16], destination: &[u8;16]) -> Result<Vec<u8>, SiitError> {
+        use crate::SiitError::*;
+        if icmp.len() < 8 {
+            return Err(ReadError::UnexpectedEndOfSlice(8).into());
+        }
+        let (icmp_type, code) = (icmp[0], icmp[1]);
+        let not_translatable = IcmpNotTranslatable{ icmp_type, code };
+
+        //query messages (only the type changes)
+        if let Some(new_type) = icmpv6_to_icmpv4_query_type(icmp_type) {
+            let mut result = icmp.to_vec();
+            result[0] = new_type;
+            let pseudo_header = checksum::ipv6_pseudo_header_sum(source, destination, IP_ICMPV6, icmp.len() as u32);
+            let checksum = Sum16BitWords::new()
+                .add_u16(!BigEndian::read_u16(&icmp[2..4]))
+                .add_u16(!BigEndian::read_u16(&icmp[0..2]))
+                .add_2bytes([new_type, code])
+                .add_u16(!pseudo_header.fold())
+                .ones_complement();
+            BigEndian::write_u16(&mut result[2..4], checksum);
+            return Ok(result);
+        }
+
+        //error messages
+        let (new_type, new_code, rest_of_header) = match (icmp_type, code) {
+            //destination unreachable
+            (1, 0) | (1, 2) | (1, 3) => (3, 1, [0;4]),
+            (1, 1) => (3, 10, [0;4]),
+            (1, 4) => (3, 3, [0;4]),
+            //packet too big
+            (2, _) => {
+                let mtu = BigEndian::read_u32(&icmp[4..8]).saturating_sub(20);
+                let mut value = [0;4];
+                BigEndian::write_u16(&mut value[2..4], std::cmp::min(mtu, u32::from(u16::MAX)) as u16);
+                (3, 4, value)
+            },
+            //time exceeded
+            (3, _) => (11, code, [0;4]),
+            //parameter problem
+            (4, 0) => (12, 0, [match BigEndian::read_u32(&icmp[4..8]) {
+                0 => 0,
+                1 => 1,
+                4 | 5 => 2,
+                6 => 9,
+                7 => 8,
+                8..=23 => 12,
+                24..=39 => 16,
+                _ => return Err(not_translatable)
+            }, 0, 0, 0]),
+            (4, 1) => (3, 2, [0;4]),
+            _ => return Err(not_translatable)
+        };
+
+        let (quoted_ip, quoted_rest) = Ipv6Header::read_from_slice(&icmp[8..])?;
+        let quoted_payload = &quoted_rest[..std::cmp::min(quoted_rest.len(), usize::from(quoted_ip.payload_length))];
+
+        let mut result = vec![new_type, new_code, 0, 0];
+        result.extend_from_slice(&rest_of_header);
+        self.write_ipv6_as_ipv4(&quoted_ip, quoted_payload, true, &mut result)?;
+        result.truncate(IPV4_MAX_ICMP_ERROR_LEN - Ipv4Header::SERIALIZED_SIZE);
+
+        let checksum = Sum16BitWords::new().add_slice(&result).ones_complement();
+        BigEndian::write_u16(&mut result[2..4], checksum);
+        Ok(result)
+    }
+}
+
+///Returns the ICMPv6 type of an ICMP echo request or reply.
+fn icmpv4_to_icmpv6_query_type(icmp_type: u8) -> Option<u8> {
+    match icmp_type {
+        8 => Some(128),
+        0 => Some(129),
+        _ => None
+    }
+}
+
+///Returns the ICMP type of an ICMPv6 echo request or reply.
+fn icmpv6_to_icmpv4_query_type(icmp_type: u8) -> Option<u8> {
+    match icmp_type {
+        128 => Some(8),
+        129 => Some(0),
+        _ => None
+    }
+}
+
+///Translates the type of a (possibly truncated) ICMP echo message quoted in an error message. The checksum
+///of the quoted message is left unchanged.
+fn translate_quoted_icmp_type(icmp: &mut [u8], translate: fn(u8) -> Option<u8>) {
+    if let Some(value) = icmp.first_mut() {
+        if let Some(new_type) = translate(*value) {
+            *value = new_type;
+        }
+    }
+}
+
+///Updates the checksum of a (possibly truncated) tcp or udp header after the pseudo header addresses changed
+///from the `old` to the `new` sum. Udp checksums of zero are left unchanged.
+fn update_transport_checksum(protocol: u8, transport: &mut [u8], old: Sum16BitWords, new: Sum16BitWords) {
+    let offset = if IP_UDP == protocol { 6 } else { 16 };
+    if transport.len() < offset + 2 {
+        return;
+    }
+    let checksum = BigEndian::read_u16(&transport[offset..offset + 2]);
+    if IP_UDP == protocol && 0 == checksum {
+        return;
+    }
+    let updated = checksum::update_u16(checksum, old.fold(), new.fold());
+    BigEndian::write_u16(
+        &mut transport[offset..offset + 2],
+        if IP_UDP == protocol && 0 == updated { 0xffff } else { updated }
+    );
+}
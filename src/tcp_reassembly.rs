@@ -0,0 +1,528 @@
+//! Reassembly of the byte streams of tcp connections.
+//!
+//! The [`TcpReassembler`](struct.TcpReassembler.html) is fed with sliced packets & delivers the in-order data of
+//! both directions of every connection to a callback as [`StreamEvent`](enum.StreamEvent.html)s.
+//!
+//! # Example
+//!
+//! ```
+//! use etherparse::*;
+//! use etherparse::tcp_reassembly::*;
+//!
+//! //SYN followed by two segments in the wrong order
+//! let mut packets = Vec::new();
+//! for (sequence_number, syn, payload) in &[(999, true, &b""[..]), (1006, false, &b"world"[..]), (1000, false, &b"hello "[..])] {
+//!     let builder = PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+//!         .tcp(1234, 80, *sequence_number, 1024);
+//!     let builder = if *syn { builder.syn() } else { builder };
+//!     let mut packet = Vec::new();
+//!     builder.write(&mut packet, payload).unwrap();
+//!     packets.push(packet);
+//! }
+//!
+//! let mut reassembler = TcpReassembler::new(ReassemblyConfig::default());
+//! let mut stream = Vec::new();
+//! for packet in &packets {
+//!     reassembler.push(&SlicedPacket::from_ip(packet).unwrap(), |event| {
+//!         if let StreamEvent::Data{ data, .. } = event {
+//!             stream.extend_from_slice(data);
+//!         }
+//!     }).unwrap();
+//! }
+//! //the out of order segment is delivered after the missing data was received
+//! assert_eq!(&b"hello world"[..], &stream[..]);
+//! ```
+use super::*;
+
+use std::collections::{BTreeMap, HashMap};
+use std::net::IpAddr;
+
+///Errors that can occur when passing a packet to a [`TcpReassembler`](struct.TcpReassembler.html).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ReassemblyError {
+    ///The packet contains no ip header or no tcp header.
+    MissingTcpHeader,
+    ///The packet is an ip fragment (fragments have to be reassembled before the tcp reassembly).
+    IpFragment,
+}
+
+impl fmt::Display for ReassemblyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use ReassemblyError::*;
+        match self {
+            MissingTcpHeader => {
+                write!(f, "ReassemblyError: The packet contains no ip header or no tcp header.")
+            },
+            IpFragment => {
+                write!(f, "ReassemblyError: The packet is an ip fragment (fragments have to be reassembled before the tcp reassembly).")
+            },
+        }
+    }
+}
+
+impl Error for ReassemblyError {
+
+}
+
+///Addresses & ports of a tcp connection.
+///
+///The keys passed in the events of the reassembler are oriented from the client (the sender of the initial SYN or
+///of the first seen segment if the SYN was not captured) to the server.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct ConnectionKey {
+    ///Ip address of the sender (the client in the keys passed in the events).
+    pub source: IpAddr,
+    ///Tcp port of the sender.
+    pub source_port: u16,
+    ///Ip address of the receiver (the server in the keys passed in the events).
+    pub destination: IpAddr,
+    ///Tcp port of the receiver.
+    pub destination_port: u16,
+}
+
+impl ConnectionKey {
+    ///Extracts the key from a packet containing an ip & a tcp header (oriented from the sender to the receiver).
+    pub fn from_sliced(packet: &SlicedPacket) -> Option<ConnectionKey> {
+        use crate::InternetSlice::*;
+
+        let (source, destination) = match &packet.ip {
+            Some(Ipv4(header)) => (IpAddr::from(header.source_addr()), IpAddr::from(header.destination_addr())),
+            Some(Ipv6(header, _)) => (IpAddr::from(header.source_addr()), IpAddr::from(header.destination_addr())),
+            None => return None
+        };
+        match &packet.transport {
+            Some(TransportSlice::Tcp(tcp)) => Some(ConnectionKey {
+                source,
+                source_port: tcp.source_port(),
+                destination,
+                destination_port: tcp.destination_port(),
+            }),
+            _ => None
+        }
+    }
+
+    ///Returns the key with the source & destination swapped.
+    pub fn reversed(&self) -> ConnectionKey {
+        ConnectionKey {
+            source: self.destination,
+            source_port: self.destination_port,
+            destination: self.source,
+            destination_port: self.source_port,
+        }
+    }
+
+    ///Returns the key in an orientation independent form (the smaller endpoint as source), so that both
+    ///directions of a connection result in the same key.
+    pub fn canonical(&self) -> ConnectionKey {
+        if (self.source, self.source_port) <= (self.destination, self.destination_port) {
+            *self
+        } else {
+            self.reversed()
+        }
+    }
+}
+
+///Direction of the data in a tcp connection.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Direction {
+    ///Data sent by the client (the source of the connection key).
+    ClientToServer,
+    ///Data sent by the server (the destination of the connection key).
+    ServerToClient,
+}
+
+///Decides which data is kept if segments overlap with data that was received before but not yet delivered.
+///
+///Data that was already delivered is never changed, retransmitted data for it is ignored.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OverlapPolicy {
+    ///Keep the data that was received first (e.g. Windows & BSD).
+    First,
+    ///Replace the buffered data with the data received last (e.g. Solaris).
+    Last,
+}
+
+///Reason why a connection was closed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CloseReason {
+    ///Both directions were finished with a FIN & all data up to the FINs was delivered.
+    Fin,
+    ///A RST was received.
+    Reset,
+    ///The connection was removed as the maximum number of connections was reached.
+    Evicted,
+    ///The connection was closed by [`TcpReassembler::flush`](struct.TcpReassembler.html#method.flush).
+    Flushed,
+}
+
+///Events delivered by the reassembler.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum StreamEvent<'a> {
+    ///A new connection was detected (by a SYN or by the first segment containing data).
+    Opened(ConnectionKey),
+    ///In-order data of one direction of a connection.
+    Data {
+        key: ConnectionKey,
+        direction: Direction,
+        ///Position of the data in the stream (number of bytes delivered & skipped before).
+        offset: u64,
+        data: &'a [u8],
+    },
+    ///Data was skipped as it was never received (because the buffer limit was reached or the connection was
+    ///closed while data was missing).
+    Gap {
+        key: ConnectionKey,
+        direction: Direction,
+        offset: u64,
+        len: u64,
+    },
+    ///All data up to the FIN of the direction was delivered.
+    Fin {
+        key: ConnectionKey,
+        direction: Direction,
+    },
+    ///The connection was closed & removed from the reassembler (after all buffered data was delivered).
+    Closed {
+        key: ConnectionKey,
+        reason: CloseReason,
+    },
+}
+
+///Configuration of a [`TcpReassembler`](struct.TcpReassembler.html).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReassemblyConfig {
+    ///Policy for overlapping segments.
+    pub overlap_policy: OverlapPolicy,
+    ///Maximum number of out of order bytes buffered per direction. If more data is buffered, the missing data is
+    ///skipped (signaled with a `Gap` event) till the buffer is below the limit.
+    pub max_buffered_len: usize,
+    ///Maximum number of tracked connections. If the limit is reached, the least recently active connection is
+    ///evicted.
+    pub max_connections: usize,
+}
+
+impl Default for ReassemblyConfig {
+    fn default() -> ReassemblyConfig {
+        ReassemblyConfig {
+            overlap_policy: OverlapPolicy::First,
+            max_buffered_len: 1024*1024,
+            max_connections: 64*1024,
+        }
+    }
+}
+
+///Reassembles the data streams of tcp connections.
+///
+///Connections are created by a SYN or by the first segment with data (allowing to pick up connections that
+///were already established when the capture started). Out of order segments are buffered till the missing data
+///arrives, overlapping data is resolved based on the configured [`OverlapPolicy`](enum.OverlapPolicy.html) &
+///sequence numbers are allowed to wrap around.
+///
+///The events of a packet are passed to the callback given to [`push`](struct.TcpReassembler.html#method.push).
+#[derive(Clone, Debug)]
+pub struct TcpReassembler {
+    config: ReassemblyConfig,
+    ///Connections stored with the canonical key.
+    connections: HashMap<ConnectionKey, Connection>,
+    ///Counter used to determine the least recently active connection.
+    time: u64,
+}
+
+impl TcpReassembler {
+    ///Creates a reassembler without any tracked connections.
+    pub fn new(config: ReassemblyConfig) -> TcpReassembler {
+        TcpReassembler {
+            config,
+            connections: HashMap::new(),
+            time: 0,
+        }
+    }
+
+    ///Configuration the reassembler was created with.
+    pub fn config(&self) -> &ReassemblyConfig {
+        &self.config
+    }
+
+    ///Number of currently tracked connections.
+    pub fn connection_count(&self) -> usize {
+        self.connections.len()
+    }
+
+    ///Processes a packet & passes the resulting events to the callback.
+    ///
+    ///Returns an error if the packet does not contain a tcp header or is an ip fragment.
+    pub fn push<F: FnMut(StreamEvent)>(&mut self, packet: &SlicedPacket, mut callback: F) -> Result<(), ReassemblyError> {
+        let (key, tcp, payload) = decode(packet)?;
+        let canonical = key.canonical();
+        self.time += 1;
+
+        //the connection is taken out of the map while the segment is processed (& reinserted if it stays open)
+        let mut connection = match self.connections.remove(&canonical) {
+            Some(connection) => connection,
+            None => {
+                //only create connections for segments that start a connection or contain data
+                let starts_connection = tcp.syn() || !payload.is_empty();
+                if !starts_connection || tcp.rst() {
+                    return Ok(());
+                }
+                if self.connections.len() >= self.config.max_connections {
+                    self.evict(&mut callback);
+                }
+                //the receiver of a SYN ACK is the client
+                let client_key = if tcp.syn() && tcp.ack() {
+                    key.reversed()
+                } else {
+                    key
+                };
+                callback(StreamEvent::Opened(client_key));
+                Connection::new(client_key)
+            }
+        };
+        connection.last_active = self.time;
+        let direction = if key == connection.key {
+            Direction::ClientToServer
+        } else {
+            Direction::ServerToClient
+        };
+
+        if tcp.rst() {
+            connection.close(CloseReason::Reset, &mut callback);
+        } else if connection.push(direction, &tcp, payload, &self.config, &mut callback) {
+            connection.close(CloseReason::Fin, &mut callback);
+        } else {
+            self.connections.insert(canonical, connection);
+        }
+        Ok(())
+    }
+
+    ///Delivers the buffered data of all connections (with gaps for the missing data) & closes them.
+    pub fn flush<F: FnMut(StreamEvent)>(&mut self, mut callback: F) {
+        let mut connections: Vec<Connection> = self.connections.drain().map(|(_, value)| value).collect();
+        connections.sort_by_key(|connection| connection.last_active);
+        for connection in connections {
+            connection.close(CloseReason::Flushed, &mut callback);
+        }
+    }
+
+    ///Closes the least recently active connection.
+    fn evict<F: FnMut(StreamEvent)>(&mut self, callback: &mut F) {
+        let oldest = self.connections
+                         .iter()
+                         .min_by_key(|(_, connection)| connection.last_active)
+                         .map(|(key, _)| *key);
+        if let Some(connection) = oldest.and_then(|key| self.connections.remove(&key)) {
+            connection.close(CloseReason::Evicted, callback);
+        }
+    }
+}
+
+///Returns the key, tcp header & tcp payload (without data past the ip payload, e.g. ethernet padding).
+pub(crate) fn decode<'a>(packet: &SlicedPacket<'a>) -> Result<(ConnectionKey, TcpHeaderSlice<'a>, &'a [u8]), ReassemblyError> {
+    use self::ReassemblyError::*;
+    const IP_FRAG: u8 = IpTrafficClass::IPv6FragmentationHeader as u8;
+
+    let tcp = match &packet.transport {
+        Some(TransportSlice::Tcp(tcp)) => tcp.clone(),
+        _ => return Err(MissingTcpHeader)
+    };
+    let tcp_len = tcp.slice().len();
+    let payload_len = match &packet.ip {
+        Some(InternetSlice::Ipv4(header)) => {
+            if header.more_fragments() || 0 != header.fragments_offset() {
+                return Err(IpFragment);
+            }
+            let auth_len = packet.authentication_header.as_ref().map(|value| value.slice().len()).unwrap_or(0);
+            usize::from(header.payload_len()).saturating_sub(auth_len + tcp_len)
+        },
+        Some(InternetSlice::Ipv6(header, extensions)) => {
+            let mut extensions_len = 0;
+            for (id, extension) in extensions.iter().flatten() {
+                if IP_FRAG == *id {
+                    return Err(IpFragment);
+                }
+                extensions_len += extension.slice().len();
+            }
+            usize::from(header.payload_length()).saturating_sub(extensions_len + tcp_len)
+        },
+        None => return Err(MissingTcpHeader)
+    };
+    let key = ConnectionKey::from_sliced(packet).ok_or(MissingTcpHeader)?;
+    let payload = &packet.payload[..std::cmp::min(payload_len, packet.payload.len())];
+    Ok((key, tcp, payload))
+}
+
+///State of a tracked connection.
+#[derive(Clone, Debug)]
+struct Connection {
+    ///Key oriented from the client to the server.
+    key: ConnectionKey,
+    client: HalfStream,
+    server: HalfStream,
+    last_active: u64,
+}
+
+impl Connection {
+    fn new(key: ConnectionKey) -> Connection {
+        Connection {
+            key,
+            client: HalfStream::default(),
+            server: HalfStream::default(),
+            last_active: 0,
+        }
+    }
+
+    ///Processes a segment & returns true if both directions are finished.
+    fn push<F: FnMut(StreamEvent)>(&mut self, direction: Direction, tcp: &TcpHeaderSlice, payload: &[u8], config: &ReassemblyConfig, callback: &mut F) -> bool {
+        let key = self.key;
+        let stream = match direction {
+            Direction::ClientToServer => &mut self.client,
+            Direction::ServerToClient => &mut self.server,
+        };
+        stream.push(tcp, payload, config);
+        stream.deliver(key, direction, config.max_buffered_len, callback);
+        if stream.check_finished() {
+            callback(StreamEvent::Fin{ key, direction });
+        }
+        self.client.finished && self.server.finished
+    }
+
+    ///Delivers all buffered data (with gaps) & signals the end of the connection.
+    fn close<F: FnMut(StreamEvent)>(mut self, reason: CloseReason, callback: &mut F) {
+        let key = self.key;
+        self.client.deliver(key, Direction::ClientToServer, 0, callback);
+        self.server.deliver(key, Direction::ServerToClient, 0, callback);
+        callback(StreamEvent::Closed{ key, reason });
+    }
+}
+
+///State of one direction of a connection.
+#[derive(Clone, Debug, Default)]
+struct HalfStream {
+    ///Sequence number of the next expected byte (`None` till the first segment of the direction was seen).
+    next_sequence_number: Option<u32>,
+    ///Stream offset of the next expected byte.
+    next_offset: u64,
+    ///Buffered out of order data (non overlapping, indexed by the stream offset).
+    segments: BTreeMap<u64, Vec<u8>>,
+    ///Number of buffered bytes.
+    buffered_len: usize,
+    ///Stream offset of the FIN.
+    fin_offset: Option<u64>,
+    ///True if all data up to the FIN was delivered.
+    finished: bool,
+}
+
+impl HalfStream {
+    ///Adds the data & the FIN of a segment to the buffer.
+    fn push(&mut self, tcp: &TcpHeaderSlice, payload: &[u8], config: &ReassemblyConfig) {
+        //the data starts after the SYN
+        let sequence_number = if tcp.syn() {
+            tcp.sequence_number().wrapping_add(1)
+        } else {
+            tcp.sequence_number()
+        };
+        let next_sequence_number = *self.next_sequence_number.get_or_insert(sequence_number);
+
+        //determine the stream offset based on the (wrapping) distance to the next expected sequence number
+        let start = self.next_offset as i64 + i64::from(sequence_number.wrapping_sub(next_sequence_number) as i32);
+        let end = start + payload.len() as i64;
+
+        if tcp.fin() && end >= self.next_offset as i64 && self.fin_offset.is_none() {
+            self.fin_offset = Some(end as u64);
+        }
+
+        //skip the already delivered data
+        if end <= self.next_offset as i64 {
+            return;
+        }
+        let skip = (self.next_offset as i64 - start).max(0) as usize;
+        self.insert((start + skip as i64) as u64, &payload[skip..], config.overlap_policy);
+    }
+
+    ///Inserts data into the buffer resolving overlaps with the given policy.
+    fn insert(&mut self, start: u64, data: &[u8], policy: OverlapPolicy) {
+        let end = start + data.len() as u64;
+
+        //buffered segments overlapping the new data
+        let overlapping: Vec<(u64, u64)> = {
+            let previous = self.segments.range(..start).next_back().map(|(key, value)| (*key, *key + value.len() as u64));
+            previous.into_iter()
+                    .chain(self.segments.range(start..end).map(|(key, value)| (*key, *key + value.len() as u64)))
+                    .filter(|(segment_start, segment_end)| *segment_start < end && start < *segment_end)
+                    .collect()
+        };
+
+        match policy {
+            OverlapPolicy::First => {
+                //only fill the holes between the buffered segments
+                let mut position = start;
+                for (segment_start, segment_end) in overlapping.iter().chain(std::iter::once(&(end, end))) {
+                    if position < *segment_start {
+                        let piece = &data[(position - start) as usize..(*segment_start - start) as usize];
+                        self.buffered_len += piece.len();
+                        self.segments.insert(position, piece.to_vec());
+                    }
+                    position = std::cmp::max(position, *segment_end);
+                }
+            },
+            OverlapPolicy::Last => {
+                //cut the overlapping parts out of the buffered segments
+                for (segment_start, segment_end) in overlapping {
+                    let segment = match self.segments.remove(&segment_start) {
+                        Some(value) => value,
+                        None => continue
+                    };
+                    self.buffered_len -= segment.len();
+                    if segment_start < start {
+                        let piece = segment[..(start - segment_start) as usize].to_vec();
+                        self.buffered_len += piece.len();
+                        self.segments.insert(segment_start, piece);
+                    }
+                    if end < segment_end {
+                        let piece = segment[(end - segment_start) as usize..].to_vec();
+                        self.buffered_len += piece.len();
+                        self.segments.insert(end, piece);
+                    }
+                }
+                self.buffered_len += data.len();
+                self.segments.insert(start, data.to_vec());
+            }
+        }
+    }
+
+    ///Delivers the in-order data & skips missing data till at most `max_buffered_len` bytes are buffered.
+    fn deliver<F: FnMut(StreamEvent)>(&mut self, key: ConnectionKey, direction: Direction, max_buffered_len: usize, callback: &mut F) {
+        while let Some(first) = self.segments.keys().next().copied() {
+            if first > self.next_offset {
+                if self.buffered_len <= max_buffered_len {
+                    return;
+                }
+                let len = first - self.next_offset;
+                callback(StreamEvent::Gap{ key, direction, offset: self.next_offset, len });
+                self.advance(len);
+            }
+            let data = match self.segments.remove(&first) {
+                Some(value) => value,
+                None => return
+            };
+            self.buffered_len -= data.len();
+            callback(StreamEvent::Data{ key, direction, offset: self.next_offset, data: &data });
+            self.advance(data.len() as u64);
+        }
+    }
+
+    fn advance(&mut self, len: u64) {
+        self.next_offset += len;
+        self.next_sequence_number = self.next_sequence_number.map(|value| value.wrapping_add(len as u32));
+    }
+
+    ///Returns true if the direction just became finished.
+    fn check_finished(&mut self) -> bool {
+        if !self.finished && Some(self.next_offset) == self.fin_offset {
+            self.finished = true;
+            true
+        } else {
+            false
+        }
+    }
+}
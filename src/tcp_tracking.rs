@@ -0,0 +1,544 @@
+//! Stateful tracking of tcp connections (similar to the connection tracking of stateful firewalls).
+//!
+//! The [`TcpTracker`](struct.TcpTracker.html) follows the RFC 793 states of both endpoints of every connection &
+//! checks that the sequence & acknowledgment numbers of every segment lie within the windows of the connection
+//! (based on "Real Stateful TCP Packet Filtering in IP Filter" by Guido van Rooij). Every packet is classified as
+//! belonging to a new connection, an established connection, being related to a connection (ICMP errors) or
+//! being invalid.
+//!
+//! # Example
+//!
+//! ```
+//! use etherparse::*;
+//! use etherparse::tcp_tracking::*;
+//!
+//! let packet = |from_client: bool, sequence_number: u32, acknowledgment_number: Option<u32>, syn: bool| {
+//!     let (source, destination) = if from_client {
+//!         (([192,168,1,1], 1234), ([192,168,1,2], 80))
+//!     } else {
+//!         (([192,168,1,2], 80), ([192,168,1,1], 1234))
+//!     };
+//!     let builder = PacketBuilder::ipv4(source.0, destination.0, 20)
+//!         .tcp(source.1, destination.1, sequence_number, 1024);
+//!     let builder = if syn { builder.syn() } else { builder };
+//!     let builder = match acknowledgment_number {
+//!         Some(value) => builder.ack(value),
+//!         None => builder
+//!     };
+//!     let mut result = Vec::new();
+//!     builder.write(&mut result, &[]).unwrap();
+//!     result
+//! };
+//!
+//! let mut tracker = TcpTracker::new(TrackerConfig::default());
+//! let mut track = |packet: Vec<u8>| tracker.track(&SlicedPacket::from_ip(&packet).unwrap()).unwrap();
+//!
+//! //three way handshake
+//! assert_eq!(PacketClassification::New, track(packet(true, 100, None, true)));
+//! assert_eq!(PacketClassification::Established, track(packet(false, 500, Some(101), true)));
+//! assert_eq!(PacketClassification::Established, track(packet(true, 101, Some(501), false)));
+//!
+//! //acknowledgment of data that was never sent
+//! assert_eq!(PacketClassification::Invalid, track(packet(true, 101, Some(10_000), false)));
+//! ```
+use super::*;
+use crate::tcp_reassembly::{decode, ConnectionKey, ReassemblyError};
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+///Errors that can occur when passing a packet to a [`TcpTracker`](struct.TcpTracker.html).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TrackingError {
+    ///The packet contains no ip header or no tcp header (& is no ICMP error message).
+    MissingTcpHeader,
+    ///The packet is an ip fragment (fragments have to be reassembled before the tracking).
+    IpFragment,
+}
+
+impl fmt::Display for TrackingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use TrackingError::*;
+        match self {
+            MissingTcpHeader => {
+                write!(f, "TrackingError: The packet contains no ip header or no tcp header (& is no ICMP error message).")
+            },
+            IpFragment => {
+                write!(f, "TrackingError: The packet is an ip fragment (fragments have to be reassembled before the tracking).")
+            },
+        }
+    }
+}
+
+impl Error for TrackingError {
+
+}
+
+///Classification of a packet by the tracker.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PacketClassification {
+    ///The packet starts a new connection (or belongs to a connection for which no packet of the server was seen yet).
+    New,
+    ///The packet belongs to a connection for which packets in both directions were seen.
+    Established,
+    ///ICMP error message caused by a packet of a tracked connection.
+    Related,
+    ///The packet does not match the state or the windows of the connection.
+    Invalid,
+}
+
+///States of a tcp endpoint (RFC 793).
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum TcpState {
+    ///Waiting for a connection request.
+    Listen,
+    ///Sent a connection request & waiting for the matching SYN-ACK.
+    SynSent,
+    ///Received a connection request, sent a SYN-ACK & waiting for the acknowledgment.
+    SynReceived,
+    ///Handshake completed, data can be transferred in both directions.
+    Established,
+    ///Sent a FIN & waiting for its acknowledgment or the FIN of the peer.
+    FinWait1,
+    ///The sent FIN was acknowledged & waiting for the FIN of the peer.
+    FinWait2,
+    ///Received the FIN of the peer & waiting for the local FIN.
+    CloseWait,
+    ///Sent & received a FIN, waiting for the acknowledgment of the sent FIN.
+    Closing,
+    ///Received the FIN of the peer, sent a FIN & waiting for its acknowledgment.
+    LastAck,
+    ///Both FINs were acknowledged, waiting for delayed segments to expire.
+    TimeWait,
+    ///No connection (e.g. after a RST).
+    Closed,
+}
+
+///Configuration of a [`TcpTracker`](struct.TcpTracker.html).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TrackerConfig {
+    ///If true, connections for which the handshake was not seen are picked up by the first segment with an
+    ///ACK flag. As the window scaling of such connections is unknown, no window checks are done for them.
+    pub mid_stream_pickup: bool,
+}
+
+impl Default for TrackerConfig {
+    fn default() -> TrackerConfig {
+        TrackerConfig {
+            mid_stream_pickup: true,
+        }
+    }
+}
+
+///Minimum window used for the acknowledgment number check (same as in the Linux connection tracking).
+const MIN_MAX_ACK_WINDOW: u32 = 66000;
+
+///Maximum window scale (RFC 7323).
+const MAX_WINDOW_SCALE: u8 = 14;
+
+///Tracking data of one endpoint of a connection.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Endpoint {
+    state: TcpState,
+    ///Highest sequence number sent by the endpoint plus one.
+    end: u32,
+    ///Highest sequence number the endpoint is allowed to send based on the windows advertised by the peer.
+    max_end: u32,
+    ///Largest (scaled) window advertised by the endpoint (0 if no packet of the endpoint was seen).
+    max_window: u32,
+    ///Window scale used for the windows of the endpoint.
+    window_scale: u8,
+    ///Window scale option of the SYN of the endpoint.
+    syn_window_scale: Option<u8>,
+    ///Sequence number following the FIN of the endpoint.
+    fin_end: Option<u32>,
+}
+
+impl Endpoint {
+    fn new(state: TcpState) -> Endpoint {
+        Endpoint {
+            state,
+            end: 0,
+            max_end: 0,
+            max_window: 0,
+            window_scale: 0,
+            syn_window_scale: None,
+            fin_end: None,
+        }
+    }
+
+    ///Initializes the endpoint with the first seen segment.
+    fn init(&mut self, tcp: &TcpHeaderSlice, payload_len: usize) {
+        let end = segment_end(tcp, payload_len);
+        let window = std::cmp::max(1, u32::from(tcp.window_size()));
+        self.end = end;
+        self.max_window = window;
+        if tcp.syn() {
+            self.max_end = end;
+            self.syn_window_scale = tcp.options_iterator().find_map(|option| match option {
+                Ok(TcpOptionElement::WindowScale(value)) => Some(std::cmp::min(value, MAX_WINDOW_SCALE)),
+                _ => None
+            });
+        } else {
+            self.max_end = end.wrapping_add(window);
+        }
+    }
+}
+
+///Tracked connection.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TrackedConnection {
+    ///Key oriented from the client to the server.
+    key: ConnectionKey,
+    client: Endpoint,
+    server: Endpoint,
+    ///True if a packet of the server was seen.
+    reply_seen: bool,
+    ///True if the connection was picked up mid stream (no window checks).
+    liberal: bool,
+}
+
+impl TrackedConnection {
+    ///Key of the connection oriented from the client to the server.
+    pub fn key(&self) -> ConnectionKey {
+        self.key
+    }
+
+    ///State of the client (the sender of the SYN).
+    pub fn client_state(&self) -> TcpState {
+        self.client.state
+    }
+
+    ///State of the server.
+    pub fn server_state(&self) -> TcpState {
+        self.server.state
+    }
+
+    ///True if the connection was picked up without the handshake (no sequence number checks are done).
+    pub fn is_picked_up(&self) -> bool {
+        self.liberal
+    }
+
+    fn endpoints(&mut self, from_client: bool) -> (&mut Endpoint, &mut Endpoint) {
+        if from_client {
+            (&mut self.client, &mut self.server)
+        } else {
+            (&mut self.server, &mut self.client)
+        }
+    }
+
+    fn classification(&self) -> PacketClassification {
+        if self.reply_seen {
+            PacketClassification::Established
+        } else {
+            PacketClassification::New
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        let closed = |state| TcpState::Closed == state || TcpState::TimeWait == state;
+        closed(self.client.state) || closed(self.server.state)
+    }
+}
+
+///Tracks the states of tcp connections & classifies packets based on them.
+#[derive(Clone, Debug)]
+pub struct TcpTracker {
+    config: TrackerConfig,
+    ///Connections stored with the canonical key.
+    connections: HashMap<ConnectionKey, TrackedConnection>,
+}
+
+impl TcpTracker {
+    ///Creates a tracker without any tracked connections.
+    pub fn new(config: TrackerConfig) -> TcpTracker {
+        TcpTracker {
+            config,
+            connections: HashMap::new(),
+        }
+    }
+
+    ///Returns the configuration of the tracker.
+    pub fn config(&self) -> &TrackerConfig {
+        &self.config
+    }
+
+    ///Number of tracked connections (including closed connections that were not removed yet).
+    pub fn connection_count(&self) -> usize {
+        self.connections.len()
+    }
+
+    ///Returns the tracked connection with the given key (in any orientation).
+    pub fn connection(&self, key: &ConnectionKey) -> Option<&TrackedConnection> {
+        self.connections.get(&key.canonical())
+    }
+
+    ///Removes a connection & returns it.
+    pub fn remove(&mut self, key: &ConnectionKey) -> Option<TrackedConnection> {
+        self.connections.remove(&key.canonical())
+    }
+
+    ///Removes all connections in which one of the endpoints reached the `Closed` or `TimeWait` state.
+    pub fn remove_closed(&mut self) {
+        self.connections.retain(|_, connection| !connection.is_closed());
+    }
+
+    ///Classifies a tcp packet or an ICMP error message & updates the state of the connection.
+    ///
+    ///Returns an error if the packet is neither a tcp packet nor an ICMP error message.
+    pub fn track(&mut self, packet: &SlicedPacket) -> Result<PacketClassification, TrackingError> {
+        match decode(packet) {
+            Ok((key, tcp, payload)) => Ok(self.track_tcp(&key, &tcp, payload.len())),
+            Err(ReassemblyError::IpFragment) => Err(TrackingError::IpFragment),
+            Err(ReassemblyError::MissingTcpHeader) => match icmp_error_key(packet) {
+                Some(key) => Ok(if self.connections.contains_key(&key.canonical()) {
+                    PacketClassification::Related
+                } else {
+                    PacketClassification::Invalid
+                }),
+                None => Err(TrackingError::MissingTcpHeader)
+            }
+        }
+    }
+
+    ///Classifies a tcp segment (`key` oriented from the sender to the receiver) & updates the state of the connection.
+    pub fn track_tcp(&mut self, key: &ConnectionKey, tcp: &TcpHeaderSlice, payload_len: usize) -> PacketClassification {
+        use self::TcpState::*;
+        use self::PacketClassification::Invalid;
+
+        let canonical = key.canonical();
+        let connection = match self.connections.get_mut(&canonical) {
+            Some(value) => value,
+            None => return self.track_new(key, tcp, payload_len)
+        };
+
+        //a new SYN reopens closed connections
+        if tcp.syn() && !tcp.ack() && connection.is_closed() && *key == connection.key {
+            self.connections.remove(&canonical);
+            return self.track_new(key, tcp, payload_len);
+        }
+        if Closed == connection.client.state && Closed == connection.server.state {
+            return Invalid;
+        }
+
+        let from_client = *key == connection.key;
+        let liberal = connection.liberal;
+        {
+            //the changes are only applied if the segment passes the checks
+            let (sender_state, receiver_state) = connection.endpoints(from_client);
+            let mut sender = sender_state.clone();
+            let mut receiver = receiver_state.clone();
+            if tcp.syn() {
+                if !from_client && tcp.ack() && SynSent == receiver.state && 0 == sender.max_window {
+                    //SYN ACK of the server (window scaling is only used if both sides sent the option)
+                    sender.init(tcp, payload_len);
+                    sender.state = SynReceived;
+                    if let (Some(sender_scale), Some(receiver_scale)) = (sender.syn_window_scale, receiver.syn_window_scale) {
+                        sender.window_scale = sender_scale;
+                        receiver.window_scale = receiver_scale;
+                    }
+                } else if tcp.sequence_number().wrapping_add(1) != sender.end || tcp.ack() == from_client {
+                    //only retransmissions of the SYN & SYN ACK are allowed
+                    return Invalid;
+                }
+            } else if liberal && 0 == sender.max_window {
+                sender.init(tcp, payload_len);
+            }
+
+            if !update_windows(&mut sender, &mut receiver, tcp, payload_len, !liberal) {
+                return Invalid;
+            }
+            *sender_state = sender;
+            *receiver_state = receiver;
+        }
+        if !from_client {
+            connection.reply_seen = true;
+        }
+        let classification = connection.classification();
+
+        let (sender, receiver) = connection.endpoints(from_client);
+        if tcp.rst() {
+            sender.state = Closed;
+            receiver.state = Closed;
+            return classification;
+        }
+
+        //handshake
+        if tcp.syn() && !from_client {
+            receiver.state = Established;
+        }
+        if tcp.ack() && SynReceived == receiver.state && tcp.acknowledgment_number() == receiver.end {
+            receiver.state = Established;
+            if SynSent == sender.state {
+                sender.state = Established;
+            }
+        }
+
+        //acknowledgment of the FIN of the receiver
+        if tcp.ack() && receiver.fin_end.map(|value| !before(tcp.acknowledgment_number(), value)).unwrap_or(false) {
+            receiver.state = match receiver.state {
+                FinWait1 => FinWait2,
+                Closing => TimeWait,
+                LastAck => Closed,
+                value => value
+            };
+        }
+
+        //FIN of the sender
+        if tcp.fin() && sender.fin_end.is_none() {
+            sender.fin_end = Some(segment_end(tcp, payload_len));
+            sender.state = match sender.state {
+                CloseWait => LastAck,
+                Closing | LastAck | TimeWait | Closed => sender.state,
+                _ => FinWait1
+            };
+            receiver.state = match receiver.state {
+                FinWait1 => Closing,
+                FinWait2 => TimeWait,
+                Closing | LastAck | TimeWait | Closed | CloseWait => receiver.state,
+                _ => CloseWait
+            };
+        }
+        classification
+    }
+
+    ///Handles a segment of an untracked connection.
+    fn track_new(&mut self, key: &ConnectionKey, tcp: &TcpHeaderSlice, payload_len: usize) -> PacketClassification {
+        let initial_syn = tcp.syn() && !tcp.ack() && !tcp.rst() && !tcp.fin();
+        let pickup = self.config.mid_stream_pickup && tcp.ack() && !tcp.syn() && !tcp.rst();
+        if !(initial_syn || pickup) {
+            return PacketClassification::Invalid;
+        }
+
+        let mut client = Endpoint::new(if initial_syn { TcpState::SynSent } else { TcpState::Established });
+        client.init(tcp, payload_len);
+        let server = Endpoint::new(if initial_syn { TcpState::Listen } else { TcpState::Established });
+        self.connections.insert(key.canonical(), TrackedConnection {
+            key: *key,
+            client,
+            server,
+            reply_seen: false,
+            liberal: pickup,
+        });
+        PacketClassification::New
+    }
+}
+
+///Returns the sequence number following the segment (SYN & FIN count as one byte).
+fn segment_end(tcp: &TcpHeaderSlice, payload_len: usize) -> u32 {
+    tcp.sequence_number()
+       .wrapping_add(payload_len as u32)
+       .wrapping_add(u32::from(tcp.syn()))
+       .wrapping_add(u32::from(tcp.fin()))
+}
+
+///Returns true if the sequence number `a` is before `b` (taking the wraparound into account).
+fn before(a: u32, b: u32) -> bool {
+    (a.wrapping_sub(b) as i32) < 0
+}
+
+///Returns true if the sequence number `a` is after `b` (taking the wraparound into account).
+fn after(a: u32, b: u32) -> bool {
+    before(b, a)
+}
+
+///Checks the sequence & acknowledgment number of a segment against the windows (if `check` is true) &
+///updates the windows. Returns false if the checks fail.
+fn update_windows(sender: &mut Endpoint, receiver: &mut Endpoint, tcp: &TcpHeaderSlice, payload_len: usize, check: bool) -> bool {
+    let sequence_number = tcp.sequence_number();
+    let end = segment_end(tcp, payload_len);
+    //the window of SYNs is never scaled
+    let window = if tcp.syn() {
+        u32::from(tcp.window_size())
+    } else {
+        u32::from(tcp.window_size()) << sender.window_scale
+    };
+    let ack = if tcp.ack() {
+        tcp.acknowledgment_number()
+    } else {
+        receiver.end
+    };
+
+    if check {
+        let max_ack_window = std::cmp::max(sender.max_window, MIN_MAX_ACK_WINDOW);
+        //I: the data starts within the window advertised by the receiver
+        let upper_sequence = !after(sequence_number, sender.max_end);
+        //II: the data does not end before the lowest sequence number the receiver could still expect
+        let lower_sequence = !before(end, sender.end.wrapping_sub(receiver.max_window));
+        //III: only data that was sent by the receiver is acknowledged
+        let upper_ack = !after(ack, receiver.end);
+        //IV: the acknowledgment is not older then the largest window
+        let lower_ack = !before(ack, receiver.end.wrapping_sub(max_ack_window));
+        if !(upper_sequence && lower_sequence && upper_ack && lower_ack) {
+            return false;
+        }
+    }
+
+    sender.max_window = std::cmp::max(sender.max_window, std::cmp::max(window, 1));
+    if after(end, sender.end) {
+        sender.end = end;
+    }
+    if tcp.ack() {
+        let max_end = ack.wrapping_add(std::cmp::max(window, 1));
+        if after(max_end, receiver.max_end) {
+            receiver.max_end = max_end;
+        }
+    }
+    true
+}
+
+///Returns the key (oriented from the sender of the quoted packet) of the tcp packet quoted in an ICMP error message.
+fn icmp_error_key(packet: &SlicedPacket) -> Option<ConnectionKey> {
+    const TCP: u8 = IpTrafficClass::Tcp as u8;
+
+    let icmp = packet.payload;
+    let quoted = match &packet.ip {
+        Some(InternetSlice::Ipv4(header)) => {
+            //destination unreachable, source quench, redirect, time exceeded & parameter problem
+            let error = icmp.first().map(|value| [3, 4, 5, 11, 12].contains(value)).unwrap_or(false);
+            if IpTrafficClass::Icmp as u8 != header.protocol() || packet.authentication_header.is_some() || !error {
+                return None;
+            }
+            icmp.get(8..)?
+        },
+        Some(InternetSlice::Ipv6(header, extensions)) => {
+            let next_header = extensions.iter()
+                                        .flatten()
+                                        .last()
+                                        .map(|(_, extension)| extension.next_header())
+                                        .unwrap_or_else(|| header.next_header());
+            //destination unreachable, packet too big, time exceeded & parameter problem
+            let error = icmp.first().map(|value| [1, 2, 3, 4].contains(value)).unwrap_or(false);
+            if IpTrafficClass::IPv6Icmp as u8 != next_header || !error {
+                return None;
+            }
+            icmp.get(8..)?
+        },
+        None => return None
+    };
+
+    //the quoted packet contains at least the first 8 bytes of the tcp header
+    let (source, destination, ports) = match quoted.first().map(|value| value >> 4) {
+        Some(4) => {
+            let header = Ipv4HeaderSlice::from_slice(quoted).ok()?;
+            if TCP != header.protocol() {
+                return None;
+            }
+            (IpAddr::from(header.source_addr()), IpAddr::from(header.destination_addr()), quoted.get(header.slice().len()..header.slice().len() + 4)?)
+        },
+        Some(6) => {
+            let header = Ipv6HeaderSlice::from_slice(quoted).ok()?;
+            if TCP != header.next_header() {
+                return None;
+            }
+            (IpAddr::from(header.source_addr()), IpAddr::from(header.destination_addr()), quoted.get(40..44)?)
+        },
+        _ => return None
+    };
+    Some(ConnectionKey {
+        source,
+        source_port: u16::from_be_bytes([ports[0], ports[1]]),
+        destination,
+        destination_port: u16::from_be_bytes([ports[2], ports[3]]),
+    })
+}
@@ -402,12 +402,7 @@ impl TcpHeader {
         }
 
         //calculate the checksum
-        Ok(self.calc_checksum_post_ip(u64::from( BigEndian::read_u16(&source_ip[0..2]) ) + //pseudo header
-                                      u64::from( BigEndian::read_u16(&source_ip[2..4]) ) +
-                                      u64::from( BigEndian::read_u16(&destination_ip[0..2]) ) +
-                                      u64::from( BigEndian::read_u16(&destination_ip[2..4]) ) +
-                                      IpTrafficClass::Tcp as u64 +
-                                      tcp_length as u64,
+        Ok(self.calc_checksum_post_ip(checksum::ipv4_pseudo_header_sum(source_ip, destination_ip, IpTrafficClass::Tcp as u8, tcp_length as u16),
                                       payload))
     }
 
@@ -425,42 +420,18 @@ impl TcpHeader {
             return Err(ValueError::TcpLengthTooLarge(tcp_length));
         }
 
-        fn calc_sum(value: &[u8;16]) -> u64 {
-            let mut result = 0;
-            for i in 0..8 {
-                let index = i*2;
-                result += u64::from( BigEndian::read_u16(&value[index..(index + 2)]) );
-            }
-            result
-        }
-        Ok(self.calc_checksum_post_ip(
-            calc_sum(source) +
-            calc_sum(destination) +
-            IpTrafficClass::Tcp as u64 +
-            {
-                let mut buffer: [u8;4] = Default::default();
-                BigEndian::write_u32(&mut buffer[..], tcp_length as u32);
-                u64::from( BigEndian::read_u16(&buffer[0..2]) ) +
-                u64::from( BigEndian::read_u16(&buffer[2..4]) )
-            },
-            payload))
+        Ok(self.calc_checksum_post_ip(checksum::ipv6_pseudo_header_sum(source, destination, IpTrafficClass::Tcp as u8, tcp_length as u32),
+                                      payload))
     }
 
     ///This method takes the sum of the preudo ip header and calculates the rest of the checksum.
-    fn calc_checksum_post_ip(&self, ip_pseudo_header_sum: u64, payload: &[u8]) -> u16 {
-        fn calc_u32_checksum(value: u32) -> u64 {
-            let mut buffer: [u8;4] = [0;4];
-            BigEndian::write_u32(&mut buffer, value);
-            u64::from( BigEndian::read_u16(&buffer[..2]) ) + 
-            u64::from( BigEndian::read_u16(&buffer[2..]) )
-        }
-        let mut sum = 
-            ip_pseudo_header_sum +
-            u64::from( self.source_port ) + //udp header start
-            u64::from( self.destination_port ) +
-            calc_u32_checksum(self.sequence_number) +
-            calc_u32_checksum(self.acknowledgment_number) +
-            u64::from( BigEndian::read_u16(&[
+    fn calc_checksum_post_ip(&self, ip_pseudo_header_sum: checksum::Sum16BitWords, payload: &[u8]) -> u16 {
+        ip_pseudo_header_sum
+            .add_u16(self.source_port) //tcp header start
+            .add_u16(self.destination_port)
+            .add_u32(self.sequence_number)
+            .add_u32(self.acknowledgment_number)
+            .add_2bytes([
                 {
                     let value = (self._data_offset << 4) & 0xF0;
                     if self.ns {
@@ -497,30 +468,12 @@ impl TcpHeader {
                     }
                     value
                 }
-            ]) ) +
-            u64::from( self.window_size ) +
-            u64::from( self.urgent_pointer );
-
-        //add the options
-        let options_len = self.options_len();
-        for i in RangeStep::new(0, options_len, 2) {
-            sum += u64::from( BigEndian::read_u16(&self.options_buffer[i..i + 2]) );
-        }
-
-        //payload
-        for i in RangeStep::new(0, payload.len()/2*2, 2) {
-            sum += u64::from( BigEndian::read_u16(&payload[i..i + 2]) );
-        }
-        //pad the last byte with 0
-        if payload.len() % 2 == 1 {
-            sum += u64::from( BigEndian::read_u16(&[*payload.last().unwrap(), 0]) );
-        }
-        let carry_add = (sum & 0xffff) + 
-                        ((sum >> 16) & 0xffff) +
-                        ((sum >> 32) & 0xffff) +
-                        ((sum >> 48) & 0xffff);
-        let result = ((carry_add & 0xffff) + (carry_add >> 16)) as u16;
-        !result
+            ])
+            .add_u16(self.window_size)
+            .add_u16(self.urgent_pointer)
+            .add_slice(self.options())
+            .add_slice(payload)
+            .ones_complement()
     }
 }
 
@@ -797,12 +750,11 @@ impl<'a> TcpHeaderSlice<'a> {
         }
 
         //calculate the checksum
-        Ok(self.calc_checksum_post_ip(u64::from( BigEndian::read_u16(&source_ip[0..2]) ) + //pseudo header
-                                      u64::from( BigEndian::read_u16(&source_ip[2..4]) ) +
-                                      u64::from( BigEndian::read_u16(&destination_ip[0..2]) ) +
-                                      u64::from( BigEndian::read_u16(&destination_ip[2..4]) ) +
-                                      IpTrafficClass::Tcp as u64 +
-                                      tcp_length as u64,
+        Ok(self.calc_checksum_post_ip(checksum::Sum16BitWords::new() //pseudo header
+                                      .add_slice(&source_ip[..4])
+                                      .add_slice(&destination_ip[..4])
+                                      .add_u16(IpTrafficClass::Tcp as u16)
+                                      .add_u16(tcp_length as u16),
                                       payload))
     }
 
@@ -820,54 +772,177 @@ impl<'a> TcpHeaderSlice<'a> {
             return Err(ValueError::TcpLengthTooLarge(tcp_length));
         }
 
-        fn calc_addr_sum(value: &[u8]) -> u64 {
-            let mut result = 0;
-            for i in 0..8 {
-                let index = i*2;
-                result += u64::from( BigEndian::read_u16(&value[index..(index + 2)]) );
-            }
-            result
-        }
-        Ok(self.calc_checksum_post_ip(
-            calc_addr_sum(source) +
-            calc_addr_sum(destination) +
-            IpTrafficClass::Tcp as u64 +
-            {
-                let mut buffer: [u8;4] = Default::default();
-                BigEndian::write_u32(&mut buffer[..], tcp_length as u32);
-                u64::from( BigEndian::read_u16(&buffer[0..2]) ) +
-                u64::from( BigEndian::read_u16(&buffer[2..4]) )
-            },
-            payload))
+        Ok(self.calc_checksum_post_ip(checksum::Sum16BitWords::new() //pseudo header
+                                      .add_slice(&source[..16])
+                                      .add_slice(&destination[..16])
+                                      .add_u16(IpTrafficClass::Tcp as u16)
+                                      .add_u32(tcp_length as u32),
+                                      payload))
     }
 
     ///This method takes the sum of the preudo ip header and calculates the rest of the checksum.
-    fn calc_checksum_post_ip(&self, ip_pseudo_header_sum: u64, payload: &[u8]) -> u16 {
+    fn calc_checksum_post_ip(&self, ip_pseudo_header_sum: checksum::Sum16BitWords, payload: &[u8]) -> u16 {
+        ip_pseudo_header_sum
+            .add_slice(&self.slice[..16]) //until checksum
+            .add_slice(&self.slice[18..]) //after checksum
+            .add_slice(payload)
+            .ones_complement()
+    }
+}
 
-        let mut sum = ip_pseudo_header_sum;
+///A mutable slice containing a tcp header of a network package. Allows the in place modification of the header fields.
+///
+///All setters (except `set_checksum`) incrementally update the checksum (rfc1624), so a valid checksum stays valid
+///after a field was modified.
+#[derive(Debug, Eq, PartialEq)]
+pub struct TcpHeaderSliceMut<'a> {
+    slice: &'a mut [u8]
+}
 
-        //until checksum
-        for i in RangeStep::new(0, 16, 2) {
-            sum += u64::from( BigEndian::read_u16(&self.slice[i..i + 2]) );
-        }
-        //after checksum
-        for i in RangeStep::new(18, self.slice.len(), 2) {
-            sum += u64::from( BigEndian::read_u16(&self.slice[i..i + 2]) );
-        }
-        //payload
-        for i in RangeStep::new(0, payload.len()/2*2, 2) {
-            sum += u64::from( BigEndian::read_u16(&payload[i..i + 2]) );
+impl<'a> TcpHeaderSliceMut<'a> {
+
+    ///Creates a mutable slice containing a tcp header.
+    pub fn from_slice(slice: &'a mut [u8]) -> Result<TcpHeaderSliceMut<'a>, ReadError> {
+        //check the header
+        let len = TcpHeaderSlice::from_slice(slice)?.slice().len();
+
+        //done
+        Ok(TcpHeaderSliceMut {
+            slice: &mut slice[..len]
+        })
+    }
+
+    ///Returns the slice containing the tcp header
+    pub fn slice(&self) -> &[u8] {
+        self.slice
+    }
+
+    ///Returns a read only slice of the header (allows the reading of the header fields).
+    pub fn as_slice(&self) -> TcpHeaderSlice<'_> {
+        TcpHeaderSlice {
+            slice: self.slice
         }
-        //pad the last byte with 0
-        if payload.len() % 2 == 1 {
-            sum += u64::from( BigEndian::read_u16(&[*payload.last().unwrap(), 0]) );
+    }
+
+    ///Sets the source port number.
+    pub fn set_source_port(&mut self, value: u16) {
+        self.set_word(0, value.to_be_bytes());
+    }
+
+    ///Sets the destination port number.
+    pub fn set_destination_port(&mut self, value: u16) {
+        self.set_word(2, value.to_be_bytes());
+    }
+
+    ///Sets the sequence number.
+    pub fn set_sequence_number(&mut self, value: u32) {
+        self.set_u32(4, value);
+    }
+
+    ///Sets the acknowledgment number.
+    pub fn set_acknowledgment_number(&mut self, value: u32) {
+        self.set_u32(8, value);
+    }
+
+    ///Sets the ECN-nonce flag.
+    pub fn set_ns(&mut self, value: bool) {
+        self.set_flag(12, 1, value);
+    }
+
+    ///Sets the fin flag.
+    pub fn set_fin(&mut self, value: bool) {
+        self.set_flag(13, 1, value);
+    }
+
+    ///Sets the syn flag.
+    pub fn set_syn(&mut self, value: bool) {
+        self.set_flag(13, 2, value);
+    }
+
+    ///Sets the rst flag.
+    pub fn set_rst(&mut self, value: bool) {
+        self.set_flag(13, 4, value);
+    }
+
+    ///Sets the psh flag.
+    pub fn set_psh(&mut self, value: bool) {
+        self.set_flag(13, 8, value);
+    }
+
+    ///Sets the ack flag.
+    pub fn set_ack(&mut self, value: bool) {
+        self.set_flag(13, 16, value);
+    }
+
+    ///Sets the urg flag.
+    pub fn set_urg(&mut self, value: bool) {
+        self.set_flag(13, 32, value);
+    }
+
+    ///Sets the ECN-Echo flag.
+    pub fn set_ece(&mut self, value: bool) {
+        self.set_flag(13, 64, value);
+    }
+
+    ///Sets the cwr flag.
+    pub fn set_cwr(&mut self, value: bool) {
+        self.set_flag(13, 128, value);
+    }
+
+    ///Sets the window size.
+    pub fn set_window_size(&mut self, value: u16) {
+        self.set_word(14, value.to_be_bytes());
+    }
+
+    ///Sets the checksum (the checksum is NOT recalculated).
+    pub fn set_checksum(&mut self, value: u16) {
+        BigEndian::write_u16(&mut self.slice[16..18], value);
+    }
+
+    ///Sets the urgent pointer.
+    pub fn set_urgent_pointer(&mut self, value: u16) {
+        self.set_word(18, value.to_be_bytes());
+    }
+
+    ///Updates the checksum after an ipv4 address in the pseudo header was changed from `old` to `new`
+    ///(the address itself has to be changed in the ip header).
+    pub fn update_checksum_ipv4_address(&mut self, old: [u8;4], new: [u8;4]) {
+        let checksum = checksum::update_ipv4_address(self.as_slice().checksum(), old, new);
+        self.set_checksum(checksum);
+    }
+
+    ///Updates the checksum after an ipv6 address in the pseudo header was changed from `old` to `new`
+    ///(the address itself has to be changed in the ip header).
+    pub fn update_checksum_ipv6_address(&mut self, old: &[u8;16], new: &[u8;16]) {
+        let checksum = checksum::update_ipv6_address(self.as_slice().checksum(), old, new);
+        self.set_checksum(checksum);
+    }
+
+    ///Sets or clears the flag bits in the given byte (12 or 13) & updates the checksum.
+    fn set_flag(&mut self, index: usize, mask: u8, value: bool) {
+        let mut word = [self.slice[12], self.slice[13]];
+        if value {
+            word[index - 12] |= mask;
+        } else {
+            word[index - 12] &= !mask;
         }
-        let carry_add = (sum & 0xffff) + 
-                        ((sum >> 16) & 0xffff) +
-                        ((sum >> 32) & 0xffff) +
-                        ((sum >> 48) & 0xffff);
-        let result = ((carry_add & 0xffff) + (carry_add >> 16)) as u16;
-        !result
+        self.set_word(12, word);
+    }
+
+    ///Writes the 32 bit value at the given offset & updates the checksum.
+    fn set_u32(&mut self, offset: usize, value: u32) {
+        let old = BigEndian::read_u32(&self.slice[offset..offset + 4]);
+        let checksum = checksum::update_u32(self.as_slice().checksum(), old, value);
+        BigEndian::write_u32(&mut self.slice[offset..offset + 4], value);
+        self.set_checksum(checksum);
+    }
+
+    ///Writes the 16 bit word at the given offset & updates the checksum.
+    fn set_word(&mut self, offset: usize, value: [u8;2]) {
+        let old = BigEndian::read_u16(&self.slice[offset..offset + 2]);
+        let checksum = checksum::update_u16(self.as_slice().checksum(), old, u16::from_be_bytes(value));
+        self.slice[offset..offset + 2].copy_from_slice(&value);
+        self.set_checksum(checksum);
     }
 }
 
@@ -71,12 +71,7 @@ impl UdpHeader {
     
     ///Calculates the upd header checksum based on a ipv4 header.
     fn calc_checksum_ipv4_internal(&self, source: [u8;4], destination: [u8;4], protocol: u8, payload: &[u8]) -> u16 {
-        self.calc_checksum_post_ip(u64::from( BigEndian::read_u16(&source[0..2]) ) + //pseudo header
-                                   u64::from( BigEndian::read_u16(&source[2..4]) ) +
-                                   u64::from( BigEndian::read_u16(&destination[0..2]) ) +
-                                   u64::from( BigEndian::read_u16(&destination[2..4]) ) +
-                                   u64::from( protocol ) +
-                                   u64::from( self.length ), 
+        self.calc_checksum_post_ip(checksum::ipv4_pseudo_header_sum(source, destination, protocol, self.length),
                                    payload)
     }
 
@@ -116,45 +111,18 @@ impl UdpHeader {
     }
 
     fn calc_checksum_ipv6_internal(&self, source: &[u8;16], destination: &[u8;16], payload: &[u8]) -> u16 {
-        fn calc_sum(value: &[u8;16]) -> u64 {
-            let mut result = 0;
-            for i in 0..8 {
-                let index = i*2;
-                result += u64::from( BigEndian::read_u16(&value[index..(index + 2)]) );
-            }
-            result
-        }
-        self.calc_checksum_post_ip(calc_sum(source) +
-                                   calc_sum(destination) +
-                                   IpTrafficClass::Udp as u64 +
-                                   u64::from( self.length ),
+        self.calc_checksum_post_ip(checksum::ipv6_pseudo_header_sum(source, destination, IpTrafficClass::Udp as u8, u32::from(self.length)),
                                    payload)
     }
 
     ///This method takes the sum of the preudo ip header and calculates the rest of the checksum.
-    fn calc_checksum_post_ip(&self, ip_pseudo_header_sum: u64, payload: &[u8]) -> u16 {
-        let mut sum = ip_pseudo_header_sum +
-                      u64::from( self.source_port ) + //udp header start
-                      u64::from( self.destination_port ) +
-                      u64::from( self.length );
-
-        for i in 0..(payload.len()/2) {
-            sum += u64::from( BigEndian::read_u16(&payload[i*2..i*2 + 2]) );
-        }
-        //pad the last byte with 0
-        if payload.len() % 2 == 1 {
-            sum += u64::from( BigEndian::read_u16(&[*payload.last().unwrap(), 0]));
-        }
-        let carry_add = (sum & 0xffff) + 
-                        ((sum >> 16) & 0xffff) +
-                        ((sum >> 32) & 0xffff) +
-                        ((sum >> 48) & 0xffff);
-        let result = ((carry_add & 0xffff) + (carry_add >> 16)) as u16;
-        if 0xffff == result {
-            result //avoid the transmition of an all 0 checksum as this value is reserved by "checksum disabled" (see rfc)
-        } else {
-            !result
-        }
+    fn calc_checksum_post_ip(&self, ip_pseudo_header_sum: checksum::Sum16BitWords, payload: &[u8]) -> u16 {
+        ip_pseudo_header_sum
+            .add_u16(self.source_port) //udp header start
+            .add_u16(self.destination_port)
+            .add_u16(self.length)
+            .add_slice(payload)
+            .to_ones_complement_with_no_zero() //avoid the transmition of an all 0 checksum as this value is reserved by "checksum disabled" (see rfc)
     }
 
     ///Reads a udp header from a slice directly and returns a tuple containing the resulting header & unused part of the slice.
@@ -247,3 +215,82 @@ impl<'a> UdpHeaderSlice<'a> {
         }
     }
 }
+
+///A mutable slice containing an udp header of a network package. Allows the in place modification of the header fields.
+///
+///The port setters incrementally update the checksum (rfc1624), so a valid checksum stays valid after a port
+///was modified. A checksum of 0 (no checksum calculated, only allowed for udp over ipv4) is left unchanged.
+#[derive(Debug, Eq, PartialEq)]
+pub struct UdpHeaderSliceMut<'a> {
+    slice: &'a mut [u8]
+}
+
+impl<'a> UdpHeaderSliceMut<'a> {
+
+    ///Creates a mutable slice containing an udp header.
+    pub fn from_slice(slice: &'a mut [u8]) -> Result<UdpHeaderSliceMut<'a>, ReadError> {
+        //check length
+        let len = UdpHeaderSlice::from_slice(slice)?.slice().len();
+
+        //done
+        Ok(UdpHeaderSliceMut{
+            slice: &mut slice[..len]
+        })
+    }
+
+    ///Returns the slice containing the udp header
+    pub fn slice(&self) -> &[u8] {
+        self.slice
+    }
+
+    ///Returns a read only slice of the header (allows the reading of the header fields).
+    pub fn as_slice(&self) -> UdpHeaderSlice<'_> {
+        UdpHeaderSlice {
+            slice: self.slice
+        }
+    }
+
+    ///Sets the "udp source port".
+    pub fn set_source_port(&mut self, value: u16) {
+        self.set_word(0, value);
+    }
+
+    ///Sets the "udp destination port".
+    pub fn set_destination_port(&mut self, value: u16) {
+        self.set_word(2, value);
+    }
+
+    ///Sets the "checksum" field (the checksum is NOT recalculated).
+    pub fn set_checksum(&mut self, value: u16) {
+        BigEndian::write_u16(&mut self.slice[6..8], value);
+    }
+
+    ///Updates the checksum after an ipv4 address in the pseudo header was changed from `old` to `new`
+    ///(the address itself has to be changed in the ip header).
+    pub fn update_checksum_ipv4_address(&mut self, old: [u8;4], new: [u8;4]) {
+        self.update_checksum(|checksum| checksum::update_ipv4_address(checksum, old, new));
+    }
+
+    ///Updates the checksum after an ipv6 address in the pseudo header was changed from `old` to `new`
+    ///(the address itself has to be changed in the ip header).
+    pub fn update_checksum_ipv6_address(&mut self, old: &[u8;16], new: &[u8;16]) {
+        self.update_checksum(|checksum| checksum::update_ipv6_address(checksum, old, new));
+    }
+
+    ///Writes the 16 bit word at the given offset & updates the checksum.
+    fn set_word(&mut self, offset: usize, value: u16) {
+        let old = BigEndian::read_u16(&self.slice[offset..offset + 2]);
+        self.update_checksum(|checksum| checksum::update_u16(checksum, old, value));
+        BigEndian::write_u16(&mut self.slice[offset..offset + 2], value);
+    }
+
+    ///Applies the given update to the checksum, unless the checksum is 0 (not calculated).
+    fn update_checksum<F: FnOnce(u16) -> u16>(&mut self, update: F) {
+        let checksum = self.as_slice().checksum();
+        if 0 != checksum {
+            //a calculated checksum of 0 is transmitted as 0xffff
+            let result = update(checksum);
+            self.set_checksum(if 0 == result { 0xffff } else { result });
+        }
+    }
+}
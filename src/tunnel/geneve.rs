@@ -0,0 +1,397 @@
+use super::super::*;
+
+use std::fmt::{Debug, Formatter};
+
+extern crate byteorder;
+use self::byteorder::{ByteOrder, BigEndian, ReadBytesExt, WriteBytesExt};
+
+///Default udp destination port of Geneve packets (assigned by IANA).
+pub const GENEVE_PORT: u16 = 6081;
+
+///Maximum length of the options of a geneve header in bytes (6 bit length field in multiples of 4 bytes).
+pub const GENEVE_MAX_OPTIONS_LENGTH: usize = 0x3f*4;
+
+///Generic Network Virtualization Encapsulation (Geneve) header according to rfc8926.
+#[derive(Clone)]
+pub struct GeneveHeader {
+    ///2 bit version of the geneve header (currently only 0 is defined).
+    pub version: u8,
+    ///"O" bit, set if the packet contains a control message instead of a data payload.
+    pub control_packet: bool,
+    ///"C" bit, set if one or more of the options has the critical bit set.
+    pub critical_options: bool,
+    ///Type of the protocol data unit following the header (ether type, e.g. 0x6558 for Ethernet).
+    pub protocol_type: u16,
+    ///24 bit virtual network identifier.
+    pub vni: u32,
+    ///Length of the options in the options_buffer in bytes.
+    options_len: u8,
+    options_buffer: [u8;GENEVE_MAX_OPTIONS_LENGTH]
+}
+
+impl SerializedSize for GeneveHeader {
+    ///Size of the header itself (without options) in bytes.
+    const SERIALIZED_SIZE: usize = 8;
+}
+
+impl GeneveHeader {
+
+    ///Creates a geneve header without options for the given protocol type & vni.
+    pub fn new(protocol_type: u16, vni: u32) -> GeneveHeader {
+        GeneveHeader {
+            version: 0,
+            control_packet: false,
+            critical_options: false,
+            protocol_type,
+            vni,
+            options_len: 0,
+            options_buffer: [0;GENEVE_MAX_OPTIONS_LENGTH]
+        }
+    }
+
+    ///Returns a slice to the options part of the header (empty if no options are present).
+    pub fn options(&self) -> &[u8] {
+        &self.options_buffer[..usize::from(self.options_len)]
+    }
+
+    ///Returns an iterator that allows to iterate through all the options of the header.
+    pub fn options_iterator(&self) -> GeneveOptionsIterator<'_> {
+        GeneveOptionsIterator::from_slice(self.options())
+    }
+
+    ///Length of the header (includes options) in bytes.
+    pub fn header_len(&self) -> usize {
+        GeneveHeader::SERIALIZED_SIZE + usize::from(self.options_len)
+    }
+
+    ///Sets the options (already encoded as bytes).
+    ///
+    ///The length of the given slice must be a multiple of 4 and maximum 252 bytes.
+    ///If the length is not fullfilling these constraints, no data is set and
+    ///an error is returned.
+    pub fn set_options_raw(&mut self, data: &[u8]) -> Result<(), ValueError> {
+        use crate::ValueError::*;
+
+        //check that the options length is within bounds
+        if (GENEVE_MAX_OPTIONS_LENGTH < data.len()) ||
+           (0 != data.len() & 0b11)
+        {
+            Err(GeneveOptionsLengthBad(data.len()))
+        } else {
+            //copy the data to the buffer
+            self.options_buffer[..data.len()].copy_from_slice(data);
+
+            //set the header length
+            self.options_len = data.len() as u8;
+            Ok(())
+        }
+    }
+
+    ///Reads a geneve header from a slice and returns a tuple containing the resulting header & unused part of the slice.
+    pub fn read_from_slice(slice: &[u8]) -> Result<(GeneveHeader, &[u8]), ReadError> {
+        let header = GeneveHeaderSlice::from_slice(slice)?.to_header();
+        let rest = &slice[header.header_len()..];
+        Ok((
+            header,
+            rest
+        ))
+    }
+
+    ///Reads a geneve header from the current position.
+    pub fn read<T: io::Read + io::Seek + Sized>(reader: &mut T) -> Result<GeneveHeader, io::Error> {
+        let (version, options_len) = {
+            let value = reader.read_u8()?;
+            (value >> 6, (value & 0x3f)*4)
+        };
+        let (control_packet, critical_options) = {
+            let value = reader.read_u8()?;
+            (0 != value & 0x80, 0 != value & 0x40)
+        };
+        let protocol_type = reader.read_u16::<BigEndian>()?;
+        let vni = reader.read_u32::<BigEndian>()? >> 8;
+        Ok(GeneveHeader {
+            version,
+            control_packet,
+            critical_options,
+            protocol_type,
+            vni,
+            options_len,
+            options_buffer: {
+                let mut values: [u8;GENEVE_MAX_OPTIONS_LENGTH] = [0;GENEVE_MAX_OPTIONS_LENGTH];
+                reader.read_exact(&mut values[..usize::from(options_len)])?;
+                values
+            }
+        })
+    }
+
+    ///Checks if the values in this header are valid values for a geneve header.
+    ///
+    ///Specifically it will be checked, that:
+    /// * version is not greater then 0x3
+    /// * vni is not greater then 0xffffff
+    pub fn check_ranges(&self) -> Result<(), ValueError> {
+        use crate::ErrorField::*;
+
+        max_check_u8(self.version, 0x3, GeneveVersion)?;
+        if self.vni > 0x00ff_ffff {
+            return Err(ValueError::U32TooLarge{
+                value: self.vni,
+                max: 0x00ff_ffff,
+                field: GeneveVni
+            });
+        }
+        Ok(())
+    }
+
+    ///Writes the geneve header (including the options) to the current position.
+    pub fn write<T: io::Write + Sized>(&self, writer: &mut T) -> Result<(), WriteError> {
+        self.check_ranges()?;
+
+        writer.write_u8((self.version << 6) | (self.options_len/4))?;
+        writer.write_u8({
+            let mut value = 0;
+            if self.control_packet {
+                value |= 0x80;
+            }
+            if self.critical_options {
+                value |= 0x40;
+            }
+            value
+        })?;
+        writer.write_u16::<BigEndian>(self.protocol_type)?;
+        writer.write_u32::<BigEndian>(self.vni << 8)?;
+        writer.write_all(self.options())?;
+        Ok(())
+    }
+}
+
+//NOTE: Same as for the Ipv4Header, Default, Debug & PartialEq have to be implemented by hand
+//      as the traits are not availible for the options buffer array.
+
+impl Default for GeneveHeader {
+    fn default() -> GeneveHeader {
+        GeneveHeader::new(0, 0)
+    }
+}
+
+impl Debug for GeneveHeader {
+    fn fmt(&self, fotmatter: &mut Formatter) -> Result<(), std::fmt::Error> {
+        write!(fotmatter, "GeneveHeader {{ version: {}, control_packet: {}, critical_options: {}, protocol_type: {}, vni: {}, options: {:?} }}",
+            self.version,
+            self.control_packet,
+            self.critical_options,
+            self.protocol_type,
+            self.vni,
+            self.options())
+    }
+}
+
+impl std::cmp::PartialEq for GeneveHeader {
+    fn eq(&self, other: &GeneveHeader) -> bool {
+        self.version == other.version &&
+        self.control_packet == other.control_packet &&
+        self.critical_options == other.critical_options &&
+        self.protocol_type == other.protocol_type &&
+        self.vni == other.vni &&
+        self.options() == other.options()
+    }
+}
+
+impl std::cmp::Eq for GeneveHeader {}
+
+///A slice containing a geneve header (including options) of a network package.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GeneveHeaderSlice<'a> {
+    slice: &'a [u8]
+}
+
+impl<'a> GeneveHeaderSlice<'a> {
+
+    ///Creates a slice containing a geneve header (including options).
+    pub fn from_slice(slice: &'a[u8]) -> Result<GeneveHeaderSlice<'a>, ReadError> {
+        //check length
+        use crate::ReadError::*;
+        if slice.len() < GeneveHeader::SERIALIZED_SIZE {
+            return Err(UnexpectedEndOfSlice(GeneveHeader::SERIALIZED_SIZE));
+        }
+
+        //check that the options are also contained in the slice
+        let header_len = GeneveHeader::SERIALIZED_SIZE + usize::from(slice[0] & 0x3f)*4;
+        if slice.len() < header_len {
+            return Err(UnexpectedEndOfSlice(header_len));
+        }
+
+        //done
+        Ok(GeneveHeaderSlice{
+            slice: &slice[..header_len]
+        })
+    }
+
+    ///Returns the slice containing the geneve header
+    #[inline]
+    pub fn slice(&self) -> &'a [u8] {
+        self.slice
+    }
+
+    ///Read the 2 bit "version" field from the slice.
+    pub fn version(&self) -> u8 {
+        self.slice[0] >> 6
+    }
+
+    ///Read the 6 bit "options length" field from the slice (length of the options in multiples of 4 bytes).
+    pub fn opt_len(&self) -> u8 {
+        self.slice[0] & 0x3f
+    }
+
+    ///Read the "O" bit from the slice (set if the packet contains a control message).
+    pub fn control_packet(&self) -> bool {
+        0 != self.slice[1] & 0x80
+    }
+
+    ///Read the "C" bit from the slice (set if critical options are present).
+    pub fn critical_options(&self) -> bool {
+        0 != self.slice[1] & 0x40
+    }
+
+    ///Read the "protocol type" field from the slice (ether type of the encapsulated data).
+    pub fn protocol_type(&self) -> u16 {
+        BigEndian::read_u16(&self.slice[2..4])
+    }
+
+    ///Read the 24 bit "virtual network identifier" from the slice.
+    pub fn vni(&self) -> u32 {
+        BigEndian::read_u32(&self.slice[4..8]) >> 8
+    }
+
+    ///Returns a slice containing the options of the header (empty if no options are present).
+    pub fn options(&self) -> &'a [u8] {
+        &self.slice[GeneveHeader::SERIALIZED_SIZE..]
+    }
+
+    ///Returns an iterator that allows to iterate through all the options of the header.
+    pub fn options_iterator(&self) -> GeneveOptionsIterator<'a> {
+        GeneveOptionsIterator::from_slice(self.options())
+    }
+
+    ///Decode all the fields and copy the results to a GeneveHeader struct
+    pub fn to_header(&self) -> GeneveHeader {
+        let options = self.options();
+        GeneveHeader {
+            version: self.version(),
+            control_packet: self.control_packet(),
+            critical_options: self.critical_options(),
+            protocol_type: self.protocol_type(),
+            vni: self.vni(),
+            options_len: options.len() as u8,
+            options_buffer: {
+                let mut result: [u8;GENEVE_MAX_OPTIONS_LENGTH] = [0;GENEVE_MAX_OPTIONS_LENGTH];
+                result[..options.len()].copy_from_slice(options);
+                result
+            }
+        }
+    }
+}
+
+///A slice containing a single geneve option (type-length-value).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GeneveOptionSlice<'a> {
+    slice: &'a [u8]
+}
+
+impl<'a> GeneveOptionSlice<'a> {
+
+    ///Size of the option header (class, type & length) in bytes.
+    pub const HEADER_LEN: usize = 4;
+
+    ///Creates a slice containing a geneve option (option header & data).
+    pub fn from_slice(slice: &'a[u8]) -> Result<GeneveOptionSlice<'a>, ReadError> {
+        //check length
+        use crate::ReadError::*;
+        if slice.len() < GeneveOptionSlice::HEADER_LEN {
+            return Err(UnexpectedEndOfSlice(GeneveOptionSlice::HEADER_LEN));
+        }
+
+        //check that the data is also present
+        let len = GeneveOptionSlice::HEADER_LEN + usize::from(slice[3] & 0x1f)*4;
+        if slice.len() < len {
+            return Err(UnexpectedEndOfSlice(len));
+        }
+
+        //done
+        Ok(GeneveOptionSlice{
+            slice: &slice[..len]
+        })
+    }
+
+    ///Returns the slice containing the option
+    #[inline]
+    pub fn slice(&self) -> &'a [u8] {
+        self.slice
+    }
+
+    ///Read the "option class" field (namespace of the option type).
+    pub fn option_class(&self) -> u16 {
+        BigEndian::read_u16(&self.slice[..2])
+    }
+
+    ///Read the "type" field (includes the critical bit as highest bit).
+    pub fn option_type(&self) -> u8 {
+        self.slice[2]
+    }
+
+    ///Returns true if the critical bit (highest bit of the type field) is set.
+    pub fn critical(&self) -> bool {
+        0 != self.slice[2] & 0x80
+    }
+
+    ///Read the 5 bit "length" field (length of the data in multiples of 4 bytes).
+    pub fn length(&self) -> u8 {
+        self.slice[3] & 0x1f
+    }
+
+    ///Returns the data of the option.
+    pub fn data(&self) -> &'a [u8] {
+        &self.slice[GeneveOptionSlice::HEADER_LEN..]
+    }
+}
+
+///Allows iterating over the options of a geneve header.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GeneveOptionsIterator<'a> {
+    options: &'a [u8]
+}
+
+impl<'a> GeneveOptionsIterator<'a> {
+    ///Creates an options iterator from a slice containing encoded geneve options.
+    pub fn from_slice(options: &'a [u8]) -> GeneveOptionsIterator<'a> {
+        GeneveOptionsIterator{ options }
+    }
+
+    ///Returns the non processed part of the options slice.
+    pub fn rest(&self) -> &'a [u8] {
+        self.options
+    }
+}
+
+impl<'a> Iterator for GeneveOptionsIterator<'a> {
+    type Item = Result<GeneveOptionSlice<'a>, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.options.is_empty() {
+            None
+        } else {
+            match GeneveOptionSlice::from_slice(self.options) {
+                Ok(value) => {
+                    self.options = &self.options[value.slice().len()..];
+                    Some(Ok(value))
+                },
+                Err(err) => {
+                    //move the slice to the end so no further elements are returned
+                    let len = self.options.len();
+                    self.options = &self.options[len..len];
+                    Some(Err(err))
+                }
+            }
+        }
+    }
+}
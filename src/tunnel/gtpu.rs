@@ -0,0 +1,678 @@
+use super::super::*;
+
+use std::fmt::{Debug, Formatter};
+
+extern crate byteorder;
+use self::byteorder::{ByteOrder, BigEndian, ReadBytesExt, WriteBytesExt};
+
+///Default udp port of GTP-U packets (assigned by IANA).
+pub const GTPU_PORT: u16 = 2152;
+
+///Maximum length of the extension headers of a gtpu header in bytes that is supported by etherparse.
+pub const GTPU_MAX_EXTENSION_HEADERS_LENGTH: usize = 256;
+
+///Message type of an "Echo Request".
+pub const GTPU_MESSAGE_TYPE_ECHO_REQUEST: u8 = 1;
+///Message type of an "Echo Response".
+pub const GTPU_MESSAGE_TYPE_ECHO_RESPONSE: u8 = 2;
+///Message type of an "Error Indication".
+pub const GTPU_MESSAGE_TYPE_ERROR_INDICATION: u8 = 26;
+///Message type of an "End Marker".
+pub const GTPU_MESSAGE_TYPE_END_MARKER: u8 = 254;
+///Message type of a "G-PDU" (message containing an encapsulated user packet).
+pub const GTPU_MESSAGE_TYPE_G_PDU: u8 = 255;
+
+///Extension header type signaling that no further extension header follows.
+pub const GTPU_EXTENSION_HEADER_NONE: u8 = 0;
+///Extension header type of the "PDU Session Container" (used in 5G).
+pub const GTPU_EXTENSION_HEADER_PDU_SESSION_CONTAINER: u8 = 0x85;
+
+const FLAG_EXTENSION_HEADER: u8 = 0b100;
+const FLAG_SEQUENCE_NUMBER: u8 = 0b010;
+const FLAG_N_PDU_NUMBER: u8 = 0b001;
+
+///GPRS Tunnelling Protocol User Plane (GTPv1-U) header according to 3GPP TS 29.281.
+#[derive(Clone)]
+pub struct GtpuHeader {
+    ///3 bit version of the header (1 for GTPv1).
+    pub version: u8,
+    ///"PT" flag, set for GTP & not set for GTP'.
+    pub protocol_type: bool,
+    ///Type of the message (e.g. `GTPU_MESSAGE_TYPE_G_PDU`).
+    pub message_type: u8,
+    ///Length of everything following the first 8 bytes of the header (optional fields, extension headers & payload) in bytes.
+    pub length: u16,
+    ///Tunnel endpoint identifier.
+    pub teid: u32,
+    ///Sequence number (if present the "S" flag is set).
+    pub sequence_number: Option<u16>,
+    ///N-PDU number (if present the "PN" flag is set).
+    pub n_pdu_number: Option<u8>,
+    ///Type of the first extension header.
+    next_extension_header_type: u8,
+    ///Length of the extension headers in the extension_headers_buffer in bytes.
+    extension_headers_len: u16,
+    extension_headers_buffer: [u8;GTPU_MAX_EXTENSION_HEADERS_LENGTH]
+}
+
+impl SerializedSize for GtpuHeader {
+    ///Size of the mandatory part of the header (without optional fields & extension headers) in bytes.
+    const SERIALIZED_SIZE: usize = 8;
+}
+
+impl GtpuHeader {
+
+    ///Size of the optional part (sequence number, n-pdu number & next extension header type) in bytes.
+    pub const OPTIONAL_FIELDS_LEN: usize = 4;
+
+    ///Creates a G-PDU gtpu header with the given tunnel endpoint identifier (no optional fields & extension headers).
+    pub fn new(teid: u32) -> GtpuHeader {
+        GtpuHeader {
+            version: 1,
+            protocol_type: true,
+            message_type: GTPU_MESSAGE_TYPE_G_PDU,
+            length: 0,
+            teid,
+            sequence_number: None,
+            n_pdu_number: None,
+            next_extension_header_type: GTPU_EXTENSION_HEADER_NONE,
+            extension_headers_len: 0,
+            extension_headers_buffer: [0;GTPU_MAX_EXTENSION_HEADERS_LENGTH]
+        }
+    }
+
+    ///Returns the type of the first extension header (`GTPU_EXTENSION_HEADER_NONE` if no extension headers are present).
+    pub fn next_extension_header_type(&self) -> u8 {
+        self.next_extension_header_type
+    }
+
+    ///Returns a slice containing the encoded extension headers (empty if no extension headers are present).
+    pub fn extension_headers(&self) -> &[u8] {
+        &self.extension_headers_buffer[..usize::from(self.extension_headers_len)]
+    }
+
+    ///Returns an iterator that allows to iterate through all the extension headers of the header.
+    pub fn extension_headers_iterator(&self) -> GtpuExtensionHeadersIterator<'_> {
+        GtpuExtensionHeadersIterator::from_slice(self.next_extension_header_type, self.extension_headers())
+    }
+
+    ///Returns true if the optional fields (sequence number, n-pdu number & next extension header type) are present when serialized.
+    pub fn has_optional_fields(&self) -> bool {
+        self.sequence_number.is_some() ||
+        self.n_pdu_number.is_some() ||
+        0 != self.extension_headers_len
+    }
+
+    ///Length of the header (includes optional fields & extension headers) in bytes.
+    pub fn header_len(&self) -> usize {
+        GtpuHeader::SERIALIZED_SIZE +
+        if self.has_optional_fields() {
+            GtpuHeader::OPTIONAL_FIELDS_LEN
+        } else {
+            0
+        } +
+        usize::from(self.extension_headers_len)
+    }
+
+    ///Appends an extension header with the given type & content to the end of the extension header chain.
+    ///
+    ///The length of the content plus 2 bytes (length & next extension header type field) must be a multiple of 4
+    ///and all extension headers together can not be bigger then `GTPU_MAX_EXTENSION_HEADERS_LENGTH`. If these
+    ///constraints are not fullfilled, no extension header is added and an error is returned.
+    ///
+    ///Note that the extension_type should not be `GTPU_EXTENSION_HEADER_NONE` (0) as this value marks the
+    ///end of the extension header chain.
+    pub fn add_extension_header(&mut self, extension_type: u8, content: &[u8]) -> Result<(), ValueError> {
+        let len = content.len() + 2;
+        let start = usize::from(self.extension_headers_len);
+        if (0 != len & 0b11) ||
+           (GTPU_MAX_EXTENSION_HEADERS_LENGTH - start < len)
+        {
+            return Err(ValueError::GtpuExtensionHeaderLengthBad(content.len()));
+        }
+
+        //link the extension header to the previous one
+        if 0 == start {
+            self.next_extension_header_type = extension_type;
+        } else {
+            self.extension_headers_buffer[start - 1] = extension_type;
+        }
+
+        //length, content & next extension header type
+        let end = start + len;
+        self.extension_headers_buffer[start] = (len/4) as u8;
+        self.extension_headers_buffer[start + 1..end - 1].copy_from_slice(content);
+        self.extension_headers_buffer[end - 1] = GTPU_EXTENSION_HEADER_NONE;
+        self.extension_headers_len = end as u16;
+        Ok(())
+    }
+
+    ///Appends a "PDU Session Container" extension header to the end of the extension header chain.
+    pub fn add_pdu_session_container(&mut self, container: &GtpuPduSessionContainer) -> Result<(), ValueError> {
+        container.check_ranges()?;
+        self.add_extension_header(
+            GTPU_EXTENSION_HEADER_PDU_SESSION_CONTAINER,
+            &container.to_bytes()
+        )
+    }
+
+    ///Removes all extension headers.
+    pub fn clear_extension_headers(&mut self) {
+        self.next_extension_header_type = GTPU_EXTENSION_HEADER_NONE;
+        self.extension_headers_len = 0;
+    }
+
+    ///Sets the length field based on the length of the payload (encapsulated packet) in bytes.
+    ///
+    ///The length of the optional fields & extension headers is automatically added.
+    pub fn set_payload_length(&mut self, size: usize) -> Result<(), ValueError> {
+        //check that the total length fits into the field
+        const MAX_LENGTH: usize = u16::MAX as usize;
+        let length = self.header_len() - GtpuHeader::SERIALIZED_SIZE + size;
+        if MAX_LENGTH < length {
+            return Err(ValueError::GtpuPayloadLengthTooLarge(size));
+        }
+
+        self.length = length as u16;
+        Ok(())
+    }
+
+    ///Reads a gtpu header from a slice and returns a tuple containing the resulting header & unused part of the slice.
+    pub fn read_from_slice(slice: &[u8]) -> Result<(GtpuHeader, &[u8]), ReadError> {
+        let header = GtpuHeaderSlice::from_slice(slice)?.to_header();
+        let rest = &slice[header.header_len()..];
+        Ok((
+            header,
+            rest
+        ))
+    }
+
+    ///Reads a gtpu header from the current position.
+    pub fn read<T: io::Read + io::Seek + Sized>(reader: &mut T) -> Result<GtpuHeader, ReadError> {
+        use crate::ReadError::*;
+
+        let flags = reader.read_u8()?;
+        let version = flags >> 5;
+        if 1 != version {
+            return Err(GtpuUnexpectedVersion(version));
+        }
+        let message_type = reader.read_u8()?;
+        let length = reader.read_u16::<BigEndian>()?;
+        let teid = reader.read_u32::<BigEndian>()?;
+
+        let mut result = GtpuHeader {
+            version,
+            protocol_type: 0 != flags & 0x10,
+            message_type,
+            length,
+            teid,
+            sequence_number: None,
+            n_pdu_number: None,
+            next_extension_header_type: GTPU_EXTENSION_HEADER_NONE,
+            extension_headers_len: 0,
+            extension_headers_buffer: [0;GTPU_MAX_EXTENSION_HEADERS_LENGTH]
+        };
+
+        //optional fields (present if any of the flags is set)
+        if 0 != flags & (FLAG_EXTENSION_HEADER | FLAG_SEQUENCE_NUMBER | FLAG_N_PDU_NUMBER) {
+            let sequence_number = reader.read_u16::<BigEndian>()?;
+            if 0 != flags & FLAG_SEQUENCE_NUMBER {
+                result.sequence_number = Some(sequence_number);
+            }
+            let n_pdu_number = reader.read_u8()?;
+            if 0 != flags & FLAG_N_PDU_NUMBER {
+                result.n_pdu_number = Some(n_pdu_number);
+            }
+            let next_extension_header_type = reader.read_u8()?;
+            if 0 != flags & FLAG_EXTENSION_HEADER {
+                result.next_extension_header_type = next_extension_header_type;
+            }
+        }
+
+        //extension headers
+        let mut next = result.next_extension_header_type;
+        let mut len = 0;
+        while GTPU_EXTENSION_HEADER_NONE != next {
+            let ext_len = usize::from(reader.read_u8()?)*4;
+            if 0 == ext_len {
+                return Err(GtpuExtensionHeaderLengthZero);
+            }
+            if GTPU_MAX_EXTENSION_HEADERS_LENGTH - len < ext_len {
+                return Err(GtpuExtensionHeadersTooLarge(len + ext_len));
+            }
+            result.extension_headers_buffer[len] = (ext_len/4) as u8;
+            reader.read_exact(&mut result.extension_headers_buffer[len + 1..len + ext_len])?;
+            len += ext_len;
+            next = result.extension_headers_buffer[len - 1];
+        }
+        result.extension_headers_len = len as u16;
+
+        Ok(result)
+    }
+
+    ///Checks if the values in this header are valid values for a gtpu header.
+    ///
+    ///Specifically it will be checked, that the version is not greater then 0x7.
+    pub fn check_ranges(&self) -> Result<(), ValueError> {
+        use crate::ErrorField::*;
+        max_check_u8(self.version, 0x7, GtpuVersion)?;
+        Ok(())
+    }
+
+    ///Writes the gtpu header (including the optional fields & extension headers) to the current position.
+    pub fn write<T: io::Write + Sized>(&self, writer: &mut T) -> Result<(), WriteError> {
+        self.check_ranges()?;
+
+        writer.write_u8({
+            let mut value = self.version << 5;
+            if self.protocol_type {
+                value |= 0x10;
+            }
+            if 0 != self.extension_headers_len {
+                value |= FLAG_EXTENSION_HEADER;
+            }
+            if self.sequence_number.is_some() {
+                value |= FLAG_SEQUENCE_NUMBER;
+            }
+            if self.n_pdu_number.is_some() {
+                value |= FLAG_N_PDU_NUMBER;
+            }
+            value
+        })?;
+        writer.write_u8(self.message_type)?;
+        writer.write_u16::<BigEndian>(self.length)?;
+        writer.write_u32::<BigEndian>(self.teid)?;
+
+        if self.has_optional_fields() {
+            writer.write_u16::<BigEndian>(self.sequence_number.unwrap_or(0))?;
+            writer.write_u8(self.n_pdu_number.unwrap_or(0))?;
+            writer.write_u8(self.next_extension_header_type)?;
+            writer.write_all(self.extension_headers())?;
+        }
+        Ok(())
+    }
+}
+
+//NOTE: Same as for the Ipv4Header, Default, Debug & PartialEq have to be implemented by hand
+//      as the traits are not availible for the extension headers buffer array.
+
+impl Default for GtpuHeader {
+    fn default() -> GtpuHeader {
+        GtpuHeader::new(0)
+    }
+}
+
+impl Debug for GtpuHeader {
+    fn fmt(&self, fotmatter: &mut Formatter) -> Result<(), std::fmt::Error> {
+        write!(fotmatter, "GtpuHeader {{ version: {}, protocol_type: {}, message_type: {}, length: {}, teid: {}, sequence_number: {:?}, n_pdu_number: {:?}, next_extension_header_type: {}, extension_headers: {:?} }}",
+            self.version,
+            self.protocol_type,
+            self.message_type,
+            self.length,
+            self.teid,
+            self.sequence_number,
+            self.n_pdu_number,
+            self.next_extension_header_type,
+            self.extension_headers())
+    }
+}
+
+impl std::cmp::PartialEq for GtpuHeader {
+    fn eq(&self, other: &GtpuHeader) -> bool {
+        self.version == other.version &&
+        self.protocol_type == other.protocol_type &&
+        self.message_type == other.message_type &&
+        self.length == other.length &&
+        self.teid == other.teid &&
+        self.sequence_number == other.sequence_number &&
+        self.n_pdu_number == other.n_pdu_number &&
+        self.next_extension_header_type == other.next_extension_header_type &&
+        self.extension_headers() == other.extension_headers()
+    }
+}
+
+impl std::cmp::Eq for GtpuHeader {}
+
+///A slice containing a gtpu header (including optional fields & extension headers) of a network package.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GtpuHeaderSlice<'a> {
+    slice: &'a [u8]
+}
+
+impl<'a> GtpuHeaderSlice<'a> {
+
+    ///Creates a slice containing a gtpu header (including optional fields & extension headers).
+    pub fn from_slice(slice: &'a[u8]) -> Result<GtpuHeaderSlice<'a>, ReadError> {
+        //check length
+        use crate::ReadError::*;
+        if slice.len() < GtpuHeader::SERIALIZED_SIZE {
+            return Err(UnexpectedEndOfSlice(GtpuHeader::SERIALIZED_SIZE));
+        }
+
+        //check version
+        let version = slice[0] >> 5;
+        if 1 != version {
+            return Err(GtpuUnexpectedVersion(version));
+        }
+
+        //check that the optional fields are present
+        let flags = slice[0];
+        if 0 == flags & (FLAG_EXTENSION_HEADER | FLAG_SEQUENCE_NUMBER | FLAG_N_PDU_NUMBER) {
+            return Ok(GtpuHeaderSlice{
+                slice: &slice[..GtpuHeader::SERIALIZED_SIZE]
+            });
+        }
+        let optional_end = GtpuHeader::SERIALIZED_SIZE + GtpuHeader::OPTIONAL_FIELDS_LEN;
+        if slice.len() < optional_end {
+            return Err(UnexpectedEndOfSlice(optional_end));
+        }
+
+        //determine the length of the extension headers
+        let mut header_len = optional_end;
+        if 0 != flags & FLAG_EXTENSION_HEADER {
+            let mut next = slice[optional_end - 1];
+            while GTPU_EXTENSION_HEADER_NONE != next {
+                if slice.len() < header_len + 1 {
+                    return Err(UnexpectedEndOfSlice(header_len + 1));
+                }
+                let ext_len = usize::from(slice[header_len])*4;
+                if 0 == ext_len {
+                    return Err(GtpuExtensionHeaderLengthZero);
+                }
+                let end = header_len + ext_len;
+                if GTPU_MAX_EXTENSION_HEADERS_LENGTH < end - optional_end {
+                    return Err(GtpuExtensionHeadersTooLarge(end - optional_end));
+                }
+                if slice.len() < end {
+                    return Err(UnexpectedEndOfSlice(end));
+                }
+                next = slice[end - 1];
+                header_len = end;
+            }
+        }
+
+        //done
+        Ok(GtpuHeaderSlice{
+            slice: &slice[..header_len]
+        })
+    }
+
+    ///Returns the slice containing the gtpu header
+    #[inline]
+    pub fn slice(&self) -> &'a [u8] {
+        self.slice
+    }
+
+    ///Read the 3 bit "version" field from the slice.
+    pub fn version(&self) -> u8 {
+        self.slice[0] >> 5
+    }
+
+    ///Read the "PT" flag from the slice (set for GTP & not set for GTP').
+    pub fn protocol_type(&self) -> bool {
+        0 != self.slice[0] & 0x10
+    }
+
+    ///Read the "E" flag from the slice (set if extension headers are present).
+    pub fn extension_header_flag(&self) -> bool {
+        0 != self.slice[0] & FLAG_EXTENSION_HEADER
+    }
+
+    ///Read the "S" flag from the slice (set if the sequence number is present).
+    pub fn sequence_number_flag(&self) -> bool {
+        0 != self.slice[0] & FLAG_SEQUENCE_NUMBER
+    }
+
+    ///Read the "PN" flag from the slice (set if the n-pdu number is present).
+    pub fn n_pdu_number_flag(&self) -> bool {
+        0 != self.slice[0] & FLAG_N_PDU_NUMBER
+    }
+
+    ///Read the "message type" field from the slice.
+    pub fn message_type(&self) -> u8 {
+        self.slice[1]
+    }
+
+    ///Read the "length" field from the slice (length of everything following the first 8 bytes in bytes).
+    pub fn length(&self) -> u16 {
+        BigEndian::read_u16(&self.slice[2..4])
+    }
+
+    ///Read the "tunnel endpoint identifier" field from the slice.
+    pub fn teid(&self) -> u32 {
+        BigEndian::read_u32(&self.slice[4..8])
+    }
+
+    ///Read the "sequence number" field from the slice (None if the "S" flag is not set).
+    pub fn sequence_number(&self) -> Option<u16> {
+        if self.sequence_number_flag() {
+            Some(BigEndian::read_u16(&self.slice[8..10]))
+        } else {
+            None
+        }
+    }
+
+    ///Read the "N-PDU number" field from the slice (None if the "PN" flag is not set).
+    pub fn n_pdu_number(&self) -> Option<u8> {
+        if self.n_pdu_number_flag() {
+            Some(self.slice[10])
+        } else {
+            None
+        }
+    }
+
+    ///Read the "next extension header type" field from the slice (`GTPU_EXTENSION_HEADER_NONE` if the "E" flag is not set).
+    pub fn next_extension_header_type(&self) -> u8 {
+        if self.extension_header_flag() {
+            self.slice[11]
+        } else {
+            GTPU_EXTENSION_HEADER_NONE
+        }
+    }
+
+    ///Returns a slice containing the encoded extension headers (empty if no extension headers are present).
+    pub fn extension_headers(&self) -> &'a [u8] {
+        if self.slice.len() > GtpuHeader::SERIALIZED_SIZE {
+            &self.slice[GtpuHeader::SERIALIZED_SIZE + GtpuHeader::OPTIONAL_FIELDS_LEN..]
+        } else {
+            &self.slice[GtpuHeader::SERIALIZED_SIZE..]
+        }
+    }
+
+    ///Returns an iterator that allows to iterate through all the extension headers of the header.
+    pub fn extension_headers_iterator(&self) -> GtpuExtensionHeadersIterator<'a> {
+        GtpuExtensionHeadersIterator::from_slice(self.next_extension_header_type(), self.extension_headers())
+    }
+
+    ///Decode all the fields and copy the results to a GtpuHeader struct
+    pub fn to_header(&self) -> GtpuHeader {
+        let extension_headers = self.extension_headers();
+        GtpuHeader {
+            version: self.version(),
+            protocol_type: self.protocol_type(),
+            message_type: self.message_type(),
+            length: self.length(),
+            teid: self.teid(),
+            sequence_number: self.sequence_number(),
+            n_pdu_number: self.n_pdu_number(),
+            next_extension_header_type: self.next_extension_header_type(),
+            extension_headers_len: extension_headers.len() as u16,
+            extension_headers_buffer: {
+                let mut result: [u8;GTPU_MAX_EXTENSION_HEADERS_LENGTH] = [0;GTPU_MAX_EXTENSION_HEADERS_LENGTH];
+                result[..extension_headers.len()].copy_from_slice(extension_headers);
+                result
+            }
+        }
+    }
+}
+
+///A slice containing a single gtpu extension header.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GtpuExtensionHeaderSlice<'a> {
+    extension_type: u8,
+    slice: &'a [u8]
+}
+
+impl<'a> GtpuExtensionHeaderSlice<'a> {
+
+    ///Creates a slice containing a gtpu extension header of the given type (the type is stored in the previous header).
+    pub fn from_slice(extension_type: u8, slice: &'a[u8]) -> Result<GtpuExtensionHeaderSlice<'a>, ReadError> {
+        //check length
+        use crate::ReadError::*;
+        if slice.is_empty() {
+            return Err(UnexpectedEndOfSlice(1));
+        }
+        let len = usize::from(slice[0])*4;
+        if 0 == len {
+            return Err(GtpuExtensionHeaderLengthZero);
+        }
+        if slice.len() < len {
+            return Err(UnexpectedEndOfSlice(len));
+        }
+
+        //done
+        Ok(GtpuExtensionHeaderSlice{
+            extension_type,
+            slice: &slice[..len]
+        })
+    }
+
+    ///Returns the slice containing the extension header
+    #[inline]
+    pub fn slice(&self) -> &'a [u8] {
+        self.slice
+    }
+
+    ///Returns the type of the extension header.
+    pub fn extension_type(&self) -> u8 {
+        self.extension_type
+    }
+
+    ///Read the "length" field (length of the extension header in multiples of 4 bytes).
+    pub fn length(&self) -> u8 {
+        self.slice[0]
+    }
+
+    ///Returns the content of the extension header (without the length & next extension header type field).
+    pub fn content(&self) -> &'a [u8] {
+        &self.slice[1..self.slice.len() - 1]
+    }
+
+    ///Read the "next extension header type" field (`GTPU_EXTENSION_HEADER_NONE` if this is the last extension header).
+    pub fn next_extension_header_type(&self) -> u8 {
+        self.slice[self.slice.len() - 1]
+    }
+
+    ///Decodes the extension header as a "PDU Session Container" (None if the extension header has a different type).
+    pub fn pdu_session_container(&self) -> Option<GtpuPduSessionContainer> {
+        if GTPU_EXTENSION_HEADER_PDU_SESSION_CONTAINER == self.extension_type {
+            //the length field is at least 1, so the content contains at least 2 bytes
+            Some(GtpuPduSessionContainer::from_bytes(self.content()))
+        } else {
+            None
+        }
+    }
+}
+
+///Allows iterating over the extension headers of a gtpu header.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GtpuExtensionHeadersIterator<'a> {
+    next_extension_header_type: u8,
+    extension_headers: &'a [u8]
+}
+
+impl<'a> GtpuExtensionHeadersIterator<'a> {
+    ///Creates an extension headers iterator from the type of the first extension header & a slice containing encoded extension headers.
+    pub fn from_slice(next_extension_header_type: u8, extension_headers: &'a [u8]) -> GtpuExtensionHeadersIterator<'a> {
+        GtpuExtensionHeadersIterator{ next_extension_header_type, extension_headers }
+    }
+
+    ///Returns the non processed part of the extension headers slice.
+    pub fn rest(&self) -> &'a [u8] {
+        self.extension_headers
+    }
+}
+
+impl<'a> Iterator for GtpuExtensionHeadersIterator<'a> {
+    type Item = Result<GtpuExtensionHeaderSlice<'a>, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if GTPU_EXTENSION_HEADER_NONE == self.next_extension_header_type {
+            None
+        } else {
+            match GtpuExtensionHeaderSlice::from_slice(self.next_extension_header_type, self.extension_headers) {
+                Ok(value) => {
+                    self.next_extension_header_type = value.next_extension_header_type();
+                    self.extension_headers = &self.extension_headers[value.slice().len()..];
+                    Some(Ok(value))
+                },
+                Err(err) => {
+                    //stop the iteration so no further elements are returned
+                    self.next_extension_header_type = GTPU_EXTENSION_HEADER_NONE;
+                    Some(Err(err))
+                }
+            }
+        }
+    }
+}
+
+///Content of a "PDU Session Container" extension header according to 3GPP TS 38.415 (only the first two bytes are decoded).
+#[derive(Clone, Debug, Eq, PartialEq, Default)]
+pub struct GtpuPduSessionContainer {
+    ///4 bit PDU type (`GtpuPduSessionContainer::DL_PDU_SESSION_INFORMATION` or `GtpuPduSessionContainer::UL_PDU_SESSION_INFORMATION`).
+    pub pdu_type: u8,
+    ///6 bit QoS flow identifier (QFI).
+    pub qos_flow_identifier: u8,
+    ///"PPP" flag (paging policy presence), only defined for downlink PDUs.
+    pub paging_policy_presence: bool,
+    ///"RQI" flag (reflective QoS indicator), only defined for downlink PDUs.
+    pub reflective_qos_indicator: bool
+}
+
+impl GtpuPduSessionContainer {
+    ///PDU type of a downlink "PDU Session Information".
+    pub const DL_PDU_SESSION_INFORMATION: u8 = 0;
+    ///PDU type of an uplink "PDU Session Information".
+    pub const UL_PDU_SESSION_INFORMATION: u8 = 1;
+
+    ///Decodes the first two bytes of the content of a "PDU Session Container" extension header.
+    ///
+    ///The given slice must at least have the length of 2.
+    pub fn from_bytes(content: &[u8]) -> GtpuPduSessionContainer {
+        GtpuPduSessionContainer {
+            pdu_type: content[0] >> 4,
+            qos_flow_identifier: content[1] & 0x3f,
+            paging_policy_presence: 0 != content[1] & 0x80,
+            reflective_qos_indicator: 0 != content[1] & 0x40
+        }
+    }
+
+    ///Checks if the values are in the allowed ranges.
+    ///
+    ///Specifically it will be checked, that:
+    /// * pdu_type is not greater then 0xf
+    /// * qos_flow_identifier is not greater then 0x3f
+    pub fn check_ranges(&self) -> Result<(), ValueError> {
+        use crate::ErrorField::*;
+        max_check_u8(self.pdu_type, 0xf, GtpuPduType)?;
+        max_check_u8(self.qos_flow_identifier, 0x3f, GtpuQosFlowIdentifier)?;
+        Ok(())
+    }
+
+    ///Encodes the values as the content of a "PDU Session Container" extension header (spare bits are set to zero).
+    pub fn to_bytes(&self) -> [u8;2] {
+        [
+            self.pdu_type << 4,
+            {
+                let mut value = self.qos_flow_identifier;
+                if self.paging_policy_presence {
+                    value |= 0x80;
+                }
+                if self.reflective_qos_indicator {
+                    value |= 0x40;
+                }
+                value
+            }
+        ]
+    }
+}
@@ -0,0 +1,530 @@
+use super::super::*;
+
+extern crate byteorder;
+use self::byteorder::{ByteOrder, BigEndian};
+
+///Udp port used by L2TPv2 & L2TPv3 (when encapsulated in udp).
+pub const L2TP_PORT: u16 = 1701;
+
+///Size of the header of an L2TP attribute value pair (flags & length, vendor id & attribute type) in bytes.
+pub const L2TP_AVP_HEADER_LENGTH: usize = 6;
+
+///"Type" flag (T) identifying a control message.
+const L2TP_FLAG_TYPE: u8 = 0b1000_0000;
+///"Length" flag (L) indicating the presence of the length field.
+const L2TP_FLAG_LENGTH: u8 = 0b0100_0000;
+///"Sequence" flag (S) indicating the presence of the Ns & Nr fields.
+const L2TP_FLAG_SEQUENCE: u8 = 0b0000_1000;
+///"Offset" flag (O) indicating the presence of the offset size field (L2TPv2 only).
+const L2TP_FLAG_OFFSET: u8 = 0b0000_0010;
+///"Priority" flag (P) indicating a data message that should receive preferential treatment (L2TPv2 only).
+const L2TP_FLAG_PRIORITY: u8 = 0b0000_0001;
+
+///A slice containing an L2TPv2 header (rfc2661).
+///
+///For control messages the slice additionally contains the attribute value pairs of the message
+///(the data up to the length given in the header). For data messages the slice only contains the
+///header (including the offset padding) and the PPP frame follows the slice.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct L2tpv2HeaderSlice<'a> {
+    slice: &'a [u8],
+    header_len: usize
+}
+
+impl<'a> L2tpv2HeaderSlice<'a> {
+
+    ///Minimum size of the L2TPv2 header (flags & version, tunnel id & session id) in bytes.
+    pub const MIN_LEN: usize = 6;
+
+    ///Creates a slice containing an L2TPv2 header (and the attribute value pairs in case of a control message).
+    pub fn from_slice(slice: &'a[u8]) -> Result<L2tpv2HeaderSlice<'a>, ReadError> {
+        //check length
+        use crate::ReadError::*;
+        if slice.len() < L2tpv2HeaderSlice::MIN_LEN {
+            return Err(UnexpectedEndOfSlice(L2tpv2HeaderSlice::MIN_LEN));
+        }
+
+        //check version
+        let version = slice[1] & 0xf;
+        if 2 != version {
+            return Err(L2tpUnexpectedVersion(version));
+        }
+
+        //determine the header length based on the flags
+        let flags = slice[0];
+        let mut header_len = L2tpv2HeaderSlice::MIN_LEN;
+        if 0 != flags & L2TP_FLAG_LENGTH {
+            header_len += 2;
+        }
+        if 0 != flags & L2TP_FLAG_SEQUENCE {
+            header_len += 4;
+        }
+        if 0 != flags & L2TP_FLAG_OFFSET {
+            header_len += 2;
+            if slice.len() < header_len {
+                return Err(UnexpectedEndOfSlice(header_len));
+            }
+            //add the offset padding
+            header_len += usize::from(BigEndian::read_u16(&slice[header_len - 2..header_len]));
+        }
+        if slice.len() < header_len {
+            return Err(UnexpectedEndOfSlice(header_len));
+        }
+
+        //determine the message length (only relevant for control messages)
+        let len = if 0 != flags & L2TP_FLAG_TYPE {
+            if 0 != flags & L2TP_FLAG_LENGTH {
+                let length = BigEndian::read_u16(&slice[2..4]);
+                if usize::from(length) < header_len {
+                    return Err(L2tpLengthTooSmall(length));
+                }
+                if slice.len() < usize::from(length) {
+                    return Err(UnexpectedEndOfSlice(usize::from(length)));
+                }
+                usize::from(length)
+            } else {
+                //without a length field the message extends to the end of the slice
+                slice.len()
+            }
+        } else {
+            header_len
+        };
+
+        //done
+        Ok(L2tpv2HeaderSlice{
+            slice: &slice[..len],
+            header_len
+        })
+    }
+
+    ///Returns the slice containing the L2TPv2 header (and the attribute value pairs in case of a control message).
+    #[inline]
+    pub fn slice(&self) -> &'a [u8] {
+        self.slice
+    }
+
+    ///Returns the length of the header (including the offset padding) in bytes.
+    #[inline]
+    pub fn header_len(&self) -> usize {
+        self.header_len
+    }
+
+    ///Returns true if the message is a control message ("type" flag is set) and false if it is a data message.
+    pub fn is_control(&self) -> bool {
+        0 != self.slice[0] & L2TP_FLAG_TYPE
+    }
+
+    ///Returns true if the "length" flag is set (length field present).
+    pub fn length_flag(&self) -> bool {
+        0 != self.slice[0] & L2TP_FLAG_LENGTH
+    }
+
+    ///Returns true if the "sequence" flag is set (Ns & Nr fields present).
+    pub fn sequence_flag(&self) -> bool {
+        0 != self.slice[0] & L2TP_FLAG_SEQUENCE
+    }
+
+    ///Returns true if the "offset" flag is set (offset size field present).
+    pub fn offset_flag(&self) -> bool {
+        0 != self.slice[0] & L2TP_FLAG_OFFSET
+    }
+
+    ///Returns true if the "priority" flag is set.
+    pub fn priority_flag(&self) -> bool {
+        0 != self.slice[0] & L2TP_FLAG_PRIORITY
+    }
+
+    ///Read the "version" field (always 2).
+    pub fn version(&self) -> u8 {
+        self.slice[1] & 0xf
+    }
+
+    ///Read the "length" field (total length of the message in bytes) if present.
+    pub fn length(&self) -> Option<u16> {
+        if self.length_flag() {
+            Some(BigEndian::read_u16(&self.slice[2..4]))
+        } else {
+            None
+        }
+    }
+
+    ///Offset of the tunnel id field (depends on the presence of the length field).
+    fn tunnel_id_offset(&self) -> usize {
+        if self.length_flag() {
+            4
+        } else {
+            2
+        }
+    }
+
+    ///Read the "tunnel id" field.
+    pub fn tunnel_id(&self) -> u16 {
+        let offset = self.tunnel_id_offset();
+        BigEndian::read_u16(&self.slice[offset..offset + 2])
+    }
+
+    ///Read the "session id" field.
+    pub fn session_id(&self) -> u16 {
+        let offset = self.tunnel_id_offset() + 2;
+        BigEndian::read_u16(&self.slice[offset..offset + 2])
+    }
+
+    ///Read the "Ns" field (sequence number of the message) if present.
+    pub fn ns(&self) -> Option<u16> {
+        if self.sequence_flag() {
+            let offset = self.tunnel_id_offset() + 4;
+            Some(BigEndian::read_u16(&self.slice[offset..offset + 2]))
+        } else {
+            None
+        }
+    }
+
+    ///Read the "Nr" field (next expected sequence number) if present.
+    pub fn nr(&self) -> Option<u16> {
+        if self.sequence_flag() {
+            let offset = self.tunnel_id_offset() + 6;
+            Some(BigEndian::read_u16(&self.slice[offset..offset + 2]))
+        } else {
+            None
+        }
+    }
+
+    ///Read the "offset size" field (number of padding bytes after the header) if present.
+    pub fn offset_size(&self) -> Option<u16> {
+        if self.offset_flag() {
+            let offset = self.tunnel_id_offset() + 4 + if self.sequence_flag() { 4 } else { 0 };
+            Some(BigEndian::read_u16(&self.slice[offset..offset + 2]))
+        } else {
+            None
+        }
+    }
+
+    ///Returns the slice containing the attribute value pairs of a control message (empty for data messages).
+    pub fn avps(&self) -> &'a [u8] {
+        &self.slice[self.header_len..]
+    }
+
+    ///Returns an iterator over the attribute value pairs of a control message.
+    pub fn avps_iterator(&self) -> L2tpAvpsIterator<'a> {
+        L2tpAvpsIterator::from_slice(self.avps())
+    }
+}
+
+///A slice containing an L2TPv3 control message header (rfc3931) and the attribute value pairs of the message.
+///
+///When transported directly over ip (protocol number 115) the slice additionally contains the leading
+///zero session id that identifies control messages.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct L2tpv3ControlHeaderSlice<'a> {
+    slice: &'a [u8],
+    header_offset: usize
+}
+
+impl<'a> L2tpv3ControlHeaderSlice<'a> {
+
+    ///Size of the L2TPv3 control message header (without the session id when transported over ip) in bytes.
+    pub const HEADER_LEN: usize = 12;
+
+    ///Creates a slice containing an L2TPv3 control message transported via udp.
+    pub fn from_slice(slice: &'a[u8]) -> Result<L2tpv3ControlHeaderSlice<'a>, ReadError> {
+        //check length
+        use crate::ReadError::*;
+        if slice.len() < L2tpv3ControlHeaderSlice::HEADER_LEN {
+            return Err(UnexpectedEndOfSlice(L2tpv3ControlHeaderSlice::HEADER_LEN));
+        }
+
+        //check version
+        let version = slice[1] & 0xf;
+        if 3 != version {
+            return Err(L2tpUnexpectedVersion(version));
+        }
+
+        //check the message length
+        let length = BigEndian::read_u16(&slice[2..4]);
+        if usize::from(length) < L2tpv3ControlHeaderSlice::HEADER_LEN {
+            return Err(L2tpLengthTooSmall(length));
+        }
+        if slice.len() < usize::from(length) {
+            return Err(UnexpectedEndOfSlice(usize::from(length)));
+        }
+
+        //done
+        Ok(L2tpv3ControlHeaderSlice{
+            slice: &slice[..usize::from(length)],
+            header_offset: 0
+        })
+    }
+
+    ///Creates a slice containing an L2TPv3 control message transported directly over ip (starting with the zero session id).
+    pub fn from_ip_slice(slice: &'a[u8]) -> Result<L2tpv3ControlHeaderSlice<'a>, ReadError> {
+        use crate::ReadError::*;
+        if slice.len() < 4 {
+            return Err(UnexpectedEndOfSlice(4));
+        }
+        let result = L2tpv3ControlHeaderSlice::from_slice(&slice[4..])
+                     .map_err(|err| err.add_slice_offset(4))?;
+        Ok(L2tpv3ControlHeaderSlice{
+            slice: &slice[..4 + result.slice.len()],
+            header_offset: 4
+        })
+    }
+
+    ///Returns the slice containing the L2TPv3 control message (including the session id when transported over ip).
+    #[inline]
+    pub fn slice(&self) -> &'a [u8] {
+        self.slice
+    }
+
+    ///Returns true if the message was transported directly over ip (slice starts with the zero session id).
+    pub fn is_over_ip(&self) -> bool {
+        0 != self.header_offset
+    }
+
+    ///Returns true if the "length" flag is set (must always be set for control messages).
+    pub fn length_flag(&self) -> bool {
+        0 != self.slice[self.header_offset] & L2TP_FLAG_LENGTH
+    }
+
+    ///Returns true if the "sequence" flag is set (must always be set for control messages).
+    pub fn sequence_flag(&self) -> bool {
+        0 != self.slice[self.header_offset] & L2TP_FLAG_SEQUENCE
+    }
+
+    ///Read the "version" field (always 3).
+    pub fn version(&self) -> u8 {
+        self.slice[self.header_offset + 1] & 0xf
+    }
+
+    ///Read the "length" field (length of the control message, starting at the flags, in bytes).
+    pub fn length(&self) -> u16 {
+        let offset = self.header_offset + 2;
+        BigEndian::read_u16(&self.slice[offset..offset + 2])
+    }
+
+    ///Read the "control connection id" field.
+    pub fn control_connection_id(&self) -> u32 {
+        let offset = self.header_offset + 4;
+        BigEndian::read_u32(&self.slice[offset..offset + 4])
+    }
+
+    ///Read the "Ns" field (sequence number of the message).
+    pub fn ns(&self) -> u16 {
+        let offset = self.header_offset + 8;
+        BigEndian::read_u16(&self.slice[offset..offset + 2])
+    }
+
+    ///Read the "Nr" field (next expected sequence number).
+    pub fn nr(&self) -> u16 {
+        let offset = self.header_offset + 10;
+        BigEndian::read_u16(&self.slice[offset..offset + 2])
+    }
+
+    ///Returns the slice containing the attribute value pairs of the control message.
+    pub fn avps(&self) -> &'a [u8] {
+        &self.slice[self.header_offset + L2tpv3ControlHeaderSlice::HEADER_LEN..]
+    }
+
+    ///Returns an iterator over the attribute value pairs of the control message.
+    pub fn avps_iterator(&self) -> L2tpAvpsIterator<'a> {
+        L2tpAvpsIterator::from_slice(self.avps())
+    }
+}
+
+///A slice containing an L2TPv3 data message session header (rfc3931).
+///
+///Transported via udp the session header consists of the flags & version, a reserved field and the session id.
+///Transported directly over ip (protocol number 115) the header only consists of the (non zero) session id.
+///
+///The session header is followed by the optional cookie, the optional L2-specific sublayer and the
+///tunneled frame. As the cookie length, the use of the sublayer and the pseudowire type are configured
+///out of band via the control connection, the data following the session header is only decoded if
+///an `L2tpv3SessionFormat` is given (see `SlicedPacket::from_ethernet_tunneled_l2tpv3`).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct L2tpv3SessionHeaderSlice<'a> {
+    slice: &'a [u8]
+}
+
+impl<'a> L2tpv3SessionHeaderSlice<'a> {
+
+    ///Size of the session header when transported via udp in bytes.
+    pub const UDP_HEADER_LEN: usize = 8;
+
+    ///Size of the session header when transported directly over ip in bytes.
+    pub const IP_HEADER_LEN: usize = 4;
+
+    ///Creates a slice containing an L2TPv3 session header transported via udp.
+    pub fn from_slice(slice: &'a[u8]) -> Result<L2tpv3SessionHeaderSlice<'a>, ReadError> {
+        //check length
+        use crate::ReadError::*;
+        if slice.len() < L2tpv3SessionHeaderSlice::UDP_HEADER_LEN {
+            return Err(UnexpectedEndOfSlice(L2tpv3SessionHeaderSlice::UDP_HEADER_LEN));
+        }
+
+        //check version
+        let version = slice[1] & 0xf;
+        if 3 != version {
+            return Err(L2tpUnexpectedVersion(version));
+        }
+
+        //done
+        Ok(L2tpv3SessionHeaderSlice{
+            slice: &slice[..L2tpv3SessionHeaderSlice::UDP_HEADER_LEN]
+        })
+    }
+
+    ///Creates a slice containing an L2TPv3 session header transported directly over ip.
+    pub fn from_ip_slice(slice: &'a[u8]) -> Result<L2tpv3SessionHeaderSlice<'a>, ReadError> {
+        //check length
+        use crate::ReadError::*;
+        if slice.len() < L2tpv3SessionHeaderSlice::IP_HEADER_LEN {
+            return Err(UnexpectedEndOfSlice(L2tpv3SessionHeaderSlice::IP_HEADER_LEN));
+        }
+
+        //done
+        Ok(L2tpv3SessionHeaderSlice{
+            slice: &slice[..L2tpv3SessionHeaderSlice::IP_HEADER_LEN]
+        })
+    }
+
+    ///Returns the slice containing the session header.
+    #[inline]
+    pub fn slice(&self) -> &'a [u8] {
+        self.slice
+    }
+
+    ///Returns true if the message was transported directly over ip.
+    pub fn is_over_ip(&self) -> bool {
+        L2tpv3SessionHeaderSlice::IP_HEADER_LEN == self.slice.len()
+    }
+
+    ///Read the "session id" field.
+    pub fn session_id(&self) -> u32 {
+        BigEndian::read_u32(&self.slice[self.slice.len() - 4..])
+    }
+}
+
+///Pseudowire types of L2TPv3 sessions that can be decoded (rfc4719 & rfc4591).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum L2tpv3PseudowireType {
+    ///Ethernet II frames (ethernet pseudowire).
+    Ethernet,
+    ///PPP frames (PPP pseudowire).
+    Ppp,
+}
+
+///Format of the data following the L2TPv3 session headers of a session (negotiated via the control connection).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct L2tpv3SessionFormat {
+    ///Length of the cookie following the session header in bytes (0, 4 or 8).
+    pub cookie_len: usize,
+    ///Length of the L2-specific sublayer following the cookie in bytes (0 if no sublayer is used, 4 for the default sublayer).
+    pub sublayer_len: usize,
+    ///Type of the tunneled frames.
+    pub pseudowire_type: L2tpv3PseudowireType,
+}
+
+///A slice containing an L2TP attribute value pair (AVP) of a control message.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct L2tpAvpSlice<'a> {
+    slice: &'a [u8]
+}
+
+impl<'a> L2tpAvpSlice<'a> {
+
+    ///Creates a slice containing an attribute value pair.
+    pub fn from_slice(slice: &'a[u8]) -> Result<L2tpAvpSlice<'a>, ReadError> {
+        //check length
+        use crate::ReadError::*;
+        if slice.len() < L2TP_AVP_HEADER_LENGTH {
+            return Err(UnexpectedEndOfSlice(L2TP_AVP_HEADER_LENGTH));
+        }
+        let length = BigEndian::read_u16(&slice[..2]) & 0x3ff;
+        if usize::from(length) < L2TP_AVP_HEADER_LENGTH {
+            return Err(L2tpAvpLengthTooSmall(length));
+        }
+        if slice.len() < usize::from(length) {
+            return Err(UnexpectedEndOfSlice(usize::from(length)));
+        }
+
+        //done
+        Ok(L2tpAvpSlice{
+            slice: &slice[..usize::from(length)]
+        })
+    }
+
+    ///Returns the slice containing the attribute value pair.
+    #[inline]
+    pub fn slice(&self) -> &'a [u8] {
+        self.slice
+    }
+
+    ///Returns true if the "mandatory" flag (M) is set.
+    pub fn mandatory(&self) -> bool {
+        0 != self.slice[0] & 0b1000_0000
+    }
+
+    ///Returns true if the "hidden" flag (H) is set (value is encrypted).
+    pub fn hidden(&self) -> bool {
+        0 != self.slice[0] & 0b0100_0000
+    }
+
+    ///Read the "length" field (length of the attribute value pair including the header in bytes).
+    pub fn length(&self) -> u16 {
+        BigEndian::read_u16(&self.slice[..2]) & 0x3ff
+    }
+
+    ///Read the "vendor id" field (0 for IETF defined attributes).
+    pub fn vendor_id(&self) -> u16 {
+        BigEndian::read_u16(&self.slice[2..4])
+    }
+
+    ///Read the "attribute type" field.
+    pub fn attribute_type(&self) -> u16 {
+        BigEndian::read_u16(&self.slice[4..6])
+    }
+
+    ///Returns the slice containing the attribute value.
+    pub fn value(&self) -> &'a [u8] {
+        &self.slice[L2TP_AVP_HEADER_LENGTH..]
+    }
+}
+
+///Allows iterating over the attribute value pairs of an L2TP control message.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct L2tpAvpsIterator<'a> {
+    avps: &'a [u8]
+}
+
+impl<'a> L2tpAvpsIterator<'a> {
+    ///Creates an attribute value pairs iterator from a slice containing encoded attribute value pairs.
+    pub fn from_slice(avps: &'a [u8]) -> L2tpAvpsIterator<'a> {
+        L2tpAvpsIterator{ avps }
+    }
+
+    ///Returns the non processed part of the attribute value pairs slice.
+    pub fn rest(&self) -> &'a [u8] {
+        self.avps
+    }
+}
+
+impl<'a> Iterator for L2tpAvpsIterator<'a> {
+    type Item = Result<L2tpAvpSlice<'a>, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.avps.is_empty() {
+            None
+        } else {
+            match L2tpAvpSlice::from_slice(self.avps) {
+                Ok(value) => {
+                    self.avps = &self.avps[value.slice().len()..];
+                    Some(Ok(value))
+                },
+                Err(err) => {
+                    //stop the iteration so no further elements are returned
+                    self.avps = &[];
+                    Some(Err(err))
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,43 @@
+pub mod vxlan;
+pub mod geneve;
+pub mod gtpu;
+pub mod l2tp;
+
+use super::*;
+
+use std::io;
+
+///The possible tunnel headers that encapsulate a further packet.
+///
+///The geneve & gtpu headers contain fixed size buffers so no allocation is needed,
+///which is why the size difference between the variants is accepted.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TunnelHeader {
+    Vxlan(vxlan::VxlanHeader),
+    Geneve(geneve::GeneveHeader),
+    Gtpu(gtpu::GtpuHeader)
+}
+
+impl TunnelHeader {
+
+    ///Returns the size of the tunnel header (including options) in bytes.
+    pub fn header_len(&self) -> usize {
+        use crate::TunnelHeader::*;
+        match self {
+            Vxlan(_) => vxlan::VxlanHeader::SERIALIZED_SIZE,
+            Geneve(value) => value.header_len(),
+            Gtpu(value) => value.header_len()
+        }
+    }
+
+    ///Write the tunnel header to the given writer.
+    pub fn write<T: io::Write + Sized>(&self, writer: &mut T) -> Result<(), WriteError> {
+        use crate::TunnelHeader::*;
+        match self {
+            Vxlan(value) => value.write(writer),
+            Geneve(value) => value.write(writer),
+            Gtpu(value) => value.write(writer)
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use super::super::*;
+
+extern crate byteorder;
+use self::byteorder::{ByteOrder, BigEndian, ReadBytesExt, WriteBytesExt};
+
+///Default udp destination port of VXLAN packets (assigned by IANA).
+pub const VXLAN_PORT: u16 = 4789;
+
+///Flag in the VXLAN header that signals that the VNI field contains a valid value.
+pub const VXLAN_FLAG_VNI_VALID: u8 = 0x08;
+
+///Virtual eXtensible Local Area Network (VXLAN) header according to rfc7348.
+#[derive(Clone, Debug, Eq, PartialEq, Default)]
+pub struct VxlanHeader {
+    ///Flags of the header (only the "I" flag `VXLAN_FLAG_VNI_VALID` is defined, the rest is reserved).
+    pub flags: u8,
+    ///24 bit VXLAN network identifier (VNI) of the overlay network the inner frame belongs to.
+    pub vni: u32
+}
+
+impl SerializedSize for VxlanHeader {
+    ///Size of the header in bytes.
+    const SERIALIZED_SIZE: usize = 8;
+}
+
+impl VxlanHeader {
+
+    ///Creates a VXLAN header with the given vni and the "I" flag set.
+    pub fn new(vni: u32) -> VxlanHeader {
+        VxlanHeader {
+            flags: VXLAN_FLAG_VNI_VALID,
+            vni
+        }
+    }
+
+    ///Reads a VXLAN header from a slice and returns a tuple containing the resulting header & unused part of the slice.
+    pub fn read_from_slice(slice: &[u8]) -> Result<(VxlanHeader, &[u8]), ReadError> {
+        Ok((
+            VxlanHeaderSlice::from_slice(slice)?.to_header(),
+            &slice[VxlanHeader::SERIALIZED_SIZE..]
+        ))
+    }
+
+    ///Reads a VXLAN header from the current position.
+    pub fn read<T: io::Read + io::Seek + Sized>(reader: &mut T) -> Result<VxlanHeader, io::Error> {
+        let flags = reader.read_u8()?;
+        //skip the reserved bytes
+        let mut reserved: [u8;3] = [0;3];
+        reader.read_exact(&mut reserved)?;
+        let vni = reader.read_u32::<BigEndian>()? >> 8;
+        Ok(VxlanHeader{
+            flags,
+            vni
+        })
+    }
+
+    ///Checks if the values in this header are valid values for a VXLAN header.
+    ///
+    ///Specifically it will be checked, that the vni is not greater then 0xffffff.
+    pub fn check_ranges(&self) -> Result<(), ValueError> {
+        use crate::ValueError::U32TooLarge;
+        use crate::ErrorField::VxlanVni;
+        if self.vni > 0x00ff_ffff {
+            Err(U32TooLarge{
+                value: self.vni,
+                max: 0x00ff_ffff,
+                field: VxlanVni
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    ///Writes the VXLAN header to the current position (reserved fields are set to zero).
+    pub fn write<T: io::Write + Sized>(&self, writer: &mut T) -> Result<(), WriteError> {
+        self.check_ranges()?;
+        writer.write_u8(self.flags)?;
+        writer.write_all(&[0;3])?;
+        writer.write_u32::<BigEndian>(self.vni << 8)?;
+        Ok(())
+    }
+}
+
+///A slice containing a VXLAN header of a network package.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VxlanHeaderSlice<'a> {
+    slice: &'a [u8]
+}
+
+impl<'a> VxlanHeaderSlice<'a> {
+
+    ///Creates a slice containing a VXLAN header.
+    pub fn from_slice(slice: &'a[u8]) -> Result<VxlanHeaderSlice<'a>, ReadError> {
+        //check length
+        use crate::ReadError::*;
+        if slice.len() < VxlanHeader::SERIALIZED_SIZE {
+            return Err(UnexpectedEndOfSlice(VxlanHeader::SERIALIZED_SIZE));
+        }
+
+        //done
+        Ok(VxlanHeaderSlice{
+            slice: &slice[..VxlanHeader::SERIALIZED_SIZE]
+        })
+    }
+
+    ///Returns the slice containing the VXLAN header
+    #[inline]
+    pub fn slice(&self) -> &'a [u8] {
+        self.slice
+    }
+
+    ///Read the "flags" field from the slice.
+    pub fn flags(&self) -> u8 {
+        self.slice[0]
+    }
+
+    ///Returns true if the "I" flag is set (signals that the vni field is valid).
+    pub fn vni_valid(&self) -> bool {
+        0 != self.slice[0] & VXLAN_FLAG_VNI_VALID
+    }
+
+    ///Read the 24 bit "VXLAN network identifier" from the slice.
+    pub fn vni(&self) -> u32 {
+        BigEndian::read_u32(&self.slice[4..8]) >> 8
+    }
+
+    ///Decode all the fields and copy the results to a VxlanHeader struct
+    pub fn to_header(&self) -> VxlanHeader {
+        VxlanHeader {
+            flags: self.flags(),
+            vni: self.vni()
+        }
+    }
+}
@@ -0,0 +1,482 @@
+use super::*;
+use etherparse::bpf::*;
+use etherparse::packet_filter::*;
+
+const SOURCE_MAC: [u8;6] = [1,2,3,4,5,6];
+const DESTINATION_MAC: [u8;6] = [7,8,9,10,11,12];
+const IPV4_A: [u8;4] = [192,168,1,1];
+const IPV4_B: [u8;4] = [192,168,1,2];
+const IPV6_A: [u8;16] = [0x20,0x01,0x0d,0xb8,0,0,0,0,0,0,0,0,0,0,0,1];
+const IPV6_B: [u8;16] = [0x20,0x01,0x0d,0xb8,0,0,0,0,0,0,0,0,0,0,0,2];
+
+#[derive(Clone, Copy, Debug)]
+enum TestIp {
+    Arp,
+    Ipv4{ source: [u8;4], destination: [u8;4], options: bool },
+    ///Ipv4 header followed by an authentication header.
+    Ipv4Auth{ source: [u8;4], destination: [u8;4] },
+    Ipv6{ source: [u8;16], destination: [u8;16] },
+    ///Ipv6 header followed by an extension header (or esp header) with the given id.
+    Ipv6Ext{ source: [u8;16], destination: [u8;16], extension: u8 },
+}
+
+const AUTH: u8 = IpTrafficClass::IPv6AuthenticationHeader as u8;
+
+///Serializes an extension header (or authentication header) with the given id pointing to the next header.
+fn extension_header(extension: u8, next_header: u8) -> Vec<u8> {
+    if AUTH == extension {
+        //length in 4 octet units minus 2 (spi, sequence number & 4 bytes icv)
+        vec![next_header, 2, 0, 0,  0, 0, 0, 1,  0, 0, 0, 1,  0, 0, 0, 0]
+    } else {
+        vec![next_header, 0, 0, 0,  0, 0, 0, 1]
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum TestTransport {
+    Tcp(u16, u16),
+    Udp(u16, u16),
+    Icmp,
+}
+
+///Builds an ethernet II frame with the given vlan tags (tag protocol identifier & vlan identifier), ip & transport header.
+fn frame(vlans: &[(u16, u16)], ip: TestIp, transport: TestTransport) -> Vec<u8> {
+    //transport layer
+    let mut transport_data = Vec::new();
+    let protocol = match transport {
+        TestTransport::Tcp(source_port, destination_port) => {
+            TcpHeader::new(source_port, destination_port, 1, 1024).write(&mut transport_data).unwrap();
+            IpTrafficClass::Tcp
+        },
+        TestTransport::Udp(source_port, destination_port) => {
+            UdpHeader{
+                source_port,
+                destination_port,
+                length: 12,
+                checksum: 0
+            }.write(&mut transport_data).unwrap();
+            IpTrafficClass::Udp
+        },
+        TestTransport::Icmp => IpTrafficClass::Icmp
+    };
+    transport_data.extend_from_slice(&[1,2,3,4]);
+
+    //ip layer
+    let mut ip_data = Vec::new();
+    let ether_type = match ip {
+        TestIp::Arp => {
+            ip_data.extend_from_slice(&[0;28]);
+            EtherType::Arp as u16
+        },
+        TestIp::Ipv4{ source, destination, options } => {
+            let mut header = Ipv4Header::new(transport_data.len() as u16, 20, protocol, source, destination);
+            if options {
+                header.set_options(&[1,2,3,4,5,6,7,8]).unwrap();
+            }
+            header.write(&mut ip_data).unwrap();
+            ip_data.extend_from_slice(&transport_data);
+            EtherType::Ipv4 as u16
+        },
+        TestIp::Ipv4Auth{ source, destination } => {
+            let auth = extension_header(AUTH, protocol as u8);
+            Ipv4Header::new((auth.len() + transport_data.len()) as u16, 20, IpTrafficClass::IPv6AuthenticationHeader, source, destination)
+                .write(&mut ip_data)
+                .unwrap();
+            ip_data.extend_from_slice(&auth);
+            ip_data.extend_from_slice(&transport_data);
+            EtherType::Ipv4 as u16
+        },
+        TestIp::Ipv6{ source, destination } => {
+            Ipv6Header{
+                traffic_class: 0,
+                flow_label: 0,
+                payload_length: transport_data.len() as u16,
+                next_header: protocol as u8,
+                hop_limit: 20,
+                source,
+                destination
+            }.write(&mut ip_data).unwrap();
+            ip_data.extend_from_slice(&transport_data);
+            EtherType::Ipv6 as u16
+        },
+        TestIp::Ipv6Ext{ source, destination, extension } => {
+            let ext = extension_header(extension, protocol as u8);
+            Ipv6Header{
+                traffic_class: 0,
+                flow_label: 0,
+                payload_length: (ext.len() + transport_data.len()) as u16,
+                next_header: extension,
+                hop_limit: 20,
+                source,
+                destination
+            }.write(&mut ip_data).unwrap();
+            ip_data.extend_from_slice(&ext);
+            ip_data.extend_from_slice(&transport_data);
+            EtherType::Ipv6 as u16
+        }
+    };
+
+    //link layer
+    let mut result = Vec::new();
+    Ethernet2Header{
+        source: SOURCE_MAC,
+        destination: DESTINATION_MAC,
+        ether_type: vlans.first().map(|vlan| vlan.0).unwrap_or(ether_type)
+    }.write(&mut result).unwrap();
+    for (index, (_, vlan_identifier)) in vlans.iter().enumerate() {
+        SingleVlanHeader{
+            priority_code_point: 0,
+            drop_eligible_indicator: false,
+            vlan_identifier: *vlan_identifier,
+            ether_type: vlans.get(index + 1).map(|vlan| vlan.0).unwrap_or(ether_type)
+        }.write(&mut result).unwrap();
+    }
+    result.extend_from_slice(&ip_data);
+    result
+}
+
+fn test_frames() -> Vec<Vec<u8>> {
+    let vlans: [&[(u16, u16)];5] = [
+        &[],
+        &[(0x8100, 1)],
+        &[(0x8100, 2)],
+        &[(0x88a8, 1), (0x8100, 2)],
+        &[(0x9100, 2), (0x8100, 1)],
+    ];
+    let ips = [
+        TestIp::Ipv4{ source: IPV4_A, destination: IPV4_B, options: false },
+        TestIp::Ipv4{ source: IPV4_A, destination: IPV4_B, options: true },
+        TestIp::Ipv4{ source: IPV4_B, destination: IPV4_A, options: false },
+        TestIp::Ipv6{ source: IPV6_A, destination: IPV6_B },
+        TestIp::Ipv6{ source: IPV6_B, destination: IPV6_A },
+        //headers before the transport header
+        TestIp::Ipv4Auth{ source: IPV4_A, destination: IPV4_B },
+        TestIp::Ipv6Ext{ source: IPV6_A, destination: IPV6_B, extension: IpTrafficClass::IPv6HeaderHopByHop as u8 },
+        TestIp::Ipv6Ext{ source: IPV6_A, destination: IPV6_B, extension: IpTrafficClass::IPv6FragmentationHeader as u8 },
+        TestIp::Ipv6Ext{ source: IPV6_A, destination: IPV6_B, extension: IpTrafficClass::IPv6DestinationOptions as u8 },
+        TestIp::Ipv6Ext{ source: IPV6_B, destination: IPV6_A, extension: AUTH },
+        TestIp::Ipv6Ext{ source: IPV6_A, destination: IPV6_B, extension: IpTrafficClass::IPv6EncapSecurityPayload as u8 },
+    ];
+    let transports = [
+        TestTransport::Tcp(80, 1234),
+        TestTransport::Udp(53, 80),
+        TestTransport::Udp(80, 53),
+        TestTransport::Icmp,
+    ];
+
+    let mut result = Vec::new();
+    for vlan in vlans.iter() {
+        result.push(frame(vlan, TestIp::Arp, TestTransport::Icmp));
+        for ip in ips.iter() {
+            for transport in transports.iter() {
+                result.push(frame(vlan, *ip, *transport));
+            }
+        }
+    }
+    result
+}
+
+fn test_filters() -> Vec<Filter> {
+    use self::ElementFilter::*;
+    let links = [
+        Any,
+        No,
+        Some(LinkFilter::Ethernet2{ source: Option::Some(SOURCE_MAC), destination: None }),
+        Some(LinkFilter::Ethernet2{ source: None, destination: Option::Some(SOURCE_MAC) }),
+        Some(LinkFilter::Ethernet2{ source: Option::Some(SOURCE_MAC), destination: Option::Some(DESTINATION_MAC) }),
+    ];
+    let vlans = [
+        Any,
+        No,
+        Some(VlanFilter::Single(None)),
+        Some(VlanFilter::Single(Option::Some(1))),
+        Some(VlanFilter::Single(Option::Some(2))),
+        Some(VlanFilter::Double{ outer_identifier: None, inner_identifier: None }),
+        Some(VlanFilter::Double{ outer_identifier: Option::Some(1), inner_identifier: Option::Some(2) }),
+        Some(VlanFilter::Double{ outer_identifier: Option::Some(2), inner_identifier: None }),
+        Some(VlanFilter::Double{ outer_identifier: None, inner_identifier: Option::Some(1) }),
+    ];
+    let ips = [
+        Any,
+        No,
+        Some(IpFilter::Ipv4{ source: None, destination: None }),
+        Some(IpFilter::Ipv4{ source: Option::Some(IPV4_A), destination: None }),
+        Some(IpFilter::Ipv4{ source: None, destination: Option::Some(IPV4_A) }),
+        Some(IpFilter::Ipv4{ source: Option::Some(IPV4_A), destination: Option::Some(IPV4_B) }),
+        Some(IpFilter::Ipv6{ source: None, destination: None }),
+        Some(IpFilter::Ipv6{ source: Option::Some(IPV6_A), destination: None }),
+        Some(IpFilter::Ipv6{ source: None, destination: Option::Some(IPV6_A) }),
+    ];
+    let transports = [
+        Any,
+        No,
+        Some(TransportFilter::Udp{ source_port: None, destination_port: None }),
+        Some(TransportFilter::Udp{ source_port: Option::Some(53), destination_port: None }),
+        Some(TransportFilter::Udp{ source_port: None, destination_port: Option::Some(53) }),
+        Some(TransportFilter::Tcp{ source_port: None, destination_port: None }),
+        Some(TransportFilter::Tcp{ source_port: Option::Some(80), destination_port: Option::Some(1234) }),
+        Some(TransportFilter::Tcp{ source_port: Option::Some(1234), destination_port: None }),
+    ];
+
+    let mut result = Vec::new();
+    for link in links.iter() {
+        for vlan in vlans.iter() {
+            for ip in ips.iter() {
+                for transport in transports.iter() {
+                    result.push(Filter{
+                        link: link.clone(),
+                        vlan: vlan.clone(),
+                        ip: ip.clone(),
+                        transport: transport.clone()
+                    });
+                }
+            }
+        }
+    }
+    result
+}
+
+#[test]
+fn compile_filters() {
+    let frames = test_frames();
+    let slices: Vec<SlicedPacket> = frames.iter().map(|frame| SlicedPacket::from_ethernet(frame).unwrap()).collect();
+
+    let mut accepted = 0;
+    let mut rejected = 0;
+    for filter in test_filters() {
+        let program = compile(&filter);
+        assert_eq!(Ok(()), validate(&program));
+        for (frame, slice) in frames.iter().zip(slices.iter()) {
+            //the transport header is not located behind extension & authentication headers, instead packets
+            //containing these headers are rejected by programs filtering the transport layer
+            let hidden_transport = slice.authentication_header.is_some() || match &slice.ip {
+                Some(InternetSlice::Ipv6(_, extensions)) => extensions[0].is_some(),
+                _ => false
+            };
+            let expected = filter.applies_to_slice(slice) &&
+                           !(hidden_transport && ElementFilter::Any != filter.transport);
+            let result = run(&program, frame).unwrap();
+            assert_eq!(
+                if expected { BPF_ACCEPT } else { 0 },
+                result,
+                "filter: {:?}\nframe: {:?}", filter, frame
+            );
+            if expected {
+                accepted += 1;
+            } else {
+                rejected += 1;
+            }
+        }
+    }
+    assert!(accepted > 0);
+    assert!(rejected > 0);
+
+    //a filter without conditions only contains the return instructions
+    assert_eq!(
+        vec![
+            BpfInstruction::statement(BPF_RET | BPF_K, BPF_ACCEPT),
+            BpfInstruction::statement(BPF_RET | BPF_K, 0),
+        ],
+        compile(&Default::default())
+    );
+}
+
+#[test]
+fn compile_filter_truncated() {
+    let filter = Filter{
+        transport: ElementFilter::Some(TransportFilter::Udp{ source_port: None, destination_port: Some(80) }),
+        ..Default::default()
+    };
+    let program = compile(&filter);
+    let frame = frame(&[(0x8100, 1)], TestIp::Ipv4{ source: IPV4_A, destination: IPV4_B, options: false }, TestTransport::Udp(53, 80));
+    assert_eq!(BPF_ACCEPT, run(&program, &frame).unwrap());
+    //loads after the end of the packet reject the packet
+    for len in 0..(frame.len() - 4 - 4) {
+        assert_eq!(0, run(&program, &frame[..len]).unwrap());
+    }
+}
+
+#[test]
+fn run_instructions() {
+    use self::BpfInstruction as I;
+    let ipv4 = frame(&[], TestIp::Ipv4{ source: IPV4_A, destination: IPV4_B, options: true }, TestTransport::Udp(53, 80));
+    let arp = frame(&[], TestIp::Arp, TestTransport::Icmp);
+
+    //program generated by tcpdump for "ip"
+    {
+        let program = [
+            I::statement(BPF_LD | BPF_H | BPF_ABS, 12),
+            I::jump(BPF_JMP | BPF_JEQ | BPF_K, 0x800, 0, 1),
+            I::statement(BPF_RET | BPF_K, 262144),
+            I::statement(BPF_RET | BPF_K, 0),
+        ];
+        assert_eq!(Ok(262144), run(&program, &ipv4));
+        assert_eq!(Ok(0), run(&program, &arp));
+    }
+
+    //loads
+    {
+        let program = [
+            I::statement(BPF_LDX | BPF_B | BPF_MSH, 14),
+            I::statement(BPF_LD | BPF_H | BPF_IND, 14 + 2), //udp destination port
+            I::statement(BPF_ST, 15),
+            I::statement(BPF_LD | BPF_LEN, 0),
+            I::statement(BPF_STX, 3),
+            I::statement(BPF_LDX | BPF_MEM, 15),
+            I::statement(BPF_ALU | BPF_ADD | BPF_X, 0),
+            I::statement(BPF_LDX | BPF_MEM, 3),
+            I::statement(BPF_ALU | BPF_SUB | BPF_X, 0),
+            I::statement(BPF_RET | BPF_A, 0),
+        ];
+        //udp destination port + length - ipv4 header length
+        assert_eq!(Ok(80 + ipv4.len() as u32 - 28), run(&program, &ipv4));
+        //loads outside of the packet reject the packet
+        assert_eq!(Ok(0), run(&program, &ipv4[..14]));
+        assert_eq!(Ok(0), run(&program, &ipv4[..14 + 28 + 3]));
+    }
+    {
+        let program = [
+            I::statement(BPF_LD | BPF_W | BPF_ABS, 0),
+            I::statement(BPF_LDX | BPF_W | BPF_IMM, 1),
+            I::statement(BPF_LD | BPF_B | BPF_IND, u32::MAX), //offset overflows
+            I::statement(BPF_RET | BPF_K, 1),
+        ];
+        assert_eq!(Ok(0), run(&program, &ipv4));
+        assert_eq!(Ok(0), run(&program, &[1,2,3]));
+    }
+
+    //alu operations
+    {
+        let alu = |operations: &[(u16, u32)], a: u32, x: u32| -> Result<u32, BpfError> {
+            let mut program = vec![
+                I::statement(BPF_LD | BPF_IMM, a),
+                I::statement(BPF_LDX | BPF_IMM, x),
+            ];
+            for (code, k) in operations {
+                program.push(I::statement(BPF_ALU | *code, *k));
+            }
+            program.push(I::statement(BPF_RET | BPF_A, 0));
+            run(&program, &[])
+        };
+        assert_eq!(Ok(0), alu(&[(BPF_ADD | BPF_K, 1)], u32::MAX, 0));
+        assert_eq!(Ok(7), alu(&[(BPF_ADD | BPF_X, 0)], 3, 4));
+        assert_eq!(Ok(u32::MAX), alu(&[(BPF_SUB | BPF_K, 1)], 0, 0));
+        assert_eq!(Ok(12), alu(&[(BPF_MUL | BPF_X, 0)], 3, 4));
+        assert_eq!(Ok(3), alu(&[(BPF_DIV | BPF_K, 4)], 15, 0));
+        assert_eq!(Ok(3), alu(&[(BPF_MOD | BPF_X, 0)], 15, 4));
+        assert_eq!(Ok(0b1110), alu(&[(BPF_OR | BPF_K, 0b0110)], 0b1100, 0));
+        assert_eq!(Ok(0b0100), alu(&[(BPF_AND | BPF_K, 0b0110)], 0b1100, 0));
+        assert_eq!(Ok(0b1010), alu(&[(BPF_XOR | BPF_K, 0b0110)], 0b1100, 0));
+        assert_eq!(Ok(0x10), alu(&[(BPF_LSH | BPF_K, 4)], 1, 0));
+        assert_eq!(Ok(0), alu(&[(BPF_LSH | BPF_X, 0)], 1, 32));
+        assert_eq!(Ok(1), alu(&[(BPF_RSH | BPF_X, 0)], 0x10, 4));
+        assert_eq!(Ok(u32::MAX), alu(&[(BPF_NEG, 0)], 1, 0));
+        //division by zero rejects the packet
+        assert_eq!(Ok(0), alu(&[(BPF_DIV | BPF_X, 0)], 15, 0));
+        assert_eq!(Ok(0), alu(&[(BPF_MOD | BPF_X, 0)], 15, 0));
+    }
+
+    //jumps & misc operations
+    {
+        let jump = |code: u16, a: u32, k: u32| -> u32 {
+            let program = [
+                I::statement(BPF_LD | BPF_IMM, k),
+                I::statement(BPF_MISC | BPF_TAX, 0),
+                I::statement(BPF_LD | BPF_IMM, a),
+                I::jump(BPF_JMP | code, k, 1, 0),
+                I::statement(BPF_RET | BPF_K, 1),
+                I::statement(BPF_MISC | BPF_TXA, 0),
+                I::statement(BPF_JMP | BPF_JA, 1),
+                I::statement(BPF_RET | BPF_K, 3),
+                I::statement(BPF_RET | BPF_A, 0),
+            ];
+            run(&program, &[]).unwrap()
+        };
+        for source in [BPF_K, BPF_X].iter() {
+            assert_eq!(5, jump(BPF_JEQ | source, 5, 5));
+            assert_eq!(1, jump(BPF_JEQ | source, 4, 5));
+            assert_eq!(5, jump(BPF_JGT | source, 6, 5));
+            assert_eq!(1, jump(BPF_JGT | source, 5, 5));
+            assert_eq!(5, jump(BPF_JGE | source, 5, 5));
+            assert_eq!(1, jump(BPF_JGE | source, 4, 5));
+            assert_eq!(5, jump(BPF_JSET | source, 4, 5));
+            assert_eq!(1, jump(BPF_JSET | source, 2, 5));
+        }
+    }
+}
+
+#[test]
+fn validate_errors() {
+    use self::BpfInstruction as I;
+    let ret = I::statement(BPF_RET | BPF_K, 0);
+
+    assert_eq!(Ok(()), validate(&[ret]));
+    assert_eq!(Err(BpfError::EmptyProgram), validate(&[]));
+    assert_eq!(Ok(()), validate(&vec![ret; BPF_MAXINSNS]));
+    assert_eq!(
+        Err(BpfError::TooManyInstructions(BPF_MAXINSNS + 1)),
+        validate(&vec![ret; BPF_MAXINSNS + 1])
+    );
+    assert_eq!(Err(BpfError::MissingReturn), validate(&[ret, I::statement(BPF_LD | BPF_IMM, 0)]));
+
+    //unknown opcodes
+    for code in [
+        BPF_LD | BPF_H | BPF_IMM,
+        BPF_LD | BPF_B | BPF_LEN,
+        BPF_LD | BPF_W | 0xe0,
+        BPF_LD | 0x18,
+        BPF_LDX | BPF_W | BPF_ABS,
+        BPF_LDX | BPF_W | BPF_MSH,
+        BPF_ST | BPF_H,
+        BPF_ALU | BPF_NEG | BPF_X,
+        BPF_ALU | 0xb0,
+        BPF_JMP | BPF_JA | BPF_X,
+        BPF_JMP | 0x50,
+        BPF_RET | BPF_X,
+        BPF_MISC | 0x08,
+    ].iter() {
+        assert_eq!(
+            Err(BpfError::UnknownOpcode{ index: 0, code: *code }),
+            validate(&[I::statement(*code, 1), ret]),
+            "{:#x}", code
+        );
+    }
+
+    //jumps
+    assert_eq!(Ok(()), validate(&[I::statement(BPF_JMP | BPF_JA, 1), ret, ret]));
+    assert_eq!(
+        Err(BpfError::JumpOutOfRange{ index: 0 }),
+        validate(&[I::statement(BPF_JMP | BPF_JA, 2), ret, ret])
+    );
+    assert_eq!(Ok(()), validate(&[I::jump(BPF_JMP | BPF_JEQ | BPF_K, 0, 1, 0), ret, ret]));
+    assert_eq!(
+        Err(BpfError::JumpOutOfRange{ index: 1 }),
+        validate(&[ret, I::jump(BPF_JMP | BPF_JEQ | BPF_K, 0, 2, 0), ret, ret])
+    );
+    assert_eq!(
+        Err(BpfError::JumpOutOfRange{ index: 0 }),
+        validate(&[I::jump(BPF_JMP | BPF_JGT | BPF_X, 0, 0, 1), ret])
+    );
+
+    //memory
+    for code in [BPF_LD | BPF_MEM, BPF_LDX | BPF_MEM, BPF_ST, BPF_STX].iter() {
+        assert_eq!(Ok(()), validate(&[I::statement(*code, BPF_MEMWORDS as u32 - 1), ret]));
+        assert_eq!(
+            Err(BpfError::InvalidMemoryIndex{ index: 0 }),
+            validate(&[I::statement(*code, BPF_MEMWORDS as u32), ret])
+        );
+    }
+
+    //constants
+    for (code, k) in [(BPF_DIV, 0), (BPF_MOD, 0), (BPF_LSH, 32), (BPF_RSH, 32)].iter() {
+        assert_eq!(
+            Err(BpfError::InvalidConstant{ index: 0 }),
+            validate(&[I::statement(BPF_ALU | code | BPF_K, *k), ret])
+        );
+        //checked during execution for X
+        assert_eq!(Ok(()), validate(&[I::statement(BPF_ALU | code | BPF_X, *k), ret]));
+    }
+
+    //run validates the program
+    assert_eq!(Err(BpfError::MissingReturn), run(&[I::statement(BPF_LD | BPF_IMM, 0)], &[]));
+}
@@ -0,0 +1,164 @@
+use super::*;
+use etherparse::checksum::*;
+
+#[test]
+fn sum_16bit_words() {
+    assert_eq!(0, Sum16BitWords::new().fold());
+    assert_eq!(Sum16BitWords::new(), Default::default());
+
+    //all add methods
+    //0x1234 + 0x5678 + 0x9abc + 0xdef0 + 0x0102 = 0x1e35a -> 0xe35a + 0x1
+    assert_eq!(
+        0xe35b,
+        Sum16BitWords::new()
+            .add_2bytes([0x12, 0x34])
+            .add_4bytes([0x56, 0x78, 0x9a, 0xbc])
+            .add_u16(0xdef0)
+            .add_slice(&[0x01, 0x02])
+            .fold()
+    );
+    assert_eq!(
+        Sum16BitWords::new().add_u32(0x12345678),
+        Sum16BitWords::new().add_u16(0x1234).add_u16(0x5678)
+    );
+    assert_eq!(
+        Sum16BitWords::new().add_16bytes([1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16]),
+        Sum16BitWords::new().add_slice(&[1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16])
+    );
+    //odd slices are padded with zero
+    assert_eq!(
+        Sum16BitWords::new().add_slice(&[1,2,3]),
+        Sum16BitWords::new().add_u16(0x0102).add_u16(0x0300)
+    );
+}
+
+#[test]
+fn ones_complement() {
+    //example from rfc1071
+    let sum = Sum16BitWords::new().add_slice(&[0x00, 0x01, 0xf2, 0x03, 0xf4, 0xf5, 0xf6, 0xf7]);
+    assert_eq!(0xddf2, sum.fold());
+    assert_eq!(!0xddf2, sum.ones_complement());
+    assert_eq!(!0xddf2, sum.to_ones_complement_with_no_zero());
+
+    //zero is replaced for udp
+    let sum = Sum16BitWords::new().add_u16(0xffff);
+    assert_eq!(0, sum.ones_complement());
+    assert_eq!(0xffff, sum.to_ones_complement_with_no_zero());
+}
+
+proptest! {
+    #[test]
+    fn pseudo_headers(
+        ref ipv4 in ipv4_any(),
+        ref ipv6 in ipv6_any(),
+        ref udp in udp_any(),
+        ref payload in proptest::collection::vec(any::<u8>(), 0..100)
+    ) {
+        let udp = UdpHeader{
+            length: (UdpHeader::SERIALIZED_SIZE + payload.len()) as u16,
+            ..udp.clone()
+        };
+        let mut serialized = Vec::new();
+        udp.write(&mut serialized).unwrap();
+        serialized[6] = 0;
+        serialized[7] = 0;
+        serialized.extend_from_slice(payload);
+
+        assert_eq!(
+            udp.calc_checksum_ipv4(ipv4, payload).unwrap(),
+            ipv4_pseudo_header_sum(ipv4.source, ipv4.destination, ipv4.protocol, udp.length)
+                .add_slice(&serialized)
+                .to_ones_complement_with_no_zero()
+        );
+        assert_eq!(
+            udp.calc_checksum_ipv6(ipv6, payload).unwrap(),
+            ipv6_pseudo_header_sum(&ipv6.source, &ipv6.destination, IpTrafficClass::Udp as u8, u32::from(udp.length))
+                .add_slice(&serialized)
+                .to_ones_complement_with_no_zero()
+        );
+    }
+}
+
+proptest! {
+    #[test]
+    fn incremental_updates(
+        ref data in proptest::collection::vec(any::<u8>(), 4..100),
+        old_ipv4 in any::<[u8;4]>(),
+        new_ipv4 in any::<[u8;4]>(),
+        old_ipv6 in any::<[u8;16]>(),
+        new_ipv6 in any::<[u8;16]>(),
+        new_u16 in any::<u16>(),
+        new_u32 in any::<u32>()
+    ) {
+        //a constant non zero word avoids the two representations of zero
+        let calc = |addr4: [u8;4], addr6: [u8;16], data: &[u8]| -> u16 {
+            Sum16BitWords::new()
+                .add_u16(0x1234)
+                .add_4bytes(addr4)
+                .add_16bytes(addr6)
+                .add_slice(data)
+                .ones_complement()
+        };
+        let checksum = calc(old_ipv4, old_ipv6, data);
+
+        //u16
+        {
+            let mut changed = data.clone();
+            changed[0..2].copy_from_slice(&new_u16.to_be_bytes());
+            assert_eq!(
+                calc(old_ipv4, old_ipv6, &changed),
+                update_u16(checksum, u16::from_be_bytes([data[0], data[1]]), new_u16)
+            );
+        }
+        //u32
+        {
+            let mut changed = data.clone();
+            changed[0..4].copy_from_slice(&new_u32.to_be_bytes());
+            assert_eq!(
+                calc(old_ipv4, old_ipv6, &changed),
+                update_u32(checksum, u32::from_be_bytes([data[0], data[1], data[2], data[3]]), new_u32)
+            );
+        }
+        //addresses
+        assert_eq!(
+            calc(new_ipv4, old_ipv6, data),
+            update_ipv4_address(checksum, old_ipv4, new_ipv4)
+        );
+        assert_eq!(
+            calc(old_ipv4, new_ipv6, data),
+            update_ipv6_address(checksum, &old_ipv6, &new_ipv6)
+        );
+    }
+}
+
+proptest! {
+    #[test]
+    fn add_slice(
+        start in any::<u16>(),
+        ref data in proptest::collection::vec(any::<u8>(), 0..300),
+        offset in 0usize..8
+    ) {
+        //reference implementation summing 2 bytes at a time
+        let reference = |data: &[u8]| -> u16 {
+            let mut sum = u64::from(start);
+            for chunk in data.chunks(2) {
+                sum += if 2 == chunk.len() {
+                    u64::from(u16::from_be_bytes([chunk[0], chunk[1]]))
+                } else {
+                    u64::from(u16::from_be_bytes([chunk[0], 0]))
+                };
+            }
+            while 0 != sum >> 16 {
+                sum = (sum & 0xffff) + (sum >> 16);
+            }
+            sum as u16
+        };
+
+        //unaligned sub slice
+        let data = &data[std::cmp::min(offset, data.len())..];
+        assert_eq!(
+            reference(data),
+            Sum16BitWords::new().add_u16(start).add_slice(data).fold()
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use super::*;
+
+///Serializes an ipv4 tcp packet (calculating the tcp checksum).
+fn ipv4_segment(identification: u16, tcp: &TcpHeader, payload: &[u8]) -> Vec<u8> {
+    let mut ip = Ipv4Header::new(
+        (usize::from(tcp.header_len()) + payload.len()) as u16,
+        20,
+        IpTrafficClass::Tcp,
+        [192,168,1,1],
+        [192,168,1,2]
+    );
+    ip.identification = identification;
+    let mut tcp = tcp.clone();
+    tcp.checksum = tcp.calc_checksum_ipv4(&ip, payload).unwrap();
+
+    let mut result = Vec::new();
+    ip.write(&mut result).unwrap();
+    tcp.write(&mut result).unwrap();
+    result.extend_from_slice(payload);
+    result
+}
+
+fn push(coalescer: &mut TcpCoalescer, packet: &[u8]) -> Vec<Vec<u8>> {
+    coalescer.push(&SlicedPacket::from_ip(packet).unwrap()).unwrap()
+}
+
+#[test]
+fn segmentation_round_trip() {
+    let payload: Vec<u8> = (0..5000).map(|value| value as u8).collect();
+
+    //ipv4 with ethernet & vlan header
+    {
+        let mut packet = Vec::new();
+        PacketBuilder::ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+            .single_vlan(12)
+            .ipv4([192,168,1,1], [192,168,1,2], 20)
+            .tcp(1234, 80, 0xffff_ff00, 1024)
+            .ack(1)
+            .psh()
+            .write(&mut packet, &payload)
+            .unwrap();
+
+        let segments = SlicedPacket::from_ethernet(&packet).unwrap().segment(1448).unwrap();
+        let mut coalescer = TcpCoalescer::new(0xffff);
+        let mut merged = Vec::new();
+        for segment in &segments {
+            merged.extend(coalescer.push(&SlicedPacket::from_ethernet(segment).unwrap()).unwrap());
+        }
+        //the psh flag of the last segment ends the merged packet
+        assert_eq!(0, coalescer.pending_len());
+        assert_eq!(vec![packet], merged);
+    }
+
+    //ipv6
+    {
+        let mut packet = Vec::new();
+        PacketBuilder::ipv6([1;16], [2;16], 20)
+            .tcp(1234, 80, 1, 1024)
+            .write(&mut packet, &payload)
+            .unwrap();
+
+        let segments = SlicedPacket::from_ip(&packet).unwrap().segment(1000).unwrap();
+        let mut coalescer = TcpCoalescer::new(0xffff);
+        for segment in &segments {
+            assert!(push(&mut coalescer, segment).is_empty());
+        }
+        assert_eq!(1, coalescer.pending_len());
+        assert_eq!(vec![packet], coalescer.flush().unwrap());
+        assert_eq!(0, coalescer.pending_len());
+    }
+}
+
+#[test]
+fn segment_sizes() {
+    let tcp = |sequence_number| {
+        let mut result = TcpHeader::new(1234, 80, sequence_number, 1024);
+        result.ack = true;
+        result
+    };
+
+    //a smaller segment ends the merged packet
+    {
+        let mut coalescer = TcpCoalescer::new(0xffff);
+        assert!(push(&mut coalescer, &ipv4_segment(1, &tcp(0), &[1;100])).is_empty());
+        assert!(push(&mut coalescer, &ipv4_segment(2, &tcp(100), &[2;100])).is_empty());
+        let merged = push(&mut coalescer, &ipv4_segment(3, &tcp(200), &[3;50]));
+        assert_eq!(1, merged.len());
+        assert_eq!(0, coalescer.pending_len());
+
+        let sliced = SlicedPacket::from_ip(&merged[0]).unwrap();
+        assert!(!sliced.verify_checksums().contains_invalid());
+        assert_eq!(250, sliced.payload.len());
+        assert_eq!(&[2;100][..], &sliced.payload[100..200]);
+        assert_matches!(sliced.ip, Some(InternetSlice::Ipv4(ip)) => assert_eq!(1, ip.identification()));
+    }
+    //a larger segment is not merged & starts a new packet
+    {
+        let mut coalescer = TcpCoalescer::new(0xffff);
+        let first = ipv4_segment(1, &tcp(0), &[1;100]);
+        assert!(push(&mut coalescer, &first).is_empty());
+        assert_eq!(vec![first], push(&mut coalescer, &ipv4_segment(2, &tcp(100), &[2;200])));
+        assert_eq!(1, coalescer.pending_len());
+    }
+    //maximum payload length
+    {
+        let mut coalescer = TcpCoalescer::new(250);
+        assert_eq!(250, coalescer.max_payload_len());
+        assert!(push(&mut coalescer, &ipv4_segment(1, &tcp(0), &[1;100])).is_empty());
+        assert!(push(&mut coalescer, &ipv4_segment(2, &tcp(100), &[2;100])).is_empty());
+        let flushed = push(&mut coalescer, &ipv4_segment(3, &tcp(200), &[3;100]));
+        assert_eq!(1, flushed.len());
+        assert_eq!(200, SlicedPacket::from_ip(&flushed[0]).unwrap().payload.len());
+        assert_eq!(1, coalescer.pending_len());
+    }
+}
+
+#[test]
+fn not_merged() {
+    let tcp = |sequence_number| {
+        let mut result = TcpHeader::new(1234, 80, sequence_number, 1024);
+        result.ack = true;
+        result.acknowledgment_number = 1;
+        result
+    };
+    let first = ipv4_segment(1, &tcp(0), &[1;100]);
+
+    let mut invalid_checksum = ipv4_segment(2, &tcp(100), &[2;100]);
+    *invalid_checksum.last_mut().unwrap() = 0;
+    let mut different_ttl = ipv4_segment(2, &tcp(100), &[2;100]);
+    Ipv4HeaderSliceMut::from_slice(&mut different_ttl).unwrap().set_ttl(21);
+
+    let cases = vec![
+        //sequence gap
+        ipv4_segment(2, &tcp(101), &[2;100]),
+        //identification not incremented
+        ipv4_segment(3, &tcp(100), &[2;100]),
+        //different acknowledgment number
+        ipv4_segment(2, &{ let mut t = tcp(100); t.acknowledgment_number = 2; t }, &[2;100]),
+        //special flags
+        ipv4_segment(2, &{ let mut t = tcp(100); t.syn = true; t }, &[2;100]),
+        ipv4_segment(2, &{ let mut t = tcp(100); t.rst = true; t }, &[2;100]),
+        ipv4_segment(2, &{ let mut t = tcp(100); t.urg = true; t }, &[2;100]),
+        ipv4_segment(2, &{ let mut t = tcp(100); t.cwr = true; t }, &[2;100]),
+        ipv4_segment(2, &{ let mut t = tcp(100); t.ece = true; t }, &[2;100]),
+        //different options
+        ipv4_segment(2, &{
+            let mut t = tcp(100);
+            t.set_options(&[TcpOptionElement::MaximumSegmentSize(1400)]).unwrap();
+            t
+        }, &[2;100]),
+        //no payload
+        ipv4_segment(2, &tcp(100), &[]),
+        invalid_checksum,
+        different_ttl,
+    ];
+    for case in &cases {
+        let mut coalescer = TcpCoalescer::new(0xffff);
+        assert!(push(&mut coalescer, &first).is_empty());
+        let result = push(&mut coalescer, case);
+        //the held back packet is returned unchanged
+        assert_eq!(first, result[0]);
+        //the segment is held back if it can be merged with later segments
+        if 2 == result.len() {
+            assert_eq!(*case, result[1]);
+            assert_eq!(0, coalescer.pending_len());
+        } else {
+            assert_eq!(1, result.len());
+            assert_eq!(vec![case.clone()], coalescer.flush().unwrap());
+        }
+    }
+}
+
+#[test]
+fn timestamps() {
+    let tcp = |sequence_number, timestamp| {
+        let mut result = TcpHeader::new(1234, 80, sequence_number, 1024);
+        result.ack = true;
+        result.set_options(&[
+            TcpOptionElement::Nop,
+            TcpOptionElement::Nop,
+            TcpOptionElement::Timestamp(timestamp, 7)
+        ]).unwrap();
+        result
+    };
+    let mut coalescer = TcpCoalescer::new(0xffff);
+    assert!(push(&mut coalescer, &ipv4_segment(1, &tcp(0, 1), &[1;100])).is_empty());
+    assert!(push(&mut coalescer, &ipv4_segment(2, &tcp(100, 2), &[2;100])).is_empty());
+    let merged = coalescer.flush().unwrap();
+    assert_eq!(1, merged.len());
+
+    //the merged packet contains the latest timestamp
+    let sliced = SlicedPacket::from_ip(&merged[0]).unwrap();
+    assert!(!sliced.verify_checksums().contains_invalid());
+    assert_eq!(200, sliced.payload.len());
+    assert_matches!(sliced.transport, Some(TransportSlice::Tcp(tcp)) => {
+        assert_eq!(
+            vec![TcpOptionElement::Nop, TcpOptionElement::Nop, TcpOptionElement::Timestamp(2, 7)],
+            tcp.options_iterator().map(|value| value.unwrap()).collect::<Vec<_>>()
+        );
+    });
+}
+
+#[test]
+fn multiple_flows() {
+    let mut coalescer = TcpCoalescer::new(0xffff);
+    for index in 0..3u32 {
+        for port in &[80, 81] {
+            let tcp = TcpHeader::new(1234, *port, index*100, 1024);
+            assert!(push(&mut coalescer, &ipv4_segment(index as u16, &tcp, &[0;100])).is_empty());
+        }
+    }
+    assert_eq!(2, coalescer.pending_len());
+    let merged = coalescer.flush().unwrap();
+    assert_eq!(2, merged.len());
+    for (packet, port) in merged.iter().zip(&[80, 81]) {
+        let sliced = SlicedPacket::from_ip(packet).unwrap();
+        assert_eq!(300, sliced.payload.len());
+        assert_matches!(sliced.transport, Some(TransportSlice::Tcp(tcp)) => assert_eq!(*port, tcp.destination_port()));
+    }
+}
+
+#[test]
+fn errors() {
+    use crate::CoalescingError::*;
+
+    let mut coalescer = TcpCoalescer::new(0xffff);
+
+    //udp
+    let mut packet = Vec::new();
+    PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+        .udp(1234, 5678)
+        .write(&mut packet, &[0;100])
+        .unwrap();
+    assert_matches!(coalescer.push(&SlicedPacket::from_ip(&packet).unwrap()), Err(MissingTcpHeader));
+
+    //fragment
+    let mut packet = ipv4_segment(1, &TcpHeader::new(1234, 80, 0, 1024), &[0;100]);
+    Ipv4HeaderSliceMut::from_slice(&mut packet).unwrap().set_more_fragments(true);
+    assert_matches!(coalescer.push(&SlicedPacket::from_ip(&packet).unwrap()), Err(IpFragment));
+
+    assert_eq!(0, coalescer.pending_len());
+}
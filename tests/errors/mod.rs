@@ -73,6 +73,48 @@ proptest! {
             &format!("ReadError: TCP data offset too small. The data offset value {} in the tcp header is smaller then the tcp header itself.", arg_u8),
             &format!("{}", TcpDataOffsetTooSmall(arg_u8))
         );
+
+        //IpAuthenticationHeaderTooSmall
+        assert_eq!(
+            &format!("ReadError: IP authentication header too small. The payload length value {} in the ip authentication header is smaller then the minimum of 1 (fixed part of the header).", arg_u8),
+            &format!("{}", IpAuthenticationHeaderTooSmall(arg_u8))
+        );
+
+        //GtpuUnexpectedVersion
+        assert_eq!(
+            &format!("ReadError: Unexpected GTP version number. Expected a GTPv1-U Header but the header contained the version number {}.", arg_u8),
+            &format!("{}", GtpuUnexpectedVersion(arg_u8))
+        );
+
+        //GtpuExtensionHeaderLengthZero
+        assert_eq!(
+            &format!("ReadError: Bad GTP-U extension header length. The length field of a GTP-U extension header is zero."),
+            &format!("{}", GtpuExtensionHeaderLengthZero)
+        );
+
+        //GtpuExtensionHeadersTooLarge
+        assert_eq!(
+            &format!("ReadError: GTP-U extension headers too large. The GTP-U extension headers ({} bytes) are bigger then the supported maximum of 256 bytes.", arg_usize),
+            &format!("{}", GtpuExtensionHeadersTooLarge(arg_usize))
+        );
+
+        //L2tpUnexpectedVersion
+        assert_eq!(
+            &format!("ReadError: Unexpected L2TP version number. The L2TP header contained the unexpected version number {}.", arg_u8),
+            &format!("{}", L2tpUnexpectedVersion(arg_u8))
+        );
+
+        //L2tpLengthTooSmall
+        assert_eq!(
+            &format!("ReadError: Bad L2TP length. The length value {} in the L2TP header is smaller then the L2TP header itself.", arg_u16),
+            &format!("{}", L2tpLengthTooSmall(arg_u16))
+        );
+
+        //L2tpAvpLengthTooSmall
+        assert_eq!(
+            &format!("ReadError: Bad L2TP attribute value pair length. The length value {} of the L2TP attribute value pair is smaller then the minimum of 6 bytes.", arg_u16),
+            &format!("{}", L2tpAvpLengthTooSmall(arg_u16))
+        );
     }
 }
 
@@ -97,6 +139,13 @@ fn read_error_source() {
         Ipv6UnexpectedVersion(0),
         Ipv6TooManyHeaderExtensions,
         TcpDataOffsetTooSmall(0),
+        IpAuthenticationHeaderTooSmall(0),
+        GtpuUnexpectedVersion(0),
+        GtpuExtensionHeaderLengthZero,
+        GtpuExtensionHeadersTooLarge(0),
+        L2tpUnexpectedVersion(0),
+        L2tpLengthTooSmall(0),
+        L2tpAvpLengthTooSmall(0),
     ];
 
     for value in &none_values {
@@ -169,10 +218,16 @@ fn value_error_source() {
 
     let none_values = [
         Ipv4OptionsLengthBad(0),
+        Ipv4OptionDataLengthBad{ option_id: 0, data_len: 0 },
+        Ipv4OptionsWithoutIpv4Header,
         Ipv4PayloadLengthTooLarge(0),
         Ipv6PayloadLengthTooLarge(0),
         UdpPayloadLengthTooLarge(0),
         TcpLengthTooLarge(0),
+        IpAuthenticationHeaderBadIcvLength(0),
+        GeneveOptionsLengthBad(0),
+        GtpuExtensionHeaderLengthBad(0),
+        GtpuPayloadLengthTooLarge(0),
         U8TooLarge{ value:0, max:0, field:ErrorField::Ipv4Dscp },
         U16TooLarge{ value:0, max:0, field:ErrorField::Ipv4Dscp },
         U32TooLarge{ value:0, max:0, field:ErrorField::Ipv4Dscp },
@@ -203,6 +258,18 @@ proptest! {
             &format!("{}", Ipv4OptionsLengthBad(arg_usize))
         );
 
+        //Ipv4OptionDataLengthBad
+        assert_eq!(
+            &format!("Bad IPv4 option data length. The data length ({} bytes) of the IPv4 option with the type {} is not a multiple of 4 bytes.", arg_usize, value_u8),
+            &format!("{}", Ipv4OptionDataLengthBad{ option_id: value_u8, data_len: arg_usize })
+        );
+
+        //Ipv4OptionsWithoutIpv4Header
+        assert_eq!(
+            "IPv4 options can not be set as the last added IP header is not an IPv4 header.",
+            &format!("{}", Ipv4OptionsWithoutIpv4Header)
+        );
+
         //Ipv4PayloadLengthTooLarge
         assert_eq!(
             &format!("IPv4 'total_legnth' too large. The IPv4 header and payload have a larger size ({} bytes) than can be be represented by the 'total_legnth' field in the IPv4 header.", arg_usize),
@@ -227,6 +294,30 @@ proptest! {
             &format!("{}", TcpLengthTooLarge(arg_usize))
         );
 
+        //IpAuthenticationHeaderBadIcvLength
+        assert_eq!(
+            &format!("Bad IP authentication header integrity check value length. The integrity check value length ({} bytes) is either not a multiple of 4 bytes or bigger then the maximum of 1016 bytes.", arg_usize),
+            &format!("{}", IpAuthenticationHeaderBadIcvLength(arg_usize))
+        );
+
+        //GeneveOptionsLengthBad
+        assert_eq!(
+            &format!("Bad Geneve options length. The Geneve options length ({} bytes) is either not a multiple of 4 bytes or bigger then the maximum of 252 bytes.", arg_usize),
+            &format!("{}", GeneveOptionsLengthBad(arg_usize))
+        );
+
+        //GtpuExtensionHeaderLengthBad
+        assert_eq!(
+            &format!("Bad GTP-U extension header length. The GTP-U extension header content length ({} bytes) plus 2 is either not a multiple of 4 bytes or the extension headers would get bigger then the maximum of 256 bytes.", arg_usize),
+            &format!("{}", GtpuExtensionHeaderLengthBad(arg_usize))
+        );
+
+        //GtpuPayloadLengthTooLarge
+        assert_eq!(
+            &format!("GTP-U 'length' too large. The GTP-U payload size ({} bytes) is larger then what can be be represented by the 'length' field in the GTP-U header.", arg_usize),
+            &format!("{}", GtpuPayloadLengthTooLarge(arg_usize))
+        );
+
         //U8TooLarge
         assert_eq!(
             &format!("The value {} of the field '{}' is larger then the allowed maximum of {}.", value_u8, field, max_u8),
@@ -267,7 +358,575 @@ fn error_field_display() {
     assert_eq!("Ipv4Header.differentiated_services_code_point", &format!("{}", Ipv4Dscp));
     assert_eq!("Ipv4Header.explicit_congestion_notification", &format!("{}", Ipv4Ecn));
     assert_eq!("Ipv4Header.fragments_offset", &format!("{}", Ipv4FragmentsOffset));
+    assert_eq!("Ipv4OptionElement::Timestamp.overflow", &format!("{}", Ipv4TimestampOverflow));
+    assert_eq!("Ipv4OptionElement::Timestamp.flags", &format!("{}", Ipv4TimestampFlags));
     assert_eq!("Ipv6Header.flow_label", &format!("{}", Ipv6FlowLabel));
     assert_eq!("SingleVlanHeader.priority_code_point", &format!("{}", VlanTagPriorityCodePoint));
     assert_eq!("SingleVlanHeader.vlan_identifier", &format!("{}", VlanTagVlanId));
+    assert_eq!("VxlanHeader.vni", &format!("{}", VxlanVni));
+    assert_eq!("GeneveHeader.version", &format!("{}", GeneveVersion));
+    assert_eq!("GeneveHeader.vni", &format!("{}", GeneveVni));
+    assert_eq!("GtpuHeader.version", &format!("{}", GtpuVersion));
+    assert_eq!("GtpuPduSessionContainer.pdu_type", &format!("{}", GtpuPduType));
+    assert_eq!("GtpuPduSessionContainer.qos_flow_identifier", &format!("{}", GtpuQosFlowIdentifier));
+}
+
+#[test]
+fn nat_error_display() {
+    use NatError::*;
+
+    assert_eq!(
+        "NatError: The packet contains no ip header.",
+        &format!("{}", NoIpHeader)
+    );
+    assert_eq!(
+        "NatError: The version of a given address does not match the version of the ip header.",
+        &format!("{}", IpVersionMismatch)
+    );
+    assert_eq!(
+        "NatError: Ports should be rewritten, but the packet contains no tcp or udp header.",
+        &format!("{}", PortsNotPresent)
+    );
+}
+
+/// Check that all values return None as source
+#[test]
+fn nat_error_source() {
+    use NatError::*;
+    use std::error::Error;
+
+    let none_values = [
+        NoIpHeader,
+        IpVersionMismatch,
+        PortsNotPresent,
+    ];
+
+    for value in &none_values {
+        assert_matches!(value.source(), None);
+    }
+}
+
+#[test]
+fn siit_error_display() {
+    use SiitError::*;
+
+    assert_eq!(
+        "SiitError: The prefix 2001:db8::/33 has an unsupported length or a non zero \"u\" octet.",
+        &format!("{}", InvalidPrefix([0x20,0x01,0x0d,0xb8, 0,0,0,0, 0,0,0,0, 0,0,0,0], 33))
+    );
+    assert_eq!(
+        "SiitError: The ipv6 address 2001:db8::1 does not contain an ipv4 address embedded with the prefix of the translator.",
+        &format!("{}", AddressNotTranslatable([0x20,0x01,0x0d,0xb8, 0,0,0,0, 0,0,0,0, 0,0,0,1]))
+    );
+    assert_eq!(
+        "SiitError: The ICMP message with type 13 & code 0 can not be translated.",
+        &format!("{}", IcmpNotTranslatable{ icmp_type: 13, code: 0 })
+    );
+    assert_eq!(
+        "SiitError: Fragmented ICMP messages can not be translated.",
+        &format!("{}", FragmentedIcmp)
+    );
+    assert_eq!(
+        "SiitError: Fragmented ipv4 udp packets without a checksum can not be translated.",
+        &format!("{}", FragmentedUdpWithoutChecksum)
+    );
+
+    //Read
+    assert_eq!(
+        &format!("SiitError: Error while decoding the packet: {}", ReadError::UnexpectedEndOfSlice(40)),
+        &format!("{}", Read(ReadError::UnexpectedEndOfSlice(40)))
+    );
+
+    //Write
+    assert_eq!(
+        &format!("SiitError: Error while encoding the translated packet: {}", WriteError::SliceTooSmall(20)),
+        &format!("{}", Write(WriteError::SliceTooSmall(20)))
+    );
+}
+
+#[test]
+fn siit_error_source() {
+    use SiitError::*;
+    use std::error::Error;
+
+    assert_matches!(
+        Read(ReadError::UnexpectedEndOfSlice(0)).source(),
+        Some(_)
+    );
+
+    assert_matches!(
+        Write(WriteError::SliceTooSmall(0)).source(),
+        Some(_)
+    );
+
+    let none_values = [
+        InvalidPrefix([0;16], 0),
+        AddressNotTranslatable([0;16]),
+        IcmpNotTranslatable{ icmp_type: 0, code: 0 },
+        FragmentedIcmp,
+        FragmentedUdpWithoutChecksum,
+    ];
+
+    for value in &none_values {
+        assert_matches!(value.source(), None);
+    }
+}
+
+#[test]
+fn fragmentation_error_display() {
+    use FragmentationError::*;
+
+    //Read
+    assert_eq!(
+        &format!("FragmentationError: Error while decoding the packet to fragment: {}", ReadError::UnexpectedEndOfSlice(20)),
+        &format!("{}", Read(ReadError::UnexpectedEndOfSlice(20)))
+    );
+
+    //Write
+    assert_eq!(
+        &format!("FragmentationError: Error while encoding the fragments: {}", WriteError::SliceTooSmall(20)),
+        &format!("{}", Write(WriteError::SliceTooSmall(20)))
+    );
+
+    //Ipv4Option
+    assert_eq!(
+        "FragmentationError: Error while decoding the ipv4 options of the packet to fragment: UnexpectedEndOfSlice(7)",
+        &format!("{}", Ipv4Option(Ipv4OptionReadError::UnexpectedEndOfSlice(7)))
+    );
+
+    assert_eq!(
+        "FragmentationError: The ipv4 packet is larger then the mtu, but the don't fragment flag is set.",
+        &format!("{}", DontFragment)
+    );
+    assert_eq!(
+        "FragmentationError: The mtu of 20 bytes is too small to contain the headers & at least 8 bytes of data per fragment.",
+        &format!("{}", MtuTooSmall(20))
+    );
+    assert_eq!(
+        "FragmentationError: The ipv6 packet already contains a fragmentation header.",
+        &format!("{}", AlreadyFragmented)
+    );
+    assert_eq!(
+        "FragmentationError: The identification Ipv6(1) does not match the ip version of the outermost ip header.",
+        &format!("{}", IdentificationMismatch(FragmentIdentification::Ipv6(1)))
+    );
+}
+
+#[test]
+fn fragmentation_error_source() {
+    use FragmentationError::*;
+    use std::error::Error;
+
+    assert_matches!(
+        Read(ReadError::UnexpectedEndOfSlice(0)).source(),
+        Some(_)
+    );
+
+    assert_matches!(
+        Write(WriteError::SliceTooSmall(0)).source(),
+        Some(_)
+    );
+
+    let none_values = [
+        Ipv4Option(Ipv4OptionReadError::UnexpectedEndOfSlice(0)),
+        DontFragment,
+        MtuTooSmall(0),
+        AlreadyFragmented,
+        IdentificationMismatch(FragmentIdentification::Ipv4(0)),
+    ];
+
+    for value in &none_values {
+        assert_matches!(value.source(), None);
+    }
+}
+
+#[test]
+fn segmentation_error_display() {
+    use SegmentationError::*;
+
+    assert_eq!(
+        "SegmentationError: The packet contains no ip header or no tcp or udp header.",
+        &format!("{}", MissingTransportHeader)
+    );
+    assert_eq!(
+        "SegmentationError: The packet is an ip fragment (only complete packets can be segmented).",
+        &format!("{}", IpFragment)
+    );
+    assert_eq!(
+        "SegmentationError: The packet contains an IPsec authentication header (the integrity check value can not be recalculated).",
+        &format!("{}", AuthenticationHeader)
+    );
+    assert_eq!(
+        "SegmentationError: The maximum segment size is zero.",
+        &format!("{}", ZeroSegmentSize)
+    );
+
+    //Write
+    assert_eq!(
+        &format!("SegmentationError: Error while encoding the segments: {}", WriteError::SliceTooSmall(20)),
+        &format!("{}", Write(WriteError::SliceTooSmall(20)))
+    );
+}
+
+#[test]
+fn segmentation_error_source() {
+    use SegmentationError::*;
+    use std::error::Error;
+
+    assert_matches!(
+        Write(WriteError::SliceTooSmall(0)).source(),
+        Some(_)
+    );
+
+    let none_values = [
+        MissingTransportHeader,
+        IpFragment,
+        AuthenticationHeader,
+        ZeroSegmentSize,
+    ];
+
+    for value in &none_values {
+        assert_matches!(value.source(), None);
+    }
+}
+
+#[test]
+fn coalescing_error_display() {
+    use CoalescingError::*;
+
+    assert_eq!(
+        "CoalescingError: The packet contains no ip header or no tcp header.",
+        &format!("{}", MissingTcpHeader)
+    );
+    assert_eq!(
+        "CoalescingError: The packet is an ip fragment.",
+        &format!("{}", IpFragment)
+    );
+    assert_eq!(
+        "CoalescingError: The packet contains an IPsec authentication header (the integrity check value can not be recalculated).",
+        &format!("{}", AuthenticationHeader)
+    );
+
+    //Write
+    assert_eq!(
+        &format!("CoalescingError: Error while encoding the merged packet: {}", WriteError::SliceTooSmall(20)),
+        &format!("{}", Write(WriteError::SliceTooSmall(20)))
+    );
+}
+
+#[test]
+fn coalescing_error_source() {
+    use CoalescingError::*;
+    use std::error::Error;
+
+    assert_matches!(
+        Write(WriteError::SliceTooSmall(0)).source(),
+        Some(_)
+    );
+
+    let none_values = [
+        MissingTcpHeader,
+        IpFragment,
+        AuthenticationHeader,
+    ];
+
+    for value in &none_values {
+        assert_matches!(value.source(), None);
+    }
+}
+
+#[test]
+fn reassembly_error_display() {
+    use etherparse::tcp_reassembly::ReassemblyError::*;
+
+    assert_eq!(
+        "ReassemblyError: The packet contains no ip header or no tcp header.",
+        &format!("{}", MissingTcpHeader)
+    );
+    assert_eq!(
+        "ReassemblyError: The packet is an ip fragment (fragments have to be reassembled before the tcp reassembly).",
+        &format!("{}", IpFragment)
+    );
+}
+
+/// Check that all values return None as source
+#[test]
+fn reassembly_error_source() {
+    use etherparse::tcp_reassembly::ReassemblyError::*;
+    use std::error::Error;
+
+    let none_values = [
+        MissingTcpHeader,
+        IpFragment,
+    ];
+
+    for value in &none_values {
+        assert_matches!(value.source(), None);
+    }
+}
+
+#[test]
+fn tracking_error_display() {
+    use etherparse::tcp_tracking::TrackingError::*;
+
+    assert_eq!(
+        "TrackingError: The packet contains no ip header or no tcp header (& is no ICMP error message).",
+        &format!("{}", MissingTcpHeader)
+    );
+    assert_eq!(
+        "TrackingError: The packet is an ip fragment (fragments have to be reassembled before the tracking).",
+        &format!("{}", IpFragment)
+    );
+}
+
+/// Check that all values return None as source
+#[test]
+fn tracking_error_source() {
+    use etherparse::tcp_tracking::TrackingError::*;
+    use std::error::Error;
+
+    let none_values = [
+        MissingTcpHeader,
+        IpFragment,
+    ];
+
+    for value in &none_values {
+        assert_matches!(value.source(), None);
+    }
+}
+
+#[test]
+fn flow_error_display() {
+    use etherparse::flow::FlowError::*;
+
+    assert_eq!(
+        "FlowError: The packet contains no ip header.",
+        &format!("{}", MissingIpHeader)
+    );
+}
+
+/// Check that all values return None as source
+#[test]
+fn flow_error_source() {
+    use etherparse::flow::FlowError::*;
+    use std::error::Error;
+
+    assert_matches!(MissingIpHeader.source(), None);
+}
+
+#[test]
+fn netflow_error_display() {
+    use etherparse::netflow::NetflowError::*;
+
+    assert_eq!(
+        "NetflowError: Unexpected end of slice. The given slice contained less then minimum required 24 bytes.",
+        &format!("{}", UnexpectedEndOfSlice(24))
+    );
+    assert_eq!(
+        "NetflowError: Unsupported export packet version 1 (only 5, 9 & 10 are supported).",
+        &format!("{}", UnsupportedVersion(1))
+    );
+    assert_eq!(
+        "NetflowError: The IPFIX message length 8 is smaller then the message header.",
+        &format!("{}", InvalidMessageLength(8))
+    );
+    assert_eq!(
+        "NetflowError: The length 3 of the set 2 is smaller then the set header or exceeds the message.",
+        &format!("{}", InvalidSetLength{ set_id: 2, length: 3 })
+    );
+    assert_eq!(
+        "NetflowError: The template record 256 is malformed.",
+        &format!("{}", InvalidTemplate(256))
+    );
+    assert_eq!(
+        "NetflowError: The data record references the unknown template 257.",
+        &format!("{}", UnknownTemplate(257))
+    );
+    assert_eq!(
+        "NetflowError: A field value for the information element 8 does not match the field specifier of the template 256.",
+        &format!("{}", InvalidFieldValue{ template_id: 256, element_id: 8 })
+    );
+    assert_eq!(
+        "NetflowError: The encoded message length of 70000 bytes exceeds the maximum IPFIX message length of 65535 bytes.",
+        &format!("{}", MessageTooLarge(70000))
+    );
+}
+
+/// Check that all values return None as source
+#[test]
+fn netflow_error_source() {
+    use etherparse::netflow::NetflowError::*;
+    use std::error::Error;
+
+    let none_values = [
+        UnexpectedEndOfSlice(0),
+        UnsupportedVersion(0),
+        InvalidMessageLength(0),
+        InvalidSetLength{ set_id: 0, length: 0 },
+        InvalidTemplate(0),
+        UnknownTemplate(0),
+        InvalidFieldValue{ template_id: 0, element_id: 0 },
+        MessageTooLarge(0),
+    ];
+
+    for value in &none_values {
+        assert_matches!(value.source(), None);
+    }
+}
+
+#[test]
+fn sflow_error_display() {
+    use etherparse::sflow::SflowError::*;
+
+    assert_eq!(
+        "SflowError: Unexpected end of slice. The given slice contained less then minimum required 28 bytes.",
+        &format!("{}", UnexpectedEndOfSlice(28))
+    );
+    assert_eq!(
+        "SflowError: Unsupported datagram version 4 (only version 5 is supported).",
+        &format!("{}", UnsupportedVersion(4))
+    );
+    assert_eq!(
+        "SflowError: Unsupported agent address type 3 (only 1 for IPv4 & 2 for IPv6 are supported).",
+        &format!("{}", UnsupportedAddressType(3))
+    );
+}
+
+/// Check that all values return None as source
+#[test]
+fn sflow_error_source() {
+    use etherparse::sflow::SflowError::*;
+    use std::error::Error;
+
+    let none_values = [
+        UnexpectedEndOfSlice(0),
+        UnsupportedVersion(0),
+        UnsupportedAddressType(0),
+    ];
+
+    for value in &none_values {
+        assert_matches!(value.source(), None);
+    }
+}
+
+#[test]
+fn bpf_error_display() {
+    use etherparse::bpf::BpfError::*;
+
+    assert_eq!(
+        "BpfError: The program contains no instructions.",
+        &format!("{}", EmptyProgram)
+    );
+    assert_eq!(
+        "BpfError: The program contains 4097 instructions (at most 4096 instructions are allowed).",
+        &format!("{}", TooManyInstructions(4097))
+    );
+    assert_eq!(
+        "BpfError: The instruction 1 has the unknown opcode 0x00ff.",
+        &format!("{}", UnknownOpcode{ index: 1, code: 0xff })
+    );
+    assert_eq!(
+        "BpfError: A jump of the instruction 2 targets an instruction after the end of the program.",
+        &format!("{}", JumpOutOfRange{ index: 2 })
+    );
+    assert_eq!(
+        "BpfError: The instruction 3 accesses a scratch memory word outside of the 16 available words.",
+        &format!("{}", InvalidMemoryIndex{ index: 3 })
+    );
+    assert_eq!(
+        "BpfError: The instruction 4 divides by 0 or shifts by more then 31 bits.",
+        &format!("{}", InvalidConstant{ index: 4 })
+    );
+    assert_eq!(
+        "BpfError: The last instruction of the program is not a return instruction.",
+        &format!("{}", MissingReturn)
+    );
+}
+
+/// Check that all values return None as source
+#[test]
+fn bpf_error_source() {
+    use etherparse::bpf::BpfError::*;
+    use std::error::Error;
+
+    let none_values = [
+        EmptyProgram,
+        TooManyInstructions(0),
+        UnknownOpcode{ index: 0, code: 0 },
+        JumpOutOfRange{ index: 0 },
+        InvalidMemoryIndex{ index: 0 },
+        InvalidConstant{ index: 0 },
+        MissingReturn,
+    ];
+
+    for value in &none_values {
+        assert_matches!(value.source(), None);
+    }
+}
+
+#[test]
+fn filter_parse_error_display() {
+    use etherparse::packet_filter::{FilterParseError, FilterParseErrorKind::*, FILTER_MAX_NESTING_DEPTH};
+
+    let error = |kind| FilterParseError{ position: 3, kind };
+
+    assert_eq!(
+        "FilterParseError: Unexpected end of filter expression at position 3, expected a port.",
+        &format!("{}", error(UnexpectedEnd{ expected: "a port" }))
+    );
+    assert_eq!(
+        "FilterParseError: Unexpected 'foo' at position 3 of filter expression, expected a port.",
+        &format!("{}", error(UnexpectedToken{ expected: "a port", found: "foo".to_string() }))
+    );
+    assert_eq!(
+        "FilterParseError: Invalid number '70000' at position 3 of filter expression (numbers have to be between 0 and 65535).",
+        &format!("{}", error(InvalidNumber{ value: "70000".to_string(), max: 65535 }))
+    );
+    assert_eq!(
+        "FilterParseError: Invalid address '10.0.0' at position 3 of filter expression.",
+        &format!("{}", error(InvalidAddress("10.0.0".to_string())))
+    );
+    assert_eq!(
+        "FilterParseError: Invalid network '10.0.0.1/8' at position 3 of filter expression (prefix length too large or bits set after the prefix length).",
+        &format!("{}", error(InvalidPrefix("10.0.0.1/8".to_string())))
+    );
+    assert_eq!(
+        "FilterParseError: Address '10.0.0.1' at position 3 of filter expression does not match the ip version qualifier.",
+        &format!("{}", error(AddressFamilyMismatch("10.0.0.1".to_string())))
+    );
+    assert_eq!(
+        "FilterParseError: Invalid range '2-1' at position 3 of filter expression (the first value is bigger then the last value).",
+        &format!("{}", error(InvalidRange("2-1".to_string())))
+    );
+    assert_eq!(
+        "FilterParseError: Invalid tcp flags 'foo' at position 3 of filter expression (expected a number or flag names separated by ',').",
+        &format!("{}", error(InvalidTcpFlags("foo".to_string())))
+    );
+    assert_eq!(
+        &format!("FilterParseError: Filter expression is nested too deeply at position 3 (at most {} nested operators & parentheses are supported).", FILTER_MAX_NESTING_DEPTH),
+        &format!("{}", error(NestingTooDeep))
+    );
+}
+
+/// Check that all values return None as source
+#[test]
+fn filter_parse_error_source() {
+    use etherparse::packet_filter::{FilterParseError, FilterParseErrorKind::*};
+    use std::error::Error;
+
+    let none_values = [
+        UnexpectedEnd{ expected: "" },
+        UnexpectedToken{ expected: "", found: String::new() },
+        InvalidNumber{ value: String::new(), max: 0 },
+        InvalidAddress(String::new()),
+        InvalidPrefix(String::new()),
+        AddressFamilyMismatch(String::new()),
+        InvalidRange(String::new()),
+        InvalidTcpFlags(String::new()),
+        NestingTooDeep,
+    ];
+
+    for kind in none_values.iter() {
+        assert_matches!(FilterParseError{ position: 0, kind: kind.clone() }.source(), None);
+    }
 }
@@ -0,0 +1,334 @@
+use super::*;
+use etherparse::flow::*;
+use std::net::IpAddr;
+use std::time::Duration;
+
+const CLIENT: ([u8;4], u16) = ([192,168,1,1], 1234);
+const SERVER: ([u8;4], u16) = ([192,168,1,2], 80);
+
+///Serializes an ipv4 tcp packet with the given flags ("S", "A", "F", "R" & "P").
+fn tcp(from_client: bool, flags: &str, payload_len: usize) -> Vec<u8> {
+    let (source, destination) = if from_client {
+        (CLIENT, SERVER)
+    } else {
+        (SERVER, CLIENT)
+    };
+    let mut tcp = TcpHeader::new(source.1, destination.1, 0, 1024);
+    tcp.syn = flags.contains('S');
+    tcp.ack = flags.contains('A');
+    tcp.fin = flags.contains('F');
+    tcp.rst = flags.contains('R');
+    tcp.psh = flags.contains('P');
+    let ip = Ipv4Header::new(
+        (usize::from(tcp.header_len()) + payload_len) as u16,
+        20,
+        IpTrafficClass::Tcp,
+        source.0,
+        destination.0
+    );
+    let mut result = Vec::new();
+    ip.write(&mut result).unwrap();
+    tcp.write(&mut result).unwrap();
+    result.resize(result.len() + payload_len, 0);
+    result
+}
+
+///Pushes a packet as slice & as decoded headers into two tables & checks that both produce the same records.
+fn push(tables: &mut [FlowTable;2], packet: &[u8], second: u64) -> Vec<FlowRecord> {
+    let timestamp = Duration::from_secs(second);
+    let result = tables[0].push(&SlicedPacket::from_ip(packet).unwrap(), timestamp).unwrap();
+    assert_eq!(result, tables[1].push_headers(&PacketHeaders::from_ip_slice(packet).unwrap(), timestamp).unwrap());
+    result
+}
+
+fn tables(config: FlowTableConfig) -> [FlowTable;2] {
+    [FlowTable::new(config.clone()), FlowTable::new(config)]
+}
+
+fn client_key() -> FlowKey {
+    FlowKey {
+        source_mac: None,
+        destination_mac: None,
+        outer_vlan: None,
+        inner_vlan: None,
+        source: IpAddr::from(CLIENT.0),
+        destination: IpAddr::from(SERVER.0),
+        protocol: IpTrafficClass::Tcp as u8,
+        source_port: CLIENT.1,
+        destination_port: SERVER.1,
+    }
+}
+
+#[test]
+fn flow_key() {
+    //link & vlan
+    {
+        let mut packet = Vec::new();
+        PacketBuilder::ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+            .double_vlan(1, 2)
+            .ipv4(CLIENT.0, SERVER.0, 20)
+            .tcp(CLIENT.1, SERVER.1, 0, 1024)
+            .write(&mut packet, &[])
+            .unwrap();
+        let expected = FlowKey {
+            source_mac: Some([1,2,3,4,5,6]),
+            destination_mac: Some([7,8,9,10,11,12]),
+            outer_vlan: Some(1),
+            inner_vlan: Some(2),
+            ..client_key()
+        };
+        let key = FlowKey::from_sliced(&SlicedPacket::from_ethernet(&packet).unwrap());
+        assert_eq!(Some(expected), key);
+        assert_eq!(key, FlowKey::from_headers(&PacketHeaders::from_ethernet_slice(&packet).unwrap()));
+        assert_eq!(client_key(), expected.without_link().without_vlan());
+    }
+    //reversed & canonical
+    {
+        let key = client_key();
+        let reversed = key.reversed();
+        assert_eq!(IpAddr::from(SERVER.0), reversed.source);
+        assert_eq!(SERVER.1, reversed.source_port);
+        assert_eq!(key, reversed.reversed());
+        assert_eq!(key.canonical(), reversed.canonical());
+    }
+    //ipv6 udp
+    {
+        let mut packet = Vec::new();
+        PacketBuilder::ipv6([1;16], [2;16], 20)
+            .udp(53, 1234)
+            .write(&mut packet, &[0;4])
+            .unwrap();
+        let key = FlowKey::from_sliced(&SlicedPacket::from_ip(&packet).unwrap()).unwrap();
+        assert_eq!(IpAddr::from([1u8;16]), key.source);
+        assert_eq!(IpTrafficClass::Udp as u8, key.protocol);
+        assert_eq!((53, 1234), (key.source_port, key.destination_port));
+        assert_eq!(Some(key), FlowKey::from_headers(&PacketHeaders::from_ip_slice(&packet).unwrap()));
+    }
+    //icmp type & code are stored in the destination port
+    {
+        let icmp = [8, 0, 0, 0, 0, 0, 0, 0];
+        let mut packet = Vec::new();
+        Ipv4Header::new(icmp.len() as u16, 20, IpTrafficClass::Icmp, CLIENT.0, SERVER.0)
+            .write(&mut packet)
+            .unwrap();
+        packet.extend_from_slice(&icmp);
+        let key = FlowKey::from_sliced(&SlicedPacket::from_ip(&packet).unwrap()).unwrap();
+        assert_eq!(IpTrafficClass::Icmp as u8, key.protocol);
+        assert_eq!((0, 0x0800), (key.source_port, key.destination_port));
+        assert_eq!(Some(key), FlowKey::from_headers(&PacketHeaders::from_ip_slice(&packet).unwrap()));
+
+        //but not for fragments
+        Ipv4HeaderSliceMut::from_slice(&mut packet).unwrap().set_more_fragments(true);
+        let key = FlowKey::from_sliced(&SlicedPacket::from_ip(&packet).unwrap()).unwrap();
+        assert_eq!((IpTrafficClass::Icmp as u8, 0, 0), (key.protocol, key.source_port, key.destination_port));
+        assert_eq!(Some(key), FlowKey::from_headers(&PacketHeaders::from_ip_slice(&packet).unwrap()));
+    }
+}
+
+#[test]
+fn unidirectional() {
+    let mut tables = tables(FlowTableConfig::default());
+    assert!(push(&mut tables, &tcp(true, "S", 0), 1).is_empty());
+    assert!(push(&mut tables, &tcp(false, "SA", 0), 2).is_empty());
+    assert!(push(&mut tables, &tcp(true, "AP", 100), 3).is_empty());
+    assert_eq!(2, tables[0].flow_count());
+
+    let record = tables[0].get(&client_key()).unwrap().clone();
+    assert_eq!(
+        FlowRecord {
+            key: client_key(),
+            first_seen: Duration::from_secs(1),
+            last_seen: Duration::from_secs(3),
+            forward: FlowCounters {
+                packets: 2,
+                bytes: 40 + 140,
+                tcp_flags: 0x02 | 0x10 | 0x08,
+            },
+            reverse: FlowCounters::default(),
+            end_reason: None,
+        },
+        record
+    );
+    assert_eq!(2, tables[0].get(&client_key().reversed()).unwrap().forward.tcp_flags & 0x02);
+
+    //a FIN ends the flow of its direction
+    let records = push(&mut tables, &tcp(true, "FA", 0), 4);
+    assert_eq!(1, records.len());
+    assert_eq!(Some(FlowEndReason::EndOfFlow), records[0].end_reason);
+    assert_eq!(3, records[0].forward.packets);
+    assert_eq!(1, tables[0].flow_count());
+}
+
+#[test]
+fn bidirectional() {
+    let mut tables = tables(FlowTableConfig{
+        bidirectional: true,
+        ..FlowTableConfig::default()
+    });
+    assert!(push(&mut tables, &tcp(true, "S", 0), 1).is_empty());
+    assert!(push(&mut tables, &tcp(false, "SA", 0), 2).is_empty());
+    assert!(push(&mut tables, &tcp(true, "A", 10), 3).is_empty());
+    assert!(push(&mut tables, &tcp(false, "FA", 0), 4).is_empty());
+    assert_eq!(1, tables[0].flow_count());
+    assert_eq!(tables[0].get(&client_key()), tables[0].get(&client_key().reversed()));
+
+    //the flow ends after both directions sent a FIN
+    let records = push(&mut tables, &tcp(true, "FA", 0), 5);
+    assert_eq!(
+        vec![FlowRecord {
+            key: client_key(),
+            first_seen: Duration::from_secs(1),
+            last_seen: Duration::from_secs(5),
+            forward: FlowCounters {
+                packets: 3,
+                bytes: 40 + 50 + 40,
+                tcp_flags: 0x01 | 0x02 | 0x10,
+            },
+            reverse: FlowCounters {
+                packets: 2,
+                bytes: 80,
+                tcp_flags: 0x01 | 0x02 | 0x10,
+            },
+            end_reason: Some(FlowEndReason::EndOfFlow),
+        }],
+        records
+    );
+    assert_eq!(0, tables[0].flow_count());
+
+    //a reset immediately ends the flow (the key is oriented in the direction of the first packet)
+    assert!(push(&mut tables, &tcp(false, "A", 10), 6).is_empty());
+    let records = push(&mut tables, &tcp(true, "R", 0), 7);
+    assert_eq!(1, records.len());
+    assert_eq!(client_key().reversed(), records[0].key);
+    assert_eq!(1, records[0].forward.packets);
+    assert_eq!(1, records[0].reverse.packets);
+}
+
+#[test]
+fn timeouts() {
+    let mut tables = tables(FlowTableConfig{
+        idle_timeout: Duration::from_secs(10),
+        active_timeout: Duration::from_secs(60),
+        ..FlowTableConfig::default()
+    });
+
+    //idle timeout detected when the next packet arrives
+    assert!(push(&mut tables, &tcp(true, "A", 0), 0).is_empty());
+    let records = push(&mut tables, &tcp(true, "A", 0), 10);
+    assert_eq!(1, records.len());
+    assert_eq!(Some(FlowEndReason::IdleTimeout), records[0].end_reason);
+    assert_eq!(Duration::from_secs(0), records[0].last_seen);
+    assert_eq!(1, tables[0].flow_count());
+
+    //active timeout
+    for second in 11..70 {
+        assert!(push(&mut tables, &tcp(true, "A", 0), second).is_empty());
+    }
+    let records = push(&mut tables, &tcp(true, "A", 0), 70);
+    assert_eq!(1, records.len());
+    assert_eq!(Some(FlowEndReason::ActiveTimeout), records[0].end_reason);
+    assert_eq!(60, records[0].forward.packets);
+    assert_eq!(Duration::from_secs(10), records[0].first_seen);
+
+    //expire
+    assert!(push(&mut tables, &tcp(false, "A", 0), 75).is_empty());
+    assert!(tables[0].expire(Duration::from_secs(79)).is_empty());
+    let records = tables[0].expire(Duration::from_secs(80));
+    assert_eq!(1, records.len());
+    assert_eq!(client_key(), records[0].key);
+    let records = tables[0].expire(Duration::from_secs(85));
+    assert_eq!(1, records.len());
+    assert_eq!(client_key().reversed(), records[0].key);
+    assert_eq!(0, tables[0].flow_count());
+}
+
+#[test]
+fn max_flows() {
+    let mut tables = tables(FlowTableConfig{
+        max_flows: 2,
+        ..FlowTableConfig::default()
+    });
+    let other = {
+        let mut packet = tcp(true, "A", 0);
+        //change the source port
+        packet[20..22].copy_from_slice(&[0, 1]);
+        packet
+    };
+    assert!(push(&mut tables, &tcp(true, "A", 0), 1).is_empty());
+    assert!(push(&mut tables, &tcp(false, "A", 0), 2).is_empty());
+    assert!(push(&mut tables, &tcp(true, "A", 0), 3).is_empty());
+
+    //the least recently active flow is evicted
+    let records = push(&mut tables, &other, 4);
+    assert_eq!(1, records.len());
+    assert_eq!(client_key().reversed(), records[0].key);
+    assert_eq!(Some(FlowEndReason::LackOfResources), records[0].end_reason);
+    assert_eq!(2, tables[0].flow_count());
+}
+
+#[test]
+fn key_fields() {
+    let packet = |vlan, source: [u8;6]| {
+        let mut result = Vec::new();
+        PacketBuilder::ethernet2(source, [7;6])
+            .single_vlan(vlan)
+            .ipv4(CLIENT.0, SERVER.0, 20)
+            .udp(CLIENT.1, SERVER.1)
+            .write(&mut result, &[])
+            .unwrap();
+        result
+    };
+    let packets = [packet(1, [1;6]), packet(1, [2;6]), packet(2, [1;6])];
+    for (include_link, include_vlan, expected) in &[(false, false, 1), (true, false, 2), (false, true, 2), (true, true, 3)] {
+        let mut table = FlowTable::new(FlowTableConfig{
+            include_link: *include_link,
+            include_vlan: *include_vlan,
+            ..FlowTableConfig::default()
+        });
+        for packet in &packets {
+            table.push(&SlicedPacket::from_ethernet(packet).unwrap(), Duration::from_secs(0)).unwrap();
+        }
+        assert_eq!(*expected, table.flow_count());
+        assert_eq!(*include_link, table.flows().all(|record| record.key.source_mac.is_some()));
+        assert_eq!(*include_vlan, table.flows().all(|record| record.key.outer_vlan.is_some()));
+    }
+}
+
+#[test]
+fn flush() {
+    let mut tables = tables(FlowTableConfig::default());
+    assert!(push(&mut tables, &tcp(false, "A", 0), 2).is_empty());
+    assert!(push(&mut tables, &tcp(true, "A", 0), 1).is_empty());
+
+    //records are sorted by their first timestamp
+    let records = tables[0].flush();
+    assert_eq!(
+        vec![client_key(), client_key().reversed()],
+        records.iter().map(|record| record.key).collect::<Vec<_>>()
+    );
+    assert!(records.iter().all(|record| Some(FlowEndReason::ForcedEnd) == record.end_reason));
+    assert_eq!(0, tables[0].flow_count());
+}
+
+#[test]
+fn errors() {
+    let mut table = FlowTable::new(FlowTableConfig::default());
+    let mut packet = Vec::new();
+    Ethernet2Header{
+        source: [1;6],
+        destination: [2;6],
+        ether_type: 0x0806,
+    }.write(&mut packet).unwrap();
+    packet.extend_from_slice(&[0;28]);
+
+    assert_eq!(None, FlowKey::from_sliced(&SlicedPacket::from_ethernet(&packet).unwrap()));
+    assert_eq!(
+        Err(FlowError::MissingIpHeader),
+        table.push(&SlicedPacket::from_ethernet(&packet).unwrap(), Duration::from_secs(0))
+    );
+    assert_eq!(
+        Err(FlowError::MissingIpHeader),
+        table.push_headers(&PacketHeaders::from_ethernet_slice(&packet).unwrap(), Duration::from_secs(0))
+    );
+    assert_eq!(0, table.flow_count());
+}
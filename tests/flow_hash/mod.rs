@@ -0,0 +1,151 @@
+use super::*;
+use etherparse::flow_hash::*;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+///Test vectors of the Microsoft RSS specification ("Verifying the RSS Hash Calculation").
+///
+///Source address & port, destination address & port, hash over the addresses, hash over addresses & ports.
+const IPV4_VECTORS: [(&str, u16, &str, u16, u32, u32);5] = [
+    ("66.9.149.187", 2794, "161.142.100.80", 1766, 0x323e_8fc2, 0x51cc_c178),
+    ("199.92.111.2", 14230, "65.69.140.83", 4739, 0xd718_262a, 0xc626_b0ea),
+    ("24.19.198.95", 12898, "12.22.207.184", 38024, 0xd2d0_a5de, 0x5c2b_394a),
+    ("38.27.205.30", 48228, "209.142.163.6", 2217, 0x8298_9176, 0xafc7_327f),
+    ("153.39.163.191", 44251, "202.188.127.2", 1303, 0x5d18_09c5, 0x10e8_28a2),
+];
+
+const IPV6_VECTORS: [(&str, u16, &str, u16, u32, u32);3] = [
+    ("3ffe:2501:200:1fff::7", 2794, "3ffe:2501:200:3::1", 1766, 0x2cc1_8cd5, 0x4020_7d3d),
+    ("3ffe:501:8::260:97ff:fe40:efab", 14230, "ff02::1", 4739, 0x0f0c_461c, 0xdde5_1bbf),
+    ("3ffe:1900:4545:3:200:f8ff:fe21:67cf", 44251, "fe80::200:f8ff:fe21:67cf", 38024, 0x4b61_e985, 0x02d1_feef),
+];
+
+fn ipv4_tcp(source: [u8;4], source_port: u16, destination: [u8;4], destination_port: u16) -> Vec<u8> {
+    let mut packet = Vec::new();
+    PacketBuilder::ipv4(source, destination, 20)
+        .tcp(source_port, destination_port, 0, 1024)
+        .write(&mut packet, &[])
+        .unwrap();
+    packet
+}
+
+#[test]
+fn toeplitz_hash() {
+    //input of the first ipv4 vector
+    let input = [66, 9, 149, 187, 161, 142, 100, 80, 0x0a, 0xea, 0x06, 0xe6];
+    assert_eq!(0x51cc_c178, etherparse::flow_hash::toeplitz_hash(&RSS_DEFAULT_KEY, &input));
+    assert_eq!(0x323e_8fc2, etherparse::flow_hash::toeplitz_hash(&RSS_DEFAULT_KEY, &input[..8]));
+    assert_eq!(0, etherparse::flow_hash::toeplitz_hash(&RSS_DEFAULT_KEY, &[]));
+    //missing key bits are treated as zeros
+    assert_eq!(0, etherparse::flow_hash::toeplitz_hash(&[], &input));
+}
+
+#[test]
+fn rss_hash_ipv4() {
+    for (source, source_port, destination, destination_port, ip_hash, ip_ports_hash) in &IPV4_VECTORS {
+        let source: Ipv4Addr = source.parse().unwrap();
+        let destination: Ipv4Addr = destination.parse().unwrap();
+
+        //tcp
+        let packet = ipv4_tcp(source.octets(), *source_port, destination.octets(), *destination_port);
+        let sliced = SlicedPacket::from_ip(&packet).unwrap();
+        assert_eq!(Some(*ip_hash), sliced.rss_hash(&RSS_DEFAULT_KEY, RssFields::Ip));
+        assert_eq!(Some(*ip_ports_hash), sliced.rss_hash(&RSS_DEFAULT_KEY, RssFields::IpPorts));
+
+        //udp with ethernet header
+        let mut packet = Vec::new();
+        PacketBuilder::ethernet2([1;6], [2;6])
+            .ipv4(source.octets(), destination.octets(), 20)
+            .udp(*source_port, *destination_port)
+            .write(&mut packet, &[1,2,3,4])
+            .unwrap();
+        let sliced = SlicedPacket::from_ethernet(&packet).unwrap();
+        assert_eq!(Some(*ip_ports_hash), sliced.rss_hash(&RSS_DEFAULT_KEY, RssFields::IpPorts));
+
+        //fragments are hashed without the ports
+        let mut packet = ipv4_tcp(source.octets(), *source_port, destination.octets(), *destination_port);
+        Ipv4HeaderSliceMut::from_slice(&mut packet).unwrap().set_more_fragments(true);
+        let sliced = SlicedPacket::from_ip(&packet).unwrap();
+        assert_eq!(Some(*ip_hash), sliced.rss_hash(&RSS_DEFAULT_KEY, RssFields::IpPorts));
+    }
+}
+
+#[test]
+fn rss_hash_ipv6() {
+    for (source, source_port, destination, destination_port, ip_hash, ip_ports_hash) in &IPV6_VECTORS {
+        let source: Ipv6Addr = source.parse().unwrap();
+        let destination: Ipv6Addr = destination.parse().unwrap();
+        let mut packet = Vec::new();
+        PacketBuilder::ipv6(source.octets(), destination.octets(), 20)
+            .tcp(*source_port, *destination_port, 0, 1024)
+            .write(&mut packet, &[])
+            .unwrap();
+        let sliced = SlicedPacket::from_ip(&packet).unwrap();
+        assert_eq!(Some(*ip_hash), sliced.rss_hash(&RSS_DEFAULT_KEY, RssFields::Ip));
+        assert_eq!(Some(*ip_ports_hash), sliced.rss_hash(&RSS_DEFAULT_KEY, RssFields::IpPorts));
+    }
+}
+
+#[test]
+fn rss_hash_symmetric() {
+    for (source, source_port, destination, destination_port, _, _) in &IPV4_VECTORS {
+        let source: Ipv4Addr = source.parse().unwrap();
+        let destination: Ipv4Addr = destination.parse().unwrap();
+        let forward = ipv4_tcp(source.octets(), *source_port, destination.octets(), *destination_port);
+        let reverse = ipv4_tcp(destination.octets(), *destination_port, source.octets(), *source_port);
+        let forward = SlicedPacket::from_ip(&forward).unwrap();
+        let reverse = SlicedPacket::from_ip(&reverse).unwrap();
+        for fields in &[RssFields::Ip, RssFields::IpPorts] {
+            assert_eq!(
+                forward.rss_hash(&RSS_SYMMETRIC_KEY, *fields),
+                reverse.rss_hash(&RSS_SYMMETRIC_KEY, *fields)
+            );
+            //not symmetric with the default key
+            assert_ne!(
+                forward.rss_hash(&RSS_DEFAULT_KEY, *fields),
+                reverse.rss_hash(&RSS_DEFAULT_KEY, *fields)
+            );
+        }
+    }
+}
+
+#[test]
+fn five_tuple_hash() {
+    let forward = ipv4_tcp([192,168,1,1], 1234, [192,168,1,2], 80);
+    let reverse = ipv4_tcp([192,168,1,2], 80, [192,168,1,1], 1234);
+    let other_port = ipv4_tcp([192,168,1,1], 1235, [192,168,1,2], 80);
+    let forward = SlicedPacket::from_ip(&forward).unwrap();
+    let reverse = SlicedPacket::from_ip(&reverse).unwrap();
+    let other_port = SlicedPacket::from_ip(&other_port).unwrap();
+
+    let hash = forward.five_tuple_hash(0).unwrap();
+    assert_eq!(Some(hash), forward.five_tuple_hash(0));
+    assert_ne!(Some(hash), forward.five_tuple_hash(1));
+    assert_ne!(Some(hash), reverse.five_tuple_hash(0));
+    assert_ne!(Some(hash), other_port.five_tuple_hash(0));
+
+    //the protocol is part of the hash
+    let mut udp = Vec::new();
+    PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+        .udp(1234, 80)
+        .write(&mut udp, &[])
+        .unwrap();
+    assert_ne!(Some(hash), SlicedPacket::from_ip(&udp).unwrap().five_tuple_hash(0));
+
+    //symmetric
+    for seed in &[0, 1, 0xffff_ffff_ffff_ffff] {
+        assert_eq!(forward.symmetric_five_tuple_hash(*seed), reverse.symmetric_five_tuple_hash(*seed));
+        assert_ne!(forward.symmetric_five_tuple_hash(*seed), other_port.symmetric_five_tuple_hash(*seed));
+    }
+
+    //no ip header
+    let mut packet = Vec::new();
+    Ethernet2Header{
+        source: [1;6],
+        destination: [2;6],
+        ether_type: 0x0806,
+    }.write(&mut packet).unwrap();
+    let sliced = SlicedPacket::from_ethernet(&packet).unwrap();
+    assert_eq!(None, sliced.five_tuple_hash(0));
+    assert_eq!(None, sliced.symmetric_five_tuple_hash(0));
+    assert_eq!(None, sliced.rss_hash(&RSS_DEFAULT_KEY, RssFields::IpPorts));
+}
@@ -0,0 +1,75 @@
+use etherparse::*;
+use super::super::*;
+
+#[test]
+fn new() {
+    assert_eq!(
+        EspHeader{
+            spi: 0x12345678,
+            sequence_number: 0x9abcdef0
+        },
+        EspHeader::new(0x12345678, 0x9abcdef0)
+    );
+}
+
+#[test]
+fn write() {
+    let mut buffer = Vec::new();
+    EspHeader::new(0x12345678, 0x9abcdef0).write(&mut buffer).unwrap();
+    assert_eq!(
+        vec![0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0],
+        buffer
+    );
+}
+
+proptest! {
+    #[test]
+    fn read_write(ref input in esp_any()) {
+        use std::io::Cursor;
+
+        //serialize
+        let mut buffer: Vec<u8> = Vec::with_capacity(EspHeader::SERIALIZED_SIZE + 1);
+        input.write(&mut buffer).unwrap();
+        assert_eq!(EspHeader::SERIALIZED_SIZE, buffer.len());
+
+        //deserialize with read
+        {
+            let result = EspHeader::read(&mut Cursor::new(&buffer)).unwrap();
+            assert_eq!(input, &result);
+        }
+        //deserialize from slice
+        {
+            //add some data to test the return slice
+            buffer.push(1);
+
+            let result = EspHeader::read_from_slice(&buffer).unwrap();
+            assert_eq!(input, &result.0);
+            assert_eq!(&buffer[buffer.len()-1 .. ], result.1);
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn from_slice(ref input in esp_any()) {
+        //serialize
+        let mut buffer: Vec<u8> = Vec::with_capacity(EspHeader::SERIALIZED_SIZE + 1);
+        input.write(&mut buffer).unwrap();
+        buffer.push(1);
+
+        //check the fields
+        let slice = EspHeaderSlice::from_slice(&buffer).unwrap();
+        assert_eq!(&buffer[..EspHeader::SERIALIZED_SIZE], slice.slice());
+        assert_eq!(input.spi, slice.spi());
+        assert_eq!(input.sequence_number, slice.sequence_number());
+        assert_eq!(input, &slice.to_header());
+
+        //check that a too small slice triggers an error
+        for len in 0..EspHeader::SERIALIZED_SIZE {
+            assert_matches!(
+                EspHeaderSlice::from_slice(&buffer[..len]),
+                Err(ReadError::UnexpectedEndOfSlice(EspHeader::SERIALIZED_SIZE))
+            );
+        }
+    }
+}
@@ -0,0 +1,301 @@
+use etherparse::*;
+use super::super::*;
+
+///Serializes an ipv4 packet with the given options & a payload of `payload_len` bytes (counting up).
+fn ipv4_packet(options: &[Ipv4OptionElement], payload_len: usize) -> (Vec<u8>, Vec<u8>) {
+    let payload: Vec<u8> = (0..payload_len).map(|value| value as u8).collect();
+    let mut header = Ipv4Header::new(payload_len as u16, 20, IpTrafficClass::Udp, [192,168,1,1], [192,168,1,2]);
+    header.dont_fragment = false;
+    header.identification = 1234;
+    header.set_option_elements(options).unwrap();
+    let mut packet = Vec::new();
+    header.write(&mut packet).unwrap();
+    packet.extend_from_slice(&payload);
+    (packet, payload)
+}
+
+///Checks the fragment headers & returns the reassembled payload.
+fn reassemble_ipv4(fragments: &[Vec<u8>], mtu: usize, first_offset: u16) -> Vec<u8> {
+    let mut result = Vec::new();
+    for (index, fragment) in fragments.iter().enumerate() {
+        assert!(fragment.len() <= mtu);
+        let header = Ipv4HeaderSlice::from_slice(fragment).unwrap();
+        assert_eq!(1234, header.identification());
+        assert_eq!(usize::from(header.total_len()), fragment.len());
+        assert_eq!(first_offset + (result.len() / 8) as u16, header.fragments_offset());
+        if index + 1 < fragments.len() {
+            assert!(header.more_fragments());
+            assert_eq!(0, header.payload_len() % 8);
+        }
+        //check the header checksum
+        assert_eq!(header.header_checksum(), header.to_header().calc_header_checksum().unwrap());
+        result.extend_from_slice(&fragment[header.slice().len()..]);
+    }
+    result
+}
+
+#[test]
+fn ipv4() {
+    //not larger then the mtu
+    {
+        let (packet, _) = ipv4_packet(&[], 100);
+        assert_eq!(vec![packet.clone()], fragment_ipv4_packet(&packet, 120).unwrap());
+
+        //data past the total length is ignored
+        let mut padded = packet.clone();
+        padded.extend_from_slice(&[0;4]);
+        assert_eq!(vec![packet], fragment_ipv4_packet(&padded, 120).unwrap());
+    }
+    //split without options
+    for (payload_len, mtu, expected_count) in &[(1000, 500, 3), (960, 500, 2), (100, 68, 3)] {
+        let (packet, payload) = ipv4_packet(&[], *payload_len);
+        let fragments = fragment_ipv4_packet(&packet, *mtu).unwrap();
+        assert_eq!(*expected_count, fragments.len());
+        assert_eq!(payload, reassemble_ipv4(&fragments, *mtu, 0));
+        assert!(!Ipv4HeaderSlice::from_slice(fragments.last().unwrap()).unwrap().more_fragments());
+    }
+}
+
+#[test]
+fn ipv4_options() {
+    //only the options with the copied flag are repeated
+    let options = [
+        Ipv4OptionElement::RecordRoute{ pointer: 4, route: &[0;8] },
+        Ipv4OptionElement::Nop,
+        Ipv4OptionElement::LooseSourceRoute{ pointer: 4, route: &[1,2,3,4] },
+        Ipv4OptionElement::RouterAlert(0),
+    ];
+    let (packet, payload) = ipv4_packet(&options, 1000);
+    let fragments = fragment_ipv4_packet(&packet, 300).unwrap();
+    assert_eq!(payload, reassemble_ipv4(&fragments, 300, 0));
+
+    let first = Ipv4HeaderSlice::from_slice(&fragments[0]).unwrap();
+    assert_eq!(
+        options.to_vec(),
+        first.options_iterator().take(4).map(|value| value.unwrap()).collect::<Vec<_>>()
+    );
+    for fragment in &fragments[1..] {
+        let header = Ipv4HeaderSlice::from_slice(fragment).unwrap();
+        assert_eq!(
+            vec![
+                Ipv4OptionElement::LooseSourceRoute{ pointer: 4, route: &[1,2,3,4] },
+                Ipv4OptionElement::RouterAlert(0),
+                Ipv4OptionElement::EndOfOptionsList,
+            ],
+            header.options_iterator().map(|value| value.unwrap()).collect::<Vec<_>>()
+        );
+    }
+}
+
+#[test]
+fn ipv4_already_fragmented() {
+    //fragmenting a fragment keeps the offset & more fragments flag of the last fragment
+    for more_fragments in &[false, true] {
+        let (mut packet, payload) = ipv4_packet(&[], 100);
+        {
+            let mut header = Ipv4HeaderSliceMut::from_slice(&mut packet).unwrap();
+            header.set_fragments_offset(10).unwrap();
+            header.set_more_fragments(*more_fragments);
+        }
+        let fragments = fragment_ipv4_packet(&packet, 60).unwrap();
+        assert_eq!(payload, reassemble_ipv4(&fragments, 60, 10));
+        assert_eq!(
+            *more_fragments,
+            Ipv4HeaderSlice::from_slice(fragments.last().unwrap()).unwrap().more_fragments()
+        );
+    }
+}
+
+#[test]
+fn ipv4_errors() {
+    use crate::FragmentationError::*;
+
+    //don't fragment
+    {
+        let (mut packet, _) = ipv4_packet(&[], 100);
+        Ipv4HeaderSliceMut::from_slice(&mut packet).unwrap().set_dont_fragment(true);
+        assert_matches!(fragment_ipv4_packet(&packet, 100), Err(DontFragment));
+        //no error if the packet does not have to be fragmented
+        assert_eq!(1, fragment_ipv4_packet(&packet, 120).unwrap().len());
+    }
+    //mtu too small
+    {
+        let (packet, _) = ipv4_packet(&[], 100);
+        assert_matches!(fragment_ipv4_packet(&packet, 27), Err(MtuTooSmall(27)));
+    }
+    //truncated packet
+    {
+        let (packet, _) = ipv4_packet(&[], 100);
+        assert_matches!(
+            fragment_ipv4_packet(&packet[..119], 60),
+            Err(Read(ReadError::UnexpectedEndOfSlice(120)))
+        );
+    }
+}
+
+///Serializes an ipv6 packet with a hop by hop & destination options header followed by an udp header & payload.
+fn ipv6_packet(payload_len: usize) -> (Vec<u8>, Vec<u8>) {
+    //destination options & udp header are part of the fragmentable part
+    let mut fragmentable = vec![17, 0, 1, 4, 0, 0, 0, 0];
+    let payload: Vec<u8> = (0..payload_len).map(|value| value as u8).collect();
+    UdpHeader::without_ipv4_checksum(1234, 5678, payload.len()).unwrap().write(&mut fragmentable).unwrap();
+    fragmentable.extend_from_slice(&payload);
+
+    let mut packet = Vec::new();
+    Ipv6Header {
+        traffic_class: 0,
+        flow_label: 0,
+        payload_length: (8 + fragmentable.len()) as u16,
+        next_header: IpTrafficClass::IPv6HeaderHopByHop as u8,
+        hop_limit: 20,
+        source: [1;16],
+        destination: [2;16]
+    }.write(&mut packet).unwrap();
+    packet.extend_from_slice(&[60, 0, 1, 4, 0, 0, 0, 0]);
+    packet.extend_from_slice(&fragmentable);
+    (packet, fragmentable)
+}
+
+#[test]
+fn ipv6() {
+    use crate::FragmentationError::*;
+
+    //not larger then the mtu
+    {
+        let (packet, _) = ipv6_packet(100);
+        assert_eq!(vec![packet.clone()], fragment_ipv6_packet(&packet, 1280, 1).unwrap());
+    }
+    //split
+    {
+        let (packet, fragmentable) = ipv6_packet(3000);
+        let fragments = fragment_ipv6_packet(&packet, 1280, 0x1234_5678).unwrap();
+        assert_eq!(3, fragments.len());
+
+        let mut reassembled = Vec::new();
+        for (index, fragment) in fragments.iter().enumerate() {
+            assert!(fragment.len() <= 1280);
+            let header = Ipv6HeaderSlice::from_slice(fragment).unwrap();
+            assert_eq!(fragment.len() - 40, usize::from(header.payload_length()));
+            assert_eq!(0, header.next_header());
+
+            //hop by hop header pointing to the fragmentation header
+            assert_eq!([44, 0, 1, 4, 0, 0, 0, 0], fragment[40..48]);
+
+            //fragmentation header
+            let frag = &fragment[48..56];
+            assert_eq!(60, frag[0]);
+            let last = index + 1 == fragments.len();
+            assert_eq!(
+                ((reassembled.len() / 8) as u16) << 3 | if last { 0 } else { 1 },
+                u16::from_be_bytes([frag[2], frag[3]])
+            );
+            assert_eq!([0x12, 0x34, 0x56, 0x78], frag[4..8]);
+            if !last {
+                assert_eq!(0, (fragment.len() - 56) % 8);
+            }
+            reassembled.extend_from_slice(&fragment[56..]);
+        }
+        assert_eq!(fragmentable, reassembled);
+    }
+    //errors
+    {
+        let (packet, _) = ipv6_packet(3000);
+        assert_matches!(fragment_ipv6_packet(&packet, 63, 1), Err(MtuTooSmall(63)));
+        assert_matches!(
+            fragment_ipv6_packet(&packet[..packet.len() - 1], 1280, 1),
+            Err(Read(ReadError::UnexpectedEndOfSlice(_)))
+        );
+
+        let fragment = fragment_ipv6_packet(&packet, 1280, 1).unwrap().remove(0);
+        let mut fragmented = fragment.clone();
+        fragmented.resize(1400, 0);
+        fragmented[4..6].copy_from_slice(&1360u16.to_be_bytes());
+        assert_matches!(fragment_ipv6_packet(&fragmented, 1280, 1), Err(AlreadyFragmented));
+    }
+}
+
+#[test]
+fn packet_builder() {
+    //ipv4 with ethernet & vlan header
+    {
+        let mut header = Ipv4Header::new(0, 20, IpTrafficClass::Udp, [192,168,1,1], [192,168,1,2]);
+        header.dont_fragment = false;
+        let payload: Vec<u8> = (0..3000).map(|value| value as u8).collect();
+        let fragments = PacketBuilder::ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+            .single_vlan(12)
+            .ip(IpHeader::Version4(header))
+            .udp(1234, 5678)
+            .write_fragmented(1500, FragmentIdentification::Ipv4(0x2345), &payload)
+            .unwrap();
+        assert_eq!(3, fragments.len());
+
+        let mut reassembled = Vec::new();
+        for fragment in &fragments {
+            assert!(fragment.len() <= 18 + 1500);
+            let sliced = SlicedPacket::from_ethernet(fragment).unwrap();
+            assert!(sliced.vlan.is_some());
+            match sliced.ip.unwrap() {
+                InternetSlice::Ipv4(ip) => {
+                    assert_eq!(0x2345, ip.identification());
+                    reassembled.extend_from_slice(&fragment[18 + ip.slice().len()..]);
+                },
+                _ => panic!("expected ipv4")
+            }
+        }
+        let packet = {
+            let mut packet = Vec::new();
+            let mut header = Ipv4Header::new(reassembled.len() as u16, 20, IpTrafficClass::Udp, [192,168,1,1], [192,168,1,2]);
+            header.dont_fragment = false;
+            header.identification = 0x2345;
+            header.write(&mut packet).unwrap();
+            packet.extend_from_slice(&reassembled);
+            packet
+        };
+        let headers = PacketHeaders::from_ip_slice(&packet).unwrap();
+        assert_eq!(&payload[..], headers.payload);
+        assert_eq!(ChecksumStatus::Valid, headers.verify_checksums().transport);
+    }
+    //ipv4 with the don't fragment flag set (default of the builder)
+    assert_matches!(
+        PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+            .udp(1234, 5678)
+            .write_fragmented(1500, FragmentIdentification::Ipv4(1), &[0;4000]),
+        Err(FragmentationError::DontFragment)
+    );
+    //the don't fragment flag is kept if no fragmentation is required
+    {
+        let fragments = PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+            .udp(1234, 5678)
+            .write_fragmented(1500, FragmentIdentification::Ipv4(1), &[0;100])
+            .unwrap();
+        assert_eq!(1, fragments.len());
+        assert!(Ipv4HeaderSlice::from_slice(&fragments[0]).unwrap().dont_fragment());
+    }
+    //identification not matching the ip version
+    assert_matches!(
+        PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+            .udp(1234, 5678)
+            .write_fragmented(1500, FragmentIdentification::Ipv6(1), &[0;2000]),
+        Err(FragmentationError::IdentificationMismatch(FragmentIdentification::Ipv6(1)))
+    );
+    assert_matches!(
+        PacketBuilder::ipv6([1;16], [2;16], 20)
+            .udp(1234, 5678)
+            .write_fragmented(1500, FragmentIdentification::Ipv4(1), &[0;2000]),
+        Err(FragmentationError::IdentificationMismatch(FragmentIdentification::Ipv4(1)))
+    );
+    //ipv6
+    {
+        let fragments = PacketBuilder::ipv6([1;16], [2;16], 20)
+            .tcp(1234, 5678, 1, 1024)
+            .write_fragmented(1280, FragmentIdentification::Ipv6(7), &[0;2000])
+            .unwrap();
+        assert_eq!(2, fragments.len());
+        for fragment in &fragments {
+            assert!(fragment.len() <= 1280);
+            assert_eq!(44, fragment[6]);
+            assert_eq!(6, fragment[40]);
+            assert_eq!([0, 0, 0, 7], fragment[44..48]);
+        }
+    }
+}
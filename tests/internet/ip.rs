@@ -127,6 +127,16 @@ fn read_ip_header_error() {
     );
 }
 
+proptest! {
+    #[test]
+    fn header_len(ref ipv4 in ipv4_any(),
+                  ref ipv6 in ipv6_any())
+    {
+        assert_eq!(ipv4.header_len(), IpHeader::Version4(ipv4.clone()).header_len());
+        assert_eq!(Ipv6Header::SERIALIZED_SIZE, IpHeader::Version6(ipv6.clone()).header_len());
+    }
+}
+
 mod ip_traffic_class {
     
     #[test]
@@ -0,0 +1,185 @@
+use etherparse::*;
+use super::super::*;
+
+#[test]
+fn new() {
+    let header = AuthenticationHeader::new(
+        IpTrafficClass::Tcp as u8,
+        0x12345678,
+        0x9abcdef0,
+        &[1,2,3,4,5,6,7,8,9,10,11,12]
+    ).unwrap();
+    assert_eq!(IpTrafficClass::Tcp as u8, header.next_header);
+    assert_eq!(0x12345678, header.spi);
+    assert_eq!(0x9abcdef0, header.sequence_number);
+    assert_eq!(&[1,2,3,4,5,6,7,8,9,10,11,12], header.raw_icv());
+    assert_eq!(24, header.header_len());
+}
+
+#[test]
+fn new_and_set_icv_bad_length() {
+    use crate::ValueError::*;
+
+    //not a multiple of 4
+    for len in [1usize, 2, 3, 5, 6, 7].iter() {
+        let icv = vec![0;*len];
+        assert_eq!(
+            Some(IpAuthenticationHeaderBadIcvLength(*len)),
+            AuthenticationHeader::new(0, 0, 0, &icv[..]).err()
+        );
+    }
+    //too large
+    {
+        let icv = vec![0;IP_AUTHENTICATION_HEADER_MAX_ICV_LEN + 4];
+        assert_eq!(
+            Some(IpAuthenticationHeaderBadIcvLength(IP_AUTHENTICATION_HEADER_MAX_ICV_LEN + 4)),
+            AuthenticationHeader::new(0, 0, 0, &icv[..]).err()
+        );
+    }
+    //set_raw_icv keeps the previous value in case of an error
+    {
+        let mut header = AuthenticationHeader::new(0, 0, 0, &[1,2,3,4]).unwrap();
+        assert_eq!(
+            Err(IpAuthenticationHeaderBadIcvLength(3)),
+            header.set_raw_icv(&[5,6,7])
+        );
+        assert_eq!(&[1,2,3,4], header.raw_icv());
+    }
+    //maximum size
+    {
+        let icv = vec![1;IP_AUTHENTICATION_HEADER_MAX_ICV_LEN];
+        let header = AuthenticationHeader::new(0, 0, 0, &icv[..]).unwrap();
+        assert_eq!(&icv[..], header.raw_icv());
+        assert_eq!(0xff*4 + 8, header.header_len());
+    }
+}
+
+#[test]
+fn write() {
+    let mut buffer = Vec::new();
+    AuthenticationHeader::new(
+        IpTrafficClass::Udp as u8,
+        0x12345678,
+        0x9abcdef0,
+        &[1,2,3,4,5,6,7,8,9,10,11,12]
+    ).unwrap().write(&mut buffer).unwrap();
+    assert_eq!(
+        vec![
+            IpTrafficClass::Udp as u8, 4, 0, 0,
+            0x12, 0x34, 0x56, 0x78,
+            0x9a, 0xbc, 0xde, 0xf0,
+            1, 2, 3, 4,
+            5, 6, 7, 8,
+            9, 10, 11, 12
+        ],
+        buffer
+    );
+}
+
+#[test]
+fn default() {
+    let header: AuthenticationHeader = Default::default();
+    assert_eq!(0, header.next_header);
+    assert_eq!(0, header.spi);
+    assert_eq!(0, header.sequence_number);
+    assert_eq!(0, header.raw_icv().len());
+    assert_eq!(AuthenticationHeader::SERIALIZED_SIZE, header.header_len());
+}
+
+#[test]
+fn debug() {
+    let header = AuthenticationHeader::new(1, 2, 3, &[4,5,6,7]).unwrap();
+    assert_eq!(
+        "AuthenticationHeader { next_header: 1, spi: 2, sequence_number: 3, raw_icv: [4, 5, 6, 7] }",
+        &format!("{:?}", header)
+    );
+}
+
+#[test]
+fn read_payload_len_too_small() {
+    use std::io::Cursor;
+    let buffer = [
+        IpTrafficClass::Udp as u8, 0, 0, 0,
+        0, 0, 0, 0,
+        0, 0, 0, 0
+    ];
+    assert_matches!(
+        AuthenticationHeader::read(&mut Cursor::new(&buffer)),
+        Err(ReadError::IpAuthenticationHeaderTooSmall(0))
+    );
+    assert_matches!(
+        AuthenticationHeader::read_from_slice(&buffer),
+        Err(ReadError::IpAuthenticationHeaderTooSmall(0))
+    );
+    assert_matches!(
+        AuthenticationHeaderSlice::from_slice(&buffer),
+        Err(ReadError::IpAuthenticationHeaderTooSmall(0))
+    );
+}
+
+proptest! {
+    #[test]
+    fn read_write(ref input in ip_authentication_any()) {
+        use std::io::Cursor;
+
+        //serialize
+        let mut buffer: Vec<u8> = Vec::with_capacity(input.header_len() + 1);
+        input.write(&mut buffer).unwrap();
+        assert_eq!(input.header_len(), buffer.len());
+
+        //deserialize with read
+        {
+            let result = AuthenticationHeader::read(&mut Cursor::new(&buffer)).unwrap();
+            assert_eq!(input, &result);
+        }
+        //deserialize from slice
+        {
+            //add some data to test the return slice
+            buffer.push(1);
+
+            let result = AuthenticationHeader::read_from_slice(&buffer).unwrap();
+            assert_eq!(input, &result.0);
+            assert_eq!(&buffer[buffer.len()-1 .. ], result.1);
+        }
+        //unexpected eof
+        {
+            let result = AuthenticationHeader::read(&mut Cursor::new(&buffer[..input.header_len() - 1]));
+            assert_matches!(result, Err(ReadError::IoError(_)));
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn from_slice(ref input in ip_authentication_any()) {
+        //serialize
+        let mut buffer: Vec<u8> = Vec::with_capacity(input.header_len() + 1);
+        input.write(&mut buffer).unwrap();
+        buffer.push(1);
+
+        //check the fields
+        let slice = AuthenticationHeaderSlice::from_slice(&buffer).unwrap();
+        assert_eq!(&buffer[..input.header_len()], slice.slice());
+        assert_eq!(input.next_header, slice.next_header());
+        assert_eq!((input.header_len()/4 - 2) as u8, slice.payload_len());
+        assert_eq!(input.spi, slice.spi());
+        assert_eq!(input.sequence_number, slice.sequence_number());
+        assert_eq!(input.raw_icv(), slice.raw_icv());
+        assert_eq!(input, &slice.to_header());
+
+        //check that a too small slice triggers an error
+        for len in 0..AuthenticationHeader::SERIALIZED_SIZE {
+            assert_matches!(
+                AuthenticationHeaderSlice::from_slice(&buffer[..len]),
+                Err(ReadError::UnexpectedEndOfSlice(AuthenticationHeader::SERIALIZED_SIZE))
+            );
+        }
+        if input.header_len() > AuthenticationHeader::SERIALIZED_SIZE {
+            let header_len = input.header_len();
+            assert_matches!(
+                AuthenticationHeaderSlice::from_slice(&buffer[..header_len - 1]),
+                Err(ReadError::UnexpectedEndOfSlice(value)) => assert_eq!(header_len, value)
+            );
+        }
+    }
+}
@@ -289,6 +289,130 @@ fn set_options() {
     }
 }
 
+#[test]
+fn set_option_elements() {
+    use crate::Ipv4OptionElement::*;
+    let route = [192,168,1,1, 192,168,1,2];
+    let timestamps = [0,0,0,1];
+
+    //padding
+    {
+        let mut header: Ipv4Header = Default::default();
+        assert_eq!(Ok(()), header.set_option_elements(&[Nop, RouterAlert(0x1234), Nop]));
+        assert_eq!(&[1, 148, 4, 0x12, 0x34, 1, 0, 0], header.options());
+        assert_eq!(7, header.ihl());
+
+        //empty
+        assert_eq!(Ok(()), header.set_option_elements(&[]));
+        assert_eq!(0, header.options().len());
+        assert_eq!(5, header.ihl());
+    }
+    //all elements
+    {
+        let mut header: Ipv4Header = Default::default();
+        let options = [
+            RecordRoute{ pointer: 4, route: &route[..4] },
+            LooseSourceRoute{ pointer: 4, route: &route[..4] },
+            StrictSourceRoute{ pointer: 8, route: &route[..] },
+            Timestamp{ pointer: 5, overflow: 0xf, flags: 1, data: &timestamps },
+            Security{ classification_level: 0xab, protection_authority: &[0x01] },
+            Unknown{ option_type: 0x99, data: &[] },
+            EndOfOptionsList
+        ];
+        assert_eq!(Ok(()), header.set_option_elements(&options));
+        assert_eq!(
+            &[
+                7, 7, 4, 192, 168, 1, 1,
+                131, 7, 4, 192, 168, 1, 1,
+                137, 11, 8, 192, 168, 1, 1, 192, 168, 1, 2,
+                68, 8, 5, 0xf1, 0, 0, 0, 1,
+                130, 4, 0xab, 0x01,
+                0x99, 2,
+                0
+            ][..],
+            header.options()
+        );
+        assert_eq!(
+            options.iter().map(|x| Ok(x.clone())).collect::<Vec<_>>(),
+            header.options_iterator().collect::<Vec<_>>()
+        );
+    }
+    //errors
+    {
+        use crate::ValueError::*;
+        use crate::ErrorField::*;
+        let mut header: Ipv4Header = Default::default();
+        header.set_option_elements(&[Nop]).unwrap();
+
+        let too_big = [0;38];
+        for (expected, option) in &[
+            (Ipv4OptionsLengthBad(41), Unknown{ option_type: 0x99, data: &too_big[..39 - 2] }),
+            (Ipv4OptionDataLengthBad{ option_id: 7, data_len: 3 }, RecordRoute{ pointer: 4, route: &route[..3] }),
+            (Ipv4OptionDataLengthBad{ option_id: 131, data_len: 5 }, LooseSourceRoute{ pointer: 4, route: &route[..5] }),
+            (Ipv4OptionDataLengthBad{ option_id: 137, data_len: 1 }, StrictSourceRoute{ pointer: 4, route: &route[..1] }),
+            (Ipv4OptionDataLengthBad{ option_id: 68, data_len: 2 }, Timestamp{ pointer: 5, overflow: 0, flags: 0, data: &timestamps[..2] }),
+            (U8TooLarge{ value: 0x10, max: 0xf, field: Ipv4TimestampOverflow }, Timestamp{ pointer: 5, overflow: 0x10, flags: 0, data: &[] }),
+            (U8TooLarge{ value: 0x10, max: 0xf, field: Ipv4TimestampFlags }, Timestamp{ pointer: 5, overflow: 0, flags: 0x10, data: &[] }),
+        ] {
+            assert_eq!(Err(expected.clone()), header.set_option_elements(&[Nop, Nop, option.clone()]));
+            //check the previous value was kept
+            assert_eq!(&[1, 0, 0, 0], header.options());
+        }
+    }
+}
+
+#[test]
+fn options_iterator() {
+    use crate::Ipv4OptionElement::*;
+    use crate::Ipv4OptionReadError::*;
+
+    //end of options list stops the iteration
+    {
+        let options = [1, 0, 1, 1];
+        let mut iterator = Ipv4OptionsIterator::from_slice(&options);
+        assert_eq!(Some(Ok(Nop)), iterator.next());
+        assert_eq!(&options[1..], iterator.rest());
+        assert_eq!(Some(Ok(EndOfOptionsList)), iterator.next());
+        assert_eq!(None, iterator.next());
+        assert_eq!(0, iterator.rest().len());
+    }
+    //ipv4 header & slice
+    {
+        let mut header: Ipv4Header = Default::default();
+        header.set_options(&[148, 4, 0, 0]).unwrap();
+        assert_eq!(vec![Ok(RouterAlert(0))], header.options_iterator().collect::<Vec<_>>());
+
+        let mut buffer = Vec::new();
+        header.write_raw(&mut buffer).unwrap();
+        let slice = Ipv4HeaderSlice::from_slice(&buffer).unwrap();
+        assert_eq!(vec![Ok(RouterAlert(0))], slice.options_iterator().collect::<Vec<_>>());
+    }
+    //errors
+    for (expected, options) in &[
+        //length field missing
+        (UnexpectedEndOfSlice(7), &[7][..]),
+        //length bigger then the slice
+        (UnexpectedEndOfSlice(0x99), &[0x99, 3][..]),
+        (UnexpectedEndOfSlice(148), &[148, 4, 0][..]),
+        //length too small for any option
+        (UnexpectedSize{ option_id: 0x99, size: 1 }, &[0x99, 1][..]),
+        //bad sizes
+        (UnexpectedSize{ option_id: 7, size: 2 }, &[7, 2][..]),
+        (UnexpectedSize{ option_id: 131, size: 4 }, &[131, 4, 4, 0][..]),
+        (UnexpectedSize{ option_id: 137, size: 8 }, &[137, 8, 4, 0, 0, 0, 0, 0][..]),
+        (UnexpectedSize{ option_id: 68, size: 3 }, &[68, 3, 4][..]),
+        (UnexpectedSize{ option_id: 68, size: 6 }, &[68, 6, 5, 0, 0, 0][..]),
+        (UnexpectedSize{ option_id: 148, size: 3 }, &[148, 3, 0][..]),
+        (UnexpectedSize{ option_id: 130, size: 2 }, &[130, 2][..]),
+    ] {
+        let mut iterator = Ipv4OptionsIterator::from_slice(options);
+        assert_eq!(Some(Err(expected.clone())), iterator.next());
+        //the iterator stops after an error
+        assert_eq!(None, iterator.next());
+        assert_eq!(0, iterator.rest().len());
+    }
+}
+
 #[test]
 fn calc_header_checksum() {
     let base: Ipv4Header = Ipv4Header::new(
@@ -591,3 +715,85 @@ fn slice_bad_version() {
     use crate::ReadError::*;
     assert_matches!(Ipv4HeaderSlice::from_slice(&buffer[..]), Err(Ipv4UnexpectedVersion(6)));
 }
+
+proptest! {
+    #[test]
+    fn slice_mut(ref input in ipv4_any(), ref other in ipv4_any())
+    {
+        //serialize (calculates a valid checksum)
+        let mut buffer: Vec<u8> = Vec::with_capacity(input.header_len());
+        input.write(&mut buffer).unwrap();
+
+        //errors are the same as for the read only slice
+        use self::ReadError::*;
+        assert_matches!(
+            Ipv4HeaderSliceMut::from_slice(&mut buffer[..input.header_len() - 1]),
+            Err(UnexpectedEndOfSlice(_))
+        );
+
+        let mut slice = Ipv4HeaderSliceMut::from_slice(&mut buffer).unwrap();
+        assert_eq!(input.header_len(), slice.slice().len());
+
+        //checks that the header checksum is still valid
+        fn assert_checksum(slice: &Ipv4HeaderSliceMut) {
+            assert_eq!(
+                slice.as_slice().to_header().calc_header_checksum().unwrap(),
+                slice.as_slice().header_checksum()
+            );
+        }
+        assert_checksum(&slice);
+
+        //range errors
+        {
+            use crate::ErrorField::*;
+            use crate::ValueError::*;
+            assert_eq!(Err(U8TooLarge{ value: 0x40, max: 0x3f, field: Ipv4Dscp }), slice.set_dcp(0x40));
+            assert_eq!(Err(U8TooLarge{ value: 4, max: 3, field: Ipv4Ecn }), slice.set_ecn(4));
+            assert_eq!(
+                Err(U16TooLarge{ value: 0x2000, max: 0x1fff, field: Ipv4FragmentsOffset }),
+                slice.set_fragments_offset(0x2000)
+            );
+        }
+
+        //modify all fields
+        slice.set_dcp(other.differentiated_services_code_point).unwrap();
+        assert_checksum(&slice);
+        slice.set_ecn(other.explicit_congestion_notification).unwrap();
+        assert_checksum(&slice);
+        slice.set_identification(other.identification);
+        assert_checksum(&slice);
+        slice.set_dont_fragment(other.dont_fragment);
+        assert_checksum(&slice);
+        slice.set_more_fragments(other.more_fragments);
+        assert_checksum(&slice);
+        slice.set_fragments_offset(other.fragments_offset).unwrap();
+        assert_checksum(&slice);
+        slice.set_ttl(other.time_to_live);
+        assert_checksum(&slice);
+        slice.set_protocol(other.protocol);
+        assert_checksum(&slice);
+        slice.set_source(other.source);
+        assert_checksum(&slice);
+        slice.set_destination(other.destination);
+        assert_checksum(&slice);
+
+        let result = slice.as_slice().to_header();
+        assert_eq!(other.differentiated_services_code_point, result.differentiated_services_code_point);
+        assert_eq!(other.explicit_congestion_notification, result.explicit_congestion_notification);
+        assert_eq!(other.identification, result.identification);
+        assert_eq!(other.dont_fragment, result.dont_fragment);
+        assert_eq!(other.more_fragments, result.more_fragments);
+        assert_eq!(other.fragments_offset, result.fragments_offset);
+        assert_eq!(other.time_to_live, result.time_to_live);
+        assert_eq!(other.protocol, result.protocol);
+        assert_eq!(other.source, result.source);
+        assert_eq!(other.destination, result.destination);
+        //not modified
+        assert_eq!(input.payload_len, result.payload_len);
+        assert_eq!(input.options(), result.options());
+
+        //raw checksum
+        slice.set_header_checksum(0x1234);
+        assert_eq!(0x1234, slice.as_slice().header_checksum());
+    }
+}
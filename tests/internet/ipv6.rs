@@ -152,13 +152,14 @@ fn skip_all_extensions() {
     use crate::IpTrafficClass::*;
     //based on RFC 8200 4.1. Extension Header Order
     // & IANA https://www.iana.org/assignments/ipv6-parameters/ipv6-parameters.xhtml
+    // (the esp header is not included as it ends the header extensions)
     const EXTENSION_IDS: [u8;12] = [
         IPv6HeaderHopByHop as u8,
         IPv6DestinationOptions as u8,
         IPv6RouteHeader as u8,
         IPv6FragmentationHeader as u8, //3
         IPv6AuthenticationHeader as u8,
-        IPv6EncapSecurityPayload as u8,
+        IPv6AuthenticationHeader as u8,
         IPv6DestinationOptions as u8,
         MobilityHeader as u8,
         Hip as u8,
@@ -191,6 +192,9 @@ fn skip_all_extensions() {
                     let len = if i == IPv6FragmentationHeader as u8 {
                         //fragmentation header has a fixed size
                         8
+                    } else if i == IPv6AuthenticationHeader as u8 {
+                        //authentication header length is in 4 octet units
+                        (2 + 2)*4
                     } else {
                         buffer.len() - 8
                     };
@@ -213,6 +217,7 @@ fn skip_all_extensions() {
     //creates an buffer filled with extension headers with the given ids
     fn create_buffer(ids: &[u8]) -> Vec<u8> {
         const FRAG: u8 = IPv6FragmentationHeader as u8;
+        const AUTH: u8 = IPv6AuthenticationHeader as u8;
         let mut prev: u8 = ids[0];
         let mut result = Vec::with_capacity(ids.len()*8*4);
         for (index, value) in ids[1..].iter().enumerate() {
@@ -222,8 +227,13 @@ fn skip_all_extensions() {
                 (index % 3) as u8
             };
 
-            //write first line
-            result.extend_from_slice(&[*value, len, 0, 0,  0, 0, 0, 0]);
+            //write first line (authentication header length is in 4 octet units)
+            let len_field = if prev == AUTH {
+                len*2
+            } else {
+                len
+            };
+            result.extend_from_slice(&[*value, len_field, 0, 0,  0, 0, 0, 0]);
             
             //fill rest with dummy data
             for _ in 0..len {
@@ -288,6 +298,25 @@ fn skip_all_extensions() {
             assert_matches!(result, Err(ReadError::Ipv6TooManyHeaderExtensions));
         }
     }
+    //the esp header ends the header extensions
+    {
+        const ESP: u8 = IPv6EncapSecurityPayload as u8;
+        let buffer = create_buffer(&[IPv6HeaderHopByHop as u8, ESP, UDP]);
+
+        //reader
+        {
+            let mut cursor = Cursor::new(&buffer);
+            let result = Ipv6Header::skip_all_header_extensions(&mut cursor, IPv6HeaderHopByHop as u8);
+            assert_matches!(result, Ok(ESP));
+            assert_eq!(8, cursor.position());
+        }
+        //slice
+        {
+            let result = Ipv6Header::skip_all_header_extensions_in_slice(&buffer, IPv6HeaderHopByHop as u8).unwrap();
+            assert_eq!(result.0, ESP);
+            assert_eq!(result.1, &buffer[8..]);
+        }
+    }
     //trigger missing unexpected eof
     {
         let ids = {
@@ -402,13 +431,18 @@ fn extension_from_slice() {
         assert_eq!(slice.next_header(), UDP);
         assert_eq!(slice.slice(), &buffer[..8])
     }
+    //authentication header (length field in 4 octet units)
+    {
+        let slice = Ipv6ExtensionHeaderSlice::from_slice(IPv6AuthenticationHeader as u8, &buffer).unwrap();
+        assert_eq!(slice.next_header(), UDP);
+        assert_eq!(slice.slice(), &buffer[..16])
+    }
     //other headers (using length field)
     {
-        const EXTENSION_IDS_WITH_LENGTH: [u8;5] = [
+        const EXTENSION_IDS_WITH_LENGTH: [u8;4] = [
             IPv6HeaderHopByHop as u8,
             IPv6DestinationOptions as u8,
             IPv6RouteHeader as u8,
-            IPv6AuthenticationHeader as u8,
             IPv6EncapSecurityPayload as u8
         ];
         for id in EXTENSION_IDS_WITH_LENGTH.iter() {
@@ -426,12 +460,11 @@ fn extension_from_slice_bad_length() {
     use self::ReadError::*;
     const FRAG: u8 = IPv6FragmentationHeader as u8;
     const UDP: u8 = Udp as u8;
-    //all extension headers that use the length field
-    const EXTENSION_IDS_WITH_LENGTH: [u8;5] = [
+    //all extension headers that use the length field (in 8 octet units)
+    const EXTENSION_IDS_WITH_LENGTH: [u8;4] = [
         IPv6HeaderHopByHop as u8,
         IPv6DestinationOptions as u8,
         IPv6RouteHeader as u8,
-        IPv6AuthenticationHeader as u8,
         IPv6EncapSecurityPayload as u8
     ];
 
@@ -461,5 +494,47 @@ fn extension_from_slice_bad_length() {
             let slice = Ipv6ExtensionHeaderSlice::from_slice(*id, &buffer);
             assert_matches!(slice, Err(UnexpectedEndOfSlice(_)));
         }
+        //authentication header (length field in 4 octet units)
+        assert_matches!(Ipv6ExtensionHeaderSlice::from_slice(IPv6AuthenticationHeader as u8, &buffer[..15]),
+                        Err(UnexpectedEndOfSlice(16)));
+    }
+}
+
+proptest! {
+    #[test]
+    fn slice_mut(ref input in ipv6_any(), ref other in ipv6_any()) {
+        //serialize
+        let mut buffer: Vec<u8> = Vec::with_capacity(Ipv6Header::SERIALIZED_SIZE);
+        input.write(&mut buffer).unwrap();
+
+        //check that a too small slice triggers an error
+        assert_matches!(
+            Ipv6HeaderSliceMut::from_slice(&mut buffer[..Ipv6Header::SERIALIZED_SIZE - 1]),
+            Err(ReadError::UnexpectedEndOfSlice(Ipv6Header::SERIALIZED_SIZE))
+        );
+
+        let mut slice = Ipv6HeaderSliceMut::from_slice(&mut buffer).unwrap();
+        assert_eq!(input, &slice.as_slice().to_header());
+
+        //range error
+        assert_eq!(
+            Err(ValueError::U32TooLarge{ value: 0x100000, max: 0xfffff, field: ErrorField::Ipv6FlowLabel }),
+            slice.set_flow_label(0x100000)
+        );
+
+        //modify
+        slice.set_traffic_class(other.traffic_class);
+        slice.set_flow_label(other.flow_label).unwrap();
+        slice.set_hop_limit(other.hop_limit);
+        slice.set_source(other.source);
+        slice.set_destination(other.destination);
+
+        let expected = Ipv6Header {
+            payload_length: input.payload_length,
+            next_header: input.next_header,
+            ..other.clone()
+        };
+        assert_eq!(expected, slice.as_slice().to_header());
+        assert_eq!(6, slice.as_slice().version());
     }
 }
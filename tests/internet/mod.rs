@@ -1,3 +1,6 @@
 pub mod ip;
 pub mod ipv4;
-pub mod ipv6;
\ No newline at end of file
+pub mod ipv6;
+pub mod ip_authentication_header;
+pub mod esp;
+pub mod fragmentation;
@@ -8,6 +8,7 @@ fn ether_type_convert() {
     assert_eq!(0x86dd, Ipv6 as u16);
     assert_eq!(0x0806, Arp as u16);
     assert_eq!(0x0842, WakeOnLan as u16);
+    assert_eq!(0x6558, TransparentEthernetBridging as u16);
     assert_eq!(0x8100, VlanTaggedFrame as u16);
     assert_eq!(0x88A8, ProviderBridging as u16);
     assert_eq!(0x9100, VlanDoubleTaggedFrame as u16);
@@ -16,6 +17,7 @@ fn ether_type_convert() {
     assert_eq!(EtherType::from_u16(0x86dd), Some(Ipv6));
     assert_eq!(EtherType::from_u16(0x0806), Some(Arp));
     assert_eq!(EtherType::from_u16(0x0842), Some(WakeOnLan));
+    assert_eq!(EtherType::from_u16(0x6558), Some(TransparentEthernetBridging));
     assert_eq!(EtherType::from_u16(0x8100), Some(VlanTaggedFrame));
     assert_eq!(EtherType::from_u16(0x88A8), Some(ProviderBridging));
     assert_eq!(EtherType::from_u16(0x9100), Some(VlanDoubleTaggedFrame));
@@ -109,4 +111,35 @@ proptest! {
         //check that the to header method also returns the original struct
         assert_eq!(input, &slice.to_header());
     }
-}
\ No newline at end of file
+}
+proptest! {
+    #[test]
+    fn slice_mut(ref input in ethernet_2_any(), ref other in ethernet_2_any()) {
+
+        //serialize
+        let mut buffer: Vec<u8> = Vec::with_capacity(15);
+        input.write(&mut buffer).unwrap();
+        buffer.push(1);
+
+        //check that a too small slice results in an error
+        use crate::ReadError::*;
+        assert_matches!(
+            Ethernet2HeaderSliceMut::from_slice(&mut buffer[..13]),
+            Err(UnexpectedEndOfSlice(Ethernet2Header::SERIALIZED_SIZE))
+        );
+
+        //read
+        let mut slice = Ethernet2HeaderSliceMut::from_slice(&mut buffer).unwrap();
+        assert_eq!(Ethernet2Header::SERIALIZED_SIZE, slice.slice().len());
+        assert_eq!(input, &slice.as_slice().to_header());
+
+        //modify
+        slice.set_destination(other.destination);
+        slice.set_source(other.source);
+        slice.set_ether_type(other.ether_type);
+        assert_eq!(other, &slice.as_slice().to_header());
+
+        //data after the header is untouched
+        assert_eq!(1, buffer[Ethernet2Header::SERIALIZED_SIZE]);
+    }
+}
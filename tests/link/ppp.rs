@@ -0,0 +1,46 @@
+use etherparse::*;
+
+#[test]
+fn from_slice() {
+    //address, control & uncompressed protocol
+    {
+        let buffer = [0xff, 0x03, 0x00, 0x21, 1, 2];
+        let slice = PppHeaderSlice::from_slice(&buffer).unwrap();
+        assert_eq!(&buffer[..4], slice.slice());
+        assert_eq!(true, slice.has_address_and_control());
+        assert_eq!(PPP_PROTOCOL_IPV4, slice.protocol());
+    }
+    //address, control & compressed protocol
+    {
+        let buffer = [0xff, 0x03, 0x57, 1, 2];
+        let slice = PppHeaderSlice::from_slice(&buffer).unwrap();
+        assert_eq!(&buffer[..3], slice.slice());
+        assert_eq!(true, slice.has_address_and_control());
+        assert_eq!(PPP_PROTOCOL_IPV6, slice.protocol());
+    }
+    //uncompressed protocol only
+    {
+        let buffer = [0xc0, 0x21, 1, 2];
+        let slice = PppHeaderSlice::from_slice(&buffer).unwrap();
+        assert_eq!(&buffer[..2], slice.slice());
+        assert_eq!(false, slice.has_address_and_control());
+        assert_eq!(0xc021, slice.protocol());
+    }
+    //compressed protocol only
+    {
+        let buffer = [0x21, 1, 2];
+        let slice = PppHeaderSlice::from_slice(&buffer).unwrap();
+        assert_eq!(&buffer[..1], slice.slice());
+        assert_eq!(false, slice.has_address_and_control());
+        assert_eq!(PPP_PROTOCOL_IPV4, slice.protocol());
+    }
+}
+
+#[test]
+fn from_slice_unexpected_end() {
+    use crate::ReadError::*;
+    assert_matches!(PppHeaderSlice::from_slice(&[]), Err(UnexpectedEndOfSlice(1)));
+    assert_matches!(PppHeaderSlice::from_slice(&[0x00]), Err(UnexpectedEndOfSlice(2)));
+    assert_matches!(PppHeaderSlice::from_slice(&[0xff, 0x03]), Err(UnexpectedEndOfSlice(3)));
+    assert_matches!(PppHeaderSlice::from_slice(&[0xff, 0x03, 0x00]), Err(UnexpectedEndOfSlice(4)));
+}
@@ -270,3 +270,101 @@ fn double_from_slice() {
     //check that the to_header results in the same as the input
     assert_eq!(slice.to_header(), input);
 }
+
+#[test]
+fn single_slice_mut() {
+    let input = SingleVlanHeader {
+        ether_type: EtherType::Ipv4 as u16,
+        priority_code_point: 2,
+        drop_eligible_indicator: true,
+        vlan_identifier: 1234,
+    };
+    let expected = SingleVlanHeader {
+        ether_type: EtherType::Ipv6 as u16,
+        priority_code_point: 7,
+        drop_eligible_indicator: false,
+        vlan_identifier: 4095,
+    };
+
+    //write it
+    let mut buffer = Vec::<u8>::new();
+    input.write(&mut buffer).unwrap();
+
+    //check that a too small slice results in an error
+    use self::ReadError::UnexpectedEndOfSlice;
+    assert_matches!(
+        SingleVlanHeaderSliceMut::from_slice(&mut buffer[..3]),
+        Err(UnexpectedEndOfSlice(SingleVlanHeader::SERIALIZED_SIZE))
+    );
+
+    let mut slice = SingleVlanHeaderSliceMut::from_slice(&mut buffer).unwrap();
+    assert_eq!(input, slice.as_slice().to_header());
+
+    //range errors
+    use crate::ErrorField::*;
+    assert_eq!(
+        Err(ValueError::U8TooLarge{ value: 8, max: 7, field: VlanTagPriorityCodePoint }),
+        slice.set_priority_code_point(8)
+    );
+    assert_eq!(
+        Err(ValueError::U16TooLarge{ value: 0x1000, max: 0xfff, field: VlanTagVlanId }),
+        slice.set_vlan_identifier(0x1000)
+    );
+    assert_eq!(input, slice.as_slice().to_header());
+
+    //modify
+    slice.set_priority_code_point(expected.priority_code_point).unwrap();
+    slice.set_drop_eligible_indicator(expected.drop_eligible_indicator);
+    slice.set_vlan_identifier(expected.vlan_identifier).unwrap();
+    slice.set_ether_type(expected.ether_type);
+    assert_eq!(expected, slice.as_slice().to_header());
+
+    slice.set_drop_eligible_indicator(true);
+    assert!(slice.as_slice().drop_eligible_indicator());
+}
+
+#[test]
+fn double_slice_mut() {
+    let input = DoubleVlanHeader {
+        outer: SingleVlanHeader {
+            ether_type: EtherType::VlanTaggedFrame as u16,
+            priority_code_point: 2,
+            drop_eligible_indicator: true,
+            vlan_identifier: 1234,
+        },
+        inner: SingleVlanHeader {
+            ether_type: EtherType::Ipv6 as u16,
+            priority_code_point: 7,
+            drop_eligible_indicator: false,
+            vlan_identifier: 4095,
+        }
+    };
+
+    //write it
+    let mut buffer = Vec::<u8>::new();
+    input.write(&mut buffer).unwrap();
+
+    //errors
+    {
+        use self::ReadError::*;
+        assert_matches!(
+            DoubleVlanHeaderSliceMut::from_slice(&mut buffer[..7]),
+            Err(UnexpectedEndOfSlice(DoubleVlanHeader::SERIALIZED_SIZE))
+        );
+        let mut bad_outer = buffer.clone();
+        bad_outer[2..4].copy_from_slice(&(EtherType::Ipv4 as u16).to_be_bytes());
+        assert_matches!(
+            DoubleVlanHeaderSliceMut::from_slice(&mut bad_outer),
+            Err(VlanDoubleTaggingUnexpectedOuterTpid(0x0800))
+        );
+    }
+
+    let mut slice = DoubleVlanHeaderSliceMut::from_slice(&mut buffer).unwrap();
+    assert_eq!(input, slice.as_slice().to_header());
+
+    slice.outer_mut().set_vlan_identifier(1).unwrap();
+    slice.inner_mut().set_vlan_identifier(2).unwrap();
+    assert_eq!(1, slice.as_slice().outer().vlan_identifier());
+    assert_eq!(2, slice.as_slice().inner().vlan_identifier());
+    assert_eq!(input.inner.ether_type, slice.as_slice().inner().ether_type());
+}
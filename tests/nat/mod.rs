@@ -0,0 +1,322 @@
+use super::*;
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use crate::test_packets::*;
+
+#[test]
+fn ipv4_tcp() {
+    let mut packet = Vec::new();
+    PacketBuilder::ipv4([10,0,0,1], [192,168,1,2], 20)
+        .tcp(1234, 80, 1, 1024)
+        .write(&mut packet, &[1,2,3,4,5])
+        .unwrap();
+
+    //source nat
+    SlicedPacketMut::from_ip(&mut packet).unwrap()
+        .apply_nat(&NatRewrite {
+            source: Some(Ipv4Addr::new(1,2,3,4).into()),
+            source_port: Some(40000),
+            ..Default::default()
+        })
+        .unwrap();
+
+    let mut expected = Vec::new();
+    PacketBuilder::ipv4([1,2,3,4], [192,168,1,2], 20)
+        .tcp(40000, 80, 1, 1024)
+        .write(&mut expected, &[1,2,3,4,5])
+        .unwrap();
+    assert_eq!(expected, packet);
+
+    //destination nat
+    SlicedPacketMut::from_ip(&mut packet).unwrap()
+        .apply_nat(&NatRewrite {
+            destination: Some(Ipv4Addr::new(10,0,0,2).into()),
+            destination_port: Some(8080),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_valid_checksums(&packet);
+    let sliced = SlicedPacket::from_ip(&packet).unwrap();
+    assert_matches!(&sliced.ip, Some(InternetSlice::Ipv4(value)) => assert_eq!([10,0,0,2], value.destination()));
+    assert_matches!(&sliced.transport, Some(TransportSlice::Tcp(value)) => assert_eq!(8080, value.destination_port()));
+}
+
+#[test]
+fn ipv4_udp() {
+    let mut packet = Vec::new();
+    PacketBuilder::ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+        .ipv4([10,0,0,1], [192,168,1,2], 20)
+        .udp(1234, 53)
+        .write(&mut packet, &[1,2,3,4,5])
+        .unwrap();
+
+    let rewrite = NatRewrite {
+        source: Some(Ipv4Addr::new(1,2,3,4).into()),
+        destination: Some(Ipv4Addr::new(8,8,8,8).into()),
+        source_port: Some(40000),
+        destination_port: Some(5353)
+    };
+    SlicedPacketMut::from_ethernet(&mut packet).unwrap().apply_nat(&rewrite).unwrap();
+
+    let mut expected = Vec::new();
+    PacketBuilder::ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+        .ipv4([1,2,3,4], [8,8,8,8], 20)
+        .udp(40000, 5353)
+        .write(&mut expected, &[1,2,3,4,5])
+        .unwrap();
+    assert_eq!(expected, packet);
+
+    //udp without checksum
+    {
+        let udp = UdpHeader::without_ipv4_checksum(1234, 53, 5).unwrap();
+        let mut payload = Vec::new();
+        udp.write(&mut payload).unwrap();
+        payload.extend_from_slice(&[1,2,3,4,5]);
+        let mut packet = ipv4(IpTrafficClass::Udp, [10,0,0,1], [192,168,1,2], &payload);
+
+        SlicedPacketMut::from_ip(&mut packet).unwrap().apply_nat(&rewrite).unwrap();
+
+        let sliced = SlicedPacket::from_ip(&packet).unwrap();
+        assert_matches!(&sliced.transport, Some(TransportSlice::Udp(value)) => {
+            assert_eq!(0, value.checksum());
+            assert_eq!(40000, value.source_port());
+            assert_eq!(5353, value.destination_port());
+        });
+        assert_eq!(
+            PacketChecksums{ ip: ChecksumStatus::Valid, transport: ChecksumStatus::NotPresent },
+            sliced.verify_checksums()
+        );
+    }
+}
+
+#[test]
+fn ipv6_tcp_udp() {
+    let source = [1;16];
+    let destination = [2;16];
+    let rewrite = NatRewrite {
+        source: Some(Ipv6Addr::from([3;16]).into()),
+        destination: Some(Ipv6Addr::from([4;16]).into()),
+        source_port: Some(40000),
+        destination_port: None
+    };
+
+    //tcp
+    {
+        let mut packet = Vec::new();
+        PacketBuilder::ipv6(source, destination, 20)
+            .tcp(1234, 80, 1, 1024)
+            .write(&mut packet, &[1,2,3])
+            .unwrap();
+        SlicedPacketMut::from_ip(&mut packet).unwrap().apply_nat(&rewrite).unwrap();
+
+        let mut expected = Vec::new();
+        PacketBuilder::ipv6([3;16], [4;16], 20)
+            .tcp(40000, 80, 1, 1024)
+            .write(&mut expected, &[1,2,3])
+            .unwrap();
+        assert_eq!(expected, packet);
+    }
+    //udp behind an extension header
+    {
+        let mut udp = UdpHeader::with_ipv6_checksum(1234, 53, &Ipv6Header{
+            traffic_class: 0,
+            flow_label: 0,
+            payload_length: 0,
+            next_header: 0,
+            hop_limit: 0,
+            source,
+            destination
+        }, &[1,2,3]).unwrap();
+        let mut payload = vec![IpTrafficClass::Udp as u8, 0, 0, 0, 0, 0, 0, 0];
+        udp.write(&mut payload).unwrap();
+        payload.extend_from_slice(&[1,2,3]);
+        let mut packet = ipv6(IpTrafficClass::IPv6DestinationOptions as u8, source, destination, &payload);
+
+        SlicedPacketMut::from_ip(&mut packet).unwrap().apply_nat(&rewrite).unwrap();
+
+        let sliced = SlicedPacket::from_ip(&packet).unwrap();
+        assert_eq!(ChecksumStatus::Valid, sliced.verify_checksums().transport);
+        udp.source_port = 40000;
+        assert_matches!(&sliced.transport, Some(TransportSlice::Udp(value)) => assert_eq!(udp.source_port, value.source_port()));
+    }
+}
+
+#[test]
+fn icmpv4_error() {
+    //original packet sent by the internal host & the same packet after the source nat
+    let mut original = Vec::new();
+    PacketBuilder::ipv4([10,0,0,1], [8,8,8,8], 20)
+        .udp(1234, 53)
+        .write(&mut original, &[1,2,3,4,5,6,7,8])
+        .unwrap();
+    let mut translated = Vec::new();
+    PacketBuilder::ipv4([1,2,3,4], [8,8,8,8], 20)
+        .udp(40000, 53)
+        .write(&mut translated, &[1,2,3,4,5,6,7,8])
+        .unwrap();
+
+    //port unreachable sent by a router quoting the ip header & the first 8 bytes of the translated packet
+    let quoted_len = Ipv4Header::SERIALIZED_SIZE + 8;
+    let mut packet = ipv4(
+        IpTrafficClass::Icmp,
+        [9,9,9,9],
+        [1,2,3,4],
+        &icmpv4(3, 3, [0;4], &translated[..quoted_len])
+    );
+
+    //reverse translation of the packet
+    SlicedPacketMut::from_ip(&mut packet).unwrap()
+        .apply_nat(&NatRewrite {
+            destination: Some(Ipv4Addr::new(10,0,0,1).into()),
+            destination_port: Some(1234),
+            ..Default::default()
+        })
+        .unwrap();
+
+    let sliced = SlicedPacket::from_ip(&packet).unwrap();
+    assert_eq!(ChecksumStatus::Valid, sliced.verify_checksums().ip);
+    assert_matches!(&sliced.ip, Some(InternetSlice::Ipv4(value)) => {
+        assert_eq!([9,9,9,9], value.source());
+        assert_eq!([10,0,0,1], value.destination());
+    });
+    assert_icmpv4_checksum(&packet);
+
+    //the quoted packet is identical to the original packet (including the checksums)
+    assert_eq!(&original[..quoted_len], &sliced.payload[8..]);
+}
+
+#[test]
+fn icmpv6_error() {
+    //original packet sent by the internal host & the same packet after the destination nat
+    let mut original = Vec::new();
+    PacketBuilder::ipv6([5;16], [1;16], 20)
+        .tcp(1234, 80, 1, 1024)
+        .write(&mut original, &[1,2,3,4])
+        .unwrap();
+    let mut translated = Vec::new();
+    PacketBuilder::ipv6([5;16], [2;16], 20)
+        .tcp(1234, 8080, 1, 1024)
+        .write(&mut translated, &[1,2,3,4])
+        .unwrap();
+
+    //packet too big sent by the internal host quoting the complete translated packet
+    let icmp = icmpv6([2;16], [5;16], 2, 0, &translated);
+    let mut packet = ipv6(58, [2;16], [5;16], &icmp);
+
+    SlicedPacketMut::from_ip(&mut packet).unwrap()
+        .apply_nat(&NatRewrite {
+            source: Some(Ipv6Addr::from([1;16]).into()),
+            source_port: Some(80),
+            ..Default::default()
+        })
+        .unwrap();
+
+    let sliced = SlicedPacket::from_ip(&packet).unwrap();
+    assert_matches!(&sliced.ip, Some(InternetSlice::Ipv6(value, _)) => assert_eq!([1;16], value.source()));
+    assert_icmpv6_checksum(&packet);
+    assert_eq!(&original[..], &sliced.payload[8..]);
+}
+
+#[test]
+fn icmp_query() {
+    //icmpv4 echo request (only the addresses are modified)
+    {
+        let icmp = icmpv4(8, 0, [0;4], &[1,2,3,4]);
+        let mut packet = ipv4(IpTrafficClass::Icmp, [10,0,0,1], [8,8,8,8], &icmp);
+        SlicedPacketMut::from_ip(&mut packet).unwrap()
+            .apply_nat(&NatRewrite {
+                source: Some(Ipv4Addr::new(1,2,3,4).into()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(ipv4(IpTrafficClass::Icmp, [1,2,3,4], [8,8,8,8], &icmp), packet);
+    }
+    //icmpv6 echo request (the checksum contains the pseudo header)
+    {
+        let icmp = icmpv6([1;16], [2;16], 128, 0, &[1,2,3,4]);
+        let mut packet = ipv6(58, [1;16], [2;16], &icmp);
+        SlicedPacketMut::from_ip(&mut packet).unwrap()
+            .apply_nat(&NatRewrite {
+                source: Some(Ipv6Addr::from([3;16]).into()),
+                ..Default::default()
+            })
+            .unwrap();
+        let expected = icmpv6([3;16], [2;16], 128, 0, &[1,2,3,4]);
+        assert_eq!(ipv6(58, [3;16], [2;16], &expected), packet);
+    }
+}
+
+#[test]
+fn fragments() {
+    //the "udp header" of a non first fragment is data & is not modified
+    let mut packet = {
+        let mut header = Ipv4Header::new(8, 20, IpTrafficClass::Udp, [10,0,0,1], [8,8,8,8]);
+        header.fragments_offset = 10;
+        let mut result = Vec::new();
+        header.write(&mut result).unwrap();
+        result.extend_from_slice(&[1,2,3,4,5,6,7,8]);
+        result
+    };
+    let mut sliced = SlicedPacketMut::from_ip(&mut packet).unwrap();
+    assert_eq!(
+        Err(NatError::PortsNotPresent),
+        sliced.apply_nat(&NatRewrite{ source_port: Some(1), ..Default::default() })
+    );
+    sliced.apply_nat(&NatRewrite{ source: Some(Ipv4Addr::new(1,2,3,4).into()), ..Default::default() }).unwrap();
+
+    let sliced = SlicedPacket::from_ip(&packet).unwrap();
+    assert_eq!(ChecksumStatus::Valid, sliced.verify_checksums().ip);
+    assert_eq!(&[1,2,3,4,5,6,7,8], &packet[Ipv4Header::SERIALIZED_SIZE..]);
+}
+
+#[test]
+fn errors() {
+    use crate::NatError::*;
+
+    let v4: IpAddr = Ipv4Addr::new(1,2,3,4).into();
+    let v6: IpAddr = Ipv6Addr::from([1;16]).into();
+
+    //no ip header
+    {
+        let mut packet = Vec::new();
+        Ethernet2Header{
+            source: [1;6],
+            destination: [2;6],
+            ether_type: EtherType::Arp as u16
+        }.write(&mut packet).unwrap();
+        let mut sliced = SlicedPacketMut::from_ethernet(&mut packet).unwrap();
+        assert_eq!(Ok(()), sliced.apply_nat(&Default::default()));
+        assert_eq!(Err(NoIpHeader), sliced.apply_nat(&NatRewrite{ source: Some(v4), ..Default::default() }));
+    }
+
+    //version mismatch & ports not present (packet stays unchanged)
+    {
+        let original = ipv4(IpTrafficClass::Icmp, [10,0,0,1], [8,8,8,8], &icmpv4(8, 0, [0;4], &[]));
+        let mut packet = original.clone();
+        let mut sliced = SlicedPacketMut::from_ip(&mut packet).unwrap();
+        assert_eq!(Err(IpVersionMismatch), sliced.apply_nat(&NatRewrite{ source: Some(v4), destination: Some(v6), ..Default::default() }));
+        assert_eq!(Err(PortsNotPresent), sliced.apply_nat(&NatRewrite{ source: Some(v4), source_port: Some(1), ..Default::default() }));
+        assert_eq!(original, packet);
+    }
+    {
+        let mut packet = ipv6(58, [1;16], [2;16], &icmpv6([1;16], [2;16], 128, 0, &[]));
+        let mut sliced = SlicedPacketMut::from_ip(&mut packet).unwrap();
+        assert_eq!(Err(IpVersionMismatch), sliced.apply_nat(&NatRewrite{ destination: Some(v4), ..Default::default() }));
+    }
+
+    //icmp error quoting a packet without ports
+    {
+        let quoted = ipv4(IpTrafficClass::Icmp, [1,2,3,4], [8,8,8,8], &icmpv4(8, 0, [0;4], &[]));
+        let mut packet = ipv4(IpTrafficClass::Icmp, [9,9,9,9], [1,2,3,4], &icmpv4(11, 0, [0;4], &quoted));
+        let mut sliced = SlicedPacketMut::from_ip(&mut packet).unwrap();
+        assert_eq!(Err(PortsNotPresent), sliced.apply_nat(&NatRewrite{ destination_port: Some(1), ..Default::default() }));
+        //addresses can still be rewritten
+        sliced.apply_nat(&NatRewrite{ destination: Some(Ipv4Addr::new(10,0,0,1).into()), ..Default::default() }).unwrap();
+        let sliced = SlicedPacket::from_ip(&packet).unwrap();
+        assert_icmpv4_checksum(&packet);
+        assert_eq!(
+            &ipv4(IpTrafficClass::Icmp, [10,0,0,1], [8,8,8,8], &icmpv4(8, 0, [0;4], &[]))[..],
+            &sliced.payload[8..]
+        );
+    }
+}
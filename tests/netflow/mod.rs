@@ -0,0 +1,494 @@
+use super::*;
+use etherparse::flow::*;
+use etherparse::netflow::*;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+fn exporter() -> SocketAddr {
+    "10.0.0.1:50000".parse().unwrap()
+}
+
+///Appends a netflow v9 or ipfix set with the given id.
+fn set(target: &mut Vec<u8>, set_id: u16, content: &[u8]) {
+    target.extend_from_slice(&set_id.to_be_bytes());
+    target.extend_from_slice(&((content.len() + 4) as u16).to_be_bytes());
+    target.extend_from_slice(content);
+}
+
+///Netflow v9 header with the given source id.
+fn netflow_v9_header(source_id: u32) -> Vec<u8> {
+    let mut result = vec![0, 9, 0, 2];
+    result.extend_from_slice(&1000u32.to_be_bytes());
+    result.extend_from_slice(&1_600_000_000u32.to_be_bytes());
+    result.extend_from_slice(&7u32.to_be_bytes());
+    result.extend_from_slice(&source_id.to_be_bytes());
+    result
+}
+
+#[test]
+fn netflow_v5() {
+    let mut packet = vec![
+        0, 5, 0, 2,
+        0, 0, 0x03, 0xe8, //sys uptime
+        0x5f, 0x5e, 0x10, 0x00, //unix secs
+        0, 0, 0, 1, //unix nsecs
+        0, 0, 0, 100, //flow sequence
+        1, 2, //engine type & id
+        0x40, 0x0a, //sampling interval
+    ];
+    for index in 0..2u8 {
+        packet.extend_from_slice(&[
+            192, 168, 1, index, //source
+            192, 168, 2, 1, //destination
+            192, 168, 0, 1, //next hop
+            0, 1, 0, 2, //input & output interface
+            0, 0, 0, 10, //packets
+            0, 0, 0x10, 0, //bytes
+            0, 0, 0, 100, //first
+            0, 0, 0, 200, //last
+            0x04, 0xd2, 0, 80, //ports
+            0, 0x1b, 6, 0x10, //pad, tcp flags, protocol & tos
+            0, 1, 0, 2, //source & destination as
+            24, 16, 0, 0, //masks & pad
+        ]);
+    }
+    let expected_record = |index| NetflowV5Record {
+        source: [192, 168, 1, index],
+        destination: [192, 168, 2, 1],
+        next_hop: [192, 168, 0, 1],
+        input_interface: 1,
+        output_interface: 2,
+        packets: 10,
+        bytes: 0x1000,
+        first: 100,
+        last: 200,
+        source_port: 1234,
+        destination_port: 80,
+        tcp_flags: 0x1b,
+        protocol: 6,
+        tos: 0x10,
+        source_as: 1,
+        destination_as: 2,
+        source_mask: 24,
+        destination_mask: 16,
+    };
+    let expected = NetflowV5Packet {
+        header: NetflowV5Header {
+            count: 2,
+            sys_uptime: 1000,
+            unix_secs: 0x5f5e_1000,
+            unix_nsecs: 1,
+            flow_sequence: 100,
+            engine_type: 1,
+            engine_id: 2,
+            sampling_interval: 0x400a,
+        },
+        records: vec![expected_record(0), expected_record(1)],
+    };
+    assert_eq!(Ok(expected.clone()), NetflowV5Packet::from_slice(&packet));
+    assert_eq!(Ok(ExportPacket::NetflowV5(expected)), ExportDecoder::new().decode(exporter(), &packet));
+
+    //errors
+    assert_eq!(Err(NetflowError::UnexpectedEndOfSlice(24 + 2*48)), NetflowV5Packet::from_slice(&packet[..packet.len() - 1]));
+    assert_eq!(Err(NetflowError::UnexpectedEndOfSlice(24)), NetflowV5Packet::from_slice(&packet[..23]));
+    packet[1] = 9;
+    assert_eq!(Err(NetflowError::UnsupportedVersion(9)), NetflowV5Packet::from_slice(&packet));
+}
+
+#[test]
+fn netflow_v9() {
+    use self::InformationElement::*;
+
+    let template = Template {
+        id: 300,
+        scope_field_count: 0,
+        fields: vec![
+            FieldSpecifier::new(SourceIpv4Address, 4),
+            FieldSpecifier::new(DestinationTransportPort, 2),
+            FieldSpecifier::new(PacketDeltaCount, 4),
+            //unknown element
+            FieldSpecifier{ element_id: 1000, length: 3, enterprise_number: None },
+        ],
+    };
+    let mut template_set = vec![1, 44, 0, 4];
+    for field in &template.fields {
+        template_set.extend_from_slice(&field.element_id.to_be_bytes());
+        template_set.extend_from_slice(&field.length.to_be_bytes());
+    }
+    let mut data_set = Vec::new();
+    for index in 0..2u8 {
+        data_set.extend_from_slice(&[10, 0, 0, index, 0, 80, 0, 0, 1, 0, 1, 2, 3]);
+    }
+    //padding
+    data_set.extend_from_slice(&[0, 0]);
+
+    let mut decoder = ExportDecoder::new();
+
+    //data set before the template is known
+    let mut packet = netflow_v9_header(1);
+    set(&mut packet, 300, &data_set);
+    let message = match decoder.decode(exporter(), &packet).unwrap() {
+        ExportPacket::NetflowV9(value) => value,
+        value => panic!("unexpected packet {:?}", value)
+    };
+    assert_eq!(
+        ExportMessage {
+            export_time: 1_600_000_000,
+            sequence_number: 7,
+            observation_domain_id: 1,
+            sys_uptime: Some(1000),
+            templates: Vec::new(),
+            records: Vec::new(),
+            missing_templates: vec![300],
+        },
+        message
+    );
+
+    //template & data in the same packet
+    let mut packet = netflow_v9_header(1);
+    set(&mut packet, 0, &template_set);
+    set(&mut packet, 300, &data_set);
+    let message = match decoder.decode(exporter(), &packet).unwrap() {
+        ExportPacket::NetflowV9(value) => value,
+        value => panic!("unexpected packet {:?}", value)
+    };
+    assert_eq!(vec![template.clone()], message.templates);
+    assert!(message.missing_templates.is_empty());
+    assert_eq!(2, message.records.len());
+    let record = &message.records[1];
+    assert_eq!(300, record.template_id);
+    assert_eq!(Some(&FieldValue::Ipv4Address([10, 0, 0, 1])), record.get(SourceIpv4Address));
+    assert_eq!(Some(&FieldValue::Unsigned(80)), record.get(DestinationTransportPort));
+    assert_eq!(Some(&FieldValue::Unsigned(256)), record.get(PacketDeltaCount));
+    assert_eq!(FieldValue::Bytes(vec![1, 2, 3]), record.fields[3].1);
+    assert_eq!(None, record.get(OctetDeltaCount));
+
+    //the template is stored per exporter & source id
+    assert_eq!(Some(&template), decoder.template(exporter(), 9, 1, 300));
+    assert_eq!(None, decoder.template(exporter(), 9, 2, 300));
+    assert_eq!(None, decoder.template(exporter(), 10, 1, 300));
+    assert_eq!(1, decoder.template_count());
+    for (other_exporter, source_id, expected) in &[(exporter(), 1, 2), (exporter(), 2, 0), ("10.0.0.2:50000".parse().unwrap(), 1, 0)] {
+        let mut packet = netflow_v9_header(*source_id);
+        set(&mut packet, 300, &data_set);
+        match decoder.decode(*other_exporter, &packet).unwrap() {
+            ExportPacket::NetflowV9(message) => assert_eq!(*expected, message.records.len()),
+            value => panic!("unexpected packet {:?}", value)
+        }
+    }
+    decoder.remove_exporter(exporter());
+    assert_eq!(0, decoder.template_count());
+}
+
+#[test]
+fn netflow_v9_options_template() {
+    let mut packet = netflow_v9_header(1);
+    //template id 257, scope (system, 4 bytes) & option (sampling interval, 4 bytes) followed by padding
+    set(&mut packet, 1, &[1, 1, 0, 4, 0, 4, 0, 1, 0, 4, 0, 34, 0, 4, 0, 0]);
+    set(&mut packet, 257, &[0, 0, 0, 1, 0, 0, 0, 100]);
+
+    let mut decoder = ExportDecoder::new();
+    let message = match decoder.decode(exporter(), &packet).unwrap() {
+        ExportPacket::NetflowV9(value) => value,
+        value => panic!("unexpected packet {:?}", value)
+    };
+    assert_eq!(
+        vec![Template {
+            id: 257,
+            scope_field_count: 1,
+            fields: vec![
+                FieldSpecifier{ element_id: 1, length: 4, enterprise_number: None },
+                FieldSpecifier::new(InformationElement::SamplingInterval, 4),
+            ],
+        }],
+        message.templates
+    );
+    assert!(message.templates[0].is_options_template());
+    assert_eq!(1, message.records.len());
+    assert_eq!(Some(&FieldValue::Unsigned(100)), message.records[0].get(InformationElement::SamplingInterval));
+}
+
+#[test]
+fn ipfix_round_trip() {
+    use self::InformationElement::*;
+
+    let template = Template {
+        id: 256,
+        scope_field_count: 0,
+        fields: vec![
+            FieldSpecifier::new(SourceIpv6Address, 16),
+            FieldSpecifier::new(SourceMacAddress, 6),
+            //reduced size encoding
+            FieldSpecifier::new(OctetDeltaCount, 4),
+            FieldSpecifier::reverse(OctetDeltaCount, 8),
+            FieldSpecifier{ element_id: 100, length: VARIABLE_LENGTH, enterprise_number: Some(12345) },
+        ],
+    };
+    let options_template = Template {
+        id: 257,
+        scope_field_count: 1,
+        fields: vec![
+            FieldSpecifier::new(ObservationDomainId, 4),
+            FieldSpecifier::new(SamplingInterval, 4),
+        ],
+    };
+    let record = |value: u64, bytes: Vec<u8>| DataRecord {
+        template_id: 256,
+        fields: vec![
+            (template.fields[0], FieldValue::Ipv6Address([1;16])),
+            (template.fields[1], FieldValue::MacAddress([2;6])),
+            (template.fields[2], FieldValue::Unsigned(value)),
+            (template.fields[3], FieldValue::Unsigned(value*2)),
+            (template.fields[4], FieldValue::Bytes(bytes)),
+        ],
+    };
+    let options_record = DataRecord {
+        template_id: 257,
+        fields: vec![
+            (options_template.fields[0], FieldValue::Unsigned(1)),
+            (options_template.fields[1], FieldValue::Unsigned(1000)),
+        ],
+    };
+    let records = vec![record(1, vec![1, 2, 3]), record(2, vec![4; 300]), options_record, record(3, Vec::new())];
+
+    let mut writer = IpfixWriter::new(42);
+    writer.add_template(template.clone()).unwrap();
+    writer.add_template(options_template.clone()).unwrap();
+    assert_eq!(vec![&template, &options_template], writer.templates().collect::<Vec<_>>());
+    let first = writer.write_message(1000, true, &records).unwrap();
+    assert_eq!(4, writer.sequence_number());
+    let second = writer.write_message(1001, false, &records[..1]).unwrap();
+    assert_eq!(5, writer.sequence_number());
+
+    let mut decoder = ExportDecoder::new();
+    assert_eq!(
+        Ok(ExportPacket::Ipfix(ExportMessage {
+            export_time: 1000,
+            sequence_number: 0,
+            observation_domain_id: 42,
+            sys_uptime: None,
+            templates: vec![template.clone(), options_template.clone()],
+            records: records.clone(),
+            missing_templates: Vec::new(),
+        })),
+        decoder.decode(exporter(), &first)
+    );
+    assert_eq!(
+        Ok(ExportPacket::Ipfix(ExportMessage {
+            export_time: 1001,
+            sequence_number: 4,
+            observation_domain_id: 42,
+            sys_uptime: None,
+            templates: Vec::new(),
+            records: records[..1].to_vec(),
+            missing_templates: Vec::new(),
+        })),
+        decoder.decode(exporter(), &second)
+    );
+    assert_eq!(Some(&FieldValue::Unsigned(2)), records[0].get_reverse(OctetDeltaCount));
+
+    //embedded in an udp packet
+    let mut packet = Vec::new();
+    PacketBuilder::ipv4([10,0,0,1], [10,0,0,2], 64)
+        .udp(50000, IPFIX_PORT)
+        .write(&mut packet, &second)
+        .unwrap();
+    let sliced = SlicedPacket::from_ip(&packet).unwrap();
+    assert_matches!(decoder.decode(exporter(), sliced.payload), Ok(ExportPacket::Ipfix(_)));
+}
+
+#[test]
+fn ipfix_template_withdrawal() {
+    let template = |id| Template {
+        id,
+        scope_field_count: 0,
+        fields: vec![FieldSpecifier::new(InformationElement::PacketDeltaCount, 8)],
+    };
+    let mut writer = IpfixWriter::new(1);
+    for id in 256..259 {
+        writer.add_template(template(id)).unwrap();
+    }
+    let mut decoder = ExportDecoder::new();
+    decoder.decode(exporter(), &writer.write_message(0, true, &[]).unwrap()).unwrap();
+    assert_eq!(3, decoder.template_count());
+
+    let message = |withdrawals: &[u16]| {
+        let mut content = Vec::new();
+        for id in withdrawals {
+            content.extend_from_slice(&id.to_be_bytes());
+            content.extend_from_slice(&[0, 0]);
+        }
+        let mut result = vec![0, 10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+        set(&mut result, 2, &content);
+        let length = result.len() as u16;
+        result[2..4].copy_from_slice(&length.to_be_bytes());
+        result
+    };
+    //single template
+    decoder.decode(exporter(), &message(&[257])).unwrap();
+    assert_eq!(2, decoder.template_count());
+    assert_eq!(None, decoder.template(exporter(), 10, 1, 257));
+    //all templates
+    decoder.decode(exporter(), &message(&[2])).unwrap();
+    assert_eq!(0, decoder.template_count());
+}
+
+#[test]
+fn flow_records() {
+    let mut table = FlowTable::new(FlowTableConfig{
+        bidirectional: true,
+        ..FlowTableConfig::default()
+    });
+    for (from_client, second) in &[(true, 1), (false, 2), (true, 3)] {
+        let (source, destination) = if *from_client {
+            ([1;16], [2;16])
+        } else {
+            ([2;16], [1;16])
+        };
+        let mut packet = Vec::new();
+        PacketBuilder::ipv6(source, destination, 20)
+            .tcp(if *from_client { 1234 } else { 80 }, if *from_client { 80 } else { 1234 }, 0, 1024)
+            .ack(0)
+            .write(&mut packet, &[0;10])
+            .unwrap();
+        table.push(&SlicedPacket::from_ip(&packet).unwrap(), Duration::from_millis(1000*second + 5)).unwrap();
+    }
+    let flows = table.flush();
+
+    let template = Template::flow_records(256, true, true);
+    let record = DataRecord::from_flow_record(&template, &flows[0]);
+    {
+        use self::InformationElement::*;
+        assert_eq!(Some(&FieldValue::Ipv6Address([1;16])), record.get(SourceIpv6Address));
+        assert_eq!(Some(&FieldValue::Ipv6Address([2;16])), record.get(DestinationIpv6Address));
+        assert_eq!(Some(&FieldValue::Unsigned(1234)), record.get(SourceTransportPort));
+        assert_eq!(Some(&FieldValue::Unsigned(80)), record.get(DestinationTransportPort));
+        assert_eq!(Some(&FieldValue::Unsigned(6)), record.get(ProtocolIdentifier));
+        assert_eq!(Some(&FieldValue::Unsigned(0x10)), record.get(TcpControlBits));
+        assert_eq!(Some(&FieldValue::Unsigned(2*70)), record.get(OctetDeltaCount));
+        assert_eq!(Some(&FieldValue::Unsigned(2)), record.get(PacketDeltaCount));
+        assert_eq!(Some(&FieldValue::Unsigned(1)), record.get_reverse(PacketDeltaCount));
+        assert_eq!(Some(&FieldValue::Unsigned(1005)), record.get(FlowStartMilliseconds));
+        assert_eq!(Some(&FieldValue::Unsigned(3005)), record.get(FlowEndMilliseconds));
+        assert_eq!(Some(&FieldValue::Unsigned(4)), record.get(FlowEndReason));
+    }
+
+    //fields without a value are zero & values are saturated
+    let other = Template {
+        id: 257,
+        scope_field_count: 0,
+        fields: vec![
+            FieldSpecifier::new(InformationElement::SourceIpv4Address, 4),
+            FieldSpecifier::new(InformationElement::BgpSourceAsNumber, 2),
+            FieldSpecifier::new(InformationElement::FlowEndMilliseconds, 1),
+        ],
+    };
+    assert_eq!(
+        vec![
+            (other.fields[0], FieldValue::Ipv4Address([0;4])),
+            (other.fields[1], FieldValue::Unsigned(0)),
+            (other.fields[2], FieldValue::Unsigned(0xff)),
+        ],
+        DataRecord::from_flow_record(&other, &flows[0]).fields
+    );
+
+    //ipfix encoding
+    let mut writer = IpfixWriter::new(1);
+    writer.add_template(template).unwrap();
+    let records = vec![record];
+    let message = writer.write_message(0, true, &records).unwrap();
+    match ExportDecoder::new().decode(exporter(), &message) {
+        Ok(ExportPacket::Ipfix(message)) => assert_eq!(records, message.records),
+        value => panic!("unexpected result {:?}", value)
+    }
+}
+
+#[test]
+fn writer_errors() {
+    use self::NetflowError::*;
+
+    let template = Template {
+        id: 256,
+        scope_field_count: 0,
+        fields: vec![FieldSpecifier::new(InformationElement::ProtocolIdentifier, 1)],
+    };
+    let mut writer = IpfixWriter::new(1);
+    assert_eq!(Err(InvalidTemplate(255)), writer.add_template(Template{ id: 255, ..template.clone() }));
+    assert_eq!(Err(InvalidTemplate(256)), writer.add_template(Template{ fields: Vec::new(), ..template.clone() }));
+    assert_eq!(Err(InvalidTemplate(256)), writer.add_template(Template{ scope_field_count: 2, ..template.clone() }));
+    assert_eq!(
+        Err(InvalidTemplate(256)),
+        writer.add_template(Template{ fields: vec![FieldSpecifier::new(InformationElement::ProtocolIdentifier, 0)], ..template.clone() })
+    );
+    writer.add_template(template.clone()).unwrap();
+
+    let record = |value| DataRecord {
+        template_id: 256,
+        fields: vec![(template.fields[0], value)],
+    };
+    assert_eq!(Err(UnknownTemplate(300)), writer.write_message(0, false, &[DataRecord{ template_id: 300, fields: Vec::new() }]));
+    assert_eq!(
+        Err(InvalidFieldValue{ template_id: 256, element_id: 4 }),
+        writer.write_message(0, false, &[record(FieldValue::Unsigned(256))])
+    );
+    assert_eq!(
+        Err(InvalidFieldValue{ template_id: 256, element_id: 4 }),
+        writer.write_message(0, false, &[record(FieldValue::Ipv4Address([0;4]))])
+    );
+    assert_eq!(
+        Err(InvalidFieldValue{ template_id: 256, element_id: 4 }),
+        writer.write_message(0, false, &[DataRecord{ template_id: 256, fields: Vec::new() }])
+    );
+    assert_eq!(
+        Err(MessageTooLarge(16 + 4 + 70_000)),
+        writer.write_message(0, false, &vec![record(FieldValue::Unsigned(6)); 70_000])
+    );
+    //the sequence number is only incremented for written messages
+    assert_eq!(0, writer.sequence_number());
+}
+
+#[test]
+fn decode_errors() {
+    use self::NetflowError::*;
+
+    let mut decoder = ExportDecoder::new();
+    assert_eq!(Err(UnexpectedEndOfSlice(2)), decoder.decode(exporter(), &[0]));
+    assert_eq!(Err(UnsupportedVersion(1)), decoder.decode(exporter(), &[0, 1, 0, 0]));
+    assert_eq!(Err(UnexpectedEndOfSlice(20)), decoder.decode(exporter(), &netflow_v9_header(1)[..19]));
+
+    //ipfix message length
+    let mut message = IpfixWriter::new(1).write_message(0, false, &[]).unwrap();
+    assert_eq!(Err(UnexpectedEndOfSlice(16)), decoder.decode(exporter(), &message[..15]));
+    message[3] = 15;
+    assert_eq!(Err(InvalidMessageLength(15)), decoder.decode(exporter(), &message));
+    message[3] = 17;
+    message.push(0);
+    message.push(0);
+    assert_eq!(Err(UnexpectedEndOfSlice(17)), decoder.decode(exporter(), &message[..16]));
+
+    //set length
+    let mut packet = netflow_v9_header(1);
+    packet.extend_from_slice(&[1, 0, 0, 8, 0, 0]);
+    assert_eq!(Err(InvalidSetLength{ set_id: 256, length: 8 }), decoder.decode(exporter(), &packet));
+    let mut packet = netflow_v9_header(1);
+    packet.extend_from_slice(&[1, 0, 0, 3]);
+    assert_eq!(Err(InvalidSetLength{ set_id: 256, length: 3 }), decoder.decode(exporter(), &packet));
+
+    //templates
+    let mut packet = netflow_v9_header(1);
+    set(&mut packet, 0, &[0, 255, 0, 1, 0, 1, 0, 4]);
+    assert_eq!(Err(InvalidTemplate(255)), decoder.decode(exporter(), &packet));
+    let mut packet = netflow_v9_header(1);
+    set(&mut packet, 0, &[1, 0, 0, 2, 0, 1, 0, 4]);
+    assert_eq!(Err(InvalidTemplate(256)), decoder.decode(exporter(), &packet));
+    assert_eq!(0, decoder.template_count());
+
+    //template with a record length of 0 (followed by a data set)
+    let mut packet = vec![0, 10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+    set(&mut packet, 2, &[1, 0, 0, 1, 0, 8, 0, 0]);
+    set(&mut packet, 256, &[1, 2, 3, 4]);
+    let length = packet.len() as u16;
+    packet[2..4].copy_from_slice(&length.to_be_bytes());
+    assert_eq!(Err(InvalidTemplate(256)), decoder.decode(exporter(), &packet));
+    assert_eq!(0, decoder.template_count());
+}
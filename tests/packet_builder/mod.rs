@@ -824,6 +824,52 @@ fn tcp_options() {
     );
 }
 
+#[test]
+fn ipv4_options() {
+    let mut serialized = Vec::new();
+
+    use crate::Ipv4OptionElement::*;
+    let options = [RouterAlert(0)];
+
+    PacketBuilder::ethernet2([1,2,3,4,5,6],[7,8,9,10,11,12])
+        .ipv4([13,14,15,16], [224,0,0,22], 1)
+        .ipv4_options(&options).unwrap()
+        .udp(1, 2)
+        .write(&mut serialized, &[1,2,3,4]).unwrap();
+
+    let decoded = SlicedPacket::from_ethernet(&serialized[..]).unwrap();
+    let ip = match decoded.ip {
+        Some(InternetSlice::Ipv4(ip)) => ip,
+        _ => panic!("expected an ipv4 header")
+    };
+    assert_eq!(24, ip.slice().len());
+    assert_eq!(ip.to_header().calc_header_checksum().unwrap(), ip.header_checksum());
+    assert_eq!(
+        vec![Ok(RouterAlert(0))],
+        ip.options_iterator().collect::<Vec<_>>()
+    );
+    assert_eq!(&[1,2,3,4], decoded.payload);
+
+    //error
+    {
+        let route = [1,2,3];
+        assert_eq!(
+            Err(ValueError::Ipv4OptionDataLengthBad{ option_id: IPV4_OPTION_ID_RECORD_ROUTE, data_len: 3 }),
+            PacketBuilder::ipv4([13,14,15,16], [17,18,19,20], 1)
+                .ipv4_options(&[RecordRoute{ pointer: 4, route: &route }])
+                .map(|_| ())
+        );
+    }
+}
+
+#[test]
+fn ipv4_options_ipv6() {
+    assert_eq!(
+        Some(ValueError::Ipv4OptionsWithoutIpv4Header),
+        PacketBuilder::ipv6([0;16], [0;16], 1).ipv4_options(&[Ipv4OptionElement::Nop]).err()
+    );
+}
+
 #[test]
 fn size() {
     //ipv4 no vlan
@@ -898,3 +944,226 @@ proptest! {
                                  .size(123));
     }
 }
+
+#[test]
+fn vxlan() {
+    let inner = [1,2,3,4,5,6,7,8,9,10,11,12,13,14,15];
+    let builder = PacketBuilder::ethernet2([1,2,3,4,5,6],[7,8,9,10,11,12])
+                  .ipv4([13,14,15,16], [17,18,19,20], 21)
+                  .udp(22, VXLAN_PORT)
+                  .vxlan(0x123456);
+    let mut serialized = Vec::new();
+    let expected_size = builder.size(inner.len());
+    builder.write(&mut serialized, &inner).unwrap();
+    assert_eq!(expected_size, serialized.len());
+    assert_eq!(
+        Ethernet2Header::SERIALIZED_SIZE + Ipv4Header::SERIALIZED_SIZE + UdpHeader::SERIALIZED_SIZE + VxlanHeader::SERIALIZED_SIZE + inner.len(),
+        serialized.len()
+    );
+
+    //check the headers
+    let (_, rest) = Ethernet2Header::read_from_slice(&serialized).unwrap();
+    let (ip, rest) = Ipv4Header::read_from_slice(rest).unwrap();
+    let (udp, rest) = UdpHeader::read_from_slice(rest).unwrap();
+    let (vxlan, rest) = VxlanHeader::read_from_slice(rest).unwrap();
+
+    //lengths & checksum include the tunnel header
+    let udp_payload = &serialized[Ethernet2Header::SERIALIZED_SIZE + Ipv4Header::SERIALIZED_SIZE + UdpHeader::SERIALIZED_SIZE..];
+    assert_eq!(ip.payload_len as usize, UdpHeader::SERIALIZED_SIZE + udp_payload.len());
+    assert_eq!(
+        udp,
+        UdpHeader::with_ipv4_checksum(22, VXLAN_PORT, &ip, udp_payload).unwrap()
+    );
+    assert_eq!(VxlanHeader::new(0x123456), vxlan);
+    assert_eq!(&inner, rest);
+}
+
+#[test]
+fn geneve() {
+    let inner = [1,2,3,4,5,6,7,8,9,10,11,12,13,14,15];
+    let builder = PacketBuilder::ipv6([1;16], [2;16], 21)
+                  .udp(22, GENEVE_PORT)
+                  .geneve(0x123456)
+                  .options_raw(&[1,2,3,4])
+                  .unwrap();
+    let mut serialized = Vec::new();
+    let expected_size = builder.size(inner.len());
+    builder.write(&mut serialized, &inner).unwrap();
+    assert_eq!(expected_size, serialized.len());
+
+    //check the headers
+    let (ip, rest) = Ipv6Header::read_from_slice(&serialized).unwrap();
+    let (udp, rest) = UdpHeader::read_from_slice(rest).unwrap();
+    let (geneve, rest) = GeneveHeader::read_from_slice(rest).unwrap();
+
+    let udp_payload = &serialized[Ipv6Header::SERIALIZED_SIZE + UdpHeader::SERIALIZED_SIZE..];
+    assert_eq!(ip.payload_length as usize, UdpHeader::SERIALIZED_SIZE + udp_payload.len());
+    assert_eq!(
+        udp,
+        UdpHeader::with_ipv6_checksum(22, GENEVE_PORT, &ip, udp_payload).unwrap()
+    );
+    let mut expected_geneve = GeneveHeader::new(EtherType::TransparentEthernetBridging as u16, 0x123456);
+    expected_geneve.set_options_raw(&[1,2,3,4]).unwrap();
+    assert_eq!(expected_geneve, geneve);
+    assert_eq!(&inner, rest);
+}
+
+#[test]
+fn geneve_options_raw_error() {
+    assert_matches!(
+        PacketBuilder::ipv6([1;16], [2;16], 21)
+        .udp(22, GENEVE_PORT)
+        .geneve(0x123456)
+        .options_raw(&[1,2,3])
+        .err(),
+        Some(ValueError::GeneveOptionsLengthBad(3))
+    );
+}
+
+#[test]
+fn gtpu() {
+    let inner = [1,2,3,4,5,6,7,8,9,10,11,12,13,14,15];
+    let container = GtpuPduSessionContainer {
+        pdu_type: GtpuPduSessionContainer::UL_PDU_SESSION_INFORMATION,
+        qos_flow_identifier: 9,
+        paging_policy_presence: false,
+        reflective_qos_indicator: false
+    };
+    let builder = PacketBuilder::ipv4([13,14,15,16], [17,18,19,20], 21)
+                  .udp(GTPU_PORT, GTPU_PORT)
+                  .gtpu(0x1234_5678)
+                  .sequence_number(1)
+                  .n_pdu_number(2)
+                  .extension_header(0x40, &[1,2])
+                  .unwrap()
+                  .pdu_session_container(&container)
+                  .unwrap();
+    let mut serialized = Vec::new();
+    let expected_size = builder.size(inner.len());
+    builder.write(&mut serialized, &inner).unwrap();
+    assert_eq!(expected_size, serialized.len());
+
+    //check the headers
+    let (ip, rest) = Ipv4Header::read_from_slice(&serialized).unwrap();
+    let (udp, rest) = UdpHeader::read_from_slice(rest).unwrap();
+    let (gtpu, rest) = GtpuHeader::read_from_slice(rest).unwrap();
+
+    let udp_payload = &serialized[Ipv4Header::SERIALIZED_SIZE + UdpHeader::SERIALIZED_SIZE..];
+    assert_eq!(
+        udp,
+        UdpHeader::with_ipv4_checksum(GTPU_PORT, GTPU_PORT, &ip, udp_payload).unwrap()
+    );
+
+    let mut expected_gtpu = GtpuHeader::new(0x1234_5678);
+    expected_gtpu.sequence_number = Some(1);
+    expected_gtpu.n_pdu_number = Some(2);
+    expected_gtpu.add_extension_header(0x40, &[1,2]).unwrap();
+    expected_gtpu.add_pdu_session_container(&container).unwrap();
+    //length contains everything after the first 8 bytes
+    expected_gtpu.length = (udp_payload.len() - 8) as u16;
+    assert_eq!(expected_gtpu, gtpu);
+    assert_eq!(&inner, rest);
+}
+
+#[test]
+fn gtpu_extension_header_errors() {
+    use crate::ErrorField::*;
+    let builder = PacketBuilder::ipv4([13,14,15,16], [17,18,19,20], 21)
+                  .udp(GTPU_PORT, GTPU_PORT)
+                  .gtpu(1);
+    assert_matches!(
+        builder.extension_header(1, &[1,2,3]).err(),
+        Some(ValueError::GtpuExtensionHeaderLengthBad(3))
+    );
+    assert_matches!(
+        PacketBuilder::ipv4([13,14,15,16], [17,18,19,20], 21)
+        .udp(GTPU_PORT, GTPU_PORT)
+        .gtpu(1)
+        .pdu_session_container(&GtpuPduSessionContainer {
+            pdu_type: 0x10,
+            qos_flow_identifier: 0,
+            paging_policy_presence: false,
+            reflective_qos_indicator: false
+        }).err(),
+        Some(ValueError::U8TooLarge{ value: 0x10, max: 0xf, field: GtpuPduType })
+    );
+}
+
+#[test]
+fn ip_in_ip() {
+    let payload = [1,2,3,4,5,6,7,8];
+    let builder = PacketBuilder::ethernet2([1,2,3,4,5,6],[7,8,9,10,11,12])
+                  .ipv6([1;16], [2;16], 21)
+                  .ipv4([13,14,15,16], [17,18,19,20], 22)
+                  .ipv4([23,24,25,26], [27,28,29,30], 23)
+                  .udp(31, 32);
+    let mut serialized = Vec::new();
+    let expected_size = builder.size(payload.len());
+    builder.write(&mut serialized, &payload).unwrap();
+    assert_eq!(expected_size, serialized.len());
+    assert_eq!(
+        Ethernet2Header::SERIALIZED_SIZE + Ipv6Header::SERIALIZED_SIZE + 2*Ipv4Header::SERIALIZED_SIZE + UdpHeader::SERIALIZED_SIZE + payload.len(),
+        serialized.len()
+    );
+
+    //ethernet (ether type of the outermost ip header)
+    let (eth, rest) = Ethernet2Header::read_from_slice(&serialized).unwrap();
+    assert_eq!(EtherType::Ipv6 as u16, eth.ether_type);
+
+    //4in6
+    let (outer, rest) = Ipv6Header::read_from_slice(rest).unwrap();
+    assert_eq!(IpTrafficClass::IPv4 as u8, outer.next_header);
+    assert_eq!(rest.len(), outer.payload_length as usize);
+    assert_eq!([1;16], outer.source);
+
+    //ip in ip
+    let (middle, rest) = Ipv4Header::read_from_slice(rest).unwrap();
+    assert_eq!(IpTrafficClass::IPv4 as u8, middle.protocol);
+    assert_eq!(rest.len(), middle.payload_len as usize);
+    assert_eq!(middle.calc_header_checksum().unwrap(), middle.header_checksum);
+    assert_eq!([13,14,15,16], middle.source);
+
+    //innermost packet
+    let (inner, rest) = Ipv4Header::read_from_slice(rest).unwrap();
+    assert_eq!(IpTrafficClass::Udp as u8, inner.protocol);
+    assert_eq!(rest.len(), inner.payload_len as usize);
+    assert_eq!([23,24,25,26], inner.source);
+    let (udp, rest) = UdpHeader::read_from_slice(rest).unwrap();
+    assert_eq!(
+        UdpHeader::with_ipv4_checksum(31, 32, &inner, &payload).unwrap(),
+        udp
+    );
+    assert_eq!(&payload, rest);
+}
+
+#[test]
+fn ip_in_ip_with_ip_header() {
+    let payload = [1,2,3,4];
+    let builder = PacketBuilder::ipv4([1,2,3,4], [5,6,7,8], 21)
+                  .ip(IpHeader::Version6(Ipv6Header{
+                      traffic_class: 1,
+                      flow_label: 2,
+                      payload_length: 0,
+                      next_header: 0,
+                      hop_limit: 3,
+                      source: [4;16],
+                      destination: [5;16]
+                  }))
+                  .tcp(1, 2, 3, 4);
+    let mut serialized = Vec::new();
+    let expected_size = builder.size(payload.len());
+    builder.write(&mut serialized, &payload).unwrap();
+    assert_eq!(expected_size, serialized.len());
+
+    //6in4
+    let (outer, rest) = Ipv4Header::read_from_slice(&serialized).unwrap();
+    assert_eq!(IpTrafficClass::Ipv6 as u8, outer.protocol);
+    assert_eq!(rest.len(), outer.payload_len as usize);
+    let (inner, rest) = Ipv6Header::read_from_slice(rest).unwrap();
+    assert_eq!(IpTrafficClass::Tcp as u8, inner.next_header);
+    assert_eq!(1, inner.traffic_class);
+    assert_eq!(rest.len(), inner.payload_length as usize);
+    let (tcp, rest) = TcpHeader::read_from_slice(rest).unwrap();
+    assert_eq!(tcp.calc_checksum_ipv6(&inner, &payload).unwrap(), tcp.checksum);
+    assert_eq!(&payload, rest);
+}
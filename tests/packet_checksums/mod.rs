@@ -0,0 +1,250 @@
+use super::*;
+use crate::ChecksumStatus::*;
+
+fn ipv4_udp() -> Vec<u8> {
+    let mut packet = Vec::new();
+    PacketBuilder::ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+        .ipv4([192,168,1,1], [192,168,1,2], 20)
+        .udp(1234, 5678)
+        .write(&mut packet, &[1,2,3,4])
+        .unwrap();
+    packet
+}
+
+fn ipv6_tcp() -> Vec<u8> {
+    let mut packet = Vec::new();
+    PacketBuilder::ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+        .ipv6([1;16], [2;16], 20)
+        .tcp(1234, 5678, 1, 1024)
+        .write(&mut packet, &[1,2,3,4,5])
+        .unwrap();
+    packet
+}
+
+fn verify(packet: &[u8]) -> PacketChecksums {
+    let sliced = SlicedPacket::from_ethernet(packet).unwrap().verify_checksums();
+    let decoded = PacketHeaders::from_ethernet_slice(packet).unwrap().verify_checksums();
+    assert_eq!(sliced, decoded);
+    sliced
+}
+
+#[test]
+fn valid() {
+    assert_eq!(PacketChecksums{ ip: Valid, transport: Valid }, verify(&ipv4_udp()));
+    assert_eq!(PacketChecksums{ ip: NotPresent, transport: Valid }, verify(&ipv6_tcp()));
+
+    //ipv4 tcp & ipv6 udp
+    {
+        let mut packet = Vec::new();
+        PacketBuilder::ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+            .ipv4([192,168,1,1], [192,168,1,2], 20)
+            .tcp(1234, 5678, 1, 1024)
+            .write(&mut packet, &[1,2,3])
+            .unwrap();
+        assert_eq!(PacketChecksums{ ip: Valid, transport: Valid }, verify(&packet));
+    }
+    {
+        let mut packet = Vec::new();
+        PacketBuilder::ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+            .ipv6([1;16], [2;16], 20)
+            .udp(1234, 5678)
+            .write(&mut packet, &[1,2,3])
+            .unwrap();
+        assert_eq!(PacketChecksums{ ip: NotPresent, transport: Valid }, verify(&packet));
+    }
+}
+
+#[test]
+fn ipv4_authentication_header() {
+    //the authentication header is not part of the checksummed data
+    let payload = [1,2,3,4];
+    let auth = AuthenticationHeader::new(IpTrafficClass::Udp as u8, 1, 2, &[0;12]).unwrap();
+    let ip = Ipv4Header::new(
+        (auth.header_len() + UdpHeader::SERIALIZED_SIZE + payload.len()) as u16,
+        20,
+        IpTrafficClass::IPv6AuthenticationHeader,
+        [192,168,1,1],
+        [192,168,1,2]
+    );
+    let mut udp = UdpHeader{
+        source_port: 1234,
+        destination_port: 5678,
+        length: (UdpHeader::SERIALIZED_SIZE + payload.len()) as u16,
+        checksum: 0
+    };
+    udp.checksum = udp.calc_checksum_ipv4_raw(ip.source, ip.destination, IpTrafficClass::Udp as u8, &payload).unwrap();
+
+    let mut packet = Vec::new();
+    ip.write(&mut packet).unwrap();
+    auth.write(&mut packet).unwrap();
+    udp.write(&mut packet).unwrap();
+    packet.extend_from_slice(&payload);
+
+    assert_eq!(
+        PacketChecksums{ ip: Valid, transport: Valid },
+        SlicedPacket::from_ip(&packet).unwrap().verify_checksums()
+    );
+}
+
+#[test]
+fn ethernet_padding() {
+    //the padding after the ip payload is not part of the checksums
+    let mut packet = ipv4_udp();
+    packet.resize(60, 0xff);
+    assert_eq!(PacketChecksums{ ip: Valid, transport: Valid }, verify(&packet));
+}
+
+#[test]
+fn invalid() {
+    //ip header
+    {
+        let mut packet = ipv4_udp();
+        //time to live
+        packet[14 + 8] = 21;
+        let result = verify(&packet);
+        assert_eq!(PacketChecksums{ ip: Invalid, transport: Valid }, result);
+        assert!(result.contains_invalid());
+    }
+    //udp payload
+    {
+        let mut packet = ipv4_udp();
+        *packet.last_mut().unwrap() = 0;
+        let result = verify(&packet);
+        assert_eq!(PacketChecksums{ ip: Valid, transport: Invalid }, result);
+        assert!(result.contains_invalid());
+    }
+    //tcp payload
+    {
+        let mut packet = ipv6_tcp();
+        *packet.last_mut().unwrap() = 0;
+        assert_eq!(PacketChecksums{ ip: NotPresent, transport: Invalid }, verify(&packet));
+    }
+    //udp length smaller then the udp header
+    {
+        let mut packet = ipv4_udp();
+        packet[14 + 20 + 4] = 0;
+        packet[14 + 20 + 5] = 7;
+        assert_eq!(Invalid, verify(&packet).transport);
+    }
+}
+
+#[test]
+fn not_present() {
+    //udp without checksum
+    {
+        let mut packet = ipv4_udp();
+        packet[14 + 20 + 6] = 0;
+        packet[14 + 20 + 7] = 0;
+        let result = verify(&packet);
+        assert_eq!(PacketChecksums{ ip: Valid, transport: NotPresent }, result);
+        assert_eq!(false, result.contains_invalid());
+    }
+    //udp without checksum is not allowed in ipv6
+    {
+        let mut packet = Vec::new();
+        PacketBuilder::ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+            .ipv6([1;16], [2;16], 20)
+            .udp(1234, 5678)
+            .write(&mut packet, &[1,2,3])
+            .unwrap();
+        packet[14 + 40 + 6] = 0;
+        packet[14 + 40 + 7] = 0;
+        assert_eq!(Invalid, verify(&packet).transport);
+    }
+    //no ip
+    {
+        let mut packet = Vec::new();
+        Ethernet2Header{
+            source: [1,2,3,4,5,6],
+            destination: [7,8,9,10,11,12],
+            ether_type: 0x1234
+        }.write(&mut packet).unwrap();
+        assert_eq!(PacketChecksums{ ip: NotPresent, transport: NotPresent }, verify(&packet));
+    }
+    //no transport
+    {
+        let mut packet = Vec::new();
+        PacketBuilder::ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+            .ipv4([192,168,1,1], [192,168,1,2], 20)
+            .udp(1234, 5678)
+            .write(&mut packet, &[])
+            .unwrap();
+        //change the protocol to an unknown protocol & correct the ip checksum
+        packet[14 + 9] = 0xfd;
+        let header = Ipv4HeaderSlice::from_slice(&packet[14..]).unwrap().to_header();
+        let checksum = header.calc_header_checksum().unwrap();
+        packet[14 + 10..14 + 12].copy_from_slice(&checksum.to_be_bytes());
+        assert_eq!(PacketChecksums{ ip: Valid, transport: NotPresent }, verify(&packet));
+    }
+}
+
+#[test]
+fn truncated() {
+    //missing payload
+    {
+        let mut packet = ipv4_udp();
+        packet.pop();
+        assert_eq!(PacketChecksums{ ip: Valid, transport: Truncated }, verify(&packet));
+    }
+    {
+        let mut packet = ipv6_tcp();
+        packet.pop();
+        assert_eq!(PacketChecksums{ ip: NotPresent, transport: Truncated }, verify(&packet));
+    }
+    //ipv4 fragment
+    {
+        let mut packet = Vec::new();
+        let mut ip = Ipv4Header::new(8 + 4, 20, IpTrafficClass::Udp, [192,168,1,1], [192,168,1,2]);
+        ip.dont_fragment = false;
+        ip.more_fragments = true;
+        ip.write(&mut packet).unwrap();
+        UdpHeader::with_ipv4_checksum(1234, 5678, &ip, &[1,2,3,4]).unwrap().write(&mut packet).unwrap();
+        packet.extend_from_slice(&[1,2,3,4]);
+
+        let expected = PacketChecksums{ ip: Valid, transport: Truncated };
+        assert_eq!(expected, SlicedPacket::from_ip(&packet).unwrap().verify_checksums());
+        assert_eq!(expected, PacketHeaders::from_ip_slice(&packet).unwrap().verify_checksums());
+    }
+    //ipv6 fragment
+    {
+        let payload = [1,2,3,4];
+        let udp = {
+            let ip = Ipv6Header{
+                traffic_class: 0,
+                flow_label: 0,
+                payload_length: (8 + 8 + payload.len()) as u16,
+                next_header: IpTrafficClass::IPv6FragmentationHeader as u8,
+                hop_limit: 20,
+                source: [1;16],
+                destination: [2;16]
+            };
+            UdpHeader::with_ipv6_checksum(1234, 5678, &ip, &payload).unwrap()
+        };
+        let packet = |fragment: [u8;8]| -> Vec<u8> {
+            let mut result = Vec::new();
+            Ipv6Header{
+                traffic_class: 0,
+                flow_label: 0,
+                payload_length: (8 + 8 + payload.len()) as u16,
+                next_header: IpTrafficClass::IPv6FragmentationHeader as u8,
+                hop_limit: 20,
+                source: [1;16],
+                destination: [2;16]
+            }.write(&mut result).unwrap();
+            result.extend_from_slice(&fragment);
+            udp.write(&mut result).unwrap();
+            result.extend_from_slice(&payload);
+            result
+        };
+        //more fragments flag
+        assert_eq!(
+            Truncated,
+            SlicedPacket::from_ip(&packet([IpTrafficClass::Udp as u8, 0, 0, 1, 0, 0, 0, 0])).unwrap().verify_checksums().transport
+        );
+        //atomic fragment
+        assert_eq!(
+            Valid,
+            SlicedPacket::from_ip(&packet([IpTrafficClass::Udp as u8, 0, 0, 0, 0, 0, 0, 0])).unwrap().verify_checksums().transport
+        );
+    }
+}
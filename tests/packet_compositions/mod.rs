@@ -131,6 +131,23 @@ impl ComponentTest {
                         Err(ReadError::Ipv6TooManyHeaderExtensions));
     }
 
+    fn run_ipv6_esp(&self) {
+        //slicing stops at the esp header
+        let buffer = self.serialize();
+        let sliced = SlicedPacket::from_ethernet(&buffer).unwrap();
+        assert!(sliced.esp.is_some());
+        assert_eq!(None, sliced.transport);
+
+        //decoding stops at the esp header
+        let decoded = PacketHeaders::from_ethernet_slice(&buffer).unwrap();
+        assert_eq!(None, decoded.transport);
+
+        //same from the internet layer down
+        let buffer = self.serialize_from_ip();
+        assert!(SlicedPacket::from_ip(&buffer).unwrap().esp.is_some());
+        assert_eq!(None, PacketHeaders::from_ip_slice(&buffer).unwrap().transport);
+    }
+
     fn assert_sliced_packet(&self, result: SlicedPacket) {
         //assert identity to touch the derives (code coverage hack)
         assert_eq!(result, result);
@@ -371,11 +388,21 @@ impl ComponentTest {
 
             //extend the vector to the maximum size
             exts.resize(IPV6_MAX_NUM_HEADER_EXTENSIONS, {
-                (*id, vec![*id,0,0,0,  0,0,0,0])
+                if IpTrafficClass::IPv6AuthenticationHeader as u8 == *id {
+                    //authentication header length is in 4 octet units & must contain the spi & sequence number
+                    (*id, vec![*id,2,0,0,  0,0,0,0,  0,0,0,0,  0,0,0,0])
+                } else {
+                    (*id, vec![*id,0,0,0,  0,0,0,0])
+                }
             });
 
-            //expect the failure
-            setup(*id, &exts).run_ipv6_ext_failure();
+            if IpTrafficClass::IPv6EncapSecurityPayload as u8 == *id {
+                //the esp header ends the extension headers -> no failure
+                setup(*id, &exts).run_ipv6_esp();
+            } else {
+                //expect the failure
+                setup(*id, &exts).run_ipv6_ext_failure();
+            }
         }
     }
 
@@ -487,6 +514,8 @@ fn test_packet_slicing_panics() {
         link: None,
         vlan: None,
         ip: None,
+        authentication_header: None,
+        esp: None,
         transport: None,
         payload: &v[..]
     };
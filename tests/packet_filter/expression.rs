@@ -0,0 +1,508 @@
+use super::*;
+use etherparse::packet_filter::FilterExpression::*;
+use etherparse::packet_filter::FilterParseErrorKind::*;
+use std::net::IpAddr;
+
+fn prefix(address: &str, prefix_len: u8) -> IpPrefix {
+    IpPrefix::new(address.parse().unwrap(), prefix_len).unwrap()
+}
+
+fn host(direction: Direction, address: &str) -> FilterExpression {
+    IpAddress{ direction, prefix: IpPrefix::host(address.parse().unwrap()) }
+}
+
+fn vlan(identifier: u16) -> FilterExpression {
+    Vlan{ identifiers: Some(identifier..=identifier), priorities: None }
+}
+
+fn parse_error(expression: &str) -> (usize, FilterParseErrorKind) {
+    let error = FilterExpression::parse(expression).unwrap_err();
+    (error.position, error.kind)
+}
+
+#[test]
+fn ip_prefix() {
+    let value = prefix("10.0.0.0", 8);
+    assert!(value.contains(&[10,1,2,3]));
+    assert!(!value.contains(&[11,0,0,0]));
+    assert!(!value.contains(&[10;16]));
+    assert!(!value.has_host_bits());
+
+    let value = prefix("192.168.4.0", 22);
+    assert!(value.contains(&[192,168,7,255]));
+    assert!(!value.contains(&[192,168,8,0]));
+    assert!(prefix("192.168.5.0", 22).has_host_bits());
+
+    assert!(prefix("0.0.0.0", 0).contains(&[1,2,3,4]));
+    assert!(prefix("2001:db8::", 32).contains(&"2001:db8:1::1".parse::<std::net::Ipv6Addr>().unwrap().octets()));
+    assert!(IpPrefix::new("10.0.0.0".parse().unwrap(), 33).is_none());
+    assert!(IpPrefix::new("::".parse().unwrap(), 129).is_none());
+}
+
+#[test]
+fn parse() {
+    assert_eq!(Any, FilterExpression::parse("").unwrap());
+    assert_eq!(Any, FilterExpression::parse("  ").unwrap());
+    assert_eq!(
+        Ok(vlan(100)
+            .and(IpAddress{ direction: Direction::Source, prefix: prefix("10.0.0.0", 8) })
+            .and(Port{ protocol: Some(6), direction: Direction::Destination, ports: 443..=443 })),
+        FilterExpression::parse("vlan 100 and ip src net 10.0.0.0/8 and tcp dst port 443")
+    );
+    let values = [
+        ("vlan", Vlan{ identifiers: None, priorities: None }),
+        ("vlan 0x10", vlan(16)),
+        ("ip", EtherType(0x0800)),
+        ("ip6", EtherType(0x86dd)),
+        ("arp", EtherType(0x0806)),
+        ("ether proto 0x88cc", EtherType(0x88cc)),
+        ("ether proto ip6", EtherType(0x86dd)),
+        ("ether src 00:1a:2b:3c:4d:5e", EthernetAddress{ direction: Direction::Source, address: [0, 0x1a, 0x2b, 0x3c, 0x4d, 0x5e] }),
+        ("ether dst host 1:2:3:4:5:6", EthernetAddress{ direction: Direction::Destination, address: [1,2,3,4,5,6] }),
+        ("ether host ff:ff:ff:ff:ff:ff", EthernetAddress{ direction: Direction::SourceOrDestination, address: [0xff;6] }),
+        ("tcp", IpProtocol(6)),
+        ("udp", IpProtocol(17)),
+        ("icmp", IpProtocol(1)),
+        ("icmp6", IpProtocol(58)),
+        ("proto 47", IpProtocol(47)),
+        ("ip proto udp", EtherType(0x0800).and(IpProtocol(17))),
+        ("ip6 proto icmp6", EtherType(0x86dd).and(IpProtocol(58))),
+        ("host 10.0.0.1", host(Direction::SourceOrDestination, "10.0.0.1")),
+        ("src 10.0.0.1", host(Direction::Source, "10.0.0.1")),
+        ("10.0.0.1", host(Direction::SourceOrDestination, "10.0.0.1")),
+        ("ip src 10.0.0.0/8", IpAddress{ direction: Direction::Source, prefix: prefix("10.0.0.0", 8) }),
+        ("fe80::/10", IpAddress{ direction: Direction::SourceOrDestination, prefix: prefix("fe80::", 10) }),
+        ("dst host ::1", host(Direction::Destination, "::1")),
+        ("src or dst host 10.0.0.1", host(Direction::SourceOrDestination, "10.0.0.1")),
+        ("src and dst host 10.0.0.1", host(Direction::SourceAndDestination, "10.0.0.1")),
+        ("ip6 net 2001:db8::/32", IpAddress{ direction: Direction::SourceOrDestination, prefix: prefix("2001:db8::", 32) }),
+        ("port 53", Port{ protocol: None, direction: Direction::SourceOrDestination, ports: 53..=53 }),
+        ("udp src port 53", Port{ protocol: Some(17), direction: Direction::Source, ports: 53..=53 }),
+    ];
+    for (expression, expected) in values.iter() {
+        assert_eq!(Ok(expected.clone()), FilterExpression::parse(expression), "{}", expression);
+    }
+    //FromStr
+    assert_eq!(Ok(IpProtocol(6)), "tcp".parse::<FilterExpression>());
+}
+
+#[test]
+fn parse_operators() {
+    //and & or have the same precedence (evaluated left to right)
+    assert_eq!(
+        Ok(IpProtocol(6).or(IpProtocol(17)).and(Vlan{ identifiers: None, priorities: None })),
+        FilterExpression::parse("tcp or udp and vlan")
+    );
+    assert_eq!(
+        Ok(IpProtocol(6).or(IpProtocol(17).and(Vlan{ identifiers: None, priorities: None }))),
+        FilterExpression::parse("tcp || (udp && vlan)")
+    );
+    //not binds stronger then and & or
+    assert_eq!(
+        Ok(IpProtocol(6).negate().and(Vlan{ identifiers: None, priorities: None })),
+        FilterExpression::parse("not tcp and vlan")
+    );
+    assert_eq!(
+        Ok(IpProtocol(6).and(Vlan{ identifiers: None, priorities: None }).negate().negate()),
+        FilterExpression::parse("!!(tcp&&vlan)")
+    );
+    //"or" as part of the direction qualifier
+    assert_eq!(
+        Ok(host(Direction::Source, "10.0.0.1").or(host(Direction::Destination, "10.0.0.2"))),
+        FilterExpression::parse("src 10.0.0.1 or dst 10.0.0.2")
+    );
+}
+
+#[test]
+fn parse_errors() {
+    let values = [
+        ("tcp dst port 70000", 13, InvalidNumber{ value: "70000".to_string(), max: 0xffff }),
+        ("vlan 4096", 5, InvalidNumber{ value: "4096".to_string(), max: 0xfff }),
+        ("tcp port", 8, UnexpectedEnd{ expected: "number" }),
+        ("tcp and", 7, UnexpectedEnd{ expected: "filter primitive" }),
+        ("(tcp", 4, UnexpectedEnd{ expected: "')'" }),
+        ("tcp)", 3, UnexpectedToken{ expected: "'and', 'or' or end of expression", found: ")".to_string() }),
+        ("tcp udp", 4, UnexpectedToken{ expected: "'and', 'or' or end of expression", found: "udp".to_string() }),
+        ("tcp & udp", 4, UnexpectedToken{ expected: "'&&'", found: "&".to_string() }),
+        ("foo", 0, UnexpectedToken{ expected: "filter primitive", found: "foo".to_string() }),
+        ("tcp host 10.0.0.1", 4, UnexpectedToken{ expected: "'port' or 'portrange'", found: "host".to_string() }),
+        ("ip port 80", 3, UnexpectedToken{ expected: "'host', 'net' or 'proto'", found: "port".to_string() }),
+        ("src foo", 4, UnexpectedToken{ expected: "'host', 'net', 'port', 'portrange' or 'proto'", found: "foo".to_string() }),
+        ("ether 1:2:3:4:5:6", 6, UnexpectedToken{ expected: "'src', 'dst', 'host' or 'proto'", found: "1:2:3:4:5:6".to_string() }),
+        ("ether src 1:2:3:4:5", 10, InvalidAddress("1:2:3:4:5".to_string())),
+        ("ether src 1:2:3:4:5:6:7", 10, InvalidAddress("1:2:3:4:5:6:7".to_string())),
+        ("host 10.0.0.256", 5, InvalidAddress("10.0.0.256".to_string())),
+        ("host 10.0.0.0/8", 5, InvalidAddress("10.0.0.0/8".to_string())),
+        ("net 10.0.0.1/8", 4, InvalidPrefix("10.0.0.1/8".to_string())),
+        ("net 10.0.0.0/33", 4, InvalidPrefix("10.0.0.0/33".to_string())),
+        ("ip6 host 10.0.0.1", 9, AddressFamilyMismatch("10.0.0.1".to_string())),
+        ("ip net ::/0", 7, AddressFamilyMismatch("::/0".to_string())),
+    ];
+    for (expression, position, kind) in values.iter() {
+        assert_eq!((*position, kind.clone()), parse_error(expression), "{}", expression);
+    }
+
+    //display
+    assert_eq!(
+        "FilterParseError: Invalid number '70000' at position 13 of filter expression (numbers have to be between 0 and 65535).",
+        format!("{}", FilterExpression::parse("tcp dst port 70000").unwrap_err())
+    );
+}
+
+#[test]
+fn parse_nesting_depth() {
+    let max = FILTER_MAX_NESTING_DEPTH;
+    let nested_not = |count: usize| "!".repeat(count) + "tcp";
+    let nested_parentheses = |count: usize| "(".repeat(count) + "tcp" + &")".repeat(count);
+    let chain = |count: usize| "tcp and ".repeat(count) + "tcp";
+
+    //expressions up to the maximum depth can be parsed & evaluated
+    let mut packet = Vec::new();
+    PacketBuilder::ipv4([10,0,0,1], [10,0,0,2], 20)
+        .tcp(1234, 80, 0, 1024)
+        .write(&mut packet, &[])
+        .unwrap();
+    let slice = SlicedPacket::from_ip(&packet).unwrap();
+    for (expression, expected) in [(nested_not(max), 0 == max & 1), (nested_parentheses(max), true), (chain(max), true)].iter() {
+        assert_eq!(*expected, FilterExpression::parse(expression).unwrap().applies_to_slice(&slice));
+    }
+    assert!(FilterExpression::parse(&"!(".repeat(max/2)).is_err());
+
+    //deeper expressions result in an error
+    assert_eq!((max, NestingTooDeep), parse_error(&nested_not(max + 1)));
+    assert_eq!((max, NestingTooDeep), parse_error(&nested_parentheses(max + 1)));
+    assert_eq!((max*8 + 4, NestingTooDeep), parse_error(&chain(max + 1)));
+    assert_eq!((0, NestingTooDeep), parse_error(&("!(".to_string() + &chain(max) + ")")));
+    assert_eq!((max, NestingTooDeep), parse_error(&nested_not(200_000)));
+    assert_eq!((max, NestingTooDeep), parse_error(&nested_parentheses(200_000)));
+    assert_eq!((max*8 + 4, NestingTooDeep), parse_error(&chain(200_000)));
+
+    let error = FilterExpression::parse(&nested_not(max + 1)).unwrap_err();
+    assert_eq!(
+        format!("FilterParseError: Filter expression is nested too deeply at position {} (at most {} nested operators & parentheses are supported).", max, max),
+        format!("{}", error)
+    );
+}
+
+#[test]
+fn applies_to_slice() {
+    let mut tcp_vlan = Vec::new();
+    PacketBuilder::ethernet2([1;6], [2;6])
+        .single_vlan(100)
+        .ipv4([10,1,2,3], [192,168,1,1], 20)
+        .tcp(50000, 443, 0, 1024)
+        .write(&mut tcp_vlan, &[])
+        .unwrap();
+    let tcp_vlan = SlicedPacket::from_ethernet(&tcp_vlan).unwrap();
+
+    let mut udp_double_vlan = Vec::new();
+    PacketBuilder::ethernet2([3;6], [4;6])
+        .double_vlan(200, 300)
+        .ipv6([0x20,1,0xd,0xb8,0,0,0,0,0,0,0,0,0,0,0,1], [0xfe,0x80,0,0,0,0,0,0,0,0,0,0,0,0,0,1], 20)
+        .udp(53, 1234)
+        .write(&mut udp_double_vlan, &[])
+        .unwrap();
+    let udp_double_vlan = SlicedPacket::from_ethernet(&udp_double_vlan).unwrap();
+
+    let mut icmp = Vec::new();
+    Ipv4Header::new(4, 20, IpTrafficClass::Icmp, [10,0,0,1], [10,0,0,2])
+        .write(&mut icmp)
+        .unwrap();
+    icmp.extend_from_slice(&[8,0,0,0]);
+    let icmp = SlicedPacket::from_ip(&icmp).unwrap();
+
+    //expression, result for tcp_vlan, udp_double_vlan & icmp
+    let values = [
+        ("", true, true, true),
+        ("vlan 100 and ip src 10.0.0.0/8 and tcp dst port 443", true, false, false),
+        ("vlan", true, true, false),
+        ("vlan 300", false, true, false),
+        ("vlan 200 and not vlan 100", false, true, false),
+        ("ip", true, false, true),
+        ("ip6", false, true, false),
+        ("ether proto 0x0800", true, false, true),
+        ("ether src 1:1:1:1:1:1", true, false, false),
+        ("ether dst 1:1:1:1:1:1", false, false, false),
+        ("ether host 4:4:4:4:4:4", false, true, false),
+        ("src and dst net 10.0.0.0/8", false, false, true),
+        ("src or dst host 192.168.1.1", true, false, false),
+        ("dst host 192.168.1.1", true, false, false),
+        ("src host 192.168.1.1", false, false, false),
+        ("ip6 src net 2001:db8::/32", false, true, false),
+        ("net ::/0", false, true, false),
+        ("tcp", true, false, false),
+        ("udp or icmp", false, true, true),
+        ("proto 1", false, false, true),
+        ("ip proto 17", false, false, false),
+        ("port 53", false, true, false),
+        ("udp src port 53", false, true, false),
+        ("tcp src port 53", false, false, false),
+        ("src port 443", false, false, false),
+        ("tcp port 443 or udp port 1234", true, true, false),
+        ("not port 443", false, true, true),
+    ];
+    for (expression, expected_tcp, expected_udp, expected_icmp) in values.iter() {
+        let filter = FilterExpression::parse(expression).unwrap();
+        assert_eq!(*expected_tcp, filter.applies_to_slice(&tcp_vlan), "{}", expression);
+        assert_eq!(*expected_udp, filter.applies_to_slice(&udp_double_vlan), "{}", expression);
+        assert_eq!(*expected_icmp, filter.applies_to_slice(&icmp), "{}", expression);
+    }
+
+    //addresses of the other ip version never match
+    let filter = IpAddress{
+        direction: Direction::SourceOrDestination,
+        prefix: IpPrefix::host(IpAddr::from([0u8;16]))
+    };
+    assert!(!filter.applies_to_slice(&tcp_vlan));
+}
+
+#[test]
+fn parse_extended() {
+    let values = [
+        ("vlan 100-200", Vlan{ identifiers: Some(100..=200), priorities: None }),
+        ("vlan pcp 5", Vlan{ identifiers: None, priorities: Some(5..=5) }),
+        ("vlan 10 pcp 4-7", Vlan{ identifiers: Some(10..=10), priorities: Some(4..=7) }),
+        ("portrange 1024-65535", Port{ protocol: None, direction: Direction::SourceOrDestination, ports: 1024..=65535 }),
+        ("udp dst portrange 0x10-0x20", Port{ protocol: Some(17), direction: Direction::Destination, ports: 16..=32 }),
+        ("tcp flags syn", TcpFlags{ mask: TCP_FLAG_SYN, value: TCP_FLAG_SYN }),
+        ("tcp flags syn/syn,ack", TcpFlags{ mask: TCP_FLAG_SYN | TCP_FLAG_ACK, value: TCP_FLAG_SYN }),
+        ("tcp flags 0/rst", TcpFlags{ mask: TCP_FLAG_RST, value: 0 }),
+        ("tcp flags 0x12/0xff", TcpFlags{ mask: 0xff, value: 0x12 }),
+        ("payloadlen 0", PayloadLength(0..=0)),
+        ("payloadlen 1-1500", PayloadLength(1..=1500)),
+    ];
+    for (expression, expected) in values.iter() {
+        assert_eq!(Ok(expected.clone()), FilterExpression::parse(expression), "{}", expression);
+    }
+
+    let errors = [
+        ("vlan 200-100", 5, InvalidRange("200-100".to_string())),
+        ("vlan 1-4096", 7, InvalidNumber{ value: "4096".to_string(), max: 0xfff }),
+        ("vlan pcp 8", 9, InvalidNumber{ value: "8".to_string(), max: 7 }),
+        ("portrange 1-", 12, InvalidNumber{ value: "".to_string(), max: 0xffff }),
+        ("tcp flags syn,foo", 10, InvalidTcpFlags("syn,foo".to_string())),
+        ("tcp flags 0x100", 10, InvalidTcpFlags("0x100".to_string())),
+        ("tcp flags", 9, UnexpectedEnd{ expected: "tcp flags" }),
+        ("udp flags syn", 4, UnexpectedToken{ expected: "'and', 'or' or end of expression", found: "flags".to_string() }),
+        ("payloadlen", 10, UnexpectedEnd{ expected: "number or range" }),
+    ];
+    for (expression, position, kind) in errors.iter() {
+        assert_eq!((*position, kind.clone()), parse_error(expression), "{}", expression);
+    }
+}
+
+#[test]
+fn applies_to_slice_extended() {
+    let mut syn = Vec::new();
+    PacketBuilder::ethernet2([1;6], [2;6])
+        .vlan(VlanHeader::Single(SingleVlanHeader {
+            priority_code_point: 5,
+            drop_eligible_indicator: false,
+            vlan_identifier: 150,
+            ether_type: 0,
+        }))
+        .ipv4([10,0,0,1], [10,0,0,2], 20)
+        .tcp(40000, 8080, 0, 1024)
+        .syn()
+        .write(&mut syn, &[])
+        .unwrap();
+    let syn = SlicedPacket::from_ethernet(&syn).unwrap();
+
+    let mut ack = Vec::new();
+    PacketBuilder::ethernet2([1;6], [2;6])
+        .double_vlan(10, 20)
+        .ipv4([10,0,0,2], [10,0,0,1], 20)
+        .tcp(8080, 40000, 0, 1024)
+        .syn()
+        .ack(1)
+        .write(&mut ack, &[1,2,3,4])
+        .unwrap();
+    let ack = SlicedPacket::from_ethernet(&ack).unwrap();
+
+    let mut udp = Vec::new();
+    PacketBuilder::ipv4([10,0,0,1], [10,0,0,2], 20)
+        .udp(1000, 2000)
+        .write(&mut udp, &[0;100])
+        .unwrap();
+    let udp = SlicedPacket::from_ip(&udp).unwrap();
+
+    //expression, result for syn, ack & udp
+    let values = [
+        ("vlan 100-200", true, false, false),
+        ("vlan 15-25", false, true, false),
+        ("vlan pcp 5", true, false, false),
+        ("vlan pcp 0-4", false, true, false),
+        ("vlan 150 pcp 5-7", true, false, false),
+        ("vlan 150 pcp 0", false, false, false),
+        ("portrange 8000-9000", true, true, false),
+        ("dst portrange 8000-9000", true, false, false),
+        ("udp portrange 1000-1999", false, false, true),
+        ("tcp flags syn", true, true, false),
+        ("tcp flags syn/syn,ack", true, false, false),
+        ("tcp flags syn,ack", false, true, false),
+        ("tcp flags 0/rst", true, true, false),
+        ("payloadlen 0", true, false, false),
+        ("payloadlen 1-100", false, true, true),
+        ("payloadlen 100", false, false, true),
+    ];
+    for (expression, expected_syn, expected_ack, expected_udp) in values.iter() {
+        let filter = FilterExpression::parse(expression).unwrap();
+        assert_eq!(*expected_syn, filter.applies_to_slice(&syn), "{}", expression);
+        assert_eq!(*expected_ack, filter.applies_to_slice(&ack), "{}", expression);
+        assert_eq!(*expected_udp, filter.applies_to_slice(&udp), "{}", expression);
+    }
+
+    //field based filters can be combined with expressions
+    let filter = etherparse::packet_filter::Filter {
+        transport: ElementFilter::Some(TransportFilter::Tcp {
+            source_port: None,
+            destination_port: Some(8080),
+        }),
+        ..Default::default()
+    };
+    assert!(filter.applies_to_slice(&syn));
+    let expression = FilterExpression::from(filter.clone());
+    assert!(expression.applies_to_slice(&syn));
+    assert!(!expression.applies_to_slice(&ack));
+    let expression = FilterExpression::from(filter).negate().and(FilterExpression::parse("tcp").unwrap());
+    assert!(!expression.applies_to_slice(&syn));
+    assert!(expression.applies_to_slice(&ack));
+    assert!(!expression.applies_to_slice(&udp));
+}
+
+///Packets used to compare the results of the different evaluation methods.
+fn evaluation_packets() -> Vec<Vec<u8>> {
+    let mut result = Vec::new();
+    {
+        let mut packet = Vec::new();
+        PacketBuilder::ethernet2([1;6], [2;6])
+            .single_vlan(100)
+            .ipv4([10,1,2,3], [192,168,1,1], 20)
+            .tcp(50000, 443, 0, 1024)
+            .syn()
+            .write(&mut packet, &[])
+            .unwrap();
+        result.push(packet);
+    }
+    {
+        let mut packet = Vec::new();
+        PacketBuilder::ethernet2([3;6], [4;6])
+            .double_vlan(200, 300)
+            .ipv6([1;16], [2;16], 20)
+            .udp(53, 1234)
+            .write(&mut packet, &[1,2,3])
+            .unwrap();
+        result.push(packet);
+    }
+    {
+        let mut packet = Vec::new();
+        PacketBuilder::ethernet2([1;6], [4;6])
+            .ipv4([192,168,1,1], [10,1,2,3], 20)
+            .tcp(443, 50000, 0, 1024)
+            .ack(1)
+            .write(&mut packet, &[0;20])
+            .unwrap();
+        result.push(packet);
+    }
+    {
+        let mut packet = Vec::new();
+        Ethernet2Header{
+            source: [5;6],
+            destination: [6;6],
+            ether_type: crate::EtherType::Arp as u16,
+        }.write(&mut packet).unwrap();
+        packet.extend_from_slice(&[0;28]);
+        result.push(packet);
+    }
+    result
+}
+
+const EVALUATION_EXPRESSIONS: [&str;14] = [
+    "",
+    "vlan",
+    "vlan 300 or ether src 1:1:1:1:1:1",
+    "arp",
+    "ip and not vlan",
+    "ip6 or ether dst 2:2:2:2:2:2",
+    "net 10.0.0.0/8",
+    "src host 192.168.1.1",
+    "tcp",
+    "ip proto 6",
+    "port 443",
+    "udp dst portrange 1000-2000",
+    "tcp flags syn/syn,ack",
+    "payloadlen 1-20 and not vlan pcp 1-7",
+];
+
+#[test]
+fn evaluation_methods_agree() {
+    for packet in evaluation_packets() {
+        let sliced = SlicedPacket::from_ethernet(&packet).unwrap();
+        let headers = PacketHeaders::from_ethernet_slice(&packet).unwrap();
+        for expression in EVALUATION_EXPRESSIONS.iter() {
+            let filter = FilterExpression::parse(expression).unwrap();
+            let expected = filter.applies_to_slice(&sliced);
+            assert_eq!(expected, filter.applies_to_headers(&headers), "{}", expression);
+            assert_eq!(expected, filter.applies_to_ethernet(&packet).unwrap(), "{}", expression);
+
+            //ip packets without the link & vlan headers
+            if let Some(ip) = &sliced.ip {
+                let ip_start = match ip {
+                    InternetSlice::Ipv4(header) => header.slice().as_ptr() as usize - packet.as_ptr() as usize,
+                    InternetSlice::Ipv6(header, _) => header.slice().as_ptr() as usize - packet.as_ptr() as usize,
+                };
+                let ip_packet = &packet[ip_start..];
+                let expected = filter.applies_to_slice(&SlicedPacket::from_ip(ip_packet).unwrap());
+                assert_eq!(expected, filter.applies_to_ip(ip_packet).unwrap(), "{}", expression);
+                let headers = PacketHeaders::from_ip_slice(ip_packet).unwrap();
+                assert_eq!(expected, filter.applies_to_headers(&headers), "{}", expression);
+            }
+        }
+    }
+}
+
+#[test]
+fn raw_evaluation_decodes_referenced_layers() {
+    //ipv4 packet with a truncated tcp header (only the ports are present)
+    let mut packet = Vec::new();
+    PacketBuilder::ethernet2([1;6], [2;6])
+        .single_vlan(100)
+        .ipv4([10,0,0,1], [10,0,0,2], 20)
+        .tcp(1234, 80, 0, 1024)
+        .write(&mut packet, &[])
+        .unwrap();
+    let truncated = &packet[..packet.len() - 16];
+    assert!(SlicedPacket::from_ethernet(truncated).is_err());
+
+    //layers before the transport layer can be evaluated
+    for (expression, expected) in [("vlan 100", true), ("ether src 1:1:1:1:1:1", true), ("ip", true), ("host 10.0.0.1", true), ("tcp", true), ("udp", false)].iter() {
+        let filter = FilterExpression::parse(expression).unwrap();
+        assert_eq!(*expected, filter.applies_to_ethernet(truncated).unwrap(), "{}", expression);
+    }
+    //referencing the transport layer results in an error
+    for expression in ["port 80", "tcp flags syn", "tcp or port 80", "payloadlen 0"].iter() {
+        let filter = FilterExpression::parse(expression).unwrap();
+        assert!(filter.applies_to_ethernet(truncated).is_err(), "{}", expression);
+    }
+
+    //same for field based filters
+    let vlan_filter = etherparse::packet_filter::Filter {
+        vlan: ElementFilter::Some(VlanFilter::Single(Some(100))),
+        ..Default::default()
+    };
+    assert!(vlan_filter.applies_to_ethernet(truncated).unwrap());
+    assert!(vlan_filter.applies_to_ethernet(&packet).unwrap());
+    let transport_filter = etherparse::packet_filter::Filter {
+        transport: ElementFilter::Some(TransportFilter::Tcp{ source_port: None, destination_port: Some(80) }),
+        ..Default::default()
+    };
+    assert!(transport_filter.applies_to_ethernet(truncated).is_err());
+    assert!(transport_filter.applies_to_ethernet(&packet).unwrap());
+    assert!(transport_filter.applies_to_ip(&packet[18..]).unwrap());
+    assert!(transport_filter.applies_to_ip(&truncated[18..]).is_err());
+
+    //the ip header is always decoded when starting at the ip layer
+    let ip_filter = FilterExpression::parse("vlan or ip6").unwrap();
+    assert!(!ip_filter.applies_to_ip(&packet[18..]).unwrap());
+    assert!(ip_filter.applies_to_ip(&[0x45]).is_err());
+}
@@ -2,6 +2,8 @@ use super::*;
 use etherparse::packet_filter::*;
 use proptest::*;
 
+mod expression;
+
 
 #[test]
 fn default() {
@@ -352,6 +354,8 @@ impl PacketFilterTest {
 
                 None => None
             },
+            authentication_header: None,
+            esp: None,
             transport: match &self.transport {
                 Some(TransportHeader::Udp(header)) => {
                     header.write(&mut transport_data).unwrap();
@@ -367,6 +371,21 @@ impl PacketFilterTest {
         };
 
         assert_eq!(expected_result, self.filter.applies_to_slice(&slice));
+
+        //decoded headers
+        let headers = PacketHeaders {
+            link: self.link.clone(),
+            vlan: self.vlan.clone(),
+            ip: self.ip.clone(),
+            transport: self.transport.clone(),
+            payload: &payload[..]
+        };
+        assert_eq!(expected_result, self.filter.applies_to_headers(&headers));
+
+        //filter as part of an expression
+        let expression = FilterExpression::from(self.filter.clone());
+        assert_eq!(expected_result, expression.applies_to_slice(&slice));
+        assert_eq!(expected_result, expression.applies_to_headers(&headers));
     }
 }
 ///Test that all known packet compositions are parsed correctly.
@@ -458,6 +477,11 @@ mod link_filter {
                     value
                 })
             }.applies_to_slice(&eth_slice));
+            //ppp header
+            assert_eq!(false, Ethernet2 {
+                source: None,
+                destination: None
+            }.applies_to_slice(&LinkSlice::Ppp(PppHeaderSlice::from_slice(&[0, 0x21]).unwrap())));
         }
     }
 }
@@ -21,4 +21,951 @@ fn from_ip_errors() {
             );
         }
     }
-}
\ No newline at end of file
+}
+mod tunneled {
+    use super::*;
+
+    ///Serializes an inner ethernet ipv4 udp packet.
+    fn inner_eth_ipv4_udp() -> Vec<u8> {
+        let builder = PacketBuilder::ethernet2([1,2,3,4,5,6],[7,8,9,10,11,12])
+                      .ipv4([10,0,0,1], [10,0,0,2], 20)
+                      .udp(1234, 5678);
+        let mut result = Vec::with_capacity(builder.size(4));
+        builder.write(&mut result, &[1,2,3,4]).unwrap();
+        result
+    }
+
+    #[test]
+    fn vxlan() {
+        let inner = inner_eth_ipv4_udp();
+        let mut packet = Vec::new();
+        PacketBuilder::ethernet2([1,2,3,4,5,6],[7,8,9,10,11,12])
+            .ipv4([192,168,1,1], [192,168,1,2], 20)
+            .udp(49152, VXLAN_PORT)
+            .vxlan(0x123456)
+            .write(&mut packet, &inner)
+            .unwrap();
+
+        let result = SlicedPacket::from_ethernet_tunneled(&packet).unwrap();
+
+        //outer packet is identical to the normal slicing
+        assert_eq!(SlicedPacket::from_ethernet(&packet).unwrap(), result.outer);
+
+        //tunnel header
+        match result.tunnel.unwrap() {
+            TunnelSlice::Vxlan(header) => {
+                assert_eq!(0x123456, header.vni());
+                assert!(header.vni_valid());
+            },
+            value => panic!("unexpected tunnel {:?}", value)
+        }
+
+        //inner packet
+        assert_eq!(Some(SlicedPacket::from_ethernet(&inner).unwrap()), result.inner);
+        assert_matches!(result.error, None);
+    }
+
+    #[test]
+    fn geneve() {
+        let inner = inner_eth_ipv4_udp();
+        let mut packet = Vec::new();
+        PacketBuilder::ipv6([1;16], [2;16], 20)
+            .udp(49152, GENEVE_PORT)
+            .geneve(0x654321)
+            .options_raw(&[0,1,2,0])
+            .unwrap()
+            .write(&mut packet, &inner)
+            .unwrap();
+
+        let result = SlicedPacket::from_ip_tunneled(&packet).unwrap();
+        assert_eq!(SlicedPacket::from_ip(&packet).unwrap(), result.outer);
+        match result.tunnel.unwrap() {
+            TunnelSlice::Geneve(header) => {
+                assert_eq!(0x654321, header.vni());
+                assert_eq!(EtherType::TransparentEthernetBridging as u16, header.protocol_type());
+                assert_eq!(&[0,1,2,0], header.options());
+            },
+            value => panic!("unexpected tunnel {:?}", value)
+        }
+        assert_eq!(Some(SlicedPacket::from_ethernet(&inner).unwrap()), result.inner);
+    }
+
+    #[test]
+    fn geneve_ip_payload() {
+        //inner ip packet
+        let inner = {
+            let builder = PacketBuilder::ipv4([10,0,0,1], [10,0,0,2], 20)
+                          .udp(1234, 5678);
+            let mut result = Vec::with_capacity(builder.size(4));
+            builder.write(&mut result, &[1,2,3,4]).unwrap();
+            result
+        };
+
+        for protocol_type in &[EtherType::Ipv4 as u16, 0x1234] {
+            //outer packet
+            let mut packet = Vec::new();
+            PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+                .udp(49152, GENEVE_PORT)
+                .write(&mut packet, &{
+                    let mut payload = Vec::new();
+                    GeneveHeader::new(*protocol_type, 1).write(&mut payload).unwrap();
+                    payload.extend_from_slice(&inner);
+                    payload
+                })
+                .unwrap();
+
+            let result = SlicedPacket::from_ip_tunneled(&packet).unwrap();
+            assert_matches!(result.tunnel, Some(TunnelSlice::Geneve(_)));
+            if *protocol_type == EtherType::Ipv4 as u16 {
+                assert_eq!(Some(SlicedPacket::from_ip(&inner).unwrap()), result.inner);
+            } else {
+                //unknown protocol types are not decoded
+                assert_eq!(None, result.inner);
+            }
+        }
+    }
+
+    #[test]
+    fn gtpu() {
+        let inner = {
+            let builder = PacketBuilder::ipv6([1;16], [2;16], 20)
+                          .tcp(1234, 5678, 1, 2);
+            let mut result = Vec::with_capacity(builder.size(4));
+            builder.write(&mut result, &[1,2,3,4]).unwrap();
+            result
+        };
+        let mut packet = Vec::new();
+        PacketBuilder::ethernet2([1,2,3,4,5,6],[7,8,9,10,11,12])
+            .ipv4([192,168,1,1], [192,168,1,2], 20)
+            .udp(GTPU_PORT, GTPU_PORT)
+            .gtpu(0x1234_5678)
+            .sequence_number(2)
+            .pdu_session_container(&GtpuPduSessionContainer{
+                pdu_type: GtpuPduSessionContainer::DL_PDU_SESSION_INFORMATION,
+                qos_flow_identifier: 1,
+                paging_policy_presence: false,
+                reflective_qos_indicator: true
+            })
+            .unwrap()
+            .write(&mut packet, &inner)
+            .unwrap();
+
+        let result = SlicedPacket::from_ethernet_tunneled(&packet).unwrap();
+        assert_eq!(SlicedPacket::from_ethernet(&packet).unwrap(), result.outer);
+        match result.tunnel.unwrap() {
+            TunnelSlice::Gtpu(header) => {
+                assert_eq!(0x1234_5678, header.teid());
+                assert_eq!(Some(2), header.sequence_number());
+                assert_eq!(GTPU_EXTENSION_HEADER_PDU_SESSION_CONTAINER, header.next_extension_header_type());
+            },
+            value => panic!("unexpected tunnel {:?}", value)
+        }
+        assert_eq!(Some(SlicedPacket::from_ip(&inner).unwrap()), result.inner);
+    }
+
+    #[test]
+    fn gtpu_non_g_pdu() {
+        //messages other then G-PDUs don't contain an ip packet
+        let mut header = GtpuHeader::new(0);
+        header.message_type = GTPU_MESSAGE_TYPE_ECHO_REQUEST;
+        header.sequence_number = Some(1);
+        let mut payload = Vec::new();
+        header.write(&mut payload).unwrap();
+
+        let mut packet = Vec::new();
+        PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+            .udp(GTPU_PORT, GTPU_PORT)
+            .write(&mut packet, &payload)
+            .unwrap();
+
+        let result = SlicedPacket::from_ip_tunneled(&packet).unwrap();
+        assert_matches!(result.tunnel, Some(TunnelSlice::Gtpu(_)));
+        assert_eq!(None, result.inner);
+    }
+
+    #[test]
+    fn l2tpv2_data() {
+        //ppp frame containing an ipv4 udp packet
+        let mut ip = Vec::new();
+        PacketBuilder::ipv4([10,0,0,1], [10,0,0,2], 20)
+            .udp(1234, 5678)
+            .write(&mut ip, &[1,2,3,4])
+            .unwrap();
+        let mut payload = vec![
+            0x40, 0x02, //flags (length) & version
+            0, 0, //length
+            0x12, 0x34, //tunnel id
+            0x56, 0x78, //session id
+            0xff, 0x03, 0x00, 0x21 //ppp
+        ];
+        payload.extend_from_slice(&ip);
+        payload[3] = payload.len() as u8;
+
+        let mut packet = Vec::new();
+        PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+            .udp(49152, L2TP_PORT)
+            .write(&mut packet, &payload)
+            .unwrap();
+
+        let result = SlicedPacket::from_ip_tunneled(&packet).unwrap();
+        assert_eq!(SlicedPacket::from_ip(&packet).unwrap(), result.outer);
+        assert_matches!(&result.tunnel, Some(TunnelSlice::L2tpv2(header)) => {
+            assert!(!header.is_control());
+            assert_eq!(0x1234, header.tunnel_id());
+            assert_eq!(0x5678, header.session_id());
+        });
+        let inner = result.inner.unwrap();
+        assert_matches!(&inner.link, Some(LinkSlice::Ppp(ppp)) => assert_eq!(PPP_PROTOCOL_IPV4, ppp.protocol()));
+        let expected = SlicedPacket::from_ip(&ip).unwrap();
+        assert_eq!(expected.ip, inner.ip);
+        assert_eq!(expected.transport, inner.transport);
+        assert_eq!(&[1,2,3,4], inner.payload);
+    }
+
+    #[test]
+    fn l2tpv2_control() {
+        let payload = [
+            0b1100_1000, 0x02, //flags (type, length, sequence) & version
+            0, 20, //length
+            0x12, 0x34, //tunnel id
+            0x00, 0x00, //session id
+            0x00, 0x01, //ns
+            0x00, 0x02, //nr
+            0x80, 0x08, 0, 0, 0, 0, 0, 1 //message type avp
+        ];
+
+        //replies are send from the l2tp port
+        let mut packet = Vec::new();
+        PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+            .udp(L2TP_PORT, 49152)
+            .write(&mut packet, &payload)
+            .unwrap();
+
+        let result = SlicedPacket::from_ip_tunneled(&packet).unwrap();
+        assert_matches!(&result.tunnel, Some(TunnelSlice::L2tpv2(header)) => {
+            assert!(header.is_control());
+            assert_eq!(&payload[..], header.slice());
+            assert_eq!(1, header.avps_iterator().count());
+        });
+        assert_eq!(None, result.inner);
+    }
+
+    #[test]
+    fn l2tpv3_udp() {
+        //session
+        {
+            let mut packet = Vec::new();
+            PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+                .udp(L2TP_PORT, L2TP_PORT)
+                .write(&mut packet, &[0, 3, 0, 0, 0x12, 0x34, 0x56, 0x78, 1, 2, 3, 4])
+                .unwrap();
+            let result = SlicedPacket::from_ip_tunneled(&packet).unwrap();
+            assert_matches!(&result.tunnel, Some(TunnelSlice::L2tpv3Session(header)) => {
+                assert_eq!(0x12345678, header.session_id());
+            });
+            assert_eq!(None, result.inner);
+        }
+        //control
+        {
+            let mut packet = Vec::new();
+            PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+                .udp(L2TP_PORT, L2TP_PORT)
+                .write(&mut packet, &[0b1100_1000, 3, 0, 12, 0x12, 0x34, 0x56, 0x78, 0, 1, 0, 2])
+                .unwrap();
+            let result = SlicedPacket::from_ip_tunneled(&packet).unwrap();
+            assert_matches!(&result.tunnel, Some(TunnelSlice::L2tpv3Control(header)) => {
+                assert_eq!(0x12345678, header.control_connection_id());
+            });
+            assert_eq!(None, result.inner);
+        }
+    }
+
+    #[test]
+    fn l2tpv3_ip() {
+        let ip = |payload: &[u8]| -> Vec<u8> {
+            let mut result = Vec::new();
+            Ipv4Header::new(payload.len() as u16, 20, IpTrafficClass::Layer2TunnelingProtocol, [192,168,1,1], [192,168,1,2])
+                .write(&mut result)
+                .unwrap();
+            result.extend_from_slice(payload);
+            result
+        };
+
+        //session
+        {
+            let packet = ip(&[0x12, 0x34, 0x56, 0x78, 1, 2, 3, 4]);
+            let result = SlicedPacket::from_ip_tunneled(&packet).unwrap();
+            assert_matches!(&result.tunnel, Some(TunnelSlice::L2tpv3Session(header)) => {
+                assert!(header.is_over_ip());
+                assert_eq!(0x12345678, header.session_id());
+            });
+            assert_eq!(None, result.inner);
+        }
+        //control
+        {
+            let packet = ip(&[0, 0, 0, 0, 0b1100_1000, 3, 0, 12, 0x12, 0x34, 0x56, 0x78, 0, 1, 0, 2]);
+            let result = SlicedPacket::from_ip_tunneled(&packet).unwrap();
+            assert_matches!(&result.tunnel, Some(TunnelSlice::L2tpv3Control(header)) => {
+                assert!(header.is_over_ip());
+                assert_eq!(0x12345678, header.control_connection_id());
+                assert_eq!(1, header.ns());
+                assert_eq!(2, header.nr());
+            });
+        }
+        //fragmented packets are not decoded
+        {
+            let mut packet = ip(&[0x12, 0x34, 0x56, 0x78]);
+            //set the more fragments flag
+            packet[6] = 0x20;
+            let result = SlicedPacket::from_ip_tunneled(&packet).unwrap();
+            assert_eq!(None, result.tunnel);
+        }
+        //too short (the outer packet is still returned & the offset is relative to the start of the packet)
+        {
+            let packet = ip(&[0x12, 0x34, 0x56]);
+            let result = SlicedPacket::from_ip_tunneled(&packet).unwrap();
+            assert_eq!(SlicedPacket::from_ip(&packet).unwrap(), result.outer);
+            assert_eq!(None, result.tunnel);
+            assert_matches!(
+                result.error,
+                Some(ReadError::UnexpectedEndOfSlice(value)) => assert_eq!(Ipv4Header::SERIALIZED_SIZE + 4, value)
+            );
+        }
+    }
+
+    #[test]
+    fn l2tpv3_session_format() {
+        //ethernet pseudowire via udp with a cookie & the default l2-specific sublayer
+        {
+            let mut frame = Vec::new();
+            PacketBuilder::ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+                .ipv4([10,0,0,1], [10,0,0,2], 20)
+                .udp(1234, 5678)
+                .write(&mut frame, &[1,2,3,4])
+                .unwrap();
+            let mut payload = vec![
+                0, 3, 0, 0, 0x12, 0x34, 0x56, 0x78, //session header
+                0xc0, 0xff, 0xee, 0x00, 0x11, 0x22, 0x33, 0x44, //cookie
+                0, 0, 0, 0 //l2-specific sublayer
+            ];
+            payload.extend_from_slice(&frame);
+            let mut packet = Vec::new();
+            PacketBuilder::ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+                .ipv4([192,168,1,1], [192,168,1,2], 20)
+                .udp(L2TP_PORT, L2TP_PORT)
+                .write(&mut packet, &payload)
+                .unwrap();
+
+            let format = L2tpv3SessionFormat{
+                cookie_len: 8,
+                sublayer_len: 4,
+                pseudowire_type: L2tpv3PseudowireType::Ethernet
+            };
+            let result = SlicedPacket::from_ethernet_tunneled_l2tpv3(&packet, &format).unwrap();
+            assert_eq!(SlicedPacket::from_ethernet(&packet).unwrap(), result.outer);
+            assert_matches!(&result.tunnel, Some(TunnelSlice::L2tpv3Session(header)) => {
+                assert_eq!(0x12345678, header.session_id());
+            });
+            assert_eq!(Some(SlicedPacket::from_ethernet(&frame).unwrap()), result.inner);
+
+            //without the session format the frame is not decoded
+            assert_eq!(None, SlicedPacket::from_ethernet_tunneled(&packet).unwrap().inner);
+
+            //too short for the cookie & sublayer
+            let header_len = packet.len() - payload.len();
+            let result = SlicedPacket::from_ethernet_tunneled_l2tpv3(&packet[..header_len + 19], &format).unwrap();
+            assert_matches!(result.tunnel, Some(TunnelSlice::L2tpv3Session(_)));
+            assert_eq!(None, result.inner);
+            assert_matches!(
+                result.error,
+                Some(ReadError::UnexpectedEndOfSlice(value)) => assert_eq!(header_len + 20, value)
+            );
+        }
+        //ppp pseudowire directly over ip without cookie
+        {
+            let mut ip = Vec::new();
+            PacketBuilder::ipv6([1;16], [2;16], 20)
+                .udp(1234, 5678)
+                .write(&mut ip, &[1,2,3,4])
+                .unwrap();
+            let mut payload = vec![
+                0x12, 0x34, 0x56, 0x78, //session id
+                0x00, 0x57 //ppp protocol (ipv6)
+            ];
+            payload.extend_from_slice(&ip);
+            let mut packet = Vec::new();
+            Ipv4Header::new(payload.len() as u16, 20, IpTrafficClass::Layer2TunnelingProtocol, [192,168,1,1], [192,168,1,2])
+                .write(&mut packet)
+                .unwrap();
+            packet.extend_from_slice(&payload);
+
+            let format = L2tpv3SessionFormat{
+                cookie_len: 0,
+                sublayer_len: 0,
+                pseudowire_type: L2tpv3PseudowireType::Ppp
+            };
+            let result = SlicedPacket::from_ip_tunneled_l2tpv3(&packet, &format).unwrap();
+            let inner = result.inner.unwrap();
+            assert_matches!(&inner.link, Some(LinkSlice::Ppp(ppp)) => assert_eq!(PPP_PROTOCOL_IPV6, ppp.protocol()));
+            let expected = SlicedPacket::from_ip(&ip).unwrap();
+            assert_eq!(expected.ip, inner.ip);
+            assert_eq!(expected.transport, inner.transport);
+            assert_eq!(&[1,2,3,4], inner.payload);
+        }
+        //control messages are not affected by the session format
+        {
+            let mut packet = Vec::new();
+            PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+                .udp(L2TP_PORT, L2TP_PORT)
+                .write(&mut packet, &[0b1100_1000, 3, 0, 12, 0x12, 0x34, 0x56, 0x78, 0, 1, 0, 2])
+                .unwrap();
+            let format = L2tpv3SessionFormat{
+                cookie_len: 4,
+                sublayer_len: 0,
+                pseudowire_type: L2tpv3PseudowireType::Ethernet
+            };
+            let result = SlicedPacket::from_ip_tunneled_l2tpv3(&packet, &format).unwrap();
+            assert_matches!(&result.tunnel, Some(TunnelSlice::L2tpv3Control(_)));
+            assert_eq!(None, result.inner);
+        }
+    }
+
+    #[test]
+    fn l2tp_errors() {
+        use crate::ReadError::*;
+        let udp = |payload: &[u8]| -> Vec<u8> {
+            let mut packet = Vec::new();
+            PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+                .udp(49152, L2TP_PORT)
+                .write(&mut packet, payload)
+                .unwrap();
+            packet
+        };
+        let payload_offset = Ipv4Header::SERIALIZED_SIZE + UdpHeader::SERIALIZED_SIZE;
+
+        //unknown version (the outer packet is still returned)
+        {
+            let packet = udp(&[0, 1, 0, 0, 0, 0]);
+            let result = SlicedPacket::from_ip_tunneled(&packet).unwrap();
+            assert_eq!(SlicedPacket::from_ip(&packet).unwrap(), result.outer);
+            assert_eq!(None, result.tunnel);
+            assert_eq!(None, result.inner);
+            assert_matches!(result.error, Some(L2tpUnexpectedVersion(1)));
+        }
+        //too short for the version
+        assert_matches!(
+            SlicedPacket::from_ip_tunneled(&udp(&[0])).unwrap().error,
+            Some(UnexpectedEndOfSlice(value)) => assert_eq!(payload_offset + 2, value)
+        );
+        //l2tpv2 header too short
+        assert_matches!(
+            SlicedPacket::from_ip_tunneled(&udp(&[0, 2, 0])).unwrap().error,
+            Some(UnexpectedEndOfSlice(value)) => assert_eq!(payload_offset + 6, value)
+        );
+        //ppp header missing (the outer packet & tunnel header are still returned)
+        {
+            let packet = udp(&[0, 2, 0, 0, 0, 0]);
+            let result = SlicedPacket::from_ip_tunneled(&packet).unwrap();
+            assert_eq!(SlicedPacket::from_ip(&packet).unwrap(), result.outer);
+            assert_matches!(result.tunnel, Some(TunnelSlice::L2tpv2(_)));
+            assert_eq!(None, result.inner);
+            assert_matches!(result.error, Some(UnexpectedEndOfSlice(_)));
+        }
+    }
+
+    #[test]
+    fn no_tunnel() {
+        //udp with a different port
+        {
+            let mut packet = Vec::new();
+            PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+                .udp(VXLAN_PORT, 1234)
+                .write(&mut packet, &[1,2,3,4,5,6,7,8,9,10])
+                .unwrap();
+            let result = SlicedPacket::from_ip_tunneled(&packet).unwrap();
+            assert_eq!(SlicedPacket::from_ip(&packet).unwrap(), result.outer);
+            assert_eq!(None, result.tunnel);
+            assert_eq!(None, result.inner);
+            assert_matches!(result.error, None);
+        }
+        //tcp
+        {
+            let mut packet = Vec::new();
+            PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+                .tcp(1234, VXLAN_PORT, 1, 2)
+                .write(&mut packet, &[1,2,3,4,5,6,7,8,9,10])
+                .unwrap();
+            let result = SlicedPacket::from_ip_tunneled(&packet).unwrap();
+            assert_eq!(None, result.tunnel);
+            assert_eq!(None, result.inner);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        use crate::ReadError::*;
+
+        let inner = inner_eth_ipv4_udp();
+        let mut packet = Vec::new();
+        PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+            .udp(49152, VXLAN_PORT)
+            .vxlan(1)
+            .write(&mut packet, &inner)
+            .unwrap();
+
+        //tunnel header too short (the outer packet is still returned)
+        let outer_len = Ipv4Header::SERIALIZED_SIZE + UdpHeader::SERIALIZED_SIZE;
+        {
+            let result = SlicedPacket::from_ip_tunneled(&packet[..outer_len + 3]).unwrap();
+            assert_eq!(SlicedPacket::from_ip(&packet[..outer_len + 3]).unwrap(), result.outer);
+            assert_eq!(None, result.tunnel);
+            assert_eq!(None, result.inner);
+            assert_matches!(
+                result.error,
+                Some(UnexpectedEndOfSlice(value)) if value == outer_len + VxlanHeader::SERIALIZED_SIZE
+            );
+        }
+
+        //inner packet too short (the outer packet & tunnel header are still returned)
+        let inner_start = outer_len + VxlanHeader::SERIALIZED_SIZE;
+        let result = SlicedPacket::from_ip_tunneled(&packet[..inner_start + 4]).unwrap();
+        assert_eq!(SlicedPacket::from_ip(&packet[..inner_start + 4]).unwrap(), result.outer);
+        assert_matches!(result.tunnel, Some(TunnelSlice::Vxlan(_)));
+        assert_eq!(None, result.inner);
+        assert_matches!(
+            result.error,
+            Some(UnexpectedEndOfSlice(value)) if value == inner_start + Ethernet2Header::SERIALIZED_SIZE
+        );
+
+        //unexpected gtpu version (the outer packet is still returned)
+        {
+            let mut packet = Vec::new();
+            PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+                .udp(GTPU_PORT, GTPU_PORT)
+                .write(&mut packet, &[0xf0, 0xff, 0, 0,  0, 0, 0, 0])
+                .unwrap();
+            let result = SlicedPacket::from_ip_tunneled(&packet).unwrap();
+            assert_eq!(SlicedPacket::from_ip(&packet).unwrap(), result.outer);
+            assert_eq!(None, result.tunnel);
+            assert_matches!(result.error, Some(GtpuUnexpectedVersion(7)));
+        }
+    }
+
+    #[test]
+    fn malformed_inner() {
+        //geneve with an ipv4 protocol type, but an invalid ip header as payload
+        let mut payload = Vec::new();
+        GeneveHeader::new(EtherType::Ipv4 as u16, 1).write(&mut payload).unwrap();
+        payload.extend_from_slice(&[0x65, 0, 0, 0]);
+        let mut packet = Vec::new();
+        PacketBuilder::ethernet2([1,2,3,4,5,6],[7,8,9,10,11,12])
+            .ipv4([192,168,1,1], [192,168,1,2], 20)
+            .udp(49152, GENEVE_PORT)
+            .write(&mut packet, &payload)
+            .unwrap();
+        let result = SlicedPacket::from_ethernet_tunneled(&packet).unwrap();
+        assert_eq!(SlicedPacket::from_ethernet(&packet).unwrap(), result.outer);
+        assert_matches!(result.tunnel, Some(TunnelSlice::Geneve(_)));
+        assert_eq!(None, result.inner);
+        assert_matches!(result.error, Some(ReadError::UnexpectedEndOfSlice(_)));
+
+        //gtpu g-pdu with a payload that is not an ip packet
+        let mut payload = Vec::new();
+        GtpuHeader::new(1).write(&mut payload).unwrap();
+        payload.extend_from_slice(&[0x10, 0, 0, 0]);
+        let mut packet = Vec::new();
+        PacketBuilder::ipv6([1;16], [2;16], 20)
+            .udp(GTPU_PORT, GTPU_PORT)
+            .write(&mut packet, &payload)
+            .unwrap();
+        let result = SlicedPacket::from_ip_tunneled(&packet).unwrap();
+        assert_eq!(SlicedPacket::from_ip(&packet).unwrap(), result.outer);
+        assert_matches!(result.tunnel, Some(TunnelSlice::Gtpu(_)));
+        assert_eq!(None, result.inner);
+        assert_matches!(result.error, Some(ReadError::IpUnsupportedVersion(1)));
+    }
+}
+
+mod decapsulated {
+    use super::*;
+
+    #[test]
+    fn ip_in_ip() {
+        //all combinations of inner & outer ip versions
+        let ipv4 = IpHeader::Version4(Ipv4Header::new(0, 20, IpTrafficClass::Udp, [192,168,1,1], [192,168,1,2]));
+        let ipv6 = IpHeader::Version6(Ipv6Header{
+            traffic_class: 0,
+            flow_label: 0,
+            payload_length: 0,
+            next_header: 0,
+            hop_limit: 20,
+            source: [1;16],
+            destination: [2;16]
+        });
+        for outer in &[ipv4.clone(), ipv6.clone()] {
+            for inner in &[ipv4.clone(), ipv6.clone()] {
+                let mut packet = Vec::new();
+                PacketBuilder::ethernet2([1,2,3,4,5,6],[7,8,9,10,11,12])
+                    .ip(outer.clone())
+                    .ip(inner.clone())
+                    .udp(1234, 5678)
+                    .write(&mut packet, &[1,2,3,4])
+                    .unwrap();
+
+                let result = SlicedPacket::from_ethernet_decapsulated(&packet, IP_MAX_DECAPSULATION_DEPTH).unwrap();
+
+                //outer packet
+                assert_eq!(SlicedPacket::from_ethernet(&packet).unwrap(), result.outer);
+                assert_eq!(None, result.outer.transport);
+                let outer_len = Ethernet2Header::SERIALIZED_SIZE + outer.header_len();
+                assert_eq!(&packet[outer_len..], result.outer.payload);
+
+                //inner packet
+                assert_eq!(1, result.depth());
+                let expected = SlicedPacket::from_ip(&packet[outer_len..]).unwrap();
+                assert_eq!(Some(expected.clone()), result.encapsulated[0]);
+                assert_eq!(&expected, result.innermost());
+                assert_eq!(&[1,2,3,4], result.innermost().payload);
+                assert_matches!(result.innermost().transport, Some(TransportSlice::Udp(_)));
+                assert_matches!(result.error, None);
+            }
+        }
+    }
+
+    #[test]
+    fn depth_limit() {
+        //build a packet with 3 encapsulated packets
+        let mut packet = Vec::new();
+        PacketBuilder::ipv4([1,1,1,1], [2,2,2,2], 20)
+            .ipv6([3;16], [4;16], 20)
+            .ipv4([5,5,5,5], [6,6,6,6], 20)
+            .ipv6([7;16], [8;16], 20)
+            .tcp(1, 2, 3, 4)
+            .write(&mut packet, &[1,2,3,4])
+            .unwrap();
+
+        //no decapsulation
+        {
+            let result = SlicedPacket::from_ip_decapsulated(&packet, 0).unwrap();
+            assert_eq!(SlicedPacket::from_ip(&packet).unwrap(), result.outer);
+            assert_eq!(0, result.depth());
+            assert_eq!(&result.outer, result.innermost());
+        }
+
+        //limited decapsulation
+        for max_depth in 1..3 {
+            let result = SlicedPacket::from_ip_decapsulated(&packet, max_depth).unwrap();
+            assert_eq!(max_depth, result.depth());
+            //the rest is kept as payload
+            assert_eq!(None, result.innermost().transport);
+        }
+
+        //full decapsulation
+        for max_depth in &[3, 4, IP_MAX_DECAPSULATION_DEPTH, IP_MAX_DECAPSULATION_DEPTH + 1] {
+            let result = SlicedPacket::from_ip_decapsulated(&packet, *max_depth).unwrap();
+            assert_eq!(3, result.depth());
+            match &result.innermost().ip {
+                Some(InternetSlice::Ipv6(header, _)) => {
+                    assert_eq!([7;16], header.source());
+                },
+                value => panic!("unexpected ip {:?}", value)
+            }
+            assert_matches!(result.innermost().transport, Some(TransportSlice::Tcp(_)));
+            assert_eq!(&[1,2,3,4], result.innermost().payload);
+        }
+    }
+
+    #[test]
+    fn max_depth() {
+        //build a packet with more encapsulated packets then supported
+        let mut builder = PacketBuilder::ipv4([1,1,1,1], [2,2,2,2], 20);
+        for _ in 0..IP_MAX_DECAPSULATION_DEPTH + 1 {
+            builder = builder.ipv4([1,1,1,1], [2,2,2,2], 20);
+        }
+        let mut packet = Vec::new();
+        builder.udp(1,2).write(&mut packet, &[]).unwrap();
+
+        let result = SlicedPacket::from_ip_decapsulated(&packet, IP_MAX_DECAPSULATION_DEPTH + 1).unwrap();
+        assert_eq!(IP_MAX_DECAPSULATION_DEPTH, result.depth());
+        let innermost = result.innermost();
+        assert_eq!(None, innermost.transport);
+        assert_eq!(Ipv4Header::SERIALIZED_SIZE + UdpHeader::SERIALIZED_SIZE, innermost.payload.len());
+    }
+
+    #[test]
+    fn fragmented() {
+        //fragmented ipv4 packets are not decapsulated
+        {
+            let mut header = Ipv4Header::new(0, 20, IpTrafficClass::Udp, [1,1,1,1], [2,2,2,2]);
+            header.more_fragments = true;
+            let mut packet = Vec::new();
+            PacketBuilder::ip(IpHeader::Version4(header))
+                .ipv4([1,1,1,1], [2,2,2,2], 20)
+                .udp(1,2)
+                .write(&mut packet, &[])
+                .unwrap();
+            let result = SlicedPacket::from_ip_decapsulated(&packet, 1).unwrap();
+            assert_eq!(0, result.depth());
+        }
+        //ipv6 packets with a fragmentation header are not decapsulated
+        {
+            let mut packet = Vec::new();
+            Ipv6Header{
+                traffic_class: 0,
+                flow_label: 0,
+                payload_length: 8 + 20,
+                next_header: IpTrafficClass::IPv6FragmentationHeader as u8,
+                hop_limit: 20,
+                source: [1;16],
+                destination: [2;16]
+            }.write(&mut packet).unwrap();
+            packet.extend_from_slice(&[IpTrafficClass::IPv4 as u8, 0, 0, 1, 0, 0, 0, 0]);
+            Ipv4Header::new(0, 20, IpTrafficClass::Udp, [1,1,1,1], [2,2,2,2]).write(&mut packet).unwrap();
+            let result = SlicedPacket::from_ip_decapsulated(&packet, 1).unwrap();
+            assert_eq!(0, result.depth());
+        }
+    }
+
+    #[test]
+    fn errors() {
+        use crate::ReadError::*;
+
+        let mut packet = Vec::new();
+        PacketBuilder::ethernet2([1,2,3,4,5,6],[7,8,9,10,11,12])
+            .ipv4([1,1,1,1], [2,2,2,2], 20)
+            .ipv6([3;16], [4;16], 20)
+            .udp(1, 2)
+            .write(&mut packet, &[])
+            .unwrap();
+
+        //inner ip header too short (the outer packet is still returned & the offset is relative to the start of the packet)
+        let inner_start = Ethernet2Header::SERIALIZED_SIZE + Ipv4Header::SERIALIZED_SIZE;
+        {
+            let result = SlicedPacket::from_ethernet_decapsulated(&packet[..inner_start + 10], 1).unwrap();
+            assert_eq!(SlicedPacket::from_ethernet(&packet[..inner_start + 10]).unwrap(), result.outer);
+            assert_eq!(0, result.depth());
+            assert_matches!(
+                result.error,
+                Some(UnexpectedEndOfSlice(value)) if value == inner_start + Ipv6Header::SERIALIZED_SIZE
+            );
+        }
+
+        //wrong inner ip version
+        let mut corrupted = packet.clone();
+        corrupted[inner_start] = 0x40;
+        {
+            let result = SlicedPacket::from_ethernet_decapsulated(&corrupted, 1).unwrap();
+            assert_eq!(SlicedPacket::from_ethernet(&corrupted).unwrap(), result.outer);
+            assert_eq!(0, result.depth());
+            assert_matches!(result.error, Some(Ipv6UnexpectedVersion(4)));
+        }
+
+        //error in a deeper encapsulated packet (the packets before are still returned)
+        {
+            let mut packet = Vec::new();
+            PacketBuilder::ipv4([1,1,1,1], [2,2,2,2], 20)
+                .ipv6([3;16], [4;16], 20)
+                .ipv4([5,5,5,5], [6,6,6,6], 20)
+                .udp(1, 2)
+                .write(&mut packet, &[])
+                .unwrap();
+            let second_start = Ipv4Header::SERIALIZED_SIZE + Ipv6Header::SERIALIZED_SIZE;
+            packet[second_start] = 0x60;
+            let result = SlicedPacket::from_ip_decapsulated(&packet, IP_MAX_DECAPSULATION_DEPTH).unwrap();
+            assert_eq!(1, result.depth());
+            assert_matches!(result.error, Some(Ipv4UnexpectedVersion(6)));
+        }
+    }
+}
+
+mod ipsec {
+    use super::*;
+
+    const AUTH: u8 = IpTrafficClass::IPv6AuthenticationHeader as u8;
+    const ESP: u8 = IpTrafficClass::IPv6EncapSecurityPayload as u8;
+
+    fn ipv4(protocol: IpTrafficClass, payload_len: usize) -> Vec<u8> {
+        let mut result = Vec::new();
+        Ipv4Header::new(payload_len as u16, 20, protocol, [192,168,1,1], [192,168,1,2])
+            .write(&mut result)
+            .unwrap();
+        result
+    }
+
+    fn ipv6(next_header: u8, payload_len: usize) -> Vec<u8> {
+        let mut result = Vec::new();
+        Ipv6Header{
+            traffic_class: 0,
+            flow_label: 0,
+            payload_length: payload_len as u16,
+            next_header,
+            hop_limit: 20,
+            source: [1;16],
+            destination: [2;16]
+        }.write(&mut result).unwrap();
+        result
+    }
+
+    fn auth(next_header: u8) -> AuthenticationHeader {
+        AuthenticationHeader::new(next_header, 0x1234_5678, 42, &[1,2,3,4,5,6,7,8,9,10,11,12]).unwrap()
+    }
+
+    #[test]
+    fn ipv4_auth_udp() {
+        let auth = auth(IpTrafficClass::Udp as u8);
+        let udp = UdpHeader::without_ipv4_checksum(1234, 5678, 4).unwrap();
+
+        let mut packet = ipv4(IpTrafficClass::IPv6AuthenticationHeader, auth.header_len() + 8 + 4);
+        auth.write(&mut packet).unwrap();
+        udp.write(&mut packet).unwrap();
+        packet.extend_from_slice(&[1,2,3,4]);
+
+        let result = SlicedPacket::from_ip(&packet).unwrap();
+        assert_eq!(Some(auth.clone()), result.authentication_header.as_ref().map(|value| value.to_header()));
+        assert_eq!(None, result.esp);
+        assert_matches!(&result.transport, Some(TransportSlice::Udp(value)) => assert_eq!(udp, value.to_header()));
+        assert_eq!(&[1,2,3,4], result.payload);
+    }
+
+    #[test]
+    fn ipv4_esp() {
+        let esp = EspHeader::new(0x1234_5678, 42);
+        let mut packet = ipv4(IpTrafficClass::IPv6EncapSecurityPayload, 8 + 4);
+        esp.write(&mut packet).unwrap();
+        packet.extend_from_slice(&[1,2,3,4]);
+
+        let result = SlicedPacket::from_ip(&packet).unwrap();
+        assert_eq!(None, result.authentication_header);
+        assert_eq!(Some(esp), result.esp.as_ref().map(|value| value.to_header()));
+        assert_eq!(None, result.transport);
+        assert_eq!(&[1,2,3,4], result.payload);
+    }
+
+    #[test]
+    fn ipv4_auth_esp() {
+        let auth = auth(ESP);
+        let esp = EspHeader::new(0x9abc_def0, 43);
+        let mut packet = ipv4(IpTrafficClass::IPv6AuthenticationHeader, auth.header_len() + 8 + 4);
+        auth.write(&mut packet).unwrap();
+        esp.write(&mut packet).unwrap();
+        packet.extend_from_slice(&[1,2,3,4]);
+
+        let result = SlicedPacket::from_ip(&packet).unwrap();
+        assert_eq!(0x1234_5678, result.authentication_header.unwrap().spi());
+        assert_eq!(0x9abc_def0, result.esp.unwrap().spi());
+        assert_eq!(&[1,2,3,4], result.payload);
+    }
+
+    #[test]
+    fn ipv6_auth_tcp() {
+        let auth = auth(IpTrafficClass::Tcp as u8);
+        let tcp = TcpHeader::new(1, 2, 3, 4);
+
+        let mut packet = ipv6(AUTH, auth.header_len() + tcp.header_len() as usize + 4);
+        auth.write(&mut packet).unwrap();
+        tcp.write(&mut packet).unwrap();
+        packet.extend_from_slice(&[1,2,3,4]);
+
+        let result = SlicedPacket::from_ip(&packet).unwrap();
+
+        //authentication header is also part of the extension headers
+        assert_matches!(&result.ip, Some(InternetSlice::Ipv6(_, extensions)) => {
+            assert_matches!(&extensions[0], Some((AUTH, ext)) => assert_eq!(auth.header_len(), ext.slice().len()));
+            assert_eq!(None, extensions[1]);
+        });
+        assert_eq!(Some(auth), result.authentication_header.as_ref().map(|value| value.to_header()));
+        assert_matches!(&result.transport, Some(TransportSlice::Tcp(value)) => assert_eq!(tcp, value.to_header()));
+        assert_eq!(&[1,2,3,4], result.payload);
+    }
+
+    #[test]
+    fn ipv6_esp() {
+        let esp = EspHeader::new(0x1234_5678, 42);
+
+        //directly after the ip header & after an extension header
+        for with_extension in &[false, true] {
+            let mut packet = if *with_extension {
+                let mut result = ipv6(IpTrafficClass::IPv6DestinationOptions as u8, 8 + 8 + 4);
+                result.extend_from_slice(&[ESP, 0, 0, 0, 0, 0, 0, 0]);
+                result
+            } else {
+                ipv6(ESP, 8 + 4)
+            };
+            esp.write(&mut packet).unwrap();
+            packet.extend_from_slice(&[1,2,3,4]);
+
+            let result = SlicedPacket::from_ip(&packet).unwrap();
+            assert_matches!(&result.ip, Some(InternetSlice::Ipv6(_, extensions)) => {
+                assert_eq!(*with_extension, extensions[0].is_some());
+                assert_eq!(None, extensions[1]);
+            });
+            assert_eq!(None, result.authentication_header);
+            assert_eq!(Some(esp.clone()), result.esp.as_ref().map(|value| value.to_header()));
+            assert_eq!(&[1,2,3,4], result.payload);
+        }
+    }
+
+    #[test]
+    fn ipv4_auth_decapsulated() {
+        //ip-in-ip protected by an authentication header (tunnel mode)
+        let mut inner = Vec::new();
+        PacketBuilder::ipv4([10,0,0,1], [10,0,0,2], 20)
+            .udp(1, 2)
+            .write(&mut inner, &[1,2,3,4])
+            .unwrap();
+
+        let auth = auth(IpTrafficClass::IPv4 as u8);
+        let mut packet = ipv4(IpTrafficClass::IPv6AuthenticationHeader, auth.header_len() + inner.len());
+        auth.write(&mut packet).unwrap();
+        packet.extend_from_slice(&inner);
+
+        let result = SlicedPacket::from_ip_decapsulated(&packet, 1).unwrap();
+        assert!(result.outer.authentication_header.is_some());
+        assert_eq!(1, result.depth());
+        assert_eq!(&SlicedPacket::from_ip(&inner).unwrap(), result.innermost());
+    }
+
+    #[test]
+    fn errors() {
+        use crate::ReadError::*;
+
+        //ipv4 authentication header too short
+        {
+            let auth = auth(IpTrafficClass::Udp as u8);
+            let mut packet = ipv4(IpTrafficClass::IPv6AuthenticationHeader, auth.header_len());
+            auth.write(&mut packet).unwrap();
+            assert_matches!(
+                SlicedPacket::from_ip(&packet[..packet.len() - 1]),
+                Err(UnexpectedEndOfSlice(value)) if value == packet.len()
+            );
+        }
+        //ipv6 authentication header too short
+        {
+            let auth = auth(IpTrafficClass::Udp as u8);
+            let mut packet = ipv6(AUTH, auth.header_len());
+            auth.write(&mut packet).unwrap();
+            assert_matches!(
+                SlicedPacket::from_ip(&packet[..packet.len() - 1]),
+                Err(UnexpectedEndOfSlice(value)) if value == packet.len()
+            );
+        }
+        //authentication header payload length zero
+        {
+            let mut packet = ipv4(IpTrafficClass::IPv6AuthenticationHeader, 12);
+            packet.extend_from_slice(&[IpTrafficClass::Udp as u8, 0, 0, 0,  0, 0, 0, 0,  0, 0, 0, 0]);
+            assert_matches!(
+                SlicedPacket::from_ip(&packet),
+                Err(IpAuthenticationHeaderTooSmall(0))
+            );
+        }
+        //esp header too short
+        {
+            let mut packet = ipv6(ESP, 8);
+            EspHeader::new(1, 2).write(&mut packet).unwrap();
+            assert_matches!(
+                SlicedPacket::from_ip(&packet[..packet.len() - 1]),
+                Err(UnexpectedEndOfSlice(value)) if value == packet.len()
+            );
+        }
+    }
+}
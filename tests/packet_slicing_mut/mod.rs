@@ -0,0 +1,249 @@
+use super::*;
+
+///Checks that the mutable slicing results in the same slices as the read only slicing.
+fn assert_same_slices(packet: &[u8], from_ethernet: bool) {
+    let expected = if from_ethernet {
+        SlicedPacket::from_ethernet(packet).unwrap()
+    } else {
+        SlicedPacket::from_ip(packet).unwrap()
+    };
+    let mut buffer = packet.to_vec();
+    let result = if from_ethernet {
+        SlicedPacketMut::from_ethernet(&mut buffer).unwrap()
+    } else {
+        SlicedPacketMut::from_ip(&mut buffer).unwrap()
+    };
+
+    assert_eq!(
+        expected.link.as_ref().map(|link| link.slice()),
+        result.link.as_ref().map(|value| value.slice())
+    );
+    assert_eq!(
+        expected.vlan.as_ref().map(|vlan| match vlan {
+            VlanSlice::SingleVlan(value) => value.slice(),
+            VlanSlice::DoubleVlan(value) => value.slice()
+        }),
+        result.vlan.as_ref().map(|vlan| match vlan {
+            VlanSliceMut::SingleVlan(value) => value.slice(),
+            VlanSliceMut::DoubleVlan(value) => value.slice()
+        })
+    );
+    assert_eq!(
+        expected.ip.as_ref().map(|ip| match ip {
+            InternetSlice::Ipv4(value) => value.slice(),
+            InternetSlice::Ipv6(value, _) => value.slice()
+        }),
+        result.ip.as_ref().map(|ip| match ip {
+            InternetSliceMut::Ipv4(value) => value.slice(),
+            InternetSliceMut::Ipv6(value) => value.slice()
+        })
+    );
+    assert_eq!(
+        expected.transport.as_ref().map(|transport| match transport {
+            TransportSlice::Udp(value) => value.slice(),
+            TransportSlice::Tcp(value) => value.slice()
+        }),
+        result.transport.as_ref().map(|transport| match transport {
+            TransportSliceMut::Udp(value) => value.slice(),
+            TransportSliceMut::Tcp(value) => value.slice()
+        })
+    );
+    assert_eq!(expected.payload, &result.payload[..]);
+}
+
+#[test]
+fn ethernet_vlan_ipv4_udp() {
+    let mut packet = Vec::new();
+    PacketBuilder::ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+        .single_vlan(123)
+        .ipv4([192,168,1,1], [192,168,1,2], 20)
+        .udp(1234, 5678)
+        .write(&mut packet, &[1,2,3,4])
+        .unwrap();
+    assert_same_slices(&packet, true);
+
+    {
+        let sliced = SlicedPacketMut::from_ethernet(&mut packet).unwrap();
+        let SlicedPacketMut{ link, vlan, ip, ip_extensions, transport, payload } = sliced;
+        assert!(ip_extensions.is_empty());
+
+        link.unwrap().set_source([0xa;6]);
+        match vlan.unwrap() {
+            VlanSliceMut::SingleVlan(mut value) => value.set_vlan_identifier(321).unwrap(),
+            value => panic!("unexpected vlan {:?}", value)
+        }
+        match ip.unwrap() {
+            InternetSliceMut::Ipv4(mut value) => {
+                value.set_ttl(19);
+                value.set_destination([10,0,0,1]);
+            },
+            value => panic!("unexpected ip {:?}", value)
+        }
+        match transport.unwrap() {
+            TransportSliceMut::Udp(mut value) => {
+                value.set_destination_port(80);
+                value.update_checksum_ipv4_address([192,168,1,2], [10,0,0,1]);
+            },
+            value => panic!("unexpected transport {:?}", value)
+        }
+        assert_eq!(&[1,2,3,4], payload);
+    }
+
+    //check the modified packet
+    let result = PacketHeaders::from_ethernet_slice(&packet).unwrap();
+    assert_eq!([0xa;6], result.link.as_ref().unwrap().source);
+    assert_matches!(&result.vlan, Some(VlanHeader::Single(value)) => assert_eq!(321, value.vlan_identifier));
+    assert_matches!(&result.ip, Some(IpHeader::Version4(value)) => {
+        assert_eq!(19, value.time_to_live);
+        assert_eq!([10,0,0,1], value.destination);
+    });
+    assert_matches!(&result.transport, Some(TransportHeader::Udp(value)) => assert_eq!(80, value.destination_port));
+    assert_eq!(&[1,2,3,4], result.payload);
+    assert_eq!(
+        PacketChecksums{ ip: ChecksumStatus::Valid, transport: ChecksumStatus::Valid },
+        result.verify_checksums()
+    );
+}
+
+#[test]
+fn ethernet_double_vlan_ipv6_tcp() {
+    let mut packet = Vec::new();
+    PacketBuilder::ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+        .double_vlan(1, 2)
+        .ipv6([1;16], [2;16], 20)
+        .tcp(1234, 5678, 1, 1024)
+        .write(&mut packet, &[1,2,3,4])
+        .unwrap();
+    assert_same_slices(&packet, true);
+
+    {
+        let mut sliced = SlicedPacketMut::from_ethernet(&mut packet).unwrap();
+        assert_matches!(&mut sliced.vlan, Some(VlanSliceMut::DoubleVlan(value)) => {
+            value.inner_mut().set_vlan_identifier(3).unwrap();
+        });
+        assert_matches!(&mut sliced.ip, Some(InternetSliceMut::Ipv6(value)) => {
+            value.set_hop_limit(19);
+            value.set_source([3;16]);
+        });
+        assert_matches!(&mut sliced.transport, Some(TransportSliceMut::Tcp(value)) => {
+            value.set_source_port(80);
+            value.set_syn(true);
+            value.update_checksum_ipv6_address(&[1;16], &[3;16]);
+        });
+    }
+
+    let result = SlicedPacket::from_ethernet(&packet).unwrap();
+    assert_matches!(&result.vlan, Some(VlanSlice::DoubleVlan(value)) => assert_eq!(3, value.inner().vlan_identifier()));
+    assert_matches!(&result.ip, Some(InternetSlice::Ipv6(value, _)) => {
+        assert_eq!(19, value.hop_limit());
+        assert_eq!([3;16], value.source());
+    });
+    assert_matches!(&result.transport, Some(TransportSlice::Tcp(value)) => {
+        assert_eq!(80, value.source_port());
+        assert!(value.syn());
+    });
+    assert!(!result.verify_checksums().contains_invalid());
+    assert_eq!(ChecksumStatus::Valid, result.verify_checksums().transport);
+}
+
+#[test]
+fn ip_extensions() {
+    //ipv6 with an authentication header
+    {
+        let auth = AuthenticationHeader::new(IpTrafficClass::Tcp as u8, 0x1234_5678, 42, &[1,2,3,4]).unwrap();
+        let tcp = TcpHeader::new(1, 2, 3, 4);
+        let mut packet = Vec::new();
+        Ipv6Header{
+            traffic_class: 0,
+            flow_label: 0,
+            payload_length: (auth.header_len() + tcp.header_len() as usize + 4) as u16,
+            next_header: IpTrafficClass::IPv6AuthenticationHeader as u8,
+            hop_limit: 20,
+            source: [1;16],
+            destination: [2;16]
+        }.write(&mut packet).unwrap();
+        auth.write(&mut packet).unwrap();
+        tcp.write(&mut packet).unwrap();
+        packet.extend_from_slice(&[1,2,3,4]);
+        assert_same_slices(&packet, false);
+
+        let sliced = SlicedPacketMut::from_ip(&mut packet).unwrap();
+        assert_eq!(None, sliced.link);
+        assert_eq!(None, sliced.vlan);
+        assert_eq!(auth.header_len(), sliced.ip_extensions.len());
+        assert_eq!(
+            auth,
+            AuthenticationHeaderSlice::from_slice(sliced.ip_extensions).unwrap().to_header()
+        );
+        assert_eq!(&[1,2,3,4], sliced.payload);
+    }
+    //ipv4 with esp
+    {
+        let esp = EspHeader::new(0x1234_5678, 42);
+        let mut packet = Vec::new();
+        Ipv4Header::new(8 + 4, 20, IpTrafficClass::IPv6EncapSecurityPayload, [192,168,1,1], [192,168,1,2])
+            .write(&mut packet)
+            .unwrap();
+        esp.write(&mut packet).unwrap();
+        packet.extend_from_slice(&[1,2,3,4]);
+        assert_same_slices(&packet, false);
+
+        let sliced = SlicedPacketMut::from_ip(&mut packet).unwrap();
+        assert_eq!(8, sliced.ip_extensions.len());
+        assert_eq!(None, sliced.transport);
+        assert_eq!(&[1,2,3,4], sliced.payload);
+    }
+}
+
+#[test]
+fn without_ip() {
+    //ethernet & vlan with an unknown ether type
+    let mut packet = Vec::new();
+    Ethernet2Header{
+        source: [1,2,3,4,5,6],
+        destination: [7,8,9,10,11,12],
+        ether_type: EtherType::VlanTaggedFrame as u16
+    }.write(&mut packet).unwrap();
+    SingleVlanHeader{
+        priority_code_point: 0,
+        drop_eligible_indicator: false,
+        vlan_identifier: 1,
+        ether_type: EtherType::Arp as u16
+    }.write(&mut packet).unwrap();
+    packet.extend_from_slice(&[1,2,3,4]);
+    assert_same_slices(&packet, true);
+
+    let sliced = SlicedPacketMut::from_ethernet(&mut packet).unwrap();
+    assert!(sliced.link.is_some());
+    assert!(sliced.vlan.is_some());
+    assert_eq!(None, sliced.ip);
+    assert!(sliced.ip_extensions.is_empty());
+    assert_eq!(None, sliced.transport);
+    assert_eq!(&[1,2,3,4], sliced.payload);
+}
+
+#[test]
+fn errors() {
+    use crate::ReadError::*;
+
+    assert_matches!(
+        SlicedPacketMut::from_ip(&mut []),
+        Err(UnexpectedEndOfSlice(1))
+    );
+    assert_matches!(
+        SlicedPacketMut::from_ethernet(&mut [0;13]),
+        Err(UnexpectedEndOfSlice(Ethernet2Header::SERIALIZED_SIZE))
+    );
+
+    //truncated udp header
+    let mut packet = Vec::new();
+    PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+        .udp(1234, 5678)
+        .write(&mut packet, &[])
+        .unwrap();
+    let len = packet.len();
+    assert_eq!(
+        format!("{:?}", SlicedPacket::from_ip(&packet[..len - 1])),
+        format!("{:?}", SlicedPacketMut::from_ip(&mut packet[..len - 1]))
+    );
+}
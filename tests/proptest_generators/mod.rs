@@ -9,9 +9,17 @@ pub fn error_field_any() -> impl Strategy<Value = ErrorField> {
         Just(Ipv4Dscp),
         Just(Ipv4Ecn),
         Just(Ipv4FragmentsOffset),
+        Just(Ipv4TimestampOverflow),
+        Just(Ipv4TimestampFlags),
         Just(Ipv6FlowLabel),
         Just(VlanTagPriorityCodePoint),
-        Just(VlanTagVlanId)
+        Just(VlanTagVlanId),
+        Just(VxlanVni),
+        Just(GeneveVersion),
+        Just(GeneveVni),
+        Just(GtpuVersion),
+        Just(GtpuPduType),
+        Just(GtpuQosFlowIdentifier)
     ]
 }
 
@@ -168,7 +176,9 @@ prop_compose! {
 
 static IPV4_KNOWN_PROTOCOLS: &'static [u8] = &[
     IpTrafficClass::Udp as u8,
-    IpTrafficClass::Tcp as u8
+    IpTrafficClass::Tcp as u8,
+    IpTrafficClass::IPv6EncapSecurityPayload as u8,
+    IpTrafficClass::IPv6AuthenticationHeader as u8
 ];
 
 prop_compose! {
@@ -308,7 +318,8 @@ prop_compose! {
         len1 in 0u8..5,
         len2 in 0u8..5,
         //skip fragmenetation header (fixed size 0))
-        len4 in 0u8..5,
+        //authentication header needs a length of at least 1
+        len4 in 1u8..5,
         len5 in 0u8..5,
         len6 in 0u8..5,
         len7 in 0u8..5,
@@ -323,7 +334,11 @@ prop_compose! {
         hdr1 in ipv6_extension_with(IpTrafficClass::IPv6RouteHeader as u8, len1),
         hdr2 in ipv6_extension_with(IpTrafficClass::IPv6DestinationOptions as u8, len2),
         hdr3 in ipv6_extension_with(IpTrafficClass::IPv6FragmentationHeader as u8, 0),
-        hdr4 in ipv6_extension_with(IpTrafficClass::IPv6DestinationOptions as u8, len4),
+        hdr4 in ipv6_extension_with(IpTrafficClass::IPv6DestinationOptions as u8, len4).prop_map(|mut value| {
+            //authentication header length is in 4 octet units
+            value[1] *= 2;
+            value
+        }),
         hdr5 in ipv6_extension_with(IpTrafficClass::IPv6DestinationOptions as u8, len5),
         hdr6 in ipv6_extension_with(IpTrafficClass::MobilityHeader as u8, len6),
         hdr7 in ipv6_extension_with(IpTrafficClass::Hip as u8, len7),
@@ -334,6 +349,11 @@ prop_compose! {
         order in proptest::sample::subsequence((0..IPV6_EXTENSION_HEADER_ORDER.len()).collect::<Vec<usize>>(), 1..IPV6_EXTENSION_HEADER_ORDER.len())
     ) -> Vec<(u8, Vec<u8>)>
     {
+        //skip the esp header (the data after it is encrypted & not parsed as extension headers)
+        let order: Vec<usize> = order.into_iter()
+                                     .filter(|i| IpTrafficClass::IPv6EncapSecurityPayload as u8 != IPV6_EXTENSION_HEADER_ORDER[*i])
+                                     .collect();
+
         let all_headers = vec![hdr0, hdr1, hdr2, hdr3, hdr4, 
                                hdr5, hdr6, hdr7, hdr8, hdr9, 
                                hdr10, hdr11];
@@ -416,3 +436,100 @@ prop_compose! {
         result
     }
 }
+
+prop_compose! {
+    pub(crate) fn esp_any()(
+            spi in any::<u32>(),
+            sequence_number in any::<u32>())
+        -> EspHeader
+    {
+        EspHeader::new(spi, sequence_number)
+    }
+}
+
+prop_compose! {
+    pub(crate) fn ip_authentication_with(next_header: u8)
+        (icv_len in 0usize..(IP_AUTHENTICATION_HEADER_MAX_ICV_LEN/4 + 1))
+        (
+            next_header in proptest::strategy::Just(next_header),
+            spi in any::<u32>(),
+            sequence_number in any::<u32>(),
+            raw_icv in proptest::collection::vec(any::<u8>(), icv_len*4))
+        -> AuthenticationHeader
+    {
+        AuthenticationHeader::new(next_header, spi, sequence_number, &raw_icv[..]).unwrap()
+    }
+}
+
+prop_compose! {
+    pub(crate) fn ip_authentication_any()
+        (next_header in any::<u8>())
+        (result in ip_authentication_with(next_header))
+        -> AuthenticationHeader
+    {
+        result
+    }
+}
+
+prop_compose! {
+    pub(crate) fn vxlan_any()(
+            flags in any::<u8>(),
+            vni in 0u32..0x0100_0000)
+        -> VxlanHeader
+    {
+        VxlanHeader {
+            flags,
+            vni
+        }
+    }
+}
+
+prop_compose! {
+    pub(crate) fn geneve_any()
+        (options_len in 0usize..(GENEVE_MAX_OPTIONS_LENGTH/4 + 1))
+        (
+            version in 0u8..4,
+            control_packet in any::<bool>(),
+            critical_options in any::<bool>(),
+            protocol_type in any::<u16>(),
+            vni in 0u32..0x0100_0000,
+            options in proptest::collection::vec(any::<u8>(), options_len*4))
+        -> GeneveHeader
+    {
+        let mut result = GeneveHeader::new(protocol_type, vni);
+        result.version = version;
+        result.control_packet = control_packet;
+        result.critical_options = critical_options;
+        result.set_options_raw(&options[..]).unwrap();
+        result
+    }
+}
+
+prop_compose! {
+    pub(crate) fn gtpu_any()
+        (extension_headers_count in 0usize..4)
+        (
+            protocol_type in any::<bool>(),
+            message_type in any::<u8>(),
+            length in any::<u16>(),
+            teid in any::<u32>(),
+            sequence_number in proptest::option::of(any::<u16>()),
+            n_pdu_number in proptest::option::of(any::<u8>()),
+            extension_headers in proptest::collection::vec(
+                (1u8..=255, (0usize..8).prop_flat_map(|len| proptest::collection::vec(any::<u8>(), len*4 + 2))),
+                extension_headers_count
+            ))
+        -> GtpuHeader
+    {
+        let mut result = GtpuHeader::new(teid);
+        result.protocol_type = protocol_type;
+        result.message_type = message_type;
+        result.length = length;
+        result.sequence_number = sequence_number;
+        result.n_pdu_number = n_pdu_number;
+        for (extension_type, content) in &extension_headers {
+            result.add_extension_header(*extension_type, &content[..]).unwrap();
+        }
+        result
+    }
+}
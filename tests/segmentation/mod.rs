@@ -0,0 +1,198 @@
+use super::*;
+
+///Checks the checksums of all segments & returns the concatenated payloads.
+fn payloads(segments: &[Vec<u8>], from_ethernet: bool) -> Vec<u8> {
+    let mut result = Vec::new();
+    for segment in segments {
+        let sliced = if from_ethernet {
+            SlicedPacket::from_ethernet(segment).unwrap()
+        } else {
+            SlicedPacket::from_ip(segment).unwrap()
+        };
+        let checksums = sliced.verify_checksums();
+        assert!(!checksums.contains_invalid());
+        result.extend_from_slice(sliced.payload);
+    }
+    result
+}
+
+#[test]
+fn tcp_ipv4() {
+    let payload: Vec<u8> = (0..3000).map(|value| value as u8).collect();
+    let mut packet = Vec::new();
+    PacketBuilder::ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+        .single_vlan(12)
+        .ipv4([192,168,1,1], [192,168,1,2], 20)
+        .tcp(1234, 80, 0xffff_fc00, 1024)
+        .ack(1)
+        .psh()
+        .fin()
+        .cwr()
+        .write(&mut packet, &payload)
+        .unwrap();
+    //ethernet padding is ignored
+    packet.extend_from_slice(&[0;4]);
+
+    let sliced = SlicedPacket::from_ethernet(&packet).unwrap();
+    let segments = sliced.segment(1000).unwrap();
+    assert_eq!(3, segments.len());
+    assert_eq!(payload, payloads(&segments, true));
+
+    for (index, segment) in segments.iter().enumerate() {
+        let headers = PacketHeaders::from_ethernet_slice(segment).unwrap();
+        assert_eq!(sliced.link.as_ref().map(|_| [7,8,9,10,11,12]), headers.link.map(|value| value.destination));
+        assert_matches!(headers.vlan, Some(VlanHeader::Single(vlan)) => assert_eq!(12, vlan.vlan_identifier));
+        assert_matches!(headers.ip, Some(IpHeader::Version4(ip)) => {
+            assert_eq!(index as u16, ip.identification);
+            assert_eq!(20 + 1000, ip.payload_len);
+        });
+        assert_matches!(headers.transport, Some(TransportHeader::Tcp(tcp)) => {
+            assert_eq!(0xffff_fc00u32.wrapping_add(index as u32 * 1000), tcp.sequence_number);
+            assert_eq!(1, tcp.acknowledgment_number);
+            assert!(tcp.ack);
+            assert_eq!(0 == index, tcp.cwr);
+            assert_eq!(2 == index, tcp.psh);
+            assert_eq!(2 == index, tcp.fin);
+        });
+    }
+    assert_eq!(
+        sliced.segment(1000).unwrap(),
+        PacketHeaders::from_ethernet_slice(&packet).unwrap().segment(1000).unwrap()
+    );
+
+    //not larger then the segment size
+    let segments = sliced.segment(3000).unwrap();
+    assert_eq!(1, segments.len());
+    assert_eq!(&packet[..packet.len() - 4], &segments[0][..]);
+}
+
+#[test]
+fn tcp_ipv6_extension_headers() {
+    let payload: Vec<u8> = (0..2000).map(|value| value as u8).collect();
+    let mut tcp = TcpHeader::new(1234, 80, 1, 1024);
+    let ip = Ipv6Header {
+        traffic_class: 0,
+        flow_label: 0,
+        payload_length: (8 + tcp.header_len() as usize + payload.len()) as u16,
+        next_header: IpTrafficClass::IPv6HeaderHopByHop as u8,
+        hop_limit: 20,
+        source: [1;16],
+        destination: [2;16]
+    };
+    tcp.checksum = tcp.calc_checksum_ipv6(&ip, &payload).unwrap();
+    let mut packet = Vec::new();
+    ip.write(&mut packet).unwrap();
+    packet.extend_from_slice(&[6, 0, 1, 4, 0, 0, 0, 0]);
+    tcp.write(&mut packet).unwrap();
+    packet.extend_from_slice(&payload);
+
+    let segments = SlicedPacket::from_ip(&packet).unwrap().segment(1440).unwrap();
+    assert_eq!(2, segments.len());
+    assert_eq!(payload, payloads(&segments, false));
+    for segment in &segments {
+        let ip = Ipv6HeaderSlice::from_slice(segment).unwrap();
+        assert_eq!(segment.len() - 40, usize::from(ip.payload_length()));
+        assert_eq!(0, ip.next_header());
+        assert_eq!([6, 0, 1, 4, 0, 0, 0, 0], segment[40..48]);
+    }
+
+    //packet headers do not contain the extension headers
+    let segments = PacketHeaders::from_ip_slice(&packet).unwrap().segment(1440).unwrap();
+    assert_eq!(2, segments.len());
+    assert_eq!(payload, payloads(&segments, false));
+    assert_eq!(6, Ipv6HeaderSlice::from_slice(&segments[0]).unwrap().next_header());
+}
+
+#[test]
+fn udp() {
+    let payload: Vec<u8> = (0..2500).map(|value| value as u8).collect();
+
+    //ipv4 & ipv6 with checksum
+    for version4 in &[true, false] {
+        let builder = if *version4 {
+            PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+        } else {
+            PacketBuilder::ipv6([1;16], [2;16], 20)
+        };
+        let mut packet = Vec::new();
+        builder.udp(1234, 5678)
+            .write(&mut packet, &payload)
+            .unwrap();
+
+        let segments = SlicedPacket::from_ip(&packet).unwrap().segment(1000).unwrap();
+        assert_eq!(3, segments.len());
+        assert_eq!(payload, payloads(&segments, false));
+        for segment in &segments {
+            let headers = PacketHeaders::from_ip_slice(segment).unwrap();
+            assert_matches!(headers.transport, Some(TransportHeader::Udp(udp)) => {
+                assert_eq!(8 + headers.payload.len(), usize::from(udp.length));
+                assert_ne!(0, udp.checksum);
+            });
+        }
+    }
+
+    //ipv4 without checksum
+    {
+        let mut packet = Vec::new();
+        let udp = UdpHeader::without_ipv4_checksum(1234, 5678, payload.len()).unwrap();
+        Ipv4Header::new(udp.length, 20, IpTrafficClass::Udp, [192,168,1,1], [192,168,1,2])
+            .write(&mut packet)
+            .unwrap();
+        udp.write(&mut packet).unwrap();
+        packet.extend_from_slice(&payload);
+
+        let segments = SlicedPacket::from_ip(&packet).unwrap().segment(1000).unwrap();
+        assert_eq!(payload, payloads(&segments, false));
+        for segment in &segments {
+            assert_eq!([0, 0], segment[26..28]);
+        }
+    }
+}
+
+#[test]
+fn empty_payload() {
+    let mut packet = Vec::new();
+    PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+        .tcp(1234, 80, 1, 1024)
+        .syn()
+        .write(&mut packet, &[])
+        .unwrap();
+    assert_eq!(vec![packet.clone()], SlicedPacket::from_ip(&packet).unwrap().segment(1000).unwrap());
+}
+
+#[test]
+fn errors() {
+    use crate::SegmentationError::*;
+
+    let mut packet = Vec::new();
+    PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+        .udp(1234, 5678)
+        .write(&mut packet, &[0;100])
+        .unwrap();
+
+    //zero segment size
+    assert_matches!(SlicedPacket::from_ip(&packet).unwrap().segment(0), Err(ZeroSegmentSize));
+
+    //fragment
+    Ipv4HeaderSliceMut::from_slice(&mut packet).unwrap().set_more_fragments(true);
+    assert_matches!(SlicedPacket::from_ip(&packet).unwrap().segment(10), Err(IpFragment));
+
+    //no transport header
+    let mut packet = Vec::new();
+    Ipv4Header::new(4, 20, IpTrafficClass::Gre, [192,168,1,1], [192,168,1,2])
+        .write(&mut packet)
+        .unwrap();
+    packet.extend_from_slice(&[1,2,3,4]);
+    assert_matches!(SlicedPacket::from_ip(&packet).unwrap().segment(10), Err(MissingTransportHeader));
+    assert_matches!(PacketHeaders::from_ip_slice(&packet).unwrap().segment(10), Err(MissingTransportHeader));
+
+    //authentication header
+    let auth = etherparse::AuthenticationHeader::new(IpTrafficClass::Udp as u8, 1, 1, &[0;4]).unwrap();
+    let mut packet = Vec::new();
+    Ipv4Header::new((auth.header_len() + 8) as u16, 20, IpTrafficClass::IPv6AuthenticationHeader, [192,168,1,1], [192,168,1,2])
+        .write(&mut packet)
+        .unwrap();
+    auth.write(&mut packet).unwrap();
+    UdpHeader::without_ipv4_checksum(1, 2, 0).unwrap().write(&mut packet).unwrap();
+    assert_matches!(SlicedPacket::from_ip(&packet).unwrap().segment(10), Err(AuthenticationHeader));
+}
@@ -0,0 +1,345 @@
+use super::*;
+use etherparse::sflow::*;
+use std::net::IpAddr;
+
+///Encodes the given values as XDR unsigned integers.
+fn words(values: &[u32]) -> Vec<u8> {
+    values.iter().flat_map(|value| value.to_be_bytes().to_vec()).collect()
+}
+
+///Appends a structure (data format & length) with the given enterprise, format & content.
+fn structure(target: &mut Vec<u8>, enterprise: u32, format: u32, content: &[u8]) {
+    target.extend_from_slice(&words(&[(enterprise << 12) | format, content.len() as u32]));
+    target.extend_from_slice(content);
+}
+
+///Raw packet header record content (padded to a multiple of 4 bytes).
+fn raw_header(protocol: u32, frame_length: u32, header: &[u8]) -> Vec<u8> {
+    let mut result = words(&[protocol, frame_length, 4, header.len() as u32]);
+    result.extend_from_slice(header);
+    while 0 != result.len() & 3 {
+        result.push(0);
+    }
+    result
+}
+
+///Datagram with an ipv4 agent address containing the given samples.
+fn datagram(samples: &[Vec<u8>]) -> Vec<u8> {
+    let mut result = words(&[5, 1, 0x0a00_0001, 2, 100, 5000, samples.len() as u32]);
+    for sample in samples {
+        result.extend_from_slice(sample);
+    }
+    result
+}
+
+fn udp_frame() -> Vec<u8> {
+    let mut frame = Vec::new();
+    PacketBuilder::ethernet2([1;6], [2;6])
+        .ipv4([192,168,1,1], [192,168,1,2], 20)
+        .udp(1234, 53)
+        .write(&mut frame, &[1,2,3,4,5])
+        .unwrap();
+    frame
+}
+
+#[test]
+fn flow_sample() {
+    let frame = udp_frame();
+    //truncated payload (the headers are complete)
+    let truncated = &frame[..frame.len() - 3];
+
+    let mut records = Vec::new();
+    structure(&mut records, 0, 1, &raw_header(1, frame.len() as u32 + 4, truncated));
+    let mut ethernet = words(&[frame.len() as u32 + 4]);
+    ethernet.extend_from_slice(&[1,1,1,1,1,1,0,0,2,2,2,2,2,2,0,0]);
+    ethernet.extend_from_slice(&words(&[0x0800]));
+    structure(&mut records, 0, 2, &ethernet);
+    structure(&mut records, 0, 1001, &words(&[10, 1, 20, 2]));
+    structure(&mut records, 9, 7, &[1,2,3,4]);
+
+    let mut content = words(&[42, (1 << 24) | 3, 512, 1024, 1, 3, 0x4000_0005, 4]);
+    content.extend_from_slice(&records);
+    let mut sample = Vec::new();
+    structure(&mut sample, 0, 1, &content);
+
+    let packet = datagram(&[sample]);
+    let decoded = SflowDatagram::from_slice(&packet).unwrap();
+    assert_eq!(IpAddr::from([10,0,0,1]), decoded.agent_address);
+    assert_eq!(2, decoded.sub_agent_id);
+    assert_eq!(100, decoded.sequence_number);
+    assert_eq!(5000, decoded.uptime);
+    assert_eq!(1, decoded.samples.len());
+
+    let flow = match &decoded.samples[0] {
+        SflowSample::Flow(flow) => flow,
+        value => panic!("unexpected sample {:?}", value)
+    };
+    assert!(!flow.expanded);
+    assert_eq!(42, flow.sequence_number);
+    assert_eq!((1, 3), (flow.source_id_type, flow.source_id_index));
+    assert_eq!((512, 1024, 1), (flow.sampling_rate, flow.sample_pool, flow.drops));
+    assert_eq!(SflowInterface{ format: 0, value: 3 }, flow.input);
+    assert_eq!(SflowInterface{ format: 1, value: 5 }, flow.output);
+    assert_eq!(4, flow.records.len());
+
+    //sampled header
+    match &flow.records[0] {
+        FlowSampleRecord::RawPacketHeader(header) => {
+            assert_eq!(1, header.protocol);
+            assert_eq!(frame.len() as u32 + 4, header.frame_length);
+            assert_eq!(4, header.stripped);
+            assert_eq!(truncated, header.header);
+            let sliced = header.sliced().unwrap().unwrap();
+            assert!(sliced.link.is_some());
+            match sliced.transport {
+                Some(TransportSlice::Udp(udp)) => assert_eq!(53, udp.destination_port()),
+                value => panic!("unexpected transport {:?}", value)
+            }
+            assert_eq!(&[1,2], sliced.payload);
+        },
+        value => panic!("unexpected record {:?}", value)
+    }
+    assert_eq!(
+        FlowSampleRecord::EthernetFrame {
+            length: frame.len() as u32 + 4,
+            source: [1;6],
+            destination: [2;6],
+            ether_type: 0x0800,
+        },
+        flow.records[1]
+    );
+    assert_eq!(
+        FlowSampleRecord::ExtendedSwitch {
+            source_vlan: 10,
+            source_priority: 1,
+            destination_vlan: 20,
+            destination_priority: 2,
+        },
+        flow.records[2]
+    );
+    assert_eq!(FlowSampleRecord::Unknown{ enterprise: 9, format: 7, data: &[1,2,3,4] }, flow.records[3]);
+}
+
+#[test]
+fn expanded_flow_sample() {
+    let mut ip_packet = Vec::new();
+    PacketBuilder::ipv6([1;16], [2;16], 20)
+        .tcp(1234, 80, 0, 1024)
+        .write(&mut ip_packet, &[])
+        .unwrap();
+
+    let mut records = Vec::new();
+    //odd header length to check the padding
+    let mut odd = ip_packet.clone();
+    odd.push(0xff);
+    structure(&mut records, 0, 1, &raw_header(12, odd.len() as u32, &odd));
+    let mut ipv4 = words(&[40, 6]);
+    ipv4.extend_from_slice(&[192,168,1,1,192,168,1,2]);
+    ipv4.extend_from_slice(&words(&[1234, 80, 0x12, 0]));
+    structure(&mut records, 0, 3, &ipv4);
+    let mut ipv6 = words(&[60, 17]);
+    ipv6.extend_from_slice(&[1;16]);
+    ipv6.extend_from_slice(&[2;16]);
+    ipv6.extend_from_slice(&words(&[53, 5353, 0, 3]));
+    structure(&mut records, 0, 4, &ipv6);
+
+    let mut content = words(&[7, 0, 0x0100_0000, 1, 2, 0, 0, 0x0100_0000, 2, 0x3fff_ffff, 3]);
+    content.extend_from_slice(&records);
+    let mut sample = Vec::new();
+    structure(&mut sample, 0, 3, &content);
+
+    let packet = datagram(&[sample]);
+    let decoded = SflowDatagram::from_slice(&packet).unwrap();
+    let flow = match &decoded.samples[0] {
+        SflowSample::Flow(flow) => flow,
+        value => panic!("unexpected sample {:?}", value)
+    };
+    assert!(flow.expanded);
+    assert_eq!((0, 0x0100_0000), (flow.source_id_type, flow.source_id_index));
+    assert_eq!(SflowInterface{ format: 0, value: 0x0100_0000 }, flow.input);
+    assert_eq!(SflowInterface{ format: 2, value: 0x3fff_ffff }, flow.output);
+    match &flow.records[0] {
+        FlowSampleRecord::RawPacketHeader(header) => {
+            assert_eq!(&odd[..], header.header);
+            let sliced = header.sliced().unwrap().unwrap();
+            match sliced.transport {
+                Some(TransportSlice::Tcp(tcp)) => assert_eq!(80, tcp.destination_port()),
+                value => panic!("unexpected transport {:?}", value)
+            }
+        },
+        value => panic!("unexpected record {:?}", value)
+    }
+    assert_eq!(
+        FlowSampleRecord::Ipv4 {
+            length: 40,
+            protocol: 6,
+            source: [192,168,1,1],
+            destination: [192,168,1,2],
+            source_port: 1234,
+            destination_port: 80,
+            tcp_flags: 0x12,
+            tos: 0,
+        },
+        flow.records[1]
+    );
+    assert_eq!(
+        FlowSampleRecord::Ipv6 {
+            length: 60,
+            protocol: 17,
+            source: [1;16],
+            destination: [2;16],
+            source_port: 53,
+            destination_port: 5353,
+            tcp_flags: 0,
+            priority: 3,
+        },
+        flow.records[2]
+    );
+}
+
+#[test]
+fn raw_packet_header_sliced() {
+    let frame = udp_frame();
+
+    //unsupported protocol
+    let header = RawPacketHeader{ protocol: 2, frame_length: 0, stripped: 0, header: &frame };
+    assert!(header.sliced().is_none());
+
+    //ipv4 without the ethernet header
+    let header = RawPacketHeader{ protocol: 11, frame_length: 0, stripped: 0, header: &frame[14..] };
+    assert!(header.sliced().unwrap().unwrap().link.is_none());
+
+    //sampled header ends within the udp header
+    let header = RawPacketHeader{ protocol: 1, frame_length: 0, stripped: 0, header: &frame[..14 + 20 + 4] };
+    assert!(header.sliced().unwrap().is_err());
+}
+
+#[test]
+fn counter_samples() {
+    let mut generic = words(&[3, 6]);
+    generic.extend_from_slice(&10_000_000_000u64.to_be_bytes());
+    generic.extend_from_slice(&words(&[1, 3]));
+    generic.extend_from_slice(&5_000_000_000u64.to_be_bytes());
+    generic.extend_from_slice(&words(&[1, 2, 3, 4, 5, 6]));
+    generic.extend_from_slice(&7u64.to_be_bytes());
+    generic.extend_from_slice(&words(&[8, 9, 10, 11, 12, 0]));
+    let ethernet = words(&(1..=13).collect::<Vec<u32>>());
+    let mut processor = words(&[100, 200, 300]);
+    processor.extend_from_slice(&1024u64.to_be_bytes());
+    processor.extend_from_slice(&512u64.to_be_bytes());
+
+    let mut records = Vec::new();
+    structure(&mut records, 0, 1, &generic);
+    structure(&mut records, 0, 2, &ethernet);
+    structure(&mut records, 0, 1001, &processor);
+    structure(&mut records, 0, 2000, &[]);
+
+    //compact
+    let mut content = words(&[1, 3, 4]);
+    content.extend_from_slice(&records);
+    let mut compact = Vec::new();
+    structure(&mut compact, 0, 2, &content);
+
+    //expanded
+    let mut content = words(&[2, 0, 3, 4]);
+    content.extend_from_slice(&records);
+    let mut expanded = Vec::new();
+    structure(&mut expanded, 0, 4, &content);
+
+    //unknown sample
+    let mut unknown = Vec::new();
+    structure(&mut unknown, 1, 1, &[0;8]);
+
+    let packet = datagram(&[compact, expanded, unknown]);
+    let decoded = SflowDatagram::from_slice(&packet).unwrap();
+    assert_eq!(3, decoded.samples.len());
+    for (index, sample) in decoded.samples[..2].iter().enumerate() {
+        let counter = match sample {
+            SflowSample::Counter(counter) => counter,
+            value => panic!("unexpected sample {:?}", value)
+        };
+        assert_eq!(1 == index, counter.expanded);
+        assert_eq!(index as u32 + 1, counter.sequence_number);
+        assert_eq!((0, 3), (counter.source_id_type, counter.source_id_index));
+        assert_eq!(
+            CounterRecord::GenericInterface(GenericInterfaceCounters {
+                index: 3,
+                if_type: 6,
+                speed: 10_000_000_000,
+                direction: 1,
+                status: 3,
+                in_octets: 5_000_000_000,
+                in_unicast_packets: 1,
+                in_multicast_packets: 2,
+                in_broadcast_packets: 3,
+                in_discards: 4,
+                in_errors: 5,
+                in_unknown_protocols: 6,
+                out_octets: 7,
+                out_unicast_packets: 8,
+                out_multicast_packets: 9,
+                out_broadcast_packets: 10,
+                out_discards: 11,
+                out_errors: 12,
+                promiscuous_mode: 0,
+            }),
+            counter.records[0]
+        );
+        match &counter.records[1] {
+            CounterRecord::EthernetInterface(value) => {
+                assert_eq!(1, value.alignment_errors);
+                assert_eq!(2, value.fcs_errors);
+                assert_eq!(13, value.symbol_errors);
+            },
+            value => panic!("unexpected record {:?}", value)
+        }
+        assert_eq!(
+            CounterRecord::Processor(ProcessorCounters {
+                cpu_5s: 100,
+                cpu_1m: 200,
+                cpu_5m: 300,
+                total_memory: 1024,
+                free_memory: 512,
+            }),
+            counter.records[2]
+        );
+        assert_eq!(CounterRecord::Unknown{ enterprise: 0, format: 2000, data: &[] }, counter.records[3]);
+    }
+    assert_eq!(SflowSample::Unknown{ enterprise: 1, format: 1, data: &[0;8] }, decoded.samples[2]);
+}
+
+#[test]
+fn errors() {
+    use etherparse::sflow::SflowError::*;
+
+    //ipv6 agent address
+    let mut packet = words(&[5, 2]);
+    packet.extend_from_slice(&[1;16]);
+    packet.extend_from_slice(&words(&[0, 1, 2, 0]));
+    let decoded = SflowDatagram::from_slice(&packet).unwrap();
+    assert_eq!(IpAddr::from([1;16]), decoded.agent_address);
+    assert!(decoded.samples.is_empty());
+
+    //truncated datagram header
+    for len in 0..packet.len() {
+        assert_matches!(SflowDatagram::from_slice(&packet[..len]), Err(UnexpectedEndOfSlice(_)));
+    }
+
+    assert_eq!(Err(UnsupportedVersion(4)), SflowDatagram::from_slice(&words(&[4, 1, 0, 0, 0, 0, 0])));
+    assert_eq!(Err(UnsupportedAddressType(3)), SflowDatagram::from_slice(&words(&[5, 3, 0, 0, 0, 0, 0])));
+
+    //sample length exceeding the datagram
+    let mut packet = datagram(&[words(&[1, 100])]);
+    packet.extend_from_slice(&[0;8]);
+    assert_eq!(Err(UnexpectedEndOfSlice(28 + 8 + 100)), SflowDatagram::from_slice(&packet));
+
+    //known record shorter then its format
+    let mut records = Vec::new();
+    structure(&mut records, 0, 1001, &words(&[1, 2]));
+    let mut content = words(&[1, 0, 1, 1, 0, 0, 0, 1]);
+    content.extend_from_slice(&records);
+    let mut sample = Vec::new();
+    structure(&mut sample, 0, 1, &content);
+    let packet = datagram(&[sample]);
+    assert_eq!(Err(UnexpectedEndOfSlice(packet.len() + 4)), SflowDatagram::from_slice(&packet));
+}
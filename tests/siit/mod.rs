@@ -0,0 +1,328 @@
+use super::*;
+
+use etherparse::checksum::*;
+use crate::test_packets::*;
+
+const PREFIX: Nat64Prefix = Nat64Prefix::WELL_KNOWN;
+
+#[test]
+fn prefix() {
+    let prefix = [0x20,0x01,0x0d,0xb8, 0x01,0x22,0x03,0x44, 0,0,0,0, 0,0,0,0];
+    let address = [192,0,2,33];
+    //examples of rfc6052 section 2.4
+    for (prefix_len, expected) in &[
+        (32, [0x20,0x01,0x0d,0xb8, 192,0,2,33, 0,0,0,0, 0,0,0,0]),
+        (40, [0x20,0x01,0x0d,0xb8, 0x01,192,0,2, 0,33,0,0, 0,0,0,0]),
+        (48, [0x20,0x01,0x0d,0xb8, 0x01,0x22,192,0, 0,2,33,0, 0,0,0,0]),
+        (56, [0x20,0x01,0x0d,0xb8, 0x01,0x22,0x03,192, 0,0,2,33, 0,0,0,0]),
+        (64, [0x20,0x01,0x0d,0xb8, 0x01,0x22,0x03,0x44, 0,192,0,2, 33,0,0,0]),
+        (96, [0x20,0x01,0x0d,0xb8, 0x01,0x22,0x03,0x44, 0,0,0,0, 192,0,2,33]),
+    ] {
+        let value = Nat64Prefix::new(prefix, *prefix_len).unwrap();
+        assert_eq!(*prefix_len, value.prefix_len());
+        assert_eq!(*expected, value.embed(address));
+        assert_eq!(Some(address), value.extract(expected));
+    }
+
+    //bits past the prefix are ignored
+    let value = Nat64Prefix::new([0xff;16], 32).unwrap();
+    assert_eq!([0xff,0xff,0xff,0xff, 0,0,0,0, 0,0,0,0, 0,0,0,0], value.prefix());
+
+    //other prefix or non zero "u" octet
+    let value = Nat64Prefix::new(prefix, 64).unwrap();
+    assert_eq!(None, value.extract(&[0x20,0x01,0x0d,0xb8, 0x01,0x22,0x03,0x45, 0,192,0,2, 33,0,0,0]));
+    assert_eq!(None, value.extract(&[0x20,0x01,0x0d,0xb8, 0x01,0x22,0x03,0x44, 1,192,0,2, 33,0,0,0]));
+
+    //invalid prefixes
+    assert_matches!(Nat64Prefix::new(prefix, 80), Err(SiitError::InvalidPrefix(_, 80)));
+    assert_matches!(Nat64Prefix::new([0xff;16], 96), Err(SiitError::InvalidPrefix(_, 96)));
+}
+
+#[test]
+fn ipv4_to_ipv6_and_back() {
+    let translator = SiitTranslator::new(PREFIX);
+    let payloads = vec![
+        {
+            let mut result = Vec::new();
+            UdpHeader::with_ipv4_checksum(1234, 53, &Ipv4Header::new(12, 20, IpTrafficClass::Udp, [192,0,2,1], [198,51,100,2]), &[1,2,3,4])
+                .unwrap()
+                .write(&mut result)
+                .unwrap();
+            result.extend_from_slice(&[1,2,3,4]);
+            (IpTrafficClass::Udp, result)
+        },
+        {
+            let mut tcp = TcpHeader::new(1234, 80, 1, 1024);
+            tcp.syn = true;
+            tcp.checksum = tcp.calc_checksum_ipv4_raw([192,0,2,1], [198,51,100,2], &[1,2,3]).unwrap();
+            let mut result = Vec::new();
+            tcp.write(&mut result).unwrap();
+            result.extend_from_slice(&[1,2,3]);
+            (IpTrafficClass::Tcp, result)
+        },
+    ];
+    for (protocol, payload) in payloads {
+        let mut packet = ipv4(protocol, [192,0,2,1], [198,51,100,2], &payload);
+        let next_header = packet[9];
+        //set the tos & ttl
+        {
+            let mut ip = Ipv4HeaderSliceMut::from_slice(&mut packet).unwrap();
+            ip.set_ttl(33);
+            ip.set_dcp(0x2e).unwrap();
+            ip.set_ecn(1).unwrap();
+        }
+
+        let translated = translator.ipv4_to_ipv6(&packet).unwrap();
+        assert_valid_checksums(&translated);
+        let (ip, rest) = Ipv6Header::read_from_slice(&translated).unwrap();
+        assert_eq!(
+            Ipv6Header {
+                traffic_class: 0xb9,
+                flow_label: 0,
+                payload_length: payload.len() as u16,
+                next_header,
+                hop_limit: 33,
+                source: PREFIX.embed([192,0,2,1]),
+                destination: PREFIX.embed([198,51,100,2])
+            },
+            ip
+        );
+        assert_eq!(payload.len(), rest.len());
+
+        assert_eq!(packet, translator.ipv6_to_ipv4(&translated).unwrap());
+        assert_eq!(packet, translator.translate(&translated).unwrap());
+        assert_eq!(translated, translator.translate(&packet).unwrap());
+    }
+}
+
+#[test]
+fn udp_without_checksum() {
+    let translator = SiitTranslator::new(PREFIX);
+    let packet = ipv4(IpTrafficClass::Udp, [192,0,2,1], [198,51,100,2], &[0,1,0,2,0,10,0,0,1,2]);
+    let translated = translator.ipv4_to_ipv6(&packet).unwrap();
+    assert_valid_checksums(&translated);
+}
+
+#[test]
+fn fragments() {
+    let translator = SiitTranslator::new(PREFIX);
+    let udp = {
+        let mut result = Vec::new();
+        UdpHeader::with_ipv4_checksum(1234, 53, &Ipv4Header::new(16, 20, IpTrafficClass::Udp, [192,0,2,1], [198,51,100,2]), &[0;8])
+            .unwrap()
+            .write(&mut result)
+            .unwrap();
+        result
+    };
+
+    //first & non first fragment
+    for (offset, payload) in &[(0, udp.clone()), (1, vec![1,2,3,4,5,6,7,8])] {
+        let mut header = Ipv4Header::new(8, 20, IpTrafficClass::Udp, [192,0,2,1], [198,51,100,2]);
+        header.dont_fragment = false;
+        header.more_fragments = 0 == *offset;
+        header.fragments_offset = *offset;
+        header.identification = 0x1234;
+        let mut packet = Vec::new();
+        header.write(&mut packet).unwrap();
+        packet.extend_from_slice(payload);
+
+        let translated = translator.ipv4_to_ipv6(&packet).unwrap();
+        let (ip, rest) = Ipv6Header::read_from_slice(&translated).unwrap();
+        assert_eq!(44, ip.next_header);
+        assert_eq!(16, ip.payload_length);
+        assert_eq!(17, rest[0]);
+        assert_eq!((*offset << 3) | if 0 == *offset { 1 } else { 0 }, u16::from_be_bytes([rest[2], rest[3]]));
+        assert_eq!([0,0,0x12,0x34], rest[4..8]);
+        if 0 == *offset {
+            //the udp checksum covers the data of all fragments
+            let checksum = ipv6_pseudo_header_sum(&ip.source, &ip.destination, 17, 16)
+                .add_slice(&rest[8..])
+                .add_slice(&[0;8])
+                .ones_complement();
+            assert_eq!(0, checksum);
+        } else {
+            assert_eq!(&payload[..], &rest[8..]);
+        }
+
+        assert_eq!(packet, translator.ipv6_to_ipv4(&translated).unwrap());
+    }
+
+    //don't fragment flag
+    for (dont_fragment, payload_len, fragment_header) in &[
+        (false, 1000, false),
+        (false, 1300, true),
+        (true, 1300, false),
+    ] {
+        let mut header = Ipv4Header::new(*payload_len, 20, IpTrafficClass::Gre, [192,0,2,1], [198,51,100,2]);
+        header.dont_fragment = *dont_fragment;
+        let mut packet = Vec::new();
+        header.write(&mut packet).unwrap();
+        packet.resize(packet.len() + usize::from(*payload_len), 0);
+
+        let translated = translator.ipv4_to_ipv6(&packet).unwrap();
+        let ip = Ipv6Header::read_from_slice(&translated).unwrap().0;
+        assert_eq!(*fragment_header, 44 == ip.next_header);
+
+        //the don't fragment flag is set for large ipv4 packets translated without a fragment header
+        let back = translator.ipv6_to_ipv4(&translated).unwrap();
+        let back_ip = Ipv4Header::read_from_slice(&back).unwrap().0;
+        assert_eq!(!*fragment_header && *payload_len > 1240, back_ip.dont_fragment);
+    }
+}
+
+#[test]
+fn ipv6_extension_headers() {
+    let translator = SiitTranslator::new(PREFIX);
+    let source = PREFIX.embed([192,0,2,1]);
+    let destination = PREFIX.embed([198,51,100,2]);
+    let mut udp = Vec::new();
+    UdpHeader::with_ipv6_checksum(1234, 53, &Ipv6Header {
+        traffic_class: 0,
+        flow_label: 0,
+        payload_length: 0,
+        next_header: 17,
+        hop_limit: 20,
+        source,
+        destination
+    }, &[1,2,3,4]).unwrap().write(&mut udp).unwrap();
+    udp.extend_from_slice(&[1,2,3,4]);
+
+    //hop by hop & destination options header
+    let mut payload = vec![60, 0, 1, 4, 0, 0, 0, 0, 17, 0, 1, 4, 0, 0, 0, 0];
+    payload.extend_from_slice(&udp);
+    let mut packet = ipv6(0, source, destination, &payload);
+
+    let translated = translator.ipv6_to_ipv4(&packet).unwrap();
+    assert_valid_checksums(&translated);
+    let headers = PacketHeaders::from_ip_slice(&translated).unwrap();
+    assert_matches!(&headers.ip, Some(IpHeader::Version4(ip)) => {
+        assert_eq!([192,0,2,1], ip.source);
+        assert_eq!([198,51,100,2], ip.destination);
+        assert_eq!(17, ip.protocol);
+        assert_eq!(12, ip.payload_len);
+    });
+    assert_eq!(&[1,2,3,4], headers.payload);
+
+    //address without the prefix
+    packet[8] = 0x20;
+    assert_matches!(translator.ipv6_to_ipv4(&packet), Err(SiitError::AddressNotTranslatable(_)));
+}
+
+#[test]
+fn icmp_echo() {
+    let translator = SiitTranslator::new(PREFIX);
+    for (icmp_type, icmpv6_type) in &[(8, 128), (0, 129)] {
+        let packet = ipv4(
+            IpTrafficClass::Icmp,
+            [192,0,2,1],
+            [198,51,100,2],
+            &icmpv4(*icmp_type, 0, [0x12,0x34,0,1], &[1,2,3,4,5])
+        );
+        let translated = translator.ipv4_to_ipv6(&packet).unwrap();
+        let (ip, icmp) = Ipv6Header::read_from_slice(&translated).unwrap();
+        assert_eq!(58, ip.next_header);
+        assert_eq!(*icmpv6_type, icmp[0]);
+        assert_eq!(&packet[24..], &icmp[4..]);
+        assert_icmpv6_checksum(&translated);
+
+        assert_eq!(packet, translator.ipv6_to_ipv4(&translated).unwrap());
+    }
+}
+
+#[test]
+fn icmp_errors() {
+    let translator = SiitTranslator::new(PREFIX);
+
+    //quoted udp packet (sent from the destination of the error)
+    let quoted = {
+        let mut udp = Vec::new();
+        UdpHeader::with_ipv4_checksum(53, 1234, &Ipv4Header::new(12, 20, IpTrafficClass::Udp, [198,51,100,2], [192,0,2,1]), &[1,2,3,4])
+            .unwrap()
+            .write(&mut udp)
+            .unwrap();
+        udp.extend_from_slice(&[1,2,3,4]);
+        let mut result = Vec::new();
+        Ipv4Header::new(12, 20, IpTrafficClass::Udp, [198,51,100,2], [192,0,2,1])
+            .write(&mut result)
+            .unwrap();
+        result.extend_from_slice(&udp);
+        result
+    };
+
+    for (icmp_type, code, rest_of_header, icmpv6_type, icmpv6_code, icmpv6_rest_of_header) in &[
+        //port unreachable
+        (3, 3, [0;4], 1, 4, [0;4]),
+        //fragmentation needed
+        (3, 4, [0,0,0x05,0xc8], 2, 0, [0,0,0x05,0xdc]),
+        //protocol unreachable
+        (3, 2, [0;4], 4, 1, [0,0,0,6]),
+        //time exceeded
+        (11, 0, [0;4], 3, 0, [0;4]),
+        //parameter problem pointing to the protocol
+        (12, 0, [9,0,0,0], 4, 0, [0,0,0,6]),
+    ] {
+        let packet = ipv4(
+            IpTrafficClass::Icmp,
+            [192,0,2,254],
+            [198,51,100,2],
+            &icmpv4(*icmp_type, *code, *rest_of_header, &quoted)
+        );
+        let translated = translator.ipv4_to_ipv6(&packet).unwrap();
+        assert_icmpv6_checksum(&translated);
+        let (_, icmp) = Ipv6Header::read_from_slice(&translated).unwrap();
+        assert_eq!([*icmpv6_type, *icmpv6_code], icmp[..2]);
+        assert_eq!(*icmpv6_rest_of_header, icmp[4..8]);
+
+        //quoted packet
+        let quoted_ip = Ipv6Header::read_from_slice(&icmp[8..]).unwrap().0;
+        assert_eq!(PREFIX.embed([198,51,100,2]), quoted_ip.source);
+        assert_eq!(PREFIX.embed([192,0,2,1]), quoted_ip.destination);
+        assert_eq!(12, quoted_ip.payload_length);
+        assert_valid_checksums(&icmp[8..]);
+
+        //protocol unreachable & parameter problem have no exact reverse translation
+        if 2 != *code && 12 != *icmp_type {
+            assert_eq!(packet, translator.ipv6_to_ipv4(&translated).unwrap());
+        }
+    }
+
+    //truncated quoted packet
+    let packet = ipv4(
+        IpTrafficClass::Icmp,
+        [192,0,2,254],
+        [198,51,100,2],
+        &icmpv4(3, 3, [0;4], &quoted[..24])
+    );
+    let translated = translator.ipv4_to_ipv6(&packet).unwrap();
+    assert_icmpv6_checksum(&translated);
+    assert_eq!(packet, translator.ipv6_to_ipv4(&translated).unwrap());
+}
+
+#[test]
+fn errors() {
+    use crate::SiitError::*;
+    let translator = SiitTranslator::new(PREFIX);
+
+    //icmp without translation (timestamp)
+    let packet = ipv4(IpTrafficClass::Icmp, [192,0,2,1], [198,51,100,2], &icmpv4(13, 0, [0;4], &[0;12]));
+    assert_matches!(translator.ipv4_to_ipv6(&packet), Err(IcmpNotTranslatable{ icmp_type: 13, code: 0 }));
+
+    //fragmented icmp & udp without checksum
+    for (protocol, payload, expected) in &[
+        (1, icmpv4(8, 0, [0;4], &[]), "FragmentedIcmp"),
+        (17, vec![0,1,0,2,0,16,0,0], "FragmentedUdpWithoutChecksum"),
+    ] {
+        let mut header = Ipv4Header::new(payload.len() as u16, 20, IpTrafficClass::Udp, [192,0,2,1], [198,51,100,2]);
+        header.protocol = *protocol;
+        header.more_fragments = true;
+        let mut packet = Vec::new();
+        header.write(&mut packet).unwrap();
+        packet.extend_from_slice(payload);
+        assert_eq!(*expected, format!("{:?}", translator.ipv4_to_ipv6(&packet).unwrap_err()));
+    }
+
+    //truncated packets
+    let packet = ipv4(IpTrafficClass::Udp, [192,0,2,1], [198,51,100,2], &[0;8]);
+    assert_matches!(translator.ipv4_to_ipv6(&packet[..27]), Err(Read(ReadError::UnexpectedEndOfSlice(28))));
+    assert_matches!(translator.ipv6_to_ipv4(&[0x60]), Err(Read(ReadError::UnexpectedEndOfSlice(40))));
+}
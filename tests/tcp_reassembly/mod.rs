@@ -0,0 +1,399 @@
+use super::*;
+use etherparse::tcp_reassembly::*;
+use std::net::IpAddr;
+
+const CLIENT: ([u8;4], u16) = ([192,168,1,1], 1234);
+const SERVER: ([u8;4], u16) = ([192,168,1,2], 80);
+
+///Owned version of the stream events (without the connection key).
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Event {
+    Opened(ConnectionKey),
+    Data(Direction, u64, Vec<u8>),
+    Gap(Direction, u64, u64),
+    Fin(Direction),
+    Closed(CloseReason),
+}
+
+impl<'a> From<StreamEvent<'a>> for Event {
+    fn from(event: StreamEvent<'a>) -> Event {
+        match event {
+            StreamEvent::Opened(key) => Event::Opened(key),
+            StreamEvent::Data{ direction, offset, data, .. } => Event::Data(direction, offset, data.to_vec()),
+            StreamEvent::Gap{ direction, offset, len, .. } => Event::Gap(direction, offset, len),
+            StreamEvent::Fin{ direction, .. } => Event::Fin(direction),
+            StreamEvent::Closed{ reason, .. } => Event::Closed(reason),
+        }
+    }
+}
+
+///Segment description used to generate the packets.
+struct Segment {
+    from_client: bool,
+    sequence_number: u32,
+    syn: bool,
+    ack: bool,
+    fin: bool,
+    rst: bool,
+    payload: Vec<u8>,
+}
+
+fn data(from_client: bool, sequence_number: u32, payload: &[u8]) -> Segment {
+    Segment {
+        from_client,
+        sequence_number,
+        syn: false,
+        ack: true,
+        fin: false,
+        rst: false,
+        payload: payload.to_vec(),
+    }
+}
+
+impl Segment {
+    fn serialize(&self) -> Vec<u8> {
+        let (source, destination) = if self.from_client {
+            (CLIENT, SERVER)
+        } else {
+            (SERVER, CLIENT)
+        };
+        let mut tcp = TcpHeader::new(source.1, destination.1, self.sequence_number, 1024);
+        tcp.syn = self.syn;
+        tcp.ack = self.ack;
+        tcp.fin = self.fin;
+        tcp.rst = self.rst;
+        let ip = Ipv4Header::new(
+            (usize::from(tcp.header_len()) + self.payload.len()) as u16,
+            20,
+            IpTrafficClass::Tcp,
+            source.0,
+            destination.0
+        );
+        let mut result = Vec::new();
+        ip.write(&mut result).unwrap();
+        tcp.write(&mut result).unwrap();
+        result.extend_from_slice(&self.payload);
+        result
+    }
+}
+
+fn push(reassembler: &mut TcpReassembler, segments: &[Segment]) -> Vec<Event> {
+    let mut result = Vec::new();
+    for segment in segments {
+        let packet = segment.serialize();
+        reassembler.push(&SlicedPacket::from_ip(&packet).unwrap(), |event| result.push(event.into())).unwrap();
+    }
+    result
+}
+
+fn client_key() -> ConnectionKey {
+    ConnectionKey {
+        source: IpAddr::from(CLIENT.0),
+        source_port: CLIENT.1,
+        destination: IpAddr::from(SERVER.0),
+        destination_port: SERVER.1,
+    }
+}
+
+#[test]
+fn connection_key() {
+    let key = client_key();
+    assert_eq!(key, key.reversed().reversed());
+    assert_eq!(key.canonical(), key.reversed().canonical());
+
+    let packet = data(true, 0, &[]).serialize();
+    assert_eq!(Some(key), ConnectionKey::from_sliced(&SlicedPacket::from_ip(&packet).unwrap()));
+}
+
+#[test]
+fn lifecycle() {
+    use self::Direction::*;
+
+    let mut reassembler = TcpReassembler::new(ReassemblyConfig::default());
+    let syn = Segment{ syn: true, ack: false, ..data(true, 99, &[]) };
+    let syn_ack = Segment{ syn: true, ..data(false, 499, &[]) };
+    let fin_client = Segment{ fin: true, ..data(true, 103, &[]) };
+    let fin_server = Segment{ fin: true, ..data(false, 500, &[5,6]) };
+    let events = push(&mut reassembler, &[
+        syn,
+        syn_ack,
+        data(true, 100, &[1,2,3]),
+        fin_client,
+        //pure ack
+        data(false, 500, &[]),
+    ]);
+    assert_eq!(
+        vec![
+            Event::Opened(client_key()),
+            Event::Data(ClientToServer, 0, vec![1,2,3]),
+            Event::Fin(ClientToServer),
+        ],
+        events
+    );
+    assert_eq!(1, reassembler.connection_count());
+
+    let events = push(&mut reassembler, &[fin_server]);
+    assert_eq!(
+        vec![
+            Event::Data(ServerToClient, 0, vec![5,6]),
+            Event::Fin(ServerToClient),
+            Event::Closed(CloseReason::Fin),
+        ],
+        events
+    );
+    assert_eq!(0, reassembler.connection_count());
+
+    //packets without data do not create new connections
+    assert!(push(&mut reassembler, &[data(true, 104, &[])]).is_empty());
+    assert_eq!(0, reassembler.connection_count());
+}
+
+#[test]
+fn syn_ack_orientation() {
+    //the receiver of a syn ack is the client (even if the syn was not captured)
+    let mut reassembler = TcpReassembler::new(ReassemblyConfig::default());
+    let events = push(&mut reassembler, &[
+        Segment{ syn: true, ..data(false, 0, &[]) },
+        data(false, 1, &[1]),
+    ]);
+    assert_eq!(
+        vec![
+            Event::Opened(client_key()),
+            Event::Data(Direction::ServerToClient, 0, vec![1]),
+        ],
+        events
+    );
+}
+
+#[test]
+fn out_of_order() {
+    use self::Direction::*;
+
+    let mut reassembler = TcpReassembler::new(ReassemblyConfig::default());
+    let events = push(&mut reassembler, &[
+        //picked up mid stream
+        data(true, 1000, &[1,2]),
+        data(true, 1006, &[7,8]),
+        data(true, 1004, &[5,6]),
+        //retransmission of delivered data
+        data(true, 1000, &[1,2]),
+    ]);
+    assert_eq!(
+        vec![
+            Event::Opened(client_key()),
+            Event::Data(ClientToServer, 0, vec![1,2]),
+        ],
+        events
+    );
+    //partially delivered data
+    let events = push(&mut reassembler, &[data(true, 1001, &[2,3,4])]);
+    assert_eq!(
+        vec![
+            Event::Data(ClientToServer, 2, vec![3,4]),
+            Event::Data(ClientToServer, 4, vec![5,6]),
+            Event::Data(ClientToServer, 6, vec![7,8]),
+        ],
+        events
+    );
+}
+
+#[test]
+fn overlap_before_first_segment() {
+    use self::Direction::*;
+
+    //segment starting before the first seen sequence number & overlapping the delivered data
+    let mut reassembler = TcpReassembler::new(ReassemblyConfig::default());
+    let events = push(&mut reassembler, &[
+        data(true, 1100, &[1;100]),
+        data(true, 1000, &[2;300]),
+    ]);
+    assert_eq!(
+        vec![
+            Event::Opened(client_key()),
+            Event::Data(ClientToServer, 0, vec![1;100]),
+            Event::Data(ClientToServer, 100, vec![2;100]),
+        ],
+        events
+    );
+}
+
+#[test]
+fn overlap_policy() {
+    for (policy, expected) in &[
+        (OverlapPolicy::First, vec![0, 1, 1, 1, 1, 2, 2, 2]),
+        (OverlapPolicy::Last, vec![0, 1, 4, 4, 4, 4, 2, 3])
+    ] {
+        let mut reassembler = TcpReassembler::new(ReassemblyConfig{
+            overlap_policy: *policy,
+            ..ReassemblyConfig::default()
+        });
+        let mut events = push(&mut reassembler, &[
+            data(true, 0, &[0]),
+            data(true, 2, &[1,1,1]),
+            data(true, 5, &[2,2,2]),
+            data(true, 2, &[4,4,4,4]),
+            data(true, 7, &[3]),
+            data(true, 1, &[1]),
+        ]);
+        events.remove(0);
+        let mut stream = Vec::new();
+        for event in events {
+            match event {
+                Event::Data(_, offset, data) => {
+                    assert_eq!(stream.len() as u64, offset);
+                    stream.extend_from_slice(&data);
+                },
+                _ => panic!("unexpected event {:?}", event)
+            }
+        }
+        assert_eq!(*expected, stream);
+    }
+}
+
+#[test]
+fn sequence_wraparound() {
+    use self::Direction::*;
+
+    let mut reassembler = TcpReassembler::new(ReassemblyConfig::default());
+    let events = push(&mut reassembler, &[
+        Segment{ syn: true, ack: false, ..data(true, 0xffff_fffd, &[]) },
+        data(true, 0, &[3,4]),
+        data(true, 0xffff_fffe, &[1,2]),
+        Segment{ fin: true, ..data(true, 2, &[5]) },
+    ]);
+    assert_eq!(
+        vec![
+            Event::Opened(client_key()),
+            Event::Data(ClientToServer, 0, vec![1,2]),
+            Event::Data(ClientToServer, 2, vec![3,4]),
+            Event::Data(ClientToServer, 4, vec![5]),
+            Event::Fin(ClientToServer),
+        ],
+        events
+    );
+}
+
+#[test]
+fn reset() {
+    use self::Direction::*;
+
+    let mut reassembler = TcpReassembler::new(ReassemblyConfig::default());
+    let events = push(&mut reassembler, &[
+        data(true, 0, &[1]),
+        data(true, 3, &[4]),
+        Segment{ rst: true, ..data(false, 0, &[]) },
+    ]);
+    //buffered data is delivered before the connection is closed
+    assert_eq!(
+        vec![
+            Event::Opened(client_key()),
+            Event::Data(ClientToServer, 0, vec![1]),
+            Event::Gap(ClientToServer, 1, 2),
+            Event::Data(ClientToServer, 3, vec![4]),
+            Event::Closed(CloseReason::Reset),
+        ],
+        events
+    );
+    assert_eq!(0, reassembler.connection_count());
+
+    //rst for an unknown connection
+    assert!(push(&mut reassembler, &[Segment{ rst: true, ..data(false, 0, &[1]) }]).is_empty());
+}
+
+#[test]
+fn memory_limits() {
+    use self::Direction::*;
+
+    //buffer limit
+    {
+        let mut reassembler = TcpReassembler::new(ReassemblyConfig{
+            max_buffered_len: 4,
+            ..ReassemblyConfig::default()
+        });
+        let events = push(&mut reassembler, &[
+            data(true, 0, &[0]),
+            data(true, 2, &[2,2]),
+            data(true, 5, &[5,5]),
+            data(true, 8, &[8]),
+        ]);
+        assert_eq!(
+            vec![
+                Event::Opened(client_key()),
+                Event::Data(ClientToServer, 0, vec![0]),
+                Event::Gap(ClientToServer, 1, 1),
+                Event::Data(ClientToServer, 2, vec![2,2]),
+            ],
+            events
+        );
+    }
+    //connection limit
+    {
+        let mut reassembler = TcpReassembler::new(ReassemblyConfig{
+            max_connections: 1,
+            ..ReassemblyConfig::default()
+        });
+        let other = {
+            let mut packet = data(true, 0, &[1]).serialize();
+            //change the source port
+            packet[20..22].copy_from_slice(&[0, 1]);
+            packet
+        };
+        let mut events = push(&mut reassembler, &[data(true, 0, &[1]), data(true, 2, &[3])]);
+        reassembler.push(&SlicedPacket::from_ip(&other).unwrap(), |event| events.push(event.into())).unwrap();
+        assert_eq!(
+            vec![
+                Event::Opened(client_key()),
+                Event::Data(ClientToServer, 0, vec![1]),
+                Event::Gap(ClientToServer, 1, 1),
+                Event::Data(ClientToServer, 2, vec![3]),
+                Event::Closed(CloseReason::Evicted),
+                Event::Opened(ConnectionKey{ source_port: 1, ..client_key() }),
+                Event::Data(ClientToServer, 0, vec![1]),
+            ],
+            events
+        );
+        assert_eq!(1, reassembler.connection_count());
+    }
+}
+
+#[test]
+fn flush() {
+    let mut reassembler = TcpReassembler::new(ReassemblyConfig::default());
+    push(&mut reassembler, &[data(true, 0, &[1]), data(false, 0, &[1]), data(true, 2, &[3])]);
+
+    let mut events = Vec::new();
+    reassembler.flush(|event| events.push(Event::from(event)));
+    assert_eq!(
+        vec![
+            Event::Gap(Direction::ClientToServer, 1, 1),
+            Event::Data(Direction::ClientToServer, 2, vec![3]),
+            Event::Closed(CloseReason::Flushed),
+        ],
+        events
+    );
+    assert_eq!(0, reassembler.connection_count());
+}
+
+#[test]
+fn errors() {
+    let mut reassembler = TcpReassembler::new(ReassemblyConfig::default());
+
+    //udp
+    let mut packet = Vec::new();
+    PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+        .udp(1234, 5678)
+        .write(&mut packet, &[0;10])
+        .unwrap();
+    assert_eq!(
+        Err(ReassemblyError::MissingTcpHeader),
+        reassembler.push(&SlicedPacket::from_ip(&packet).unwrap(), |_| {})
+    );
+
+    //fragment
+    let mut packet = data(true, 0, &[1]).serialize();
+    Ipv4HeaderSliceMut::from_slice(&mut packet).unwrap().set_more_fragments(true);
+    assert_eq!(
+        Err(ReassemblyError::IpFragment),
+        reassembler.push(&SlicedPacket::from_ip(&packet).unwrap(), |_| {})
+    );
+}
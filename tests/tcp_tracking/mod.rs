@@ -0,0 +1,302 @@
+use super::*;
+use etherparse::tcp_tracking::*;
+use etherparse::tcp_tracking::PacketClassification::*;
+
+const CLIENT: ([u8;4], u16) = ([192,168,1,1], 1234);
+const SERVER: ([u8;4], u16) = ([192,168,1,2], 80);
+
+///Tcp header with the given sequence & acknowledgment number.
+fn tcp(from_client: bool, sequence_number: u32, acknowledgment_number: Option<u32>) -> TcpHeader {
+    let (source, destination) = if from_client {
+        (CLIENT, SERVER)
+    } else {
+        (SERVER, CLIENT)
+    };
+    let mut result = TcpHeader::new(source.1, destination.1, sequence_number, 1024);
+    if let Some(value) = acknowledgment_number {
+        result.ack = true;
+        result.acknowledgment_number = value;
+    }
+    result
+}
+
+fn syn(from_client: bool, sequence_number: u32, acknowledgment_number: Option<u32>) -> TcpHeader {
+    let mut result = tcp(from_client, sequence_number, acknowledgment_number);
+    result.syn = true;
+    result
+}
+
+fn fin(from_client: bool, sequence_number: u32, acknowledgment_number: u32) -> TcpHeader {
+    let mut result = tcp(from_client, sequence_number, Some(acknowledgment_number));
+    result.fin = true;
+    result
+}
+
+fn serialize(header: &TcpHeader, payload_len: usize) -> Vec<u8> {
+    let from_client = CLIENT.1 == header.source_port;
+    let (source, destination) = if from_client {
+        (CLIENT.0, SERVER.0)
+    } else {
+        (SERVER.0, CLIENT.0)
+    };
+    let ip = Ipv4Header::new(
+        (usize::from(header.header_len()) + payload_len) as u16,
+        20,
+        IpTrafficClass::Tcp,
+        source,
+        destination
+    );
+    let mut result = Vec::new();
+    ip.write(&mut result).unwrap();
+    header.write(&mut result).unwrap();
+    result.resize(result.len() + payload_len, 0);
+    result
+}
+
+fn track(tracker: &mut TcpTracker, header: &TcpHeader, payload_len: usize) -> PacketClassification {
+    let packet = serialize(header, payload_len);
+    tracker.track(&SlicedPacket::from_ip(&packet).unwrap()).unwrap()
+}
+
+fn states(tracker: &TcpTracker) -> (TcpState, TcpState) {
+    let connection = tracker.connection(&key()).unwrap();
+    (connection.client_state(), connection.server_state())
+}
+
+fn key() -> etherparse::tcp_reassembly::ConnectionKey {
+    let packet = serialize(&tcp(true, 0, None), 0);
+    etherparse::tcp_reassembly::ConnectionKey::from_sliced(&SlicedPacket::from_ip(&packet).unwrap()).unwrap()
+}
+
+///Creates a tracker with a connection after the three way handshake (client isn 100, server isn 500).
+fn established(config: TrackerConfig) -> TcpTracker {
+    let mut tracker = TcpTracker::new(config);
+    assert_eq!(New, track(&mut tracker, &syn(true, 100, None), 0));
+    assert_eq!(Established, track(&mut tracker, &syn(false, 500, Some(101)), 0));
+    assert_eq!(Established, track(&mut tracker, &tcp(true, 101, Some(501)), 0));
+    tracker
+}
+
+#[test]
+fn lifecycle() {
+    let mut tracker = TcpTracker::new(TrackerConfig::default());
+
+    //handshake
+    assert_eq!(New, track(&mut tracker, &syn(true, 100, None), 0));
+    assert_eq!((TcpState::SynSent, TcpState::Listen), states(&tracker));
+    //retransmitted syn
+    assert_eq!(New, track(&mut tracker, &syn(true, 100, None), 0));
+    assert_eq!(Established, track(&mut tracker, &syn(false, 500, Some(101)), 0));
+    assert_eq!((TcpState::Established, TcpState::SynReceived), states(&tracker));
+    assert_eq!(Established, track(&mut tracker, &tcp(true, 101, Some(501)), 10));
+    assert_eq!((TcpState::Established, TcpState::Established), states(&tracker));
+    assert_eq!(Established, track(&mut tracker, &tcp(false, 501, Some(111)), 20));
+
+    //client closes
+    assert_eq!(Established, track(&mut tracker, &fin(true, 111, 521), 0));
+    assert_eq!((TcpState::FinWait1, TcpState::CloseWait), states(&tracker));
+    assert_eq!(Established, track(&mut tracker, &tcp(false, 521, Some(112)), 0));
+    assert_eq!((TcpState::FinWait2, TcpState::CloseWait), states(&tracker));
+
+    //server closes
+    assert_eq!(Established, track(&mut tracker, &fin(false, 521, 112), 0));
+    assert_eq!((TcpState::TimeWait, TcpState::LastAck), states(&tracker));
+    assert_eq!(Established, track(&mut tracker, &tcp(true, 112, Some(522)), 0));
+    assert_eq!((TcpState::TimeWait, TcpState::Closed), states(&tracker));
+
+    assert_eq!(1, tracker.connection_count());
+    tracker.remove_closed();
+    assert_eq!(0, tracker.connection_count());
+}
+
+#[test]
+fn simultaneous_close() {
+    let mut tracker = established(TrackerConfig::default());
+    assert_eq!(Established, track(&mut tracker, &fin(true, 101, 501), 0));
+    assert_eq!(Established, track(&mut tracker, &fin(false, 501, 101), 0));
+    assert_eq!((TcpState::Closing, TcpState::LastAck), states(&tracker));
+    assert_eq!(Established, track(&mut tracker, &tcp(true, 102, Some(502)), 0));
+    assert_eq!(Established, track(&mut tracker, &tcp(false, 502, Some(102)), 0));
+    assert_eq!((TcpState::TimeWait, TcpState::Closed), states(&tracker));
+}
+
+#[test]
+fn window_checks() {
+    let mut tracker = established(TrackerConfig::default());
+
+    //data within the window of the server
+    assert_eq!(Established, track(&mut tracker, &tcp(true, 101, Some(501)), 1024));
+    //data starting after the window
+    assert_eq!(Invalid, track(&mut tracker, &tcp(true, 101 + 1024 + 1, Some(501)), 10));
+    //acknowledgment of data that was not sent
+    assert_eq!(Invalid, track(&mut tracker, &tcp(true, 1125, Some(502)), 0));
+    //acknowledgment older then the maximum ack window
+    assert_eq!(Invalid, track(&mut tracker, &tcp(true, 1125, Some(501u32.wrapping_sub(70_000))), 0));
+    //data ending before the window of the receiver
+    assert_eq!(Invalid, track(&mut tracker, &tcp(true, 0, Some(501)), 10));
+    //retransmission of data within the window
+    assert_eq!(Established, track(&mut tracker, &tcp(true, 101, Some(501)), 10));
+
+    //syn with a different sequence number
+    assert_eq!(Invalid, track(&mut tracker, &syn(true, 200, None), 0));
+    //syn ack acknowledging a different sequence number
+    let mut tracker = TcpTracker::new(TrackerConfig::default());
+    assert_eq!(New, track(&mut tracker, &syn(true, 100, None), 0));
+    assert_eq!(Invalid, track(&mut tracker, &syn(false, 500, Some(102)), 0));
+    assert_eq!(TcpState::Listen, states(&tracker).1);
+}
+
+#[test]
+fn sequence_wraparound() {
+    let mut tracker = TcpTracker::new(TrackerConfig::default());
+    assert_eq!(New, track(&mut tracker, &syn(true, 0xffff_fff0, None), 0));
+    assert_eq!(Established, track(&mut tracker, &syn(false, 500, Some(0xffff_fff1)), 0));
+    assert_eq!(Established, track(&mut tracker, &tcp(true, 0xffff_fff1, Some(501)), 100));
+    assert_eq!(Established, track(&mut tracker, &tcp(false, 501, Some(0x55)), 0));
+    assert_eq!(Established, track(&mut tracker, &tcp(true, 0x55, Some(501)), 100));
+    assert_eq!(Invalid, track(&mut tracker, &tcp(false, 501, Some(0x55 + 101)), 0));
+}
+
+#[test]
+fn window_scaling() {
+    for (client_scale, server_scale, expected) in &[
+        (Some(4), Some(4), Established),
+        //scaling is only used if both sides send the option
+        (Some(4), None, Invalid),
+        (None, Some(4), Invalid),
+    ] {
+        let mut tracker = TcpTracker::new(TrackerConfig::default());
+        let mut client_syn = syn(true, 100, None);
+        if let Some(value) = client_scale {
+            client_syn.set_options(&[TcpOptionElement::WindowScale(*value)]).unwrap();
+        }
+        let mut server_syn = syn(false, 500, Some(101));
+        if let Some(value) = server_scale {
+            server_syn.set_options(&[TcpOptionElement::WindowScale(*value)]).unwrap();
+        }
+        assert_eq!(New, track(&mut tracker, &client_syn, 0));
+        assert_eq!(Established, track(&mut tracker, &server_syn, 0));
+        //the window of the syn is not scaled
+        assert_eq!(Invalid, track(&mut tracker, &tcp(true, 101 + 2000, Some(501)), 0));
+
+        //scaled window of the server (1024 << 4)
+        assert_eq!(Established, track(&mut tracker, &tcp(false, 501, Some(101)), 0));
+        assert_eq!(*expected, track(&mut tracker, &tcp(true, 101 + 2000, Some(501)), 1000));
+    }
+}
+
+#[test]
+fn reset() {
+    let mut tracker = established(TrackerConfig{ mid_stream_pickup: false });
+    //reset outside of the window
+    let mut rst = tcp(false, 501 + 5000, None);
+    rst.rst = true;
+    assert_eq!(Invalid, track(&mut tracker, &rst, 0));
+    assert_eq!((TcpState::Established, TcpState::Established), states(&tracker));
+
+    let mut rst = tcp(false, 501, None);
+    rst.rst = true;
+    assert_eq!(Established, track(&mut tracker, &rst, 0));
+    assert_eq!((TcpState::Closed, TcpState::Closed), states(&tracker));
+    assert_eq!(Invalid, track(&mut tracker, &tcp(true, 101, Some(501)), 0));
+
+    //new connection with the same ports
+    assert_eq!(New, track(&mut tracker, &syn(true, 1000, None), 0));
+    assert_eq!((TcpState::SynSent, TcpState::Listen), states(&tracker));
+}
+
+#[test]
+fn mid_stream_pickup() {
+    //disabled
+    {
+        let mut tracker = TcpTracker::new(TrackerConfig{ mid_stream_pickup: false });
+        assert_eq!(Invalid, track(&mut tracker, &tcp(true, 100, Some(500)), 10));
+        assert_eq!(0, tracker.connection_count());
+    }
+    //enabled
+    {
+        let mut tracker = TcpTracker::new(TrackerConfig::default());
+        assert_eq!(New, track(&mut tracker, &tcp(true, 100, Some(500)), 10));
+        assert!(tracker.connection(&key()).unwrap().is_picked_up());
+        assert_eq!(New, track(&mut tracker, &tcp(true, 110, Some(500)), 10));
+        assert_eq!(Established, track(&mut tracker, &tcp(false, 500, Some(120)), 10));
+        //no window checks
+        assert_eq!(Established, track(&mut tracker, &tcp(true, 100_000, Some(500)), 10));
+        //packets without ack do not start connections
+        let mut tracker = TcpTracker::new(TrackerConfig::default());
+        assert_eq!(Invalid, track(&mut tracker, &tcp(true, 100, None), 10));
+    }
+}
+
+#[test]
+fn related() {
+    let mut tracker = established(TrackerConfig::default());
+
+    //icmp destination unreachable quoting a packet of the server
+    let icmp = |icmp_type: u8, quoted: &[u8]| {
+        let mut payload = vec![icmp_type, 0, 0, 0, 0, 0, 0, 0];
+        payload.extend_from_slice(&quoted[..28]);
+        let mut ip = Ipv4Header::new(payload.len() as u16, 20, IpTrafficClass::Icmp, [10,0,0,1], SERVER.0);
+        ip.protocol = IpTrafficClass::Icmp as u8;
+        let mut result = Vec::new();
+        ip.write(&mut result).unwrap();
+        result.extend_from_slice(&payload);
+        result
+    };
+    let quoted = serialize(&tcp(false, 501, Some(101)), 0);
+    assert_eq!(Related, tracker.track(&SlicedPacket::from_ip(&icmp(3, &quoted)).unwrap()).unwrap());
+
+    //unknown connection
+    let mut other = quoted.clone();
+    other[20..22].copy_from_slice(&[0, 1]);
+    assert_eq!(Invalid, tracker.track(&SlicedPacket::from_ip(&icmp(3, &other)).unwrap()).unwrap());
+
+    //no error message (echo request)
+    assert_eq!(
+        Err(TrackingError::MissingTcpHeader),
+        tracker.track(&SlicedPacket::from_ip(&icmp(8, &quoted)).unwrap())
+    );
+
+    //ipv6 packet too big
+    {
+        let mut tracker = TcpTracker::new(TrackerConfig::default());
+        let mut quoted = Vec::new();
+        PacketBuilder::ipv6([1;16], [2;16], 20)
+            .tcp(1234, 80, 100, 1024)
+            .syn()
+            .write(&mut quoted, &[])
+            .unwrap();
+        assert_eq!(New, tracker.track(&SlicedPacket::from_ip(&quoted).unwrap()).unwrap());
+
+        let mut packet = Vec::new();
+        let mut payload = vec![2, 0, 0, 0, 0, 0, 5, 0];
+        payload.extend_from_slice(&quoted);
+        Ipv6Header {
+            traffic_class: 0,
+            flow_label: 0,
+            payload_length: payload.len() as u16,
+            next_header: IpTrafficClass::IPv6Icmp as u8,
+            hop_limit: 20,
+            source: [3;16],
+            destination: [1;16]
+        }.write(&mut packet).unwrap();
+        packet.extend_from_slice(&payload);
+        assert_eq!(Related, tracker.track(&SlicedPacket::from_ip(&packet).unwrap()).unwrap());
+    }
+}
+
+#[test]
+fn errors() {
+    let mut tracker = TcpTracker::new(TrackerConfig::default());
+
+    let mut packet = Vec::new();
+    PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20)
+        .udp(1234, 5678)
+        .write(&mut packet, &[0;10])
+        .unwrap();
+    assert_eq!(Err(TrackingError::MissingTcpHeader), tracker.track(&SlicedPacket::from_ip(&packet).unwrap()));
+
+    let mut packet = serialize(&syn(true, 100, None), 10);
+    Ipv4HeaderSliceMut::from_slice(&mut packet).unwrap().set_more_fragments(true);
+    assert_eq!(Err(TrackingError::IpFragment), tracker.track(&SlicedPacket::from_ip(&packet).unwrap()));
+}
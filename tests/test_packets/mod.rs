@@ -0,0 +1,75 @@
+use super::*;
+use etherparse::checksum::*;
+
+//helpers to serialize & check the packets used in the nat & siit tests
+
+///Serializes an ipv4 packet without the don't fragment flag (siit translates it back to the same packet).
+pub fn ipv4(protocol: IpTrafficClass, source: [u8;4], destination: [u8;4], payload: &[u8]) -> Vec<u8> {
+    let mut header = Ipv4Header::new(payload.len() as u16, 20, protocol, source, destination);
+    header.dont_fragment = false;
+    let mut result = Vec::new();
+    header.write(&mut result).unwrap();
+    result.extend_from_slice(payload);
+    result
+}
+
+///Serializes an ipv6 packet with the given next header & payload.
+pub fn ipv6(next_header: u8, source: [u8;16], destination: [u8;16], payload: &[u8]) -> Vec<u8> {
+    let mut result = Vec::new();
+    Ipv6Header {
+        traffic_class: 0,
+        flow_label: 0,
+        payload_length: payload.len() as u16,
+        next_header,
+        hop_limit: 20,
+        source,
+        destination
+    }.write(&mut result).unwrap();
+    result.extend_from_slice(payload);
+    result
+}
+
+///Serializes an ICMP message with a valid checksum.
+pub fn icmpv4(icmp_type: u8, code: u8, rest_of_header: [u8;4], data: &[u8]) -> Vec<u8> {
+    let mut result = vec![icmp_type, code, 0, 0];
+    result.extend_from_slice(&rest_of_header);
+    result.extend_from_slice(data);
+    let checksum = Sum16BitWords::new().add_slice(&result).ones_complement();
+    result[2..4].copy_from_slice(&checksum.to_be_bytes());
+    result
+}
+
+///Serializes an ICMPv6 message with a valid checksum.
+pub fn icmpv6(source: [u8;16], destination: [u8;16], icmp_type: u8, code: u8, data: &[u8]) -> Vec<u8> {
+    let mut result = vec![icmp_type, code, 0, 0, 0, 0, 0, 0];
+    result.extend_from_slice(data);
+    let checksum = ipv6_pseudo_header_sum(&source, &destination, 58, result.len() as u32)
+        .add_slice(&result)
+        .ones_complement();
+    result[2..4].copy_from_slice(&checksum.to_be_bytes());
+    result
+}
+
+///Asserts that the ICMP message following the ipv4 header (without options) has a valid checksum.
+pub fn assert_icmpv4_checksum(packet: &[u8]) {
+    let (_, icmp) = Ipv4Header::read_from_slice(packet).unwrap();
+    assert_eq!(0, Sum16BitWords::new().add_slice(icmp).ones_complement());
+}
+
+///Asserts that the ICMPv6 message directly following the ipv6 header has a valid checksum.
+pub fn assert_icmpv6_checksum(packet: &[u8]) {
+    let (ip, icmp) = Ipv6Header::read_from_slice(packet).unwrap();
+    assert_eq!(
+        0,
+        ipv6_pseudo_header_sum(&ip.source, &ip.destination, 58, icmp.len() as u32)
+            .add_slice(icmp)
+            .ones_complement()
+    );
+}
+
+///Asserts that the ip header checksum (if present) & the transport checksum of the packet are valid.
+pub fn assert_valid_checksums(packet: &[u8]) {
+    let checksums = SlicedPacket::from_ip(packet).unwrap().verify_checksums();
+    assert_ne!(ChecksumStatus::Invalid, checksums.ip);
+    assert_eq!(ChecksumStatus::Valid, checksums.transport);
+}
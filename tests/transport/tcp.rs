@@ -1047,4 +1047,106 @@ fn options_iterator_unexpected_id() {
     assert_eq!(0, it.rest().len());
     assert_eq!(None, it.next());
     assert_eq!(0, it.rest().len());
-}
\ No newline at end of file
+}
+proptest! {
+    #[test]
+    fn slice_mut(
+        ref input in tcp_any(),
+        ref other in tcp_any(),
+        ipv4_old in any::<[u8;4]>(),
+        ipv4_new in any::<[u8;4]>(),
+        ipv6_old in any::<[u8;16]>(),
+        ipv6_new in any::<[u8;16]>(),
+        ref payload in proptest::collection::vec(any::<u8>(), 0..20)
+    ) {
+        //too small slice
+        assert_matches!(
+            TcpHeaderSliceMut::from_slice(&mut [0;TCP_MINIMUM_HEADER_SIZE - 1]),
+            Err(ReadError::UnexpectedEndOfSlice(TCP_MINIMUM_HEADER_SIZE))
+        );
+
+        //ipv4 (all setters keep the checksum valid)
+        {
+            let mut header = input.clone();
+            header.checksum = header.calc_checksum_ipv4_raw(ipv4_old, ipv4_old, payload).unwrap();
+            let mut buffer = Vec::new();
+            header.write(&mut buffer).unwrap();
+
+            let mut slice = TcpHeaderSliceMut::from_slice(&mut buffer).unwrap();
+            assert_eq!(header, slice.as_slice().to_header());
+
+            let assert_checksum = |slice: &TcpHeaderSliceMut| {
+                assert_eq!(
+                    slice.as_slice().calc_checksum_ipv4_raw(&ipv4_old, &ipv4_old, payload).unwrap(),
+                    slice.as_slice().checksum()
+                );
+            };
+
+            slice.set_source_port(other.source_port);
+            assert_checksum(&slice);
+            slice.set_destination_port(other.destination_port);
+            assert_checksum(&slice);
+            slice.set_sequence_number(other.sequence_number);
+            assert_checksum(&slice);
+            slice.set_acknowledgment_number(other.acknowledgment_number);
+            assert_checksum(&slice);
+            slice.set_ns(other.ns);
+            assert_checksum(&slice);
+            slice.set_fin(other.fin);
+            assert_checksum(&slice);
+            slice.set_syn(other.syn);
+            assert_checksum(&slice);
+            slice.set_rst(other.rst);
+            assert_checksum(&slice);
+            slice.set_psh(other.psh);
+            assert_checksum(&slice);
+            slice.set_ack(other.ack);
+            assert_checksum(&slice);
+            slice.set_urg(other.urg);
+            assert_checksum(&slice);
+            slice.set_ece(other.ece);
+            assert_checksum(&slice);
+            slice.set_cwr(other.cwr);
+            assert_checksum(&slice);
+            slice.set_window_size(other.window_size);
+            assert_checksum(&slice);
+            slice.set_urgent_pointer(other.urgent_pointer);
+            assert_checksum(&slice);
+
+            //the options & the data offset are not modified
+            let expected = {
+                let mut expected = other.clone();
+                expected.set_options_raw(input.options()).unwrap();
+                expected.checksum = slice.as_slice().checksum();
+                expected
+            };
+            assert_eq!(expected, slice.as_slice().to_header());
+
+            //address change in the pseudo header
+            slice.update_checksum_ipv4_address(ipv4_old, ipv4_new);
+            assert_eq!(
+                slice.as_slice().calc_checksum_ipv4_raw(&ipv4_old, &ipv4_new, payload).unwrap(),
+                slice.as_slice().checksum()
+            );
+
+            //raw checksum
+            slice.set_checksum(0x1234);
+            assert_eq!(0x1234, slice.as_slice().checksum());
+        }
+        //ipv6
+        {
+            let mut header = input.clone();
+            header.checksum = header.calc_checksum_ipv6_raw(&ipv6_old, &ipv6_old, payload).unwrap();
+            let mut buffer = Vec::new();
+            header.write(&mut buffer).unwrap();
+
+            let mut slice = TcpHeaderSliceMut::from_slice(&mut buffer).unwrap();
+            slice.set_source_port(other.source_port);
+            slice.update_checksum_ipv6_address(&ipv6_old, &ipv6_new);
+            assert_eq!(
+                slice.as_slice().calc_checksum_ipv6_raw(&ipv6_new, &ipv6_old, payload).unwrap(),
+                slice.as_slice().checksum()
+            );
+        }
+    }
+}
@@ -376,3 +376,81 @@ fn from_slice() {
     //check that the to_header method also results in the same header
     assert_eq!(slice.to_header(), input);
 }
+
+proptest! {
+    #[test]
+    fn slice_mut(
+        ref input in udp_any(),
+        source_port in any::<u16>(),
+        destination_port in any::<u16>(),
+        ipv4_old in any::<[u8;4]>(),
+        ipv4_new in any::<[u8;4]>(),
+        ipv6_old in any::<[u8;16]>(),
+        ipv6_new in any::<[u8;16]>(),
+        ref payload in proptest::collection::vec(any::<u8>(), 0..20)
+    ) {
+        //too small slice
+        assert_matches!(
+            UdpHeaderSliceMut::from_slice(&mut [0;UdpHeader::SERIALIZED_SIZE - 1]),
+            Err(ReadError::UnexpectedEndOfSlice(UdpHeader::SERIALIZED_SIZE))
+        );
+
+        //ipv4 (checksum is kept valid)
+        {
+            let mut header = input.clone();
+            header.checksum = header.calc_checksum_ipv4_raw(ipv4_old, ipv4_old, 17, payload).unwrap();
+            let mut buffer = Vec::new();
+            header.write(&mut buffer).unwrap();
+
+            let mut slice = UdpHeaderSliceMut::from_slice(&mut buffer).unwrap();
+            assert_eq!(header, slice.as_slice().to_header());
+
+            slice.set_source_port(source_port);
+            slice.set_destination_port(destination_port);
+            slice.update_checksum_ipv4_address(ipv4_old, ipv4_new);
+
+            let result = slice.as_slice().to_header();
+            assert_eq!(source_port, result.source_port);
+            assert_eq!(destination_port, result.destination_port);
+            assert_eq!(header.length, result.length);
+            assert_eq!(
+                result.calc_checksum_ipv4_raw(ipv4_new, ipv4_old, 17, payload).unwrap(),
+                result.checksum
+            );
+        }
+        //ipv6 (checksum is kept valid)
+        {
+            let mut header = input.clone();
+            header.checksum = header.calc_checksum_ipv6_raw(&ipv6_old, &ipv6_old, payload).unwrap();
+            let mut buffer = Vec::new();
+            header.write(&mut buffer).unwrap();
+
+            let mut slice = UdpHeaderSliceMut::from_slice(&mut buffer).unwrap();
+            slice.set_source_port(source_port);
+            slice.update_checksum_ipv6_address(&ipv6_old, &ipv6_new);
+
+            let result = slice.as_slice().to_header();
+            assert_eq!(
+                result.calc_checksum_ipv6_raw(&ipv6_old, &ipv6_new, payload).unwrap(),
+                result.checksum
+            );
+        }
+        //no checksum (stays zero)
+        {
+            let mut header = input.clone();
+            header.checksum = 0;
+            let mut buffer = Vec::new();
+            header.write(&mut buffer).unwrap();
+
+            let mut slice = UdpHeaderSliceMut::from_slice(&mut buffer).unwrap();
+            slice.set_source_port(source_port);
+            slice.set_destination_port(destination_port);
+            slice.update_checksum_ipv4_address(ipv4_old, ipv4_new);
+            slice.update_checksum_ipv6_address(&ipv6_old, &ipv6_new);
+            assert_eq!(0, slice.as_slice().checksum());
+
+            slice.set_checksum(0x1234);
+            assert_eq!(0x1234, slice.as_slice().checksum());
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use etherparse::*;
+use super::super::*;
+
+#[test]
+fn new() {
+    let header = GeneveHeader::new(0x6558, 0x123456);
+    assert_eq!(0, header.version);
+    assert!(!header.control_packet);
+    assert!(!header.critical_options);
+    assert_eq!(0x6558, header.protocol_type);
+    assert_eq!(0x123456, header.vni);
+    assert_eq!(0, header.options().len());
+    assert_eq!(GeneveHeader::SERIALIZED_SIZE, header.header_len());
+}
+
+#[test]
+fn default() {
+    assert_eq!(GeneveHeader::new(0, 0), Default::default());
+}
+
+proptest! {
+    #[test]
+    fn eq_debug(ref a in geneve_any(), ref b in geneve_any()) {
+        assert_eq!(a, &a.clone());
+        assert_eq!(
+            a == b,
+            a.version == b.version &&
+            a.control_packet == b.control_packet &&
+            a.critical_options == b.critical_options &&
+            a.protocol_type == b.protocol_type &&
+            a.vni == b.vni &&
+            a.options() == b.options()
+        );
+        assert_eq!(
+            &format!("GeneveHeader {{ version: {}, control_packet: {}, critical_options: {}, protocol_type: {}, vni: {}, options: {:?} }}",
+                a.version,
+                a.control_packet,
+                a.critical_options,
+                a.protocol_type,
+                a.vni,
+                a.options()),
+            &format!("{:?}", a)
+        );
+    }
+}
+
+#[test]
+fn set_options_raw() {
+    let mut header = GeneveHeader::new(0x6558, 1);
+
+    //ok
+    for len in (0..=GENEVE_MAX_OPTIONS_LENGTH).step_by(4) {
+        let data: Vec<u8> = (0..len).map(|x| x as u8).collect();
+        header.set_options_raw(&data).unwrap();
+        assert_eq!(&data[..], header.options());
+        assert_eq!(GeneveHeader::SERIALIZED_SIZE + len, header.header_len());
+    }
+
+    //not aligned or too big
+    header.set_options_raw(&[1,2,3,4]).unwrap();
+    for len in &[1, 2, 3, 5, GENEVE_MAX_OPTIONS_LENGTH + 4] {
+        let data = vec![0;*len];
+        assert_eq!(
+            Err(ValueError::GeneveOptionsLengthBad(*len)),
+            header.set_options_raw(&data)
+        );
+        //check that the options were not modified
+        assert_eq!(&[1,2,3,4], header.options());
+    }
+}
+
+#[test]
+fn write() {
+    let mut header = GeneveHeader::new(0x6558, 0x123456);
+    header.version = 1;
+    header.control_packet = true;
+    header.critical_options = true;
+    header.set_options_raw(&[1,2,3,4,5,6,7,8]).unwrap();
+
+    let mut buffer = Vec::new();
+    header.write(&mut buffer).unwrap();
+    assert_eq!(
+        vec![
+            0x42, 0xc0, 0x65, 0x58,
+            0x12, 0x34, 0x56, 0,
+            1,2,3,4,5,6,7,8
+        ],
+        buffer
+    );
+}
+
+#[test]
+fn write_range_errors() {
+    use crate::ValueError::*;
+    use crate::ErrorField::*;
+    //version
+    {
+        let mut header = GeneveHeader::new(0x6558, 0);
+        header.version = 4;
+        let mut buffer = Vec::new();
+        assert_matches!(
+            header.write(&mut buffer),
+            Err(WriteError::ValueError(U8TooLarge{value: 4, max: 3, field: GeneveVersion}))
+        );
+        assert_eq!(0, buffer.len());
+    }
+    //vni
+    {
+        let header = GeneveHeader::new(0x6558, 0x0100_0000);
+        let mut buffer = Vec::new();
+        assert_matches!(
+            header.write(&mut buffer),
+            Err(WriteError::ValueError(U32TooLarge{value: 0x0100_0000, max: 0x00ff_ffff, field: GeneveVni}))
+        );
+        assert_eq!(0, buffer.len());
+    }
+}
+
+proptest! {
+    #[test]
+    fn read_write(ref input in geneve_any()) {
+        use std::io::Cursor;
+
+        //serialize
+        let mut buffer: Vec<u8> = Vec::with_capacity(input.header_len() + 1);
+        input.write(&mut buffer).unwrap();
+        assert_eq!(input.header_len(), buffer.len());
+
+        //deserialize with read
+        {
+            let result = GeneveHeader::read(&mut Cursor::new(&buffer)).unwrap();
+            assert_eq!(input, &result);
+        }
+        //deserialize from slice
+        {
+            //add some data to test the return slice
+            buffer.push(1);
+
+            let result = GeneveHeader::read_from_slice(&buffer).unwrap();
+            assert_eq!(input, &result.0);
+            assert_eq!(&buffer[buffer.len()-1 .. ], result.1);
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn from_slice(ref input in geneve_any()) {
+        //serialize
+        let mut buffer: Vec<u8> = Vec::with_capacity(input.header_len() + 1);
+        input.write(&mut buffer).unwrap();
+        buffer.push(1);
+
+        //check the fields
+        let slice = GeneveHeaderSlice::from_slice(&buffer).unwrap();
+        assert_eq!(&buffer[..input.header_len()], slice.slice());
+        assert_eq!(input.version, slice.version());
+        assert_eq!((input.options().len()/4) as u8, slice.opt_len());
+        assert_eq!(input.control_packet, slice.control_packet());
+        assert_eq!(input.critical_options, slice.critical_options());
+        assert_eq!(input.protocol_type, slice.protocol_type());
+        assert_eq!(input.vni, slice.vni());
+        assert_eq!(input.options(), slice.options());
+        assert_eq!(input, &slice.to_header());
+
+        //check that a too small slice triggers an error
+        for len in 0..GeneveHeader::SERIALIZED_SIZE {
+            assert_matches!(
+                GeneveHeaderSlice::from_slice(&buffer[..len]),
+                Err(ReadError::UnexpectedEndOfSlice(GeneveHeader::SERIALIZED_SIZE))
+            );
+        }
+        for len in GeneveHeader::SERIALIZED_SIZE..input.header_len() {
+            let expected_len = input.header_len();
+            assert_matches!(
+                GeneveHeaderSlice::from_slice(&buffer[..len]),
+                Err(ReadError::UnexpectedEndOfSlice(value)) if value == expected_len
+            );
+        }
+    }
+}
+
+#[test]
+fn options_iterator() {
+    let options = [
+        //class 0x0102, type 0x83 (critical), no data
+        0x01, 0x02, 0x83, 0x00,
+        //class 0xffff, type 0x04, 8 bytes data
+        0xff, 0xff, 0x04, 0x02,
+        1, 2, 3, 4, 5, 6, 7, 8,
+    ];
+    let mut header = GeneveHeader::new(0x6558, 1);
+    header.set_options_raw(&options).unwrap();
+
+    let mut buffer = Vec::new();
+    header.write(&mut buffer).unwrap();
+    let slice = GeneveHeaderSlice::from_slice(&buffer).unwrap();
+
+    for mut iterator in [header.options_iterator(), slice.options_iterator()] {
+        assert_eq!(&options[..], iterator.rest());
+
+        let first = iterator.next().unwrap().unwrap();
+        assert_eq!(&options[..4], first.slice());
+        assert_eq!(0x0102, first.option_class());
+        assert_eq!(0x83, first.option_type());
+        assert!(first.critical());
+        assert_eq!(0, first.length());
+        assert_eq!(0, first.data().len());
+
+        let second = iterator.next().unwrap().unwrap();
+        assert_eq!(&options[4..], second.slice());
+        assert_eq!(0xffff, second.option_class());
+        assert_eq!(0x04, second.option_type());
+        assert!(!second.critical());
+        assert_eq!(2, second.length());
+        assert_eq!(&[1, 2, 3, 4, 5, 6, 7, 8], second.data());
+
+        assert_matches!(iterator.next(), None);
+        assert_eq!(0, iterator.rest().len());
+    }
+}
+
+#[test]
+fn options_iterator_errors() {
+    //option header too short
+    {
+        let mut iterator = GeneveOptionsIterator::from_slice(&[1, 2, 3]);
+        assert_matches!(iterator.next(), Some(Err(ReadError::UnexpectedEndOfSlice(4))));
+        assert_matches!(iterator.next(), None);
+    }
+    //data too short
+    {
+        let mut iterator = GeneveOptionsIterator::from_slice(&[1, 2, 3, 1, 0, 0, 0]);
+        assert_matches!(iterator.next(), Some(Err(ReadError::UnexpectedEndOfSlice(8))));
+        assert_matches!(iterator.next(), None);
+    }
+}
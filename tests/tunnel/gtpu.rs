@@ -0,0 +1,474 @@
+use etherparse::*;
+use super::super::*;
+
+#[test]
+fn new() {
+    let header = GtpuHeader::new(0x1234_5678);
+    assert_eq!(1, header.version);
+    assert!(header.protocol_type);
+    assert_eq!(GTPU_MESSAGE_TYPE_G_PDU, header.message_type);
+    assert_eq!(0, header.length);
+    assert_eq!(0x1234_5678, header.teid);
+    assert_eq!(None, header.sequence_number);
+    assert_eq!(None, header.n_pdu_number);
+    assert_eq!(GTPU_EXTENSION_HEADER_NONE, header.next_extension_header_type());
+    assert_eq!(0, header.extension_headers().len());
+    assert!(!header.has_optional_fields());
+    assert_eq!(GtpuHeader::SERIALIZED_SIZE, header.header_len());
+}
+
+#[test]
+fn default() {
+    assert_eq!(GtpuHeader::new(0), Default::default());
+}
+
+proptest! {
+    #[test]
+    fn eq_debug(ref a in gtpu_any(), ref b in gtpu_any()) {
+        assert_eq!(a, &a.clone());
+        assert_eq!(
+            a == b,
+            a.version == b.version &&
+            a.protocol_type == b.protocol_type &&
+            a.message_type == b.message_type &&
+            a.length == b.length &&
+            a.teid == b.teid &&
+            a.sequence_number == b.sequence_number &&
+            a.n_pdu_number == b.n_pdu_number &&
+            a.next_extension_header_type() == b.next_extension_header_type() &&
+            a.extension_headers() == b.extension_headers()
+        );
+        assert_eq!(
+            &format!("GtpuHeader {{ version: {}, protocol_type: {}, message_type: {}, length: {}, teid: {}, sequence_number: {:?}, n_pdu_number: {:?}, next_extension_header_type: {}, extension_headers: {:?} }}",
+                a.version,
+                a.protocol_type,
+                a.message_type,
+                a.length,
+                a.teid,
+                a.sequence_number,
+                a.n_pdu_number,
+                a.next_extension_header_type(),
+                a.extension_headers()),
+            &format!("{:?}", a)
+        );
+    }
+}
+
+#[test]
+fn header_len() {
+    let mut header = GtpuHeader::new(1);
+    assert_eq!(8, header.header_len());
+
+    //optional fields
+    header.sequence_number = Some(1);
+    assert_eq!(12, header.header_len());
+    header.sequence_number = None;
+    header.n_pdu_number = Some(1);
+    assert_eq!(12, header.header_len());
+    header.n_pdu_number = None;
+
+    //extension headers
+    header.add_extension_header(1, &[1,2]).unwrap();
+    assert_eq!(16, header.header_len());
+    header.add_extension_header(2, &[1,2,3,4,5,6]).unwrap();
+    assert_eq!(24, header.header_len());
+    header.clear_extension_headers();
+    assert_eq!(8, header.header_len());
+}
+
+#[test]
+fn add_extension_header() {
+    let mut header = GtpuHeader::new(1);
+
+    //first extension header
+    header.add_extension_header(0x40, &[1,2]).unwrap();
+    assert_eq!(0x40, header.next_extension_header_type());
+    assert_eq!(&[1, 1,2, 0], header.extension_headers());
+
+    //second extension header (linked via the previous one)
+    header.add_extension_header(0x85, &[3,4,5,6,7,8]).unwrap();
+    assert_eq!(0x40, header.next_extension_header_type());
+    assert_eq!(&[1, 1,2, 0x85, 2, 3,4,5,6,7,8, 0], header.extension_headers());
+
+    //bad lengths (header is not modified)
+    for len in &[0, 1, 3, 4, 5] {
+        let content = vec![0;*len];
+        assert_eq!(
+            Err(ValueError::GtpuExtensionHeaderLengthBad(*len)),
+            header.add_extension_header(1, &content)
+        );
+    }
+    assert_eq!(&[1, 1,2, 0x85, 2, 3,4,5,6,7,8, 0], header.extension_headers());
+
+    //maximum length
+    {
+        let mut header = GtpuHeader::new(1);
+        let content = vec![0;GTPU_MAX_EXTENSION_HEADERS_LENGTH - 2];
+        header.add_extension_header(1, &content).unwrap();
+        assert_eq!(GTPU_MAX_EXTENSION_HEADERS_LENGTH, header.extension_headers().len());
+        assert_eq!(
+            Err(ValueError::GtpuExtensionHeaderLengthBad(2)),
+            header.add_extension_header(1, &[1,2])
+        );
+    }
+}
+
+#[test]
+fn add_pdu_session_container() {
+    use crate::ValueError::*;
+    use crate::ErrorField::*;
+
+    let container = GtpuPduSessionContainer {
+        pdu_type: GtpuPduSessionContainer::DL_PDU_SESSION_INFORMATION,
+        qos_flow_identifier: 0x3f,
+        paging_policy_presence: true,
+        reflective_qos_indicator: false
+    };
+    let mut header = GtpuHeader::new(1);
+    header.add_pdu_session_container(&container).unwrap();
+    assert_eq!(GTPU_EXTENSION_HEADER_PDU_SESSION_CONTAINER, header.next_extension_header_type());
+    assert_eq!(&[1, 0x00, 0xbf, 0], header.extension_headers());
+
+    let result = header.extension_headers_iterator().next().unwrap().unwrap();
+    assert_eq!(Some(container.clone()), result.pdu_session_container());
+
+    //range errors
+    assert_eq!(
+        Err(U8TooLarge{ value: 0x10, max: 0xf, field: GtpuPduType }),
+        header.add_pdu_session_container(&{
+            let mut value = container.clone();
+            value.pdu_type = 0x10;
+            value
+        })
+    );
+    assert_eq!(
+        Err(U8TooLarge{ value: 0x40, max: 0x3f, field: GtpuQosFlowIdentifier }),
+        header.add_pdu_session_container(&{
+            let mut value = container.clone();
+            value.qos_flow_identifier = 0x40;
+            value
+        })
+    );
+}
+
+proptest! {
+    #[test]
+    fn pdu_session_container_bytes(
+        pdu_type in 0u8..0x10,
+        qos_flow_identifier in 0u8..0x40,
+        paging_policy_presence in any::<bool>(),
+        reflective_qos_indicator in any::<bool>())
+    {
+        let container = GtpuPduSessionContainer {
+            pdu_type,
+            qos_flow_identifier,
+            paging_policy_presence,
+            reflective_qos_indicator
+        };
+        assert_eq!(Ok(()), container.check_ranges());
+        assert_eq!(container, GtpuPduSessionContainer::from_bytes(&container.to_bytes()));
+    }
+}
+
+#[test]
+fn set_payload_length() {
+    let mut header = GtpuHeader::new(1);
+    header.set_payload_length(20).unwrap();
+    assert_eq!(20, header.length);
+
+    header.sequence_number = Some(1);
+    header.add_extension_header(1, &[1,2]).unwrap();
+    header.set_payload_length(20).unwrap();
+    assert_eq!(28, header.length);
+
+    //maximum
+    let max = u16::MAX as usize - 8;
+    header.set_payload_length(max).unwrap();
+    assert_eq!(u16::MAX, header.length);
+
+    //too large
+    assert_eq!(
+        Err(ValueError::GtpuPayloadLengthTooLarge(max + 1)),
+        header.set_payload_length(max + 1)
+    );
+}
+
+#[test]
+fn write() {
+    //mandatory part only
+    {
+        let mut header = GtpuHeader::new(0x1234_5678);
+        header.length = 0x0102;
+        let mut buffer = Vec::new();
+        header.write(&mut buffer).unwrap();
+        assert_eq!(
+            vec![
+                0x30, 0xff, 0x01, 0x02,
+                0x12, 0x34, 0x56, 0x78
+            ],
+            buffer
+        );
+    }
+    //optional fields & extension headers
+    {
+        let mut header = GtpuHeader::new(0x1234_5678);
+        header.length = 0x0102;
+        header.sequence_number = Some(0xabcd);
+        header.n_pdu_number = Some(0xef);
+        header.add_extension_header(0x85, &[1,2]).unwrap();
+        let mut buffer = Vec::new();
+        header.write(&mut buffer).unwrap();
+        assert_eq!(
+            vec![
+                0x37, 0xff, 0x01, 0x02,
+                0x12, 0x34, 0x56, 0x78,
+                0xab, 0xcd, 0xef, 0x85,
+                1, 1, 2, 0
+            ],
+            buffer
+        );
+    }
+    //only the sequence number
+    {
+        let mut header = GtpuHeader::new(0x1234_5678);
+        header.protocol_type = false;
+        header.sequence_number = Some(0xabcd);
+        let mut buffer = Vec::new();
+        header.write(&mut buffer).unwrap();
+        assert_eq!(
+            vec![
+                0x22, 0xff, 0x00, 0x00,
+                0x12, 0x34, 0x56, 0x78,
+                0xab, 0xcd, 0x00, 0x00,
+            ],
+            buffer
+        );
+    }
+}
+
+#[test]
+fn write_range_errors() {
+    use crate::ValueError::*;
+    use crate::ErrorField::*;
+
+    let mut header = GtpuHeader::new(1);
+    header.version = 8;
+    let mut buffer = Vec::new();
+    assert_matches!(
+        header.write(&mut buffer),
+        Err(WriteError::ValueError(U8TooLarge{value: 8, max: 7, field: GtpuVersion}))
+    );
+    assert_eq!(0, buffer.len());
+}
+
+proptest! {
+    #[test]
+    fn read_write(ref input in gtpu_any()) {
+        use std::io::Cursor;
+
+        //serialize
+        let mut buffer: Vec<u8> = Vec::with_capacity(input.header_len() + 1);
+        input.write(&mut buffer).unwrap();
+        assert_eq!(input.header_len(), buffer.len());
+
+        //deserialize with read
+        {
+            let result = GtpuHeader::read(&mut Cursor::new(&buffer)).unwrap();
+            assert_eq!(input, &result);
+        }
+        //deserialize from slice
+        {
+            //add some data to test the return slice
+            buffer.push(1);
+
+            let result = GtpuHeader::read_from_slice(&buffer).unwrap();
+            assert_eq!(input, &result.0);
+            assert_eq!(&buffer[buffer.len()-1 .. ], result.1);
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn from_slice(ref input in gtpu_any()) {
+        //serialize
+        let mut buffer: Vec<u8> = Vec::with_capacity(input.header_len() + 1);
+        input.write(&mut buffer).unwrap();
+        buffer.push(1);
+
+        //check the fields
+        let slice = GtpuHeaderSlice::from_slice(&buffer).unwrap();
+        assert_eq!(&buffer[..input.header_len()], slice.slice());
+        assert_eq!(input.version, slice.version());
+        assert_eq!(input.protocol_type, slice.protocol_type());
+        assert_eq!(!input.extension_headers().is_empty(), slice.extension_header_flag());
+        assert_eq!(input.sequence_number.is_some(), slice.sequence_number_flag());
+        assert_eq!(input.n_pdu_number.is_some(), slice.n_pdu_number_flag());
+        assert_eq!(input.message_type, slice.message_type());
+        assert_eq!(input.length, slice.length());
+        assert_eq!(input.teid, slice.teid());
+        assert_eq!(input.sequence_number, slice.sequence_number());
+        assert_eq!(input.n_pdu_number, slice.n_pdu_number());
+        assert_eq!(input.next_extension_header_type(), slice.next_extension_header_type());
+        assert_eq!(input.extension_headers(), slice.extension_headers());
+        assert_eq!(input, &slice.to_header());
+
+        //check that a too small slice triggers an error
+        use crate::ReadError::*;
+        for len in 0..input.header_len() {
+            assert_matches!(
+                GtpuHeaderSlice::from_slice(&buffer[..len]),
+                Err(UnexpectedEndOfSlice(_))
+            );
+            assert_matches!(
+                GtpuHeader::read(&mut std::io::Cursor::new(&buffer[..len])),
+                Err(IoError(_))
+            );
+        }
+    }
+}
+
+#[test]
+fn from_slice_errors() {
+    use crate::ReadError::*;
+
+    //version
+    for version in &[0u8, 2, 7] {
+        let buffer = [version << 5, 0xff, 0, 0, 0, 0, 0, 0];
+        assert_matches!(
+            GtpuHeaderSlice::from_slice(&buffer),
+            Err(GtpuUnexpectedVersion(value)) if value == *version
+        );
+        assert_matches!(
+            GtpuHeader::read(&mut std::io::Cursor::new(&buffer)),
+            Err(GtpuUnexpectedVersion(value)) if value == *version
+        );
+    }
+
+    //length too small for optional fields & extension headers
+    {
+        let buffer = [0x34, 0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x85, 1, 2];
+        assert_matches!(GtpuHeaderSlice::from_slice(&buffer[..11]), Err(UnexpectedEndOfSlice(12)));
+        assert_matches!(GtpuHeaderSlice::from_slice(&buffer[..12]), Err(UnexpectedEndOfSlice(13)));
+        assert_matches!(GtpuHeaderSlice::from_slice(&buffer[..]), Err(UnexpectedEndOfSlice(16)));
+    }
+
+    //extension header length zero
+    {
+        let buffer = [0x34, 0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x85, 0, 0, 0, 0];
+        assert_matches!(
+            GtpuHeaderSlice::from_slice(&buffer),
+            Err(GtpuExtensionHeaderLengthZero)
+        );
+        assert_matches!(
+            GtpuHeader::read(&mut std::io::Cursor::new(&buffer)),
+            Err(GtpuExtensionHeaderLengthZero)
+        );
+    }
+
+    //extension headers too large
+    {
+        let mut buffer = vec![0x34, 0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x85];
+        //first extension header with 252 bytes
+        buffer.push(63);
+        buffer.resize(buffer.len() + 63*4 - 2, 0);
+        buffer.push(0x85);
+        //second extension header with 8 bytes
+        buffer.push(2);
+        buffer.resize(buffer.len() + 2*4 - 1, 0);
+        assert_matches!(
+            GtpuHeaderSlice::from_slice(&buffer),
+            Err(GtpuExtensionHeadersTooLarge(260))
+        );
+        assert_matches!(
+            GtpuHeader::read(&mut std::io::Cursor::new(&buffer)),
+            Err(GtpuExtensionHeadersTooLarge(260))
+        );
+    }
+}
+
+#[test]
+fn ignored_optional_fields() {
+    //the sequence number, n-pdu number & next extension header type fields are
+    //ignored if the corresponding flags are not set
+    let buffer = [0x31, 0xff, 0, 4, 0, 0, 0, 1, 0xab, 0xcd, 0xef, 0x85];
+    let slice = GtpuHeaderSlice::from_slice(&buffer).unwrap();
+    assert_eq!(12, slice.slice().len());
+    assert_eq!(None, slice.sequence_number());
+    assert_eq!(Some(0xef), slice.n_pdu_number());
+    assert_eq!(GTPU_EXTENSION_HEADER_NONE, slice.next_extension_header_type());
+    assert_eq!(0, slice.extension_headers().len());
+    assert_matches!(slice.extension_headers_iterator().next(), None);
+
+    let header = GtpuHeader::read(&mut std::io::Cursor::new(&buffer)).unwrap();
+    assert_eq!(slice.to_header(), header);
+}
+
+#[test]
+fn extension_headers_iterator() {
+    let mut header = GtpuHeader::new(1);
+    header.add_extension_header(0x40, &[1,2]).unwrap();
+    header.add_pdu_session_container(&GtpuPduSessionContainer {
+        pdu_type: GtpuPduSessionContainer::UL_PDU_SESSION_INFORMATION,
+        qos_flow_identifier: 5,
+        paging_policy_presence: false,
+        reflective_qos_indicator: false
+    }).unwrap();
+
+    let mut buffer = Vec::new();
+    header.write(&mut buffer).unwrap();
+    let slice = GtpuHeaderSlice::from_slice(&buffer).unwrap();
+
+    for mut iterator in [header.extension_headers_iterator(), slice.extension_headers_iterator()] {
+        assert_eq!(header.extension_headers(), iterator.rest());
+
+        let first = iterator.next().unwrap().unwrap();
+        assert_eq!(&[1, 1, 2, 0x85], first.slice());
+        assert_eq!(0x40, first.extension_type());
+        assert_eq!(1, first.length());
+        assert_eq!(&[1, 2], first.content());
+        assert_eq!(0x85, first.next_extension_header_type());
+        assert_eq!(None, first.pdu_session_container());
+
+        let second = iterator.next().unwrap().unwrap();
+        assert_eq!(&[1, 0x10, 5, 0], second.slice());
+        assert_eq!(GTPU_EXTENSION_HEADER_PDU_SESSION_CONTAINER, second.extension_type());
+        assert_eq!(GTPU_EXTENSION_HEADER_NONE, second.next_extension_header_type());
+        assert_eq!(
+            Some(GtpuPduSessionContainer {
+                pdu_type: GtpuPduSessionContainer::UL_PDU_SESSION_INFORMATION,
+                qos_flow_identifier: 5,
+                paging_policy_presence: false,
+                reflective_qos_indicator: false
+            }),
+            second.pdu_session_container()
+        );
+
+        assert_matches!(iterator.next(), None);
+        assert_eq!(0, iterator.rest().len());
+    }
+}
+
+#[test]
+fn extension_headers_iterator_errors() {
+    use crate::ReadError::*;
+
+    //missing length
+    {
+        let mut iterator = GtpuExtensionHeadersIterator::from_slice(1, &[]);
+        assert_matches!(iterator.next(), Some(Err(UnexpectedEndOfSlice(1))));
+        assert_matches!(iterator.next(), None);
+    }
+    //length zero
+    {
+        let mut iterator = GtpuExtensionHeadersIterator::from_slice(1, &[0, 1, 2, 0]);
+        assert_matches!(iterator.next(), Some(Err(GtpuExtensionHeaderLengthZero)));
+        assert_matches!(iterator.next(), None);
+    }
+    //content too short
+    {
+        let mut iterator = GtpuExtensionHeadersIterator::from_slice(1, &[2, 1, 2, 0]);
+        assert_matches!(iterator.next(), Some(Err(UnexpectedEndOfSlice(8))));
+        assert_matches!(iterator.next(), None);
+    }
+}
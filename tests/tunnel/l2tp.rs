@@ -0,0 +1,335 @@
+use etherparse::*;
+
+///Serializes an attribute value pair.
+fn avp(mandatory: bool, vendor_id: u16, attribute_type: u16, value: &[u8]) -> Vec<u8> {
+    let length = (6 + value.len()) as u16;
+    let mut result = vec![
+        (if mandatory { 0x80 } else { 0 }) | (length >> 8) as u8, length as u8,
+        (vendor_id >> 8) as u8, vendor_id as u8,
+        (attribute_type >> 8) as u8, attribute_type as u8
+    ];
+    result.extend_from_slice(value);
+    result
+}
+
+mod l2tpv2_header_slice {
+    use super::*;
+
+    #[test]
+    fn data_minimal() {
+        let buffer = [
+            0x00, 0x02, //flags & version
+            0x12, 0x34, //tunnel id
+            0x56, 0x78, //session id
+            0xff, 0x03 //ppp
+        ];
+        let slice = L2tpv2HeaderSlice::from_slice(&buffer).unwrap();
+        assert_eq!(&buffer[..6], slice.slice());
+        assert_eq!(6, slice.header_len());
+        assert!(!slice.is_control());
+        assert!(!slice.length_flag());
+        assert!(!slice.sequence_flag());
+        assert!(!slice.offset_flag());
+        assert!(!slice.priority_flag());
+        assert_eq!(2, slice.version());
+        assert_eq!(None, slice.length());
+        assert_eq!(0x1234, slice.tunnel_id());
+        assert_eq!(0x5678, slice.session_id());
+        assert_eq!(None, slice.ns());
+        assert_eq!(None, slice.nr());
+        assert_eq!(None, slice.offset_size());
+        assert_eq!(0, slice.avps().len());
+        assert_matches!(slice.avps_iterator().next(), None);
+    }
+
+    #[test]
+    fn data_all_fields() {
+        let buffer = [
+            0b0100_1011, 0x02, //flags (length, sequence, offset, priority) & version
+            0x00, 0x14, //length
+            0x12, 0x34, //tunnel id
+            0x56, 0x78, //session id
+            0x00, 0x01, //ns
+            0x00, 0x02, //nr
+            0x00, 0x02, //offset size
+            0x00, 0x00, //offset padding
+            0xff, 0x03, 0x00, 0x21 //ppp
+        ];
+        let slice = L2tpv2HeaderSlice::from_slice(&buffer).unwrap();
+        assert_eq!(&buffer[..16], slice.slice());
+        assert_eq!(16, slice.header_len());
+        assert!(!slice.is_control());
+        assert!(slice.length_flag());
+        assert!(slice.sequence_flag());
+        assert!(slice.offset_flag());
+        assert!(slice.priority_flag());
+        assert_eq!(Some(0x14), slice.length());
+        assert_eq!(0x1234, slice.tunnel_id());
+        assert_eq!(0x5678, slice.session_id());
+        assert_eq!(Some(1), slice.ns());
+        assert_eq!(Some(2), slice.nr());
+        assert_eq!(Some(2), slice.offset_size());
+
+        //too short (fixed part & offset padding)
+        for len in 0..16 {
+            assert_matches!(
+                L2tpv2HeaderSlice::from_slice(&buffer[..len]),
+                Err(ReadError::UnexpectedEndOfSlice(_))
+            );
+        }
+    }
+
+    #[test]
+    fn control() {
+        let avp0 = avp(true, 0, 0, &[0, 1]);
+        let avp1 = avp(false, 9, 7, &[1, 2, 3, 4]);
+        let length = 12 + avp0.len() + avp1.len();
+        let mut buffer = vec![
+            0b1100_1000, 0x02, //flags (type, length, sequence) & version
+            0, length as u8, //length
+            0x12, 0x34, //tunnel id
+            0x00, 0x00, //session id
+            0x00, 0x03, //ns
+            0x00, 0x04, //nr
+        ];
+        buffer.extend_from_slice(&avp0);
+        buffer.extend_from_slice(&avp1);
+        //additional data not part of the message
+        buffer.extend_from_slice(&[1, 2, 3]);
+
+        let slice = L2tpv2HeaderSlice::from_slice(&buffer).unwrap();
+        assert_eq!(&buffer[..length], slice.slice());
+        assert_eq!(12, slice.header_len());
+        assert!(slice.is_control());
+        assert_eq!(0x1234, slice.tunnel_id());
+        assert_eq!(0, slice.session_id());
+        assert_eq!(Some(3), slice.ns());
+        assert_eq!(Some(4), slice.nr());
+        assert_eq!(&buffer[12..length], slice.avps());
+
+        let mut iterator = slice.avps_iterator();
+        assert_matches!(iterator.next(), Some(Ok(value)) => {
+            assert!(value.mandatory());
+            assert!(!value.hidden());
+            assert_eq!(8, value.length());
+            assert_eq!(0, value.vendor_id());
+            assert_eq!(0, value.attribute_type());
+            assert_eq!(&[0, 1], value.value());
+        });
+        assert_matches!(iterator.next(), Some(Ok(value)) => {
+            assert!(!value.mandatory());
+            assert_eq!(9, value.vendor_id());
+            assert_eq!(7, value.attribute_type());
+            assert_eq!(&[1, 2, 3, 4], value.value());
+        });
+        assert_matches!(iterator.next(), None);
+
+        //length bigger then the slice
+        assert_matches!(
+            L2tpv2HeaderSlice::from_slice(&buffer[..length - 1]),
+            Err(ReadError::UnexpectedEndOfSlice(value)) => assert_eq!(length, value)
+        );
+    }
+
+    #[test]
+    fn control_without_length() {
+        let buffer = [
+            0b1000_0000, 0x02, //flags (type) & version
+            0x12, 0x34, //tunnel id
+            0x00, 0x00, //session id
+            0x00, 0x06, 0x00, 0x00, 0x00, 0x00 //avp
+        ];
+        let slice = L2tpv2HeaderSlice::from_slice(&buffer).unwrap();
+        assert_eq!(&buffer[..], slice.slice());
+        assert_eq!(&buffer[6..], slice.avps());
+    }
+
+    #[test]
+    fn errors() {
+        use crate::ReadError::*;
+
+        //version
+        assert_matches!(
+            L2tpv2HeaderSlice::from_slice(&[0, 3, 0, 0, 0, 0]),
+            Err(L2tpUnexpectedVersion(3))
+        );
+        //length smaller then the header
+        assert_matches!(
+            L2tpv2HeaderSlice::from_slice(&[0b1100_1000, 2, 0, 11, 0, 0, 0, 0, 0, 0, 0, 0]),
+            Err(L2tpLengthTooSmall(11))
+        );
+        //too short
+        for len in 0..6 {
+            assert_matches!(
+                L2tpv2HeaderSlice::from_slice(&[0, 2, 0, 0, 0, 0][..len]),
+                Err(UnexpectedEndOfSlice(6))
+            );
+        }
+    }
+}
+
+mod l2tpv3_control_header_slice {
+    use super::*;
+
+    fn message() -> Vec<u8> {
+        let avp0 = avp(true, 0, 0, &[0, 1]);
+        let mut result = vec![
+            0b1100_1000, 0x03, //flags (type, length, sequence) & version
+            0, 12 + avp0.len() as u8, //length
+            0x12, 0x34, 0x56, 0x78, //control connection id
+            0x00, 0x05, //ns
+            0x00, 0x06, //nr
+        ];
+        result.extend_from_slice(&avp0);
+        result
+    }
+
+    #[test]
+    fn from_slice() {
+        let mut buffer = message();
+        let length = buffer.len();
+        buffer.push(1);
+
+        let slice = L2tpv3ControlHeaderSlice::from_slice(&buffer).unwrap();
+        assert_eq!(&buffer[..length], slice.slice());
+        assert!(!slice.is_over_ip());
+        assert!(slice.length_flag());
+        assert!(slice.sequence_flag());
+        assert_eq!(3, slice.version());
+        assert_eq!(length as u16, slice.length());
+        assert_eq!(0x12345678, slice.control_connection_id());
+        assert_eq!(5, slice.ns());
+        assert_eq!(6, slice.nr());
+        assert_eq!(&buffer[12..length], slice.avps());
+        assert_eq!(1, slice.avps_iterator().count());
+    }
+
+    #[test]
+    fn from_ip_slice() {
+        let mut buffer = vec![0, 0, 0, 0];
+        buffer.extend_from_slice(&message());
+        let slice = L2tpv3ControlHeaderSlice::from_ip_slice(&buffer).unwrap();
+        assert_eq!(&buffer[..], slice.slice());
+        assert!(slice.is_over_ip());
+        assert_eq!(3, slice.version());
+        assert_eq!(0x12345678, slice.control_connection_id());
+        assert_eq!(5, slice.ns());
+        assert_eq!(6, slice.nr());
+        assert_eq!(&buffer[16..], slice.avps());
+
+        //too short (offset includes the session id)
+        assert_matches!(
+            L2tpv3ControlHeaderSlice::from_ip_slice(&buffer[..3]),
+            Err(ReadError::UnexpectedEndOfSlice(4))
+        );
+        assert_matches!(
+            L2tpv3ControlHeaderSlice::from_ip_slice(&buffer[..buffer.len() - 1]),
+            Err(ReadError::UnexpectedEndOfSlice(value)) => assert_eq!(buffer.len(), value)
+        );
+    }
+
+    #[test]
+    fn errors() {
+        use crate::ReadError::*;
+        let buffer = message();
+
+        //too short
+        for len in 0..12 {
+            assert_matches!(
+                L2tpv3ControlHeaderSlice::from_slice(&buffer[..len]),
+                Err(UnexpectedEndOfSlice(12))
+            );
+        }
+        //version
+        {
+            let mut buffer = buffer.clone();
+            buffer[1] = 2;
+            assert_matches!(
+                L2tpv3ControlHeaderSlice::from_slice(&buffer),
+                Err(L2tpUnexpectedVersion(2))
+            );
+        }
+        //length too small
+        {
+            let mut buffer = buffer.clone();
+            buffer[3] = 11;
+            assert_matches!(
+                L2tpv3ControlHeaderSlice::from_slice(&buffer),
+                Err(L2tpLengthTooSmall(11))
+            );
+        }
+    }
+}
+
+mod l2tpv3_session_header_slice {
+    use super::*;
+
+    #[test]
+    fn from_slice() {
+        let buffer = [0x00, 0x03, 0x00, 0x00, 0x12, 0x34, 0x56, 0x78, 1, 2];
+        let slice = L2tpv3SessionHeaderSlice::from_slice(&buffer).unwrap();
+        assert_eq!(&buffer[..8], slice.slice());
+        assert!(!slice.is_over_ip());
+        assert_eq!(0x12345678, slice.session_id());
+
+        //too short
+        for len in 0..8 {
+            assert_matches!(
+                L2tpv3SessionHeaderSlice::from_slice(&buffer[..len]),
+                Err(ReadError::UnexpectedEndOfSlice(8))
+            );
+        }
+        //version
+        assert_matches!(
+            L2tpv3SessionHeaderSlice::from_slice(&[0, 2, 0, 0, 0, 0, 0, 1]),
+            Err(ReadError::L2tpUnexpectedVersion(2))
+        );
+    }
+
+    #[test]
+    fn from_ip_slice() {
+        let buffer = [0x12, 0x34, 0x56, 0x78, 1, 2];
+        let slice = L2tpv3SessionHeaderSlice::from_ip_slice(&buffer).unwrap();
+        assert_eq!(&buffer[..4], slice.slice());
+        assert!(slice.is_over_ip());
+        assert_eq!(0x12345678, slice.session_id());
+
+        //too short
+        for len in 0..4 {
+            assert_matches!(
+                L2tpv3SessionHeaderSlice::from_ip_slice(&buffer[..len]),
+                Err(ReadError::UnexpectedEndOfSlice(4))
+            );
+        }
+    }
+}
+
+mod l2tp_avps_iterator {
+    use super::*;
+
+    #[test]
+    fn errors() {
+        use crate::ReadError::*;
+
+        //length field too small (the iterator stops after the error)
+        {
+            let mut buffer = avp(true, 0, 0, &[1, 2]);
+            buffer.extend_from_slice(&[0, 5, 0, 0, 0, 0]);
+            buffer.extend_from_slice(&avp(true, 0, 0, &[]));
+            let mut iterator = L2tpAvpsIterator::from_slice(&buffer);
+            assert_matches!(iterator.next(), Some(Ok(_)));
+            assert_eq!(&buffer[8..], iterator.rest());
+            assert_matches!(iterator.next(), Some(Err(L2tpAvpLengthTooSmall(5))));
+            assert_matches!(iterator.next(), None);
+        }
+        //too short
+        {
+            let buffer = avp(true, 0, 0, &[1, 2]);
+            for len in 1..buffer.len() {
+                let mut iterator = L2tpAvpsIterator::from_slice(&buffer[..len]);
+                assert_matches!(iterator.next(), Some(Err(UnexpectedEndOfSlice(_))));
+                assert_matches!(iterator.next(), None);
+            }
+        }
+    }
+}
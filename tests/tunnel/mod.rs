@@ -0,0 +1,56 @@
+pub mod vxlan;
+pub mod geneve;
+pub mod gtpu;
+pub mod l2tp;
+
+mod tunnel_header {
+    use super::super::*;
+
+    proptest! {
+        #[test]
+        fn header_len(ref vxlan in vxlan_any(),
+                      ref geneve in geneve_any(),
+                      ref gtpu in gtpu_any())
+        {
+            assert_eq!(VxlanHeader::SERIALIZED_SIZE,
+                       TunnelHeader::Vxlan(vxlan.clone()).header_len());
+            assert_eq!(geneve.header_len(),
+                       TunnelHeader::Geneve(geneve.clone()).header_len());
+            assert_eq!(gtpu.header_len(),
+                       TunnelHeader::Gtpu(gtpu.clone()).header_len());
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn write(ref vxlan in vxlan_any(),
+                 ref geneve in geneve_any(),
+                 ref gtpu in gtpu_any())
+        {
+            //vxlan
+            {
+                let mut expected = Vec::new();
+                vxlan.write(&mut expected).unwrap();
+                let mut actual = Vec::new();
+                TunnelHeader::Vxlan(vxlan.clone()).write(&mut actual).unwrap();
+                assert_eq!(expected, actual);
+            }
+            //geneve
+            {
+                let mut expected = Vec::new();
+                geneve.write(&mut expected).unwrap();
+                let mut actual = Vec::new();
+                TunnelHeader::Geneve(geneve.clone()).write(&mut actual).unwrap();
+                assert_eq!(expected, actual);
+            }
+            //gtpu
+            {
+                let mut expected = Vec::new();
+                gtpu.write(&mut expected).unwrap();
+                let mut actual = Vec::new();
+                TunnelHeader::Gtpu(gtpu.clone()).write(&mut actual).unwrap();
+                assert_eq!(expected, actual);
+            }
+        }
+    }
+}
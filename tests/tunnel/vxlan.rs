@@ -0,0 +1,91 @@
+use etherparse::*;
+use super::super::*;
+
+#[test]
+fn new() {
+    assert_eq!(
+        VxlanHeader{
+            flags: VXLAN_FLAG_VNI_VALID,
+            vni: 0x123456
+        },
+        VxlanHeader::new(0x123456)
+    );
+}
+
+#[test]
+fn write() {
+    let mut buffer = Vec::new();
+    VxlanHeader::new(0x123456).write(&mut buffer).unwrap();
+    assert_eq!(
+        vec![0x08, 0, 0, 0, 0x12, 0x34, 0x56, 0],
+        buffer
+    );
+}
+
+#[test]
+fn write_vni_too_large() {
+    use crate::ValueError::*;
+    let mut buffer = Vec::new();
+    assert_matches!(
+        VxlanHeader::new(0x0100_0000).write(&mut buffer),
+        Err(WriteError::ValueError(U32TooLarge{
+            value: 0x0100_0000,
+            max: 0x00ff_ffff,
+            field: ErrorField::VxlanVni
+        }))
+    );
+    assert_eq!(0, buffer.len());
+}
+
+proptest! {
+    #[test]
+    fn read_write(ref input in vxlan_any()) {
+        use std::io::Cursor;
+
+        //serialize
+        let mut buffer: Vec<u8> = Vec::with_capacity(VxlanHeader::SERIALIZED_SIZE + 1);
+        input.write(&mut buffer).unwrap();
+        assert_eq!(VxlanHeader::SERIALIZED_SIZE, buffer.len());
+
+        //deserialize with read
+        {
+            let result = VxlanHeader::read(&mut Cursor::new(&buffer)).unwrap();
+            assert_eq!(input, &result);
+        }
+        //deserialize from slice
+        {
+            //add some data to test the return slice
+            buffer.push(1);
+
+            let result = VxlanHeader::read_from_slice(&buffer).unwrap();
+            assert_eq!(input, &result.0);
+            assert_eq!(&buffer[buffer.len()-1 .. ], result.1);
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn from_slice(ref input in vxlan_any()) {
+        //serialize
+        let mut buffer: Vec<u8> = Vec::with_capacity(VxlanHeader::SERIALIZED_SIZE + 1);
+        input.write(&mut buffer).unwrap();
+        buffer.push(1);
+
+        //check the fields
+        let slice = VxlanHeaderSlice::from_slice(&buffer).unwrap();
+        assert_eq!(&buffer[..VxlanHeader::SERIALIZED_SIZE], slice.slice());
+        assert_eq!(input.flags, slice.flags());
+        assert_eq!(0 != input.flags & VXLAN_FLAG_VNI_VALID, slice.vni_valid());
+        assert_eq!(input.vni, slice.vni());
+        assert_eq!(input, &slice.to_header());
+
+        //check that a too small slice triggers an error
+        for len in 0..VxlanHeader::SERIALIZED_SIZE {
+            assert_matches!(
+                VxlanHeaderSlice::from_slice(&buffer[..len]),
+                Err(ReadError::UnexpectedEndOfSlice(VxlanHeader::SERIALIZED_SIZE))
+            );
+        }
+    }
+}
@@ -14,13 +14,29 @@ mod errors;
 mod link;
 mod internet;
 mod transport;
+mod tunnel;
 mod packet_builder;
 mod packet_filter;
 mod packet_slicing;
+mod packet_slicing_mut;
+mod nat;
+mod siit;
+mod segmentation;
+mod coalescing;
+mod tcp_reassembly;
+mod tcp_tracking;
+mod flow;
+mod flow_hash;
+mod netflow;
+mod sflow;
+mod bpf;
+mod packet_checksums;
+mod checksum;
 mod proptest_generators;
 pub use crate::proptest_generators::*;
 use proptest::prelude::*;
 mod packet_compositions;
+mod test_packets;
 
 #[test]
 fn test_debug_write() {
@@ -50,7 +66,14 @@ fn test_debug_write() {
             Ipv4TotalLengthTooSmall(0),
             Ipv6UnexpectedVersion(0),
             Ipv6TooManyHeaderExtensions,
-            TcpDataOffsetTooSmall(0)
+            TcpDataOffsetTooSmall(0),
+            IpAuthenticationHeaderTooSmall(0),
+            GtpuUnexpectedVersion(0),
+            GtpuExtensionHeaderLengthZero,
+            GtpuExtensionHeadersTooLarge(0),
+            L2tpUnexpectedVersion(0),
+            L2tpLengthTooSmall(0),
+            L2tpAvpLengthTooSmall(0)
         ].iter() {
             println!("{:?}", value);
         }
@@ -72,9 +95,15 @@ fn test_debug_write() {
         use crate::ValueError::*;
         for value in [
             Ipv4OptionsLengthBad(0),
+            Ipv4OptionDataLengthBad{ option_id: 0, data_len: 0 },
+            Ipv4OptionsWithoutIpv4Header,
             Ipv4PayloadLengthTooLarge(0),
             Ipv6PayloadLengthTooLarge(0),
             UdpPayloadLengthTooLarge(0),
+            IpAuthenticationHeaderBadIcvLength(0),
+            GeneveOptionsLengthBad(0),
+            GtpuExtensionHeaderLengthBad(0),
+            GtpuPayloadLengthTooLarge(0),
             U8TooLarge{value: 0, max: 0, field: ErrorField::Ipv4Ecn},
             U16TooLarge{value: 0, max: 0, field: ErrorField::Ipv4Ecn},
             U32TooLarge{value: 0, max: 0, field: ErrorField::Ipv4Ecn}
@@ -90,9 +119,17 @@ fn test_debug_write() {
             Ipv4Dscp,
             Ipv4Ecn,
             Ipv4FragmentsOffset,
+            Ipv4TimestampOverflow,
+            Ipv4TimestampFlags,
             Ipv6FlowLabel,
             VlanTagPriorityCodePoint,
-            VlanTagVlanId
+            VlanTagVlanId,
+            VxlanVni,
+            GeneveVersion,
+            GeneveVni,
+            GtpuVersion,
+            GtpuPduType,
+            GtpuQosFlowIdentifier
         ].iter() {
             println!("{:?}", value);
         }